@@ -22,6 +22,7 @@ use tower_sessions::Session;
 
 use crate::api::login::components::{AccountPreferencesError, LoginStatusErrors, PasswordRotationContent, PasswordRotationError, PasswordRotationFinalize, PasswordRotationSnapshot};
 use crate::api::modules::components::ModuleContent;
+use crate::api::modules::events::ModuleEvents;
 use crate::global_security::generate_salt_raw;
 
 const LOGIN_ATTEMPT_POLICY: AttemptPolicy = AttemptPolicy::new("login.attempts", 3, Duration::from_secs(15 * 60));
@@ -139,7 +140,7 @@ impl AuthenticatedUser
 		return Self::fromSessionWithConfig(&session).await;
 	}
 
-	async fn fromSessionWithConfig(session: &Session) -> Result<(Self,HConfig), UserBackHelperError>
+	pub(crate) async fn fromSessionWithConfig(session: &Session) -> Result<(Self,HConfig), UserBackHelperError>
 	{
 		let user = Self::fromSession(session).await?;
 		let config = user.userConfig_get()?;
@@ -151,6 +152,21 @@ impl AuthenticatedUser
 		return Ok((user,config));
 	}
 
+	/// reload the session of a long-lived request, false once it is logged out or its credential is rotated
+	pub(crate) async fn session_isStillCurrent(session: &Session, user: &Self) -> bool
+	{
+		if (session.load().await.is_err())
+		{
+			return false;
+		}
+		let Ok(current) = Self::fromSession(session).await else {return false};
+		if (current.identity != user.identity || current.credentialVersion != user.credentialVersion)
+		{
+			return false;
+		}
+		return current.userConfig_get().is_ok_and(|config| current.credentialVersion_isCurrent(&config));
+	}
+
 	pub(super) async fn session_isAuthenticated(session: &Session) -> Result<bool, UserBackHelperError>
 	{
 		let user = match Self::fromSession(session).await
@@ -214,6 +230,17 @@ impl AuthenticatedUser
 		return UserBackHelper::getUserConfigFromIdentity(&self.identity, false);
 	}
 
+	/// name of the live module events channel, shared by every session of the account
+	pub(crate) fn eventChannel_get(&self) -> &str
+	{
+		return self.identity.configName_get();
+	}
+
+	pub(crate) fn credentialVersion_get(&self) -> u64
+	{
+		return self.credentialVersion;
+	}
+
 	fn credentialVersion_isCurrent(&self, config: &HConfig) -> bool
 	{
 		return self.credentialVersion == UserBackHelper::credentialVersion_get(config);
@@ -256,6 +283,7 @@ impl AuthenticatedUser
 			return Err(UserBackHelperError::LoginError(LoginStatusErrors::USER_DISCONNECTED));
 		}
 		return Ok(AuthenticatedUserMutation {
+			_user: requestUser,
 			_config: config,
 			_guard: guard,
 		});
@@ -264,6 +292,7 @@ impl AuthenticatedUser
 
 pub(crate) struct AuthenticatedUserMutation
 {
+	_user: AuthenticatedUser,
 	_config: HConfig,
 	_guard: MutexGuardArc<()>,
 }
@@ -274,6 +303,11 @@ impl AuthenticatedUserMutation
 	{
 		return &mut self._config;
 	}
+
+	pub(crate) fn user_get(&self) -> &AuthenticatedUser
+	{
+		return &self._user;
+	}
 }
 
 #[derive(Default)]
@@ -900,6 +934,8 @@ impl UserBackHelper
 		let receipt = serde_json::to_string(&receipt).map_err(|_| PasswordRotationError::SERVER_ERROR)?;
 		config.value_set(Self::ROTATION_RECEIPT_FIELD,JsonValue::String(receipt));
 		config.file_save().map_err(|_| PasswordRotationError::SERVER_ERROR)?;
+		// the live updates of the sessions still bound to the previous credential stop here
+		ModuleEvents::singleton().credentialRotated_publish(user.eventChannel_get(),nextVersion);
 
 		user.credentialVersion = nextVersion;
 		user.passwordRotationId = None;
@@ -1066,49 +1102,34 @@ impl UserBackHelper
 	}
 }
 
+/// user configuration directory and authenticated sessions shared by the tests of the server functions
 #[cfg(test)]
-mod tests
+pub(crate) mod test_support
 {
 	use std::path::PathBuf;
 	use std::sync::{Arc, Mutex, MutexGuard, Once};
 
-	use axum::body::{to_bytes, Body};
-	use axum::extract::Path as AxumPath;
-	use axum::http::header::{ACCEPT, CONTENT_TYPE, COOKIE, SET_COOKIE};
-	use axum::http::{Request, Response, StatusCode};
-	use axum::routing::{get, post};
-	use axum::{middleware, Router};
-	use leptos::server_fn::codec::{Json, PostUrl};
-	use leptos::server_fn::{ContentType, ServerFn};
-	use serde::de::DeserializeOwned;
-	use tower::ServiceExt;
-	use tower_sessions::{MemoryStore, Session, SessionStore};
-
-	use crate::api::modules::components::{ModuleContent, ModuleID};
-	use crate::api::modules::{ApiModuleRetrieve, ApiModuleUpdate, ModuleApiError, ModuleReturnRetrieve};
-	use crate::api::login::{ApiUserPreferencesGet, ApiUserPreferencesSet};
-	use crate::api::login::session::SessionCookie;
-	use crate::api::Htrace::ApiHtraceLog;
-	use crate::api::proxys::imap::ApiProxysImapListbox;
-	use crate::api::proxys::imap_components::imap_connector;
-	use crate::api::proxys::imap_error::ImapError;
-	use crate::api::proxys::wget::{ApiProxysWget, proxys_return};
+	use base64ct::{Base64, Encoding};
+	use Hconfig::HConfigManager::HConfigManager;
+	use Hconfig::tinyjson::JsonValue;
+	use tower_sessions::{MemoryStore, Session};
 
-	use super::*;
+	use super::{AuthenticatedUser, UserBackHelper, UserConfigIdentity};
 
 	static CONFIG_PATH_LOCK: Mutex<()> = Mutex::new(());
 	static TRACE_INITIALIZATION: Once = Once::new();
 
-	struct ConfigPathGuard
+	/// a temporary configuration directory, held by one test at a time
+	pub(crate) struct ConfigPathGuard
 	{
 		_lock: MutexGuard<'static, ()>,
 		previousPath: String,
-		testPath: PathBuf,
+		pub(crate) testPath: PathBuf,
 	}
 
 	impl ConfigPathGuard
 	{
-		fn new() -> Self
+		pub(crate) fn new() -> Self
 		{
 			trace_initialize();
 			let lock = CONFIG_PATH_LOCK.lock().unwrap_or_else(|err| err.into_inner());
@@ -1120,13 +1141,6 @@ mod tests
 		}
 	}
 
-	fn trace_initialize()
-	{
-		TRACE_INITIALIZATION.call_once(|| {
-			Htrace::htracer::HTracer::globalContext_set(Htrace::components::context::Context::default());
-		});
-	}
-
 	impl Drop for ConfigPathGuard
 	{
 		fn drop(&mut self)
@@ -1136,6 +1150,56 @@ mod tests
 		}
 	}
 
+	pub(crate) fn trace_initialize()
+	{
+		TRACE_INITIALIZATION.call_once(|| {
+			Htrace::htracer::HTracer::globalContext_set(Htrace::components::context::Context::default());
+		});
+	}
+
+	/// a session signed in to a new account of the configuration directory held by the caller
+	pub(crate) async fn authenticatedSession_get(seed: u8) -> Session
+	{
+		let identity = UserConfigIdentity::fromGeneratedId(&Base64::encode_string(&[seed; 32])).unwrap();
+		let mut config = UserBackHelper::getUserConfigFromIdentity(&identity, true).unwrap();
+		config.value_set("dateSignUp", JsonValue::String("server function test".to_string()));
+		config.file_save().unwrap();
+		let session = Session::new(None, Arc::new(MemoryStore::default()), None);
+		AuthenticatedUser::establish(&session,identity,"test-credential-salt".to_string(),0).await.unwrap();
+		return session;
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::sync::Arc;
+
+	use axum::body::{to_bytes, Body};
+	use axum::extract::Path as AxumPath;
+	use axum::http::header::{ACCEPT, CONTENT_TYPE, COOKIE, SET_COOKIE};
+	use axum::http::{Request, Response, StatusCode};
+	use axum::routing::{get, post};
+	use axum::{middleware, Router};
+	use leptos::server_fn::codec::{Json, PostUrl};
+	use leptos::server_fn::{ContentType, ServerFn};
+	use serde::de::DeserializeOwned;
+	use tower::ServiceExt;
+	use tower_sessions::{MemoryStore, Session, SessionStore};
+
+	use crate::api::modules::components::{ModuleContent, ModuleID};
+	use crate::api::modules::{ApiModuleRetrieve, ApiModuleUpdate, ModuleApiError, ModuleReturnRetrieve};
+	use crate::api::login::{ApiUserPreferencesGet, ApiUserPreferencesSet};
+	use crate::api::login::session::SessionCookie;
+	use crate::api::Htrace::ApiHtraceLog;
+	use crate::api::proxys::imap::ApiProxysImapListbox;
+	use crate::api::proxys::imap_components::{imap_connector, ImapConnectionSecurity};
+	use crate::api::proxys::imap_error::ImapError;
+	use crate::api::proxys::wget::{ApiProxysWget, proxys_return};
+
+	use super::*;
+	use super::test_support::{trace_initialize, ConfigPathGuard};
+
 	fn validGeneratedId_get() -> String
 	{
 		return Base64::encode_string(&[7u8; 32]);
@@ -1506,6 +1570,7 @@ mod tests
 				pos: [1, 2],
 				size: [3, 4],
 				depth: 5,
+				parent: None,
 			};
 
 			let anonymousUpdate = ModuleAuthorizationTest::module_update(&router, None, contentA.clone()).await;
@@ -1569,6 +1634,8 @@ mod tests
 				username: "not-used".to_string(),
 				password: "not-used".to_string(),
 				extra: None,
+				oauth: None,
+				security: ImapConnectionSecurity::TLS,
 			};
 
 			let anonymousRss = ModuleAuthorizationTest::proxy_wget(&router, None, "http://127.0.0.1/feed").await;
@@ -1663,6 +1730,7 @@ mod tests
 					pos: [1,2],
 					size: [3,4],
 					depth: 5,
+					parent: None,
 				},
 				ModuleContent {
 					id: ModuleID {id: "unknown-module".to_string()},
@@ -1672,6 +1740,7 @@ mod tests
 					pos: [6,7],
 					size: [8,9],
 					depth: 10,
+					parent: None,
 				},
 			];
 			let (identity,sessionA,sessionB) = rotationAccount_create(31,oldCredential.clone(),modules.clone()).await;
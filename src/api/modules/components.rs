@@ -64,6 +64,9 @@ pub struct ModuleContent
 	pub size: [u32; 2],
 	#[serde(default)]
 	pub depth: u32,
+	// container holding this module, positions are then relative to it
+	#[serde(default)]
+	pub parent: Option<ModuleID>,
 }
 
 impl Default for ModuleContent
@@ -77,6 +80,7 @@ impl Default for ModuleContent
 			pos: [0,0],
 			size: [0,0],
 			depth: 0,
+			parent: None,
 		}
 	}
 }
@@ -130,6 +134,10 @@ impl ModuleContent
 		content.insert("sizeX".to_string(), JsonValue::Number(self.size[0] as f64));
 		content.insert("sizeY".to_string(), JsonValue::Number(self.size[1] as f64));
 		content.insert("depth".to_string(), JsonValue::Number(self.depth as f64));
+		if let Some(parent) = &self.parent
+		{
+			content.insert("parent".to_string(), JsonValue::String(parent.id.clone()));
+		}
 
 		config.value_set(&self.getModulePath(),JsonValue::Object(content));
 		config.file_save().map_err(|err| ModuleErrors::ConfigError(err))?;
@@ -168,21 +176,27 @@ impl ModuleContent
 		if let Some(JsonValue::Number(content) ) = content.get("depth"){
 			self.depth = *content as u32;
 		}
+		self.parent = None;
+		if let Some(JsonValue::String(content) ) = content.get("parent"){
+			self.parent = Some(ModuleID{id: content.clone()});
+		}
 
 		return Ok(());
 	}
 
 	#[cfg(feature = "ssr")]
-	pub fn remove(config: &mut Hconfig::HConfig::HConfig, name: ModuleID) -> bool
+	pub fn remove(config: &mut Hconfig::HConfig::HConfig, name: &ModuleID) -> Result<(), ModuleErrors>
 	{
 		use Hconfig::tinyjson::JsonValue;
 		let modulePath = Self::getModulePathNamed(&name.id);
-		let Some(JsonValue::Object(_)) = config.value_get(&modulePath) else {return false};
+		let Some(JsonValue::Object(_)) = config.value_get(&modulePath) else {return Err(ModuleErrors::Empty)};
 
-		if config.value_remove(&modulePath) {
-			return config.file_save().is_ok();
+		if (!config.value_remove(&modulePath))
+		{
+			return Err(ModuleErrors::Empty);
 		}
-		return false;
+		config.file_save().map_err(|err| ModuleErrors::ConfigError(err))?;
+		return Ok(());
 	}
 
 	#[cfg(feature = "ssr")]
@@ -262,9 +276,11 @@ impl ModuleContent
 #[cfg(all(test, feature = "ssr"))]
 mod tests
 {
+	use Hconfig::HConfig::HConfig;
+	use Hconfig::IO::json::WrapperJson;
 	use Hconfig::tinyjson::JsonValue;
 
-	use super::ModuleContent;
+	use super::{ModuleContent, ModuleID};
 
 	#[test]
 	fn timestampRead_acceptsStringAndNumber()
@@ -288,4 +304,29 @@ mod tests
 		);
 		assert_eq!(ModuleContent::timestamp_getFromValue(None), 0);
 	}
+
+	#[test]
+	fn parentContainer_isPersistedAndCleared()
+	{
+		let testPath = std::env::temp_dir().join(format!("webhome-module-test-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(&testPath).unwrap();
+		let mut config = HConfig::new::<WrapperJson>("modules".to_string(), testPath.to_string_lossy().to_string()).unwrap();
+
+		let mut child = ModuleContent::new(ModuleID {id: "child".to_string()}, "TODO".to_string());
+		child.timestamp = 1;
+		child.parent = Some(ModuleID {id: "container".to_string()});
+		child.update(&mut config, false).unwrap();
+
+		let mut retrieved = ModuleContent::newFromName(&child.id);
+		retrieved.retrieve(&config).unwrap();
+		assert_eq!(retrieved.parent, Some(ModuleID {id: "container".to_string()}));
+
+		child.timestamp = 2;
+		child.parent = None;
+		child.update(&mut config, false).unwrap();
+		retrieved.retrieve(&config).unwrap();
+		assert_eq!(retrieved.parent, None);
+
+		let _ = std::fs::remove_dir_all(testPath);
+	}
 }
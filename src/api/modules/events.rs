@@ -0,0 +1,410 @@
+use serde::{Deserialize, Serialize};
+use crate::api::modules::components::ModuleID;
+
+/// server-sent events stream of the module changes made by the other sessions of the account
+pub const MODULE_EVENTS_PATH: &str = "/api/module_events";
+/// one module was saved, its content must be retrieved if it is newer than the local one
+pub const MODULE_EVENT_UPDATED: &str = "module";
+/// one module was removed, it must be dropped from the dashboard
+pub const MODULE_EVENT_REMOVED: &str = "removed";
+/// some events were dropped, every module must be checked again
+pub const MODULE_EVENT_LAGGED: &str = "lagged";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ModuleEventUpdated
+{
+	pub id: ModuleID,
+	pub timestamp: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ModuleEventRemoved
+{
+	pub id: ModuleID,
+}
+
+#[cfg(feature = "ssr")]
+pub(crate) use self::server::ModuleEvents;
+
+#[cfg(feature = "ssr")]
+mod server
+{
+	use std::collections::HashMap;
+	use std::future::Future;
+	use std::sync::{Mutex, OnceLock};
+	use std::time::Duration;
+	use axum::response::sse::{Event, KeepAlive, Sse};
+	use axum::response::{IntoResponse, Response};
+	use axum::http::StatusCode;
+	use futures::future::{self, Either};
+	use futures::{Stream, StreamExt};
+	use tokio::sync::broadcast;
+	use tokio::time::MissedTickBehavior;
+	use tokio::sync::broadcast::error::RecvError;
+	use tower_sessions::Session;
+	use crate::api::login::user_back::{AuthenticatedUser, UserBackHelperError};
+	use crate::api::modules::components::{ModuleContent, ModuleID};
+	use super::{ModuleEventRemoved, ModuleEventUpdated, MODULE_EVENT_LAGGED, MODULE_EVENT_REMOVED, MODULE_EVENT_UPDATED};
+
+	#[derive(Clone, Debug, PartialEq, Eq)]
+	enum ModuleEventMessage
+	{
+		UPDATED(ModuleEventUpdated),
+		REMOVED(ModuleEventRemoved),
+		// new credential version of the account
+		CREDENTIAL_ROTATED(u64),
+	}
+
+	/// broadcast channels of the connected accounts, one per account, shared by all its sessions
+	#[derive(Default)]
+	pub(crate) struct ModuleEvents
+	{
+		channels: Mutex<HashMap<String, broadcast::Sender<ModuleEventMessage>>>,
+	}
+
+	impl ModuleEvents
+	{
+		// events kept for a slow client before it is told to check everything again
+		const CHANNEL_CAPACITY: usize = 64;
+		// a logged out session stops receiving events within this delay
+		const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+		pub(crate) fn singleton() -> &'static Self
+		{
+			static SINGLETON: OnceLock<ModuleEvents> = OnceLock::new();
+			return SINGLETON.get_or_init(Self::default);
+		}
+
+		pub(crate) fn updated_publish(&self, channel: &str, content: &ModuleContent)
+		{
+			self.publish(channel, ModuleEventMessage::UPDATED(ModuleEventUpdated {
+				id: content.id.clone(),
+				timestamp: content.timestamp,
+			}));
+		}
+
+		pub(crate) fn removed_publish(&self, channel: &str, moduleId: &ModuleID)
+		{
+			self.publish(channel, ModuleEventMessage::REMOVED(ModuleEventRemoved {
+				id: moduleId.clone(),
+			}));
+		}
+
+		pub(crate) fn credentialRotated_publish(&self, channel: &str, credentialVersion: u64)
+		{
+			self.publish(channel, ModuleEventMessage::CREDENTIAL_ROTATED(credentialVersion));
+		}
+
+		fn publish(&self, channel: &str, message: ModuleEventMessage)
+		{
+			let Ok(channels) = self.channels.lock() else {return};
+			if let Some(sender) = channels.get(channel)
+			{
+				// no receiver left is not an error, the channel is dropped on the next subscription
+				let _ = sender.send(message);
+			}
+		}
+
+		/// resolves once the credential of the account moves away from credentialVersion, ends the long-lived requests of the session.
+		/// Lagged events may hide a rotation, they resolve it too
+		pub(crate) async fn credentialRotation_wait(&self, channel: &str, credentialVersion: u64)
+		{
+			let Some(mut receiver) = self.subscribe(channel) else {return};
+			loop
+			{
+				match receiver.recv().await
+				{
+					Ok(ModuleEventMessage::CREDENTIAL_ROTATED(newVersion)) if newVersion != credentialVersion => return,
+					Ok(_) => continue,
+					Err(_) => return,
+				}
+			}
+		}
+
+		/// resolves once the session is logged out or its credential is rotated, the session is reloaded every SESSION_CHECK_INTERVAL
+		pub(crate) async fn sessionEnd_wait(&self, session: Session, user: AuthenticatedUser)
+		{
+			let rotation = Box::pin(self.credentialRotation_wait(user.eventChannel_get(),user.credentialVersion_get()));
+			let logout = Box::pin(async {
+				loop
+				{
+					tokio::time::sleep(Self::SESSION_CHECK_INTERVAL).await;
+					if (!AuthenticatedUser::session_isStillCurrent(&session, &user).await)
+					{
+						return;
+					}
+				}
+			});
+			future::select(rotation, logout).await;
+		}
+
+		fn subscribe(&self, channel: &str) -> Option<broadcast::Receiver<ModuleEventMessage>>
+		{
+			let mut channels = self.channels.lock().ok()?;
+			channels.retain(|_, sender| sender.receiver_count() > 0);
+			let sender = channels.entry(channel.to_string())
+				.or_insert_with(|| broadcast::channel(Self::CHANNEL_CAPACITY).0);
+			return Some(sender.subscribe());
+		}
+
+		/// axum handler of MODULE_EVENTS_PATH, only for an authenticated session
+		pub(crate) async fn stream_get(session: Session) -> Response
+		{
+			let user = match AuthenticatedUser::fromSessionWithConfig(&session).await
+			{
+				Ok((user, _)) => user,
+				Err(UserBackHelperError::LoginError(_)) => return StatusCode::UNAUTHORIZED.into_response(),
+				Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+			};
+			let Some(receiver) = Self::singleton().subscribe(user.eventChannel_get()) else {return StatusCode::INTERNAL_SERVER_ERROR.into_response()};
+
+			let messages = Box::pin(Self::messages_get(receiver, user.credentialVersion_get()));
+			let sessionCheck = move || {
+				let session = session.clone();
+				let user = user.clone();
+				async move {AuthenticatedUser::session_isStillCurrent(&session, &user).await}
+			};
+			return Sse::new(Self::messages_checked(messages, Self::SESSION_CHECK_INTERVAL, sessionCheck).map(ModuleEventSent::event_get))
+				.keep_alive(KeepAlive::default())
+				.into_response();
+		}
+
+		/// the stream ends when the credential of the session is rotated or the session is logged out, the browser reconnection is then refused.
+		/// The session is checked before each event and at every interval while nothing is sent
+		fn messages_checked<Check, CheckFuture>(messages: impl Stream<Item = ModuleEventSent> + Unpin, interval: Duration, sessionCheck: Check) -> impl Stream<Item = ModuleEventSent>
+		where
+			Check: Fn() -> CheckFuture,
+			CheckFuture: Future<Output = bool>,
+		{
+			let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+			ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+			return futures::stream::unfold((messages, ticker, sessionCheck), |(mut messages, mut ticker, sessionCheck)| async move {
+				loop
+				{
+					let sent = match future::select(messages.next(), Box::pin(ticker.tick())).await
+					{
+						Either::Left((Some(sent), _)) => Some(sent),
+						Either::Left((None, _)) => return None,
+						Either::Right(_) => None,
+					};
+					if (!sessionCheck().await)
+					{
+						return None;
+					}
+					if let Some(sent) = sent
+					{
+						return Some((sent, (messages, ticker, sessionCheck)));
+					}
+				}
+			});
+		}
+
+		fn messages_get(receiver: broadcast::Receiver<ModuleEventMessage>, credentialVersion: u64) -> impl Stream<Item = ModuleEventSent>
+		{
+			return futures::stream::unfold(receiver, move |mut receiver| async move {
+				loop
+				{
+					let sent = match receiver.recv().await
+					{
+						Ok(ModuleEventMessage::UPDATED(updated)) => ModuleEventSent::UPDATED(updated),
+						Ok(ModuleEventMessage::REMOVED(removed)) => ModuleEventSent::REMOVED(removed),
+						Ok(ModuleEventMessage::CREDENTIAL_ROTATED(newVersion)) if newVersion != credentialVersion => return None,
+						Ok(ModuleEventMessage::CREDENTIAL_ROTATED(_)) => continue,
+						Err(RecvError::Lagged(_)) => ModuleEventSent::LAGGED,
+						Err(RecvError::Closed) => return None,
+					};
+					return Some((sent, receiver));
+				}
+			});
+		}
+	}
+
+	#[derive(Debug, PartialEq, Eq)]
+	enum ModuleEventSent
+	{
+		UPDATED(ModuleEventUpdated),
+		REMOVED(ModuleEventRemoved),
+		LAGGED,
+	}
+
+	impl ModuleEventSent
+	{
+		fn event_get(self) -> Result<Event, axum::Error>
+		{
+			return match self
+			{
+				Self::UPDATED(updated) => Event::default().event(MODULE_EVENT_UPDATED).json_data(updated),
+				Self::REMOVED(removed) => Event::default().event(MODULE_EVENT_REMOVED).json_data(removed),
+				Self::LAGGED => Ok(Event::default().event(MODULE_EVENT_LAGGED).data("")),
+			};
+		}
+	}
+
+	#[cfg(test)]
+	mod tests
+	{
+		use std::sync::Arc;
+		use std::sync::atomic::{AtomicBool, Ordering};
+		use std::time::Duration;
+		use futures::{FutureExt, StreamExt};
+		use crate::api::modules::components::{ModuleContent, ModuleID};
+		use crate::api::modules::events::{ModuleEventRemoved, ModuleEventUpdated};
+		use super::{ModuleEventSent, ModuleEvents};
+
+		fn content_get(id: &str, timestamp: i64) -> ModuleContent
+		{
+			return ModuleContent {
+				id: ModuleID {id: id.to_string()},
+				timestamp,
+				..Default::default()
+			};
+		}
+
+		#[test]
+		fn moduleEvents_reachOnlyTheSessionsOfTheSameAccount()
+		{
+			let runtime = tokio::runtime::Runtime::new().unwrap();
+			runtime.block_on(async {
+				let events = ModuleEvents::default();
+				let receiverA = events.subscribe("account-a").unwrap();
+				let receiverB = events.subscribe("account-b").unwrap();
+
+				events.updated_publish("account-a", &content_get("todo", 42));
+				events.credentialRotated_publish("account-b", 1);
+
+				let mut streamA = Box::pin(ModuleEvents::messages_get(receiverA, 0));
+				assert_eq!(streamA.next().await, Some(ModuleEventSent::UPDATED(ModuleEventUpdated {
+					id: ModuleID {id: "todo".to_string()},
+					timestamp: 42,
+				})));
+
+				let mut streamB = Box::pin(ModuleEvents::messages_get(receiverB, 0));
+				assert!(streamB.next().await.is_none());
+			});
+		}
+
+		#[test]
+		fn moduleEvents_keepTheSessionOfTheNewCredential()
+		{
+			let runtime = tokio::runtime::Runtime::new().unwrap();
+			runtime.block_on(async {
+				let events = ModuleEvents::default();
+				let receiverOld = events.subscribe("account").unwrap();
+				let receiverNew = events.subscribe("account").unwrap();
+
+				events.credentialRotated_publish("account", 3);
+				events.updated_publish("account", &content_get("rss", 7));
+
+				let mut streamOld = Box::pin(ModuleEvents::messages_get(receiverOld, 2));
+				assert!(streamOld.next().await.is_none());
+				let mut streamNew = Box::pin(ModuleEvents::messages_get(receiverNew, 3));
+				assert_eq!(streamNew.next().await, Some(ModuleEventSent::UPDATED(ModuleEventUpdated {
+					id: ModuleID {id: "rss".to_string()},
+					timestamp: 7,
+				})));
+			});
+		}
+
+		#[test]
+		fn moduleEvents_forwardRemovals()
+		{
+			let runtime = tokio::runtime::Runtime::new().unwrap();
+			runtime.block_on(async {
+				let events = ModuleEvents::default();
+				let receiver = events.subscribe("account").unwrap();
+				events.removed_publish("account", &ModuleID {id: "todo".to_string()});
+
+				let mut stream = Box::pin(ModuleEvents::messages_get(receiver, 0));
+				assert_eq!(stream.next().await, Some(ModuleEventSent::REMOVED(ModuleEventRemoved {
+					id: ModuleID {id: "todo".to_string()},
+				})));
+			});
+		}
+
+		#[test]
+		fn moduleEvents_endWhenTheSessionIsNoLongerCurrent()
+		{
+			let runtime = tokio::runtime::Runtime::new().unwrap();
+			runtime.block_on(async {
+				let events = ModuleEvents::default();
+				let receiver = events.subscribe("account").unwrap();
+				let isCurrent = Arc::new(AtomicBool::new(true));
+				let check = {
+					let isCurrent = isCurrent.clone();
+					move || {
+						let isCurrent = isCurrent.load(Ordering::SeqCst);
+						async move {isCurrent}
+					}
+				};
+				let messages = Box::pin(ModuleEvents::messages_get(receiver, 0));
+				let mut stream = Box::pin(ModuleEvents::messages_checked(messages, Duration::from_millis(20), check));
+
+				events.updated_publish("account", &content_get("todo", 1));
+				assert!(matches!(stream.next().await, Some(ModuleEventSent::UPDATED(_))));
+
+				isCurrent.store(false, Ordering::SeqCst);
+				events.updated_publish("account", &content_get("todo", 2));
+				assert!(stream.next().await.is_none());
+			});
+		}
+
+		#[test]
+		fn moduleEvents_closeAnIdleStreamOnceLoggedOut()
+		{
+			let runtime = tokio::runtime::Runtime::new().unwrap();
+			runtime.block_on(async {
+				let events = ModuleEvents::default();
+				let receiver = events.subscribe("account").unwrap();
+				let messages = Box::pin(ModuleEvents::messages_get(receiver, 0));
+				let mut stream = Box::pin(ModuleEvents::messages_checked(messages, Duration::from_millis(20), || async {false}));
+
+				let ended = tokio::time::timeout(Duration::from_secs(5), stream.next()).await;
+				assert!(matches!(ended, Ok(None)));
+			});
+		}
+
+		#[test]
+		fn moduleEvents_signalLaggedReceivers()
+		{
+			let runtime = tokio::runtime::Runtime::new().unwrap();
+			runtime.block_on(async {
+				let events = ModuleEvents::default();
+				let receiver = events.subscribe("account").unwrap();
+				for timestamp in 0..(ModuleEvents::CHANNEL_CAPACITY as i64 + 1)
+				{
+					events.updated_publish("account", &content_get("todo", timestamp));
+				}
+
+				let mut stream = Box::pin(ModuleEvents::messages_get(receiver, 0));
+				assert_eq!(stream.next().await, Some(ModuleEventSent::LAGGED));
+			});
+		}
+
+		#[test]
+		fn moduleEvents_credentialRotationWaitIgnoresTheCurrentCredential()
+		{
+			let events = ModuleEvents::default();
+			let mut rotation = Box::pin(events.credentialRotation_wait("account", 2));
+			assert!((&mut rotation).now_or_never().is_none());
+
+			events.credentialRotated_publish("account", 2);
+			events.updated_publish("account", &content_get("mail", 1));
+			assert!((&mut rotation).now_or_never().is_none());
+
+			events.credentialRotated_publish("account", 3);
+			assert!(rotation.now_or_never().is_some());
+		}
+
+		#[test]
+		fn moduleEvents_dropChannelsWithoutReceiver()
+		{
+			let events = ModuleEvents::default();
+			drop(events.subscribe("account-a").unwrap());
+			let _receiverB = events.subscribe("account-b").unwrap();
+
+			let channels = events.channels.lock().unwrap();
+			assert!(!channels.contains_key("account-a"));
+			assert!(channels.contains_key("account-b"));
+		}
+	}
+}
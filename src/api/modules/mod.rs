@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::api::modules::components::{ApiModulesID, ModuleContent, ModuleID};
 
 pub mod components;
+pub mod events;
 #[cfg(feature = "ssr")]
 pub mod helper;
 
@@ -66,7 +67,9 @@ impl FromServerFnError for ModuleApiError
 pub enum ModuleReturnUpdate
 {
 	OK,
-	OUTDATED(ModuleContent)
+	OUTDATED(ModuleContent),
+	// the module removed during the edit session is still saved
+	REMOVE_FAILED,
 }
 
 
@@ -76,12 +79,14 @@ pub async fn API_module_update(content: ModuleContent, overwrite:bool) -> Result
 {
 	use crate::api::login::user_back::AuthenticatedUser;
 	use crate::api::modules::components::ModuleErrors;
+	use crate::api::modules::events::ModuleEvents;
 	let mut mutation = AuthenticatedUser::mutation_begin().await.map_err(ModuleApiError::fromUserBackError)?;
+	let channel = mutation.user_get().eventChannel_get().to_string();
 	let config = mutation.config_getMut();
 	let mut content = content;
 
 	match content.update(config,overwrite) {
-		Ok(_) => {}
+		Ok(_) => ModuleEvents::singleton().updated_publish(&channel, &content),
 		Err(ModuleErrors::SavedIsNewer) => { // never send if overwrite is true
 			if content.retrieve(config).is_ok() {
 				return Ok(ModuleReturnUpdate::OUTDATED(content));
@@ -95,17 +100,21 @@ pub async fn API_module_update(content: ModuleContent, overwrite:bool) -> Result
 
 /// api function that updates module content based on ModuleID and their last fetch
 #[server]
-pub async fn API_modules_update(contents: Vec<ModuleContent>, overwrite:bool) -> Result<HashMap<ModuleID,ModuleReturnUpdate>, ModuleApiError>
+pub async fn API_modules_update(contents: Vec<ModuleContent>, #[server(default)] removed: Vec<ModuleID>, overwrite:bool) -> Result<HashMap<ModuleID,ModuleReturnUpdate>, ModuleApiError>
 {
 	use crate::api::login::user_back::AuthenticatedUser;
 	use crate::api::modules::components::ModuleErrors;
+	use crate::api::modules::events::ModuleEvents;
+	use Htrace::components::level::Level;
+	use Htrace::HTrace;
 	let mut mutation = AuthenticatedUser::mutation_begin().await.map_err(ModuleApiError::fromUserBackError)?;
+	let channel = mutation.user_get().eventChannel_get().to_string();
 	let config = mutation.config_getMut();
 	let mut returning = HashMap::new();
 
 	for mut content in contents {
 		match content.update(config,overwrite) {
-			Ok(_) => {}
+			Ok(_) => ModuleEvents::singleton().updated_publish(&channel, &content),
 			Err(ModuleErrors::SavedIsNewer) => { // never send if overwrite is true
 				if content.retrieve(config).is_ok() {
 					returning.insert(content.id.clone(), ModuleReturnUpdate::OUTDATED(content));
@@ -115,6 +124,18 @@ pub async fn API_modules_update(contents: Vec<ModuleContent>, overwrite:bool) ->
 		}
 	}
 
+	// modules removed during an edit session, one already missing is not an error
+	for moduleId in removed {
+		match ModuleContent::remove(config, &moduleId) {
+			Ok(_) => ModuleEvents::singleton().removed_publish(&channel, &moduleId),
+			Err(ModuleErrors::Empty) => {},
+			Err(err) => {
+				HTrace!((Level::ERROR) "module removal failed: {:?}", err);
+				returning.insert(moduleId, ModuleReturnUpdate::REMOVE_FAILED);
+			},
+		}
+	}
+
 	return Ok(returning);
 }
 
@@ -190,16 +211,3 @@ pub async fn API_module_retrieveMissingModule(#[server(default)] modules: Vec<Mo
 	return Ok(missing_module);
 }
 
-
-/// remove a specific module
-#[server]
-pub async fn API_module_remove(moduleName: ModuleID) -> Result<(), ModuleApiError>
-{
-	use crate::api::login::user_back::AuthenticatedUser;
-	let mut mutation = AuthenticatedUser::mutation_begin().await.map_err(ModuleApiError::fromUserBackError)?;
-
-	return match ModuleContent::remove(mutation.config_getMut(), moduleName) {
-		true => Ok(()),
-		false => Err(ModuleApiError::NOT_FOUND)
-	};
-}
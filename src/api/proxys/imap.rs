@@ -1,11 +1,11 @@
 use leptos::server;
-use leptos::server_fn::codec::Json;
+use leptos::server_fn::codec::{ByteStream, Json, Streaming, StreamingText, TextStream};
 
-use crate::api::proxys::imap_components::{BoxName, ImapMailboxSync, ImapMail, ImapMailKey, ImapSyncRequest, imap_connector};
+use crate::api::proxys::imap_components::{BoxName, ImapProxyResponse, ImapMailboxSync, ImapMail, ImapMailAction, ImapMailKey, ImapMailSource, ImapOAuthAuthorization, ImapSearchQuery, ImapSearchResult, ImapSyncRequest, imap_connector, imap_connector_oauth};
 use crate::api::proxys::imap_error::ImapError;
 
 #[server]
-pub async fn API_proxys_imap_listbox(config: imap_connector) -> Result<Vec<BoxName>, ImapError>
+pub async fn API_proxys_imap_listbox(config: imap_connector) -> Result<ImapProxyResponse<Vec<BoxName>>, ImapError>
 {
 	use crate::api::proxys::imap_inner::ImapProxy;
 
@@ -14,7 +14,7 @@ pub async fn API_proxys_imap_listbox(config: imap_connector) -> Result<Vec<BoxNa
 }
 
 #[server(input = Json)]
-pub async fn API_proxys_imap_sync(config: imap_connector, request: ImapSyncRequest) -> Result<Vec<ImapMailboxSync>, ImapError>
+pub async fn API_proxys_imap_sync(config: imap_connector, request: ImapSyncRequest) -> Result<ImapProxyResponse<Vec<ImapMailboxSync>>, ImapError>
 {
 	#[cfg(feature = "ssr")]
 	{
@@ -35,7 +35,7 @@ pub async fn API_proxys_imap_sync(config: imap_connector, request: ImapSyncReque
 	let result = inner::ImapRequest::new(config,"sync").await?
 		.run(move |proxy| proxy.sync_get(request)).await;
 	#[cfg(feature = "ssr")]
-	if let Ok(mailboxes) = &result
+	if let Ok(ImapProxyResponse { result: Ok(mailboxes), .. }) = &result
 	{
 		use Htrace::components::level::Level;
 		use Htrace::HTrace;
@@ -52,19 +52,101 @@ pub async fn API_proxys_imap_sync(config: imap_connector, request: ImapSyncReque
 }
 
 #[server]
-pub async fn API_proxys_imap_getMailContent(config: imap_connector, mail: ImapMailKey) -> Result<ImapMail, ImapError>
+pub async fn API_proxys_imap_getMailContent(config: imap_connector, mail: ImapMailKey) -> Result<ImapProxyResponse<ImapMail>, ImapError>
 {
 	return inner::ImapRequest::new(config,"content").await?
 		.run(move |proxy| proxy.mailContent_get(mail)).await;
 }
 
+/// one attachment listed by API_proxys_imap_getMailContent, by its MIME section path, decoded from its transfer encoding.
+/// The part is fetched from the IMAP server in ranges, each one sent once decoded.
+/// The stream starts with the ImapProxyResponse::attachmentHead_get line of the request, the data follows it
+#[server(input = Json, output = Streaming)]
+pub async fn API_proxys_imap_getAttachment(config: imap_connector, mail: ImapMailKey, section: String) -> Result<ByteStream<ImapError>, ImapError>
+{
+	return inner::ImapRequest::new(config,"attachment").await?
+		.attachment_stream(mail,section).await;
+}
+
+/// raw RFC 822 source of a mail, for its headers and an .eml download
+#[server]
+pub async fn API_proxys_imap_getMailSource(config: imap_connector, mail: ImapMailKey) -> Result<ImapProxyResponse<ImapMailSource>, ImapError>
+{
+	return inner::ImapRequest::new(config,"source").await?
+		.run(move |proxy| proxy.mailSource_get(mail)).await;
+}
+
 #[server]
-pub async fn API_proxys_imap_setMailSee(config: imap_connector, mail: ImapMailKey) -> Result<(), ImapError>
+pub async fn API_proxys_imap_setMailSee(config: imap_connector, mail: ImapMailKey) -> Result<ImapProxyResponse<()>, ImapError>
 {
 	return inner::ImapRequest::new(config,"seen").await?
 		.run(move |proxy| proxy.mailSeen_set(mail)).await;
 }
 
+/// moves or flags a selection of mails, possibly spread over several mailboxes
+#[server(input = Json)]
+pub async fn API_proxys_imap_mailsAction(config: imap_connector, mails: Vec<ImapMailKey>, action: ImapMailAction) -> Result<ImapProxyResponse<()>, ImapError>
+{
+	return inner::ImapRequest::new(config,"action").await?
+		.run(move |proxy| proxy.mailsAction_apply(mails,action)).await;
+}
+
+/// one page of the mails of the selected mailboxes matching a structured query
+#[server(input = Json)]
+pub async fn API_proxys_imap_search(config: imap_connector, query: ImapSearchQuery) -> Result<ImapProxyResponse<ImapSearchResult>, ImapError>
+{
+	return inner::ImapRequest::new(config,"search").await?
+		.run(move |proxy| proxy.search_get(query)).await;
+}
+
+/// names of the OAuth providers of site.json, a connector authorized by one of them needs no password
+#[server]
+pub async fn API_proxys_imap_oauthProviders() -> Result<Vec<String>, ImapError>
+{
+	use crate::api::proxys::imap_oauth::ImapOAuthProvider;
+	use crate::api::proxys::outbound_policy::OutboundPolicy;
+
+	OutboundPolicy::authentication_require().await
+		.map_err(|error| ImapError::from(error).trace("oauth_providers","authentication",None))?;
+	return Ok(ImapOAuthProvider::names_get());
+}
+
+/// starts an authorization code flow, the returned page is opened by the browser
+#[server]
+pub async fn API_proxys_imap_oauthBegin(provider: String) -> Result<ImapOAuthAuthorization, ImapError>
+{
+	use crate::api::proxys::imap_oauth::{ImapOAuthAuthorizations, ImapOAuthProvider};
+	use crate::api::proxys::outbound_policy::OutboundPolicy;
+
+	let user = OutboundPolicy::authentication_require().await
+		.map_err(|error| ImapError::from(error).trace("oauth_begin","authentication",None))?;
+	let provider = ImapOAuthProvider::current_get(&provider)
+		.map_err(|error| error.trace("oauth_begin","provider",None))?;
+	return ImapOAuthAuthorizations::singleton().begin(&provider,user.eventChannel_get())
+		.map_err(|error| error.trace("oauth_begin","pending",None));
+}
+
+/// OAUTH_PENDING until the provider redirected the user back, then the authorization to keep in the module
+#[server]
+pub async fn API_proxys_imap_oauthFinish(state: String) -> Result<imap_connector_oauth, ImapError>
+{
+	use crate::api::proxys::imap_oauth::ImapOAuthAuthorizations;
+	use crate::api::proxys::outbound_policy::OutboundPolicy;
+
+	let user = OutboundPolicy::authentication_require().await
+		.map_err(|error| ImapError::from(error).trace("oauth_finish","authentication",None))?;
+	return ImapOAuthAuthorizations::singleton().finish(&state,user.eventChannel_get()).await
+		.map_err(|error| if (error == ImapError::OAUTH_PENDING) {error} else {error.trace("oauth_finish","exchange",None)});
+}
+
+/// holds an IMAP IDLE connection on one selected mailbox and streams its ImapIdleEvent lines,
+/// until the listener leaves, a few minutes pass, the session is logged out or the account credential is rotated
+#[server(input = Json, output = StreamingText)]
+pub async fn API_proxys_imap_idle(config: imap_connector, boxName: String) -> Result<TextStream<ImapError>, ImapError>
+{
+	return inner::ImapRequest::idle_stream(config,boxName).await;
+}
+
 #[cfg(test)]
 mod contract_tests
 {
@@ -96,47 +178,171 @@ mod contract_tests
 #[cfg(feature = "ssr")]
 mod inner
 {
+	use std::future::Future;
+	use std::pin::Pin;
+	use std::time::{Duration, Instant};
+
 	use async_lock::SemaphoreGuard;
+	use futures::future::Either;
+	use futures::StreamExt;
+	use leptos::server_fn::codec::{ByteStream, TextStream};
+	use tokio::sync::mpsc;
 
-	use crate::api::proxys::imap_components::imap_connector;
+	use crate::api::modules::events::ModuleEvents;
+	use crate::api::proxys::imap_components::{imap_connector, imap_connector_oauth, ImapIdleEvent, ImapMailKey, ImapProxyResponse};
 	use crate::api::proxys::imap_error::ImapError;
 	use crate::api::proxys::imap_inner::ImapProxy;
-	use crate::api::proxys::outbound_policy::OutboundPolicy;
+	use crate::api::proxys::imap_oauth::ImapOAuthLogin;
+	use crate::api::proxys::outbound_policy::{ImapIdlePermit, OutboundPolicy};
+
+	// events waiting for a slow listener before the IDLE worker blocks
+	const IDLE_EVENT_CAPACITY: usize = 8;
+	// decoded ranges of an attachment waiting for a slow reader before its worker blocks
+	const ATTACHMENT_RANGE_CAPACITY: usize = 2;
+
+	// a short request shares the IMAP limit, an IDLE connection has its own limits.
+	// The permits are only held, they are released when dropped
+	#[allow(dead_code)]
+	enum ImapPermit
+	{
+		REQUEST(SemaphoreGuard<'static>),
+		IDLE(ImapIdlePermit),
+	}
 
 	pub(super) struct ImapRequest
 	{
 		operation: &'static str,
-		permit: SemaphoreGuard<'static>,
+		permit: ImapPermit,
 		proxy: ImapProxy,
+		oauthRotated: Option<imap_connector_oauth>,
 	}
 
 	impl ImapRequest
 	{
+		// an IDLE stream ends after this delay, the browser opens a new one right away
+		const IDLE_LIFETIME: Duration = Duration::from_secs(5 * 60);
+
 		pub(super) async fn new(config: imap_connector, operation: &'static str) -> Result<Self, ImapError>
 		{
 			OutboundPolicy::authentication_require().await
 				.map_err(|error| ImapError::from(error).trace(operation,"authentication",None))?;
 			let permit = OutboundPolicy::imapPermit_get()
 				.map_err(|error| ImapError::from(error).trace(operation,"concurrency",None))?;
-			let destination = OutboundPolicy::imapDestination_get(&config.host, config.port).await
+			return Self::withPermit_new(config, operation, ImapPermit::REQUEST(permit)).await;
+		}
+
+		async fn withPermit_new(config: imap_connector, operation: &'static str, permit: ImapPermit) -> Result<Self, ImapError>
+		{
+			let destination = OutboundPolicy::imapDestination_get(&config.host, config.port, config.security).await
 				.map_err(|error| ImapError::from(error).trace(operation,"destination",None))?;
+			let oauthLogin = ImapOAuthLogin::forConnector_get(&config).await
+				.map_err(|error| error.trace(operation,"oauth",None))?;
+			let oauthRotated = ImapOAuthLogin::rotated_get(oauthLogin.as_ref());
 			let proxy = ImapProxy::new(config, destination)
-				.map_err(|error| error.trace(operation,"config",None))?;
-			return Ok(Self { operation, permit, proxy });
+				.map_err(|error| error.trace(operation,"config",None))?
+				.oauthLogin_set(oauthLogin);
+			return Ok(Self { operation, permit, proxy, oauthRotated });
 		}
 
-		pub(super) async fn run<Output, Operation>(self, operationFn: Operation) -> Result<Output, ImapError>
+		/// a refresh token rotated by the login goes back with the answer, or with its error
+		pub(super) async fn run<Output, Operation>(self, operationFn: Operation) -> Result<ImapProxyResponse<Output>, ImapError>
 		where
 			Output: Send + 'static,
 			Operation: FnOnce(ImapProxy) -> Result<Output, ImapError> + Send + 'static,
 		{
-			let Self { operation, permit, proxy } = self;
+			let Self { operation, permit, proxy, oauthRotated } = self;
 			let result = tokio::task::spawn_blocking(move ||
 			{
 				let _permit = permit;
 				return operationFn(proxy);
-			}).await.map_err(|error| ImapError::from(error).trace(operation,"worker",None))?;
-			return result.map_err(|error| error.trace(operation,"complete",None));
+			}).await
+				.map_err(|error| ImapError::from(error).trace(operation,"worker",None))
+				.and_then(|result| result.map_err(|error| error.trace(operation,"complete",None)));
+			return Ok(ImapProxyResponse { result, oauthRotated });
+		}
+
+		/// the worker keeps the permit while the part is sent, a reader leaving stops it at its next range.
+		/// The head carries the rotated refresh token with the failure met before the first range,
+		/// a later failure ends the stream with an error
+		pub(super) async fn attachment_stream(self, mail: ImapMailKey, section: String) -> Result<ByteStream<ImapError>, ImapError>
+		{
+			let Self { operation, permit, proxy, oauthRotated } = self;
+			let (sender, mut receiver) = mpsc::channel(ATTACHMENT_RANGE_CAPACITY);
+			let worker = tokio::task::spawn_blocking(move ||
+			{
+				let _permit = permit;
+				return proxy.attachment_stream(mail,section,|data| sender.blocking_send(data).is_ok())
+					.map_err(|error| error.trace(operation,"complete",None));
+			});
+			let Some(firstData) = receiver.recv().await else {
+				let result = worker.await
+					.map_err(|error| ImapError::from(error).trace(operation,"worker",None))
+					.and_then(|result| result);
+				let head = ImapProxyResponse { result, oauthRotated }.attachmentHead_get();
+				return Ok(ByteStream::new(futures::stream::iter([Ok(head)])));
+			};
+
+			let head = ImapProxyResponse { result: Ok(()), oauthRotated }.attachmentHead_get();
+			let nextData = futures::stream::unfold((receiver, Some(worker)), move |(mut receiver, worker)| async move {
+				if let Some(data) = receiver.recv().await
+				{
+					return Some((Ok(data), (receiver, worker)));
+				}
+				// the worker ended, its failure reaches the reader as the end of the stream
+				let error = worker?.await
+					.map_err(|error| ImapError::from(error).trace(operation,"worker",None))
+					.and_then(|result| result)
+					.err()?;
+				return Some((Err(error), (receiver, None)));
+			});
+			return Ok(ByteStream::new(futures::stream::iter([Ok(head), Ok(firstData)]).chain(nextData)));
+		}
+
+		/// the IDLE worker keeps its IDLE permit while the stream lives, a failure before the first event is returned as is.
+		/// The stream ends after IDLE_LIFETIME, or once the session is logged out or its credential is rotated
+		pub(super) async fn idle_stream(config: imap_connector, boxName: String) -> Result<TextStream<ImapError>, ImapError>
+		{
+			let operation = "idle";
+			let (session, user) = OutboundPolicy::sessionAuthentication_require().await
+				.map_err(|error| ImapError::from(error).trace(operation,"authentication",None))?;
+			let permit = OutboundPolicy::imapIdlePermit_get(user.eventChannel_get())
+				.map_err(|error| ImapError::from(error).trace(operation,"concurrency",None))?;
+			let Self { operation, permit, proxy, oauthRotated } = Self::withPermit_new(config, operation, ImapPermit::IDLE(permit)).await?;
+			let rotatedLine = oauthRotated.map(|oauth| Ok(ImapIdleEvent::OAUTH_ROTATED(oauth).line_get()));
+
+			let deadline = Instant::now() + Self::IDLE_LIFETIME;
+			let (sender, mut receiver) = mpsc::channel(IDLE_EVENT_CAPACITY);
+			let worker = tokio::task::spawn_blocking(move ||
+			{
+				let _permit = permit;
+				return proxy.idle_watch(boxName,deadline,|event| sender.blocking_send(event).is_ok())
+					.map_err(|error| error.trace(operation,"complete",None));
+			});
+			let Some(firstEvent) = receiver.recv().await else {
+				let result = worker.await
+					.map_err(|error| ImapError::from(error).trace(operation,"worker",None))
+					.and_then(|result| result);
+				// a rotated refresh token still reaches the module, ahead of the failure
+				return match (result, rotatedLine)
+				{
+					(Ok(_), rotatedLine) => Ok(TextStream::new(futures::stream::iter(rotatedLine))),
+					(Err(error), None) => Err(error),
+					(Err(error), Some(rotatedLine)) => Ok(TextStream::new(futures::stream::iter([rotatedLine, Err(error)]))),
+				};
+			};
+
+			// dropping the receiver stops the worker at its next heartbeat
+			let sessionEnd: Pin<Box<dyn Future<Output = ()> + Send>> = Box::pin(ModuleEvents::singleton().sessionEnd_wait(session,user));
+			let nextEvents = futures::stream::unfold((receiver, sessionEnd), |(mut receiver, sessionEnd)| async move {
+				let (event, sessionEnd) = match futures::future::select(Box::pin(receiver.recv()), sessionEnd).await
+				{
+					Either::Left((Some(event), sessionEnd)) => (event, sessionEnd),
+					_ => return None,
+				};
+				return Some((Ok(event.line_get()), (receiver, sessionEnd)));
+			});
+			let firstLines = rotatedLine.into_iter().chain([Ok(firstEvent.line_get())]);
+			return Ok(TextStream::new(futures::stream::iter(firstLines).chain(nextEvents)));
 		}
 	}
 
@@ -145,11 +351,34 @@ mod inner
 	{
 		use std::thread;
 
-		use crate::api::proxys::imap_components::imap_connector;
+		use base64ct::{Base64, Encoding};
+		use futures::StreamExt;
+
+		use crate::api::proxys::imap::{API_proxys_imap_getAttachment, API_proxys_imap_getMailContent, API_proxys_imap_search, API_proxys_imap_setMailSee, API_proxys_imap_sync};
+		use crate::api::proxys::imap_components::{imap_connector, ImapMailKey, ImapMailboxSyncState, ImapProxyResponse, ImapSearchQuery, ImapSyncRequest};
+		use crate::api::proxys::imap_error::ImapError;
 		use crate::api::proxys::imap_inner::ImapProxy;
+		use crate::api::proxys::imap_test_server::ImapTestServer;
 		use crate::api::proxys::outbound_policy::{OutboundPolicy, ValidatedImapDestination};
 
-		use super::ImapRequest;
+		use super::{ImapPermit, ImapRequest};
+
+		fn textMail_get(subject: &str) -> Vec<u8>
+		{
+			return format!(
+				"From: sender@example.com\r\nTo: user@localhost\r\nSubject: {}\r\nDate: Mon, 19 Oct 2026 10:00:00 +0000\r\nMessage-ID: <{}@example.com>\r\nContent-Type: text/plain; charset=utf-8\r\n\r\nbody of {}\r\n",
+				subject,
+				subject.replace(' ', "-"),
+				subject,
+			).into_bytes();
+		}
+
+		fn syncRequest_get(uidValidity: Option<u32>, knownUids: Vec<u32>) -> ImapSyncRequest
+		{
+			return ImapSyncRequest {
+				mailboxes: Some(vec![ImapMailboxSyncState { boxName: "INBOX".to_string(), uidValidity, knownUids }]),
+			};
+		}
 
 		#[test]
 		fn request_runsOperationOnBlockingWorker()
@@ -163,10 +392,145 @@ mod inner
 					vec!["8.8.8.8:993".parse().unwrap()],
 				);
 				let proxy = ImapProxy::new(imap_connector::default(), destination).unwrap();
-				let workerThread = ImapRequest { operation: "test", permit, proxy }
-					.run(|_| Ok(thread::current().id())).await.unwrap();
+				let workerThread = ImapRequest { operation: "test", permit: ImapPermit::REQUEST(permit), proxy, oauthRotated: None }
+					.run(|_| Ok(thread::current().id())).await.unwrap().result.unwrap();
 				assert_ne!(workerThread, callerThread);
 			});
 		}
+
+		#[test]
+		fn sync_followsExpungesAndUidValidityChanges()
+		{
+			let server = ImapTestServer::start();
+			server.mailbox_create("INBOX", 7);
+			let first = server.mail_append("INBOX", &[], &textMail_get("first mail"));
+			let second = server.mail_append("INBOX", &[], &textMail_get("second mail"));
+			server.mail_append("INBOX", &["\\Seen"], &textMail_get("read mail"));
+			server.authenticated_run(|| async {
+				let synced = API_proxys_imap_sync(server.connector_get(), syncRequest_get(None, Vec::new())).await.unwrap().result.unwrap();
+				assert_eq!(synced.len(), 1);
+				assert_eq!(synced[0].uidValidity, 7);
+				let mut uids = synced[0].mails.iter().map(|mail| mail.uid).collect::<Vec<_>>();
+				uids.sort_unstable();
+				assert_eq!(uids, vec![first, second]);
+				assert_eq!(synced[0].mails.iter().find(|mail| mail.uid == first).unwrap().subject.as_deref(), Some("first mail"));
+
+				server.mail_expunge("INBOX", first);
+				let synced = API_proxys_imap_sync(server.connector_get(), syncRequest_get(Some(7), vec![first, second])).await.unwrap().result.unwrap();
+				assert_eq!(synced[0].removedUids, vec![first]);
+				assert!(synced[0].mails.is_empty());
+
+				server.uidValidity_set("INBOX", 8);
+				let synced = API_proxys_imap_sync(server.connector_get(), syncRequest_get(Some(7), vec![second])).await.unwrap().result.unwrap();
+				assert_eq!(synced[0].uidValidity, 8);
+				assert!(synced[0].removedUids.is_empty());
+				assert_eq!(synced[0].mails.iter().map(|mail| mail.uid).collect::<Vec<_>>(), vec![second]);
+			});
+			assert_eq!(server.connections_get(), 3);
+			assert!(server.commands_get().iter().any(|command| command.starts_with("UID SEARCH UNSEEN")));
+		}
+
+		#[test]
+		fn search_pagesAfterTheLastListedUid()
+		{
+			let server = ImapTestServer::start();
+			server.mailbox_create("Archive", 2);
+			server.mailbox_create("INBOX", 1);
+			for index in 0..3
+			{
+				server.mail_append("Archive", &[], &textMail_get(&format!("archived {}", index)));
+			}
+			let inboxUids = (0..50).map(|index| server.mail_append("INBOX", &[], &textMail_get(&format!("mail {}", index)))).collect::<Vec<_>>();
+			let query = ImapSearchQuery { unseen: true, ..Default::default() };
+			server.authenticated_run(|| async {
+				let first = API_proxys_imap_search(server.connector_get(), query.clone()).await.unwrap().result.unwrap();
+				assert_eq!(first.total, 53);
+				assert_eq!(first.mails.len(), ImapSearchQuery::PAGE_SIZE);
+				let next = first.next.clone().unwrap();
+				assert_eq!((next.boxName.as_str(), next.uidValidity, next.uid), ("INBOX", 1, inboxUids[3]));
+
+				// a mail received between two pages does not shift the next one
+				server.mail_append("Archive", &[], &textMail_get("new mail"));
+				let second = API_proxys_imap_search(server.connector_get(), query.nextPage_get(next)).await.unwrap().result.unwrap();
+				assert_eq!(second.total, 54);
+				assert_eq!(second.mails.iter().map(|(key, _)| key.uid).collect::<Vec<_>>(), vec![inboxUids[2], inboxUids[1], inboxUids[0]]);
+				assert_eq!(second.next, None);
+			});
+		}
+
+		#[test]
+		fn mailContent_listsALargeAttachmentThenStreamsIt()
+		{
+			let attachment = (0..3 * 1024 * 1024).map(|index| (index % 251) as u8).collect::<Vec<_>>();
+			let encoded = Base64::encode_string(&attachment);
+			let encodedLines = encoded.as_bytes().chunks(76).map(|line| String::from_utf8_lossy(line).into_owned()).collect::<Vec<_>>();
+			let mail = format!(
+				concat!(
+					"From: sender@example.com\r\nTo: user@localhost\r\nSubject: report\r\nDate: Mon, 19 Oct 2026 10:00:00 +0000\r\n",
+					"MIME-Version: 1.0\r\nContent-Type: multipart/mixed; boundary=\"limit\"\r\n\r\n",
+					"--limit\r\nContent-Type: text/plain; charset=utf-8\r\n\r\nthe report is attached\r\n",
+					"--limit\r\nContent-Type: application/octet-stream; name=\"report.bin\"\r\n",
+					"Content-Disposition: attachment; filename=\"report.bin\"\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+					"--limit--\r\n",
+				),
+				encodedLines.join("\r\n"),
+			);
+			let server = ImapTestServer::start();
+			server.mailbox_create("INBOX", 3);
+			let uid = server.mail_append("INBOX", &[], mail.as_bytes());
+			let key = ImapMailKey { boxName: "INBOX".to_string(), uidValidity: 3, uid };
+			server.authenticated_run(|| async {
+				let content = API_proxys_imap_getMailContent(server.connector_get(), key.clone()).await.unwrap().result.unwrap();
+				assert_eq!(content.attachement.len(), 1);
+				assert_eq!(content.attachement[0].filename.as_deref(), Some("report.bin"));
+				assert_eq!(content.attachement[0].section.as_deref(), Some("2"));
+				assert!(content.attachement[0].data.is_empty());
+
+				let (head, data) = attachmentStream_collect(server.connector_get(), key.clone()).await;
+				assert!(head.result.is_ok());
+				assert!(data == attachment);
+
+				let staleKey = ImapMailKey { uidValidity: 2, ..key.clone() };
+				let (stale, data) = attachmentStream_collect(server.connector_get(), staleKey).await;
+				assert!(matches!(stale.result, Err(ImapError::MAIL_NOT_FOUND)));
+				assert!(data.is_empty());
+			});
+			let commands = server.commands_get();
+			let attachmentFetches = commands.iter().filter(|command| command.contains("BODY.PEEK[2]")).count();
+			assert!(attachmentFetches > 1);
+			assert!(commands.iter().any(|command| command.contains("BODY.PEEK[2]<524288.")));
+		}
+
+		async fn attachmentStream_collect(connector: imap_connector, key: ImapMailKey) -> (ImapProxyResponse<()>, Vec<u8>)
+		{
+			let stream = API_proxys_imap_getAttachment(connector, key, "2".to_string()).await.unwrap().into_inner();
+			let chunks = stream.collect::<Vec<_>>().await;
+			let mut buffer = chunks.into_iter().map(Result::unwrap).fold(Vec::new(), |mut buffer, chunk| {
+				buffer.extend_from_slice(&chunk);
+				buffer
+			});
+			let head = ImapProxyResponse::attachmentHead_take(&mut buffer).unwrap().unwrap();
+			return (head, buffer);
+		}
+
+		#[test]
+		fn mailSee_storesTheSeenFlagWhileReadingPeeks()
+		{
+			let server = ImapTestServer::start();
+			server.mailbox_create("INBOX", 5);
+			let uid = server.mail_append("INBOX", &[], &textMail_get("unread mail"));
+			let key = ImapMailKey { boxName: "INBOX".to_string(), uidValidity: 5, uid };
+			server.authenticated_run(|| async {
+				let content = API_proxys_imap_getMailContent(server.connector_get(), key.clone()).await.unwrap().result.unwrap();
+				assert_eq!(content.subject.as_deref(), Some("unread mail"));
+				assert!(server.flags_get("INBOX", uid).is_empty());
+
+				API_proxys_imap_setMailSee(server.connector_get(), key.clone()).await.unwrap().result.unwrap();
+				assert_eq!(server.flags_get("INBOX", uid), vec!["\\Seen".to_string()]);
+
+				let synced = API_proxys_imap_sync(server.connector_get(), syncRequest_get(Some(5), vec![uid])).await.unwrap().result.unwrap();
+				assert_eq!(synced[0].removedUids, vec![uid]);
+			});
+		}
 	}
 }
@@ -1,4 +1,11 @@
 use serde::{Deserialize, Serialize};
+use crate::api::proxys::imap_error::ImapError;
+
+/// signed addresses of the server proxy for the remote images of mails
+pub const MAIL_IMAGE_PROXY_PATH: &str = "/api/mail_image";
+
+/// largest attachment read from the server, downloaded or previewed in the browser
+pub const ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BoxName
@@ -51,6 +58,11 @@ pub struct imap_connector
 	pub username: String,
 	pub password: String,
 	pub extra: Option<imap_connector_extra>,
+	/// replaces the password login when present
+	#[serde(default)]
+	pub oauth: Option<imap_connector_oauth>,
+	#[serde(default)]
+	pub security: ImapConnectionSecurity,
 }
 
 impl imap_connector
@@ -135,10 +147,134 @@ impl Default for imap_connector
 			username: "".to_string(),
 			password: "".to_string(),
 			extra: None,
+			oauth: None,
+			security: ImapConnectionSecurity::TLS,
+		}
+	}
+}
+
+/// how the connection to the IMAP server is protected
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ImapConnectionSecurity
+{
+	/// TLS from the first byte, usually on port 993
+	#[default]
+	TLS,
+	/// plain connection upgraded before the login, usually on port 143, refused when the server does not offer it
+	STARTTLS,
+	/// no encryption, only toward a loopback address and when site.json/imap_plaintext_loopback allows it
+	NONE,
+}
+
+impl ImapConnectionSecurity
+{
+	pub fn translateKey_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::TLS => "MODULE_MAIL_SECURITY_TLS",
+			Self::STARTTLS => "MODULE_MAIL_SECURITY_STARTTLS",
+			Self::NONE => "MODULE_MAIL_SECURITY_NONE",
+		};
+	}
+
+	pub fn fromValue(value: &str) -> Self
+	{
+		return match value
+		{
+			"STARTTLS" => Self::STARTTLS,
+			"NONE" => Self::NONE,
+			_ => Self::TLS,
+		};
+	}
+
+	pub fn value_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::TLS => "TLS",
+			Self::STARTTLS => "STARTTLS",
+			Self::NONE => "NONE",
+		};
+	}
+
+	pub fn defaultPort_get(&self) -> u16
+	{
+		return match self
+		{
+			Self::TLS => 993,
+			Self::STARTTLS | Self::NONE => 143,
+		};
+	}
+
+	/// keeps a port chosen by the user, only follows the default port of the previous mode
+	pub fn port_switch(&self, port: u16, security: Self) -> u16
+	{
+		if (port == self.defaultPort_get())
+		{
+			return security.defaultPort_get();
 		}
+		return port;
+	}
+}
+
+/// OAuth authorization of the connector, its refresh token lives in the encrypted module content.
+/// A token rotated by the provider comes back with the answer of the request that refreshed it
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct imap_connector_oauth
+{
+	/// name of a provider of site.json/imap_oauth_providers
+	pub provider: String,
+	pub refreshToken: String,
+}
+
+/// answer of a proxy request once it logged in, with the refresh token its provider rotated for the login.
+/// The module saves it in place of its own right away, even when the request failed: the server does not keep it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImapProxyResponse<T>
+{
+	pub result: Result<T, ImapError>,
+	#[serde(default)]
+	pub oauthRotated: Option<imap_connector_oauth>,
+}
+
+/// head of the API_proxys_imap_getAttachment stream, one JSON line before the decoded data of the part.
+/// Its result is the failure met before the first range, nothing follows it then
+impl ImapProxyResponse<()>
+{
+	// the head only holds an error and a refresh token, a longer one is not a head
+	pub const ATTACHMENT_HEAD_MAXIMUM_BYTES: usize = 64 * 1024;
+
+	#[cfg(feature = "ssr")]
+	pub fn attachmentHead_get(&self) -> Vec<u8>
+	{
+		let mut line = serde_json::to_vec(self).unwrap_or_default();
+		line.push(b'\n');
+		return line;
+	}
+
+	/// takes the head line out of the received bytes, the data after it stays in the buffer. None while the line is incomplete
+	pub fn attachmentHead_take(buffer: &mut Vec<u8>) -> Option<Result<Self, ImapError>>
+	{
+		let Some(end) = buffer.iter().position(|byte| *byte == b'\n')
+		else
+		{
+			return (buffer.len() > Self::ATTACHMENT_HEAD_MAXIMUM_BYTES).then_some(Err(ImapError::SERVER_ERROR));
+		};
+		let data = buffer.split_off(end + 1);
+		let head = std::mem::replace(buffer, data);
+		return Some(serde_json::from_slice(&head[..end]).map_err(|_| ImapError::SERVER_ERROR));
 	}
 }
 
+/// provider page to open for a new OAuth authorization, state identifies it until its end
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImapOAuthAuthorization
+{
+	pub state: String,
+	pub url: String,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct imap_connector_extra
 {
@@ -156,6 +292,8 @@ pub struct ImapMail
 	pub uid: u32,
 	pub from: String,
 	pub to: String,
+	#[serde(default)]
+	pub cc: String,
 	pub subject: Option<String>,
 	pub content: ImapMailContentType,
 	pub date: i64,
@@ -164,6 +302,104 @@ pub struct ImapMail
 	pub attachement: Vec<Attachment>,
 	#[serde(default)]
 	pub confirmVue: bool,
+	#[serde(default)]
+	pub flagged: bool,
+	/// only a search can return seen mails, the synchronization keeps the unseen ones
+	#[serde(default)]
+	pub seen: bool,
+	/// Message-ID, In-Reply-To and References headers, they group the list in threads and keep an answer in its thread
+	#[serde(default)]
+	pub messageId: Option<String>,
+	#[serde(default)]
+	pub inReplyTo: Option<String>,
+	#[serde(default)]
+	pub references: Option<String>,
+	/// meeting invitation of the mail, only read with its content
+	#[serde(default)]
+	pub invitation: Option<ImapInvitation>,
+	/// signed or encrypted layer of the mail, only read with its content
+	#[serde(default)]
+	pub secured: Option<ImapSecuredPart>,
+	/// RFC822.SIZE of the whole message, in bytes
+	#[serde(default)]
+	pub size: u32,
+	/// values of ImapMail::FILTER_HEADERS present in the mail, for the filtering rules of the browser
+	#[serde(default)]
+	pub headers: Vec<(String, String)>,
+}
+
+impl ImapMail
+{
+	/// headers synchronized with the list in addition to the displayed ones
+	pub const FILTER_HEADERS: [&'static str; 3] = ["List-Id", "Reply-To", "Content-Type"];
+
+	pub fn header_get(&self, name: &str) -> Option<&str>
+	{
+		return self.headers.iter().find(|(header,_)| header.eq_ignore_ascii_case(name)).map(|(_,value)| value.as_str());
+	}
+
+	/// IMAP has no attachment flag: like the search, a multipart/mixed mail is taken as having one
+	pub fn hasAttachment(&self) -> bool
+	{
+		return !self.attachement.is_empty()
+			|| self.header_get("Content-Type").is_some_and(|contentType| {
+				return contentType.trim_start().get(..15).is_some_and(|prefix| prefix.eq_ignore_ascii_case("multipart/mixed"));
+			});
+	}
+}
+
+/// protection of a mail, OpenPGP/MIME (RFC 3156) or S/MIME (RFC 8551)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ImapSecuredKind
+{
+	OPENPGP_SIGNED,
+	OPENPGP_ENCRYPTED,
+	SMIME_SIGNED,
+	/// application/pkcs7-mime, an enveloped or an opaque signed CMS structure
+	SMIME_MIME,
+}
+
+/// raw parts of a signed or encrypted mail. The server never checks them: the browser holds the keys
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ImapSecuredPart
+{
+	pub kind: ImapSecuredKind,
+	/// MIME entity covered by a detached signature, as it appears in the mail
+	#[serde(default, with = "attachmentDataBase64")]
+	pub signed: Vec<u8>,
+	/// detached signature, or the encrypted content
+	#[serde(with = "attachmentDataBase64")]
+	pub payload: Vec<u8>,
+}
+
+/// iTIP method of a calendar part (RFC 5546), the parts with another method stay attachments
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ImapInvitationMethod
+{
+	REQUEST,
+	CANCEL,
+}
+
+impl ImapInvitationMethod
+{
+	#[cfg(feature = "ssr")]
+	pub fn fromValue(value: &str) -> Option<Self>
+	{
+		return match value.trim().to_ascii_uppercase().as_str()
+		{
+			"REQUEST" => Some(Self::REQUEST),
+			"CANCEL" => Some(Self::CANCEL),
+			_ => None,
+		};
+	}
+}
+
+/// text/calendar part of an invitation, the browser parses it with the calendar module
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ImapInvitation
+{
+	pub method: ImapInvitationMethod,
+	pub calendar: String,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, Hash, PartialEq)]
@@ -174,6 +410,129 @@ pub struct ImapMailKey
 	pub uid: u32,
 }
 
+/// change applied to a selection of mails, TRASH and ARCHIVE target the SPECIAL-USE mailboxes
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum ImapMailAction
+{
+	TRASH,
+	ARCHIVE,
+	MOVE(String),
+	FLAGGED(bool),
+	UNSEEN,
+}
+
+impl ImapMailAction
+{
+	/// the mails leave their mailbox and must leave the list with them
+	pub fn isMoving(&self) -> bool
+	{
+		return matches!(self, Self::TRASH | Self::ARCHIVE | Self::MOVE(_));
+	}
+}
+
+/// one line of the IMAP IDLE stream, sent as newline-delimited JSON
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum ImapIdleEvent
+{
+	/// the mailbox is selected and idling, sent once at the stream start
+	WATCHING(String),
+	/// new, removed or changed mails in the watched mailbox
+	CHANGED(String),
+	/// nothing happened during the last IDLE round, keeps the connection alive
+	HEARTBEAT,
+	/// the server does not advertise IDLE, the stream ends and polling goes on
+	UNSUPPORTED,
+	/// refresh token rotated by the provider while the stream logged in, sent before any other line
+	OAUTH_ROTATED(imap_connector_oauth),
+}
+
+impl ImapIdleEvent
+{
+	#[cfg(feature = "ssr")]
+	pub fn line_get(&self) -> String
+	{
+		let mut line = serde_json::to_string(self).unwrap_or_default();
+		line.push('\n');
+		return line;
+	}
+
+	/// takes the complete lines out of the received text, a partial last line stays in the buffer
+	pub fn lines_take(buffer: &mut String) -> Vec<Self>
+	{
+		let Some(lastEnd) = buffer.rfind('\n') else {return Vec::new()};
+		let remaining = buffer.split_off(lastEnd + 1);
+		let lines = std::mem::replace(buffer, remaining);
+		return lines.lines()
+			.filter_map(|line| serde_json::from_str(line).ok())
+			.collect();
+	}
+}
+
+/// structured mail search over the selected mailboxes, every filled criterion must match
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct ImapSearchQuery
+{
+	/// anywhere in the headers or the body
+	#[serde(default)]
+	pub text: String,
+	#[serde(default)]
+	pub from: String,
+	#[serde(default)]
+	pub to: String,
+	#[serde(default)]
+	pub subject: String,
+	#[serde(default)]
+	pub body: String,
+	/// first day included, as YYYY-MM-DD
+	#[serde(default)]
+	pub since: Option<String>,
+	/// last day included, as YYYY-MM-DD
+	#[serde(default)]
+	pub until: Option<String>,
+	#[serde(default)]
+	pub unseen: bool,
+	#[serde(default)]
+	pub hasAttachment: bool,
+	/// last mail of the previous page, the page lists the mails after it
+	#[serde(default)]
+	pub after: Option<ImapMailKey>,
+}
+
+impl ImapSearchQuery
+{
+	#[cfg(feature="ssr")]
+	pub const PAGE_SIZE: usize = 50;
+
+	/// a query without criterion would list every mail, it is not sent
+	pub fn hasCriterion(&self) -> bool
+	{
+		return [&self.text, &self.from, &self.to, &self.subject, &self.body].iter().any(|term| !term.trim().is_empty())
+			|| self.since.is_some()
+			|| self.until.is_some()
+			|| self.unseen
+			|| self.hasAttachment;
+	}
+
+	pub fn nextPage_get(&self, after: ImapMailKey) -> Self
+	{
+		return Self {
+			after: Some(after),
+			..self.clone()
+		};
+	}
+}
+
+/// one page of the search results, newest first in each mailbox, mailboxes by name
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ImapSearchResult
+{
+	pub mails: Vec<(ImapMailKey, ImapMail)>,
+	/// matching mails of every page
+	pub total: usize,
+	/// last mail of the page when more mails match, the next page starts after it
+	pub next: Option<ImapMailKey>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct ImapMailboxSyncState
 {
@@ -196,6 +555,9 @@ pub struct ImapMailboxSync
 	pub uidValidity: u32,
 	pub removedUids: Vec<u32>,
 	pub mails: Vec<ImapMail>,
+	/// tracked uids grouped by the server THREAD extension, empty without it
+	#[serde(default)]
+	pub threads: Vec<Vec<u32>>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -204,8 +566,20 @@ pub struct Attachment
 	pub filename: Option<String>,
 	pub content_type: String,
 	pub content_id: Option<String>,
-	#[serde(with = "attachmentDataBase64")]
+	/// empty while the attachment stays on the server, see section
+	#[serde(default, with = "attachmentDataBase64")]
 	pub data: Vec<u8>,
+	/// MIME part path ("2", "1.3") of an attachment listed without its data, fetched on demand by API_proxys_imap_getAttachment
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub section: Option<String>,
+}
+
+impl Attachment
+{
+	pub fn isLoaded(&self) -> bool
+	{
+		return self.section.is_none();
+	}
 }
 
 mod attachmentDataBase64
@@ -213,7 +587,7 @@ mod attachmentDataBase64
 	use base64ct::{Base64, Encoding};
 	use serde::{Deserialize, Deserializer, Serializer};
 
-	const DATA_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
+	const DATA_MAXIMUM_BYTES: usize = super::ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES;
 	const BASE64_MAXIMUM_BYTES: usize = (DATA_MAXIMUM_BYTES + 2) / 3 * 4;
 
 	#[derive(Deserialize)]
@@ -250,6 +624,60 @@ mod attachmentDataBase64
 	}
 }
 
+/// one header of a mail source, unfolded and decoded by the server
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ImapMailSourceHeader
+{
+	pub name: String,
+	pub value: String,
+	/// timestamp after the last semicolon of a Received header
+	#[serde(default)]
+	pub date: Option<i64>,
+}
+
+/// RFC 822 source of a mail, cut at ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct ImapMailSource
+{
+	#[serde(with = "attachmentDataBase64")]
+	pub data: Vec<u8>,
+	/// size of the whole mail, larger than data when it was cut
+	pub size: u32,
+	/// first headers of the data, the browser has no MIME parser
+	#[serde(default)]
+	pub headers: Vec<ImapMailSourceHeader>,
+}
+
+impl ImapMailSource
+{
+	#[cfg(feature = "ssr")]
+	const HEADERS_MAXIMUM: usize = 500;
+
+	#[cfg(feature = "ssr")]
+	pub fn new(data: Vec<u8>, size: u32) -> Self
+	{
+		let headers = mailparse::parse_headers(&data)
+			.map(|(headers,_)| {
+				return headers.iter().take(Self::HEADERS_MAXIMUM).map(|header| {
+					let name = header.get_key();
+					let value = header.get_value();
+					let date = name.eq_ignore_ascii_case("received")
+						.then(|| value.rsplit_once(';'))
+						.flatten()
+						.and_then(|(_,date)| mailparse::dateparse(date.trim()).ok());
+					return ImapMailSourceHeader {name,value,date};
+				}).collect();
+			})
+			.unwrap_or_default();
+		return Self {data,size,headers};
+	}
+
+	pub fn isTruncated(&self) -> bool
+	{
+		return self.size as usize > self.data.len();
+	}
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ImapMailContentType
 {
@@ -289,7 +717,8 @@ impl ImapMailContentType
 #[cfg(test)]
 mod tests
 {
-	use super::{Attachment, Attributs, BoxName, ImapMailboxSyncState, imap_connector, imap_connector_extra};
+	use super::{Attachment, Attributs, BoxName, ImapIdleEvent, ImapMailboxSyncState, ImapProxyResponse, imap_connector, imap_connector_extra, imap_connector_oauth};
+	use crate::api::proxys::imap_error::ImapError;
 
 	fn boxName_get(name: &str, attributes: Attributs) -> BoxName
 	{
@@ -307,6 +736,42 @@ mod tests
 		assert_eq!(extra.boxBlackList,vec!["Blocked"]);
 	}
 
+	#[cfg(feature = "ssr")]
+	#[test]
+	fn idleEventLinesKeepThePartialLineForTheNextChunk()
+	{
+		let changed = ImapIdleEvent::CHANGED("INBOX".to_string()).line_get();
+		let mut buffer = format!("{}{}", ImapIdleEvent::WATCHING("INBOX".to_string()).line_get(), &changed[..4]);
+
+		assert_eq!(ImapIdleEvent::lines_take(&mut buffer),vec![ImapIdleEvent::WATCHING("INBOX".to_string())]);
+		assert_eq!(buffer,&changed[..4]);
+
+		buffer.push_str(&changed[4..]);
+		buffer.push_str(&ImapIdleEvent::HEARTBEAT.line_get());
+		assert_eq!(ImapIdleEvent::lines_take(&mut buffer),vec![ImapIdleEvent::CHANGED("INBOX".to_string()),ImapIdleEvent::HEARTBEAT]);
+		assert!(buffer.is_empty());
+	}
+
+	#[cfg(feature = "ssr")]
+	#[test]
+	fn attachmentHeadIsTakenOnceCompleteAndLeavesTheData()
+	{
+		let rotated = imap_connector_oauth {provider: "provider".to_string(),refreshToken: "rotated".to_string()};
+		let head = ImapProxyResponse {result: Ok(()),oauthRotated: Some(rotated.clone())}.attachmentHead_get();
+		let mut buffer = head[..5].to_vec();
+
+		assert!(ImapProxyResponse::attachmentHead_take(&mut buffer).is_none());
+		buffer.extend_from_slice(&head[5..]);
+		buffer.extend_from_slice(b"\ndata");
+		let taken = ImapProxyResponse::attachmentHead_take(&mut buffer).unwrap().unwrap();
+		assert_eq!(taken.result,Ok(()));
+		assert_eq!(taken.oauthRotated,Some(rotated));
+		assert_eq!(buffer,b"\ndata");
+
+		let mut endless = vec![b'{'; ImapProxyResponse::ATTACHMENT_HEAD_MAXIMUM_BYTES + 1];
+		assert_eq!(ImapProxyResponse::attachmentHead_take(&mut endless).unwrap().unwrap_err(),ImapError::SERVER_ERROR);
+	}
+
 	#[test]
 	fn connectorWithoutExtraDoesNotSelectAnyMailbox()
 	{
@@ -417,6 +882,7 @@ mod tests
 			content_type: "application/octet-stream".to_string(),
 			content_id: None,
 			data: vec![1,2,3],
+			section: None,
 		};
 
 		let serialized = serde_json::to_value(&attachment).unwrap();
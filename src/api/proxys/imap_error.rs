@@ -12,11 +12,21 @@ pub enum ImapError {
 	IMAP_SERVER_CONNECTION,
 	IMAP_SERVER_CONNECTION_TLS,
 	MAIL_NOT_FOUND,
+	MAILBOX_NOT_FOUND,
 	INVALID_DATE,
 	AUTH_REQUIRED,
 	DESTINATION_FORBIDDEN,
 	RESOURCE_LIMIT,
 	SERVER_ERROR,
+	SMTP_AUTHENTICATION,
+	SMTP_REJECTED,
+	MESSAGE_INVALID,
+	SEARCH_INVALID,
+	SEARCH_CHARSET_UNSUPPORTED,
+	OAUTH_PROVIDER_UNKNOWN,
+	OAUTH_REJECTED,
+	OAUTH_UNAVAILABLE,
+	OAUTH_PENDING,
 }
 
 #[cfg(feature = "ssr")]
@@ -89,6 +99,55 @@ impl ImapError
 		return result;
 	}
 
+	/// SMTP failures share the IMAP error contract, the outgoing server uses the same credentials
+	pub(super) fn fromSmtpAt(
+		value: lettre::transport::smtp::Error,
+		operation: &'static str,
+		stage: &'static str,
+	) -> Self
+	{
+		use Htrace::components::level::Level;
+		use Htrace::HTrace;
+
+		let (source, result) = if (value.is_tls())
+		{
+			("tls", Self::IMAP_SERVER_CONNECTION_TLS)
+		}
+		else if (value.is_timeout())
+		{
+			("timeout", Self::RESOURCE_LIMIT)
+		}
+		else if (value.is_permanent() || value.is_transient())
+		{
+			let source = if (value.is_permanent()) {"permanent_response"} else {"transient_response"};
+			if (stage == "authentication") {(source, Self::SMTP_AUTHENTICATION)} else {(source, Self::SMTP_REJECTED)}
+		}
+		else if (value.is_client())
+		{
+			// the client refuses a server without STARTTLS, a common authentication mechanism or SMTPUTF8
+			("client", match stage
+			{
+				"connection" => Self::IMAP_SERVER_CONNECTION_TLS,
+				"authentication" => Self::SMTP_AUTHENTICATION,
+				_ => Self::SMTP_REJECTED,
+			})
+		}
+		else
+		{
+			("connection", Self::IMAP_SERVER_CONNECTION)
+		};
+		HTrace!(
+			(Level::ERROR)
+			"[SMTP proxy] operation={} stage={} source={} status={:?} error={}",
+			operation,
+			stage,
+			source,
+			value.status().map(|code| code.to_string()),
+			result
+		);
+		return result;
+	}
+
 	pub(super) fn imapSource_get(value: &Error) -> &'static str
 	{
 		return match value
@@ -159,6 +218,16 @@ impl IsToastable for ImapError {
 			ImapError::RESOURCE_LIMIT => Some(ToastLevel::Error),
 			ImapError::SERVER_ERROR => Some(ToastLevel::Error),
 			ImapError::MAIL_NOT_FOUND => Some(ToastLevel::Error),
+			ImapError::MAILBOX_NOT_FOUND => Some(ToastLevel::Error),
+			ImapError::SMTP_AUTHENTICATION => Some(ToastLevel::Error),
+			ImapError::SMTP_REJECTED => Some(ToastLevel::Error),
+			ImapError::MESSAGE_INVALID => Some(ToastLevel::Error),
+			ImapError::SEARCH_INVALID => Some(ToastLevel::Warn),
+			ImapError::SEARCH_CHARSET_UNSUPPORTED => Some(ToastLevel::Warn),
+			ImapError::OAUTH_PROVIDER_UNKNOWN => Some(ToastLevel::Error),
+			ImapError::OAUTH_REJECTED => Some(ToastLevel::Error),
+			ImapError::OAUTH_UNAVAILABLE => Some(ToastLevel::Error),
+			ImapError::OAUTH_PENDING => Some(ToastLevel::Info),
 		}
 	}
 
@@ -1,29 +1,45 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::io::{Read, Write};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
 use ammonia::{Builder, UrlRelative};
 use base64ct::{Base64, Encoding};
-use imap::types::{Fetch, Fetches, Uid};
+use imap::extensions::idle::{SetReadTimeout, WaitOutcome};
+use imap::types::{Fetch, Fetches, Flag, Uid, UnsolicitedResponse};
 use imap::{Connection, Error, Session};
+use imap_proto::{BodyStructure, MessageSection, SectionPath};
 use mailparse::{parse_mail, DispositionType, MailHeaderMap, ParsedMail};
+use time::{Date, Month};
 
 use crate::api::proxys::imap_components::{
 	imap_connector,
 	Attachment,
 	Attributs,
 	BoxName,
+	ImapIdleEvent,
+	ImapInvitation,
+	ImapInvitationMethod,
 	ImapMailboxSync,
 	ImapMailboxSyncState,
 	ImapMail,
+	ImapMailAction,
 	ImapMailContentType,
 	ImapMailKey,
+	ImapMailSource,
+	ImapSearchQuery,
+	ImapSearchResult,
+	ImapSecuredKind,
+	ImapSecuredPart,
 	ImapSyncRequest,
+	ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES,
+	MAIL_IMAGE_PROXY_PATH,
 };
 use crate::api::proxys::imap_error::ImapError;
+use crate::api::proxys::imap_oauth::ImapOAuthLogin;
+use crate::api::proxys::mail_image::MailImageProxy;
 use crate::api::proxys::outbound_policy::ValidatedImapDestination;
 
 struct ImapLimits;
@@ -34,19 +50,33 @@ impl ImapLimits
 	const ATTACHMENT_FILENAME_MAXIMUM_BYTES: usize = 1_024;
 	const ATTACHMENT_MIME_MAXIMUM_BYTES: usize = 255;
 	const ATTACHMENT_CONTENT_ID_MAXIMUM_BYTES: usize = 1_024;
-	const ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
+	// base64 with its line breaks takes about 1.37 times the decoded size
+	const ATTACHMENT_ENCODED_MAXIMUM_BYTES: usize = 24 * 1024 * 1024;
+	const ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES: usize = ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES;
+	// encoded bytes of a part fetched at once, each range is decoded and sent before the next one
+	const ATTACHMENT_RANGE_BYTES: usize = 512 * 1024;
 	const ATTACHMENT_TOTAL_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
 	const BLACKLIST_ENTRY_MAXIMUM: usize = 128;
 	const BOX_NAME_MAXIMUM_BYTES: usize = 1_024;
 	const CREDENTIAL_MAXIMUM_BYTES: usize = 4_096;
 	const HEADER_MAXIMUM_BYTES: usize = 64 * 1024;
+	// IDLE is re-issued after each round, the heartbeat sent between rounds reveals a closed listener
+	const IDLE_ROUND: Duration = Duration::from_secs(60);
+	const IDLE_IO_TIMEOUT: Duration = Duration::from_secs(30);
+	const INVITATION_MAXIMUM_BYTES: usize = 256 * 1024;
 	const MAILBOX_MAXIMUM: usize = 64;
+	const MAIL_ACTION_MAXIMUM: usize = Self::MAIL_HEADER_MAXIMUM;
 	const MAIL_CONTENT_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
 	const MAIL_HEADER_MAXIMUM: usize = 100;
+	const MAIL_PART_MAXIMUM: usize = 128;
+	const MAIL_PART_DEPTH_MAXIMUM: usize = 16;
 	const MAIL_RENDERED_CONTENT_MAXIMUM_BYTES: usize = 24 * 1024 * 1024;
 	const MAIL_RENDERED_INLINE_MAXIMUM_BYTES: usize = 8 * 1024 * 1024;
+	const MAIL_SOURCE_MAXIMUM_BYTES: usize = ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES;
 	const OPERATION_TIMEOUT: Duration = Duration::from_secs(90);
+	const SEARCH_TERM_MAXIMUM_BYTES: usize = 256;
 	const SYNC_KNOWN_UID_MAXIMUM: usize = Self::MAIL_HEADER_MAXIMUM;
+	const THREAD_LINE_MAXIMUM_BYTES: usize = 16 * 1024;
 }
 
 struct ImapWorkBudget
@@ -171,21 +201,246 @@ impl ImapWorkBudget
 
 }
 
+/// keeps a read timeout on the connection outside of the IDLE rounds, the imap crate removes it after each one
+struct ImapIdleConnection<T>(T);
+
+impl<T: Read> Read for ImapIdleConnection<T>
+{
+	fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize>
+	{
+		return self.0.read(buffer);
+	}
+}
+
+impl<T: Write> Write for ImapIdleConnection<T>
+{
+	fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize>
+	{
+		return self.0.write(buffer);
+	}
+
+	fn flush(&mut self) -> std::io::Result<()>
+	{
+		return self.0.flush();
+	}
+}
+
+impl<T: SetReadTimeout> SetReadTimeout for ImapIdleConnection<T>
+{
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> imap::Result<()>
+	{
+		return self.0.set_read_timeout(Some(timeout.unwrap_or(ImapLimits::IDLE_IO_TIMEOUT)));
+	}
+}
+
+/// rewrites the THREAD answers of the connections made with stream_wrap while a THREAD command runs, imap-proto has no parser for them
+#[derive(Clone, Default)]
+struct ImapThreadRewrite(Arc<AtomicBool>);
+
+impl ImapThreadRewrite
+{
+	const LINE_PREFIX: &[u8] = b"* THREAD";
+	// an untagged OK with the answer as its text, the imap crate keeps it in the response
+	const LINE_REPLACEMENT: &[u8] = b"* OK THREAD";
+
+	fn stream_wrap<T>(&self, inner: T) -> ImapThreadRewriteStream<T>
+	{
+		return ImapThreadRewriteStream {
+			inner,
+			rewrite: self.clone(),
+			line: Vec::new(),
+			output: VecDeque::new(),
+			isLineStart: true,
+		};
+	}
+
+	fn begin(&self)
+	{
+		self.0.store(true,Ordering::Relaxed);
+	}
+
+	fn end(&self)
+	{
+		self.0.store(false,Ordering::Relaxed);
+	}
+
+	fn isActive(&self) -> bool
+	{
+		return self.0.load(Ordering::Relaxed);
+	}
+
+	/// top level groups of the rewritten THREAD answers of a response, whatever their inner tree: "(2)(3 6 (4 23)(44 7))" gives [2] and [3,6,4,23,44,7]
+	fn response_parse(response: &[u8]) -> Vec<Vec<u32>>
+	{
+		return response.split(|&byte| byte == b'\n')
+			.filter_map(|line| line.strip_prefix(Self::LINE_REPLACEMENT))
+			.flat_map(Self::groups_parse)
+			.collect();
+	}
+
+	fn groups_parse(answer: &[u8]) -> Vec<Vec<u32>>
+	{
+		let mut groups = Vec::new();
+		let mut group = Vec::new();
+		let mut depth = 0usize;
+		let mut number = None::<u32>;
+		for &byte in answer
+		{
+			if byte.is_ascii_digit()
+			{
+				number = Some(number.unwrap_or(0).saturating_mul(10).saturating_add(u32::from(byte - b'0')));
+				continue;
+			}
+			if let Some(uid) = number.take() && depth > 0 && uid > 0
+			{
+				group.push(uid);
+			}
+			match byte
+			{
+				b'(' => depth += 1,
+				b')' if depth > 0 =>
+				{
+					depth -= 1;
+					if (depth == 0)
+					{
+						groups.push(std::mem::take(&mut group));
+					}
+				},
+				_ => {},
+			}
+		}
+		return groups;
+	}
+}
+
+/// rewrites the THREAD answers of the stream while a rewrite is active, the imap crate fails on them
+struct ImapThreadRewriteStream<T>
+{
+	inner: T,
+	rewrite: ImapThreadRewrite,
+	// start of a line which may be a THREAD answer
+	line: Vec<u8>,
+	output: VecDeque<u8>,
+	isLineStart: bool,
+}
+
+impl<T> ImapThreadRewriteStream<T>
+{
+	fn byte_push(&mut self, byte: u8)
+	{
+		if (!self.isLineStart && self.line.is_empty())
+		{
+			self.output.push_back(byte);
+			self.isLineStart = byte == b'\n';
+			return;
+		}
+		self.isLineStart = false;
+		self.line.push(byte);
+		let prefix = ImapThreadRewrite::LINE_PREFIX;
+		let compared = self.line.len().min(prefix.len());
+		if (!self.line[..compared].eq_ignore_ascii_case(&prefix[..compared]) || self.line.len() > ImapLimits::THREAD_LINE_MAXIMUM_BYTES)
+		{
+			self.output.extend(self.line.drain(..));
+			self.isLineStart = byte == b'\n';
+			return;
+		}
+		if (byte == b'\n')
+		{
+			self.output.extend(ImapThreadRewrite::LINE_REPLACEMENT);
+			self.output.extend(self.line.drain(prefix.len()..));
+			self.line.clear();
+			self.isLineStart = true;
+		}
+	}
+}
+
+impl<T: Read> Read for ImapThreadRewriteStream<T>
+{
+	fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize>
+	{
+		loop
+		{
+			if (!self.output.is_empty())
+			{
+				let length = buffer.len().min(self.output.len());
+				for (target,byte) in buffer.iter_mut().zip(self.output.drain(..length))
+				{
+					*target = byte;
+				}
+				return Ok(length);
+			}
+			if (self.line.is_empty() && !self.rewrite.isActive())
+			{
+				let length = self.inner.read(buffer)?;
+				if (length > 0)
+				{
+					self.isLineStart = buffer[length - 1] == b'\n';
+				}
+				return Ok(length);
+			}
+			let mut chunk = [0u8; 4096];
+			let length = self.inner.read(&mut chunk)?;
+			if (length == 0)
+			{
+				if (self.line.is_empty())
+				{
+					return Ok(0);
+				}
+				self.output.extend(self.line.drain(..));
+				continue;
+			}
+			for &byte in &chunk[..length]
+			{
+				self.byte_push(byte);
+			}
+		}
+	}
+}
+
+impl<T: Write> Write for ImapThreadRewriteStream<T>
+{
+	fn write(&mut self, buffer: &[u8]) -> std::io::Result<usize>
+	{
+		return self.inner.write(buffer);
+	}
+
+	fn flush(&mut self) -> std::io::Result<()>
+	{
+		return self.inner.flush();
+	}
+}
+
+impl<T: SetReadTimeout> SetReadTimeout for ImapThreadRewriteStream<T>
+{
+	fn set_read_timeout(&mut self, timeout: Option<Duration>) -> imap::Result<()>
+	{
+		return self.inner.set_read_timeout(timeout);
+	}
+}
+
 pub(super) struct ImapProxy
 {
 	config: imap_connector,
 	destination: ValidatedImapDestination,
+	oauthLogin: Option<ImapOAuthLogin>,
 }
 
 impl ImapProxy
 {
 	pub(super) fn new(config: imap_connector, destination: ValidatedImapDestination) -> Result<Self, ImapError>
 	{
-		let proxy = Self { config, destination };
+		let proxy = Self { config, destination, oauthLogin: None };
 		proxy.config_validate()?;
 		return Ok(proxy);
 	}
 
+	/// the sessions of an OAuth connector authenticate with this access token instead of the password
+	pub(super) fn oauthLogin_set(mut self, oauthLogin: Option<ImapOAuthLogin>) -> Self
+	{
+		self.oauthLogin = oauthLogin;
+		return self;
+	}
+
 	pub(super) fn listbox_get(self) -> Result<Vec<BoxName>, ImapError>
 	{
 		let (mut session, isGmail) = self.connection_get("list")?;
@@ -213,7 +468,10 @@ impl ImapProxy
 		}
 
 		let mut budget = ImapWorkBudget::new();
-		let (mut session, isGmail) = self.connection_get("sync")?;
+		let threadRewrite = ImapThreadRewrite::default();
+		let (mut session, isGmail) = self.threadConnection_get(&threadRewrite,"sync")?;
+		// cleared by the first mailbox whose THREAD command is refused
+		let mut threadRewrite = Some(&threadRewrite);
 		let mut mailboxStates = match request.mailboxes
 		{
 			Some(mailboxes) => mailboxes,
@@ -242,7 +500,7 @@ impl ImapProxy
 			{
 				return Ok(result);
 			}
-			if let Some(mailbox) = Self::mailboxSync_get(&mut session,&mailboxState,&mut budget,mailboxIndex)?
+			if let Some(mailbox) = Self::mailboxSync_get(&mut session,&mailboxState,&mut budget,mailboxIndex,&mut threadRewrite)?
 			{
 				result.push(mailbox);
 			}
@@ -252,14 +510,98 @@ impl ImapProxy
 		return syncResult;
 	}
 
+	/// threadRewrite is only given on a connection made with it, it is cleared when the server refuses the THREAD command
 	fn mailboxSync_get<T: Read + Write>(
 		session: &mut Session<T>,
 		state: &ImapMailboxSyncState,
 		budget: &mut ImapWorkBudget,
 		mailboxIndex: usize,
+		threadRewrite: &mut Option<&ImapThreadRewrite>,
 	) -> Result<Option<ImapMailboxSync>, ImapError>
 	{
-		let mailbox = match session.select(&state.boxName)
+		let Some(uidValidity) = Self::mailbox_select(session,&state.boxName,"sync",mailboxIndex)?
+		else
+		{
+			return Ok(None);
+		};
+		let unseenUids = session.uid_search(
+			"UNSEEN UNKEYWORD $Junk UNKEYWORD $Spam UNDELETED UNANSWERED UNDRAFT",
+		).map_err(|error| ImapError::fromImapAt(error,"sync","mailbox_search",Some(mailboxIndex)))?;
+		let trackedUids = budget.trackedUids_get(unseenUids)
+			.map_err(|error| error.trace("sync","search_result",Some(mailboxIndex)))?;
+		let threads = match *threadRewrite
+		{
+			Some(rewrite) => Self::mailboxThreads_get(session,rewrite,&trackedUids,mailboxIndex)?
+				.unwrap_or_else(|| {
+					*threadRewrite = None;
+					return Vec::new();
+				}),
+			None => Vec::new(),
+		};
+		let delta = ImapMailboxDelta::new(Some(state),uidValidity,trackedUids);
+		let mails = ImapMailParser::headers_get(session,delta.newUids,budget,"sync",mailboxIndex)?;
+		return Ok(Some(ImapMailboxSync {
+			boxName: state.boxName.clone(),
+			uidValidity,
+			removedUids: delta.removedUids,
+			mails,
+			threads,
+		}));
+	}
+
+	/// server side threads of the tracked mails, only the ones grouping several of them, None when the server refuses THREAD
+	fn mailboxThreads_get<T: Read + Write>(
+		session: &mut Session<T>,
+		threadRewrite: &ImapThreadRewrite,
+		trackedUids: &HashSet<Uid>,
+		mailboxIndex: usize,
+	) -> Result<Option<Vec<Vec<u32>>>, ImapError>
+	{
+		let trackedUidSet = ImapUidSet::new(trackedUids.iter().copied().collect());
+		if (trackedUidSet.is_empty())
+		{
+			return Ok(Some(Vec::new()));
+		}
+		threadRewrite.begin();
+		let result = session.run_command_and_read_response(format!("UID THREAD REFERENCES UTF-8 UID {}",trackedUidSet.sequence_get()));
+		threadRewrite.end();
+		let response = match result
+		{
+			Ok(response) => response,
+			// the threads only complete the header threading of the browser
+			Err(error @ (Error::No(_) | Error::Bad(_))) =>
+			{
+				use Htrace::components::level::Level;
+				use Htrace::HTrace;
+
+				HTrace!(
+					(Level::WARNING)
+					"[IMAP proxy] operation=sync stage=mailbox_thread mailbox_index={} source={} action=disable",
+					mailboxIndex,
+					ImapError::imapSource_get(&error)
+				);
+				return Ok(None);
+			},
+			Err(error) => return Err(ImapError::fromImapAt(error,"sync","mailbox_thread",Some(mailboxIndex))),
+		};
+		return Ok(Some(ImapThreadRewrite::response_parse(&response).into_iter()
+			.map(|mut group| {
+				group.retain(|uid| trackedUids.contains(uid));
+				return group;
+			})
+			.filter(|group| group.len() > 1)
+			.collect()));
+	}
+
+	/// uid validity of the selected mailbox, None when the server refuses to select it
+	fn mailbox_select<T: Read + Write>(
+		session: &mut Session<T>,
+		boxName: &str,
+		operation: &'static str,
+		mailboxIndex: usize,
+	) -> Result<Option<u32>, ImapError>
+	{
+		let mailbox = match session.select(boxName)
 		{
 			Ok(mailbox) => mailbox,
 			Err(error @ (Error::No(_) | Error::Bad(_))) =>
@@ -269,7 +611,8 @@ impl ImapProxy
 
 				HTrace!(
 					(Level::WARNING)
-					"[IMAP proxy] operation=sync stage=mailbox_select mailbox_index={} source={} action=skip",
+					"[IMAP proxy] operation={} stage=mailbox_select mailbox_index={} source={} action=skip",
+					operation,
 					mailboxIndex,
 					ImapError::imapSource_get(&error)
 				);
@@ -277,24 +620,126 @@ impl ImapProxy
 			},
 			Err(error) =>
 			{
-				return Err(ImapError::fromImapAt(error,"sync","mailbox_select",Some(mailboxIndex)));
+				return Err(ImapError::fromImapAt(error,operation,"mailbox_select",Some(mailboxIndex)));
 			},
 		};
 		let uidValidity = mailbox.uid_validity.filter(|uidValidity| *uidValidity > 0)
-			.ok_or_else(|| ImapError::SERVER_ERROR.trace("sync","uid_validity",Some(mailboxIndex)))?;
-		let unseenUids = session.uid_search(
-			"UNSEEN UNKEYWORD $Junk UNKEYWORD $Spam UNDELETED UNANSWERED UNDRAFT",
-		).map_err(|error| ImapError::fromImapAt(error,"sync","mailbox_search",Some(mailboxIndex)))?;
-		let trackedUids = budget.trackedUids_get(unseenUids)
-			.map_err(|error| error.trace("sync","search_result",Some(mailboxIndex)))?;
-		let delta = ImapMailboxDelta::new(Some(state),uidValidity,trackedUids);
-		let mails = ImapMailParser::headers_get(session,delta.newUids,budget,mailboxIndex)?;
-		return Ok(Some(ImapMailboxSync {
-			boxName: state.boxName.clone(),
-			uidValidity,
-			removedUids: delta.removedUids,
-			mails,
-		}));
+			.ok_or_else(|| ImapError::SERVER_ERROR.trace(operation,"uid_validity",Some(mailboxIndex)))?;
+		return Ok(Some(uidValidity));
+	}
+
+	/// one page of the mails matching the query in the selected mailboxes
+	pub(super) fn search_get(self, query: ImapSearchQuery) -> Result<ImapSearchResult, ImapError>
+	{
+		let criteria = ImapSearchCriteria::fromQuery(&query)
+			.map_err(|error| error.trace("search","request_validation",None))?;
+		if let Some(after) = &query.after
+		{
+			Self::mailKey_validate(after)
+				.map_err(|_| ImapError::SEARCH_INVALID.trace("search","request_validation",None))?;
+		}
+		if (self.config.extra.is_none() || self.config.selectedBoxNames_get().is_some_and(<[String]>::is_empty))
+		{
+			return Ok(ImapSearchResult::default());
+		}
+		let mut budget = ImapWorkBudget::new();
+		let (mut session, isGmail) = self.connection_get("search")?;
+		let result = self.searchBoxNames_get(&mut session,isGmail)
+			.and_then(|boxNames| Self::searchInSession_get(&mut session,&boxNames,&criteria,query.after.as_ref(),&mut budget));
+		let _ = session.logout();
+		return result;
+	}
+
+	fn searchBoxNames_get(&self, session: &mut Session<Connection>, isGmail: bool) -> Result<Vec<String>, ImapError>
+	{
+		let mut boxNames = match self.config.selectedBoxNames_get()
+		{
+			Some(boxNames) => boxNames.to_vec(),
+			None => self.mailboxes_get(session,isGmail,"search")?
+				.into_iter()
+				.filter(|mailbox| !mailbox.attributes.is_uninteresting())
+				.map(|mailbox| mailbox.name)
+				.collect(),
+		};
+		boxNames.retain(|boxName| !Self::mailbox_isAutomaticallyExcluded(boxName,isGmail) && self.config.isBoxSelected(boxName));
+		boxNames.sort_unstable();
+		boxNames.dedup();
+		boxNames.truncate(ImapLimits::MAILBOX_MAXIMUM);
+		return Ok(boxNames);
+	}
+
+	/// every mailbox is searched for the total, only the mails of the page get their headers fetched.
+	/// The page starts after the UID of the previous one, the mails received meanwhile do not shift it
+	fn searchInSession_get<T: Read + Write>(
+		session: &mut Session<T>,
+		boxNames: &[String],
+		criteria: &str,
+		after: Option<&ImapMailKey>,
+		budget: &mut ImapWorkBudget,
+	) -> Result<ImapSearchResult, ImapError>
+	{
+		if (!criteria.is_ascii())
+		{
+			Self::searchLiterals_require(session)?;
+		}
+		let mut result = ImapSearchResult::default();
+		// the mails vanished before their fetch still count in the page, the cursor stays on the searched UIDs
+		let mut taken = 0;
+		let mut lastTaken = None;
+		let mut hasMore = false;
+		for (mailboxIndex,boxName) in boxNames.iter().enumerate()
+		{
+			budget.active_require()
+				.map_err(|error| error.trace("search","budget",Some(mailboxIndex)))?;
+			let Some(uidValidity) = Self::mailbox_select(session,boxName,"search",mailboxIndex)?
+			else
+			{
+				continue;
+			};
+			let mut uids = session.uid_search(criteria)
+				.map_err(|error| ImapError::fromImapAt(error,"search","mailbox_search",Some(mailboxIndex)))?
+				.into_iter()
+				.collect::<Vec<_>>();
+			if (uids.contains(&0))
+			{
+				return Err(ImapError::SERVER_ERROR.trace("search","search_result",Some(mailboxIndex)));
+			}
+			uids.sort_unstable_by(|left,right| right.cmp(left));
+			result.total += uids.len();
+			let pageUids = match after
+			{
+				Some(after) if *boxName < after.boxName => continue,
+				// a mailbox whose UIDs were renumbered is listed again from its newest mail
+				Some(after) if *boxName == after.boxName && uidValidity == after.uidValidity => uids.iter().filter(|uid| **uid < after.uid).copied().collect(),
+				_ => uids,
+			};
+			let remaining = ImapSearchQuery::PAGE_SIZE - taken;
+			hasMore |= pageUids.len() > remaining;
+			let pageUids = pageUids.into_iter().take(remaining).collect::<Vec<_>>();
+			let Some(lastUid) = pageUids.last().copied() else {continue};
+			taken += pageUids.len();
+			lastTaken = Some(ImapMailKey {boxName: boxName.clone(),uidValidity,uid: lastUid});
+			let pageUids = pageUids.into_iter().collect::<HashSet<_>>();
+			let mut mails = ImapMailParser::headers_get(session,pageUids,budget,"search",mailboxIndex)?;
+			mails.sort_unstable_by(|left,right| right.uid.cmp(&left.uid));
+			result.mails.extend(mails.into_iter().map(|mail| {
+				return (ImapMailKey {boxName: boxName.clone(),uidValidity,uid: mail.uid},mail);
+			}));
+		}
+		result.next = lastTaken.filter(|_| hasMore);
+		return Ok(result);
+	}
+
+	/// non-ASCII terms are non-synchronizing literals: the command is written at once, it cannot wait for a continuation
+	fn searchLiterals_require<T: Read + Write>(session: &mut Session<T>) -> Result<(), ImapError>
+	{
+		let capabilities = session.capabilities()
+			.map_err(|error| ImapError::fromImapAt(error,"search","capabilities",None))?;
+		if (!capabilities.has_str("LITERAL+") && !capabilities.has_str("LITERAL-"))
+		{
+			return Err(ImapError::SEARCH_CHARSET_UNSUPPORTED.trace("search","literal",None));
+		}
+		return Ok(());
 	}
 
 	pub(super) fn mailContent_get(self, mail: ImapMailKey) -> Result<ImapMail, ImapError>
@@ -320,6 +765,65 @@ impl ImapProxy
 			.map_err(|error| ImapError::fromImapAt(error,"content","mailbox_select",None))?;
 		Self::mailKeyValidity_require(mail,mailbox.uid_validity)
 			.map_err(|error| error.trace("content","uid_validity",None))?;
+		let query = format!(
+			"(RFC822.SIZE BODYSTRUCTURE BODY.PEEK[HEADER]<0.{}>)",
+			ImapLimits::HEADER_MAXIMUM_BYTES + 1,
+		);
+		let structures = session.uid_fetch(mail.uid.to_string(), query)
+			.map_err(|error| {
+				let _ = ImapError::fromImapAt(error,"content","structure_fetch",None);
+				return ImapError::MAIL_NOT_FOUND;
+			})?;
+		let structure = structures.iter()
+			.find(|message| message.uid == Some(mail.uid))
+			.ok_or_else(|| ImapError::MAIL_NOT_FOUND.trace("content","mail_missing",None))?;
+		let parts = match structure.bodystructure()
+		{
+			Some(bodyStructure) => ImapMailStructure::parts_get(bodyStructure)
+				.map_err(|error| error.trace("content","structure_parse",None))?,
+			None => None,
+		};
+		let Some(parts) = parts
+		else
+		{
+			return Self::mailFullContentFromSession_get(session,mail,budget);
+		};
+
+		budget.active_require()
+			.map_err(|error| error.trace("content","parts_budget",None))?;
+		// nothing left to fetch when every part is skipped
+		let partsFetch = if (parts.is_empty())
+		{
+			None
+		}
+		else
+		{
+			Some(session.uid_fetch(mail.uid.to_string(), ImapMailStructure::query_get(&parts))
+				.map_err(|error| {
+					let _ = ImapError::fromImapAt(error,"content","parts_fetch",None);
+					return ImapError::MAIL_NOT_FOUND;
+				})?)
+		};
+		let partsMessage = match &partsFetch
+		{
+			Some(partsFetch) => Some(partsFetch.iter()
+				.find(|message| message.uid == Some(mail.uid))
+				.ok_or_else(|| ImapError::MAIL_NOT_FOUND.trace("content","parts_missing",None))?),
+			None => None,
+		};
+		return ImapMailParser::fromParts(mail.uid,structure,&parts,partsMessage)
+			.map_err(|error| error.trace("content","mail_parse",None));
+	}
+
+	/// signed and encrypted mails are checked in the browser with their raw bytes, they are fetched whole
+	fn mailFullContentFromSession_get<T: Read + Write>(
+		session: &mut Session<T>,
+		mail: &ImapMailKey,
+		budget: &ImapWorkBudget,
+	) -> Result<ImapMail, ImapError>
+	{
+		budget.active_require()
+			.map_err(|error| error.trace("content","full_budget",None))?;
 		let query = format!(
 			"(RFC822.SIZE BODY.PEEK[]<0.{}>)",
 			ImapLimits::MAIL_CONTENT_MAXIMUM_BYTES + 1,
@@ -338,45 +842,412 @@ impl ImapProxy
 		return Ok(mail);
 	}
 
-	pub(super) fn mailSeen_set(self, mail: ImapMailKey) -> Result<(), ImapError>
+	/// one part of a mail listed by mailContent_get, fetched in ranges and decoded from its transfer encoding.
+	/// onData receives each decoded piece, a first one as soon as the part is found even when it is empty,
+	/// and returns false once nobody reads them anymore
+	pub(super) fn attachment_stream(self, mail: ImapMailKey, section: String, onData: impl FnMut(Vec<u8>) -> bool) -> Result<(), ImapError>
 	{
 		Self::mailKey_validate(&mail)
-			.map_err(|error| error.trace("seen","mail_key",None))?;
+			.map_err(|error| error.trace("attachment","mail_key",None))?;
+		let section = ImapMailStructure::section_parse(&section)
+			.map_err(|error| error.trace("attachment","section",None))?;
 		let budget = ImapWorkBudget::new();
-		let (mut session, _) = self.connection_get("seen")?;
-		let result = Self::mailSeenInSession_set(&mut session,&mail,&budget);
+		let (mut session, _) = self.connection_get("attachment")?;
+		let result = Self::attachmentFromSession_stream(&mut session,&mail,&section,&budget,onData);
 		let _ = session.logout();
 		return result;
 	}
 
-	fn mailSeenInSession_set<T: Read + Write>(
+	fn attachmentFromSession_stream<T: Read + Write>(
 		session: &mut Session<T>,
 		mail: &ImapMailKey,
+		section: &[u32],
 		budget: &ImapWorkBudget,
+		mut onData: impl FnMut(Vec<u8>) -> bool,
 	) -> Result<(), ImapError>
 	{
 		budget.active_require()
-			.map_err(|error| error.trace("seen","budget",None))?;
+			.map_err(|error| error.trace("attachment","budget",None))?;
 		let mailbox = session.select(&mail.boxName)
-			.map_err(|error| ImapError::fromImapAt(error,"seen","mailbox_select",None))?;
+			.map_err(|error| ImapError::fromImapAt(error,"attachment","mailbox_select",None))?;
 		Self::mailKeyValidity_require(mail,mailbox.uid_validity)
-			.map_err(|error| error.trace("seen","uid_validity",None))?;
-		session.uid_store(mail.uid.to_string(), "+FLAGS.SILENT (\\Seen)")
-			.map_err(|error| ImapError::fromImapAt(error,"seen","mail_store",None))?;
-		return Ok(());
-	}
-
-	fn config_validate(&self) -> Result<(), ImapError>
-	{
-		if (self.config.username.len() > ImapLimits::CREDENTIAL_MAXIMUM_BYTES
-			|| self.config.password.len() > ImapLimits::CREDENTIAL_MAXIMUM_BYTES)
-		{
-			return Err(ImapError::RESOURCE_LIMIT);
-		}
-		if let Some(extra) = &self.config.extra
+			.map_err(|error| error.trace("attachment","uid_validity",None))?;
+		let sectionName = ImapMailStructure::section_format(section);
+		let mut decoder = None;
+		let mut offset = 0;
+		loop
 		{
-			if (extra.boxBlackList.len() > ImapLimits::BLACKLIST_ENTRY_MAXIMUM
-				|| extra.flagBlackList.len() > ImapLimits::BLACKLIST_ENTRY_MAXIMUM
+			budget.active_require()
+				.map_err(|error| error.trace("attachment","range_budget",None))?;
+			// the MIME header comes with the first range, it gives the transfer encoding of the next ones
+			let query = if (offset == 0)
+			{
+				format!(
+					"(BODY.PEEK[{}.MIME]<0.{}> BODY.PEEK[{}]<0.{}>)",
+					sectionName,
+					ImapLimits::HEADER_MAXIMUM_BYTES + 1,
+					sectionName,
+					ImapLimits::ATTACHMENT_RANGE_BYTES,
+				)
+			}
+			else
+			{
+				format!("BODY.PEEK[{}]<{}.{}>",sectionName,offset,ImapLimits::ATTACHMENT_RANGE_BYTES)
+			};
+			let results = session.uid_fetch(mail.uid.to_string(), query)
+				.map_err(|error| {
+					let _ = ImapError::fromImapAt(error,"attachment","part_fetch",None);
+					return ImapError::MAIL_NOT_FOUND;
+				})?;
+			let message = results.iter()
+				.find(|message| message.uid == Some(mail.uid))
+				.ok_or_else(|| ImapError::MAIL_NOT_FOUND.trace("attachment","mail_missing",None))?;
+			let decoder = match &mut decoder
+			{
+				Some(decoder) => decoder,
+				None => decoder.insert(ImapPartDecoder::fromMessage(message,section)
+					.map_err(|error| error.trace("attachment","part_header",None))?),
+			};
+			// a server may answer NIL past the end of the part
+			let range = match message.section(&SectionPath::Part(section.to_vec(), None))
+			{
+				Some(range) => range,
+				None if offset > 0 => &[],
+				None => return Err(ImapError::MAIL_NOT_FOUND.trace("attachment","part_missing",None)),
+			};
+			if (range.len() > ImapLimits::ATTACHMENT_RANGE_BYTES)
+			{
+				return Err(ImapError::RESOURCE_LIMIT.trace("attachment","range_size",None));
+			}
+			let isLast = range.len() < ImapLimits::ATTACHMENT_RANGE_BYTES;
+			let isFirst = offset == 0;
+			offset += range.len();
+			if (offset > ImapLimits::ATTACHMENT_ENCODED_MAXIMUM_BYTES)
+			{
+				return Err(ImapError::RESOURCE_LIMIT.trace("attachment","part_size",None));
+			}
+			let data = decoder.push(range,isLast)
+				.map_err(|error| error.trace("attachment","part_decode",None))?;
+			if ((isFirst || !data.is_empty()) && !onData(data))
+			{
+				return Ok(());
+			}
+			if (isLast)
+			{
+				return Ok(());
+			}
+		}
+	}
+
+	/// the raw mail for its headers and an .eml download, a larger mail is cut
+	pub(super) fn mailSource_get(self, mail: ImapMailKey) -> Result<ImapMailSource, ImapError>
+	{
+		Self::mailKey_validate(&mail)
+			.map_err(|error| error.trace("source","mail_key",None))?;
+		let budget = ImapWorkBudget::new();
+		let (mut session, _) = self.connection_get("source")?;
+		let result = Self::mailSourceFromSession_get(&mut session,&mail,&budget);
+		let _ = session.logout();
+		return result;
+	}
+
+	fn mailSourceFromSession_get<T: Read + Write>(
+		session: &mut Session<T>,
+		mail: &ImapMailKey,
+		budget: &ImapWorkBudget,
+	) -> Result<ImapMailSource, ImapError>
+	{
+		budget.active_require()
+			.map_err(|error| error.trace("source","budget",None))?;
+		let mailbox = session.select(&mail.boxName)
+			.map_err(|error| ImapError::fromImapAt(error,"source","mailbox_select",None))?;
+		Self::mailKeyValidity_require(mail,mailbox.uid_validity)
+			.map_err(|error| error.trace("source","uid_validity",None))?;
+		let query = format!(
+			"(RFC822.SIZE BODY.PEEK[]<0.{}>)",
+			ImapLimits::MAIL_SOURCE_MAXIMUM_BYTES,
+		);
+		let results = session.uid_fetch(mail.uid.to_string(), query)
+			.map_err(|error| {
+				let _ = ImapError::fromImapAt(error,"source","mail_fetch",None);
+				return ImapError::MAIL_NOT_FOUND;
+			})?;
+		let message = results.iter()
+			.find(|message| message.uid == Some(mail.uid))
+			.ok_or_else(|| ImapError::MAIL_NOT_FOUND.trace("source","mail_missing",None))?;
+		let body = message.body()
+			.ok_or_else(|| ImapError::MAIL_NOT_FOUND.trace("source","body_missing",None))?;
+		// a server ignoring the partial range is cut here
+		let data = body[..body.len().min(ImapLimits::MAIL_SOURCE_MAXIMUM_BYTES)].to_vec();
+		let size = message.size.unwrap_or_default().max(body.len() as u32);
+		return Ok(ImapMailSource::new(data, size));
+	}
+
+	pub(super) fn mailSeen_set(self, mail: ImapMailKey) -> Result<(), ImapError>
+	{
+		Self::mailKey_validate(&mail)
+			.map_err(|error| error.trace("seen","mail_key",None))?;
+		let budget = ImapWorkBudget::new();
+		let (mut session, _) = self.connection_get("seen")?;
+		let result = Self::mailSeenInSession_set(&mut session,&mail,&budget);
+		let _ = session.logout();
+		return result;
+	}
+
+	fn mailSeenInSession_set<T: Read + Write>(
+		session: &mut Session<T>,
+		mail: &ImapMailKey,
+		budget: &ImapWorkBudget,
+	) -> Result<(), ImapError>
+	{
+		budget.active_require()
+			.map_err(|error| error.trace("seen","budget",None))?;
+		let mailbox = session.select(&mail.boxName)
+			.map_err(|error| ImapError::fromImapAt(error,"seen","mailbox_select",None))?;
+		Self::mailKeyValidity_require(mail,mailbox.uid_validity)
+			.map_err(|error| error.trace("seen","uid_validity",None))?;
+		session.uid_store(mail.uid.to_string(), "+FLAGS.SILENT (\\Seen)")
+			.map_err(|error| ImapError::fromImapAt(error,"seen","mail_store",None))?;
+		return Ok(());
+	}
+
+	pub(super) fn mailsAction_apply(self, mails: Vec<ImapMailKey>, action: ImapMailAction) -> Result<(), ImapError>
+	{
+		Self::mailsAction_validate(&mails,&action)
+			.map_err(|error| error.trace("action","request",None))?;
+		let budget = ImapWorkBudget::new();
+		let (mut session, _) = self.connection_get("action")?;
+		let result = Self::mailsActionInSession_apply(&mut session,&mails,&action,&budget);
+		let _ = session.logout();
+		return result;
+	}
+
+	/// one SELECT per mailbox, then a single UID command on the compacted UID set
+	fn mailsActionInSession_apply<T: Read + Write>(
+		session: &mut Session<T>,
+		mails: &[ImapMailKey],
+		action: &ImapMailAction,
+		budget: &ImapWorkBudget,
+	) -> Result<(), ImapError>
+	{
+		let targetBoxName = match action
+		{
+			ImapMailAction::TRASH => Some(Self::specialMailbox_get(session,"action",|attributes| attributes.is_trash,&["Trash","Deleted Items"])?),
+			ImapMailAction::ARCHIVE => Some(Self::specialMailbox_get(session,"action",|attributes| attributes.is_archive,&["Archive","Archives"])?),
+			ImapMailAction::MOVE(boxName) => Some(boxName.clone()),
+			ImapMailAction::FLAGGED(_) | ImapMailAction::UNSEEN => None,
+		};
+		let capabilities = match &targetBoxName
+		{
+			Some(_) => Some(session.capabilities()
+				.map_err(|error| ImapError::fromImapAt(error,"action","capabilities",None))?),
+			None => None,
+		};
+		let canMove = capabilities.as_ref().is_some_and(|capabilities| capabilities.has_str("MOVE"));
+		let canExpungeUids = capabilities.as_ref().is_some_and(|capabilities| capabilities.has_str("UIDPLUS"));
+
+		let mut mailboxUids = BTreeMap::<(&str,u32),Vec<Uid>>::new();
+		for mail in mails
+		{
+			mailboxUids.entry((mail.boxName.as_str(),mail.uidValidity)).or_default().push(mail.uid);
+		}
+		mailboxUids.retain(|(boxName,_),_| targetBoxName.as_deref() != Some(*boxName));
+		// nothing is changed unless every mailbox still has the uid validity of the selection, the first one is checked by its SELECT
+		for (mailboxIndex,(boxName,uidValidity)) in mailboxUids.keys().enumerate().skip(1)
+		{
+			let status = session.status(boxName,"(UIDVALIDITY)")
+				.map_err(|error| ImapError::fromImapAt(error,"action","mailbox_status",Some(mailboxIndex)))?;
+			if (status.uid_validity != Some(*uidValidity))
+			{
+				return Err(ImapError::MAIL_NOT_FOUND.trace("action","uid_validity",Some(mailboxIndex)));
+			}
+		}
+		for (mailboxIndex,((boxName,uidValidity),uids)) in mailboxUids.into_iter().enumerate()
+		{
+			budget.active_require()
+				.map_err(|error| error.trace("action","budget",Some(mailboxIndex)))?;
+			let mailbox = session.select(boxName)
+				.map_err(|error| ImapError::fromImapAt(error,"action","mailbox_select",Some(mailboxIndex)))?;
+			if (mailbox.uid_validity != Some(uidValidity))
+			{
+				return Err(ImapError::MAIL_NOT_FOUND.trace("action","uid_validity",Some(mailboxIndex)));
+			}
+			let uidSequence = ImapUidSet::new(uids).sequence_get();
+			let result = match (&targetBoxName,action)
+			{
+				(Some(targetBoxName),_) if canMove => session.uid_mv(&uidSequence,targetBoxName),
+				(Some(targetBoxName),_) => Self::mailsInSession_copyThenDelete(session,&uidSequence,targetBoxName,canExpungeUids),
+				(None,ImapMailAction::FLAGGED(true)) => session.uid_store(&uidSequence,"+FLAGS.SILENT (\\Flagged)").map(|_| ()),
+				(None,ImapMailAction::FLAGGED(false)) => session.uid_store(&uidSequence,"-FLAGS.SILENT (\\Flagged)").map(|_| ()),
+				(None,_) => session.uid_store(&uidSequence,"-FLAGS.SILENT (\\Seen)").map(|_| ()),
+			};
+			result.map_err(|error| ImapError::fromImapAt(error,"action","mail_update",Some(mailboxIndex)))?;
+		}
+		return Ok(());
+	}
+
+	/// servers without MOVE: without UIDPLUS, the deleted originals wait for the next expunge of another client
+	fn mailsInSession_copyThenDelete<T: Read + Write>(
+		session: &mut Session<T>,
+		uidSequence: &str,
+		targetBoxName: &str,
+		canExpungeUids: bool,
+	) -> Result<(), Error>
+	{
+		session.uid_copy(uidSequence,targetBoxName)?;
+		session.uid_store(uidSequence,"+FLAGS.SILENT (\\Deleted)")?;
+		if (canExpungeUids)
+		{
+			session.uid_expunge(uidSequence)?;
+		}
+		return Ok(());
+	}
+
+	fn mailsAction_validate(mails: &[ImapMailKey], action: &ImapMailAction) -> Result<(), ImapError>
+	{
+		if (mails.is_empty() || mails.len() > ImapLimits::MAIL_ACTION_MAXIMUM)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		for mail in mails
+		{
+			Self::mailKey_validate(mail)?;
+		}
+		if let ImapMailAction::MOVE(boxName) = action
+		{
+			Self::boxName_validate(boxName)?;
+		}
+		return Ok(());
+	}
+
+	/// mailbox flagged with a SPECIAL-USE attribute, or carrying one of the usual names on servers without it
+	fn specialMailbox_get<T: Read + Write>(
+		session: &mut Session<T>,
+		operation: &'static str,
+		isSpecial: fn(&Attributs) -> bool,
+		fallbackNames: &[&str],
+	) -> Result<String, ImapError>
+	{
+		let names = session.list(None, Some("*"))
+			.map_err(|error| ImapError::fromImapAt(error,operation,"mailbox_list",None))?;
+		let mut specialBoxName = None;
+		for name in names.iter().take(ImapLimits::MAILBOX_MAXIMUM)
+		{
+			let mut attributes = Attributs::default();
+			name.attributes().iter().for_each(|attribute| attributes.add(attribute));
+			if (attributes.is_no_select || Self::boxName_validate(name.name()).is_err())
+			{
+				continue;
+			}
+			if (isSpecial(&attributes))
+			{
+				return Ok(name.name().to_string());
+			}
+			if (specialBoxName.is_none() && fallbackNames.iter().any(|fallbackName| name.name().eq_ignore_ascii_case(fallbackName)))
+			{
+				specialBoxName = Some(name.name().to_string());
+			}
+		}
+		return specialBoxName
+			.ok_or_else(|| ImapError::MAILBOX_NOT_FOUND.trace(operation,"mailbox_missing",None));
+	}
+
+	/// watches one selected mailbox with IDLE on its own connection and reports each change to notify.
+	/// UNSUPPORTED is reported once without IDLE on the server, WATCHING once the mailbox is selected,
+	/// then a HEARTBEAT every IDLE_ROUND without change. The connection is logged out at the deadline
+	/// or as soon as notify returns false for a closed listener
+	pub(super) fn idle_watch(self, boxName: String, deadline: Instant, mut notify: impl FnMut(ImapIdleEvent) -> bool) -> Result<(), ImapError>
+	{
+		Self::boxName_validate(&boxName)
+			.map_err(|error| error.trace("idle","request_validation",None))?;
+		if (!self.config.isBoxSelected(&boxName))
+		{
+			return Err(ImapError::MAILBOX_NOT_FOUND.trace("idle","request_validation",None));
+		}
+		let (connection, isGreetingRead) = self.destination.connection_get()
+			.map_err(|error| ImapError::fromImapAt(error,"idle","connection",None))?;
+		let mut session = self.session_open(ImapIdleConnection(connection),isGreetingRead,"idle")?;
+		let result = Self::idleInSession_watch(&mut session,&boxName,deadline,&mut notify);
+		let _ = session.logout();
+		return result;
+	}
+
+	fn idleInSession_watch<T: Read + Write + SetReadTimeout>(
+		session: &mut Session<T>,
+		boxName: &str,
+		deadline: Instant,
+		notify: &mut impl FnMut(ImapIdleEvent) -> bool,
+	) -> Result<(), ImapError>
+	{
+		let isIdleSupported = session.capabilities()
+			.map_err(|error| ImapError::fromImapAt(error,"idle","capabilities",None))?
+			.has_str("IDLE");
+		if (!isIdleSupported)
+		{
+			notify(ImapIdleEvent::UNSUPPORTED);
+			return Ok(());
+		}
+		session.select(boxName)
+			.map_err(|error| ImapError::fromImapAt(error,"idle","select",None))?;
+		if (!notify(ImapIdleEvent::WATCHING(boxName.to_string())))
+		{
+			return Ok(());
+		}
+		while (Instant::now() < deadline)
+		{
+			let outcome = session.idle()
+				.timeout(ImapLimits::IDLE_ROUND)
+				.keepalive(false)
+				.wait_while(|response| !matches!(response,
+					UnsolicitedResponse::Exists(_)
+					| UnsolicitedResponse::Expunge(_)
+					| UnsolicitedResponse::Fetch {..}
+					| UnsolicitedResponse::Vanished {..}
+				))
+				.map_err(|error| ImapError::fromImapAt(error,"idle","wait",None))?;
+			let event = match outcome
+			{
+				WaitOutcome::MailboxChanged => ImapIdleEvent::CHANGED(boxName.to_string()),
+				WaitOutcome::TimedOut => ImapIdleEvent::HEARTBEAT,
+			};
+			if (!notify(event))
+			{
+				return Ok(());
+			}
+		}
+		return Ok(());
+	}
+
+	/// stores an already sent message as read in the mailbox flagged \Sent, or named "Sent" without SPECIAL-USE
+	pub(super) fn sentCopy_append(self, message: &[u8]) -> Result<(), ImapError>
+	{
+		let (mut session, _) = self.connection_get("sent_copy")?;
+		let result = Self::sentCopyInSession_append(&mut session,message);
+		let _ = session.logout();
+		return result;
+	}
+
+	fn sentCopyInSession_append<T: Read + Write>(session: &mut Session<T>, message: &[u8]) -> Result<(), ImapError>
+	{
+		let sentBoxName = Self::specialMailbox_get(session,"sent_copy",|attributes| attributes.is_sent,&["Sent"])?;
+		session.append(&sentBoxName, message)
+			.flag(Flag::Seen)
+			.finish()
+			.map_err(|error| ImapError::fromImapAt(error,"sent_copy","append",None))?;
+		return Ok(());
+	}
+
+	fn config_validate(&self) -> Result<(), ImapError>
+	{
+		if (self.config.username.len() > ImapLimits::CREDENTIAL_MAXIMUM_BYTES
+			|| self.config.password.len() > ImapLimits::CREDENTIAL_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		if let Some(extra) = &self.config.extra
+		{
+			if (extra.boxBlackList.len() > ImapLimits::BLACKLIST_ENTRY_MAXIMUM
+				|| extra.flagBlackList.len() > ImapLimits::BLACKLIST_ENTRY_MAXIMUM
 				|| extra.boxAllowList.as_ref().is_some_and(|boxAllowList| {
 					return boxAllowList.len() > ImapLimits::MAILBOX_MAXIMUM
 						|| boxAllowList.iter().any(|entry| {
@@ -426,15 +1297,48 @@ impl ImapProxy
 	fn connection_get(&self, operation: &'static str) -> Result<(Session<Connection>, bool), ImapError>
 	{
 		let isGmail = self.config.isGmail();
-		let connection = self.destination.connection_get()
+		let (connection, isGreetingRead) = self.destination.connection_get()
+			.map_err(|error| ImapError::fromImapAt(error,operation,"connection",None))?;
+		return Ok((self.session_open(connection,isGreetingRead,operation)?, isGmail));
+	}
+
+	fn threadConnection_get(&self, threadRewrite: &ImapThreadRewrite, operation: &'static str) -> Result<(Session<Connection>, bool), ImapError>
+	{
+		let isGmail = self.config.isGmail();
+		let (connection, isGreetingRead) = self.destination.connection_get()
 			.map_err(|error| ImapError::fromImapAt(error,operation,"connection",None))?;
-		let mut client = imap::Client::new(connection);
-		client.read_greeting()
-			.map_err(|error| ImapError::fromImapAt(error,operation,"greeting",None))?;
-		let session = client
-			.login(self.config.username.clone(), self.config.password.clone())
-			.map_err(|error| ImapError::fromImapAt(error.0,operation,"login",None))?;
-		return Ok((session, isGmail));
+		let connection: Connection = Box::new(threadRewrite.stream_wrap(connection));
+		return Ok((self.session_open(connection,isGreetingRead,operation)?, isGmail));
+	}
+
+	fn session_open<T: Read + Write>(&self, stream: T, isGreetingRead: bool, operation: &'static str) -> Result<Session<T>, ImapError>
+	{
+		let mut client = imap::Client::new(stream);
+		if (isGreetingRead)
+		{
+			client.greeting_read = true;
+		}
+		else
+		{
+			client.read_greeting()
+				.map_err(|error| ImapError::fromImapAt(error,operation,"greeting",None))?;
+		}
+		return Self::client_authenticate(client,&self.config,self.oauthLogin.as_ref(),operation);
+	}
+
+	fn client_authenticate<T: Read + Write>(
+		client: imap::Client<T>,
+		config: &imap_connector,
+		oauthLogin: Option<&ImapOAuthLogin>,
+		operation: &'static str,
+	) -> Result<Session<T>, ImapError>
+	{
+		let session = match oauthLogin
+		{
+			Some(oauthLogin) => client.authenticate(oauthLogin.mechanism.name_get(),&oauthLogin.authenticator_get(config)),
+			None => client.login(config.username.clone(), config.password.clone()),
+		};
+		return session.map_err(|error| ImapError::fromImapAt(error.0,operation,"login",None));
 	}
 
 	fn mailboxes_get(
@@ -500,66 +1404,380 @@ impl ImapProxy
 	}
 }
 
-struct ImapMailParser;
+/// IMAP SEARCH keys of a structured query. ASCII terms are sent as quoted strings, the other ones
+/// as non-synchronizing literals (RFC 7888) since a quoted string only carries 7-bit characters
+struct ImapSearchCriteria;
 
-impl ImapMailParser
+impl ImapSearchCriteria
 {
-	fn headers_get<T: Read + Write>(
-		session: &mut Session<T>,
-		results: HashSet<Uid>,
-		budget: &mut ImapWorkBudget,
-		mailboxIndex: usize,
-	) -> Result<Vec<ImapMail>, ImapError>
+	const MONTHS: [&'static str; 12] = ["Jan","Feb","Mar","Apr","May","Jun","Jul","Aug","Sep","Oct","Nov","Dec"];
+
+	fn fromQuery(query: &ImapSearchQuery) -> Result<String, ImapError>
 	{
-		let selectedUids = ImapUidSet::new(results.into_iter().collect());
-		if (selectedUids.is_empty())
+		if (!query.hasCriterion())
 		{
-			return Ok(Vec::new());
+			return Err(ImapError::SEARCH_INVALID);
 		}
-		budget.active_require()
-			.map_err(|error| error.trace("sync","header_budget",Some(mailboxIndex)))?;
-		let query = format!(
-			"(INTERNALDATE BODY.PEEK[HEADER.FIELDS (SUBJECT FROM TO)]<0.{}>)",
-			ImapLimits::HEADER_MAXIMUM_BYTES + 1,
-		);
-		let fetches = session.uid_fetch(selectedUids.sequence_get(),query)
-			.map_err(|error| ImapError::fromImapAt(error,"sync","header_fetch",Some(mailboxIndex)))?;
-		let mut mails = Vec::new();
-		for message in fetches.iter()
+		let mut keys = vec!["UNDELETED".to_string()];
+		for (key,term) in [("TEXT",&query.text),("FROM",&query.from),("TO",&query.to),("SUBJECT",&query.subject),("BODY",&query.body)]
 		{
-			budget.active_require()
-				.map_err(|error| error.trace("sync","header_parse_budget",Some(mailboxIndex)))?;
-			let Some(uid) = message.uid.filter(|uid| selectedUids.contains(*uid))
-			else
-			{
-				continue;
-			};
-			match Self::fromMessage(uid,message,None)
+			let term = term.trim();
+			if (!term.is_empty())
 			{
-				Ok(mail) => mails.push(mail),
-				Err(ImapError::RESOURCE_LIMIT) => {},
-				Err(error) => return Err(error.trace("sync","header_parse",Some(mailboxIndex))),
+				keys.push(format!("{} {}",key,Self::term_get(term)?));
 			}
 		}
-		return Ok(mails);
+		let since = query.since.as_deref().map(Self::date_get).transpose()?;
+		let until = query.until.as_deref().map(Self::date_get).transpose()?;
+		if let (Some(since),Some(until)) = (since,until) && since > until
+		{
+			return Err(ImapError::SEARCH_INVALID);
+		}
+		if let Some(since) = since
+		{
+			keys.push(format!("SINCE {}",Self::date_format(since)));
+		}
+		if let Some(until) = until
+		{
+			// BEFORE excludes its day
+			let before = until.next_day().ok_or(ImapError::SEARCH_INVALID)?;
+			keys.push(format!("BEFORE {}",Self::date_format(before)));
+		}
+		if (query.unseen)
+		{
+			keys.push("UNSEEN".to_string());
+		}
+		if (query.hasAttachment)
+		{
+			// IMAP has no attachment key, mixed multiparts are the messages carrying files
+			keys.push("HEADER Content-Type \"multipart/mixed\"".to_string());
+		}
+		let criteria = keys.join(" ");
+		if (!criteria.is_ascii())
+		{
+			return Ok(format!("CHARSET UTF-8 {}",criteria));
+		}
+		return Ok(criteria);
 	}
 
-	fn fromFetch(
-		uid: u32,
-		messages: Fetches,
-		bodyMaximumBytes: Option<usize>,
-	) -> Result<Option<ImapMail>, ImapError>
+	fn term_get(term: &str) -> Result<String, ImapError>
 	{
-		let message = messages.iter()
-			.find(|message| message.uid == Some(uid));
-		let Some(message) = message
-		else
+		if (term.len() > ImapLimits::SEARCH_TERM_MAXIMUM_BYTES || term.chars().any(char::is_control))
 		{
-			return Ok(None);
-		};
-		return Self::fromMessage(uid,message,bodyMaximumBytes).map(Some);
-	}
-
+			return Err(ImapError::SEARCH_INVALID);
+		}
+		if (!term.is_ascii())
+		{
+			// the bound of LITERAL- is 4096 bytes, far above SEARCH_TERM_MAXIMUM_BYTES
+			return Ok(format!("{{{}+}}\r\n{}",term.len(),term));
+		}
+		return Ok(format!("\"{}\"",term.replace('\\',"\\\\").replace('"',"\\\"")));
+	}
+
+	fn date_get(value: &str) -> Result<Date, ImapError>
+	{
+		let mut parts = value.splitn(3,'-').map(str::parse::<u16>);
+		let (Some(Ok(year)),Some(Ok(month)),Some(Ok(day))) = (parts.next(),parts.next(),parts.next())
+		else
+		{
+			return Err(ImapError::SEARCH_INVALID);
+		};
+		let month = u8::try_from(month).ok().and_then(|month| Month::try_from(month).ok()).ok_or(ImapError::SEARCH_INVALID)?;
+		let day = u8::try_from(day).map_err(|_| ImapError::SEARCH_INVALID)?;
+		return Date::from_calendar_date(i32::from(year),month,day).map_err(|_| ImapError::SEARCH_INVALID);
+	}
+
+	fn date_format(date: Date) -> String
+	{
+		return format!("{}-{}-{}",date.day(),Self::MONTHS[usize::from(u8::from(date.month())) - 1],date.year());
+	}
+}
+
+/// leaf of the BODYSTRUCTURE of a mail, fetched alone by its section path
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct ImapMailPart
+{
+	section: Vec<u32>,
+	/// the body is fetched with the content, otherwise only its MIME header to list it as an attachment
+	isContent: bool,
+}
+
+/// plans the parts of a mail fetched for its content, attachments are left on the server
+struct ImapMailStructure;
+
+impl ImapMailStructure
+{
+	/// leaves in the order ImapMailParser::bodyContent_apply visits them, None when the whole mail is needed:
+	/// a single part, or signed and encrypted content checked in the browser with the raw bytes
+	fn parts_get(root: &BodyStructure) -> Result<Option<Vec<ImapMailPart>>, ImapError>
+	{
+		if (!matches!(root, BodyStructure::Multipart {..}))
+		{
+			return Ok(None);
+		}
+		let mut parts = Vec::new();
+		let mut contentBytes = 0usize;
+		let mut inlineBytes = 0usize;
+		let mut pending = vec![(root, Vec::new())];
+		while let Some((body, section)) = pending.pop()
+		{
+			if (section.len() > ImapLimits::MAIL_PART_DEPTH_MAXIMUM || parts.len() >= ImapLimits::MAIL_PART_MAXIMUM)
+			{
+				return Err(ImapError::RESOURCE_LIMIT);
+			}
+			let (common, other) = match body
+			{
+				BodyStructure::Multipart {common, bodies, ..} =>
+				{
+					match common.ty.subtype.to_ascii_lowercase().as_str()
+					{
+						"alternative" | "mixed" | "related" => pending.extend(bodies.iter().enumerate().map(|(index, child)| {
+							let mut childSection = section.clone();
+							childSection.push(index as u32 + 1);
+							return (child, childSection);
+						})),
+						"signed" | "encrypted" => return Ok(None),
+						_ => {},
+					}
+					continue;
+				},
+				BodyStructure::Basic {common, other, ..}
+				| BodyStructure::Text {common, other, ..}
+				| BodyStructure::Message {common, other, ..} => (common, other),
+			};
+			let mime = format!("{}/{}", common.ty.ty, common.ty.subtype).to_ascii_lowercase();
+			let isAttachment = common.disposition.as_ref().is_some_and(|disposition| disposition.ty.eq_ignore_ascii_case("attachment"));
+			let octets = other.octets as usize;
+			let isContent = match mime.as_str()
+			{
+				"application/pkcs7-mime" | "application/x-pkcs7-mime" => return Ok(None),
+				"text/plain" | "text/html" if !isAttachment =>
+				{
+					contentBytes = contentBytes.saturating_add(octets);
+					if (contentBytes > ImapLimits::MAIL_CONTENT_MAXIMUM_BYTES)
+					{
+						return Err(ImapError::RESOURCE_LIMIT);
+					}
+					true
+				},
+				// an invitation is read from the calendar, a larger calendar is only listed
+				"text/calendar" => octets <= ImapLimits::INVITATION_MAXIMUM_BYTES * 2,
+				_ if isAttachment => false,
+				// inline images are only shown through a cid: address of the html, as far as their budget allows
+				_ =>
+				{
+					if (other.id.is_none()
+						|| ImapMailParser::inlineMime_get(&mime).is_none()
+						|| inlineBytes.saturating_add(octets) > ImapLimits::MAIL_RENDERED_INLINE_MAXIMUM_BYTES)
+					{
+						continue;
+					}
+					inlineBytes = inlineBytes.saturating_add(octets);
+					true
+				},
+			};
+			parts.push(ImapMailPart {section, isContent});
+		}
+		return Ok(Some(parts));
+	}
+
+	fn query_get(parts: &[ImapMailPart]) -> String
+	{
+		let items = parts.iter().map(|part| {
+			let section = Self::section_format(&part.section);
+			let mut item = format!("BODY.PEEK[{}.MIME]<0.{}>", section, ImapLimits::HEADER_MAXIMUM_BYTES + 1);
+			if (part.isContent)
+			{
+				item.push_str(&format!(" BODY.PEEK[{}]<0.{}>", section, ImapLimits::MAIL_CONTENT_MAXIMUM_BYTES + 1));
+			}
+			return item;
+		}).collect::<Vec<_>>();
+		return format!("({})", items.join(" "));
+	}
+
+	fn section_format(section: &[u32]) -> String
+	{
+		return section.iter().map(u32::to_string).collect::<Vec<_>>().join(".");
+	}
+
+	/// dot separated part numbers, as written by section_format
+	fn section_parse(section: &str) -> Result<Vec<u32>, ImapError>
+	{
+		let numbers = section.split('.')
+			.map(|number| {
+				if (number.is_empty() || number.len() > 9 || !number.bytes().all(|byte| byte.is_ascii_digit()))
+				{
+					return None;
+				}
+				return number.parse::<u32>().ok().filter(|number| *number > 0);
+			})
+			.collect::<Option<Vec<_>>>()
+			.ok_or(ImapError::MAIL_NOT_FOUND)?;
+		if (numbers.len() > ImapLimits::MAIL_PART_DEPTH_MAXIMUM)
+		{
+			return Err(ImapError::MAIL_NOT_FOUND);
+		}
+		return Ok(numbers);
+	}
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ImapPartEncoding
+{
+	BASE64,
+	QUOTED_PRINTABLE,
+	// 7bit, 8bit, binary and unknown encodings are sent as they are
+	RAW,
+}
+
+/// transfer decoding of a part fetched in ranges: each range is decoded up to the last boundary of its encoding,
+/// the rest waits for the next range
+struct ImapPartDecoder
+{
+	mimeHeader: Vec<u8>,
+	encoding: ImapPartEncoding,
+	pending: Vec<u8>,
+	decodedBytes: usize,
+}
+
+impl ImapPartDecoder
+{
+	fn fromMessage(message: &Fetch, section: &[u32]) -> Result<Self, ImapError>
+	{
+		let mimeHeader = message.section(&SectionPath::Part(section.to_vec(), Some(MessageSection::Mime)))
+			.ok_or(ImapError::MAIL_NOT_FOUND)?;
+		if (mimeHeader.len() > ImapLimits::HEADER_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		let (headers,_) = mailparse::parse_headers(mimeHeader)
+			.map_err(|_| ImapError::MAIL_NOT_FOUND)?;
+		let encoding = match headers.get_first_value("Content-Transfer-Encoding").map(|encoding| encoding.trim().to_ascii_lowercase()).as_deref()
+		{
+			Some("base64") => ImapPartEncoding::BASE64,
+			Some("quoted-printable") => ImapPartEncoding::QUOTED_PRINTABLE,
+			_ => ImapPartEncoding::RAW,
+		};
+		return Ok(Self {
+			mimeHeader: mimeHeader.to_vec(),
+			encoding,
+			pending: Vec::new(),
+			decodedBytes: 0,
+		});
+	}
+
+	/// the data decoded with this range, the last range decodes everything left
+	fn push(&mut self, range: &[u8], isLast: bool) -> Result<Vec<u8>, ImapError>
+	{
+		self.pending.extend_from_slice(range);
+		let end = if (isLast) {self.pending.len()} else {self.boundary_get()};
+		let encoded = self.pending.drain(..end).collect::<Vec<_>>();
+		let data = if (self.encoding == ImapPartEncoding::RAW || encoded.is_empty())
+		{
+			encoded
+		}
+		else
+		{
+			parse_mail(&ImapMailParser::partRaw_get(&self.mimeHeader, &encoded))
+				.and_then(|parsed| parsed.get_body_raw())
+				.map_err(|_| ImapError::MAIL_NOT_FOUND)?
+		};
+		self.decodedBytes = self.decodedBytes.saturating_add(data.len());
+		ImapMailParser::attachmentSize_require(0,self.decodedBytes)?;
+		return Ok(data);
+	}
+
+	/// end of the pending bytes that decode on their own
+	fn boundary_get(&self) -> usize
+	{
+		return match self.encoding
+		{
+			// four characters make three bytes, the line breaks between them are skipped
+			ImapPartEncoding::BASE64 => {
+				let mut characters = 0;
+				let mut boundary = 0;
+				for (index,byte) in self.pending.iter().enumerate()
+				{
+					if (!byte.is_ascii_whitespace())
+					{
+						characters += 1;
+						if (characters % 4 == 0)
+						{
+							boundary = index + 1;
+						}
+					}
+				}
+				boundary
+			},
+			// an escape or a soft line break never goes over a line break
+			ImapPartEncoding::QUOTED_PRINTABLE => self.pending.iter().rposition(|byte| *byte == b'\n').map_or(0,|index| index + 1),
+			ImapPartEncoding::RAW => self.pending.len(),
+		};
+	}
+}
+
+pub(super) struct ImapMailParser;
+
+impl ImapMailParser
+{
+	fn headers_get<T: Read + Write>(
+		session: &mut Session<T>,
+		results: HashSet<Uid>,
+		budget: &mut ImapWorkBudget,
+		operation: &'static str,
+		mailboxIndex: usize,
+	) -> Result<Vec<ImapMail>, ImapError>
+	{
+		let selectedUids = ImapUidSet::new(results.into_iter().collect());
+		if (selectedUids.is_empty())
+		{
+			return Ok(Vec::new());
+		}
+		budget.active_require()
+			.map_err(|error| error.trace(operation,"header_budget",Some(mailboxIndex)))?;
+		let query = format!(
+			"(INTERNALDATE FLAGS RFC822.SIZE BODY.PEEK[HEADER.FIELDS (SUBJECT FROM TO MESSAGE-ID IN-REPLY-TO REFERENCES {})]<0.{}>)",
+			ImapMail::FILTER_HEADERS.join(" ").to_ascii_uppercase(),
+			ImapLimits::HEADER_MAXIMUM_BYTES + 1,
+		);
+		let fetches = session.uid_fetch(selectedUids.sequence_get(),query)
+			.map_err(|error| ImapError::fromImapAt(error,operation,"header_fetch",Some(mailboxIndex)))?;
+		let mut mails = Vec::new();
+		for message in fetches.iter()
+		{
+			budget.active_require()
+				.map_err(|error| error.trace(operation,"header_parse_budget",Some(mailboxIndex)))?;
+			let Some(uid) = message.uid.filter(|uid| selectedUids.contains(*uid))
+			else
+			{
+				continue;
+			};
+			match Self::fromMessage(uid,message,None)
+			{
+				Ok(mail) => mails.push(mail),
+				Err(ImapError::RESOURCE_LIMIT) => {},
+				Err(error) => return Err(error.trace(operation,"header_parse",Some(mailboxIndex))),
+			}
+		}
+		return Ok(mails);
+	}
+
+	fn fromFetch(
+		uid: u32,
+		messages: Fetches,
+		bodyMaximumBytes: Option<usize>,
+	) -> Result<Option<ImapMail>, ImapError>
+	{
+		let message = messages.iter()
+			.find(|message| message.uid == Some(uid));
+		let Some(message) = message
+		else
+		{
+			return Ok(None);
+		};
+		return Self::fromMessage(uid,message,bodyMaximumBytes).map(Some);
+	}
+
 	fn fromMessage(
 		uid: u32,
 		message: &Fetch,
@@ -569,6 +1787,8 @@ impl ImapMailParser
 		let mut mailData = ImapMail {
 			uid,
 			subject: None,
+			flagged: message.flags().contains(&Flag::Flagged),
+			seen: message.flags().contains(&Flag::Seen),
 			..Default::default()
 		};
 		if let Some(header) = message.header()
@@ -582,8 +1802,15 @@ impl ImapMailParser
 				mailData.subject = parsed.headers.get_first_value("Subject");
 				mailData.from = parsed.headers.get_first_value("From").unwrap_or_default();
 				mailData.to = parsed.headers.get_first_value("To").unwrap_or_default();
+				mailData.messageId = parsed.headers.get_first_value("Message-ID");
+				mailData.inReplyTo = parsed.headers.get_first_value("In-Reply-To");
+				mailData.references = parsed.headers.get_first_value("References");
+				mailData.headers = ImapMail::FILTER_HEADERS.iter()
+					.filter_map(|name| parsed.headers.get_first_value(name).map(|value| (name.to_string(),value)))
+					.collect();
 			}
 		}
+		mailData.size = message.size.unwrap_or_default();
 		if let Some(maximumBytes) = bodyMaximumBytes
 		{
 			if (message.size.is_some_and(|size| size as usize > maximumBytes))
@@ -599,9 +1826,7 @@ impl ImapMailParser
 				if (!body.is_empty())
 				{
 					let parsed = parse_mail(body).map_err(|_| ImapError::MAIL_NOT_FOUND)?;
-					mailData.subject = parsed.headers.get_first_value("Subject").or(mailData.subject);
-					mailData.from = parsed.headers.get_first_value("From").unwrap_or(mailData.from);
-					mailData.to = parsed.headers.get_first_value("To").unwrap_or(mailData.to);
+					Self::contentHeaders_apply(&mut mailData, &parsed);
 					Self::bodyContent_apply(&mut mailData, &parsed)?;
 					Self::content_finalize(&mut mailData)?;
 				}
@@ -614,6 +1839,100 @@ impl ImapMailParser
 		return Ok(mailData);
 	}
 
+	/// content of the parts planned by ImapMailStructure, the attachments only get their metadata and section
+	fn fromParts(
+		uid: u32,
+		message: &Fetch,
+		parts: &[ImapMailPart],
+		partsMessage: Option<&Fetch>,
+	) -> Result<ImapMail, ImapError>
+	{
+		let mut mailData = ImapMail {
+			uid,
+			size: message.size.unwrap_or_default(),
+			..Default::default()
+		};
+		let header = message.header().ok_or(ImapError::MAIL_NOT_FOUND)?;
+		if (header.len() > ImapLimits::HEADER_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		let parsed = parse_mail(header).map_err(|_| ImapError::MAIL_NOT_FOUND)?;
+		Self::contentHeaders_apply(&mut mailData, &parsed);
+
+		let mut contentBytes = 0usize;
+		for part in parts
+		{
+			let Some(partsMessage) = partsMessage
+			else
+			{
+				break;
+			};
+			let Some(mimeHeader) = partsMessage.section(&SectionPath::Part(part.section.clone(), Some(MessageSection::Mime)))
+			else
+			{
+				continue;
+			};
+			if (mimeHeader.len() > ImapLimits::HEADER_MAXIMUM_BYTES)
+			{
+				return Err(ImapError::RESOURCE_LIMIT);
+			}
+			if (!part.isContent)
+			{
+				let parsed = parse_mail(mimeHeader).map_err(|_| ImapError::MAIL_NOT_FOUND)?;
+				Self::attachmentMetadata_apply(&mut mailData, &parsed, ImapMailStructure::section_format(&part.section))?;
+				continue;
+			}
+			let body = partsMessage.section(&SectionPath::Part(part.section.clone(), None)).unwrap_or_default();
+			contentBytes = contentBytes.saturating_add(body.len());
+			if (contentBytes > ImapLimits::MAIL_CONTENT_MAXIMUM_BYTES)
+			{
+				return Err(ImapError::RESOURCE_LIMIT);
+			}
+			let raw = Self::partRaw_get(mimeHeader, body);
+			let parsed = parse_mail(&raw).map_err(|_| ImapError::MAIL_NOT_FOUND)?;
+			Self::bodyContent_apply(&mut mailData, &parsed)?;
+		}
+		Self::content_finalize(&mut mailData)?;
+		if let Some(date) = message.internal_date()
+		{
+			mailData.date = date.timestamp();
+		}
+		return Ok(mailData);
+	}
+
+	/// a part as a standalone entity, servers do not all end the MIME header with its empty line
+	fn partRaw_get(mimeHeader: &[u8], body: &[u8]) -> Vec<u8>
+	{
+		let mut raw = Vec::with_capacity(mimeHeader.len() + body.len() + 4);
+		raw.extend_from_slice(mimeHeader);
+		if (!raw.ends_with(b"\n\n") && !raw.ends_with(b"\r\n\r\n"))
+		{
+			if (!raw.is_empty() && !raw.ends_with(b"\n"))
+			{
+				raw.extend_from_slice(b"\r\n");
+			}
+			raw.extend_from_slice(b"\r\n");
+		}
+		raw.extend_from_slice(body);
+		return raw;
+	}
+
+	/// headers read with the content, they replace the ones given by the mailbox list
+	fn contentHeaders_apply(mailData: &mut ImapMail, parsed: &ParsedMail)
+	{
+		mailData.subject = parsed.headers.get_first_value("Subject").or(mailData.subject.take());
+		mailData.from = parsed.headers.get_first_value("From").unwrap_or(std::mem::take(&mut mailData.from));
+		mailData.to = parsed.headers.get_first_value("To").unwrap_or(std::mem::take(&mut mailData.to));
+		mailData.cc = parsed.headers.get_first_value("Cc").unwrap_or_default();
+		mailData.messageId = parsed.headers.get_first_value("Message-ID").or(mailData.messageId.take());
+		mailData.inReplyTo = parsed.headers.get_first_value("In-Reply-To").or(mailData.inReplyTo.take());
+		mailData.references = parsed.headers.get_first_value("References").or(mailData.references.take());
+		mailData.headers = ImapMail::FILTER_HEADERS.iter()
+			.filter_map(|name| parsed.headers.get_first_value(name).map(|value| (name.to_string(),value)))
+			.collect();
+	}
+
 	fn bodyContent_apply(mailData: &mut ImapMail, root: &ParsedMail) -> Result<(), ImapError>
 	{
 		let mut pending = vec![root];
@@ -621,6 +1940,7 @@ impl ImapMailParser
 		{
 			match body.ctype.mimetype.as_str()
 			{
+				"text/plain" | "text/html" if body.get_content_disposition().disposition == DispositionType::Attachment => Self::attachment_apply(mailData, body)?,
 				"text/plain" =>
 				{
 					if mailData.content.is_none() && let Ok(text) = body.get_body()
@@ -639,6 +1959,23 @@ impl ImapMailParser
 				{
 					pending.extend(body.subparts.iter());
 				},
+				"multipart/signed" =>
+				{
+					match Self::signed_apply(mailData, body)
+					{
+						Some(signedPart) => pending.push(signedPart),
+						None => pending.extend(body.subparts.iter()),
+					}
+				},
+				"multipart/encrypted" =>
+				{
+					if (!Self::encrypted_apply(mailData, body))
+					{
+						pending.extend(body.subparts.iter());
+					}
+				},
+				"application/pkcs7-mime" | "application/x-pkcs7-mime" => Self::smimeMime_apply(mailData, body)?,
+				"text/calendar" => Self::invitation_apply(mailData, body)?,
 				_ => Self::attachment_apply(mailData, body)?,
 			}
 		}
@@ -686,7 +2023,7 @@ impl ImapMailParser
 			.add_generic_attributes(&["dir","style"])
 			.filter_style_properties(styleProperties)
 			.add_url_schemes(&["cid","data"])
-			.url_relative(UrlRelative::Deny)
+			.url_relative(UrlRelative::Custom(Box::new(Self::relativeUrl_filter)))
 			.set_tag_attribute_value("a","target","_blank")
 			.attribute_filter(move |element,attribute,value| {
 				if (element == "img" && attribute == "src")
@@ -706,6 +2043,8 @@ impl ImapMailParser
 					{
 						return Some(Cow::Borrowed(value));
 					}
+					// remote images go through the server, the sender never sees the address nor the reading time
+					return MailImageProxy::url_sign(value).map(Cow::Owned);
 				}
 				if (Self::cidName_get(value).is_some())
 				{
@@ -760,7 +2099,14 @@ impl ImapMailParser
 		return result;
 	}
 
-	fn inlineMime_get(contentType: &str) -> Option<&'static str>
+	/// relative addresses are refused, except the signed image proxy addresses written by the attribute filter
+	fn relativeUrl_filter(url: &str) -> Option<Cow<'_, str>>
+	{
+		let isProxy = url.strip_prefix(MAIL_IMAGE_PROXY_PATH).is_some_and(|query| query.starts_with('?'));
+		return isProxy.then_some(Cow::Borrowed(url));
+	}
+
+	pub(super) fn inlineMime_get(contentType: &str) -> Option<&'static str>
 	{
 		return match contentType.trim().to_ascii_lowercase().as_str()
 		{
@@ -802,6 +2148,82 @@ impl ImapMailParser
 		});
 	}
 
+	/// keeps the signed entity and its detached signature for the browser, then shows the entity as usual.
+	/// Nested signatures stay displayed without their check
+	fn signed_apply<'a>(mailData: &mut ImapMail, body: &'a ParsedMail<'a>) -> Option<&'a ParsedMail<'a>>
+	{
+		let kind = match body.ctype.params.get("protocol")?.to_ascii_lowercase().as_str()
+		{
+			"application/pgp-signature" => ImapSecuredKind::OPENPGP_SIGNED,
+			"application/pkcs7-signature" | "application/x-pkcs7-signature" => ImapSecuredKind::SMIME_SIGNED,
+			_ => return None,
+		};
+		let [signedPart, signaturePart] = body.subparts.as_slice() else {return None};
+		if (mailData.secured.is_some())
+		{
+			return Some(signedPart);
+		}
+		let payload = signaturePart.get_body_raw().ok()?;
+		mailData.secured = Some(ImapSecuredPart {kind, signed: signedPart.raw_bytes.to_vec(), payload});
+		return Some(signedPart);
+	}
+
+	/// the second part of an OpenPGP/MIME message holds the encrypted entity, the first one only its version
+	fn encrypted_apply(mailData: &mut ImapMail, body: &ParsedMail) -> bool
+	{
+		if (mailData.secured.is_some()
+			|| !body.ctype.params.get("protocol").is_some_and(|protocol| protocol.eq_ignore_ascii_case("application/pgp-encrypted")))
+		{
+			return false;
+		}
+		let [_, encryptedPart] = body.subparts.as_slice() else {return false};
+		let Ok(payload) = encryptedPart.get_body_raw() else {return false};
+		mailData.secured = Some(ImapSecuredPart {kind: ImapSecuredKind::OPENPGP_ENCRYPTED, signed: Vec::new(), payload});
+		return true;
+	}
+
+	/// enveloped or opaque signed S/MIME content, a certificate list stays an attachment
+	fn smimeMime_apply(mailData: &mut ImapMail, body: &ParsedMail) -> Result<(), ImapError>
+	{
+		let isMessage = body.ctype.params.get("smime-type")
+			.is_none_or(|smimeType| smimeType.eq_ignore_ascii_case("enveloped-data") || smimeType.eq_ignore_ascii_case("signed-data"));
+		if mailData.secured.is_none() && isMessage && let Ok(payload) = body.get_body_raw()
+		{
+			mailData.secured = Some(ImapSecuredPart {kind: ImapSecuredKind::SMIME_MIME, signed: Vec::new(), payload});
+			return Ok(());
+		}
+		return Self::attachment_apply(mailData, body);
+	}
+
+	/// the first iTIP REQUEST or CANCEL becomes the invitation of the mail, the other calendars stay attachments.
+	/// Clients often send the same invitation twice, inline and as an .ics attachment
+	fn invitation_apply(mailData: &mut ImapMail, body: &ParsedMail) -> Result<(), ImapError>
+	{
+		if (mailData.invitation.is_none())
+		{
+			if let Ok(calendar) = body.get_body() && calendar.len() <= ImapLimits::INVITATION_MAXIMUM_BYTES
+			{
+				let method = body.ctype.params.get("method").map(String::as_str)
+					.or_else(|| Self::calendarMethod_get(&calendar))
+					.and_then(ImapInvitationMethod::fromValue);
+				if let Some(method) = method
+				{
+					mailData.invitation = Some(ImapInvitation {method, calendar});
+					return Ok(());
+				}
+			}
+		}
+		return Self::attachment_apply(mailData, body);
+	}
+
+	/// METHOD property of the calendar, when the Content-Type does not repeat it
+	fn calendarMethod_get(calendar: &str) -> Option<&str>
+	{
+		return calendar.lines()
+			.find(|line| line.get(..7).is_some_and(|name| name.eq_ignore_ascii_case("METHOD:")))
+			.map(|line| &line[7..]);
+	}
+
 	fn attachment_apply(mailData: &mut ImapMail, body: &ParsedMail) -> Result<(), ImapError>
 	{
 		let disposition = body.get_content_disposition();
@@ -811,21 +2233,32 @@ impl ImapMailParser
 		{
 			return Ok(());
 		}
-		if (mailData.parts.len().saturating_add(mailData.attachement.len()) >= ImapLimits::ATTACHMENT_MAXIMUM)
-		{
-			return Err(ImapError::RESOURCE_LIMIT);
-		}
 		let Ok(data) = body.get_body_raw()
 		else
 		{
 			return Ok(());
 		};
-		let filename = disposition.params.get("filename").cloned();
-		let contentType = body.ctype.mimetype.clone();
-		let contentId = body.headers.get_first_value("Content-ID")
-			.map(|contentId| contentId.trim().trim_start_matches('<').trim_end_matches('>').to_string());
-		if (filename.as_ref().is_some_and(|filename| {
-				return filename.len() > ImapLimits::ATTACHMENT_FILENAME_MAXIMUM_BYTES
+		return Self::attachment_push(mailData, body, isInline, data, None);
+	}
+
+	/// attachment left on the server, its MIME header gives the metadata shown in the mail
+	fn attachmentMetadata_apply(mailData: &mut ImapMail, header: &ParsedMail, section: String) -> Result<(), ImapError>
+	{
+		return Self::attachment_push(mailData, header, false, Vec::new(), Some(section));
+	}
+
+	fn attachment_push(mailData: &mut ImapMail, body: &ParsedMail, isInline: bool, data: Vec<u8>, section: Option<String>) -> Result<(), ImapError>
+	{
+		if (mailData.parts.len().saturating_add(mailData.attachement.len()) >= ImapLimits::ATTACHMENT_MAXIMUM)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		let filename = body.get_content_disposition().params.get("filename").cloned();
+		let contentType = body.ctype.mimetype.clone();
+		let contentId = body.headers.get_first_value("Content-ID")
+			.map(|contentId| contentId.trim().trim_start_matches('<').trim_end_matches('>').to_string());
+		if (filename.as_ref().is_some_and(|filename| {
+				return filename.len() > ImapLimits::ATTACHMENT_FILENAME_MAXIMUM_BYTES
 					|| filename.chars().any(char::is_control);
 			})
 			|| contentType.is_empty()
@@ -847,6 +2280,7 @@ impl ImapMailParser
 			content_type: contentType,
 			content_id: contentId,
 			data,
+			section,
 		};
 		if (isInline)
 		{
@@ -889,6 +2323,7 @@ mod tests
 	use std::sync::{Arc,Mutex};
 
 	use super::*;
+	use crate::api::proxys::imap_oauth::ImapSaslMechanism;
 
 	#[derive(Debug)]
 	struct RecordingStream
@@ -908,9 +2343,13 @@ mod tests
 
 	impl Read for RecordingStream
 	{
+		// one line per read like a server answering each command, nothing of a later answer is buffered ahead
 		fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize>
 		{
-			return self.responses.read(buffer);
+			let remaining = &self.responses.get_ref()[self.responses.position() as usize..];
+			let lineLength = remaining.iter().position(|&byte| byte == b'\n').map_or(remaining.len(),|index| index + 1);
+			let length = lineLength.min(buffer.len());
+			return self.responses.read(&mut buffer[..length]);
 		}
 	}
 
@@ -928,20 +2367,29 @@ mod tests
 		}
 	}
 
+	impl SetReadTimeout for RecordingStream
+	{
+		fn set_read_timeout(&mut self, _timeout: Option<Duration>) -> imap::Result<()>
+		{
+			return Ok(());
+		}
+	}
+
 	struct MailboxSyncFixture;
 
 	impl MailboxSyncFixture
 	{
-		fn fetchResponse_get(sequence: u32, uid: u32, subject: &str) -> String
+		fn fetchResponse_get(sequence: u32, uid: u32, subject: &str, flags: &str) -> String
 		{
 			let header = format!(
-				"Subject: {}\r\nFrom: sender@example.com\r\nTo: receiver@example.com\r\n\r\n",
+				"Subject: {}\r\nFrom: sender@example.com\r\nTo: receiver@example.com\r\nList-Id: <news.example.com>\r\n\r\n",
 				subject,
 			);
 			return format!(
-				"* {} FETCH (UID {} INTERNALDATE \"09-Aug-2026 10:00:00 +0000\" BODY[HEADER.FIELDS (SUBJECT FROM TO)] {{{}}}\r\n{})\r\n",
+				"* {} FETCH (UID {} RFC822.SIZE 2048 FLAGS ({}) INTERNALDATE \"09-Aug-2026 10:00:00 +0000\" BODY[HEADER.FIELDS (SUBJECT FROM TO)] {{{}}}\r\n{})\r\n",
 				sequence,
 				uid,
+				flags,
 				header.len(),
 				header,
 			);
@@ -962,8 +2410,8 @@ mod tests
 					"{}{}",
 					"a4 OK Fetch completed\r\n",
 				),
-				Self::fetchResponse_get(1,7,"Seven"),
-				Self::fetchResponse_get(2,8,"Eight"),
+				Self::fetchResponse_get(1,7,"Seven",""),
+				Self::fetchResponse_get(2,8,"Eight","\\Flagged"),
 			).into_bytes();
 		}
 
@@ -996,6 +2444,28 @@ mod tests
 			).as_bytes().to_vec();
 		}
 
+		fn literal_get(name: &str, data: &str) -> String
+		{
+			return format!("{} {{{}}}\r\n{}",name,data.len(),data);
+		}
+
+		/// login and SELECT, then one tagged FETCH per given untagged response
+		fn contentResponses_get(fetches: &[String]) -> Vec<u8>
+		{
+			let mut responses = concat!(
+				"a1 OK Logged in\r\n",
+				"* FLAGS (\\Seen)\r\n",
+				"* 1 EXISTS\r\n",
+				"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+				"a2 OK [READ-ONLY] Select completed\r\n",
+			).to_string();
+			for (index,fetch) in fetches.iter().enumerate()
+			{
+				responses.push_str(&format!("{}a{} OK Fetch completed\r\n",fetch,index + 3));
+			}
+			return responses.into_bytes();
+		}
+
 		fn seenResponses_get() -> Vec<u8>
 		{
 			return concat!(
@@ -1085,17 +2555,19 @@ mod tests
 			uidValidity: None,
 			knownUids: Vec::new(),
 		};
-		let result = ImapProxy::mailboxSync_get(&mut session,&state,&mut ImapWorkBudget::new(),0).unwrap().unwrap();
+		let result = ImapProxy::mailboxSync_get(&mut session,&state,&mut ImapWorkBudget::new(),0,&mut None).unwrap().unwrap();
 		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
 
 		assert_eq!(result.uidValidity,42);
 		assert_eq!(result.mails.len(),2);
+		assert_eq!(result.mails.iter().filter(|mail| mail.flagged).map(|mail| mail.uid).collect::<Vec<_>>(),vec![8]);
 		assert_eq!(commands.lines().count(),3);
 		assert_eq!(commands.matches("UID FETCH").count(),1);
-		assert!(commands.contains("UID FETCH 7:8"));
-		assert!(commands.contains("HEADER.FIELDS (SUBJECT FROM TO)"));
-		assert!(!commands.contains("LIST"));
-		assert!(!commands.contains("FLAGS"));
+		assert!(commands.contains("UID FETCH 7:8 (INTERNALDATE FLAGS RFC822.SIZE BODY.PEEK[HEADER.FIELDS (SUBJECT FROM TO MESSAGE-ID IN-REPLY-TO REFERENCES LIST-ID REPLY-TO CONTENT-TYPE)"),"{}",commands);
+		assert_eq!(result.mails.iter().map(|mail| mail.size).collect::<Vec<_>>(),vec![2048,2048]);
+		assert!(result.mails.iter().all(|mail| mail.header_get("list-id") == Some("<news.example.com>")));
+		assert!(!commands.contains(" LIST "));
+		assert!(!commands.contains("(FLAGS)"));
 		assert!(!commands.contains("SINCE"));
 		let legacyCommands = MailboxSyncFixture::legacyCommands_get();
 		assert_eq!(legacyCommands.lines().count(),5);
@@ -1115,7 +2587,7 @@ mod tests
 			knownUids: vec![7],
 		};
 
-		let result = ImapProxy::mailboxSync_get(&mut session,&state,&mut ImapWorkBudget::new(),0).unwrap().unwrap();
+		let result = ImapProxy::mailboxSync_get(&mut session,&state,&mut ImapWorkBudget::new(),0,&mut None).unwrap().unwrap();
 		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
 
 		assert!(result.mails.is_empty());
@@ -1124,6 +2596,95 @@ mod tests
 		assert!(!commands.contains("UID FETCH"));
 	}
 
+	#[test]
+	fn mailboxSyncReadsServerThreadsOfTrackedUids()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* FLAGS (\\Seen)\r\n",
+			"* 3 EXISTS\r\n",
+			"* 0 RECENT\r\n",
+			"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+			"a2 OK [READ-WRITE] Select completed\r\n",
+			"* SEARCH 7 8 9\r\n",
+			"a3 OK Search completed\r\n",
+			"* THREAD (7 (8)(12))(9)\r\n",
+			"a4 OK Thread completed\r\n",
+		).as_bytes().to_vec());
+		let threadRewrite = ImapThreadRewrite::default();
+		let client = imap::Client::new(threadRewrite.stream_wrap(stream));
+		let mut session = client.login("user","password").map_err(|(error,_)| error).unwrap();
+		commands.lock().unwrap().clear();
+		let state = ImapMailboxSyncState {
+			boxName: "Alerts".to_string(),
+			uidValidity: Some(42),
+			knownUids: vec![7,8,9],
+		};
+		let mut rewrite = Some(&threadRewrite);
+
+		let result = ImapProxy::mailboxSync_get(&mut session,&state,&mut ImapWorkBudget::new(),0,&mut rewrite).unwrap().unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(result.threads,vec![vec![7,8]]);
+		assert!(commands.contains("a4 UID THREAD REFERENCES UTF-8 UID 7:9\r\n"));
+		assert!(rewrite.is_some());
+		assert!(!threadRewrite.isActive());
+	}
+
+	#[test]
+	fn mailboxSyncStopsThreadingAfterARefusedThreadCommand()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+			"a2 OK [READ-WRITE] Select completed\r\n",
+			"* SEARCH 7 8\r\n",
+			"a3 OK Search completed\r\n",
+			"a4 BAD Unknown command\r\n",
+		).as_bytes().to_vec());
+		let threadRewrite = ImapThreadRewrite::default();
+		let client = imap::Client::new(threadRewrite.stream_wrap(stream));
+		let mut session = client.login("user","password").map_err(|(error,_)| error).unwrap();
+		commands.lock().unwrap().clear();
+		let state = ImapMailboxSyncState {
+			boxName: "Alerts".to_string(),
+			uidValidity: Some(42),
+			knownUids: vec![7,8],
+		};
+		let mut rewrite = Some(&threadRewrite);
+
+		let result = ImapProxy::mailboxSync_get(&mut session,&state,&mut ImapWorkBudget::new(),0,&mut rewrite).unwrap().unwrap();
+
+		assert!(result.threads.is_empty());
+		assert!(rewrite.is_none());
+		assert!(!String::from_utf8(commands.lock().unwrap().clone()).unwrap().contains("CAPABILITY"));
+	}
+
+	#[test]
+	fn threadAnswerIsOnlyReadableOnceRewritten()
+	{
+		let fixture = "a1 OK Logged in\r\n* THREAD (2)(3 4)\r\na2 OK Thread completed\r\n";
+		let (stream,_) = RecordingStream::new(fixture.as_bytes().to_vec());
+		let mut session = imap::Client::new(stream).login("user","password").map_err(|(error,_)| error).unwrap();
+		assert!(matches!(session.run_command_and_read_response("UID THREAD REFERENCES UTF-8 ALL"),Err(Error::Bye(_))));
+
+		let threadRewrite = ImapThreadRewrite::default();
+		let (stream,_) = RecordingStream::new(fixture.as_bytes().to_vec());
+		let mut session = imap::Client::new(threadRewrite.stream_wrap(stream)).login("user","password").map_err(|(error,_)| error).unwrap();
+		threadRewrite.begin();
+		let response = session.run_command_and_read_response("UID THREAD REFERENCES UTF-8 ALL").unwrap();
+		threadRewrite.end();
+		assert_eq!(ImapThreadRewrite::response_parse(&response),vec![vec![2],vec![3,4]]);
+	}
+
+	#[test]
+	fn threadRewriteParsesTopLevelGroups()
+	{
+		assert_eq!(ImapThreadRewrite::response_parse(b"* OK THREAD (2)(3 6 (4 23)(44 7 96))\r\n"),vec![vec![2],vec![3,6,4,23,44,7,96]]);
+		assert!(ImapThreadRewrite::response_parse(b"* OK THREAD\r\n").is_empty());
+		assert!(ImapThreadRewrite::response_parse(b"* OK (2 3)\r\n").is_empty());
+	}
+
 	#[test]
 	fn mailboxSyncSkipsMailboxRejectedBySelectAndContinuesSession()
 	{
@@ -1143,15 +2704,15 @@ mod tests
 			knownUids: vec![7],
 		};
 
-		let rejected = ImapProxy::mailboxSync_get(&mut session,&rejectedState,&mut ImapWorkBudget::new(),0).unwrap();
-		let accepted = ImapProxy::mailboxSync_get(&mut session,&acceptedState,&mut ImapWorkBudget::new(),1).unwrap();
+		let rejected = ImapProxy::mailboxSync_get(&mut session,&rejectedState,&mut ImapWorkBudget::new(),0,&mut None).unwrap();
+		let accepted = ImapProxy::mailboxSync_get(&mut session,&acceptedState,&mut ImapWorkBudget::new(),1,&mut None).unwrap();
 		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
 
 		assert!(rejected.is_none());
 		assert!(accepted.is_some());
 		assert_eq!(commands.lines().count(),3);
-		assert!(commands.contains("SELECT Container") || commands.contains("SELECT \"Container\""),"{}",commands);
-		assert!(commands.contains("SELECT Alerts") || commands.contains("SELECT \"Alerts\""),"{}",commands);
+		assert!(commands.contains("SELECT \"Container\""),"{}",commands);
+		assert!(commands.contains("SELECT \"Alerts\""),"{}",commands);
 		assert_eq!(commands.matches("UID SEARCH").count(),1);
 		assert!(!commands.contains("UID FETCH"));
 	}
@@ -1169,10 +2730,552 @@ mod tests
 		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
 
 		assert_eq!(commands.lines().count(),2);
-		assert!(commands.contains("SELECT Alerts") || commands.contains("SELECT \"Alerts\""),"{}",commands);
+		assert!(commands.contains("SELECT \"Alerts\""),"{}",commands);
 		assert!(commands.contains("UID STORE 7 +FLAGS.SILENT (\\Seen)"),"{}",commands);
 	}
 
+	#[test]
+	fn mailsActionMovesEachMailboxWithCompactedUidSet()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* LIST (\\HasNoChildren) \"/\" \"Trash\"\r\n",
+			"* LIST (\\HasNoChildren \\Trash) \"/\" \"Corbeille\"\r\n",
+			"a2 OK List completed\r\n",
+			"* CAPABILITY IMAP4rev1 MOVE\r\n",
+			"a3 OK Capability completed\r\n",
+			"* STATUS Work (UIDVALIDITY 5)\r\n",
+			"a4 OK Status completed\r\n",
+			"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+			"a5 OK [READ-WRITE] Select completed\r\n",
+			"a6 OK Move completed\r\n",
+			"* OK [UIDVALIDITY 5] UIDs valid\r\n",
+			"a7 OK [READ-WRITE] Select completed\r\n",
+			"a8 OK Move completed\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mails = [9,7,8,12].into_iter()
+			.map(|uid| ImapMailKey {boxName: "Alerts".to_string(),uidValidity: 42,uid})
+			.chain([ImapMailKey {boxName: "Work".to_string(),uidValidity: 5,uid: 3}])
+			.collect::<Vec<_>>();
+
+		ImapProxy::mailsActionInSession_apply(&mut session,&mails,&ImapMailAction::TRASH,&ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.contains("UID MOVE 7:9,12 \"Corbeille\""),"{}",commands);
+		assert!(commands.contains("UID MOVE 3 \"Corbeille\""),"{}",commands);
+	}
+
+	#[test]
+	fn mailsActionCopiesThenExpungesWithoutMoveCapability()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* CAPABILITY IMAP4rev1 UIDPLUS\r\n",
+			"a2 OK Capability completed\r\n",
+			"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+			"a3 OK [READ-WRITE] Select completed\r\n",
+			"a4 OK Copy completed\r\n",
+			"a5 OK Store completed\r\n",
+			"a6 OK Expunge completed\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mails = vec![ImapMailKey {boxName: "Alerts".to_string(),uidValidity: 42,uid: 7}];
+
+		ImapProxy::mailsActionInSession_apply(&mut session,&mails,&ImapMailAction::MOVE("Projects".to_string()),&ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.contains("a4 UID COPY 7 Projects\r\n"),"{}",commands);
+		assert!(commands.contains("UID STORE 7 +FLAGS.SILENT (\\Deleted)"),"{}",commands);
+		assert!(commands.contains("UID EXPUNGE 7"),"{}",commands);
+	}
+
+	#[test]
+	fn mailsActionFlagsWithoutListingAndRejectsChangedUidValidity()
+	{
+		let (stream,commands) = RecordingStream::new(MailboxSyncFixture::seenResponses_get());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mails = vec![ImapMailKey {boxName: "Alerts".to_string(),uidValidity: 42,uid: 7}];
+
+		ImapProxy::mailsActionInSession_apply(&mut session,&mails,&ImapMailAction::FLAGGED(true),&ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(commands.lines().count(),2,"{}",commands);
+		assert!(commands.contains("UID STORE 7 +FLAGS.SILENT (\\Flagged)"),"{}",commands);
+
+		let (stream,commands) = RecordingStream::new(MailboxSyncFixture::seenResponses_get());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mails = vec![ImapMailKey {boxName: "Alerts".to_string(),uidValidity: 41,uid: 7}];
+
+		let error = ImapProxy::mailsActionInSession_apply(&mut session,&mails,&ImapMailAction::UNSEEN,&ImapWorkBudget::new()).unwrap_err();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(error,ImapError::MAIL_NOT_FOUND);
+		assert!(!commands.contains("UID STORE"),"{}",commands);
+	}
+
+	#[test]
+	fn mailsActionChangesNothingWhenALaterMailboxChangedUidValidity()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* STATUS Work (UIDVALIDITY 6)\r\n",
+			"a2 OK Status completed\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mails = vec![
+			ImapMailKey {boxName: "Alerts".to_string(),uidValidity: 42,uid: 7},
+			ImapMailKey {boxName: "Work".to_string(),uidValidity: 5,uid: 3},
+		];
+
+		let error = ImapProxy::mailsActionInSession_apply(&mut session,&mails,&ImapMailAction::UNSEEN,&ImapWorkBudget::new()).unwrap_err();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(error,ImapError::MAIL_NOT_FOUND);
+		assert_eq!(commands,"a2 STATUS \"Work\" (UIDVALIDITY)\r\n");
+	}
+
+	#[test]
+	fn mailsActionRejectsOversizedSelection()
+	{
+		let mails = (1..=(ImapLimits::MAIL_ACTION_MAXIMUM as u32 + 1))
+			.map(|uid| ImapMailKey {boxName: "INBOX".to_string(),uidValidity: 1,uid})
+			.collect::<Vec<_>>();
+
+		assert_eq!(ImapProxy::mailsAction_validate(&mails,&ImapMailAction::UNSEEN),Err(ImapError::RESOURCE_LIMIT));
+		assert_eq!(ImapProxy::mailsAction_validate(&[],&ImapMailAction::UNSEEN),Err(ImapError::RESOURCE_LIMIT));
+		assert_eq!(ImapProxy::mailsAction_validate(&mails[..1],&ImapMailAction::MOVE(String::new())),Err(ImapError::RESOURCE_LIMIT));
+	}
+
+	#[test]
+	fn idleForwardsNewMailOfTheWatchedMailboxThenStopsForAClosedListener()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* CAPABILITY IMAP4rev1 IDLE\r\n",
+			"a2 OK Capability completed\r\n",
+			"* 2 EXISTS\r\n",
+			"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+			"a3 OK [READ-WRITE] Select completed\r\n",
+			"+ idling\r\n",
+			"* 3 EXISTS\r\n",
+			"a4 OK Idle terminated\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mut events = Vec::new();
+
+		ImapProxy::idleInSession_watch(&mut session,"Alerts",Instant::now() + Duration::from_secs(60),&mut |event| {
+			let isWatching = matches!(event,ImapIdleEvent::WATCHING(_));
+			events.push(event);
+			return isWatching;
+		}).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(events,vec![ImapIdleEvent::WATCHING("Alerts".to_string()),ImapIdleEvent::CHANGED("Alerts".to_string())]);
+		assert!(commands.contains("SELECT \"Alerts\""),"{}",commands);
+		assert!(commands.contains("a4 IDLE\r\nDONE\r\n"),"{}",commands);
+	}
+
+	#[test]
+	fn idleWithoutServerCapabilityReportsUnsupportedWithoutSelecting()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* CAPABILITY IMAP4rev1 MOVE\r\n",
+			"a2 OK Capability completed\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let mut events = Vec::new();
+
+		ImapProxy::idleInSession_watch(&mut session,"Alerts",Instant::now() + Duration::from_secs(60),&mut |event| {
+			events.push(event);
+			return true;
+		}).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(events,vec![ImapIdleEvent::UNSUPPORTED]);
+		assert!(!commands.contains("SELECT"),"{}",commands);
+		assert!(!commands.contains("IDLE"),"{}",commands);
+	}
+
+	#[test]
+	fn oauthConnectorAuthenticatesWithXoauth2InsteadOfThePassword()
+	{
+		use base64ct::{Base64, Encoding};
+
+		let (stream,commands) = RecordingStream::new(concat!(
+			"+ \r\n",
+			"a1 OK Authenticated\r\n",
+		).as_bytes().to_vec());
+		let config = imap_connector {
+			host: "imap.example.com".to_string(),
+			username: "me@example.com".to_string(),
+			password: "unused".to_string(),
+			..Default::default()
+		};
+		let oauthLogin = ImapOAuthLogin {mechanism: ImapSaslMechanism::XOAUTH2, accessToken: "access".to_string(), rotated: None};
+
+		ImapProxy::client_authenticate(imap::Client::new(stream),&config,Some(&oauthLogin),"list").unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.starts_with("a1 AUTHENTICATE XOAUTH2\r\n"),"{}",commands);
+		assert!(commands.contains(&Base64::encode_string(b"user=me@example.com\x01auth=Bearer access\x01\x01")),"{}",commands);
+		assert!(!commands.contains("LOGIN"),"{}",commands);
+		assert!(!commands.contains("unused"),"{}",commands);
+	}
+
+	#[test]
+	fn startTlsSessionLogsInWithoutASecondGreeting()
+	{
+		let (stream,commands) = RecordingStream::new("a1 OK Logged in\r\n".as_bytes().to_vec());
+		let config = imap_connector {
+			host: "imap.example.com".to_string(),
+			username: "me@example.com".to_string(),
+			password: "secret".to_string(),
+			..Default::default()
+		};
+		let destination = ValidatedImapDestination::test_get(
+			"imap.example.com".to_string(),
+			vec!["8.8.8.8:143".parse().unwrap()],
+		);
+		let proxy = ImapProxy::new(config,destination).unwrap();
+
+		proxy.session_open(stream,true,"list").unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.starts_with("a1 LOGIN "),"{}",commands);
+	}
+
+	#[test]
+	fn searchCriteriaQuoteAsciiTermsSendTheOthersAsLiteralsAndMakeTheUntilDayInclusive()
+	{
+		let query = ImapSearchQuery {
+			from: " boss@example.com ".to_string(),
+			subject: "say \"hi\" \\ bye".to_string(),
+			since: Some("2026-08-09".to_string()),
+			until: Some("2026-08-31".to_string()),
+			unseen: true,
+			..Default::default()
+		};
+		assert_eq!(
+			ImapSearchCriteria::fromQuery(&query).unwrap(),
+			"UNDELETED FROM \"boss@example.com\" SUBJECT \"say \\\"hi\\\" \\\\ bye\" SINCE 9-Aug-2026 BEFORE 1-Sep-2026 UNSEEN",
+		);
+
+		let accented = ImapSearchQuery {text: "réunion".to_string(), hasAttachment: true, ..Default::default()};
+		assert_eq!(
+			ImapSearchCriteria::fromQuery(&accented).unwrap(),
+			"CHARSET UTF-8 UNDELETED TEXT {8+}\r\nréunion HEADER Content-Type \"multipart/mixed\"",
+		);
+	}
+
+	#[test]
+	fn searchCriteriaRejectEmptyInjectedOrInvertedQueries()
+	{
+		let invalid = [
+			ImapSearchQuery {text: "   ".to_string(), ..Default::default()},
+			ImapSearchQuery {text: "a\r\nb2 LOGOUT".to_string(), ..Default::default()},
+			ImapSearchQuery {body: "x".repeat(ImapLimits::SEARCH_TERM_MAXIMUM_BYTES + 1), ..Default::default()},
+			ImapSearchQuery {since: Some("2026-13-01".to_string()), ..Default::default()},
+			ImapSearchQuery {since: Some("2026-08-10".to_string()), until: Some("2026-08-09".to_string()), ..Default::default()},
+		];
+		for query in invalid
+		{
+			assert_eq!(ImapSearchCriteria::fromQuery(&query),Err(ImapError::SEARCH_INVALID),"{:?}",query);
+		}
+	}
+
+	#[test]
+	fn searchCountsEveryMailboxAndSkipsTheUnselectableOnes()
+	{
+		let (stream,commands) = RecordingStream::new(format!(
+			concat!(
+				"a1 OK Logged in\r\n",
+				"* FLAGS (\\Seen)\r\n",
+				"* 2 EXISTS\r\n",
+				"* 0 RECENT\r\n",
+				"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+				"a2 OK [READ-WRITE] Select completed\r\n",
+				"* SEARCH 7 8\r\n",
+				"a3 OK Search completed\r\n",
+				"{}{}",
+				"a4 OK Fetch completed\r\n",
+				"a5 NO Mailbox is not selectable\r\n",
+				"* FLAGS (\\Seen)\r\n",
+				"* 1 EXISTS\r\n",
+				"* 0 RECENT\r\n",
+				"* OK [UIDVALIDITY 9] UIDs valid\r\n",
+				"a6 OK [READ-WRITE] Select completed\r\n",
+				"* SEARCH 3\r\n",
+				"a7 OK Search completed\r\n",
+				"{}",
+				"a8 OK Fetch completed\r\n",
+			),
+			MailboxSyncFixture::fetchResponse_get(1,7,"Seven","\\Seen"),
+			MailboxSyncFixture::fetchResponse_get(2,8,"Eight",""),
+			MailboxSyncFixture::fetchResponse_get(1,3,"Three",""),
+		).into_bytes());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+		let boxNames = ["Alerts".to_string(),"Folders".to_string(),"INBOX".to_string()];
+
+		let result = ImapProxy::searchInSession_get(&mut session,&boxNames,"UNDELETED SUBJECT \"e\"",None,&mut ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(result.total,3);
+		assert_eq!(
+			result.mails.iter().map(|(key,mail)| (key.boxName.as_str(),key.uidValidity,mail.uid,mail.seen)).collect::<Vec<_>>(),
+			vec![("Alerts",42,8,false),("Alerts",42,7,true),("INBOX",9,3,false)],
+		);
+		assert_eq!(commands.matches("UID SEARCH UNDELETED SUBJECT \"e\"").count(),2,"{}",commands);
+		assert!(commands.contains("UID FETCH 7:8"),"{}",commands);
+		assert!(commands.contains("UID FETCH 3 "),"{}",commands);
+	}
+
+	#[test]
+	fn searchSendsNonAsciiTermsOnlyToServersWithNonSynchronizingLiterals()
+	{
+		let criteria = ImapSearchCriteria::fromQuery(&ImapSearchQuery {subject: "réunion".to_string(), ..Default::default()}).unwrap();
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* CAPABILITY IMAP4rev1 IDLE\r\n",
+			"a2 OK Capability completed\r\n",
+		).as_bytes().to_vec());
+		let mut session = imap::Client::new(stream).login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+
+		let error = ImapProxy::searchInSession_get(&mut session,&["INBOX".to_string()],&criteria,None,&mut ImapWorkBudget::new()).unwrap_err();
+		assert_eq!(error,ImapError::SEARCH_CHARSET_UNSUPPORTED);
+		assert!(!String::from_utf8(commands.lock().unwrap().clone()).unwrap().contains("SEARCH"));
+
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* CAPABILITY IMAP4rev1 LITERAL+\r\n",
+			"a2 OK Capability completed\r\n",
+			"* FLAGS (\\Seen)\r\n",
+			"* 0 EXISTS\r\n",
+			"* OK [UIDVALIDITY 42] UIDs valid\r\n",
+			"a3 OK [READ-WRITE] Select completed\r\n",
+			"* SEARCH\r\n",
+			"a4 OK Search completed\r\n",
+		).as_bytes().to_vec());
+		let mut session = imap::Client::new(stream).login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+
+		let result = ImapProxy::searchInSession_get(&mut session,&["INBOX".to_string()],&criteria,None,&mut ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+		assert_eq!(result.total,0);
+		assert!(commands.contains("a4 UID SEARCH CHARSET UTF-8 UNDELETED SUBJECT {8+}\r\nréunion\r\n"),"{}",commands);
+	}
+
+	#[test]
+	fn sentCopyAppendsSeenMessageToSpecialUseSentMailbox()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* LIST (\\HasNoChildren) \"/\" \"Sent\"\r\n",
+			"* LIST (\\HasNoChildren \\Sent) \"/\" \"Envoyes\"\r\n",
+			"a2 OK List completed\r\n",
+			"+ Ready for literal data\r\n",
+			"a3 OK Append completed\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+
+		ImapProxy::sentCopyInSession_append(&mut session,b"Subject: sent\r\n\r\nbody").unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.contains("a3 APPEND \"Envoyes\" (\\Seen) {21}\r\n"),"{}",commands);
+		assert!(commands.ends_with("Subject: sent\r\n\r\nbody\r\n"),"{}",commands);
+	}
+
+	#[test]
+	fn sentCopyWithoutSentMailboxDoesNotAppend()
+	{
+		let (stream,commands) = RecordingStream::new(concat!(
+			"a1 OK Logged in\r\n",
+			"* LIST (\\HasNoChildren) \"/\" \"INBOX\"\r\n",
+			"a2 OK List completed\r\n",
+		).as_bytes().to_vec());
+		let client = imap::Client::new(stream);
+		let mut session = client.login("user","password").unwrap();
+		commands.lock().unwrap().clear();
+
+		let error = ImapProxy::sentCopyInSession_append(&mut session,b"body").unwrap_err();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(error,ImapError::MAILBOX_NOT_FOUND);
+		assert!(!commands.contains("APPEND"),"{}",commands);
+	}
+
+	#[test]
+	fn mailContentFetchesTheStructureThenOnlyTheTextParts()
+	{
+		let structure = format!(
+			"* 1 FETCH (UID 7 RFC822.SIZE 40000000 BODYSTRUCTURE ({}{} \"MIXED\" (\"BOUNDARY\" \"outer\") NIL NIL NIL) {})\r\n",
+			"((\"TEXT\" \"PLAIN\" (\"CHARSET\" \"utf-8\") NIL NIL \"7BIT\" 10 1 NIL NIL NIL NIL)(\"TEXT\" \"HTML\" (\"CHARSET\" \"utf-8\") NIL NIL \"7BIT\" 17 1 NIL NIL NIL NIL) \"ALTERNATIVE\" (\"BOUNDARY\" \"inner\") NIL NIL NIL)",
+			"(\"APPLICATION\" \"PDF\" (\"NAME\" \"menu.pdf\") NIL NIL \"BASE64\" 38000000 NIL (\"ATTACHMENT\" (\"FILENAME\" \"menu.pdf\")) NIL NIL)",
+			MailboxSyncFixture::literal_get("BODY[HEADER]<0>","Subject: Menu\r\nFrom: chef@example.com\r\nTo: me@example.com\r\n\r\n"),
+		);
+		let parts = format!(
+			"* 1 FETCH (UID 7 {} {} {} {} {})\r\n",
+			MailboxSyncFixture::literal_get("BODY[2.MIME]<0>","Content-Type: application/pdf\r\nContent-Transfer-Encoding: base64\r\nContent-Disposition: attachment; filename=\"menu.pdf\"\r\n\r\n"),
+			MailboxSyncFixture::literal_get("BODY[1.2.MIME]<0>","Content-Type: text/html; charset=utf-8\r\n\r\n"),
+			MailboxSyncFixture::literal_get("BODY[1.2]<0>","<b>Tonight</b>"),
+			MailboxSyncFixture::literal_get("BODY[1.1.MIME]<0>","Content-Type: text/plain; charset=utf-8\r\n"),
+			MailboxSyncFixture::literal_get("BODY[1.1]<0>","Tonight"),
+		);
+		let (stream,commands) = RecordingStream::new(MailboxSyncFixture::contentResponses_get(&[structure,parts]));
+		let mut session = imap::Client::new(stream).login("user","password").unwrap();
+		let key = ImapMailKey {boxName: "INBOX".to_string(),uidValidity: 42,uid: 7};
+
+		let mail = ImapProxy::mailContentFromSession_get(&mut session,&key,&ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.contains("UID FETCH 7 (RFC822.SIZE BODYSTRUCTURE BODY.PEEK[HEADER]<0."),"{}",commands);
+		assert!(commands.contains("BODY.PEEK[2.MIME]<0."),"{}",commands);
+		assert!(commands.contains("BODY.PEEK[1.2]<0."),"{}",commands);
+		assert!(!commands.contains("BODY.PEEK[2]<0.") && !commands.contains("BODY.PEEK[]"),"{}",commands);
+		assert_eq!(mail.subject.as_deref(),Some("Menu"));
+		assert_eq!(mail.size,40000000);
+		assert!(matches!(mail.content,ImapMailContentType::Html(ref html) if html.contains("<b>Tonight</b>")));
+		assert_eq!(mail.attachement.len(),1);
+		assert_eq!(mail.attachement[0].filename.as_deref(),Some("menu.pdf"));
+		assert_eq!(mail.attachement[0].section.as_deref(),Some("2"));
+		assert!(mail.attachement[0].data.is_empty());
+	}
+
+	#[test]
+	fn mailContentFetchesSignedMailsWhole()
+	{
+		let structure = format!(
+			"* 1 FETCH (UID 7 RFC822.SIZE 300 BODYSTRUCTURE ({}{} \"SIGNED\" (\"BOUNDARY\" \"sig\" \"PROTOCOL\" \"application/pgp-signature\") NIL NIL NIL) {})\r\n",
+			"(\"TEXT\" \"PLAIN\" NIL NIL NIL \"7BIT\" 5 1 NIL NIL NIL NIL)",
+			"(\"APPLICATION\" \"PGP-SIGNATURE\" NIL NIL NIL \"7BIT\" 9 NIL NIL NIL NIL)",
+			MailboxSyncFixture::literal_get("BODY[HEADER]<0>","Subject: Signed\r\n\r\n"),
+		);
+		let raw = concat!(
+			"Subject: Signed\r\nContent-Type: multipart/signed; boundary=sig; protocol=\"application/pgp-signature\"\r\n\r\n",
+			"--sig\r\nContent-Type: text/plain\r\n\r\nhello\r\n",
+			"--sig\r\nContent-Type: application/pgp-signature\r\n\r\nsignature\r\n",
+			"--sig--\r\n",
+		);
+		let whole = format!("* 1 FETCH (UID 7 RFC822.SIZE 300 {})\r\n",MailboxSyncFixture::literal_get("BODY[]<0>",raw));
+		let (stream,commands) = RecordingStream::new(MailboxSyncFixture::contentResponses_get(&[structure,whole]));
+		let mut session = imap::Client::new(stream).login("user","password").unwrap();
+		let key = ImapMailKey {boxName: "INBOX".to_string(),uidValidity: 42,uid: 7};
+
+		let mail = ImapProxy::mailContentFromSession_get(&mut session,&key,&ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert!(commands.contains("UID FETCH 7 (RFC822.SIZE BODY.PEEK[]<0."),"{}",commands);
+		assert!(mail.secured.is_some_and(|secured| secured.kind == ImapSecuredKind::OPENPGP_SIGNED));
+		assert!(matches!(mail.content,ImapMailContentType::Text(ref text) if text.contains("hello")));
+	}
+
+	#[test]
+	fn attachmentFetchDecodesOnlyTheRequestedSection()
+	{
+		let part = format!(
+			"* 1 FETCH (UID 7 {} {})\r\n",
+			MailboxSyncFixture::literal_get("BODY[2.MIME]<0>","Content-Type: application/pdf\r\nContent-Transfer-Encoding: base64\r\n\r\n"),
+			MailboxSyncFixture::literal_get("BODY[2]<0>","JVBERi0=\r\n"),
+		);
+		let (stream,commands) = RecordingStream::new(MailboxSyncFixture::contentResponses_get(&[part]));
+		let mut session = imap::Client::new(stream).login("user","password").unwrap();
+		let key = ImapMailKey {boxName: "INBOX".to_string(),uidValidity: 42,uid: 7};
+
+		let mut pieces = Vec::new();
+		ImapProxy::attachmentFromSession_stream(&mut session,&key,&[2],&ImapWorkBudget::new(),|data| {
+			pieces.push(data);
+			return true;
+		}).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(pieces,vec![b"%PDF-".to_vec()]);
+		assert!(commands.contains(&format!("UID FETCH 7 (BODY.PEEK[2.MIME]<0.{}> BODY.PEEK[2]<0.{}>)",ImapLimits::HEADER_MAXIMUM_BYTES + 1,ImapLimits::ATTACHMENT_RANGE_BYTES)),"{}",commands);
+	}
+
+	#[test]
+	fn partDecoder_decodesRangesCutAnywhereInTheirEncoding()
+	{
+		let data = (0..2000).map(|index| (index % 251) as u8).collect::<Vec<_>>();
+		let base64 = Base64::encode_string(&data).as_bytes().chunks(76).map(|line| String::from_utf8_lossy(line).into_owned()).collect::<Vec<_>>().join("\r\n");
+		let quotedPrintable = "caf=C3=A9 au lait=\r\n sans sucre\r\nd=C3=A9j=C3=A0 servi\r\n";
+		for (encoding,encoded,expected) in [
+			("base64",base64.as_str(),data.clone()),
+			("quoted-printable",quotedPrintable,"café au lait sans sucre\r\ndéjà servi\r\n".as_bytes().to_vec()),
+			("8bit","déjà\r\n","déjà\r\n".as_bytes().to_vec()),
+		]
+		{
+			for rangeBytes in [1,3,7,76,encoded.len()]
+			{
+				let mut decoder = ImapPartDecoder {
+					mimeHeader: format!("Content-Transfer-Encoding: {}\r\n\r\n",encoding).into_bytes(),
+					encoding: match encoding {"base64" => ImapPartEncoding::BASE64, "quoted-printable" => ImapPartEncoding::QUOTED_PRINTABLE, _ => ImapPartEncoding::RAW},
+					pending: Vec::new(),
+					decodedBytes: 0,
+				};
+				let ranges = encoded.as_bytes().chunks(rangeBytes).collect::<Vec<_>>();
+				let mut decoded = Vec::new();
+				for (index,range) in ranges.iter().enumerate()
+				{
+					decoded.extend(decoder.push(range,index + 1 == ranges.len()).unwrap());
+				}
+				assert_eq!(decoded,expected,"{} {}",encoding,rangeBytes);
+			}
+		}
+	}
+
+	#[test]
+	fn attachmentSectionAcceptsOnlyPartNumbers()
+	{
+		assert_eq!(ImapMailStructure::section_parse("1.2").unwrap(),vec![1,2]);
+		assert_eq!(ImapMailStructure::section_format(&[3,1]),"3.1");
+		for section in ["", "0", "1..2", "1.", "2.MIME", "+1", "1 2", "9999999999"]
+		{
+			assert_eq!(ImapMailStructure::section_parse(section).unwrap_err(),ImapError::MAIL_NOT_FOUND,"{}",section);
+		}
+		assert!(ImapMailStructure::section_parse(&vec!["1"; ImapLimits::MAIL_PART_DEPTH_MAXIMUM + 1].join(".")).is_err());
+	}
+
+	#[test]
+	fn mailSourceFetchesAPartialBodyAndKeepsTheWholeSize()
+	{
+		let source = "Received: from mx.example.org\r\nSubject: Hi\r\n\r\nbody\r\n";
+		let fetch = format!(
+			"* 1 FETCH (UID 7 RFC822.SIZE 90000000 {})\r\n",
+			MailboxSyncFixture::literal_get("BODY[]<0>",source),
+		);
+		let (stream,commands) = RecordingStream::new(MailboxSyncFixture::contentResponses_get(&[fetch]));
+		let mut session = imap::Client::new(stream).login("user","password").unwrap();
+		let key = ImapMailKey {boxName: "INBOX".to_string(),uidValidity: 42,uid: 7};
+
+		let mailSource = ImapProxy::mailSourceFromSession_get(&mut session,&key,&ImapWorkBudget::new()).unwrap();
+		let commands = String::from_utf8(commands.lock().unwrap().clone()).unwrap();
+
+		assert_eq!(mailSource.data,source.as_bytes().to_vec());
+		assert_eq!(mailSource.size,90000000);
+		assert!(mailSource.isTruncated());
+		assert!(commands.contains(&format!("UID FETCH 7 (RFC822.SIZE BODY.PEEK[]<0.{}>)",ImapLimits::MAIL_SOURCE_MAXIMUM_BYTES)),"{}",commands);
+	}
+
 	#[test]
 	fn mailSeenStoreRejectsChangedUidValidityBeforeStore()
 	{
@@ -1210,6 +3313,82 @@ mod tests
 		assert!(matches!(mail.content, ImapMailContentType::Html(ref content) if content.contains("html body")));
 	}
 
+	#[test]
+	fn mailParserKeepsTheFirstInvitationAndLeavesPublishedCalendarsAsAttachments()
+	{
+		let raw = concat!(
+			"Content-Type: multipart/mixed; boundary=outer\r\n\r\n",
+			"--outer\r\nContent-Type: multipart/alternative; boundary=inner\r\n\r\n",
+			"--inner\r\nContent-Type: text/plain\r\n\r\nYou are invited\r\n",
+			"--inner\r\nContent-Type: text/calendar; charset=utf-8\r\n\r\n",
+			"BEGIN:VCALENDAR\r\nmethod:request\r\nEND:VCALENDAR\r\n",
+			"--inner--\r\n",
+			"--outer\r\nContent-Type: text/calendar; method=PUBLISH\r\nContent-Disposition: attachment; filename=\"holidays.ics\"\r\n\r\n",
+			"BEGIN:VCALENDAR\r\nMETHOD:PUBLISH\r\nEND:VCALENDAR\r\n",
+			"--outer--\r\n",
+		);
+		let parsed = parse_mail(raw.as_bytes()).unwrap();
+		let mut mail = ImapMail::default();
+
+		ImapMailParser::bodyContent_apply(&mut mail, &parsed).unwrap();
+
+		let invitation = mail.invitation.unwrap();
+		assert_eq!(invitation.method,ImapInvitationMethod::REQUEST);
+		assert!(invitation.calendar.contains("BEGIN:VCALENDAR"));
+		assert_eq!(mail.attachement.len(),1);
+		assert_eq!(mail.attachement[0].filename.as_deref(),Some("holidays.ics"));
+	}
+
+	#[test]
+	fn mailParserKeepsSignedAndEncryptedPartsForTheBrowser()
+	{
+		let signed = concat!(
+			"Content-Type: multipart/signed; micalg=pgp-sha256; protocol=\"application/pgp-signature\"; boundary=sig\r\n\r\n",
+			"--sig\r\nContent-Type: text/plain\r\n\r\nSigned text\r\n",
+			"--sig\r\nContent-Type: application/pgp-signature; name=\"signature.asc\"\r\nContent-Disposition: attachment; filename=\"signature.asc\"\r\n\r\n",
+			"-----BEGIN PGP SIGNATURE-----\r\n",
+			"--sig--\r\n",
+		);
+		let parsed = parse_mail(signed.as_bytes()).unwrap();
+		let mut mail = ImapMail::default();
+
+		ImapMailParser::bodyContent_apply(&mut mail, &parsed).unwrap();
+
+		let secured = mail.secured.unwrap();
+		assert_eq!(secured.kind,ImapSecuredKind::OPENPGP_SIGNED);
+		assert_eq!(secured.signed,b"Content-Type: text/plain\r\n\r\nSigned text");
+		assert!(secured.payload.starts_with(b"-----BEGIN PGP SIGNATURE-----"));
+		assert!(matches!(mail.content,ImapMailContentType::Text(ref text) if text.contains("Signed text")));
+		assert!(mail.attachement.is_empty());
+
+		let encrypted = concat!(
+			"Content-Type: multipart/encrypted; protocol=\"application/pgp-encrypted\"; boundary=enc\r\n\r\n",
+			"--enc\r\nContent-Type: application/pgp-encrypted\r\n\r\nVersion: 1\r\n",
+			"--enc\r\nContent-Type: application/octet-stream\r\n\r\n-----BEGIN PGP MESSAGE-----\r\n",
+			"--enc--\r\n",
+		);
+		let parsed = parse_mail(encrypted.as_bytes()).unwrap();
+		let mut mail = ImapMail::default();
+
+		ImapMailParser::bodyContent_apply(&mut mail, &parsed).unwrap();
+
+		let secured = mail.secured.unwrap();
+		assert_eq!(secured.kind,ImapSecuredKind::OPENPGP_ENCRYPTED);
+		assert!(secured.payload.starts_with(b"-----BEGIN PGP MESSAGE-----"));
+		assert!(mail.content.is_none());
+		assert!(mail.attachement.is_empty());
+
+		let enveloped = "Content-Type: application/pkcs7-mime; smime-type=enveloped-data; name=smime.p7m\r\nContent-Transfer-Encoding: base64\r\n\r\nMIAGCSqGSIb3DQEHA6CAMIA=\r\n";
+		let parsed = parse_mail(enveloped.as_bytes()).unwrap();
+		let mut mail = ImapMail::default();
+
+		ImapMailParser::bodyContent_apply(&mut mail, &parsed).unwrap();
+
+		let secured = mail.secured.unwrap();
+		assert_eq!(secured.kind,ImapSecuredKind::SMIME_MIME);
+		assert_eq!(secured.payload[..2],[0x30,0x80]);
+	}
+
 	#[test]
 	fn mailParserSanitizesHtmlAndForcesSafeLinks()
 	{
@@ -1219,6 +3398,8 @@ mod tests
 			"<a href=\"javascript:alert(3)\" target=\"_self\">bad</a>",
 			"<a href=\"https://example.com\">safe</a>",
 			"<div style=\"position:fixed;color:red\">text</div>",
+			"<a href=\"/settings\">relative</a>",
+			"<img src=\"/api/mail_image?url=https%3A%2F%2Fforged.example%2F\">",
 		);
 
 		let sanitized = ImapMailParser::html_sanitize(html,&[]).unwrap();
@@ -1227,7 +3408,10 @@ mod tests
 		assert!(!sanitized.contains("onerror"));
 		assert!(!sanitized.contains("javascript:"));
 		assert!(!sanitized.contains("position"));
-		assert!(sanitized.contains("https://tracker.example/pixel"));
+		assert!(!sanitized.contains("https://tracker.example/pixel"));
+		assert!(sanitized.contains("src=\"/api/mail_image?url=https%3A%2F%2Ftracker.example%2Fpixel&amp;expires="));
+		assert!(!sanitized.contains("forged.example"));
+		assert!(!sanitized.contains("/settings"));
 		assert!(sanitized.contains("href=\"https://example.com\""));
 		assert!(sanitized.contains("target=\"_blank\""));
 		assert!(sanitized.contains("rel=\"noopener noreferrer\""));
@@ -1244,6 +3428,7 @@ mod tests
 				content_type: "image/png".to_string(),
 				content_id: Some("logo".to_string()),
 				data: vec![1,2,3],
+				section: None,
 			}],
 			..Default::default()
 		};
@@ -1263,6 +3448,7 @@ mod tests
 			content_type: "image/svg+xml".to_string(),
 			content_id: Some("vector".to_string()),
 			data: b"<svg onload='alert(1)'></svg>".to_vec(),
+			section: None,
 		}];
 
 		let sanitized = ImapMailParser::html_sanitize(
@@ -0,0 +1,786 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use Hconfig::HConfigManager::HConfigManager;
+use Hconfig::tinyjson::JsonValue;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use url::form_urlencoded;
+use url::Url;
+
+use crate::api::proxys::imap_components::{imap_connector, imap_connector_oauth, ImapOAuthAuthorization};
+use crate::api::proxys::imap_error::ImapError;
+use crate::api::proxys::outbound_policy::OutboundPolicy;
+
+/// site.json entry listing the OAuth providers offered to the mail modules
+pub(crate) const OAUTH_PROVIDERS_CONFIG_KEY: &str = "imap_oauth_providers";
+/// return address of the provider authorization page, it must be the redirect_url of every provider
+pub const IMAP_OAUTH_CALLBACK_PATH: &str = "/api/imap_oauth/callback";
+
+struct ImapOAuthLimits;
+
+impl ImapOAuthLimits
+{
+	// the cached access token is renewed this long before the provider expiry
+	const ACCESS_TOKEN_MARGIN: Duration = Duration::from_secs(60);
+	const ACCESS_TOKEN_CACHE_MAXIMUM: usize = 256;
+	// providers without expires_in get the usual one hour lifetime
+	const ACCESS_TOKEN_DEFAULT_LIFETIME: Duration = Duration::from_secs(3600);
+	const PENDING_LIFETIME: Duration = Duration::from_secs(10 * 60);
+	const PENDING_MAXIMUM: usize = 64;
+	const PROVIDER_MAXIMUM: usize = 16;
+	const PROVIDER_NAME_MAXIMUM_BYTES: usize = 64;
+	const TOKEN_MAXIMUM_BYTES: usize = 8_192;
+	const TOKEN_RESPONSE_MAXIMUM_BYTES: usize = 64 * 1024;
+}
+
+/// SASL mechanism announced to the IMAP server, both carry the access token as a bearer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum ImapSaslMechanism
+{
+	XOAUTH2,
+	OAUTHBEARER,
+}
+
+impl ImapSaslMechanism
+{
+	pub(super) fn name_get(self) -> &'static str
+	{
+		return match self
+		{
+			Self::XOAUTH2 => "XOAUTH2",
+			Self::OAUTHBEARER => "OAUTHBEARER",
+		};
+	}
+}
+
+/// one entry of site.json/imap_oauth_providers
+#[derive(Clone, Debug)]
+pub(super) struct ImapOAuthProvider
+{
+	name: String,
+	authorizeUrl: Url,
+	tokenUrl: Url,
+	redirectUrl: Url,
+	clientId: String,
+	clientSecret: Option<String>,
+	scope: String,
+	mechanism: ImapSaslMechanism,
+}
+
+impl ImapOAuthProvider
+{
+	pub(super) fn names_get() -> Vec<String>
+	{
+		return Self::all_get().into_iter().map(|provider| provider.name).collect();
+	}
+
+	pub(super) fn current_get(name: &str) -> Result<Self, ImapError>
+	{
+		return Self::all_get().into_iter()
+			.find(|provider| provider.name == name)
+			.ok_or(ImapError::OAUTH_PROVIDER_UNKNOWN);
+	}
+
+	/// an invalid entry is left out with a warning, the other providers stay usable
+	fn all_get() -> Vec<Self>
+	{
+		use Htrace::components::level::Level;
+		use Htrace::HTrace;
+
+		let Some(siteConfig) = HConfigManager::singleton().get("site") else {return Vec::new()};
+		let Some(JsonValue::Array(values)) = siteConfig.value_get(OAUTH_PROVIDERS_CONFIG_KEY) else {return Vec::new()};
+		let mut providers = Vec::new();
+		for (index,value) in values.iter().take(ImapOAuthLimits::PROVIDER_MAXIMUM).enumerate()
+		{
+			match Self::fromConfigValue(value)
+			{
+				Some(provider) if !providers.iter().any(|known: &Self| known.name == provider.name) => providers.push(provider),
+				_ => HTrace!((Level::WARNING) "[IMAP OAuth] {}[{}] is invalid or duplicated, the provider is ignored", OAUTH_PROVIDERS_CONFIG_KEY, index),
+			}
+		}
+		return providers;
+	}
+
+	fn fromConfigValue(value: &JsonValue) -> Option<Self>
+	{
+		let JsonValue::Object(fields) = value else {return None};
+		let text = |key: &str| match fields.get(key)
+		{
+			Some(JsonValue::String(value)) if !value.trim().is_empty() => Some(value.trim().to_string()),
+			_ => None,
+		};
+		let url = |key: &str| text(key)
+			.and_then(|value| Url::parse(&value).ok())
+			.filter(|url| url.scheme() == "https" && url.host_str().is_some() && url.username().is_empty() && url.password().is_none());
+		let name = text("name").filter(|name| name.len() <= ImapOAuthLimits::PROVIDER_NAME_MAXIMUM_BYTES)?;
+		let mechanism = match text("mechanism").as_deref()
+		{
+			None | Some("XOAUTH2") => ImapSaslMechanism::XOAUTH2,
+			Some("OAUTHBEARER") => ImapSaslMechanism::OAUTHBEARER,
+			Some(_) => return None,
+		};
+		return Some(Self {
+			name,
+			authorizeUrl: url("authorize_url")?,
+			tokenUrl: url("token_url")?,
+			redirectUrl: url("redirect_url").filter(|url| url.path() == IMAP_OAUTH_CALLBACK_PATH)?,
+			clientId: text("client_id")?,
+			clientSecret: text("client_secret"),
+			scope: text("scope")?,
+			mechanism,
+		});
+	}
+
+	fn authorizeUrl_get(&self, state: &str, verifier: &str) -> Url
+	{
+		let challenge = Base64UrlUnpadded::encode_string(&Sha256::digest(verifier.as_bytes()));
+		let mut url = self.authorizeUrl.clone();
+		url.query_pairs_mut()
+			.append_pair("response_type","code")
+			.append_pair("client_id",&self.clientId)
+			.append_pair("redirect_uri",self.redirectUrl.as_str())
+			.append_pair("scope",&self.scope)
+			.append_pair("state",state)
+			.append_pair("code_challenge",&challenge)
+			.append_pair("code_challenge_method","S256")
+			// providers only hand out a refresh token for an offline and explicitly consented access
+			.append_pair("access_type","offline")
+			.append_pair("prompt","consent");
+		return url;
+	}
+
+	/// client credentials are sent in the form body, the way every large provider accepts them
+	async fn token_request(&self, client: &Client, grant: &[(&str, &str)]) -> Result<ImapOAuthTokenResponse, ImapError>
+	{
+		let body = {
+			let mut body = form_urlencoded::Serializer::new(String::new());
+			body.extend_pairs(grant);
+			body.append_pair("client_id",&self.clientId);
+			if let Some(clientSecret) = &self.clientSecret
+			{
+				body.append_pair("client_secret",clientSecret);
+			}
+			body.finish()
+		};
+		let response = client.post(self.tokenUrl.clone())
+			.header(CONTENT_TYPE,"application/x-www-form-urlencoded")
+			.header(ACCEPT,"application/json")
+			.body(body)
+			.send().await
+			.map_err(|_| ImapError::OAUTH_UNAVAILABLE)?;
+		let status = response.status();
+		if (response.content_length().is_some_and(|length| length > ImapOAuthLimits::TOKEN_RESPONSE_MAXIMUM_BYTES as u64))
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		let content = response.bytes().await.map_err(|_| ImapError::OAUTH_UNAVAILABLE)?;
+		if (content.len() > ImapOAuthLimits::TOKEN_RESPONSE_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		// RFC 6749 answers a revoked or expired grant with 400 invalid_grant
+		if (status == reqwest::StatusCode::BAD_REQUEST || status == reqwest::StatusCode::UNAUTHORIZED)
+		{
+			return Err(ImapError::OAUTH_REJECTED);
+		}
+		if (!status.is_success())
+		{
+			return Err(ImapError::OAUTH_UNAVAILABLE);
+		}
+		let tokens = serde_json::from_slice::<ImapOAuthTokenResponse>(&content)
+			.map_err(|_| ImapError::OAUTH_UNAVAILABLE)?;
+		if (tokens.access_token.is_empty()
+			|| tokens.access_token.len() > ImapOAuthLimits::TOKEN_MAXIMUM_BYTES
+			|| tokens.refresh_token.as_ref().is_some_and(|token| token.is_empty() || token.len() > ImapOAuthLimits::TOKEN_MAXIMUM_BYTES))
+		{
+			return Err(ImapError::OAUTH_UNAVAILABLE);
+		}
+		return Ok(tokens);
+	}
+}
+
+#[derive(Deserialize, Debug)]
+struct ImapOAuthTokenResponse
+{
+	access_token: String,
+	#[serde(default)]
+	expires_in: Option<u64>,
+	#[serde(default)]
+	refresh_token: Option<String>,
+}
+
+/// access token of one connector, given to the IMAP and SMTP logins in place of the password
+#[derive(Clone, Debug)]
+pub(super) struct ImapOAuthLogin
+{
+	pub(super) mechanism: ImapSaslMechanism,
+	pub(super) accessToken: String,
+	/// refresh token the provider gave in place of the one of the connector, the answer hands it to the module
+	pub(super) rotated: Option<imap_connector_oauth>,
+}
+
+impl ImapOAuthLogin
+{
+	/// None for a password connector, otherwise a fresh access token of its refresh token
+	pub(super) async fn forConnector_get(config: &imap_connector) -> Result<Option<Self>, ImapError>
+	{
+		let Some(oauth) = &config.oauth else {return Ok(None)};
+		if (oauth.refreshToken.is_empty() || oauth.refreshToken.len() > ImapOAuthLimits::TOKEN_MAXIMUM_BYTES || !Self::username_isValid(&config.username))
+		{
+			return Err(ImapError::OAUTH_REJECTED);
+		}
+		let provider = ImapOAuthProvider::current_get(&oauth.provider)?;
+		let client = OutboundPolicy::configuredHttpClient_get()?;
+		let (accessToken,rotated) = ImapOAuthTokenCache::singleton().accessToken_get(&provider,&oauth.refreshToken,&client).await?;
+		return Ok(Some(Self {
+			mechanism: provider.mechanism,
+			accessToken,
+			rotated: rotated.map(|refreshToken| imap_connector_oauth {provider: provider.name, refreshToken}),
+		}));
+	}
+
+	/// the refresh token to hand to the module, the server keeps none
+	pub(super) fn rotated_get(login: Option<&Self>) -> Option<imap_connector_oauth>
+	{
+		return login.and_then(|login| login.rotated.clone());
+	}
+
+	/// the SASL fields are separated by \x01, the SMTP and IMAP commands by CRLF
+	fn username_isValid(username: &str) -> bool
+	{
+		return !username.contains(['\x01','\r','\n']);
+	}
+
+	pub(super) fn authenticator_get<'a>(&'a self, config: &'a imap_connector) -> ImapOAuthAuthenticator<'a>
+	{
+		return ImapOAuthAuthenticator {login: self, config};
+	}
+}
+
+/// SASL exchange of XOAUTH2 (Google, Microsoft) and OAUTHBEARER (RFC 7628)
+pub(super) struct ImapOAuthAuthenticator<'a>
+{
+	login: &'a ImapOAuthLogin,
+	config: &'a imap_connector,
+}
+
+impl imap::Authenticator for ImapOAuthAuthenticator<'_>
+{
+	type Response = String;
+
+	fn process(&self, challenge: &[u8]) -> Self::Response
+	{
+		// a non-empty challenge carries the failure details, the client must answer it to end the exchange.
+		// A username able to add SASL fields is answered the same way, the server then refuses the login
+		if (!challenge.is_empty() || !ImapOAuthLogin::username_isValid(&self.config.username))
+		{
+			return match self.login.mechanism
+			{
+				ImapSaslMechanism::XOAUTH2 => String::new(),
+				ImapSaslMechanism::OAUTHBEARER => "\x01".to_string(),
+			};
+		}
+		return match self.login.mechanism
+		{
+			ImapSaslMechanism::XOAUTH2 => format!("user={}\x01auth=Bearer {}\x01\x01",self.config.username,self.login.accessToken),
+			ImapSaslMechanism::OAUTHBEARER => format!(
+				"n,a={},\x01host={}\x01port={}\x01auth=Bearer {}\x01\x01",
+				self.config.username.replace('=',"=3D").replace(',',"=2C"),
+				self.config.host,
+				self.config.port,
+				self.login.accessToken,
+			),
+		};
+	}
+}
+
+/// access tokens in memory, keyed by a digest of the refresh token they were obtained with.
+/// The refresh tokens only live in the module content encrypted by the browser, a rotated one goes back in the answer
+#[derive(Default)]
+struct ImapOAuthTokenCache
+{
+	tokens: Mutex<HashMap<[u8; 32], (String, Instant)>>,
+}
+
+impl ImapOAuthTokenCache
+{
+	fn singleton() -> &'static Self
+	{
+		static SINGLETON: OnceLock<ImapOAuthTokenCache> = OnceLock::new();
+		return SINGLETON.get_or_init(Self::default);
+	}
+
+	fn key_get(provider: &ImapOAuthProvider, refreshToken: &str) -> [u8; 32]
+	{
+		let mut hasher = Sha256::new();
+		hasher.update(provider.name.as_bytes());
+		hasher.update([0]);
+		hasher.update(refreshToken.as_bytes());
+		return hasher.finalize().into();
+	}
+
+	/// the access token, with the refresh token the provider rotated while renewing it.
+	/// Providers rotating refresh tokens revoke the previous one: the cached access token stays reachable
+	/// from both until the module saved the new one
+	async fn accessToken_get(&self, provider: &ImapOAuthProvider, refreshToken: &str, client: &Client) -> Result<(String, Option<String>), ImapError>
+	{
+		let key = Self::key_get(provider,refreshToken);
+		if let Ok(tokens) = self.tokens.lock()
+			&& let Some((accessToken,expiry)) = tokens.get(&key)
+			&& *expiry > Instant::now()
+		{
+			return Ok((accessToken.clone(),None));
+		}
+		let tokens = provider.token_request(client,&[("grant_type","refresh_token"),("refresh_token",refreshToken)]).await?;
+		let rotated = tokens.refresh_token.filter(|rotated| rotated != refreshToken);
+		let lifetime = tokens.expires_in.map_or(ImapOAuthLimits::ACCESS_TOKEN_DEFAULT_LIFETIME,Duration::from_secs);
+		let expiry = Instant::now() + lifetime.saturating_sub(ImapOAuthLimits::ACCESS_TOKEN_MARGIN);
+		if let Ok(mut cached) = self.tokens.lock()
+		{
+			let now = Instant::now();
+			cached.retain(|_, (_, expiry)| *expiry > now);
+			let keys = [Some(key),rotated.as_deref().map(|rotated| Self::key_get(provider,rotated))];
+			for key in keys.into_iter().flatten()
+			{
+				if (cached.len() < ImapOAuthLimits::ACCESS_TOKEN_CACHE_MAXIMUM)
+				{
+					cached.insert(key,(tokens.access_token.clone(),expiry));
+				}
+			}
+		}
+		return Ok((tokens.access_token,rotated));
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ImapOAuthPendingState
+{
+	WAITING,
+	AUTHORIZED(String),
+	DENIED,
+}
+
+struct ImapOAuthPending
+{
+	provider: String,
+	// account which started the authorization, the only one allowed to finish it
+	owner: String,
+	verifier: String,
+	created: Instant,
+	state: ImapOAuthPendingState,
+}
+
+/// authorizations between the provider page and the module, keyed by their unguessable state.
+/// The session cookie is not sent on the provider redirection, the state binds the callback instead
+#[derive(Default)]
+pub(crate) struct ImapOAuthAuthorizations
+{
+	pending: Mutex<HashMap<String, ImapOAuthPending>>,
+}
+
+impl ImapOAuthAuthorizations
+{
+	pub(super) fn singleton() -> &'static Self
+	{
+		static SINGLETON: OnceLock<ImapOAuthAuthorizations> = OnceLock::new();
+		return SINGLETON.get_or_init(Self::default);
+	}
+
+	fn random_get() -> Result<String, ImapError>
+	{
+		let mut bytes = [0u8; 32];
+		getrandom::fill(&mut bytes).map_err(|_| ImapError::SERVER_ERROR)?;
+		return Ok(Base64UrlUnpadded::encode_string(&bytes));
+	}
+
+	pub(super) fn begin(&self, provider: &ImapOAuthProvider, owner: &str) -> Result<ImapOAuthAuthorization, ImapError>
+	{
+		let state = Self::random_get()?;
+		let verifier = Self::random_get()?;
+		let url = provider.authorizeUrl_get(&state,&verifier);
+		let mut pending = self.pending.lock().map_err(|_| ImapError::SERVER_ERROR)?;
+		pending.retain(|_, authorization| authorization.created.elapsed() < ImapOAuthLimits::PENDING_LIFETIME);
+		if (pending.len() >= ImapOAuthLimits::PENDING_MAXIMUM)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		pending.insert(state.clone(),ImapOAuthPending {
+			provider: provider.name.clone(),
+			owner: owner.to_string(),
+			verifier,
+			created: Instant::now(),
+			state: ImapOAuthPendingState::WAITING,
+		});
+		return Ok(ImapOAuthAuthorization {state, url: url.to_string()});
+	}
+
+	/// false for an unknown, expired or already answered state
+	fn callback_set(&self, state: &str, result: ImapOAuthPendingState) -> bool
+	{
+		let Ok(mut pending) = self.pending.lock() else {return false};
+		let Some(authorization) = pending.get_mut(state) else {return false};
+		if (authorization.created.elapsed() >= ImapOAuthLimits::PENDING_LIFETIME || authorization.state != ImapOAuthPendingState::WAITING)
+		{
+			return false;
+		}
+		authorization.state = result;
+		return true;
+	}
+
+	/// the provider name, the PKCE verifier and the authorization code, once the provider answered
+	fn code_take(&self, state: &str, owner: &str) -> Result<(String, String, String), ImapError>
+	{
+		let mut pending = self.pending.lock().map_err(|_| ImapError::SERVER_ERROR)?;
+		let Some(authorization) = pending.get(state).filter(|authorization| authorization.owner == owner)
+		else
+		{
+			return Err(ImapError::OAUTH_REJECTED);
+		};
+		if (authorization.created.elapsed() >= ImapOAuthLimits::PENDING_LIFETIME)
+		{
+			pending.remove(state);
+			return Err(ImapError::OAUTH_REJECTED);
+		}
+		if (authorization.state == ImapOAuthPendingState::WAITING)
+		{
+			return Err(ImapError::OAUTH_PENDING);
+		}
+		let Some(authorization) = pending.remove(state) else {return Err(ImapError::OAUTH_REJECTED)};
+		return match authorization.state
+		{
+			ImapOAuthPendingState::AUTHORIZED(code) => Ok((authorization.provider,authorization.verifier,code)),
+			_ => Err(ImapError::OAUTH_REJECTED),
+		};
+	}
+
+	/// exchanges the authorization code, the refresh token goes straight back to the module content
+	pub(super) async fn finish(&self, state: &str, owner: &str) -> Result<imap_connector_oauth, ImapError>
+	{
+		let (providerName,verifier,code) = self.code_take(state,owner)?;
+		let provider = ImapOAuthProvider::current_get(&providerName)?;
+		let client = OutboundPolicy::configuredHttpClient_get()?;
+		return Self::code_exchange(&provider,&client,&code,&verifier).await;
+	}
+
+	async fn code_exchange(provider: &ImapOAuthProvider, client: &Client, code: &str, verifier: &str) -> Result<imap_connector_oauth, ImapError>
+	{
+		let tokens = provider.token_request(client,&[
+			("grant_type","authorization_code"),
+			("code",code),
+			("redirect_uri",provider.redirectUrl.as_str()),
+			("code_verifier",verifier),
+		]).await?;
+		let refreshToken = tokens.refresh_token.ok_or(ImapError::OAUTH_REJECTED)?;
+		return Ok(imap_connector_oauth {provider: provider.name.clone(), refreshToken});
+	}
+
+	/// axum handler of IMAP_OAUTH_CALLBACK_PATH, the page only tells the user to go back to the module
+	pub(crate) async fn callback_receive(Query(parameters): Query<HashMap<String, String>>) -> Response
+	{
+		let Some(state) = parameters.get("state") else {return Self::callbackPage_get(StatusCode::BAD_REQUEST)};
+		let result = match parameters.get("code")
+		{
+			Some(code) if !code.is_empty() && code.len() <= ImapOAuthLimits::TOKEN_MAXIMUM_BYTES => ImapOAuthPendingState::AUTHORIZED(code.clone()),
+			_ => ImapOAuthPendingState::DENIED,
+		};
+		if (!Self::singleton().callback_set(state,result))
+		{
+			return Self::callbackPage_get(StatusCode::BAD_REQUEST);
+		}
+		return Self::callbackPage_get(StatusCode::OK);
+	}
+
+	fn callbackPage_get(status: StatusCode) -> Response
+	{
+		let message = if (status.is_success())
+		{
+			"Authorization received, this window can be closed. / Autorisation reçue, cette fenêtre peut être fermée."
+		}
+		else
+		{
+			"This authorization is unknown or expired. / Cette autorisation est inconnue ou expirée."
+		};
+		return (status, Html(format!("<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>web-home</title></head><body><p>{}</p></body></html>",message))).into_response();
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::collections::HashMap;
+	use std::sync::{Arc, Mutex};
+
+	use base64ct::{Base64UrlUnpadded, Encoding};
+	use Hconfig::tinyjson::JsonValue;
+	use imap::Authenticator;
+	use reqwest::Client;
+	use sha2::{Digest, Sha256};
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+	use url::Url;
+
+	use crate::api::proxys::imap_components::imap_connector;
+	use crate::api::proxys::imap_error::ImapError;
+	use super::{ImapOAuthAuthorizations, ImapOAuthLogin, ImapOAuthPendingState, ImapOAuthProvider, ImapOAuthTokenCache, ImapSaslMechanism};
+
+	/// local stand-in of a provider token endpoint, answers the canned responses in order and records the form bodies
+	struct AuthorizationServerStandIn
+	{
+		url: Url,
+		bodies: Arc<Mutex<Vec<String>>>,
+	}
+
+	impl AuthorizationServerStandIn
+	{
+		async fn start(responses: Vec<(u16, &'static str)>) -> Self
+		{
+			let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+			let url = Url::parse(&format!("http://{}/token",listener.local_addr().unwrap())).unwrap();
+			let bodies = Arc::new(Mutex::new(Vec::new()));
+			let bodiesServer = bodies.clone();
+			tokio::spawn(async move {
+				for (status,body) in responses
+				{
+					let (mut stream,_) = listener.accept().await.unwrap();
+					let request = Self::request_read(&mut stream).await;
+					bodiesServer.lock().unwrap().push(request);
+					let response = format!(
+						"HTTP/1.1 {} Canned\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+						status,
+						body.len(),
+						body,
+					);
+					stream.write_all(response.as_bytes()).await.unwrap();
+				}
+			});
+			return Self {url, bodies};
+		}
+
+		async fn request_read(stream: &mut tokio::net::TcpStream) -> String
+		{
+			let mut request = Vec::new();
+			let mut buffer = [0u8; 4096];
+			loop
+			{
+				let read = stream.read(&mut buffer).await.unwrap();
+				request.extend_from_slice(&buffer[..read]);
+				let text = String::from_utf8_lossy(&request).to_string();
+				if let Some((headers,body)) = text.split_once("\r\n\r\n")
+				{
+					let length = headers.lines()
+						.find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length: ").map(|value| value.trim().parse::<usize>().unwrap()))
+						.unwrap_or(0);
+					if (body.len() >= length || read == 0)
+					{
+						return body.to_string();
+					}
+				}
+			}
+		}
+
+		fn provider_get(&self) -> ImapOAuthProvider
+		{
+			return ImapOAuthProvider {
+				name: "standin".to_string(),
+				authorizeUrl: Url::parse("https://accounts.example.com/authorize").unwrap(),
+				tokenUrl: self.url.clone(),
+				redirectUrl: Url::parse("https://home.example.com/api/imap_oauth/callback").unwrap(),
+				clientId: "client".to_string(),
+				clientSecret: Some("secret".to_string()),
+				scope: "https://mail.example.com/".to_string(),
+				mechanism: ImapSaslMechanism::XOAUTH2,
+			};
+		}
+	}
+
+	fn providerConfig_get(mechanism: Option<&str>, tokenUrl: &str) -> JsonValue
+	{
+		let mut fields = HashMap::from([
+			("name".to_string(),JsonValue::String("google".to_string())),
+			("authorize_url".to_string(),JsonValue::String("https://accounts.google.com/o/oauth2/v2/auth".to_string())),
+			("token_url".to_string(),JsonValue::String(tokenUrl.to_string())),
+			("redirect_url".to_string(),JsonValue::String("https://home.example.com/api/imap_oauth/callback".to_string())),
+			("client_id".to_string(),JsonValue::String("client".to_string())),
+			("scope".to_string(),JsonValue::String("https://mail.google.com/".to_string())),
+		]);
+		if let Some(mechanism) = mechanism
+		{
+			fields.insert("mechanism".to_string(),JsonValue::String(mechanism.to_string()));
+		}
+		return JsonValue::Object(fields);
+	}
+
+	#[test]
+	fn providerConfig_requiresHttpsEndpointsAndAKnownMechanism()
+	{
+		let provider = ImapOAuthProvider::fromConfigValue(&providerConfig_get(None,"https://oauth2.googleapis.com/token")).unwrap();
+		assert_eq!(provider.mechanism,ImapSaslMechanism::XOAUTH2);
+		assert!(provider.clientSecret.is_none());
+		let bearer = ImapOAuthProvider::fromConfigValue(&providerConfig_get(Some("OAUTHBEARER"),"https://oauth2.googleapis.com/token")).unwrap();
+		assert_eq!(bearer.mechanism,ImapSaslMechanism::OAUTHBEARER);
+
+		assert!(ImapOAuthProvider::fromConfigValue(&providerConfig_get(None,"http://oauth2.googleapis.com/token")).is_none());
+		assert!(ImapOAuthProvider::fromConfigValue(&providerConfig_get(Some("PLAIN"),"https://oauth2.googleapis.com/token")).is_none());
+	}
+
+	#[test]
+	fn authorizeUrl_carriesStateAndS256Challenge()
+	{
+		let provider = ImapOAuthProvider::fromConfigValue(&providerConfig_get(None,"https://oauth2.googleapis.com/token")).unwrap();
+		let url = provider.authorizeUrl_get("state-value","verifier-value");
+		let query = url.query_pairs().into_owned().collect::<HashMap<_,_>>();
+
+		assert_eq!(query["state"],"state-value");
+		assert_eq!(query["code_challenge_method"],"S256");
+		assert_eq!(query["code_challenge"],Base64UrlUnpadded::encode_string(&Sha256::digest(b"verifier-value")));
+		assert_eq!(query["redirect_uri"],"https://home.example.com/api/imap_oauth/callback");
+		assert!(!url.as_str().contains("verifier-value"));
+	}
+
+	#[test]
+	fn authenticator_formatsBothMechanismsAndEndsAFailedExchange()
+	{
+		let config = imap_connector {
+			host: "imap.example.com".to_string(),
+			port: 993,
+			username: "me@example.com".to_string(),
+			..Default::default()
+		};
+		let xoauth2 = ImapOAuthLogin {mechanism: ImapSaslMechanism::XOAUTH2, accessToken: "token".to_string(), rotated: None};
+		assert_eq!(xoauth2.authenticator_get(&config).process(b""),"user=me@example.com\x01auth=Bearer token\x01\x01");
+		assert_eq!(xoauth2.authenticator_get(&config).process(b"{\"status\":\"401\"}"),"");
+
+		let oauthbearer = ImapOAuthLogin {mechanism: ImapSaslMechanism::OAUTHBEARER, accessToken: "token".to_string(), rotated: None};
+		assert_eq!(
+			oauthbearer.authenticator_get(&config).process(b""),
+			"n,a=me@example.com,\x01host=imap.example.com\x01port=993\x01auth=Bearer token\x01\x01",
+		);
+		assert_eq!(oauthbearer.authenticator_get(&config).process(b"{\"status\":\"invalid_token\"}"),"\x01");
+
+		for username in ["me@example.com\x01auth=Bearer other","me@example.com\r\na2 LOGOUT","me@example.com\n"]
+		{
+			let injected = imap_connector {username: username.to_string(), ..config.clone()};
+			assert_eq!(xoauth2.authenticator_get(&injected).process(b""),"");
+			assert_eq!(oauthbearer.authenticator_get(&injected).process(b""),"\x01");
+		}
+	}
+
+	#[test]
+	fn accessToken_isRefreshedOnceThenCached()
+	{
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		runtime.block_on(async {
+			let server = AuthorizationServerStandIn::start(vec![
+				(200,"{\"access_token\":\"access-1\",\"expires_in\":3600,\"token_type\":\"Bearer\"}"),
+			]).await;
+			let provider = server.provider_get();
+			let cache = ImapOAuthTokenCache::default();
+			let client = Client::new();
+
+			assert_eq!(cache.accessToken_get(&provider,"refresh",&client).await.unwrap().0,"access-1");
+			assert_eq!(cache.accessToken_get(&provider,"refresh",&client).await.unwrap().0,"access-1");
+
+			let bodies = server.bodies.lock().unwrap().clone();
+			assert_eq!(bodies.len(),1);
+			assert!(bodies[0].contains("grant_type=refresh_token"));
+			assert!(bodies[0].contains("refresh_token=refresh"));
+			assert!(bodies[0].contains("client_secret=secret"));
+			assert!(!cache.tokens.lock().unwrap().keys().any(|key| key.as_slice() == b"refresh"));
+		});
+	}
+
+	#[test]
+	fn accessToken_returnsTheRotatedRefreshTokenWithoutKeepingIt()
+	{
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		runtime.block_on(async {
+			let server = AuthorizationServerStandIn::start(vec![
+				(200,"{\"access_token\":\"access-1\",\"expires_in\":3600,\"refresh_token\":\"refresh-2\"}"),
+			]).await;
+			let provider = server.provider_get();
+			let cache = ImapOAuthTokenCache::default();
+			let client = Client::new();
+
+			assert_eq!(cache.accessToken_get(&provider,"refresh",&client).await.unwrap(),("access-1".to_string(),Some("refresh-2".to_string())));
+			// the answer carried the rotation, the module asks with either token until it saved the new one
+			assert_eq!(cache.accessToken_get(&provider,"refresh",&client).await.unwrap(),("access-1".to_string(),None));
+			assert_eq!(cache.accessToken_get(&provider,"refresh-2",&client).await.unwrap(),("access-1".to_string(),None));
+
+			assert_eq!(server.bodies.lock().unwrap().len(),1);
+			assert!(cache.tokens.lock().unwrap().values().all(|(accessToken,_)| accessToken == "access-1"));
+		});
+	}
+
+	#[test]
+	fn accessToken_revokedGrantAsksForANewAuthorization()
+	{
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		runtime.block_on(async {
+			let server = AuthorizationServerStandIn::start(vec![
+				(400,"{\"error\":\"invalid_grant\"}"),
+				(503,"{}"),
+			]).await;
+			let provider = server.provider_get();
+			let cache = ImapOAuthTokenCache::default();
+			let client = Client::new();
+
+			assert_eq!(cache.accessToken_get(&provider,"revoked",&client).await,Err(ImapError::OAUTH_REJECTED));
+			assert_eq!(cache.accessToken_get(&provider,"revoked",&client).await,Err(ImapError::OAUTH_UNAVAILABLE));
+			assert!(cache.tokens.lock().unwrap().is_empty());
+		});
+	}
+
+	#[test]
+	fn authorization_isFinishedOnlyByItsOwnerWithThePkceVerifier()
+	{
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		runtime.block_on(async {
+			let server = AuthorizationServerStandIn::start(vec![
+				(200,"{\"access_token\":\"access\",\"refresh_token\":\"refresh-new\",\"expires_in\":3600}"),
+			]).await;
+			let provider = server.provider_get();
+			let authorizations = ImapOAuthAuthorizations::default();
+			let authorization = authorizations.begin(&provider,"account-a").unwrap();
+
+			assert_eq!(authorizations.code_take(&authorization.state,"account-a"),Err(ImapError::OAUTH_PENDING));
+			assert!(authorizations.callback_set(&authorization.state,ImapOAuthPendingState::AUTHORIZED("code-1".to_string())));
+			assert!(!authorizations.callback_set(&authorization.state,ImapOAuthPendingState::AUTHORIZED("code-2".to_string())));
+			assert_eq!(authorizations.code_take(&authorization.state,"account-b"),Err(ImapError::OAUTH_REJECTED));
+
+			let (providerName,verifier,code) = authorizations.code_take(&authorization.state,"account-a").unwrap();
+			assert_eq!((providerName.as_str(),code.as_str()),("standin","code-1"));
+			let challenge = Url::parse(&authorization.url).unwrap().query_pairs()
+				.find(|(key,_)| key == "code_challenge")
+				.map(|(_,value)| value.to_string())
+				.unwrap();
+			assert_eq!(challenge,Base64UrlUnpadded::encode_string(&Sha256::digest(verifier.as_bytes())));
+
+			let oauth = ImapOAuthAuthorizations::code_exchange(&provider,&Client::new(),&code,&verifier).await.unwrap();
+			assert_eq!(oauth.refreshToken,"refresh-new");
+			assert_eq!(oauth.provider,"standin");
+			let bodies = server.bodies.lock().unwrap().clone();
+			assert!(bodies[0].contains("grant_type=authorization_code"));
+			assert!(bodies[0].contains(&format!("code_verifier={}",verifier)));
+			assert_eq!(authorizations.code_take(&authorization.state,"account-a"),Err(ImapError::OAUTH_REJECTED));
+		});
+	}
+
+	#[test]
+	fn authorization_deniedByTheUserIsRejected()
+	{
+		let provider = ImapOAuthProvider::fromConfigValue(&providerConfig_get(None,"https://oauth2.googleapis.com/token")).unwrap();
+		let authorizations = ImapOAuthAuthorizations::default();
+		let authorization = authorizations.begin(&provider,"account").unwrap();
+
+		assert!(authorizations.callback_set(&authorization.state,ImapOAuthPendingState::DENIED));
+		assert_eq!(authorizations.code_take(&authorization.state,"account"),Err(ImapError::OAUTH_REJECTED));
+		assert!(!authorizations.callback_set("unknown",ImapOAuthPendingState::DENIED));
+	}
+}
@@ -0,0 +1,796 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use Hconfig::HConfigManager::HConfigManager;
+use Hconfig::IO::json::WrapperJson;
+use Hconfig::tinyjson::JsonValue;
+use leptos::prelude::{provide_context, Owner};
+use leptos::reactive::computed::ScopedFuture;
+use mailparse::{parse_headers, parse_mail, DispositionType, MailHeaderMap, ParsedMail};
+use native_tls::{Identity, TlsAcceptor, TlsStream};
+use openssl::asn1::Asn1Time;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509Builder, X509NameBuilder};
+
+use crate::api::login::user_back::test_support::{authenticatedSession_get, ConfigPathGuard};
+use crate::api::proxys::imap_components::{imap_connector, imap_connector_extra, ImapConnectionSecurity};
+use crate::api::proxys::outbound_policy::{OutboundPolicy, IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY};
+
+struct ImapTestLimits;
+
+impl ImapTestLimits
+{
+	// a client left waiting is a failed test, not a hung one
+	const SESSION_TIMEOUT: Duration = Duration::from_secs(30);
+	const COMMAND_LINE_MAXIMUM_BYTES: usize = 64 * 1024;
+}
+
+#[derive(Clone, Debug)]
+struct ImapTestMail
+{
+	uid: u32,
+	flags: Vec<String>,
+	data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct ImapTestMailbox
+{
+	uidValidity: u32,
+	uidNext: u32,
+	mails: Vec<ImapTestMail>,
+}
+
+#[derive(Debug, Default)]
+struct ImapTestModel
+{
+	mailboxes: BTreeMap<String, ImapTestMailbox>,
+	/// every command received, without its tag, LOGIN without its credentials
+	commands: Vec<String>,
+	connections: usize,
+}
+
+/// in-process IMAP server over loopback TLS, with a certificate generated at start and trusted by
+/// OutboundPolicy until its drop. Mailboxes live in memory and the tests change them between requests.
+/// It answers the commands of ImapProxy: CAPABILITY, LOGIN, LIST, SELECT, EXAMINE, UID SEARCH, UID FETCH,
+/// UID STORE, NOOP and LOGOUT. BODYSTRUCTURE describes multipart, text and basic parts, not message/rfc822 ones
+pub(super) struct ImapTestServer
+{
+	port: u16,
+	certificate: Vec<u8>,
+	model: Arc<Mutex<ImapTestModel>>,
+	isStopped: Arc<AtomicBool>,
+	listener: Option<JoinHandle<()>>,
+}
+
+impl ImapTestServer
+{
+	pub(super) const USERNAME: &str = "user@localhost";
+	pub(super) const PASSWORD: &str = "test-password";
+	pub(super) const INTERNAL_DATE: &str = "19-Oct-2026 10:00:00 +0000";
+
+	pub(super) fn start() -> Self
+	{
+		let (identity, certificate) = Self::identity_generate();
+		let acceptor = Arc::new(TlsAcceptor::new(identity).unwrap());
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let port = listener.local_addr().unwrap().port();
+		let model = Arc::new(Mutex::new(ImapTestModel::default()));
+		let isStopped = Arc::new(AtomicBool::new(false));
+
+		let listenerModel = model.clone();
+		let listenerStopped = isStopped.clone();
+		let listener = thread::spawn(move || {
+			for stream in listener.incoming()
+			{
+				if (listenerStopped.load(Ordering::SeqCst))
+				{
+					return;
+				}
+				let Ok(stream) = stream else {continue};
+				let acceptor = acceptor.clone();
+				let model = listenerModel.clone();
+				thread::spawn(move || ImapTestSession::run(stream, &acceptor, model));
+			}
+		});
+		OutboundPolicy::imapTestRootCertificate_add(certificate.clone());
+		return Self { port, certificate, model, isStopped, listener: Some(listener) };
+	}
+
+	/// self-signed certificate of localhost and 127.0.0.1, with the DER bytes the client trusts
+	fn identity_generate() -> (Identity, Vec<u8>)
+	{
+		let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+		let key = PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap();
+		let mut name = X509NameBuilder::new().unwrap();
+		name.append_entry_by_text("CN", "localhost").unwrap();
+		let name = name.build();
+		let mut builder = X509Builder::new().unwrap();
+		builder.set_version(2).unwrap();
+		builder.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap()).unwrap();
+		builder.set_subject_name(&name).unwrap();
+		builder.set_issuer_name(&name).unwrap();
+		builder.set_pubkey(&key).unwrap();
+		builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+		builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+		let alternativeNames = SubjectAlternativeName::new()
+			.dns("localhost")
+			.ip("127.0.0.1")
+			.build(&builder.x509v3_context(None, None))
+			.unwrap();
+		builder.append_extension(alternativeNames).unwrap();
+		builder.sign(&key, MessageDigest::sha256()).unwrap();
+		let certificate = builder.build();
+		let identity = Identity::from_pkcs8(&certificate.to_pem().unwrap(), &key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+		return (identity, certificate.to_der().unwrap());
+	}
+
+	/// a connector of this server selecting every mailbox
+	pub(super) fn connector_get(&self) -> imap_connector
+	{
+		return imap_connector {
+			host: "localhost".to_string(),
+			port: self.port,
+			username: Self::USERNAME.to_string(),
+			password: Self::PASSWORD.to_string(),
+			extra: Some(imap_connector_extra::default()),
+			oauth: None,
+			security: ImapConnectionSecurity::TLS,
+		};
+	}
+
+	fn model_get(&self) -> MutexGuard<'_, ImapTestModel>
+	{
+		return self.model.lock().unwrap_or_else(|error| error.into_inner());
+	}
+
+	pub(super) fn mailbox_create(&self, boxName: &str, uidValidity: u32)
+	{
+		self.model_get().mailboxes.insert(boxName.to_string(), ImapTestMailbox {uidValidity, uidNext: 1, mails: Vec::new()});
+	}
+
+	/// the uid given to the new mail
+	pub(super) fn mail_append(&self, boxName: &str, flags: &[&str], data: &[u8]) -> u32
+	{
+		let mut model = self.model_get();
+		let mailbox = model.mailboxes.get_mut(boxName).unwrap();
+		let uid = mailbox.uidNext;
+		mailbox.uidNext += 1;
+		mailbox.mails.push(ImapTestMail {
+			uid,
+			flags: flags.iter().map(|flag| flag.to_string()).collect(),
+			data: data.to_vec(),
+		});
+		return uid;
+	}
+
+	pub(super) fn mail_expunge(&self, boxName: &str, uid: u32)
+	{
+		self.model_get().mailboxes.get_mut(boxName).unwrap().mails.retain(|mail| mail.uid != uid);
+	}
+
+	/// as a server rebuilding its index, the uids stay but the clients must forget them
+	pub(super) fn uidValidity_set(&self, boxName: &str, uidValidity: u32)
+	{
+		self.model_get().mailboxes.get_mut(boxName).unwrap().uidValidity = uidValidity;
+	}
+
+	pub(super) fn flags_get(&self, boxName: &str, uid: u32) -> Vec<String>
+	{
+		return self.model_get().mailboxes.get(boxName)
+			.and_then(|mailbox| mailbox.mails.iter().find(|mail| mail.uid == uid))
+			.map(|mail| mail.flags.clone())
+			.unwrap_or_default();
+	}
+
+	pub(super) fn commands_get(&self) -> Vec<String>
+	{
+		return self.model_get().commands.clone();
+	}
+
+	pub(super) fn connections_get(&self) -> usize
+	{
+		return self.model_get().connections;
+	}
+
+	/// runs a server function test inside a signed in request, as leptos_axum would.
+	/// The site.json of the run allows the port of this server on loopback, OutboundPolicy checks it as any other
+	pub(super) fn authenticated_run<Output, Test>(&self, test: impl FnOnce() -> Test) -> Output
+	where
+		Test: Future<Output = Output>,
+	{
+		let _configPath = ConfigPathGuard::new();
+		let _siteConfig = ImapTestSiteConfig::new(self.port);
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		return runtime.block_on(async {
+			let session = authenticatedSession_get(71).await;
+			let (mut parts, _) = http::Request::new(()).into_parts();
+			parts.extensions.insert(session);
+			let owner = Owner::new();
+			return owner.with(|| {
+				provide_context(parts);
+				return ScopedFuture::new(test());
+			}).await;
+		});
+	}
+}
+
+impl Drop for ImapTestServer
+{
+	fn drop(&mut self)
+	{
+		OutboundPolicy::imapTestRootCertificate_forget(&self.certificate);
+		self.isStopped.store(true, Ordering::SeqCst);
+		// wakes the listener up so it sees the stop
+		let _ = TcpStream::connect(("127.0.0.1", self.port));
+		if let Some(listener) = self.listener.take()
+		{
+			let _ = listener.join();
+		}
+	}
+}
+
+/// the site.json values of one authenticated run, removed at its end so the tests after it see the defaults
+struct ImapTestSiteConfig;
+
+impl ImapTestSiteConfig
+{
+	const ALLOWED_PORTS_KEY: &str = "imap_allowed_ports";
+
+	/// the configuration directory must be held, the site.json is created in it
+	fn new(port: u16) -> Self
+	{
+		HConfigManager::singleton().create::<WrapperJson>("site").unwrap();
+		let mut siteConfig = HConfigManager::singleton().get("site").unwrap();
+		siteConfig.value_set(Self::ALLOWED_PORTS_KEY, vec![JsonValue::Number(port as f64)]);
+		siteConfig.value_set(IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY, true);
+		return Self;
+	}
+}
+
+impl Drop for ImapTestSiteConfig
+{
+	fn drop(&mut self)
+	{
+		if let Some(mut siteConfig) = HConfigManager::singleton().get("site")
+		{
+			siteConfig.value_remove(Self::ALLOWED_PORTS_KEY);
+			siteConfig.value_remove(IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY);
+		}
+	}
+}
+
+struct ImapTestSession
+{
+	model: Arc<Mutex<ImapTestModel>>,
+	isAuthenticated: bool,
+	selected: Option<String>,
+}
+
+impl ImapTestSession
+{
+	fn run(stream: TcpStream, acceptor: &TlsAcceptor, model: Arc<Mutex<ImapTestModel>>)
+	{
+		let _ = stream.set_read_timeout(Some(ImapTestLimits::SESSION_TIMEOUT));
+		let _ = stream.set_write_timeout(Some(ImapTestLimits::SESSION_TIMEOUT));
+		let Ok(stream) = acceptor.accept(stream) else {return};
+		model.lock().unwrap_or_else(|error| error.into_inner()).connections += 1;
+		let mut session = Self {model, isAuthenticated: false, selected: None};
+		let mut reader = BufReader::new(stream);
+		if (Self::answer_write(&mut reader, b"* OK [CAPABILITY IMAP4rev1 IDLE UIDPLUS] test server ready\r\n").is_err())
+		{
+			return;
+		}
+		loop
+		{
+			let mut line = Vec::new();
+			match (&mut reader).take(ImapTestLimits::COMMAND_LINE_MAXIMUM_BYTES as u64).read_until(b'\n', &mut line)
+			{
+				Ok(0) | Err(_) => return,
+				Ok(_) => {},
+			}
+			let line = String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string();
+			let Some((tag, command)) = line.split_once(' ') else {return};
+			let (answer, isClosed) = session.command_answer(tag, command);
+			if (Self::answer_write(&mut reader, &answer).is_err() || isClosed)
+			{
+				return;
+			}
+		}
+	}
+
+	fn answer_write(reader: &mut BufReader<TlsStream<TcpStream>>, answer: &[u8]) -> std::io::Result<()>
+	{
+		let stream = reader.get_mut();
+		stream.write_all(answer)?;
+		return stream.flush();
+	}
+
+	fn model_get(&self) -> MutexGuard<'_, ImapTestModel>
+	{
+		return self.model.lock().unwrap_or_else(|error| error.into_inner());
+	}
+
+	/// the untagged responses then the tagged one, and whether the connection ends
+	fn command_answer(&mut self, tag: &str, command: &str) -> (Vec<u8>, bool)
+	{
+		let (name, arguments) = command.split_once(' ').unwrap_or((command, ""));
+		let name = name.to_ascii_uppercase();
+		self.model_get().commands.push(if (name == "LOGIN") {name.clone()} else {command.to_string()});
+		let result = match name.as_str()
+		{
+			"CAPABILITY" => Ok(b"* CAPABILITY IMAP4rev1 IDLE UIDPLUS\r\n".to_vec()),
+			"NOOP" => Ok(Vec::new()),
+			"LOGOUT" => return ([b"* BYE test server closing\r\n".to_vec(), format!("{} OK LOGOUT completed\r\n", tag).into_bytes()].concat(), true),
+			"LOGIN" => self.login(arguments),
+			_ if !self.isAuthenticated => Err("not authenticated".to_string()),
+			"LIST" => Ok(self.list()),
+			"SELECT" | "EXAMINE" => self.select(arguments),
+			"UID" => self.uidCommand_answer(arguments),
+			_ => Err(format!("{} is not supported by the test server", name)),
+		};
+		return match result
+		{
+			Ok(mut answer) =>
+			{
+				answer.extend(format!("{} OK {} completed\r\n", tag, name).into_bytes());
+				(answer, false)
+			},
+			Err(reason) if reason.starts_with("NO ") => (format!("{} {}\r\n", tag, reason).into_bytes(), false),
+			Err(reason) => (format!("{} BAD {}\r\n", tag, reason).into_bytes(), false),
+		};
+	}
+
+	fn login(&mut self, arguments: &str) -> Result<Vec<u8>, String>
+	{
+		let credentials = strings_parse(arguments);
+		if (credentials != [ImapTestServer::USERNAME, ImapTestServer::PASSWORD])
+		{
+			return Err("NO [AUTHENTICATIONFAILED] invalid credentials".to_string());
+		}
+		self.isAuthenticated = true;
+		return Ok(Vec::new());
+	}
+
+	fn list(&self) -> Vec<u8>
+	{
+		return self.model_get().mailboxes.keys()
+			.flat_map(|boxName| format!("* LIST (\\HasNoChildren) \"/\" {}\r\n", quoted_get(boxName)).into_bytes())
+			.collect();
+	}
+
+	fn select(&mut self, arguments: &str) -> Result<Vec<u8>, String>
+	{
+		let boxName = strings_parse(arguments).into_iter().next().unwrap_or_default();
+		self.selected = None;
+		let model = self.model_get();
+		let mailbox = model.mailboxes.get(&boxName)
+			.ok_or_else(|| "NO [NONEXISTENT] unknown mailbox".to_string())?;
+		let answer = format!(
+			concat!(
+				"* FLAGS (\\Answered \\Flagged \\Deleted \\Seen \\Draft)\r\n",
+				"* {} EXISTS\r\n",
+				"* OK [UIDVALIDITY {}] UIDs valid\r\n",
+				"* OK [UIDNEXT {}] predicted next UID\r\n",
+			),
+			mailbox.mails.len(),
+			mailbox.uidValidity,
+			mailbox.uidNext,
+		).into_bytes();
+		drop(model);
+		self.selected = Some(boxName);
+		return Ok(answer);
+	}
+
+	fn uidCommand_answer(&mut self, arguments: &str) -> Result<Vec<u8>, String>
+	{
+		let (name, arguments) = arguments.split_once(' ').unwrap_or((arguments, ""));
+		let boxName = self.selected.clone().ok_or_else(|| "no mailbox selected".to_string())?;
+		let mut model = self.model_get();
+		let mailbox = model.mailboxes.get_mut(&boxName)
+			.ok_or_else(|| "NO the selected mailbox was deleted".to_string())?;
+		return match name.to_ascii_uppercase().as_str()
+		{
+			"SEARCH" => ImapTestCommands::search(mailbox, arguments),
+			"FETCH" => ImapTestCommands::fetch(mailbox, arguments),
+			"STORE" => ImapTestCommands::store(mailbox, arguments),
+			_ => Err(format!("UID {} is not supported by the test server", name)),
+		};
+	}
+}
+
+struct ImapTestCommands;
+
+impl ImapTestCommands
+{
+	fn search(mailbox: &ImapTestMailbox, criteria: &str) -> Result<Vec<u8>, String>
+	{
+		let criteria = criteria.split_whitespace().collect::<Vec<_>>();
+		let mut uids = Vec::new();
+		for mail in &mailbox.mails
+		{
+			if (Self::search_matches(mail, &criteria)?)
+			{
+				uids.push(mail.uid.to_string());
+			}
+		}
+		let separator = if (uids.is_empty()) {""} else {" "};
+		return Ok(format!("* SEARCH{}{}\r\n", separator, uids.join(" ")).into_bytes());
+	}
+
+	/// the flag and UID criteria only, anything else is refused so that a test never passes on an ignored criterion
+	fn search_matches(mail: &ImapTestMail, criteria: &[&str]) -> Result<bool, String>
+	{
+		let hasFlag = |flag: &str| mail.flags.iter().any(|mailFlag| mailFlag.eq_ignore_ascii_case(flag));
+		let mut matches = true;
+		let mut criteria = criteria.iter();
+		while let Some(criterion) = criteria.next()
+		{
+			let (flag, isExpected) = match criterion.to_ascii_uppercase().as_str()
+			{
+				"ALL" => continue,
+				"UID" =>
+				{
+					let sequence = criteria.next().ok_or_else(|| "uid set missing".to_string())?;
+					matches &= sequence_parse(sequence, u32::MAX)?.iter().any(|(first, last)| (*first..=*last).contains(&mail.uid));
+					continue;
+				},
+				"KEYWORD" | "UNKEYWORD" =>
+				{
+					let keyword = criteria.next().ok_or_else(|| "keyword missing".to_string())?;
+					(keyword.to_string(), criterion.eq_ignore_ascii_case("KEYWORD"))
+				},
+				name =>
+				{
+					let (flag, isExpected) = match name.strip_prefix("UN")
+					{
+						Some(flag) => (flag, false),
+						None => (name, true),
+					};
+					if (!matches!(flag, "SEEN" | "ANSWERED" | "DELETED" | "DRAFT" | "FLAGGED"))
+					{
+						return Err(format!("search criterion {} is not supported by the test server", criterion));
+					}
+					(format!("\\{}", flag), isExpected)
+				},
+			};
+			matches &= hasFlag(&flag) == isExpected;
+		}
+		return Ok(matches);
+	}
+
+	fn fetch(mailbox: &mut ImapTestMailbox, arguments: &str) -> Result<Vec<u8>, String>
+	{
+		let (sequence, items) = arguments.split_once(' ').ok_or_else(|| "fetch items missing".to_string())?;
+		let items = items.trim();
+		let items = items.strip_prefix('(').and_then(|items| items.strip_suffix(')')).unwrap_or(items);
+		let items = items_split(items);
+		let uids = sequence_parse(sequence, mailbox.mails.iter().map(|mail| mail.uid).max().unwrap_or(0))?;
+		let mut answer = Vec::new();
+		for (index, mail) in mailbox.mails.iter_mut().enumerate()
+		{
+			if (!uids.iter().any(|(first, last)| (*first..=*last).contains(&mail.uid)))
+			{
+				continue;
+			}
+			answer.extend(format!("* {} FETCH (UID {}", index + 1, mail.uid).into_bytes());
+			for item in &items
+			{
+				answer.push(b' ');
+				answer.extend(Self::fetchItem_get(mail, item)?);
+			}
+			answer.extend(b")\r\n");
+		}
+		return Ok(answer);
+	}
+
+	fn fetchItem_get(mail: &mut ImapTestMail, item: &str) -> Result<Vec<u8>, String>
+	{
+		let upperItem = item.to_ascii_uppercase();
+		match upperItem.as_str()
+		{
+			"UID" => return Ok(format!("UID {}", mail.uid).into_bytes()),
+			"FLAGS" => return Ok(format!("FLAGS ({})", mail.flags.join(" ")).into_bytes()),
+			"INTERNALDATE" => return Ok(format!("INTERNALDATE \"{}\"", ImapTestServer::INTERNAL_DATE).into_bytes()),
+			"RFC822.SIZE" => return Ok(format!("RFC822.SIZE {}", mail.data.len()).into_bytes()),
+			"BODYSTRUCTURE" =>
+			{
+				let parsed = parse_mail(&mail.data).map_err(|error| error.to_string())?;
+				return Ok(format!("BODYSTRUCTURE {}", ImapTestStructure::get(&parsed)).into_bytes());
+			},
+			_ => {},
+		}
+		let (isPeek, section) = match (upperItem.strip_prefix("BODY.PEEK["), upperItem.strip_prefix("BODY["))
+		{
+			(Some(_), _) => (true, &item["BODY.PEEK[".len()..]),
+			(_, Some(_)) => (false, &item["BODY[".len()..]),
+			_ => return Err(format!("fetch item {} is not supported by the test server", item)),
+		};
+		let (section, partial) = section.split_once(']').ok_or_else(|| "section not closed".to_string())?;
+		let data = ImapTestStructure::section_get(&mail.data, section)?;
+		let (origin, data) = match partial.strip_prefix('<').and_then(|partial| partial.strip_suffix('>'))
+		{
+			Some(partial) =>
+			{
+				let (origin, length) = partial.split_once('.').ok_or_else(|| "partial length missing".to_string())?;
+				let origin = origin.parse::<usize>().map_err(|error| error.to_string())?.min(data.len());
+				let length = length.parse::<usize>().map_err(|error| error.to_string())?;
+				(Some(origin), data[origin..data.len().min(origin.saturating_add(length))].to_vec())
+			},
+			None => (None, data),
+		};
+		if (!isPeek && !mail.flags.iter().any(|flag| flag == "\\Seen"))
+		{
+			mail.flags.push("\\Seen".to_string());
+		}
+		let mut answer = format!("BODY[{}]", section).into_bytes();
+		if let Some(origin) = origin
+		{
+			answer.extend(format!("<{}>", origin).into_bytes());
+		}
+		answer.extend(format!(" {{{}}}\r\n", data.len()).into_bytes());
+		answer.extend(data);
+		return Ok(answer);
+	}
+
+	fn store(mailbox: &mut ImapTestMailbox, arguments: &str) -> Result<Vec<u8>, String>
+	{
+		let mut parts = arguments.splitn(3, ' ');
+		let (Some(sequence), Some(action), Some(flags)) = (parts.next(), parts.next(), parts.next())
+		else
+		{
+			return Err("store arguments missing".to_string());
+		};
+		let action = action.to_ascii_uppercase();
+		let isSilent = action.ends_with(".SILENT");
+		let flags = flags.trim_matches(['(', ')']).split_whitespace().map(str::to_string).collect::<Vec<_>>();
+		let uids = sequence_parse(sequence, mailbox.mails.iter().map(|mail| mail.uid).max().unwrap_or(0))?;
+		let mut answer = Vec::new();
+		for (index, mail) in mailbox.mails.iter_mut().enumerate()
+		{
+			if (!uids.iter().any(|(first, last)| (*first..=*last).contains(&mail.uid)))
+			{
+				continue;
+			}
+			match action.trim_end_matches(".SILENT")
+			{
+				"+FLAGS" => mail.flags.extend(flags.iter().filter(|flag| !mail.flags.contains(flag)).cloned().collect::<Vec<_>>()),
+				"-FLAGS" => mail.flags.retain(|flag| !flags.contains(flag)),
+				"FLAGS" => mail.flags = flags.clone(),
+				_ => return Err(format!("store action {} is not supported by the test server", action)),
+			}
+			if (!isSilent)
+			{
+				answer.extend(format!("* {} FETCH (UID {} FLAGS ({}))\r\n", index + 1, mail.uid, mail.flags.join(" ")).into_bytes());
+			}
+		}
+		return Ok(answer);
+	}
+}
+
+struct ImapTestStructure;
+
+impl ImapTestStructure
+{
+	fn get(part: &ParsedMail) -> String
+	{
+		let (mainType, subtype) = part.ctype.mimetype.split_once('/').unwrap_or(("text", "plain"));
+		if (mainType.eq_ignore_ascii_case("multipart"))
+		{
+			let children = part.subparts.iter().map(Self::get).collect::<String>();
+			return format!("({} {})", children, quoted_get(subtype));
+		}
+		let parameters = if (part.ctype.params.is_empty())
+		{
+			"NIL".to_string()
+		}
+		else
+		{
+			format!("({})", part.ctype.params.iter()
+				.map(|(name, value)| format!("{} {}", quoted_get(name), quoted_get(value)))
+				.collect::<Vec<_>>()
+				.join(" "))
+		};
+		let contentId = part.headers.get_first_value("Content-ID").map_or("NIL".to_string(), |id| quoted_get(&id));
+		let encoding = part.headers.get_first_value("Content-Transfer-Encoding").unwrap_or("7bit".to_string());
+		let body = Self::partBody_get(part.raw_bytes);
+		let mut fields = format!(
+			"{} {} {} {} NIL {} {}",
+			quoted_get(mainType),
+			quoted_get(subtype),
+			parameters,
+			contentId,
+			quoted_get(&encoding),
+			body.len(),
+		);
+		if (mainType.eq_ignore_ascii_case("text"))
+		{
+			fields.push_str(&format!(" {}", body.split(|byte| *byte == b'\n').count()));
+		}
+		let disposition = part.headers.get_first_value("Content-Disposition").map_or("NIL".to_string(), |_| {
+			let disposition = part.get_content_disposition();
+			let name = match &disposition.disposition
+			{
+				DispositionType::Inline => "inline".to_string(),
+				DispositionType::Attachment => "attachment".to_string(),
+				DispositionType::FormData => "form-data".to_string(),
+				DispositionType::Extension(name) => name.clone(),
+			};
+			let parameters = disposition.params.iter()
+				.map(|(name, value)| format!("{} {}", quoted_get(name), quoted_get(value)))
+				.collect::<Vec<_>>();
+			let parameters = if (parameters.is_empty()) {"NIL".to_string()} else {format!("({})", parameters.join(" "))};
+			return format!("({} {})", quoted_get(&name), parameters);
+		});
+		return format!("({} NIL {})", fields, disposition);
+	}
+
+	/// "", HEADER, HEADER.FIELDS (..), TEXT, or a part path with an optional MIME, HEADER or TEXT
+	fn section_get(data: &[u8], section: &str) -> Result<Vec<u8>, String>
+	{
+		let upperSection = section.to_ascii_uppercase();
+		if let Some(fields) = upperSection.strip_prefix("HEADER.FIELDS ")
+		{
+			let fields = fields.trim_matches(['(', ')']).split_whitespace().collect::<Vec<_>>();
+			return Ok(Self::headerFields_get(Self::partHeader_get(data), &fields));
+		}
+		let parsed = parse_mail(data).map_err(|error| error.to_string())?;
+		let mut part = &parsed;
+		let mut keyword = "";
+		for (index, number) in upperSection.split('.').enumerate().filter(|(_, number)| !number.is_empty())
+		{
+			let Ok(number) = number.parse::<usize>()
+			else
+			{
+				keyword = &section[upperSection.split('.').take(index).map(|number| number.len() + 1).sum::<usize>()..];
+				break;
+			};
+			if (part.subparts.is_empty() && number == 1 && std::ptr::eq(part, &parsed))
+			{
+				continue;
+			}
+			part = part.subparts.get(number.wrapping_sub(1)).ok_or_else(|| format!("no part {}", section))?;
+		}
+		return match keyword.to_ascii_uppercase().as_str()
+		{
+			"" => Ok(if (section.is_empty()) {data.to_vec()} else {Self::partBody_get(part.raw_bytes)}),
+			"HEADER" | "MIME" => Ok(Self::partHeader_get(part.raw_bytes).to_vec()),
+			"TEXT" => Ok(Self::partBody_get(part.raw_bytes)),
+			_ => Err(format!("section {} is not supported by the test server", section)),
+		};
+	}
+
+	fn partHeader_get(raw: &[u8]) -> &[u8]
+	{
+		let bodyStart = parse_headers(raw).map_or(raw.len(), |(_, bodyStart)| bodyStart);
+		return &raw[..bodyStart];
+	}
+
+	fn partBody_get(raw: &[u8]) -> Vec<u8>
+	{
+		return raw[Self::partHeader_get(raw).len()..].to_vec();
+	}
+
+	/// the header lines with their folded continuations, then the blank line
+	fn headerFields_get(header: &[u8], fields: &[&str]) -> Vec<u8>
+	{
+		let mut result = Vec::new();
+		let mut isKept = false;
+		for line in header.split_inclusive(|byte| *byte == b'\n')
+		{
+			if (line == b"\r\n" || line == b"\n")
+			{
+				break;
+			}
+			if (!line.starts_with(b" ") && !line.starts_with(b"\t"))
+			{
+				let name = line.split(|byte| *byte == b':').next().unwrap_or_default();
+				isKept = fields.iter().any(|field| field.as_bytes().eq_ignore_ascii_case(name));
+			}
+			if (isKept)
+			{
+				result.extend_from_slice(line);
+			}
+		}
+		result.extend_from_slice(b"\r\n");
+		return result;
+	}
+}
+
+fn quoted_get(value: &str) -> String
+{
+	return format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+/// atoms and quoted strings of a command, as LOGIN and SELECT send them
+fn strings_parse(arguments: &str) -> Vec<String>
+{
+	let mut strings = Vec::new();
+	let mut characters = arguments.chars().peekable();
+	while let Some(character) = characters.next()
+	{
+		match character
+		{
+			' ' => continue,
+			'"' =>
+			{
+				let mut value = String::new();
+				while let Some(character) = characters.next()
+				{
+					match character
+					{
+						'\\' => value.extend(characters.next()),
+						'"' => break,
+						_ => value.push(character),
+					}
+				}
+				strings.push(value);
+			},
+			_ =>
+			{
+				let mut value = character.to_string();
+				while let Some(character) = characters.next_if(|character| *character != ' ')
+				{
+					value.push(character);
+				}
+				strings.push(value);
+			},
+		}
+	}
+	return strings;
+}
+
+/// fetch items split on the spaces outside of their brackets and parentheses
+fn items_split(items: &str) -> Vec<&str>
+{
+	let mut result = Vec::new();
+	let mut depth = 0usize;
+	let mut start = 0;
+	for (index, character) in items.char_indices()
+	{
+		match character
+		{
+			'[' | '(' => depth += 1,
+			']' | ')' => depth = depth.saturating_sub(1),
+			' ' if depth == 0 =>
+			{
+				result.push(&items[start..index]);
+				start = index + 1;
+			},
+			_ => {},
+		}
+	}
+	result.push(&items[start..]);
+	return result.into_iter().filter(|item| !item.is_empty()).collect();
+}
+
+/// inclusive uid ranges of a sequence set, "*" being the highest uid
+fn sequence_parse(sequence: &str, highestUid: u32) -> Result<Vec<(u32, u32)>, String>
+{
+	let number_parse = |number: &str| -> Result<u32, String> {
+		if (number == "*")
+		{
+			return Ok(highestUid);
+		}
+		return number.parse::<u32>().map_err(|_| format!("invalid sequence {}", sequence));
+	};
+	return sequence.split(',').map(|range| {
+		let (first, last) = match range.split_once(':')
+		{
+			Some((first, last)) => (number_parse(first)?, number_parse(last)?),
+			None => (number_parse(range)?, number_parse(range)?),
+		};
+		return Ok((first.min(last), first.max(last)));
+	}).collect();
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime};
+
+use Htrace::HTrace;
+use axum::body::Body;
+use axum::extract::Query;
+use axum::http::header::{CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use base64ct::{Base64, Base64UrlUnpadded, Encoding};
+use reqwest::header::{ACCEPT, LOCATION};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use url::form_urlencoded;
+use url::Url;
+
+use crate::api::proxys::imap_components::MAIL_IMAGE_PROXY_PATH;
+use crate::api::proxys::imap_inner::ImapMailParser;
+use crate::api::proxys::outbound_policy::{OutboundPolicy, ValidatedHttpDestination};
+use crate::api::proxys::proxy_cache::ProxyCache;
+use crate::global_security::hash;
+
+struct MailImageLimits;
+
+impl MailImageLimits
+{
+	const BODY_MAXIMUM_BYTES: usize = 5 * 1024 * 1024;
+	const BROWSER_CACHE_SECONDS: u64 = 24 * 60 * 60;
+	const CACHE_ENTRY_MAXIMUM_BYTES: usize = Self::BODY_MAXIMUM_BYTES / 3 * 4 + 64 * 1024;
+	const CACHE_MAXIMUM_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+	const CACHE_MAXIMUM_BYTES: u64 = 128 * 1024 * 1024;
+	const CACHE_MAXIMUM_ENTRIES: usize = 1024;
+	const CACHE_TTL_SECONDS: u64 = 24 * 60 * 60;
+	// a mail is signed again each time it is opened, its addresses only need to outlive the reading
+	const SIGNATURE_LIFETIME_SECONDS: u64 = 24 * 60 * 60;
+	// expiries are rounded up to the hour, the same image keeps one address and its browser cache
+	const SIGNATURE_ROUNDING_SECONDS: u64 = 60 * 60;
+	const URL_MAXIMUM_BYTES: usize = 2048;
+}
+
+/// random for each server start: the signed addresses of the previous process stop working with it
+static MAIL_IMAGE_SIGNING_KEY: LazyLock<Option<[u8; 32]>> = LazyLock::new(|| {
+	let mut key = [0u8; 32];
+	return getrandom::fill(&mut key).ok().map(|_| key);
+});
+
+/// image of a mail, fetched by the server so the sender never sees the address nor the reading time of the user
+#[derive(Deserialize, Serialize)]
+struct MailImageRecord
+{
+	sourceUrl: String,
+	contentType: String,
+	// base64 of the image
+	content: String,
+	fetchedAt: u64,
+}
+
+pub(crate) struct MailImageProxy;
+
+impl MailImageProxy
+{
+	/// address of the proxy for a remote image of a sanitized mail, None for anything but an absolute http(s) URL
+	pub(super) fn url_sign(remoteUrl: &str) -> Option<String>
+	{
+		return Self::url_signAt(remoteUrl,Self::now_get());
+	}
+
+	/// the expiry is rounded up to the hour, an address signed again within that hour keeps its browser cache
+	fn url_signAt(remoteUrl: &str, now: u64) -> Option<String>
+	{
+		let remoteUrl = Self::remoteUrl_get(remoteUrl)?;
+		let expires = (now + MailImageLimits::SIGNATURE_LIFETIME_SECONDS).div_ceil(MailImageLimits::SIGNATURE_ROUNDING_SECONDS)
+			* MailImageLimits::SIGNATURE_ROUNDING_SECONDS;
+		let signature = Self::signature_get(&remoteUrl,expires)?;
+		let query = form_urlencoded::Serializer::new(String::new())
+			.append_pair("url",&remoteUrl)
+			.append_pair("expires",&expires.to_string())
+			.append_pair("signature",&signature)
+			.finish();
+		return Some(format!("{}?{}",MAIL_IMAGE_PROXY_PATH,query));
+	}
+
+	/// axum handler of MAIL_IMAGE_PROXY_PATH. The sandboxed mail frame sends no session cookie,
+	/// the signature proves the address comes from a mail read by an authenticated user
+	pub(crate) async fn response_get(Query(parameters): Query<HashMap<String, String>>) -> Response
+	{
+		let Some(remoteUrl) = Self::request_check(&parameters,Self::now_get())
+		else
+		{
+			return StatusCode::FORBIDDEN.into_response();
+		};
+		return match Self::image_get(&remoteUrl).await
+		{
+			Ok(image) => Self::imageResponse_get(image),
+			Err(status) => status.into_response(),
+		};
+	}
+
+	fn remoteUrl_get(value: &str) -> Option<String>
+	{
+		let mut url = Url::parse(value.trim()).ok()?;
+		if (!matches!(url.scheme(),"http" | "https") || url.host_str().is_none_or(str::is_empty))
+		{
+			return None;
+		}
+		url.set_fragment(None);
+		let url = url.to_string();
+		return (url.len() <= MailImageLimits::URL_MAXIMUM_BYTES).then_some(url);
+	}
+
+	fn request_check(parameters: &HashMap<String, String>, now: u64) -> Option<String>
+	{
+		let remoteUrl = parameters.get("url")?;
+		let expires = parameters.get("expires")?.parse::<u64>().ok()?;
+		let signature = Base64UrlUnpadded::decode_vec(parameters.get("signature")?).ok()?;
+		let lifetimeMaximum = MailImageLimits::SIGNATURE_LIFETIME_SECONDS + MailImageLimits::SIGNATURE_ROUNDING_SECONDS;
+		if (expires < now || expires - now > lifetimeMaximum || remoteUrl.len() > MailImageLimits::URL_MAXIMUM_BYTES)
+		{
+			return None;
+		}
+		let expected = Self::hmac_get(&(*MAIL_IMAGE_SIGNING_KEY)?,Self::signedMessage_get(remoteUrl,expires).as_bytes());
+		// constant time, a timing difference would let a forger find the signature byte by byte
+		let difference = expected.iter().zip(signature.iter()).fold(0u8,|difference,(left,right)| difference | (left ^ right));
+		return (signature.len() == expected.len() && difference == 0).then(|| remoteUrl.clone());
+	}
+
+	fn signature_get(remoteUrl: &str, expires: u64) -> Option<String>
+	{
+		let key = (*MAIL_IMAGE_SIGNING_KEY)?;
+		return Some(Base64UrlUnpadded::encode_string(&Self::hmac_get(&key,Self::signedMessage_get(remoteUrl,expires).as_bytes())));
+	}
+
+	fn signedMessage_get(remoteUrl: &str, expires: u64) -> String
+	{
+		return format!("{}\n{}",expires,remoteUrl);
+	}
+
+	/// HMAC-SHA256 (RFC 2104), for keys up to the block size
+	fn hmac_get(key: &[u8], message: &[u8]) -> [u8; 32]
+	{
+		let mut innerPad = [0x36u8; 64];
+		let mut outerPad = [0x5cu8; 64];
+		for (index,byte) in key.iter().take(64).enumerate()
+		{
+			innerPad[index] ^= byte;
+			outerPad[index] ^= byte;
+		}
+		let inner = Sha256::new().chain_update(innerPad).chain_update(message).finalize();
+		return Sha256::new().chain_update(outerPad).chain_update(inner).finalize().into();
+	}
+
+	async fn image_get(remoteUrl: &str) -> Result<MailImageRecord, StatusCode>
+	{
+		let cache = ProxyCache::get("mail_image").map_err(|error| {
+			HTrace!("[mail image proxy] cache unavailable: {}", error);
+			return StatusCode::INTERNAL_SERVER_ERROR;
+		})?;
+		let cacheKey = hash(remoteUrl.to_string());
+		if let Some(record) = Self::cacheRecord_get(&cache,&cacheKey,remoteUrl)
+		{
+			return Ok(record);
+		}
+
+		let _permit = OutboundPolicy::httpPermit_get().map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+		let destination = OutboundPolicy::httpDestination_get(remoteUrl).await.map_err(|_| StatusCode::FORBIDDEN)?;
+		let (contentType,content) = Self::fetch_get(destination).await?;
+		let record = MailImageRecord {
+			sourceUrl: remoteUrl.to_string(),
+			contentType: contentType.to_string(),
+			content: Base64::encode_string(&content),
+			fetchedAt: Self::now_get(),
+		};
+		Self::cacheRecord_save(&cache,&cacheKey,&record);
+		return Ok(record);
+	}
+
+	fn cacheRecord_get(cache: &ProxyCache, cacheKey: &str, remoteUrl: &str) -> Option<MailImageRecord>
+	{
+		let raw = cache.load(cacheKey,MailImageLimits::CACHE_ENTRY_MAXIMUM_BYTES).ok()??;
+		let record = serde_json::from_slice::<MailImageRecord>(&raw).ok();
+		let isFresh = record.as_ref().is_some_and(|record| {
+			return record.sourceUrl == remoteUrl
+				&& Self::now_get().saturating_sub(record.fetchedAt) <= MailImageLimits::CACHE_TTL_SECONDS;
+		});
+		if (!isFresh)
+		{
+			let _ = cache.remove(cacheKey);
+			return None;
+		}
+		return record;
+	}
+
+	fn cacheRecord_save(cache: &ProxyCache, cacheKey: &str, record: &MailImageRecord)
+	{
+		let result = serde_json::to_vec(record)
+			.map_err(|error| error.to_string())
+			.and_then(|raw| cache.save(cacheKey,&raw).map_err(|error| error.to_string()));
+		if let Err(error) = result
+		{
+			HTrace!("[mail image proxy] Cache save failed: {}", error);
+			return;
+		}
+		if let Err(error) = cache.cleanup(
+			MailImageLimits::CACHE_MAXIMUM_BYTES,
+			MailImageLimits::CACHE_MAXIMUM_ENTRIES,
+			MailImageLimits::CACHE_MAXIMUM_AGE,
+		)
+		{
+			HTrace!("[mail image proxy] Cache cleanup failed: {}", error);
+		}
+	}
+
+	/// the request carries no cookie nor referrer, only the raster types shown inline in mails are accepted
+	async fn fetch_get(mut destination: ValidatedHttpDestination) -> Result<(&'static str,Vec<u8>), StatusCode>
+	{
+		let mut redirectCount = 0;
+		let mut response = loop
+		{
+			let client = destination.client_get().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+			let response = client.get(destination.url_get().clone())
+				.header(ACCEPT,"image/*")
+				.send().await
+				.map_err(|_| StatusCode::BAD_GATEWAY)?;
+			if (!response.status().is_redirection())
+			{
+				break response;
+			}
+			if (redirectCount >= ValidatedHttpDestination::redirectMaximum_get())
+			{
+				return Err(StatusCode::FORBIDDEN);
+			}
+			let location = response.headers().get(LOCATION)
+				.and_then(|value| value.to_str().ok())
+				.ok_or(StatusCode::BAD_GATEWAY)?
+				.to_string();
+			destination = destination.redirected_get(&location).await.map_err(|_| StatusCode::FORBIDDEN)?;
+			redirectCount += 1;
+		};
+		if (response.status() != reqwest::StatusCode::OK)
+		{
+			return Err(StatusCode::BAD_GATEWAY);
+		}
+		let contentType = response.headers().get(reqwest::header::CONTENT_TYPE)
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| ImapMailParser::inlineMime_get(value.split(';').next().unwrap_or_default()))
+			.ok_or(StatusCode::UNSUPPORTED_MEDIA_TYPE)?;
+		if (response.content_length().is_some_and(|length| length > MailImageLimits::BODY_MAXIMUM_BYTES as u64))
+		{
+			return Err(StatusCode::PAYLOAD_TOO_LARGE);
+		}
+		let mut content = Vec::new();
+		while let Some(chunk) = response.chunk().await.map_err(|_| StatusCode::BAD_GATEWAY)?
+		{
+			if (chunk.len() > MailImageLimits::BODY_MAXIMUM_BYTES.saturating_sub(content.len()))
+			{
+				return Err(StatusCode::PAYLOAD_TOO_LARGE);
+			}
+			content.extend_from_slice(&chunk);
+		}
+		return Ok((contentType,content));
+	}
+
+	fn imageResponse_get(record: MailImageRecord) -> Response
+	{
+		let Ok(content) = Base64::decode_vec(&record.content) else {return StatusCode::INTERNAL_SERVER_ERROR.into_response()};
+		let Ok(contentType) = HeaderValue::from_str(&record.contentType) else {return StatusCode::INTERNAL_SERVER_ERROR.into_response()};
+		let mut response = Response::new(Body::from(content));
+		let headers = response.headers_mut();
+		headers.insert(CONTENT_TYPE,contentType);
+		headers.insert(CONTENT_DISPOSITION,HeaderValue::from_static("inline"));
+		if let Ok(cacheControl) = HeaderValue::from_str(&format!("private, max-age={}",MailImageLimits::BROWSER_CACHE_SECONDS))
+		{
+			headers.insert(CACHE_CONTROL,cacheControl);
+		}
+		return response;
+	}
+
+	fn now_get() -> u64
+	{
+		return SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::collections::HashMap;
+
+	use url::form_urlencoded;
+
+	use super::{MailImageLimits, MailImageProxy};
+	use crate::api::proxys::imap_components::MAIL_IMAGE_PROXY_PATH;
+
+	fn parameters_get(proxyUrl: &str) -> HashMap<String, String>
+	{
+		let query = proxyUrl.strip_prefix(MAIL_IMAGE_PROXY_PATH).and_then(|query| query.strip_prefix('?')).unwrap();
+		return form_urlencoded::parse(query.as_bytes()).into_owned().collect();
+	}
+
+	#[test]
+	fn hmac_matchesTheRfc4231Vector()
+	{
+		let mac = MailImageProxy::hmac_get(b"Jefe",b"what do ya want for nothing?");
+		let hex = mac.iter().map(|byte| format!("{:02x}",byte)).collect::<String>();
+		assert_eq!(hex,"5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+	}
+
+	#[test]
+	fn signedUrl_onlyAcceptsAbsoluteHttpImages()
+	{
+		assert!(MailImageProxy::url_sign("https://tracker.example/pixel.gif#top").unwrap().starts_with("/api/mail_image?url=https%3A%2F%2Ftracker.example%2Fpixel.gif&"));
+		assert!(MailImageProxy::url_sign("http://images.example/logo.png").is_some());
+		assert_eq!(MailImageProxy::url_sign("/local/image.png"),None);
+		assert_eq!(MailImageProxy::url_sign("file:///etc/passwd"),None);
+		assert_eq!(MailImageProxy::url_sign("javascript:alert(1)"),None);
+		assert_eq!(MailImageProxy::url_sign(&format!("https://images.example/{}","a".repeat(MailImageLimits::URL_MAXIMUM_BYTES))),None);
+	}
+
+	#[test]
+	fn signedUrl_isCheckedWithItsAddressAndExpiry()
+	{
+		// a few minutes into an hour
+		let hour = 497_888 * MailImageLimits::SIGNATURE_ROUNDING_SECONDS;
+		let now = hour + 100;
+		let proxyUrl = MailImageProxy::url_signAt("https://images.example/logo.png",now).unwrap();
+		let parameters = parameters_get(&proxyUrl);
+		let expires = parameters["expires"].parse::<u64>().unwrap();
+		assert_eq!(expires,hour + MailImageLimits::SIGNATURE_LIFETIME_SECONDS + MailImageLimits::SIGNATURE_ROUNDING_SECONDS);
+		assert_eq!(MailImageProxy::request_check(&parameters,now).as_deref(),Some("https://images.example/logo.png"));
+		// the same address later in the hour keeps its browser cache, the next hour gets a new expiry
+		assert_eq!(MailImageProxy::url_signAt("https://images.example/logo.png",now + 600).unwrap(),proxyUrl);
+		assert_ne!(MailImageProxy::url_signAt("https://images.example/logo.png",hour + MailImageLimits::SIGNATURE_ROUNDING_SECONDS + 1).unwrap(),proxyUrl);
+
+		assert_eq!(MailImageProxy::request_check(&parameters,expires + 1),None);
+
+		let mut otherImage = parameters.clone();
+		otherImage.insert("url".to_string(),"https://images.example/other.png".to_string());
+		assert_eq!(MailImageProxy::request_check(&otherImage,now),None);
+
+		let mut laterExpiry = parameters.clone();
+		laterExpiry.insert("expires".to_string(),(expires + 60).to_string());
+		assert_eq!(MailImageProxy::request_check(&laterExpiry,now),None);
+
+		let mut truncated = parameters.clone();
+		truncated.get_mut("signature").unwrap().pop();
+		assert_eq!(MailImageProxy::request_check(&truncated,now),None);
+		assert_eq!(MailImageProxy::request_check(&HashMap::new(),now),None);
+	}
+}
@@ -7,4 +7,14 @@ pub mod imap_components;
 #[cfg(feature = "ssr")]
 mod imap_inner;
 #[cfg(feature = "ssr")]
-mod outbound_policy;
+pub(crate) mod imap_oauth;
+#[cfg(feature = "ssr")]
+pub(crate) mod mail_image;
+#[cfg(feature = "ssr")]
+pub(crate) mod outbound_policy;
+#[cfg(all(test, feature = "ssr"))]
+mod imap_test_server;
+pub mod smtp;
+pub mod smtp_components;
+#[cfg(feature = "ssr")]
+mod smtp_inner;
@@ -1,16 +1,24 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 use Hconfig::HConfigManager::HConfigManager;
 use Hconfig::tinyjson::JsonValue;
 use async_lock::{Semaphore, SemaphoreGuard};
 use imap::Connection;
+use leptos_axum::extract;
+use lettre::transport::smtp::client::{SmtpConnection, TlsParameters};
+use lettre::transport::smtp::extension::ClientId;
 use native_tls::TlsConnector;
 use reqwest::redirect::Policy;
 use reqwest::Client;
+use tower_sessions::Session;
 use url::{Host, Url};
 
 use crate::api::login::user_back::{AuthenticatedUser, UserBackHelperError};
+use crate::api::proxys::imap_components::ImapConnectionSecurity;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) enum OutboundPolicyError
@@ -35,36 +43,85 @@ impl OutboundLimits
 	const HTTP_REDIRECT_MAX: usize = 5;
 	const IMAP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 	const IMAP_CONCURRENCY_MAX: usize = 4;
+	const IMAP_IDLE_CONCURRENCY_MAX: usize = 16;
+	// IDLE connections of one account, for its mail modules and its open tabs
+	const IMAP_IDLE_ACCOUNT_MAX: usize = 2;
 	const IMAP_IO_TIMEOUT: Duration = Duration::from_secs(30);
+	const IMAP_PLAIN_LINE_MAX: usize = 8 * 1024;
+	const SMTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+	const SMTP_CONCURRENCY_MAX: usize = 2;
+	const SMTP_IO_TIMEOUT: Duration = Duration::from_secs(60);
 }
 
+/// site.json key allowing IMAP toward loopback addresses, without encryption only toward them
+pub(crate) const IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY: &str = "imap_plaintext_loopback";
+
 static HTTP_CONCURRENCY: Semaphore = Semaphore::new(OutboundLimits::HTTP_CONCURRENCY_MAX);
 static IMAP_CONCURRENCY: Semaphore = Semaphore::new(OutboundLimits::IMAP_CONCURRENCY_MAX);
+static SMTP_CONCURRENCY: Semaphore = Semaphore::new(OutboundLimits::SMTP_CONCURRENCY_MAX);
+// IDLE connections are long-lived, they never take a permit of the short IMAP requests
+static IMAP_IDLE_CONCURRENCY: Semaphore = Semaphore::new(OutboundLimits::IMAP_IDLE_CONCURRENCY_MAX);
+static IMAP_IDLE_ACCOUNTS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
 
-#[derive(Debug)]
-struct ImapAllowedPorts(Vec<u16>);
+/// DER root certificates of the IMAP servers started by the tests, trusted with the system ones
+#[cfg(test)]
+static IMAP_TEST_ROOT_CERTIFICATES: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
 
-impl ImapAllowedPorts
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum MailProtocol
 {
-	const CONFIG_KEY: &'static str = "imap_allowed_ports";
-	const DEFAULT_PORT: u16 = 993;
+	IMAP,
+	SMTP,
+}
+
+impl MailProtocol
+{
+	fn configKey_get(self) -> &'static str
+	{
+		return match self
+		{
+			Self::IMAP => "imap_allowed_ports",
+			Self::SMTP => "smtp_allowed_ports",
+		};
+	}
+
+	fn defaultPorts_get(self) -> &'static [u16]
+	{
+		return match self
+		{
+			Self::IMAP => &[993],
+			// implicit TLS, then STARTTLS submission
+			Self::SMTP => &[465, 587],
+		};
+	}
+}
 
-	fn current_get() -> Result<Self, OutboundPolicyError>
+#[derive(Debug)]
+struct MailAllowedPorts(Vec<u16>);
+
+impl MailAllowedPorts
+{
+	fn current_get(protocol: MailProtocol) -> Result<Self, OutboundPolicyError>
 	{
 		let Some(siteConfig) = HConfigManager::singleton().get("site")
 		else
 		{
-			return Ok(Self::default());
+			return Ok(Self::default_get(protocol));
 		};
-		return Self::fromConfigValue(siteConfig.value_get(Self::CONFIG_KEY));
+		return Self::fromConfigValue(protocol, siteConfig.value_get(protocol.configKey_get()));
 	}
 
-	fn fromConfigValue(value: Option<JsonValue>) -> Result<Self, OutboundPolicyError>
+	fn default_get(protocol: MailProtocol) -> Self
+	{
+		return Self(protocol.defaultPorts_get().to_vec());
+	}
+
+	fn fromConfigValue(protocol: MailProtocol, value: Option<JsonValue>) -> Result<Self, OutboundPolicyError>
 	{
 		let Some(value) = value
 		else
 		{
-			return Ok(Self::default());
+			return Ok(Self::default_get(protocol));
 		};
 		let JsonValue::Array(values) = value
 		else
@@ -100,14 +157,6 @@ impl ImapAllowedPorts
 	}
 }
 
-impl Default for ImapAllowedPorts
-{
-	fn default() -> Self
-	{
-		return Self(vec![Self::DEFAULT_PORT]);
-	}
-}
-
 #[derive(Clone, Debug)]
 pub(super) struct ValidatedHttpDestination
 {
@@ -158,11 +207,85 @@ pub(super) struct ValidatedImapDestination
 {
 	addresses: Vec<SocketAddr>,
 	host: String,
+	security: ImapConnectionSecurity,
 }
 
 impl ValidatedImapDestination
 {
-	pub(super) fn connection_get(&self) -> Result<Connection, imap::Error>
+	/// the boolean tells that the server greeting was already read, STARTTLS needs it before the upgrade
+	pub(super) fn connection_get(&self) -> Result<(Connection, bool), imap::Error>
+	{
+		let stream = self.tcpStream_get()?;
+		return match self.security
+		{
+			ImapConnectionSecurity::TLS => Ok((Box::new(self.tls_start(stream)?), false)),
+			ImapConnectionSecurity::STARTTLS =>
+			{
+				let stream = Self::startTls_negotiate(stream)?;
+				Ok((Box::new(self.tls_start(stream)?), true))
+			},
+			ImapConnectionSecurity::NONE => Ok((Box::new(stream), false)),
+		};
+	}
+
+	fn tls_start(&self, stream: TcpStream) -> Result<native_tls::TlsStream<TcpStream>, imap::Error>
+	{
+		#[cfg_attr(not(test), allow(unused_mut))]
+		let mut builder = TlsConnector::builder();
+		#[cfg(test)]
+		for certificate in IMAP_TEST_ROOT_CERTIFICATES.lock().unwrap_or_else(|error| error.into_inner()).iter()
+		{
+			builder.add_root_certificate(native_tls::Certificate::from_der(certificate)?);
+		}
+		let connector = builder.build()?;
+		return Ok(connector.connect(&self.host, stream)?);
+	}
+
+	/// never falls back to plain text: a server without STARTTLS is refused.
+	/// Bytes sent by the server after its STARTTLS answer are dropped with the plain text buffer
+	fn startTls_negotiate<T: Read + Write>(stream: T) -> Result<T, imap::Error>
+	{
+		let mut client = imap::Client::new(stream);
+		client.read_greeting()?;
+		if (!client.capabilities()?.has_str("STARTTLS"))
+		{
+			return Err(imap::Error::StartTlsNotAvailable);
+		}
+		// the client keeps its command runner private before the login, the capability query used the tag a1
+		let mut stream = client.into_inner()?;
+		stream.write_all(b"a2 STARTTLS\r\n")?;
+		stream.flush()?;
+		loop
+		{
+			let line = Self::line_read(&mut stream)?;
+			if let Some(status) = line.strip_prefix(b"a2 ")
+			{
+				if (status.len() >= 2 && status[..2].eq_ignore_ascii_case(b"OK"))
+				{
+					return Ok(stream);
+				}
+				return Err(imap::Error::StartTlsNotAvailable);
+			}
+		}
+	}
+
+	/// byte by byte, so nothing of the following TLS handshake is buffered
+	fn line_read<T: Read>(stream: &mut T) -> Result<Vec<u8>, imap::Error>
+	{
+		let mut line = Vec::new();
+		let mut byte = [0u8; 1];
+		while (!line.ends_with(b"\r\n"))
+		{
+			if (line.len() >= OutboundLimits::IMAP_PLAIN_LINE_MAX || stream.read(&mut byte)? == 0)
+			{
+				return Err(imap::Error::ConnectionLost);
+			}
+			line.push(byte[0]);
+		}
+		return Ok(line);
+	}
+
+	fn tcpStream_get(&self) -> Result<TcpStream, imap::Error>
 	{
 		let deadline = Instant::now() + OutboundLimits::IMAP_CONNECT_TIMEOUT;
 		let mut lastError = None;
@@ -179,9 +302,7 @@ impl ValidatedImapDestination
 				{
 					stream.set_read_timeout(Some(OutboundLimits::IMAP_IO_TIMEOUT))?;
 					stream.set_write_timeout(Some(OutboundLimits::IMAP_IO_TIMEOUT))?;
-					let connector = TlsConnector::builder().build()?;
-					let stream = connector.connect(&self.host, stream)?;
-					return Ok(Box::new(stream));
+					return Ok(stream);
 				},
 				Err(error) => lastError = Some(error),
 			}
@@ -195,7 +316,98 @@ impl ValidatedImapDestination
 	#[cfg(test)]
 	pub(super) fn test_get(host: String, addresses: Vec<SocketAddr>) -> Self
 	{
-		return Self { addresses, host };
+		return Self { addresses, host, security: ImapConnectionSecurity::TLS };
+	}
+}
+
+pub(super) struct ValidatedSmtpDestination
+{
+	addresses: Vec<SocketAddr>,
+	host: String,
+	port: u16,
+}
+
+impl ValidatedSmtpDestination
+{
+	// the only port where TLS starts with the connection, every other allowed port must upgrade with STARTTLS
+	const IMPLICIT_TLS_PORT: u16 = 465;
+
+	/// never falls back to plain text: a server without STARTTLS is refused
+	pub(super) fn connection_get(&self) -> Result<SmtpConnection, lettre::transport::smtp::Error>
+	{
+		let tlsParameters = TlsParameters::new(self.host.clone())?;
+		let helloName = ClientId::default();
+		let isImplicitTls = self.port == Self::IMPLICIT_TLS_PORT;
+		let deadline = Instant::now() + OutboundLimits::SMTP_CONNECT_TIMEOUT;
+		let mut lastError = None;
+		for address in &self.addresses
+		{
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			if (remaining.is_zero())
+			{
+				break;
+			}
+			let connection = SmtpConnection::connect(
+				*address,
+				Some(remaining),
+				&helloName,
+				isImplicitTls.then_some(&tlsParameters),
+				None,
+			);
+			match connection
+			{
+				Ok(mut connection) =>
+				{
+					// the connect timeout stays in place if the socket refuses the longer one
+					let _ = connection.set_timeout(Some(OutboundLimits::SMTP_IO_TIMEOUT));
+					if (!isImplicitTls)
+					{
+						if let Err(error) = connection.starttls(&tlsParameters, &helloName)
+						{
+							connection.abort();
+							return Err(error);
+						}
+					}
+					return Ok(connection);
+				},
+				Err(error) => lastError = Some(error),
+			}
+		}
+		return match lastError
+		{
+			Some(error) => Err(error),
+			// lettre keeps its error constructors private, an empty address list yields its own connection error
+			None => SmtpConnection::connect(Vec::<SocketAddr>::new().as_slice(), None, &helloName, None, None),
+		};
+	}
+
+	#[cfg(test)]
+	pub(super) fn test_get(host: String, port: u16, addresses: Vec<SocketAddr>) -> Self
+	{
+		return Self { addresses, host, port };
+	}
+}
+
+/// permit of one IMAP IDLE connection, counted in the global IDLE limit and in the limit of its account
+pub(super) struct ImapIdlePermit
+{
+	_global: SemaphoreGuard<'static>,
+	account: String,
+}
+
+impl Drop for ImapIdlePermit
+{
+	fn drop(&mut self)
+	{
+		let Ok(mut accounts) = IMAP_IDLE_ACCOUNTS.lock() else {return};
+		if let Some(count) = accounts.get_mut(&self.account)
+		{
+			*count = count.saturating_sub(1);
+			if (*count == 0)
+			{
+				accounts.remove(&self.account);
+			}
+		}
 	}
 }
 
@@ -213,15 +425,57 @@ impl OutboundPolicy
 		return IMAP_CONCURRENCY.try_acquire().ok_or(OutboundPolicyError::ResourceLimitReached);
 	}
 
-	pub(super) async fn authentication_require() -> Result<(), OutboundPolicyError>
+	pub(super) fn imapIdlePermit_get(account: &str) -> Result<ImapIdlePermit, OutboundPolicyError>
+	{
+		let global = IMAP_IDLE_CONCURRENCY.try_acquire().ok_or(OutboundPolicyError::ResourceLimitReached)?;
+		let mut accounts = IMAP_IDLE_ACCOUNTS.lock().map_err(|_| OutboundPolicyError::Internal)?;
+		let count = accounts.entry(account.to_string()).or_insert(0);
+		if (*count >= OutboundLimits::IMAP_IDLE_ACCOUNT_MAX)
+		{
+			return Err(OutboundPolicyError::ResourceLimitReached);
+		}
+		*count += 1;
+		return Ok(ImapIdlePermit { _global: global, account: account.to_string() });
+	}
+
+	pub(super) fn smtpPermit_get() -> Result<SemaphoreGuard<'static>, OutboundPolicyError>
+	{
+		return SMTP_CONCURRENCY.try_acquire().ok_or(OutboundPolicyError::ResourceLimitReached);
+	}
+
+	/// client of the endpoints set by the site administrator in site.json, they are not user destinations
+	pub(super) fn configuredHttpClient_get() -> Result<Client, OutboundPolicyError>
+	{
+		return Client::builder()
+			.connect_timeout(OutboundLimits::HTTP_CONNECT_TIMEOUT)
+			.timeout(OutboundLimits::HTTP_REQUEST_TIMEOUT)
+			.redirect(Policy::none())
+			.build()
+			.map_err(|_| OutboundPolicyError::Internal);
+	}
+
+	pub(super) async fn authentication_require() -> Result<AuthenticatedUser, OutboundPolicyError>
 	{
 		return AuthenticatedUser::current().await
-			.map(|_| ())
-			.map_err(|error| match error
-			{
-				UserBackHelperError::LoginError(_) => OutboundPolicyError::AuthenticationRequired,
-				_ => OutboundPolicyError::Internal,
-			});
+			.map_err(Self::authenticationError_get);
+	}
+
+	/// authenticated user with its session, for the long-lived requests which must end with the session
+	pub(super) async fn sessionAuthentication_require() -> Result<(Session, AuthenticatedUser), OutboundPolicyError>
+	{
+		let session = extract::<Session>().await.map_err(|_| OutboundPolicyError::Internal)?;
+		let (user, _) = AuthenticatedUser::fromSessionWithConfig(&session).await
+			.map_err(Self::authenticationError_get)?;
+		return Ok((session, user));
+	}
+
+	fn authenticationError_get(error: UserBackHelperError) -> OutboundPolicyError
+	{
+		return match error
+		{
+			UserBackHelperError::LoginError(_) => OutboundPolicyError::AuthenticationRequired,
+			_ => OutboundPolicyError::Internal,
+		};
 	}
 
 	pub(super) async fn httpDestination_get(rawUrl: &str) -> Result<ValidatedHttpDestination, OutboundPolicyError>
@@ -247,21 +501,122 @@ impl OutboundPolicy
 		return Ok(ValidatedHttpDestination { addresses, host, url });
 	}
 
-	pub(super) async fn imapDestination_get(host: &str, port: u16) -> Result<ValidatedImapDestination, OutboundPolicyError>
+	/// without encryption, the destination must only resolve to loopback addresses and the site must allow it.
+	/// With encryption, a site allowing them also accepts a destination resolving to loopback addresses only
+	pub(super) async fn imapDestination_get(host: &str, port: u16, security: ImapConnectionSecurity) -> Result<ValidatedImapDestination, OutboundPolicyError>
+	{
+		let isLoopbackAllowed = Self::imapPlaintextFromConfigValue(Self::siteValue_get(IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY))?;
+		let addresses = if (security == ImapConnectionSecurity::NONE)
+		{
+			if (!isLoopbackAllowed)
+			{
+				return Err(OutboundPolicyError::DestinationForbidden);
+			}
+			Self::mailAddresses_check(MailProtocol::IMAP, host, port)?;
+			Self::loopbackAddresses_get(host, port).await?
+		}
+		else if (isLoopbackAllowed)
+		{
+			Self::mailAddresses_check(MailProtocol::IMAP, host, port)?;
+			Self::publicOrLoopbackAddresses_get(host, port).await?
+		}
+		else
+		{
+			Self::mailAddresses_get(MailProtocol::IMAP, host, port).await?
+		};
+		return Ok(ValidatedImapDestination { addresses, host: host.to_string(), security });
+	}
+
+	/// the certificate of a test server is trusted by every IMAP connection until it is forgotten
+	#[cfg(test)]
+	pub(super) fn imapTestRootCertificate_add(certificate: Vec<u8>)
+	{
+		IMAP_TEST_ROOT_CERTIFICATES.lock().unwrap_or_else(|error| error.into_inner()).push(certificate);
+	}
+
+	#[cfg(test)]
+	pub(super) fn imapTestRootCertificate_forget(certificate: &[u8])
+	{
+		IMAP_TEST_ROOT_CERTIFICATES.lock().unwrap_or_else(|error| error.into_inner()).retain(|trusted| trusted != certificate);
+	}
+
+	pub(super) async fn smtpDestination_get(host: &str, port: u16) -> Result<ValidatedSmtpDestination, OutboundPolicyError>
+	{
+		let addresses = Self::mailAddresses_get(MailProtocol::SMTP, host, port).await?;
+		return Ok(ValidatedSmtpDestination { addresses, host: host.to_string(), port });
+	}
+
+	async fn mailAddresses_get(protocol: MailProtocol, host: &str, port: u16) -> Result<Vec<SocketAddr>, OutboundPolicyError>
+	{
+		Self::mailAddresses_check(protocol, host, port)?;
+		return Self::addresses_get(host, port).await;
+	}
+
+	fn mailAddresses_check(protocol: MailProtocol, host: &str, port: u16) -> Result<(), OutboundPolicyError>
 	{
 		if (host.is_empty() || host.len() > 253 || host.trim() != host || host.contains('/') || host.contains('@'))
 		{
 			return Err(OutboundPolicyError::DestinationForbidden);
 		}
-		if (!ImapAllowedPorts::current_get()?.contains(port))
+		if (!MailAllowedPorts::current_get(protocol)?.contains(port))
+		{
+			return Err(OutboundPolicyError::DestinationForbidden);
+		}
+		return Ok(());
+	}
+
+	fn siteValue_get(key: &str) -> Option<JsonValue>
+	{
+		return HConfigManager::singleton().get("site")?.value_get(key);
+	}
+
+	fn imapPlaintextFromConfigValue(value: Option<JsonValue>) -> Result<bool, OutboundPolicyError>
+	{
+		return match value
+		{
+			None => Ok(false),
+			Some(JsonValue::Boolean(isAllowed)) => Ok(isAllowed),
+			Some(_) => Err(OutboundPolicyError::ConfigurationInvalid),
+		};
+	}
+
+	/// the plain text counterpart of addresses_get: every address must be a loopback one
+	async fn loopbackAddresses_get(host: &str, port: u16) -> Result<Vec<SocketAddr>, OutboundPolicyError>
+	{
+		let addresses = Self::addresses_resolve(host, port).await?;
+		if (addresses.iter().any(|address| !address.ip().to_canonical().is_loopback()))
+		{
+			return Err(OutboundPolicyError::DestinationForbidden);
+		}
+		return Ok(addresses);
+	}
+
+	/// every address must be a public one, or every address a loopback one
+	async fn publicOrLoopbackAddresses_get(host: &str, port: u16) -> Result<Vec<SocketAddr>, OutboundPolicyError>
+	{
+		let addresses = Self::addresses_resolve(host, port).await?;
+		if (addresses.iter().all(|address| address.ip().to_canonical().is_loopback()))
+		{
+			return Ok(addresses);
+		}
+		if (addresses.iter().any(|address| !Self::address_isPublic(address.ip())))
 		{
 			return Err(OutboundPolicyError::DestinationForbidden);
 		}
-		let addresses = Self::addresses_get(host, port).await?;
-		return Ok(ValidatedImapDestination { addresses, host: host.to_string() });
+		return Ok(addresses);
 	}
 
 	async fn addresses_get(host: &str, port: u16) -> Result<Vec<SocketAddr>, OutboundPolicyError>
+	{
+		let addresses = Self::addresses_resolve(host, port).await?;
+		if (addresses.iter().any(|address| !Self::address_isPublic(address.ip())))
+		{
+			return Err(OutboundPolicyError::DestinationForbidden);
+		}
+		return Ok(addresses);
+	}
+
+	async fn addresses_resolve(host: &str, port: u16) -> Result<Vec<SocketAddr>, OutboundPolicyError>
 	{
 		let mut addresses = if let Ok(address) = host.parse::<IpAddr>()
 		{
@@ -282,10 +637,6 @@ impl OutboundPolicy
 		{
 			return Err(OutboundPolicyError::ResolutionFailed);
 		}
-		if (addresses.iter().any(|address| !Self::address_isPublic(address.ip())))
-		{
-			return Err(OutboundPolicyError::DestinationForbidden);
-		}
 		return Ok(addresses);
 	}
 
@@ -329,12 +680,28 @@ impl OutboundPolicy
 #[cfg(test)]
 mod tests
 {
+	use crate::api::login::user_back::test_support::ConfigPathGuard;
+
 	use super::*;
 
+	#[test]
+	fn imapIdlePermit_limitsEachAccountAndIsReleasedOnDrop()
+	{
+		let first = OutboundPolicy::imapIdlePermit_get("idle-account-a").unwrap();
+		let second = OutboundPolicy::imapIdlePermit_get("idle-account-a").unwrap();
+		assert_eq!(OutboundPolicy::imapIdlePermit_get("idle-account-a").err(), Some(OutboundPolicyError::ResourceLimitReached));
+		let other = OutboundPolicy::imapIdlePermit_get("idle-account-b").unwrap();
+
+		drop(first);
+		let third = OutboundPolicy::imapIdlePermit_get("idle-account-a").unwrap();
+		drop((second, third, other));
+		assert!(!IMAP_IDLE_ACCOUNTS.lock().unwrap().contains_key("idle-account-a"));
+	}
+
 	#[test]
 	fn imapAllowedPorts_defaultsToTlsPort()
 	{
-		let ports = ImapAllowedPorts::fromConfigValue(None).unwrap();
+		let ports = MailAllowedPorts::fromConfigValue(MailProtocol::IMAP, None).unwrap();
 		assert!(ports.contains(993));
 		assert!(!ports.contains(143));
 	}
@@ -343,15 +710,15 @@ mod tests
 	fn imapAllowedPorts_requiresNonEmptyIntegerArray()
 	{
 		assert_eq!(
-			ImapAllowedPorts::fromConfigValue(Some(JsonValue::Array(vec![]))).unwrap_err(),
+			MailAllowedPorts::fromConfigValue(MailProtocol::IMAP, Some(JsonValue::Array(vec![]))).unwrap_err(),
 			OutboundPolicyError::ConfigurationInvalid,
 		);
 		assert_eq!(
-			ImapAllowedPorts::fromConfigValue(Some(JsonValue::Array(vec![993.5.into()]))).unwrap_err(),
+			MailAllowedPorts::fromConfigValue(MailProtocol::IMAP, Some(JsonValue::Array(vec![993.5.into()]))).unwrap_err(),
 			OutboundPolicyError::ConfigurationInvalid,
 		);
 		assert_eq!(
-			ImapAllowedPorts::fromConfigValue(Some(JsonValue::String("993".to_string()))).unwrap_err(),
+			MailAllowedPorts::fromConfigValue(MailProtocol::IMAP, Some(JsonValue::String("993".to_string()))).unwrap_err(),
 			OutboundPolicyError::ConfigurationInvalid,
 		);
 	}
@@ -359,7 +726,7 @@ mod tests
 	#[test]
 	fn imapAllowedPorts_acceptsAndDeduplicatesConfiguredPorts()
 	{
-		let ports = ImapAllowedPorts::fromConfigValue(Some(JsonValue::Array(vec![
+		let ports = MailAllowedPorts::fromConfigValue(MailProtocol::IMAP, Some(JsonValue::Array(vec![
 			993.0.into(),
 			1993.0.into(),
 			993.0.into(),
@@ -369,6 +736,158 @@ mod tests
 		assert_eq!(ports.0.len(), 2);
 	}
 
+	#[test]
+	fn smtpAllowedPorts_defaultsToSubmissionPorts()
+	{
+		let ports = MailAllowedPorts::fromConfigValue(MailProtocol::SMTP, None).unwrap();
+		assert!(ports.contains(465));
+		assert!(ports.contains(587));
+		assert!(!ports.contains(25));
+		assert!(!MailAllowedPorts::fromConfigValue(MailProtocol::IMAP, None).unwrap().contains(587));
+	}
+
+	#[test]
+	fn smtpDestination_rejectsPortsOutsideOfTheSmtpSetting()
+	{
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		runtime.block_on(async {
+			assert_eq!(
+				OutboundPolicy::smtpDestination_get("8.8.8.8", 25).await.err(),
+				Some(OutboundPolicyError::DestinationForbidden),
+			);
+			assert_eq!(
+				OutboundPolicy::smtpDestination_get("127.0.0.1", 465).await.err(),
+				Some(OutboundPolicyError::DestinationForbidden),
+			);
+			assert!(OutboundPolicy::smtpDestination_get("8.8.8.8", 587).await.is_ok());
+		});
+	}
+
+	struct ScriptedStream
+	{
+		input: std::io::Cursor<Vec<u8>>,
+		output: Vec<u8>,
+	}
+
+	impl ScriptedStream
+	{
+		fn new(input: &str) -> Self
+		{
+			return Self { input: std::io::Cursor::new(input.as_bytes().to_vec()), output: Vec::new() };
+		}
+	}
+
+	/// one server line per read, as a server waiting for the next command
+	impl Read for ScriptedStream
+	{
+		fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+		{
+			let start = self.input.position() as usize;
+			let remaining = &self.input.get_ref()[start..];
+			let lineLength = remaining.windows(2).position(|window| window == b"\r\n").map_or(remaining.len(), |index| index + 2);
+			let length = lineLength.min(buf.len());
+			buf[..length].copy_from_slice(&remaining[..length]);
+			self.input.set_position((start + length) as u64);
+			return Ok(length);
+		}
+	}
+
+	impl Write for ScriptedStream
+	{
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+		{
+			return self.output.write(buf);
+		}
+
+		fn flush(&mut self) -> std::io::Result<()>
+		{
+			return Ok(());
+		}
+	}
+
+	#[test]
+	fn startTls_upgradesOnlyWhenTheServerOffersIt()
+	{
+		let stream = ValidatedImapDestination::startTls_negotiate(ScriptedStream::new(concat!(
+			"* OK ready\r\n",
+			"* CAPABILITY IMAP4rev1 STARTTLS LOGINDISABLED\r\n",
+			"a1 OK done\r\n",
+			"a2 OK Begin TLS\r\n",
+			"\x16\x03\x01",
+		))).unwrap();
+		assert_eq!(String::from_utf8(stream.output.clone()).unwrap(), "a1 CAPABILITY\r\na2 STARTTLS\r\n");
+		// the TLS bytes stay for the handshake
+		assert_eq!(stream.input.position() as usize, stream.input.get_ref().len() - 3);
+
+		let withoutStartTls = ValidatedImapDestination::startTls_negotiate(ScriptedStream::new(concat!(
+			"* OK ready\r\n",
+			"* CAPABILITY IMAP4rev1 AUTH=PLAIN\r\n",
+			"a1 OK done\r\n",
+		)));
+		assert!(matches!(withoutStartTls, Err(imap::Error::StartTlsNotAvailable)));
+
+		let refused = ValidatedImapDestination::startTls_negotiate(ScriptedStream::new(concat!(
+			"* OK ready\r\n",
+			"* CAPABILITY IMAP4rev1 STARTTLS\r\n",
+			"a1 OK done\r\n",
+			"a2 NO not now\r\n",
+		)));
+		assert!(matches!(refused, Err(imap::Error::StartTlsNotAvailable)));
+	}
+
+	#[test]
+	fn imapPlaintext_requiresTheSiteSettingAndALoopbackDestination()
+	{
+		// the IMAP test servers allow loopback in site.json while they hold the configuration directory
+		let _configPath = ConfigPathGuard::new();
+		assert!(!OutboundPolicy::imapPlaintextFromConfigValue(None).unwrap());
+		assert!(OutboundPolicy::imapPlaintextFromConfigValue(Some(JsonValue::Boolean(true))).unwrap());
+		assert_eq!(
+			OutboundPolicy::imapPlaintextFromConfigValue(Some(JsonValue::String("true".to_string()))).unwrap_err(),
+			OutboundPolicyError::ConfigurationInvalid,
+		);
+
+		let runtime = tokio::runtime::Runtime::new().unwrap();
+		runtime.block_on(async {
+			assert_eq!(
+				OutboundPolicy::imapDestination_get("127.0.0.1", 993, ImapConnectionSecurity::NONE).await.err(),
+				Some(OutboundPolicyError::DestinationForbidden),
+			);
+			assert_eq!(
+				OutboundPolicy::imapDestination_get("127.0.0.1", 993, ImapConnectionSecurity::STARTTLS).await.err(),
+				Some(OutboundPolicyError::DestinationForbidden),
+			);
+			assert!(OutboundPolicy::imapDestination_get("8.8.8.8", 993, ImapConnectionSecurity::STARTTLS).await.is_ok());
+
+			assert!(OutboundPolicy::loopbackAddresses_get("127.0.0.1", 143).await.is_ok());
+			assert!(OutboundPolicy::loopbackAddresses_get("::1", 143).await.is_ok());
+			assert!(OutboundPolicy::loopbackAddresses_get("::ffff:127.0.0.1", 143).await.is_ok());
+			for host in ["8.8.8.8", "10.0.0.1", "0.0.0.0"]
+			{
+				assert_eq!(
+					OutboundPolicy::loopbackAddresses_get(host, 143).await.unwrap_err(),
+					OutboundPolicyError::DestinationForbidden,
+					"{}",
+					host,
+				);
+			}
+
+			for host in ["127.0.0.1", "::1", "8.8.8.8"]
+			{
+				assert!(OutboundPolicy::publicOrLoopbackAddresses_get(host, 993).await.is_ok(), "{}", host);
+			}
+			for host in ["10.0.0.1", "0.0.0.0"]
+			{
+				assert_eq!(
+					OutboundPolicy::publicOrLoopbackAddresses_get(host, 993).await.unwrap_err(),
+					OutboundPolicyError::DestinationForbidden,
+					"{}",
+					host,
+				);
+			}
+		});
+	}
+
 	#[test]
 	fn publicAddressPolicy_rejectsSpecialIpv4Ranges()
 	{
@@ -0,0 +1,144 @@
+use leptos::server;
+use leptos::server_fn::codec::Json;
+
+use crate::api::proxys::imap_components::{imap_connector, ImapProxyResponse};
+use crate::api::proxys::imap_error::ImapError;
+use crate::api::proxys::smtp_components::{smtp_connector, SmtpMessage, SmtpSent};
+
+/// request body allowed to the send server function: base64 attachments and the text fit in it
+#[cfg(feature = "ssr")]
+pub const SMTP_SEND_REQUEST_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
+
+/// sends with the IMAP credentials, then stores the sent copy in the Sent mailbox
+#[server(input = Json)]
+pub async fn API_proxys_smtp_send(imap: imap_connector, smtp: smtp_connector, message: SmtpMessage) -> Result<ImapProxyResponse<SmtpSent>, ImapError>
+{
+	return inner::SmtpRequest::new(imap,smtp,"send").await?
+		.run(message).await;
+}
+
+#[cfg(feature = "ssr")]
+mod inner
+{
+	use async_lock::SemaphoreGuard;
+	use Htrace::components::level::Level;
+	use Htrace::HTrace;
+
+	use crate::api::proxys::imap_components::{imap_connector, imap_connector_oauth, ImapProxyResponse};
+	use crate::api::proxys::imap_error::ImapError;
+	use crate::api::proxys::imap_inner::ImapProxy;
+	use crate::api::proxys::imap_oauth::ImapOAuthLogin;
+	use crate::api::proxys::outbound_policy::OutboundPolicy;
+	use crate::api::proxys::smtp_components::{smtp_connector, SmtpMessage, SmtpSent};
+	use crate::api::proxys::smtp_inner::SmtpProxy;
+
+	/// where the sent message goes once the SMTP server accepted it
+	enum SentCopy
+	{
+		// Gmail already files the messages sent through its SMTP server
+		STORED_BY_SERVER,
+		// the IMAP permit is only taken once the SMTP transaction is over
+		APPEND(ImapProxy),
+		UNAVAILABLE,
+	}
+
+	pub(super) struct SmtpRequest
+	{
+		operation: &'static str,
+		permit: SemaphoreGuard<'static>,
+		proxy: SmtpProxy,
+		sentCopy: SentCopy,
+		oauthRotated: Option<imap_connector_oauth>,
+	}
+
+	impl SmtpRequest
+	{
+		pub(super) async fn new(imap: imap_connector, smtp: smtp_connector, operation: &'static str) -> Result<Self, ImapError>
+		{
+			OutboundPolicy::authentication_require().await
+				.map_err(|error| ImapError::from(error).trace(operation,"authentication",None))?;
+			let permit = OutboundPolicy::smtpPermit_get()
+				.map_err(|error| ImapError::from(error).trace(operation,"concurrency",None))?;
+			let destination = OutboundPolicy::smtpDestination_get(&smtp.host, smtp.port).await
+				.map_err(|error| ImapError::from(error).trace(operation,"destination",None))?;
+			let oauthLogin = ImapOAuthLogin::forConnector_get(&imap).await
+				.map_err(|error| error.trace(operation,"oauth",None))?;
+			let oauthRotated = ImapOAuthLogin::rotated_get(oauthLogin.as_ref());
+			let sentCopy = Self::sentCopy_get(&imap, oauthLogin.clone(), operation).await;
+			let proxy = SmtpProxy::new(imap, smtp, destination)
+				.map_err(|error| error.trace(operation,"config",None))?
+				.oauthLogin_set(oauthLogin);
+			return Ok(Self { operation, permit, proxy, sentCopy, oauthRotated });
+		}
+
+		/// a failing IMAP side never prevents the sending, the copy is only reported as missing
+		async fn sentCopy_get(imap: &imap_connector, oauthLogin: Option<ImapOAuthLogin>, operation: &'static str) -> SentCopy
+		{
+			if (imap.isGmail())
+			{
+				return SentCopy::STORED_BY_SERVER;
+			}
+			let destination = match OutboundPolicy::imapDestination_get(&imap.host, imap.port, imap.security).await
+			{
+				Ok(destination) => destination,
+				Err(error) =>
+				{
+					let _ = ImapError::from(error).trace(operation,"sent_copy_destination",None);
+					return SentCopy::UNAVAILABLE;
+				},
+			};
+			return match ImapProxy::new(imap.clone(), destination)
+			{
+				Ok(proxy) => SentCopy::APPEND(proxy.oauthLogin_set(oauthLogin)),
+				Err(error) =>
+				{
+					let _ = error.trace(operation,"sent_copy_config",None);
+					SentCopy::UNAVAILABLE
+				},
+			};
+		}
+
+		/// a busy IMAP side skips the copy, the message is already sent
+		fn sentCopy_append(imapProxy: ImapProxy, sent: &[u8], operation: &'static str) -> bool
+		{
+			let Ok(_imapPermit) = OutboundPolicy::imapPermit_get()
+			else
+			{
+				HTrace!((Level::WARNING) "[SMTP proxy] operation={} stage=sent_copy_concurrency action=skip", operation);
+				return false;
+			};
+			return imapProxy.sentCopy_append(sent).is_ok();
+		}
+
+		/// a refresh token rotated by the login goes back with the answer, or with its error
+		pub(super) async fn run(self, message: SmtpMessage) -> Result<ImapProxyResponse<SmtpSent>, ImapError>
+		{
+			let Self { operation, permit, proxy, sentCopy, oauthRotated } = self;
+			let result = tokio::task::spawn_blocking(move ||
+			{
+				let sent = proxy.send(message);
+				drop(permit);
+				let sent = sent?;
+				let sentCopySaved = match sentCopy
+				{
+					SentCopy::STORED_BY_SERVER => true,
+					SentCopy::APPEND(imapProxy) => Self::sentCopy_append(imapProxy, &sent, operation),
+					SentCopy::UNAVAILABLE => false,
+				};
+				return Ok(SmtpSent { sentCopySaved });
+			}).await
+				.map_err(|error| ImapError::from(error).trace(operation,"worker",None))
+				.and_then(|result| result.map_err(|error: ImapError| error.trace(operation,"complete",None)));
+			if let Ok(sent) = &result
+			{
+				HTrace!(
+					(Level::DEBUG)
+					"[SMTP proxy] operation={} stage=success sent_copy_saved={}",
+					operation,
+					sent.sentCopySaved
+				);
+			}
+			return Ok(ImapProxyResponse { result, oauthRotated });
+		}
+	}
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api::proxys::imap_components::Attachment;
+
+/// sum of the attachments of one sent mail, before their base64 encoding
+pub const SMTP_ATTACHMENT_TOTAL_MAXIMUM_BYTES: usize = 10 * 1024 * 1024;
+
+/// outgoing server of a mail module, it logs in with the credentials of its imap_connector
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct smtp_connector
+{
+	pub host: String,
+	pub port: u16,
+	/// From address, the IMAP login is used when empty
+	#[serde(default)]
+	pub sender: String,
+}
+
+impl smtp_connector
+{
+	pub fn isConfigured(&self) -> bool
+	{
+		return !self.host.trim().is_empty();
+	}
+
+	pub fn sender_get<'a>(&'a self, username: &'a str) -> &'a str
+	{
+		let sender = self.sender.trim();
+		if (sender.is_empty())
+		{
+			return username.trim();
+		}
+		return sender;
+	}
+}
+
+impl Default for smtp_connector
+{
+	fn default() -> Self
+	{
+		Self {
+			host: "".to_string(),
+			port: 465,
+			sender: "".to_string(),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct SmtpMessage
+{
+	pub to: Vec<String>,
+	#[serde(default)]
+	pub cc: Vec<String>,
+	#[serde(default)]
+	pub bcc: Vec<String>,
+	pub subject: String,
+	pub body: String,
+	/// Message-ID of the answered mail
+	#[serde(default)]
+	pub inReplyTo: Option<String>,
+	#[serde(default)]
+	pub references: Vec<String>,
+	#[serde(default)]
+	pub attachments: Vec<Attachment>,
+	/// iTIP REPLY sent beside the text as an iMIP text/calendar part (RFC 6047), without attachments
+	#[serde(default)]
+	pub calendar: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Eq, PartialEq)]
+pub struct SmtpSent
+{
+	/// false when the mail was sent but could not be stored in the Sent mailbox
+	pub sentCopySaved: bool,
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::smtp_connector;
+
+	#[test]
+	fn connectorSendsWithImapLoginWithoutExplicitSender()
+	{
+		let mut connector = smtp_connector::default();
+		assert!(!connector.isConfigured());
+		assert_eq!(connector.sender_get(" user@example.com "),"user@example.com");
+
+		connector.sender = "Alias <alias@example.com>".to_string();
+		assert_eq!(connector.sender_get("user@example.com"),"Alias <alias@example.com>");
+	}
+}
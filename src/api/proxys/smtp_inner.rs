@@ -0,0 +1,335 @@
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment as SmtpAttachment, Mailbox, Message, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::client::SmtpConnection;
+
+use crate::api::proxys::imap_components::imap_connector;
+use crate::api::proxys::imap_error::ImapError;
+use crate::api::proxys::imap_oauth::ImapOAuthLogin;
+use crate::api::proxys::outbound_policy::ValidatedSmtpDestination;
+use crate::api::proxys::smtp_components::{smtp_connector, SmtpMessage, SMTP_ATTACHMENT_TOTAL_MAXIMUM_BYTES};
+
+struct SmtpLimits;
+
+impl SmtpLimits
+{
+	const ATTACHMENT_FILENAME_MAXIMUM_BYTES: usize = 1_024;
+	const ATTACHMENT_MAXIMUM: usize = 32;
+	const ATTACHMENT_MIME_MAXIMUM_BYTES: usize = 255;
+	const ATTACHMENT_TOTAL_MAXIMUM_BYTES: usize = SMTP_ATTACHMENT_TOTAL_MAXIMUM_BYTES;
+	const BODY_MAXIMUM_BYTES: usize = 1024 * 1024;
+	const CALENDAR_MAXIMUM_BYTES: usize = 64 * 1024;
+	const CREDENTIAL_MAXIMUM_BYTES: usize = 4_096;
+	// RFC 5322 line length, also used for every single-line header value
+	const HEADER_VALUE_MAXIMUM_BYTES: usize = 998;
+	const RECIPIENT_MAXIMUM: usize = 100;
+	const REFERENCE_MAXIMUM: usize = 64;
+}
+
+pub(super) struct SmtpProxy
+{
+	imap: imap_connector,
+	smtp: smtp_connector,
+	destination: ValidatedSmtpDestination,
+	oauthLogin: Option<ImapOAuthLogin>,
+}
+
+impl SmtpProxy
+{
+	pub(super) fn new(imap: imap_connector, smtp: smtp_connector, destination: ValidatedSmtpDestination) -> Result<Self, ImapError>
+	{
+		if (imap.username.len() > SmtpLimits::CREDENTIAL_MAXIMUM_BYTES
+			|| imap.password.len() > SmtpLimits::CREDENTIAL_MAXIMUM_BYTES
+			|| smtp.sender.len() > SmtpLimits::HEADER_VALUE_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		return Ok(Self { imap, smtp, destination, oauthLogin: None });
+	}
+
+	/// an OAuth connector sends with its access token, through XOAUTH2 which every OAuth SMTP server offers
+	pub(super) fn oauthLogin_set(mut self, oauthLogin: Option<ImapOAuthLogin>) -> Self
+	{
+		self.oauthLogin = oauthLogin;
+		return self;
+	}
+
+	/// returns the sent message, Bcc excluded, to be stored in the Sent mailbox
+	pub(super) fn send(self, message: SmtpMessage) -> Result<Vec<u8>, ImapError>
+	{
+		let email = self.message_build(&message)
+			.map_err(|error| error.trace("send","message",None))?;
+		let mut connection = self.destination.connection_get()
+			.map_err(|error| ImapError::fromSmtpAt(error,"send","connection"))?;
+		let (credentials,mechanisms) = match &self.oauthLogin
+		{
+			Some(oauthLogin) => (Credentials::new(self.imap.username.clone(), oauthLogin.accessToken.clone()), [Mechanism::Xoauth2].as_slice()),
+			None => (Credentials::new(self.imap.username.clone(), self.imap.password.clone()), [Mechanism::Plain, Mechanism::Login].as_slice()),
+		};
+		let result = Self::sendInConnection(&mut connection,&credentials,mechanisms,&email);
+		if (result.is_ok())
+		{
+			let _ = connection.quit();
+		}
+		else
+		{
+			connection.abort();
+		}
+		result?;
+		return Ok(email.formatted());
+	}
+
+	fn sendInConnection(connection: &mut SmtpConnection, credentials: &Credentials, mechanisms: &[Mechanism], email: &Message) -> Result<(), ImapError>
+	{
+		connection.auth(mechanisms, credentials)
+			.map_err(|error| ImapError::fromSmtpAt(error,"send","authentication"))?;
+		connection.send(email.envelope(), &email.formatted())
+			.map_err(|error| ImapError::fromSmtpAt(error,"send","transaction"))?;
+		return Ok(());
+	}
+
+	fn message_build(&self, message: &SmtpMessage) -> Result<Message, ImapError>
+	{
+		Self::message_validate(message)?;
+		let from = Self::mailbox_parse(self.smtp.sender_get(&self.imap.username))?;
+		let messageId = format!("<{}@{}>", uuid::Uuid::new_v4(), from.email.domain());
+		let mut builder = Message::builder()
+			.from(from)
+			.subject(message.subject.clone())
+			.message_id(Some(messageId));
+		for recipient in &message.to
+		{
+			builder = builder.to(Self::mailbox_parse(recipient)?);
+		}
+		for recipient in &message.cc
+		{
+			builder = builder.cc(Self::mailbox_parse(recipient)?);
+		}
+		for recipient in &message.bcc
+		{
+			builder = builder.bcc(Self::mailbox_parse(recipient)?);
+		}
+		if let Some(inReplyTo) = &message.inReplyTo
+		{
+			builder = builder.in_reply_to(inReplyTo.clone());
+		}
+		if (!message.references.is_empty())
+		{
+			builder = builder.references(message.references.join(" "));
+		}
+
+		let body = SinglePart::plain(message.body.clone());
+		let email = if let Some(calendar) = &message.calendar
+		{
+			let calendarPart = SinglePart::builder()
+				.header(ContentType::parse("text/calendar; charset=utf-8; method=REPLY").expect("static content type"))
+				.body(calendar.clone());
+			builder.multipart(MultiPart::alternative().singlepart(body).singlepart(calendarPart))
+		}
+		else if (message.attachments.is_empty())
+		{
+			builder.singlepart(body)
+		}
+		else
+		{
+			let multipart = message.attachments.iter().fold(MultiPart::mixed().singlepart(body), |multipart,attachment| {
+				let filename = attachment.filename.clone()
+					.filter(|filename| !filename.trim().is_empty())
+					.unwrap_or_else(|| "attachment".to_string());
+				let contentType = ContentType::parse(&attachment.content_type)
+					.unwrap_or_else(|_| ContentType::parse("application/octet-stream").expect("static content type"));
+				return multipart.singlepart(SmtpAttachment::new(filename).body(attachment.data.clone(),contentType));
+			});
+			builder.multipart(multipart)
+		};
+		return email.map_err(|_| ImapError::MESSAGE_INVALID);
+	}
+
+	fn message_validate(message: &SmtpMessage) -> Result<(), ImapError>
+	{
+		let recipientCount = message.to.len() + message.cc.len() + message.bcc.len();
+		if (recipientCount == 0
+			|| !Self::headerValue_isValid(&message.subject)
+			|| message.inReplyTo.as_deref().is_some_and(|messageId| !Self::messageId_isValid(messageId))
+			|| message.references.iter().any(|messageId| !Self::messageId_isValid(messageId))
+			|| message.calendar.as_ref().is_some_and(|calendar| !calendar.starts_with("BEGIN:VCALENDAR") || !message.attachments.is_empty())
+			// a forwarded attachment still left on the IMAP server would be sent empty
+			|| message.attachments.iter().any(|attachment| !attachment.isLoaded()))
+		{
+			return Err(ImapError::MESSAGE_INVALID);
+		}
+		if (recipientCount > SmtpLimits::RECIPIENT_MAXIMUM
+			|| message.body.len() > SmtpLimits::BODY_MAXIMUM_BYTES
+			|| message.calendar.as_ref().is_some_and(|calendar| calendar.len() > SmtpLimits::CALENDAR_MAXIMUM_BYTES)
+			|| message.references.len() > SmtpLimits::REFERENCE_MAXIMUM
+			|| message.attachments.len() > SmtpLimits::ATTACHMENT_MAXIMUM
+			|| message.attachments.iter().map(|attachment| attachment.data.len()).sum::<usize>() > SmtpLimits::ATTACHMENT_TOTAL_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+		if (message.attachments.iter().any(|attachment| {
+			return attachment.content_type.len() > SmtpLimits::ATTACHMENT_MIME_MAXIMUM_BYTES
+				|| attachment.filename.as_deref().is_some_and(|filename| {
+					return filename.len() > SmtpLimits::ATTACHMENT_FILENAME_MAXIMUM_BYTES
+						|| filename.chars().any(char::is_control);
+				});
+		}))
+		{
+			return Err(ImapError::MESSAGE_INVALID);
+		}
+		return Ok(());
+	}
+
+	fn mailbox_parse(value: &str) -> Result<Mailbox, ImapError>
+	{
+		if (!Self::headerValue_isValid(value))
+		{
+			return Err(ImapError::MESSAGE_INVALID);
+		}
+		return value.trim().parse::<Mailbox>().map_err(|_| ImapError::MESSAGE_INVALID);
+	}
+
+	fn headerValue_isValid(value: &str) -> bool
+	{
+		return value.len() <= SmtpLimits::HEADER_VALUE_MAXIMUM_BYTES && !value.chars().any(char::is_control);
+	}
+
+	fn messageId_isValid(value: &str) -> bool
+	{
+		return Self::headerValue_isValid(value)
+			&& value.len() > 2
+			&& value.starts_with('<')
+			&& value.ends_with('>')
+			&& value.contains('@')
+			&& !value.chars().any(char::is_whitespace);
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::proxys::imap_components::{imap_connector, Attachment};
+	use crate::api::proxys::imap_error::ImapError;
+	use crate::api::proxys::outbound_policy::ValidatedSmtpDestination;
+	use crate::api::proxys::smtp_components::{smtp_connector, SmtpMessage};
+
+	use super::{SmtpLimits, SmtpProxy};
+
+	fn proxy_get() -> SmtpProxy
+	{
+		let imap = imap_connector {
+			username: "me@example.com".to_string(),
+			..Default::default()
+		};
+		let destination = ValidatedSmtpDestination::test_get(
+			"smtp.example.com".to_string(),
+			465,
+			vec!["8.8.8.8:465".parse().unwrap()],
+		);
+		return SmtpProxy::new(imap,smtp_connector::default(),destination).unwrap();
+	}
+
+	fn message_get() -> SmtpMessage
+	{
+		return SmtpMessage {
+			to: vec!["Friend <friend@example.org>".to_string()],
+			bcc: vec!["hidden@example.org".to_string()],
+			subject: "Re: lunch".to_string(),
+			body: "See you at noon".to_string(),
+			inReplyTo: Some("<first@example.org>".to_string()),
+			references: vec!["<root@example.org>".to_string(),"<first@example.org>".to_string()],
+			..Default::default()
+		};
+	}
+
+	#[test]
+	fn messageBuild_threadsTheAnswerAndHidesBcc()
+	{
+		let email = proxy_get().message_build(&message_get()).unwrap();
+		let formatted = String::from_utf8(email.formatted()).unwrap();
+
+		assert!(formatted.contains("From: me@example.com\r\n"),"{}",formatted);
+		assert!(formatted.contains("To: Friend <friend@example.org>\r\n"),"{}",formatted);
+		assert!(formatted.contains("In-Reply-To: <first@example.org>\r\n"),"{}",formatted);
+		assert!(formatted.contains("References: <root@example.org> <first@example.org>\r\n"),"{}",formatted);
+		assert!(formatted.contains("@example.com>\r\n"),"{}",formatted);
+		assert!(!formatted.contains("hidden@example.org"),"{}",formatted);
+		assert_eq!(email.envelope().to().len(),2);
+	}
+
+	#[test]
+	fn messageBuild_attachesFilesInMixedMultipart()
+	{
+		let mut message = message_get();
+		message.attachments.push(Attachment {
+			filename: Some("notes.txt".to_string()),
+			content_type: "text/plain".to_string(),
+			content_id: None,
+			data: b"attached".to_vec(),
+			section: None,
+		});
+
+		let formatted = String::from_utf8(proxy_get().message_build(&message).unwrap().formatted()).unwrap();
+
+		assert!(formatted.contains("multipart/mixed"),"{}",formatted);
+		assert!(formatted.contains("filename=\"notes.txt\""),"{}",formatted);
+
+		message.attachments[0].section = Some("2".to_string());
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::MESSAGE_INVALID);
+	}
+
+	#[test]
+	fn messageBuild_sendsTheCalendarReplyAsAnAlternative()
+	{
+		let mut message = message_get();
+		message.calendar = Some("BEGIN:VCALENDAR\r\nMETHOD:REPLY\r\nEND:VCALENDAR\r\n".to_string());
+
+		let formatted = String::from_utf8(proxy_get().message_build(&message).unwrap().formatted()).unwrap();
+
+		assert!(formatted.contains("multipart/alternative"),"{}",formatted);
+		assert!(formatted.contains("text/calendar; charset=utf-8; method=REPLY"),"{}",formatted);
+		assert!(formatted.contains("METHOD:REPLY"),"{}",formatted);
+
+		message.attachments.push(Attachment {
+			filename: Some("notes.txt".to_string()),
+			content_type: "text/plain".to_string(),
+			content_id: None,
+			data: b"attached".to_vec(),
+			section: None,
+		});
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::MESSAGE_INVALID);
+	}
+
+	#[test]
+	fn messageBuild_rejectsHeaderInjectionAndMissingRecipients()
+	{
+		let mut message = message_get();
+		message.subject = "Hello\r\nBcc: victim@example.org".to_string();
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::MESSAGE_INVALID);
+
+		let mut message = message_get();
+		message.to = vec!["friend@example.org\r\nBcc: victim@example.org".to_string()];
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::MESSAGE_INVALID);
+
+		let mut message = message_get();
+		message.inReplyTo = Some("<first@example.org> <other@example.org>".to_string());
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::MESSAGE_INVALID);
+
+		let mut message = message_get();
+		message.to.clear();
+		message.bcc.clear();
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::MESSAGE_INVALID);
+	}
+
+	#[test]
+	fn messageBuild_limitsAttachmentSize()
+	{
+		let mut message = message_get();
+		message.attachments.push(Attachment {
+			data: vec![0; SmtpLimits::ATTACHMENT_TOTAL_MAXIMUM_BYTES + 1],
+			..Default::default()
+		});
+
+		assert_eq!(proxy_get().message_build(&message).unwrap_err(),ImapError::RESOURCE_LIMIT);
+	}
+}
@@ -132,7 +132,7 @@ impl BrowserContentSecurity
 				"font-src 'none'; ",
 				"form-action 'self'; ",
 				"frame-ancestors 'none'; ",
-				"frame-src 'self'; ",
+				"frame-src 'self' blob:; ",
 				"img-src 'self' data: blob: http: https:; ",
 				"manifest-src 'self'; ",
 				"media-src 'none'; ",
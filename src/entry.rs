@@ -17,6 +17,7 @@ use crate::front::utils::dialog::{DialogHost, DialogManager};
 use crate::front::utils::all_front_enum::AllFrontErrorEnum;
 use crate::front::utils::toaster_helpers::{toasterOwner_provide, toastingErr};
 use crate::front::utils::translate::Translate;
+use crate::front::utils::unread_badge::{DOCUMENT_TITLE, FAVICON_PATH};
 use crate::front::utils::users_data::ClientState;
 
 pub fn shell((options,trace_front_log,allowRegistration): (LeptosOptions, bool, bool)) -> impl IntoView {
@@ -157,10 +158,10 @@ pub fn App(traceFrontLog: bool,allowRegistration: bool) -> impl IntoView {
 			crossorigin="anonymous"
 		/>
 
-		<Link rel="icon" href="/favicon.png" type_="image/png" sizes="64x64" />
+		<Link rel="icon" href=FAVICON_PATH type_="image/png" sizes="64x64" />
 
 		// sets the document title
-		<Title text="Web Home"/>
+		<Title text=DOCUMENT_TITLE/>
 		<Meta name="description" content="Web Home"/>
 		<div class="toaster_host" role="status" aria-live="polite" aria-atomic="false">
 			<Toaster stacked={false} />
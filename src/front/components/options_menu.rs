@@ -1,9 +1,10 @@
 use leptoaster::expect_toaster;
 use leptos::ev::{KeyboardEvent, MouseEvent, SubmitEvent};
-use leptos::prelude::{AriaAttributes, BindAttribute, ClassAttribute, ElementChild, Get, GetUntracked, GlobalAttributes, IntoAny, OnAttribute, PropAttribute, RwSignal, Set, StyleAttribute};
+use leptos::prelude::{AriaAttributes, BindAttribute, ClassAttribute, CollectView, ElementChild, Get, GetUntracked, GlobalAttributes, IntoAny, OnAttribute, PropAttribute, RwSignal, Set, StyleAttribute};
 use leptos::task::spawn_local;
 use leptos::{component, view, IntoView};
 use leptos_router::hooks;
+use strum::IntoEnumIterator;
 #[cfg(feature="hydrate")]
 use wasm_bindgen::JsCast;
 
@@ -11,8 +12,10 @@ use crate::api::login::components::{AccountPreferencesError, PasswordRotationErr
 use crate::front::modules::module_holder::ModuleHolder;
 use crate::front::utils::all_front_enum::{AllFrontErrorEnum, AllFrontLoginEnum};
 use crate::front::utils::dialog::{DialogData, DialogManager};
+use crate::front::utils::shortcuts::{KeyCombo, ShortcutAction};
 use crate::front::utils::toaster_helpers::{toastingErr, toastingSuccess};
 use crate::front::utils::translate::TranslateText;
+use crate::front::utils::unread_badge::unreadNotifications_permissionRequest;
 use crate::front::utils::users_data::{ClientCryptoContext, ClientState};
 
 #[component]
@@ -108,6 +111,9 @@ fn OptionsPreferences(
 	let hueStyleState = clientState.clone();
 	let hueMouseState = clientState.clone();
 	let hueKeyboardState = clientState.clone();
+	let notificationsState = clientState.clone();
+	let notificationsInputState = clientState.clone();
+	let shortcutsState = clientState.clone();
 	let passwordState = clientState.clone();
 
 	return view! {
@@ -183,7 +189,30 @@ fn OptionsPreferences(
 					</div>
 				</div>
 				<p id="options-primary-hue-help" class="options_help"><TranslateText key="FRONTUI_OPTIONS_PRIMARY_HUE_HELP"/></p>
+				<label class="options_field options_toggle" for="options-notifications">
+					<input
+						id="options-notifications"
+						type="checkbox"
+						aria-describedby="options-notifications-help"
+						disabled=move || preferencesSaving.get()
+						prop:checked=move || notificationsState.notificationsPreview_get()
+						on:change=move |event| {
+							let enabled = leptos::prelude::event_target_checked(&event);
+							notificationsInputState.preferencesPreview_notificationsSet(enabled);
+							if (enabled)
+							{
+								unreadNotifications_permissionRequest();
+							}
+						}
+					/>
+					<span><TranslateText key="FRONTUI_OPTIONS_NOTIFICATIONS"/></span>
+				</label>
+				<p id="options-notifications-help" class="options_help"><TranslateText key="FRONTUI_OPTIONS_NOTIFICATIONS_HELP"/></p>
 			</section>
+			<OptionsShortcuts
+				clientState=shortcutsState
+				preferencesSaving
+			/>
 			<OptionsPasswordRotation
 				clientState=passwordState
 				preferencesSaving
@@ -193,6 +222,92 @@ fn OptionsPreferences(
 	};
 }
 
+#[component]
+fn OptionsShortcuts(
+	clientState: ClientState,
+	preferencesSaving: RwSignal<bool>,
+) -> impl IntoView
+{
+	let recording = RwSignal::new(None::<ShortcutAction>);
+	let conflict = RwSignal::new(None::<ShortcutAction>);
+
+	let rows = ShortcutAction::iter().map(|action| {
+		let bindingState = clientState.clone();
+		let recordState = clientState.clone();
+		let resetState = clientState.clone();
+		let labelId = format!("options-shortcut-{}", action);
+		let record = move |event: KeyboardEvent| {
+			if (recording.get_untracked() != Some(action) || event.key() == "Tab")
+			{
+				return;
+			}
+			// the dialog must not see the recorded key, Escape only stops the recording
+			event.prevent_default();
+			event.stop_propagation();
+			if (event.key() == "Escape")
+			{
+				recording.set(None);
+				return;
+			}
+			let Some(combo) = KeyCombo::fromEvent(&event) else {return};
+			recording.set(None);
+			conflict.set(recordState.preferencesPreview_shortcutSet(action, Some(combo)).err());
+		};
+		view! {
+			<li class="options_shortcut">
+				<span id=labelId.clone()><TranslateText key={action.translateKey_get()}/></span>
+				<button
+					type="button"
+					class="options_shortcut_binding"
+					class:recording=move || recording.get() == Some(action)
+					aria-describedby=labelId
+					disabled=move || preferencesSaving.get()
+					on:click=move |_| {
+						conflict.set(None);
+						recording.set(if recording.get_untracked() == Some(action) {None} else {Some(action)});
+					}
+					on:keydown=record
+					on:blur=move |_| if recording.get_untracked() == Some(action) {recording.set(None)}
+				>
+					{move || if recording.get() == Some(action)
+					{
+						view!{<TranslateText key="FRONTUI_OPTIONS_SHORTCUT_RECORDING"/>}.into_any()
+					}
+					else
+					{
+						view!{<kbd>{bindingState.shortcutsPreview_get().binding_get(action).to_string()}</kbd>}.into_any()
+					}}
+				</button>
+				<button
+					type="button"
+					class="options_shortcut_reset"
+					disabled=move || preferencesSaving.get()
+					on:click=move |_| {
+						recording.set(None);
+						conflict.set(resetState.preferencesPreview_shortcutSet(action, None).err());
+					}
+				>
+					<TranslateText key="FRONTUI_OPTIONS_SHORTCUT_RESET"/>
+				</button>
+			</li>
+		}
+	}).collect_view();
+
+	return view! {
+		<section class="options_section" aria-labelledby="options-shortcuts-title">
+			<h3 id="options-shortcuts-title"><TranslateText key="FRONTUI_OPTIONS_SHORTCUTS"/></h3>
+			<p class="options_help"><TranslateText key="FRONTUI_OPTIONS_SHORTCUTS_HELP"/></p>
+			{move || conflict.get().map(|action| view! {
+				<p class="options_shortcut_conflict" role="alert">
+					<TranslateText key="FRONTUI_OPTIONS_SHORTCUT_CONFLICT"/>" "
+					<TranslateText key={action.translateKey_get()}/>
+				</p>
+			})}
+			<ul class="options_shortcuts">{rows}</ul>
+		</section>
+	};
+}
+
 #[component]
 fn OptionsPasswordRotation(
 	clientState: ClientState,
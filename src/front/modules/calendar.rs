@@ -4,13 +4,16 @@ mod domain;
 mod holiday;
 #[cfg(any(feature = "hydrate",test))]
 mod icalendar_codec;
+pub(crate) mod invitation;
 mod view;
 
 use crate::api::modules::components::{ModuleContent,ModuleID};
 use crate::front::modules::components::{
-	Backable,BoxFuture,Cache,Cacheable,ModuleName,ModuleSizeContrainte,RefreshTime,moduleContent,
+	Backable,RefreshFuture,Cache,Cacheable,ModuleName,ModuleSizeContrainte,RefreshTime,moduleContent,
 };
 use crate::front::modules::module_actions::ModuleActionFn;
+#[cfg(feature = "hydrate")]
+use crate::front::modules::components::RefreshOutcome;
 use caldav::CalDavError;
 #[cfg(feature = "hydrate")]
 use caldav::CalDavClient;
@@ -91,9 +94,9 @@ impl Default for CalendarRuntime
 
 impl CalendarRuntime
 {
-	fn period_set(&mut self, anchor: Date, viewMode: CalendarViewMode) -> bool
+	fn period_set(&mut self, anchor: Date, viewMode: CalendarViewMode, agendaDays: u8) -> bool
 	{
-		let period = CalendarPeriod::from_anchor(anchor,viewMode);
+		let period = CalendarPeriod::from_anchor(anchor,viewMode,agendaDays);
 		if (self.period != Some(period))
 		{
 			self.anchor = Some(anchor);
@@ -261,9 +264,9 @@ impl Calendar
 		{
 			return (anchor,period);
 		}
-		let viewMode = self.config.get_untracked().viewMode;
+		let config = self.config.get_untracked();
 		let anchor = browser_today_get();
-		return (anchor,CalendarPeriod::from_anchor(anchor,viewMode));
+		return (anchor,CalendarPeriod::from_anchor(anchor,config.viewMode,config.agendaDays));
 	}
 }
 
@@ -333,7 +336,7 @@ impl Backable for Calendar
 		RefreshTime::MINUTES(10)
 	}
 
-	fn refresh(&self,moduleActions: ModuleActionFn,_moduleId: ModuleID,_toaster: ToasterContext) -> Option<BoxFuture>
+	fn refresh(&self,moduleActions: ModuleActionFn,_moduleId: ModuleID,_toaster: ToasterContext) -> Option<RefreshFuture>
 	{
 		#[cfg(not(feature = "hydrate"))]
 		{
@@ -372,7 +375,7 @@ impl Backable for Calendar
 								runtime.update(|runtime| runtime.holidays_finish(generation,&country,result));
 							}
 						}
-						return;
+						return RefreshOutcome::FAILED;
 					},
 				};
 				let mut events = Vec::new();
@@ -385,7 +388,7 @@ impl Backable for Calendar
 				{
 					if (!moduleActions.lifecycle_isActive())
 					{
-						return;
+						return RefreshOutcome::SUCCESS;
 					}
 					match client.events_get(collection,period,&floatingTimezone).await
 					{
@@ -402,8 +405,9 @@ impl Backable for Calendar
 				}
 				if (!moduleActions.lifecycle_isActive())
 				{
-					return;
+					return RefreshOutcome::SUCCESS;
 				}
+				let outcome = if (failures.is_empty()) {RefreshOutcome::SUCCESS} else {RefreshOutcome::FAILED};
 				runtime.update(|runtime| runtime.refresh_finish(
 					generation,events,succeededCollections,failures,rejectedEvents,rejectedSamples,
 				));
@@ -412,10 +416,11 @@ impl Backable for Calendar
 					let result = holidays_get(&country,period).await;
 					if (!moduleActions.lifecycle_isActive())
 					{
-						return;
+						return outcome;
 					}
 					runtime.update(|runtime| runtime.holidays_finish(generation,&country,result));
 				}
+				return outcome;
 			}));
 		}
 	}
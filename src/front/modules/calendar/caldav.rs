@@ -20,10 +20,11 @@ use super::domain::CalendarConfigError;
 use super::icalendar_codec::CalendarCodecError;
 #[cfg(feature = "hydrate")]
 use super::icalendar_codec::{
-	CalendarParsedEvents,CalendarResourceSource,build_event,event_is_recurring,exclude_occurrence,parse_expanded_events,
+	CalendarBuiltEvent,CalendarParsedEvents,CalendarResourceSource,build_event,event_is_recurring,exclude_occurrence,
+	invitation_isUpdateOf,invitation_uid_get,override_occurrence,parse_expanded_events,update_event,
 };
 #[cfg(feature = "hydrate")]
-use super::domain::CalendarCreateInput;
+use super::domain::{CalendarCreateInput,CalendarStoredEvent};
 
 const DAV_NAMESPACE: &[u8] = b"DAV:";
 const CALDAV_NAMESPACE: &[u8] = b"urn:ietf:params:xml:ns:caldav";
@@ -52,6 +53,8 @@ pub(super) enum CalDavError
 	InvalidResponse,
 	InvalidCalendar,
 	MissingEtag,
+	InvitationMismatch,
+	SeriesMoveUnsupported,
 }
 
 #[cfg(any(feature = "hydrate",test))]
@@ -63,6 +66,7 @@ impl From<CalendarCodecError> for CalDavError
 		{
 			CalendarCodecError::InputTooLarge | CalendarCodecError::FieldTooLarge => Self::ResponseTooLarge,
 			CalendarCodecError::TooManyEvents => Self::TooManyItems,
+			CalendarCodecError::SeriesMoveUnsupported => Self::SeriesMoveUnsupported,
 			_ => Self::InvalidCalendar,
 		};
 	}
@@ -542,21 +546,57 @@ mod browser
 			input: &CalendarCreateInput,
 		) -> Result<(),CalDavError>
 		{
-			let collectionUrl = href_resolve(&self.baseUrl,&collection.href)?;
 			let uid = uuid::Uuid::new_v4().to_string();
 			let built = build_event(input,uid,OffsetDateTime::now_utc())?;
-			let resourceUrl = collection_directory_get(&collectionUrl)
-				.join(&format!("{}.ics",built.uid)).map_err(|_| CalDavError::InvalidConfiguration)?;
-			if (!collection_resource_contains(&collectionUrl,&resourceUrl))
-			{
-				return Err(CalDavError::InvalidConfiguration);
-			}
+			return self.event_store(collection,&built).await;
+		}
+
+		/// stores a new resource, never replacing one of the same name
+		pub async fn event_store(&self, collection: &CalendarCollection, built: &CalendarBuiltEvent) -> Result<(),CalDavError>
+		{
+			let resourceUrl = self.collectionResource_get(collection,&built.uid)?;
 			let response = self.request(
 				"PUT",&resourceUrl,
 				&[("Content-Type","text/calendar; charset=utf-8"),("If-None-Match","*")],
 				Some(&built.content),CALDAV_MAX_DISCOVERY_BYTES,
 			).await?;
-			return status_expect(response.status,&[201,204]);
+			return status_expect(response.status,&[200,201,204]);
+		}
+
+		/// stores an answered invitation. The event already in the collection is only replaced by a version
+		/// of the same organizer which is not older, and while it did not change since it was read
+		pub async fn invitation_store(&self, collection: &CalendarCollection, built: &CalendarBuiltEvent) -> Result<(),CalDavError>
+		{
+			match self.event_store(collection,built).await
+			{
+				Err(CalDavError::Conflict) => {},
+				result => return result,
+			}
+			let resourceUrl = self.collectionResource_get(collection,&built.uid)?;
+			let stored = self.resource_load(&resourceUrl,None).await?;
+			if (!invitation_isUpdateOf(&stored.content,&built.content)?)
+			{
+				return Err(CalDavError::InvitationMismatch);
+			}
+			return self.resource_replace(&resourceUrl,&stored,&built.content).await;
+		}
+
+		/// removes the event of a cancelled or declined invitation, nothing when the collection does not hold it
+		pub async fn invitation_remove(&self, collection: &CalendarCollection, calendar: &str) -> Result<(),CalDavError>
+		{
+			let resourceUrl = self.collectionResource_get(collection,&invitation_uid_get(calendar)?)?;
+			let stored = match self.resource_load(&resourceUrl,None).await
+			{
+				Ok(stored) => stored,
+				Err(CalDavError::NotFound) => return Ok(()),
+				Err(error) => return Err(error),
+			};
+			if (!invitation_isUpdateOf(&stored.content,calendar)?)
+			{
+				return Err(CalDavError::InvitationMismatch);
+			}
+			let response = self.request("DELETE",&resourceUrl,&[("If-Match",stored.etag.as_str())],None,CALDAV_MAX_DISCOVERY_BYTES).await?;
+			return status_expect(response.status,&[200,204,404]);
 		}
 
 		pub async fn event_delete_series(&self, event: &CalendarEvent) -> Result<(),CalDavError>
@@ -568,17 +608,70 @@ mod browser
 		}
 
 		pub async fn event_delete_occurrence(&self, event: &CalendarEvent) -> Result<(),CalDavError>
+		{
+			let stored = self.event_load(event).await?;
+			let occurrence = event.occurrence.as_ref().unwrap_or(&event.start);
+			let updated = exclude_occurrence(&stored.content,&event.identity.uid,occurrence,OffsetDateTime::now_utc())?;
+			return self.event_replace(event,&stored,&updated).await;
+		}
+
+		/// the resource of the event with the version an update must still match
+		pub async fn event_load(&self, event: &CalendarEvent) -> Result<CalendarStoredEvent,CalDavError>
 		{
 			let resourceUrl = self.event_resource_get(event)?;
-			let response = self.request("GET",&resourceUrl,&[],None,CALDAV_MAX_RESOURCE_BYTES).await?;
-			status_expect(response.status,&[200])?;
-			let etag = response.etag.or_else(|| event.etag.clone()).ok_or(CalDavError::MissingEtag)?;
+			return self.resource_load(&resourceUrl,event.etag.as_deref()).await;
+		}
+
+		/// rewrites the master event, a single event or a whole series
+		pub async fn event_update_series(
+			&self,
+			event: &CalendarEvent,
+			stored: &CalendarStoredEvent,
+			input: &CalendarCreateInput,
+			isRecurrenceKept: bool,
+		) -> Result<(),CalDavError>
+		{
+			let updated = update_event(&stored.content,&event.identity.uid,input,isRecurrenceKept,OffsetDateTime::now_utc())?;
+			return self.event_replace(event,stored,&updated).await;
+		}
+
+		pub async fn event_update_occurrence(
+			&self,
+			event: &CalendarEvent,
+			stored: &CalendarStoredEvent,
+			input: &CalendarCreateInput,
+			floatingTimezone: &str,
+		) -> Result<(),CalDavError>
+		{
 			let occurrence = event.occurrence.as_ref().unwrap_or(&event.start);
-			let updated = exclude_occurrence(&response.body,&event.identity.uid,occurrence,OffsetDateTime::now_utc())?;
+			let updated = override_occurrence(
+				&stored.content,&event.identity.uid,occurrence,input,floatingTimezone,OffsetDateTime::now_utc(),
+			)?;
+			return self.event_replace(event,stored,&updated).await;
+		}
+
+		async fn event_replace(&self, event: &CalendarEvent, stored: &CalendarStoredEvent, content: &str) -> Result<(),CalDavError>
+		{
+			let resourceUrl = self.event_resource_get(event)?;
+			return self.resource_replace(&resourceUrl,stored,content).await;
+		}
+
+		/// knownEtag is the version listed with the event, for a server which does not answer it to GET
+		async fn resource_load(&self, resourceUrl: &Url, knownEtag: Option<&str>) -> Result<CalendarStoredEvent,CalDavError>
+		{
+			let response = self.request("GET",resourceUrl,&[],None,CALDAV_MAX_RESOURCE_BYTES).await?;
+			status_expect(response.status,&[200])?;
+			let etag = response.etag.or_else(|| knownEtag.map(str::to_string)).ok_or(CalDavError::MissingEtag)?;
+			return Ok(CalendarStoredEvent {content: response.body,etag});
+		}
+
+		/// a change made on the server since the load answers 412, reported as a conflict
+		async fn resource_replace(&self, resourceUrl: &Url, stored: &CalendarStoredEvent, content: &str) -> Result<(),CalDavError>
+		{
 			let response = self.request(
-				"PUT",&resourceUrl,
-				&[("Content-Type","text/calendar; charset=utf-8"),("If-Match",etag.as_str())],
-				Some(&updated),CALDAV_MAX_DISCOVERY_BYTES,
+				"PUT",resourceUrl,
+				&[("Content-Type","text/calendar; charset=utf-8"),("If-Match",stored.etag.as_str())],
+				Some(content),CALDAV_MAX_DISCOVERY_BYTES,
 			).await?;
 			return status_expect(response.status,&[200,201,204]);
 		}
@@ -591,6 +684,29 @@ mod browser
 			return event_is_recurring(&response.body,&event.identity.uid).map_err(Into::into);
 		}
 
+		/// the resource of the collection named after the uid when it is usable in an URL
+		fn collectionResource_get(&self, collection: &CalendarCollection, uid: &str) -> Result<Url,CalDavError>
+		{
+			let collectionUrl = href_resolve(&self.baseUrl,&collection.href)?;
+			let name = if (!uid.is_empty() && uid.len() <= 128
+				&& uid.chars().all(|character| character.is_ascii_alphanumeric() || "-_.@".contains(character)))
+			{
+				uid.to_string()
+			}
+			else
+			{
+				// the other uids are percent-encoded, the same invitation always gets the same resource
+				url::form_urlencoded::byte_serialize(uid.as_bytes()).collect::<String>()
+			};
+			let resourceUrl = collection_directory_get(&collectionUrl)
+				.join(&format!("{name}.ics")).map_err(|_| CalDavError::InvalidConfiguration)?;
+			if (!collection_resource_contains(&collectionUrl,&resourceUrl))
+			{
+				return Err(CalDavError::InvalidConfiguration);
+			}
+			return Ok(resourceUrl);
+		}
+
 		fn event_resource_get(&self, event: &CalendarEvent) -> Result<Url,CalDavError>
 		{
 			let collectionUrl = href_resolve(&self.baseUrl,&event.identity.collectionHref)?;
@@ -6,6 +6,8 @@ use time::{Date, Duration, Month, PrimitiveDateTime, Time};
 use url::Url;
 
 pub(super) const CALENDAR_CONFIG_VERSION: u8 = 1;
+pub(super) const CALENDAR_DEFAULT_AGENDA_DAYS: u8 = 14;
+pub(super) const CALENDAR_MAX_AGENDA_DAYS: u8 = 60;
 pub(super) const CALENDAR_MAX_COLLECTIONS: usize = 16;
 pub(super) const CALENDAR_MAX_COLLECTION_NAME_BYTES: usize = 1_024;
 pub(super) const CALENDAR_MAX_REJECTED_SAMPLES: usize = 16;
@@ -21,6 +23,8 @@ pub(super) enum CalendarViewMode
 	#[default]
 	Month,
 	Week,
+	Day,
+	Agenda,
 }
 
 #[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -73,6 +77,8 @@ pub(super) struct CalendarConfig
 	pub highlightWeekends: bool,
 	#[serde(default)]
 	pub holidayCountry: String,
+	#[serde(default = "calendar_agenda_days")]
+	pub agendaDays: u8,
 }
 
 fn calendar_config_version() -> u8
@@ -80,6 +86,11 @@ fn calendar_config_version() -> u8
 	CALENDAR_CONFIG_VERSION
 }
 
+fn calendar_agenda_days() -> u8
+{
+	CALENDAR_DEFAULT_AGENDA_DAYS
+}
+
 impl Default for CalendarConfig
 {
 	fn default() -> Self
@@ -94,6 +105,7 @@ impl Default for CalendarConfig
 			viewMode: CalendarViewMode::Month,
 			highlightWeekends: false,
 			holidayCountry: String::new(),
+			agendaDays: CALENDAR_DEFAULT_AGENDA_DAYS,
 		}
 	}
 }
@@ -289,6 +301,44 @@ impl CalendarEvent
 
 }
 
+/// fields of a stored event as the edit dialog shows them, a custom recurrence rule is kept as is
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct CalendarEditableEvent
+{
+	pub title: String,
+	pub description: String,
+	pub location: String,
+	pub start: CalendarMoment,
+	pub end: CalendarMoment,
+	pub recurrence: Option<CalendarRecurrence>,
+	pub isRecurrenceCustom: bool,
+}
+
+impl CalendarEditableEvent
+{
+	/// an occurrence is edited as displayed, without its recurrence
+	pub fn from_occurrence(event: &CalendarEvent) -> Self
+	{
+		return Self {
+			title: event.title.clone(),
+			description: event.description.clone(),
+			location: event.location.clone(),
+			start: event.start.clone(),
+			end: event.end.clone(),
+			recurrence: None,
+			isRecurrenceCustom: false,
+		};
+	}
+}
+
+/// calendar resource read before an update, the update only applies while its ETag still matches
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct CalendarStoredEvent
+{
+	pub content: String,
+	pub etag: String,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) struct CalendarPeriod
 {
@@ -298,10 +348,17 @@ pub(super) struct CalendarPeriod
 
 impl CalendarPeriod
 {
-	pub fn from_anchor(anchor: Date, viewMode: CalendarViewMode) -> Self
+	/// the agenda starts on the anchor and lists agendaDays days, kept between one and the maximum
+	pub fn from_anchor(anchor: Date, viewMode: CalendarViewMode, agendaDays: u8) -> Self
 	{
 		return match viewMode
 		{
+			CalendarViewMode::Day => Self {start: anchor,endExclusive: anchor + Duration::DAY},
+			CalendarViewMode::Agenda =>
+			{
+				let days = agendaDays.clamp(1,CALENDAR_MAX_AGENDA_DAYS);
+				Self {start: anchor,endExclusive: anchor + Duration::days(days as i64)}
+			},
 			CalendarViewMode::Week =>
 			{
 				let start = anchor - Duration::days(anchor.weekday().number_days_from_monday() as i64);
@@ -360,6 +417,92 @@ pub(super) enum CalendarCreateMoment
 	Local(PrimitiveDateTime),
 }
 
+/// where an event was dropped on the grid, in the browser timezone
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum CalendarDrop
+{
+	/// moves by whole days, the time of day stays
+	Days(i64),
+	/// moves the start to a date and time, the length stays
+	Start(PrimitiveDateTime),
+	/// sets the end, the start stays
+	End(PrimitiveDateTime),
+}
+
+impl CalendarDrop
+{
+	/// the period after the drop, None when it leaves the event unchanged, empty or out of range
+	pub fn period_apply(
+		&self,
+		start: &CalendarCreateMoment,
+		end: &CalendarCreateMoment,
+	) -> Option<(CalendarCreateMoment, CalendarCreateMoment)>
+	{
+		let period = match (self,start,end)
+		{
+			(Self::Days(days),CalendarCreateMoment::AllDay(start),CalendarCreateMoment::AllDay(end)) => (
+				CalendarCreateMoment::AllDay(start.checked_add(Duration::days(*days))?),
+				CalendarCreateMoment::AllDay(end.checked_add(Duration::days(*days))?),
+			),
+			(Self::Days(days),CalendarCreateMoment::Local(start),CalendarCreateMoment::Local(end)) => (
+				CalendarCreateMoment::Local(start.checked_add(Duration::days(*days))?),
+				CalendarCreateMoment::Local(end.checked_add(Duration::days(*days))?),
+			),
+			(Self::Start(target),CalendarCreateMoment::AllDay(start),CalendarCreateMoment::AllDay(end)) => (
+				CalendarCreateMoment::AllDay(target.date()),
+				CalendarCreateMoment::AllDay(target.date().checked_add(*end - *start)?),
+			),
+			(Self::Start(target),CalendarCreateMoment::Local(start),CalendarCreateMoment::Local(end)) => (
+				CalendarCreateMoment::Local(*target),
+				CalendarCreateMoment::Local(target.checked_add(*end - *start)?),
+			),
+			(Self::End(target),CalendarCreateMoment::Local(start),CalendarCreateMoment::Local(_)) if target > start => (
+				CalendarCreateMoment::Local(*start),
+				CalendarCreateMoment::Local(*target),
+			),
+			_ => return None,
+		};
+		if (period.0 == *start && period.1 == *end)
+		{
+			return None;
+		}
+		return Some(period);
+	}
+}
+
+/// moves the master period of a series like one of its occurrences was moved, its length becomes the new one
+pub(super) fn master_period_reschedule(
+	master: (&CalendarCreateMoment, &CalendarCreateMoment),
+	occurrenceStart: &CalendarCreateMoment,
+	target: (&CalendarCreateMoment, &CalendarCreateMoment),
+) -> Option<(CalendarCreateMoment, CalendarCreateMoment)>
+{
+	return match (master.0,occurrenceStart,target.0,target.1)
+	{
+		(
+			CalendarCreateMoment::AllDay(masterStart),
+			CalendarCreateMoment::AllDay(occurrenceStart),
+			CalendarCreateMoment::AllDay(targetStart),
+			CalendarCreateMoment::AllDay(targetEnd),
+		) =>
+		{
+			let start = masterStart.checked_add(*targetStart - *occurrenceStart)?;
+			Some((CalendarCreateMoment::AllDay(start),CalendarCreateMoment::AllDay(start.checked_add(*targetEnd - *targetStart)?)))
+		},
+		(
+			CalendarCreateMoment::Local(masterStart),
+			CalendarCreateMoment::Local(occurrenceStart),
+			CalendarCreateMoment::Local(targetStart),
+			CalendarCreateMoment::Local(targetEnd),
+		) =>
+		{
+			let start = masterStart.checked_add(*targetStart - *occurrenceStart)?;
+			Some((CalendarCreateMoment::Local(start),CalendarCreateMoment::Local(start.checked_add(*targetEnd - *targetStart)?)))
+		},
+		_ => None,
+	};
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(super) enum CalendarRecurrenceFrequency
 {
@@ -472,10 +615,10 @@ fn timezone_is_safe(timezone: &str) -> bool
 mod tests
 {
 	use super::{
-		CalendarCollection, CalendarConfig, CalendarEvent, CalendarEventIdentity, CalendarMoment,
-		CalendarPeriod, CalendarViewMode,
+		CALENDAR_DEFAULT_AGENDA_DAYS, CALENDAR_MAX_AGENDA_DAYS, CalendarCollection, CalendarConfig, CalendarCreateMoment, CalendarDrop, CalendarEvent, CalendarEventIdentity,
+		CalendarMoment, CalendarPeriod, CalendarViewMode, master_period_reschedule,
 	};
-	use time::{Date, Month};
+	use time::{Date, Month, PrimitiveDateTime, Time};
 
 	#[test]
 	fn legacyConfig_deserializesWithDefaults()
@@ -487,6 +630,7 @@ mod tests
 		assert!(config.collections.is_empty());
 		assert!(!config.highlightWeekends);
 		assert!(config.holidayCountry.is_empty());
+		assert_eq!(config.agendaDays,CALENDAR_DEFAULT_AGENDA_DAYS);
 	}
 
 	#[test]
@@ -558,7 +702,7 @@ mod tests
 	fn monthPeriod_includesCompleteMondayToSundayGrid()
 	{
 		let anchor = Date::from_calendar_date(2026,Month::August,13).unwrap();
-		let period = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Month);
+		let period = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Month,CALENDAR_DEFAULT_AGENDA_DAYS);
 
 		assert_eq!(period.start,Date::from_calendar_date(2026,Month::July,27).unwrap());
 		assert_eq!(period.endExclusive,Date::from_calendar_date(2026,Month::September,7).unwrap());
@@ -571,7 +715,7 @@ mod tests
 	fn weekPeriod_includesEveryDayRegardlessOfCurrentTime()
 	{
 		let anchor = Date::from_calendar_date(2026,Month::August,13).unwrap();
-		let period = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Week);
+		let period = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Week,CALENDAR_DEFAULT_AGENDA_DAYS);
 
 		assert_eq!(period.start,Date::from_calendar_date(2026,Month::August,10).unwrap());
 		assert_eq!(period.endExclusive,Date::from_calendar_date(2026,Month::August,17).unwrap());
@@ -580,6 +724,21 @@ mod tests
 		assert_eq!(period.query_end_utc(),"20260818T000000Z");
 	}
 
+	#[test]
+	fn dayAndAgendaPeriods_startOnTheAnchor()
+	{
+		let anchor = Date::from_calendar_date(2026,Month::August,13).unwrap();
+		let day = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Day,CALENDAR_DEFAULT_AGENDA_DAYS);
+		let agenda = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Agenda,10);
+
+		assert_eq!(day.days().collect::<Vec<_>>(),vec![anchor]);
+		assert_eq!(day.query_start_utc(),"20260812T000000Z");
+		assert_eq!(agenda.start,anchor);
+		assert_eq!(agenda.endExclusive,Date::from_calendar_date(2026,Month::August,23).unwrap());
+		assert_eq!(CalendarPeriod::from_anchor(anchor,CalendarViewMode::Agenda,0).days().count(),1);
+		assert_eq!(CalendarPeriod::from_anchor(anchor,CalendarViewMode::Agenda,u8::MAX).days().count(),CALENDAR_MAX_AGENDA_DAYS as usize);
+	}
+
 	#[test]
 	fn events_sortByStartThenStableIdentity()
 	{
@@ -609,4 +768,54 @@ mod tests
 
 		assert_eq!(events.iter().map(|event| event.title.as_str()).collect::<Vec<_>>(),["all","a","b"]);
 	}
+
+	fn local(day: u8,hour: u8,minute: u8) -> CalendarCreateMoment
+	{
+		return CalendarCreateMoment::Local(PrimitiveDateTime::new(
+			Date::from_calendar_date(2026,Month::August,day).unwrap(),
+			Time::from_hms(hour,minute,0).unwrap(),
+		));
+	}
+
+	#[test]
+	fn drop_movesOrResizesThePeriod()
+	{
+		let allDayStart = CalendarCreateMoment::AllDay(Date::from_calendar_date(2026,Month::August,13).unwrap());
+		let allDayEnd = CalendarCreateMoment::AllDay(Date::from_calendar_date(2026,Month::August,15).unwrap());
+		let target = PrimitiveDateTime::new(Date::from_calendar_date(2026,Month::August,16).unwrap(),Time::from_hms(14,15,0).unwrap());
+
+		assert_eq!(
+			CalendarDrop::Days(-2).period_apply(&local(13,9,30),&local(13,10,30)),
+			Some((local(11,9,30),local(11,10,30))),
+		);
+		assert_eq!(
+			CalendarDrop::Days(3).period_apply(&allDayStart,&allDayEnd),
+			Some((
+				CalendarCreateMoment::AllDay(Date::from_calendar_date(2026,Month::August,16).unwrap()),
+				CalendarCreateMoment::AllDay(Date::from_calendar_date(2026,Month::August,18).unwrap()),
+			)),
+		);
+		assert_eq!(CalendarDrop::Start(target).period_apply(&local(13,9,30),&local(13,10,30)),Some((local(16,14,15),local(16,15,15))));
+		assert_eq!(CalendarDrop::End(target).period_apply(&local(16,9,30),&local(16,10,30)),Some((local(16,9,30),local(16,14,15))));
+		assert_eq!(CalendarDrop::End(target).period_apply(&local(16,14,15),&local(16,15,0)),None);
+		assert_eq!(CalendarDrop::End(target).period_apply(&allDayStart,&allDayEnd),None);
+		assert_eq!(CalendarDrop::Days(0).period_apply(&local(13,9,30),&local(13,10,30)),None);
+	}
+
+	#[test]
+	fn masterPeriod_followsTheMovedOccurrence()
+	{
+		let rescheduled = master_period_reschedule(
+			(&local(3,9,0),&local(3,10,0)),
+			&local(10,9,0),
+			(&local(11,8,30),&local(11,10,0)),
+		);
+
+		assert_eq!(rescheduled,Some((local(4,8,30),local(4,10,0))));
+		assert_eq!(master_period_reschedule(
+			(&CalendarCreateMoment::AllDay(Date::from_calendar_date(2026,Month::August,3).unwrap()),&local(3,10,0)),
+			&local(10,9,0),
+			(&local(11,8,30),&local(11,10,0)),
+		),None);
+	}
 }
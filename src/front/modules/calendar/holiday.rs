@@ -278,7 +278,7 @@ pub(in crate::front::modules::calendar) use browser::holidays_get;
 mod tests
 {
 	use super::{CalendarHolidayError,holidays_parse,holidays_period_get};
-	use crate::front::modules::calendar::domain::{CalendarPeriod,CalendarViewMode};
+	use crate::front::modules::calendar::domain::{CALENDAR_DEFAULT_AGENDA_DAYS,CalendarPeriod,CalendarViewMode};
 	use time::{Date,Month};
 
 	#[test]
@@ -314,7 +314,7 @@ mod tests
 		]"#;
 		let holidayDate = Date::from_calendar_date(2026,Month::August,15).unwrap();
 		let anchor = Date::from_calendar_date(2026,Month::August,13).unwrap();
-		let period = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Week);
+		let period = CalendarPeriod::from_anchor(anchor,CalendarViewMode::Week,CALENDAR_DEFAULT_AGENDA_DAYS);
 
 		let holidays = holidays_period_get(holidays_parse(input,"FR",2026).unwrap(),period);
 
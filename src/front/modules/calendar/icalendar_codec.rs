@@ -1,11 +1,12 @@
-use icalendar::{Calendar, CalendarDateTime, Component, DatePerhapsTime, Event, EventLike, Property};
-use time::{Date, OffsetDateTime, PrimitiveDateTime, UtcOffset};
+use icalendar::{Calendar, CalendarComponent, CalendarDateTime, Component, DatePerhapsTime, Event, EventLike, Property};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
 
 use super::domain::{
-	CalendarCreateInput, CalendarCreateMoment, CalendarEvent, CalendarEventIdentity, CalendarMoment,
-	CalendarRecurrenceEnd, CalendarRecurrenceFrequency, CalendarRejectedEvent,
+	CalendarCreateInput, CalendarCreateMoment, CalendarEditableEvent, CalendarEvent, CalendarEventIdentity, CalendarMoment,
+	CalendarRecurrence, CalendarRecurrenceEnd, CalendarRecurrenceFrequency, CalendarRejectedEvent,
 	CalendarRejectedReason, CALENDAR_MAX_REJECTED_SAMPLES,
 };
+use super::invitation::{CalendarInvitation, CalendarInvitationAnswer};
 
 const CALENDAR_MAX_ICALENDAR_BYTES: usize = 2 * 1024 * 1024;
 const CALENDAR_MAX_EVENTS_PER_RESOURCE: usize = 2_048;
@@ -46,6 +47,7 @@ pub(super) enum CalendarCodecError
 	InvalidCreateInput,
 	MasterEventNotFound,
 	EventIsNotRecurring,
+	SeriesMoveUnsupported,
 }
 
 pub(super) struct CalendarBuiltEvent
@@ -123,20 +125,7 @@ fn event_parse(
 ) -> Result<CalendarEvent,CalendarCodecError>
 {
 	let uid = bounded_field(event.get_uid().ok_or(CalendarCodecError::MissingUid)?,CALENDAR_MAX_UID_BYTES)?;
-	let start = moment_from_icalendar(event.get_start().ok_or(CalendarCodecError::MissingStart)?,floatingTimezone)?;
-	let end = match event.get_end()
-	{
-		Some(end) => moment_from_icalendar(end,floatingTimezone)?,
-		None => match start
-		{
-			CalendarMoment::AllDay(date) => CalendarMoment::AllDay(date.next_day().ok_or(CalendarCodecError::InvalidEnd)?),
-			CalendarMoment::Timed(timestamp) => CalendarMoment::Timed(timestamp),
-		},
-	};
-	if (!moments_are_compatible(&start,&end) || end.sort_key() < start.sort_key())
-	{
-		return Err(CalendarCodecError::InvalidEnd);
-	}
+	let (start,end) = event_period_parse(event,floatingTimezone)?;
 	let occurrenceProperty = event.properties().get("RECURRENCE-ID");
 	let occurrenceId = occurrenceProperty.map(property_identity);
 	let occurrence = occurrenceProperty
@@ -168,6 +157,25 @@ fn event_parse(
 	});
 }
 
+fn event_period_parse(event: &Event, floatingTimezone: &str) -> Result<(CalendarMoment,CalendarMoment),CalendarCodecError>
+{
+	let start = moment_from_icalendar(event.get_start().ok_or(CalendarCodecError::MissingStart)?,floatingTimezone)?;
+	let end = match event.get_end()
+	{
+		Some(end) => moment_from_icalendar(end,floatingTimezone)?,
+		None => match start
+		{
+			CalendarMoment::AllDay(date) => CalendarMoment::AllDay(date.next_day().ok_or(CalendarCodecError::InvalidEnd)?),
+			CalendarMoment::Timed(timestamp) => CalendarMoment::Timed(timestamp),
+		},
+	};
+	if (!moments_are_compatible(&start,&end) || end.sort_key() < start.sort_key())
+	{
+		return Err(CalendarCodecError::InvalidEnd);
+	}
+	return Ok((start,end));
+}
+
 fn bounded_field(value: &str, maxBytes: usize) -> Result<String,CalendarCodecError>
 {
 	if (value.len() > maxBytes)
@@ -236,28 +244,67 @@ pub(super) fn build_event(
 	uid: String,
 	now: OffsetDateTime,
 ) -> Result<CalendarBuiltEvent,CalendarCodecError>
+{
+	input_check(input)?;
+	if (uid.is_empty() || uid.len() > CALENDAR_MAX_UID_BYTES)
+	{
+		return Err(CalendarCodecError::FieldTooLarge);
+	}
+
+	let mut event = Event::with_uid(&uid);
+	event_fields_set(&mut event,input)?;
+	event.append_property(Property::new("DTSTAMP",format_utc(now)));
+	event.sequence(0);
+	if let Some(recurrence) = &input.recurrence
+	{
+		event.append_property(Property::new("RRULE",recurrence_format(recurrence,&input.start)?));
+	}
+
+	let mut calendar = Calendar::new();
+	calendar.properties.retain(|property| property.key() != "PRODID");
+	calendar.append_property(Property::new("PRODID","-//WebHome//Calendar//EN"));
+	if (matches!(input.start,CalendarCreateMoment::Local(_)))
+	{
+		calendar.timezone(input.timezone.clone());
+	}
+	calendar.push(event);
+	let content = calendar.to_string();
+	if (content.len() > CALENDAR_MAX_ICALENDAR_BYTES)
+	{
+		return Err(CalendarCodecError::InputTooLarge);
+	}
+	return Ok(CalendarBuiltEvent {uid,content});
+}
+
+fn input_check(input: &CalendarCreateInput) -> Result<(),CalendarCodecError>
 {
 	input.validate().map_err(|_| CalendarCodecError::InvalidCreateInput)?;
-	if (uid.is_empty() || uid.len() > CALENDAR_MAX_UID_BYTES
-		|| input.title.len() > CALENDAR_MAX_TITLE_BYTES
+	if (input.title.len() > CALENDAR_MAX_TITLE_BYTES
 		|| input.location.len() > CALENDAR_MAX_LOCATION_BYTES
 		|| input.description.len() > CALENDAR_MAX_DESCRIPTION_BYTES)
 	{
 		return Err(CalendarCodecError::FieldTooLarge);
 	}
+	return Ok(());
+}
 
-	let mut event = Event::with_uid(&uid);
+/// title, details and period of the input, the fields it leaves empty are removed
+fn event_fields_set(event: &mut Event, input: &CalendarCreateInput) -> Result<(),CalendarCodecError>
+{
 	event.summary(input.title.trim());
+	event.remove_description();
 	if (!input.description.is_empty())
 	{
 		event.description(&input.description);
 	}
+	event.remove_location();
 	if (!input.location.is_empty())
 	{
 		event.location(&input.location);
 	}
-	event.append_property(Property::new("DTSTAMP",format_utc(now)));
-	event.sequence(0);
+	event.remove_property("DTSTART");
+	event.remove_property("DTEND");
+	event.remove_property("DURATION");
 	match (&input.start,&input.end)
 	{
 		(CalendarCreateMoment::AllDay(start),CalendarCreateMoment::AllDay(end)) =>
@@ -271,29 +318,11 @@ pub(super) fn build_event(
 		},
 		_ => return Err(CalendarCodecError::InvalidCreateInput),
 	}
-	if let Some(recurrence) = &input.recurrence
-	{
-		event.append_property(Property::new("RRULE",recurrence_format(recurrence,&input.start)?));
-	}
-
-	let mut calendar = Calendar::new();
-	calendar.properties.retain(|property| property.key() != "PRODID");
-	calendar.append_property(Property::new("PRODID","-//WebHome//Calendar//EN"));
-	if (matches!(input.start,CalendarCreateMoment::Local(_)))
-	{
-		calendar.timezone(input.timezone.clone());
-	}
-	calendar.push(event);
-	let content = calendar.to_string();
-	if (content.len() > CALENDAR_MAX_ICALENDAR_BYTES)
-	{
-		return Err(CalendarCodecError::InputTooLarge);
-	}
-	return Ok(CalendarBuiltEvent {uid,content});
+	return Ok(());
 }
 
 fn recurrence_format(
-	recurrence: &super::domain::CalendarRecurrence,
+	recurrence: &CalendarRecurrence,
 	start: &CalendarCreateMoment,
 ) -> Result<String,CalendarCodecError>
 {
@@ -350,7 +379,7 @@ pub(super) fn exclude_occurrence(
 	{
 		return Err(CalendarCodecError::EventIsNotRecurring);
 	}
-	let exclusion = occurrence_property(occurrence)?;
+	let exclusion = occurrence_property("EXDATE",occurrence)?;
 	let alreadyExcluded = event.multi_properties().get("EXDATE")
 		.map(|values| values.iter().any(|value| value.value() == exclusion.value() && value.params() == exclusion.params()))
 		.unwrap_or(false);
@@ -386,15 +415,386 @@ pub(super) fn event_is_recurring(input: &str, uid: &str) -> Result<bool,Calendar
 	return Ok(event.property_value("RRULE").is_some());
 }
 
-fn occurrence_property(occurrence: &CalendarMoment) -> Result<Property,CalendarCodecError>
+/// the master event as the edit dialog shows it, a rule beyond frequency, interval and end is marked custom
+pub(super) fn editable_event_get(
+	input: &str,
+	uid: &str,
+	floatingTimezone: &str,
+) -> Result<CalendarEditableEvent,CalendarCodecError>
+{
+	let calendar = calendar_parse(input)?;
+	let event = calendar.events()
+		.find(|event| event.get_uid() == Some(uid) && event.get_recurrence_id().is_none())
+		.ok_or(CalendarCodecError::MasterEventNotFound)?;
+	let (start,end) = event_period_parse(event,floatingTimezone)?;
+	let rule = event.property_value("RRULE");
+	let recurrence = rule.and_then(recurrence_parse);
+	let isRecurrenceCustom = (rule.is_some() && recurrence.is_none()) || event.multi_properties().contains_key("RDATE");
+	return Ok(CalendarEditableEvent {
+		title: bounded_field(event.get_summary().unwrap_or_default(),CALENDAR_MAX_TITLE_BYTES)?,
+		description: bounded_field(event.get_description().unwrap_or_default(),CALENDAR_MAX_DESCRIPTION_BYTES)?,
+		location: bounded_field(event.get_location().unwrap_or_default(),CALENDAR_MAX_LOCATION_BYTES)?,
+		start,
+		end,
+		recurrence: if (isRecurrenceCustom) {None} else {recurrence},
+		isRecurrenceCustom,
+	});
+}
+
+fn recurrence_parse(rule: &str) -> Option<CalendarRecurrence>
+{
+	let mut frequency = None;
+	let mut interval = 1;
+	let mut end = CalendarRecurrenceEnd::Never;
+	for part in rule.split(';').filter(|part| !part.is_empty())
+	{
+		let (name,value) = part.split_once('=')?;
+		match name.to_ascii_uppercase().as_str()
+		{
+			"FREQ" => frequency = Some(match value.to_ascii_uppercase().as_str()
+			{
+				"DAILY" => CalendarRecurrenceFrequency::Daily,
+				"WEEKLY" => CalendarRecurrenceFrequency::Weekly,
+				"MONTHLY" => CalendarRecurrenceFrequency::Monthly,
+				"YEARLY" => CalendarRecurrenceFrequency::Yearly,
+				_ => return None,
+			}),
+			"INTERVAL" => interval = value.parse().ok()?,
+			"COUNT" => end = CalendarRecurrenceEnd::Count(value.parse().ok()?),
+			"UNTIL" =>
+			{
+				// a byte slice of a multibyte character would panic
+				let date = value.get(..8).filter(|date| date.bytes().all(|byte| byte.is_ascii_digit()))?;
+				let date = chrono_date_into_time(format!("{}-{}-{}",&date[..4],&date[4..6],&date[6..])).ok()?;
+				let utcEndTimestamp = PrimitiveDateTime::new(date,Time::from_hms(23,59,59).ok()?).assume_utc().unix_timestamp();
+				end = CalendarRecurrenceEnd::Until {date,utcEndTimestamp};
+			},
+			// only weighs on BY* parts, which make the rule custom anyway
+			"WKST" => {},
+			_ => return None,
+		}
+	}
+	return Some(CalendarRecurrence {frequency: frequency?,interval,end});
+}
+
+/// rewrites the master event with the input, other properties like attendees stay.
+/// Removing the recurrence also removes the exceptions and the overrides of the series.
+/// Moving the start is refused while exceptions, overrides or a custom rule would no longer match the occurrences
+pub(super) fn update_event(
+	input: &str,
+	uid: &str,
+	edit: &CalendarCreateInput,
+	isRecurrenceKept: bool,
+	now: OffsetDateTime,
+) -> Result<String,CalendarCodecError>
+{
+	input_check(edit)?;
+	let mut calendar = calendar_parse(input)?;
+	let hasOverrides = calendar.events().any(|event| event.get_uid() == Some(uid) && event.get_recurrence_id().is_some());
+	let event = calendar.events_mut()
+		.find(|event| event.get_uid() == Some(uid) && event.get_recurrence_id().is_none())
+		.ok_or(CalendarCodecError::MasterEventNotFound)?;
+	let previousStart = event.get_start().and_then(|start| moment_from_icalendar(start,&edit.timezone).ok());
+	event_fields_set(event,edit)?;
+	let isSeriesRemoved = !isRecurrenceKept && edit.recurrence.is_none() && event.property_value("RRULE").is_some();
+	// EXDATE, RDATE and RECURRENCE-ID name occurrences of the former start, a custom rule may not match the new one
+	let isStartMoved = event.get_start().and_then(|start| moment_from_icalendar(start,&edit.timezone).ok()) != previousStart;
+	let hasExceptions = hasOverrides || ["EXDATE","RDATE"].iter().any(|name| event.multi_properties().contains_key(*name));
+	let isRuleCustom = isRecurrenceKept && event.property_value("RRULE").is_some_and(|rule| recurrence_parse(rule).is_none());
+	if (isStartMoved && !isSeriesRemoved && (hasExceptions || isRuleCustom))
+	{
+		return Err(CalendarCodecError::SeriesMoveUnsupported);
+	}
+	if (!isRecurrenceKept)
+	{
+		event.remove_property("RRULE");
+		if let Some(recurrence) = &edit.recurrence
+		{
+			event.append_property(Property::new("RRULE",recurrence_format(recurrence,&edit.start)?));
+		}
+	}
+	if (isSeriesRemoved)
+	{
+		event.remove_multi_property("EXDATE");
+		event.remove_multi_property("RDATE");
+	}
+	let sequence = event.get_sequence().unwrap_or(0).saturating_add(1);
+	event.sequence(sequence);
+	event.append_property(Property::new("DTSTAMP",format_utc(now)));
+	if (isSeriesRemoved)
+	{
+		calendar.components.retain(|component| match component
+		{
+			CalendarComponent::Event(event) => event.get_uid() != Some(uid) || event.get_recurrence_id().is_none(),
+			_ => true,
+		});
+	}
+	return calendar_content_get(&calendar);
+}
+
+/// changes a single occurrence through a RECURRENCE-ID override, the one already written for it when there is one
+pub(super) fn override_occurrence(
+	input: &str,
+	uid: &str,
+	occurrence: &CalendarMoment,
+	edit: &CalendarCreateInput,
+	floatingTimezone: &str,
+	now: OffsetDateTime,
+) -> Result<String,CalendarCodecError>
+{
+	input_check(edit)?;
+	if (edit.recurrence.is_some())
+	{
+		return Err(CalendarCodecError::InvalidCreateInput);
+	}
+	let mut calendar = calendar_parse(input)?;
+	let master = calendar.events()
+		.find(|event| event.get_uid() == Some(uid) && event.get_recurrence_id().is_none())
+		.ok_or(CalendarCodecError::MasterEventNotFound)?;
+	if (master.property_value("RRULE").is_none() && !master.multi_properties().contains_key("RDATE"))
+	{
+		return Err(CalendarCodecError::EventIsNotRecurring);
+	}
+	let masterSequence = master.get_sequence().unwrap_or(0);
+	let masterOrganizer = master.properties().get("ORGANIZER").cloned();
+	let masterAttendees = master.multi_properties().get("ATTENDEE").cloned().unwrap_or_default();
+
+	let existing = calendar.events_mut().find(|event| {
+		return event.get_uid() == Some(uid) && event.properties().get("RECURRENCE-ID")
+			.and_then(DatePerhapsTime::from_property)
+			.and_then(|moment| moment_from_icalendar(moment,floatingTimezone).ok())
+			.is_some_and(|moment| moment == *occurrence);
+	});
+	match existing
+	{
+		Some(event) =>
+		{
+			event_fields_set(event,edit)?;
+			let sequence = event.get_sequence().unwrap_or(masterSequence).max(masterSequence).saturating_add(1);
+			event.sequence(sequence);
+			event.append_property(Property::new("DTSTAMP",format_utc(now)));
+		},
+		None =>
+		{
+			let mut event = Event::with_uid(uid);
+			event.append_property(occurrence_property("RECURRENCE-ID",occurrence)?);
+			event_fields_set(&mut event,edit)?;
+			event.sequence(masterSequence.saturating_add(1));
+			event.append_property(Property::new("DTSTAMP",format_utc(now)));
+			if let Some(organizer) = masterOrganizer
+			{
+				event.append_property(organizer);
+			}
+			for attendee in masterAttendees
+			{
+				event.append_multi_property(attendee);
+			}
+			calendar.push(event);
+		},
+	}
+	return calendar_content_get(&calendar);
+}
+
+fn calendar_parse(input: &str) -> Result<Calendar,CalendarCodecError>
+{
+	if (input.len() > CALENDAR_MAX_ICALENDAR_BYTES)
+	{
+		return Err(CalendarCodecError::InputTooLarge);
+	}
+	return input.parse().map_err(|_| CalendarCodecError::InvalidCalendar);
+}
+
+fn calendar_content_get(calendar: &Calendar) -> Result<String,CalendarCodecError>
+{
+	let content = calendar.to_string();
+	if (content.len() > CALENDAR_MAX_ICALENDAR_BYTES)
+	{
+		return Err(CalendarCodecError::InputTooLarge);
+	}
+	return Ok(content);
+}
+
+pub(super) fn invitation_parse(input: &str, floatingTimezone: &str) -> Result<CalendarInvitation,CalendarCodecError>
+{
+	let calendar = invitation_calendar_parse(input)?;
+	let event = invitation_event_get(&calendar)?;
+	bounded_field(event.get_uid().ok_or(CalendarCodecError::MissingUid)?,CALENDAR_MAX_UID_BYTES)?;
+	// an unknown timezone only hides the time, the calendar server still reads its VTIMEZONE
+	let start = event.get_start().and_then(|start| moment_from_icalendar(start,floatingTimezone).ok());
+	let end = event.get_end().and_then(|end| moment_from_icalendar(end,floatingTimezone).ok());
+	return Ok(CalendarInvitation {
+		title: bounded_field(event.get_summary().unwrap_or_default(),CALENDAR_MAX_TITLE_BYTES)?,
+		location: bounded_field(event.get_location().unwrap_or_default(),CALENDAR_MAX_LOCATION_BYTES)?,
+		organizer: event.property_value("ORGANIZER").and_then(mailto_address),
+		start,
+		end,
+		isCancelled: calendar.property_value("METHOD").is_some_and(|method| method.trim().eq_ignore_ascii_case("CANCEL")),
+	});
+}
+
+pub(super) fn invitation_uid_get(input: &str) -> Result<String,CalendarCodecError>
+{
+	let calendar = invitation_calendar_parse(input)?;
+	return bounded_field(invitation_event_get(&calendar)?.get_uid().ok_or(CalendarCodecError::MissingUid)?,CALENDAR_MAX_UID_BYTES);
+}
+
+/// whether the invitation may replace or remove the stored event: same uid and organizer, and a SEQUENCE not lower (RFC 5546 2.1.4)
+pub(super) fn invitation_isUpdateOf(stored: &str, input: &str) -> Result<bool,CalendarCodecError>
+{
+	let storedCalendar = calendar_parse(stored)?;
+	let storedEvent = invitation_event_get(&storedCalendar)?;
+	let calendar = invitation_calendar_parse(input)?;
+	let event = invitation_event_get(&calendar)?;
+	let organizer = |event: &Event| event.property_value("ORGANIZER").and_then(mailto_address);
+	let isSameOrganizer = match (organizer(storedEvent),organizer(event))
+	{
+		(Some(storedOrganizer),Some(organizer)) => storedOrganizer.eq_ignore_ascii_case(&organizer),
+		_ => false,
+	};
+	return Ok(isSameOrganizer
+		&& storedEvent.get_uid().is_some()
+		&& storedEvent.get_uid() == event.get_uid()
+		&& event.get_sequence().unwrap_or(0) >= storedEvent.get_sequence().unwrap_or(0));
+}
+
+/// the invitation as the attendee keeps it: without METHOD, with the answer in its ATTENDEE line
+pub(super) fn invitation_event_build(
+	input: &str,
+	attendee: &str,
+	answer: CalendarInvitationAnswer,
+) -> Result<CalendarBuiltEvent,CalendarCodecError>
+{
+	let mut calendar = invitation_calendar_parse(input)?;
+	let uid = bounded_field(invitation_event_get(&calendar)?.get_uid().ok_or(CalendarCodecError::MissingUid)?,CALENDAR_MAX_UID_BYTES)?;
+	calendar.properties.retain(|property| property.key() != "METHOD");
+	// the VTIMEZONE components stay, the event times refer to them
+	calendar.components.retain(|component| match component
+	{
+		CalendarComponent::Event(event) => event.get_uid() == Some(uid.as_str()),
+		CalendarComponent::Other(_) => true,
+		_ => false,
+	});
+	for component in &mut calendar.components
+	{
+		if let CalendarComponent::Event(event) = component
+		{
+			attendee_answer(event,attendee,answer);
+		}
+	}
+	let content = calendar.to_string();
+	if (content.len() > CALENDAR_MAX_ICALENDAR_BYTES)
+	{
+		return Err(CalendarCodecError::InputTooLarge);
+	}
+	return Ok(CalendarBuiltEvent {uid,content});
+}
+
+/// iTIP REPLY of the attendee to the organizer (RFC 5546 3.2.3)
+pub(super) fn invitation_reply_build(
+	input: &str,
+	attendee: &str,
+	answer: CalendarInvitationAnswer,
+	now: OffsetDateTime,
+) -> Result<String,CalendarCodecError>
+{
+	let calendar = invitation_calendar_parse(input)?;
+	let invited = invitation_event_get(&calendar)?;
+	let uid = bounded_field(invited.get_uid().ok_or(CalendarCodecError::MissingUid)?,CALENDAR_MAX_UID_BYTES)?;
+	let mut event = Event::with_uid(&uid);
+	event.append_property(Property::new("DTSTAMP",format_utc(now)));
+	event.sequence(invited.get_sequence().unwrap_or(0));
+	for name in ["ORGANIZER","RECURRENCE-ID","SUMMARY"]
+	{
+		if let Some(property) = invited.properties().get(name)
+		{
+			event.append_property(property.clone());
+		}
+	}
+	event.append_multi_property(attendee_property(attendee,None,answer));
+
+	let mut reply = Calendar::new();
+	reply.properties.retain(|property| property.key() != "PRODID");
+	reply.append_property(Property::new("PRODID","-//WebHome//Calendar//EN"));
+	reply.append_property(Property::new("METHOD","REPLY"));
+	reply.push(event);
+	return Ok(reply.to_string());
+}
+
+fn invitation_calendar_parse(input: &str) -> Result<Calendar,CalendarCodecError>
+{
+	if (input.len() > CALENDAR_MAX_ICALENDAR_BYTES)
+	{
+		return Err(CalendarCodecError::InputTooLarge);
+	}
+	return input.parse().map_err(|_| CalendarCodecError::InvalidCalendar);
+}
+
+/// the master event, or the first one for an invitation to a single occurrence
+fn invitation_event_get(calendar: &Calendar) -> Result<&Event,CalendarCodecError>
+{
+	return calendar.events().find(|event| event.get_recurrence_id().is_none())
+		.or_else(|| calendar.events().next())
+		.ok_or(CalendarCodecError::MasterEventNotFound);
+}
+
+fn attendee_answer(event: &mut Event, attendee: &str, answer: CalendarInvitationAnswer)
+{
+	let mut isFound = false;
+	let mut attendees = event.multi_properties().get("ATTENDEE").cloned().unwrap_or_default();
+	for property in &mut attendees
+	{
+		if (mailto_address(property.value()).is_some_and(|address| address.eq_ignore_ascii_case(attendee)))
+		{
+			*property = attendee_property(attendee,Some(property),answer);
+			isFound = true;
+		}
+	}
+	if (!isFound)
+	{
+		attendees.push(attendee_property(attendee,None,answer));
+	}
+	event.remove_multi_property("ATTENDEE");
+	for property in attendees
+	{
+		event.append_multi_property(property);
+	}
+}
+
+/// the ATTENDEE line of the answer, it keeps the parameters of the invited one except the pending RSVP
+fn attendee_property(attendee: &str, invited: Option<&Property>, answer: CalendarInvitationAnswer) -> Property
+{
+	let mut property = Property::new("ATTENDEE",format!("mailto:{attendee}"));
+	for (name,value) in invited.map(Property::params).into_iter().flatten()
+	{
+		if (name != "PARTSTAT" && name != "RSVP")
+		{
+			property.add_parameter(name,value.value());
+		}
+	}
+	return property.add_parameter("PARTSTAT",answer.partstat_get()).done();
+}
+
+fn mailto_address(value: &str) -> Option<String>
+{
+	let value = value.trim();
+	let address = value.get(..7).filter(|scheme| scheme.eq_ignore_ascii_case("mailto:")).map_or(value,|_| &value[7..]);
+	if (!address.contains('@') || address.contains(|character: char| character.is_whitespace() || character.is_control()))
+	{
+		return None;
+	}
+	return Some(address.to_string());
+}
+
+/// EXDATE or RECURRENCE-ID of an occurrence, in UTC for a timed one
+fn occurrence_property(name: &str, occurrence: &CalendarMoment) -> Result<Property,CalendarCodecError>
 {
 	return match occurrence
 	{
-		CalendarMoment::AllDay(date) => Ok(Property::new("EXDATE",format_date(*date)).add_parameter("VALUE","DATE").done()),
+		CalendarMoment::AllDay(date) => Ok(Property::new(name,format_date(*date)).add_parameter("VALUE","DATE").done()),
 		CalendarMoment::Timed(timestamp) =>
 		{
 			let dateTime = OffsetDateTime::from_unix_timestamp(*timestamp).map_err(|_| CalendarCodecError::UnsupportedDateTime)?;
-			Ok(Property::new("EXDATE",format_utc(dateTime)))
+			Ok(Property::new(name,format_utc(dateTime)))
 		},
 	};
 }
@@ -425,8 +825,11 @@ fn format_utc(dateTime: OffsetDateTime) -> String
 mod tests
 {
 	use super::{
-		CalendarResourceSource, build_event, event_is_recurring, exclude_occurrence, parse_expanded_events,
+		CalendarCodecError, CalendarResourceSource, build_event, editable_event_get, event_is_recurring, exclude_occurrence,
+		invitation_event_build, invitation_isUpdateOf, invitation_parse, invitation_reply_build, invitation_uid_get,
+		override_occurrence, parse_expanded_events, recurrence_parse, update_event,
 	};
+	use crate::front::modules::calendar::invitation::CalendarInvitationAnswer;
 	use crate::front::modules::calendar::domain::{
 		CalendarCreateInput, CalendarCreateMoment, CalendarMoment, CalendarRecurrence,
 		CalendarRecurrenceEnd, CalendarRecurrenceFrequency, CalendarRejectedReason,
@@ -536,4 +939,168 @@ mod tests
 		assert_eq!(event_is_recurring(recurring,"series"),Ok(true));
 		assert_eq!(event_is_recurring(simple,"single"),Ok(false));
 	}
+
+	const INVITATION: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Test//EN\r\nMETHOD:REQUEST\r\nBEGIN:VEVENT\r\nUID:meeting@example.invalid\r\nDTSTAMP:20260801T080000Z\r\nDTSTART:20260813T080000Z\r\nDTEND:20260813T090000Z\r\nSEQUENCE:1\r\nSUMMARY:Review\r\nLOCATION:Room 2\r\nORGANIZER;CN=Alice:mailto:alice@example.invalid\r\nATTENDEE;CN=Bob;ROLE=REQ-PARTICIPANT;PARTSTAT=NEEDS-ACTION;RSVP=TRUE:mailto:bob@example.invalid\r\nATTENDEE;PARTSTAT=NEEDS-ACTION:mailto:carol@example.invalid\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+	#[test]
+	fn invitation_parsesTheRequestAndItsCancellation()
+	{
+		let invitation = invitation_parse(INVITATION,"UTC").unwrap();
+
+		assert_eq!(invitation.title,"Review");
+		assert_eq!(invitation.location,"Room 2");
+		assert_eq!(invitation.organizer.as_deref(),Some("alice@example.invalid"));
+		assert_eq!(invitation.start,Some(CalendarMoment::Timed(1_786_608_000)));
+		assert_eq!(invitation.end,Some(CalendarMoment::Timed(1_786_611_600)));
+		assert!(!invitation.isCancelled);
+		assert!(invitation_parse(&INVITATION.replace("METHOD:REQUEST","METHOD:CANCEL"),"UTC").unwrap().isCancelled);
+	}
+
+	#[test]
+	fn invitationEvent_keepsTheAnswerWithoutTheMethod()
+	{
+		let built = invitation_event_build(INVITATION,"BOB@example.invalid",CalendarInvitationAnswer::Tentative).unwrap();
+
+		assert_eq!(built.uid,"meeting@example.invalid");
+		assert!(!built.content.contains("METHOD:"));
+		assert!(!built.content.contains("RSVP"));
+		assert!(built.content.contains("ROLE=REQ-PARTICIPANT"));
+		assert!(built.content.contains("PARTSTAT=TENTATIVE"));
+		assert_eq!(built.content.matches("PARTSTAT=NEEDS-ACTION").count(),1);
+		assert_eq!(built.content.matches("ATTENDEE").count(),2);
+	}
+
+	#[test]
+	fn invitation_updatesOnlyTheEventOfItsOrganizerWithoutGoingBack()
+	{
+		let stored = invitation_event_build(INVITATION,"bob@example.invalid",CalendarInvitationAnswer::Accepted).unwrap().content;
+
+		assert!(invitation_isUpdateOf(&stored,INVITATION).unwrap());
+		assert!(invitation_isUpdateOf(&stored,&INVITATION.replace("SEQUENCE:1","SEQUENCE:2").replace("METHOD:REQUEST","METHOD:CANCEL")).unwrap());
+		assert!(!invitation_isUpdateOf(&stored,&INVITATION.replace("SEQUENCE:1","SEQUENCE:0")).unwrap());
+		assert!(!invitation_isUpdateOf(&stored,&INVITATION.replace("mailto:alice@","mailto:mallory@")).unwrap());
+		assert!(!invitation_isUpdateOf(&stored.replace("ORGANIZER;CN=Alice:mailto:alice@example.invalid\r\n",""),INVITATION).unwrap());
+		assert_eq!(invitation_uid_get(INVITATION).unwrap(),"meeting@example.invalid");
+	}
+
+	#[test]
+	fn invitationReply_answersOnlyForTheAttendee()
+	{
+		let reply = invitation_reply_build(INVITATION,"bob@example.invalid",CalendarInvitationAnswer::Declined,OffsetDateTime::from_unix_timestamp(1_786_608_000).unwrap()).unwrap();
+
+		assert!(reply.contains("METHOD:REPLY"));
+		assert!(reply.contains("UID:meeting@example.invalid"));
+		assert!(reply.contains("SEQUENCE:1"));
+		assert!(reply.contains("ORGANIZER;CN=Alice:mailto:alice@example.invalid"));
+		assert!(reply.contains("ATTENDEE;PARTSTAT=DECLINED:mailto:bob@example.invalid"));
+		assert!(!reply.contains("carol"));
+		assert!(!reply.contains("PRODID:-//Test//EN"));
+	}
+
+	fn editInput(title: &str,recurrence: Option<CalendarRecurrence>) -> CalendarCreateInput
+	{
+		let startDate = Date::from_calendar_date(2026,Month::August,14).unwrap();
+		return CalendarCreateInput {
+			title: title.to_string(),
+			description: String::new(),
+			location: "Room 3".to_string(),
+			start: CalendarCreateMoment::Local(PrimitiveDateTime::new(startDate,Time::from_hms(10,0,0).unwrap())),
+			end: CalendarCreateMoment::Local(PrimitiveDateTime::new(startDate,Time::from_hms(11,0,0).unwrap())),
+			timezone: "UTC".to_string(),
+			recurrence,
+		};
+	}
+
+	#[test]
+	fn editableEvent_readsSimpleRulesAndFlagsCustomOnes()
+	{
+		let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:series\r\nDTSTART:20260813T080000Z\r\nDTEND:20260813T090000Z\r\nRRULE:FREQ=WEEKLY;INTERVAL=2;COUNT=4\r\nSUMMARY:Weekly\r\nLOCATION:Room 2\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+		let editable = editable_event_get(input,"series","UTC").unwrap();
+		let custom = editable_event_get(&input.replace("COUNT=4","BYDAY=MO,TH"),"series","UTC").unwrap();
+
+		assert_eq!(editable.title,"Weekly");
+		assert_eq!(editable.location,"Room 2");
+		assert_eq!(editable.start,CalendarMoment::Timed(1_786_608_000));
+		assert_eq!(editable.recurrence,Some(CalendarRecurrence {
+			frequency: CalendarRecurrenceFrequency::Weekly,
+			interval: 2,
+			end: CalendarRecurrenceEnd::Count(4),
+		}));
+		assert!(!editable.isRecurrenceCustom);
+		assert!(custom.isRecurrenceCustom);
+	}
+
+	#[test]
+	fn recurrenceParse_refusesAnUntilThatIsNotADate()
+	{
+		assert!(recurrence_parse("FREQ=DAILY;UNTIL=202€00").is_none());
+		assert!(recurrence_parse("FREQ=DAILY;UNTIL=2026-8-13").is_none());
+		assert!(matches!(
+			recurrence_parse("FREQ=DAILY;UNTIL=20260813T080000Z").map(|recurrence| recurrence.end),
+			Some(CalendarRecurrenceEnd::Until {..})
+		));
+	}
+
+	#[test]
+	fn updateEvent_keepsAttendeesAndIncrementsSequence()
+	{
+		let updated = update_event(INVITATION,"meeting@example.invalid",&editInput("Moved review",None),false,OffsetDateTime::from_unix_timestamp(1_786_608_000).unwrap()).unwrap();
+
+		assert!(updated.contains("SUMMARY:Moved review"));
+		assert!(updated.contains("DTSTART;TZID=UTC:20260814T100000"));
+		assert!(updated.contains("LOCATION:Room 3"));
+		assert!(updated.contains("SEQUENCE:2"));
+		assert_eq!(updated.matches("ATTENDEE").count(),2);
+		assert!(!updated.contains("SUMMARY:Review"));
+	}
+
+	#[test]
+	fn updateEvent_refusesToMoveASeriesWithExceptionsOrACustomRule()
+	{
+		let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Test//EN\r\nBEGIN:VEVENT\r\nUID:series\r\nDTSTART:20260813T080000Z\r\nDTEND:20260813T090000Z\r\nRRULE:FREQ=DAILY\r\nSUMMARY:Daily\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+		let excluded = input.replace("RRULE:FREQ=DAILY\r\n","RRULE:FREQ=DAILY\r\nEXDATE:20260815T080000Z\r\n");
+		let custom = input.replace("FREQ=DAILY","FREQ=WEEKLY;BYDAY=MO,TH");
+		let now = OffsetDateTime::from_unix_timestamp(1_786_608_000).unwrap();
+		let overridden = override_occurrence(input,"series",&CalendarMoment::Timed(1_786_694_400),&editInput("Moved",None),"UTC",now).unwrap();
+		let sameStart = |mut edit: CalendarCreateInput| {
+			let startDate = Date::from_calendar_date(2026,Month::August,13).unwrap();
+			edit.start = CalendarCreateMoment::Local(PrimitiveDateTime::new(startDate,Time::from_hms(8,0,0).unwrap()));
+			edit.end = CalendarCreateMoment::Local(PrimitiveDateTime::new(startDate,Time::from_hms(9,0,0).unwrap()));
+			return edit;
+		};
+
+		assert!(update_event(input,"series",&editInput("Moved",None),true,now).unwrap().contains("DTSTART;TZID=UTC:20260814T100000"));
+		for input in [&excluded,&custom,&overridden]
+		{
+			assert_eq!(update_event(input,"series",&editInput("Moved",None),true,now),Err(CalendarCodecError::SeriesMoveUnsupported));
+			assert!(update_event(input,"series",&sameStart(editInput("Renamed",None)),true,now).unwrap().contains("SUMMARY:Renamed"));
+		}
+		let removed = update_event(&excluded,"series",&editInput("Single",None),false,now).unwrap();
+		assert!(!removed.contains("EXDATE"));
+	}
+
+	#[test]
+	fn overrideOccurrence_writesOneRecurrenceIdPerOccurrence()
+	{
+		let input = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Test//EN\r\nBEGIN:VEVENT\r\nUID:series\r\nDTSTART:20260813T080000Z\r\nDTEND:20260813T090000Z\r\nRRULE:FREQ=DAILY\r\nSEQUENCE:2\r\nSUMMARY:Daily\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+		let occurrence = CalendarMoment::Timed(1_786_694_400);
+		let now = OffsetDateTime::from_unix_timestamp(1_786_608_000).unwrap();
+
+		let overridden = override_occurrence(input,"series",&occurrence,&editInput("Moved",None),"UTC",now).unwrap();
+		let updated = override_occurrence(&overridden,"series",&occurrence,&editInput("Moved again",None),"UTC",now).unwrap();
+
+		assert!(overridden.contains("RECURRENCE-ID:20260814T080000Z"));
+		assert!(overridden.contains("SUMMARY:Daily"));
+		assert!(overridden.contains("SUMMARY:Moved"));
+		assert!(overridden.contains("SEQUENCE:3"));
+		assert_eq!(updated.matches("RECURRENCE-ID").count(),1);
+		assert!(updated.contains("SUMMARY:Moved again"));
+		assert!(updated.contains("SEQUENCE:4"));
+		assert!(override_occurrence(input,"series",&occurrence,&editInput("Moved",Some(CalendarRecurrence {
+			frequency: CalendarRecurrenceFrequency::Daily,
+			interval: 1,
+			end: CalendarRecurrenceEnd::Never,
+		})),"UTC",now).is_err());
+	}
 }
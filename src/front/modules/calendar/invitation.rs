@@ -0,0 +1,181 @@
+use super::domain::{CalendarCollection,CalendarConfig,CalendarMoment};
+use super::view::{collectionLabel_fallback,eventMoment_label};
+#[cfg(feature = "hydrate")]
+use super::browserTimezone_get;
+#[cfg(feature = "hydrate")]
+use super::caldav::CalDavClient;
+#[cfg(feature = "hydrate")]
+use super::icalendar_codec::{invitation_event_build,invitation_parse,invitation_reply_build};
+#[cfg(feature = "hydrate")]
+use super::view::calDavError_key;
+use crate::front::modules::module_holder::ModuleHolder;
+use crate::front::modules::module_type::ModuleType;
+use leptos::prelude::{ArcRwSignal,GetUntracked,WithUntracked};
+#[cfg(feature = "hydrate")]
+use time::OffsetDateTime;
+
+/// participation of the attendee, the PARTSTAT of its iTIP answer
+#[derive(Clone,Copy,Debug,Eq,PartialEq)]
+pub(crate) enum CalendarInvitationAnswer
+{
+	Accepted,
+	Tentative,
+	Declined,
+}
+
+impl CalendarInvitationAnswer
+{
+	pub(crate) const ALL: [Self; 3] = [Self::Accepted,Self::Tentative,Self::Declined];
+
+	pub(crate) fn translateKey_get(self) -> &'static str
+	{
+		return match self
+		{
+			Self::Accepted => "MODULE_CALENDAR_INVITATION_ACCEPT",
+			Self::Tentative => "MODULE_CALENDAR_INVITATION_TENTATIVE",
+			Self::Declined => "MODULE_CALENDAR_INVITATION_DECLINE",
+		};
+	}
+
+	#[cfg(any(feature = "hydrate",test))]
+	pub(super) fn partstat_get(self) -> &'static str
+	{
+		return match self
+		{
+			Self::Accepted => "ACCEPTED",
+			Self::Tentative => "TENTATIVE",
+			Self::Declined => "DECLINED",
+		};
+	}
+
+	/// a declined invitation stays out of the calendar, a copy kept by an earlier answer is removed
+	pub(crate) fn isStored(self) -> bool
+	{
+		return self != Self::Declined;
+	}
+}
+
+/// meeting invitation received by mail (iTIP REQUEST or CANCEL)
+#[derive(Clone,Debug,Eq,PartialEq)]
+#[cfg_attr(not(feature = "hydrate"),allow(dead_code))]
+pub(crate) struct CalendarInvitation
+{
+	pub(crate) title: String,
+	pub(crate) location: String,
+	pub(crate) organizer: Option<String>,
+	pub(super) start: Option<CalendarMoment>,
+	pub(super) end: Option<CalendarMoment>,
+	pub(crate) isCancelled: bool,
+}
+
+impl CalendarInvitation
+{
+	#[cfg(feature = "hydrate")]
+	pub(crate) fn parse(calendar: &str) -> Option<Self>
+	{
+		return invitation_parse(calendar,&browserTimezone_get()).ok();
+	}
+
+	#[cfg(not(feature = "hydrate"))]
+	pub(crate) fn parse(_calendar: &str) -> Option<Self>
+	{
+		return None;
+	}
+
+	pub(crate) fn period_label(&self) -> Option<String>
+	{
+		let start = self.start.as_ref()?;
+		// the end of an all-day event is the day after its last one
+		let end = match &self.end
+		{
+			Some(CalendarMoment::AllDay(date)) => date.previous_day().map(CalendarMoment::AllDay),
+			end => end.clone(),
+		};
+		return Some(match end.filter(|end| end != start)
+		{
+			Some(end) => format!("{} – {}",eventMoment_label(start),eventMoment_label(&end)),
+			None => eventMoment_label(start),
+		});
+	}
+}
+
+/// collection of a calendar module of the dashboard, where an answered invitation can be kept
+#[derive(Clone)]
+#[cfg_attr(not(feature = "hydrate"),allow(dead_code))]
+pub(crate) struct CalendarInvitationTarget
+{
+	config: ArcRwSignal<CalendarConfig>,
+	collection: CalendarCollection,
+	pub(crate) label: String,
+}
+
+impl CalendarInvitationTarget
+{
+	pub(crate) fn list_get() -> Vec<Self>
+	{
+		let modules = ModuleHolder::getSingleton().with_untracked(|holder| holder.blocks_view());
+		let mut targets = Vec::new();
+		for (_,positions) in modules
+		{
+			let Some(config) = positions.with_untracked(|positions| match positions.inner()
+			{
+				ModuleType::CALENDAR(calendar) => Some(calendar.config.clone()),
+				_ => None,
+			}) else {continue};
+			let current = config.get_untracked();
+			for collection in current.collections
+			{
+				let name = if (collection.name.trim().is_empty()) {collectionLabel_fallback(&collection.href)} else {collection.name.clone()};
+				let label = if (current.title.trim().is_empty()) {name} else {format!("{} – {}",current.title.trim(),name)};
+				targets.push(Self {config: config.clone(),collection,label});
+			}
+		}
+		return targets;
+	}
+
+	/// writes the invitation with the answer of the attendee, a decline removes it. Errors are translation keys
+	#[cfg(feature = "hydrate")]
+	pub(crate) async fn invitation_store(&self, calendar: &str, attendee: &str, answer: CalendarInvitationAnswer) -> Result<(),&'static str>
+	{
+		if (!answer.isStored())
+		{
+			return self.invitation_remove(calendar).await;
+		}
+		let built = invitation_event_build(calendar,attendee,answer).map_err(|error| calDavError_key(error.into()))?;
+		let client = CalDavClient::new(&self.config.get_untracked()).map_err(calDavError_key)?;
+		return client.invitation_store(&self.collection,&built).await.map_err(calDavError_key);
+	}
+
+	#[cfg(not(feature = "hydrate"))]
+	pub(crate) async fn invitation_store(&self, _calendar: &str, _attendee: &str, _answer: CalendarInvitationAnswer) -> Result<(),&'static str>
+	{
+		return Err("MODULE_CALENDAR_ERROR_CONFIGURATION");
+	}
+
+	/// removes the event of a cancelled or declined invitation, errors are translation keys
+	#[cfg(feature = "hydrate")]
+	pub(crate) async fn invitation_remove(&self, calendar: &str) -> Result<(),&'static str>
+	{
+		let client = CalDavClient::new(&self.config.get_untracked()).map_err(calDavError_key)?;
+		return client.invitation_remove(&self.collection,calendar).await.map_err(calDavError_key);
+	}
+
+	#[cfg(not(feature = "hydrate"))]
+	pub(crate) async fn invitation_remove(&self, _calendar: &str) -> Result<(),&'static str>
+	{
+		return Err("MODULE_CALENDAR_ERROR_CONFIGURATION");
+	}
+}
+
+/// iTIP REPLY to send to the organizer
+#[cfg(feature = "hydrate")]
+pub(crate) fn invitation_reply_get(calendar: &str, attendee: &str, answer: CalendarInvitationAnswer) -> Option<String>
+{
+	return invitation_reply_build(calendar,attendee,answer,OffsetDateTime::now_utc()).ok();
+}
+
+#[cfg(not(feature = "hydrate"))]
+pub(crate) fn invitation_reply_get(_calendar: &str, _attendee: &str, _answer: CalendarInvitationAnswer) -> Option<String>
+{
+	return None;
+}
@@ -2,10 +2,15 @@ use super::{CalendarLoadState,CalendarRuntime,browserTimezone_get,browser_today_
 use super::caldav::CalDavError;
 #[cfg(feature = "hydrate")]
 use super::caldav::CalDavClient;
+#[cfg(feature = "hydrate")]
+use super::icalendar_codec::editable_event_get;
+#[cfg(feature = "hydrate")]
+use super::domain::master_period_reschedule;
 use super::domain::{
-	CALENDAR_MAX_COLLECTIONS,CalendarCollection,CalendarConfig,CalendarCreateInput,CalendarCreateMoment,CalendarEvent,
-	CalendarConfigError,CalendarHolidayError,CalendarMoment,CalendarRecurrence,CalendarRecurrenceEnd,
-	CalendarRecurrenceFrequency,CalendarRejectedReason,CalendarViewMode,
+	CALENDAR_MAX_AGENDA_DAYS,CALENDAR_MAX_COLLECTIONS,CalendarCollection,CalendarConfig,CalendarCreateInput,CalendarCreateMoment,CalendarDrop,
+	CalendarEditableEvent,CalendarEvent,CalendarStoredEvent,CalendarConfigError,CalendarHolidayError,CalendarMoment,
+	CalendarPeriod,CalendarRecurrence,CalendarRecurrenceEnd,CalendarRecurrenceFrequency,CalendarRejectedReason,
+	CalendarViewMode,
 };
 use crate::api::modules::components::ModuleID;
 use crate::front::modules::components::{Cache,FieldHelper,FieldHelperType};
@@ -19,7 +24,7 @@ use crate::HWebTrace;
 use leptoaster::expect_toaster;
 use leptos::prelude::{
 	ArcRwSignal,AriaAttributes,BindAttribute,ClassAttribute,CollectView,Effect,ElementChild,Get,GetUntracked,
-	GlobalAttributes,IntoAny,OnAttribute,PropAttribute,RwSignal,Set,StyleAttribute,Update,
+	GlobalAttributes,IntoAny,OnAttribute,PropAttribute,RwSignal,Set,StyleAttribute,Update,WithUntracked,
 	event_target_checked,use_context,
 };
 use leptos::{component,view,IntoView};
@@ -27,6 +32,8 @@ use std::collections::{HashMap,HashSet};
 use time::{Date,Duration,Month,PrimitiveDateTime,Time,Weekday};
 #[cfg(not(feature = "hydrate"))]
 use time::OffsetDateTime;
+#[cfg(feature = "hydrate")]
+use wasm_bindgen::JsCast;
 
 #[component]
 pub(super) fn CalendarDraw(
@@ -54,8 +61,8 @@ pub(super) fn CalendarDraw(
 		if (!currentEditMode && previousEditMode != Some(false))
 		{
 			let anchor = browser_today_get();
-			let viewMode = effectConfig.get_untracked().viewMode;
-			let periodChanged = effectRuntime.try_update(|runtime| runtime.period_set(anchor,viewMode))
+			let config = effectConfig.get_untracked();
+			let periodChanged = effectRuntime.try_update(|runtime| runtime.period_set(anchor,config.viewMode,config.agendaDays))
 				.unwrap_or(false);
 			if (periodChanged || previousEditMode == Some(true))
 			{
@@ -154,6 +161,15 @@ fn CalendarConfigDraw(
 				.collect::<String>()
 				.to_ascii_uppercase();
 		});
+	let mut agendaDaysField = FieldHelper::new(&config,&update,"MODULE_CALENDAR_AGENDA_DAYS",
+		|config| config.get().agendaDays.to_string(),
+		|event,config| {
+			if let Ok(days) = event.target().value().trim().parse::<u8>()
+			{
+				config.agendaDays = days.clamp(1,CALENDAR_MAX_AGENDA_DAYS);
+			}
+		});
+	agendaDaysField.setInputType(FieldHelperType::NUMBER(1,CALENDAR_MAX_AGENDA_DAYS as i64));
 	let serverInputRef = serverField.inputRef_get();
 	let usernameInputRef = usernameField.inputRef_get();
 	let passwordInputRef = passwordField.inputRef_get();
@@ -298,6 +314,7 @@ fn CalendarConfigDraw(
 				</label>
 				{holidayCountryField.draw()}
 				<p class="module_config_help"><TranslateText key="MODULE_CALENDAR_HOLIDAY_COUNTRY_HELP"/></p>
+				{agendaDaysField.draw()}
 			</fieldset>
 		</div>
 	}
@@ -315,7 +332,7 @@ fn collectionOptions_get(config: &CalendarConfig,runtime: &CalendarRuntime) -> V
 	return collections;
 }
 
-fn collectionLabel_fallback(href: &str) -> String
+pub(super) fn collectionLabel_fallback(href: &str) -> String
 {
 	return url::Url::parse(href).ok()
 		.and_then(|url| url.path_segments().and_then(|segments| segments.filter(|segment| !segment.is_empty()).next_back()).map(str::to_string))
@@ -353,22 +370,6 @@ fn CalendarContentDraw(
 	let today = move |_| calendarPeriod_set(
 		browser_today_get(),todayConfig.clone(),todayRuntime.clone(),todayActions.clone(),todayId.clone(),
 	);
-	let monthConfig = config.clone();
-	let monthRuntime = runtime.clone();
-	let monthUpdate = update.clone();
-	let monthActions = moduleActions.clone();
-	let monthId = moduleId.clone();
-	let month = move |_| calendarViewMode_set(
-		CalendarViewMode::Month,monthConfig.clone(),monthRuntime.clone(),monthUpdate.clone(),monthActions.clone(),monthId.clone(),
-	);
-	let weekConfig = config.clone();
-	let weekRuntime = runtime.clone();
-	let weekUpdate = update.clone();
-	let weekActions = moduleActions.clone();
-	let weekId = moduleId.clone();
-	let week = move |_| calendarViewMode_set(
-		CalendarViewMode::Week,weekConfig.clone(),weekRuntime.clone(),weekUpdate.clone(),weekActions.clone(),weekId.clone(),
-	);
 	let refreshActions = moduleActions.clone();
 	let refreshId = moduleId.clone();
 	let refresh = move |_| (refreshActions.refreshFn)(refreshId.clone());
@@ -385,12 +386,10 @@ fn CalendarContentDraw(
 	let gridActions = moduleActions.clone();
 	let gridId = moduleId.clone();
 	let gridDialog = dialogManager.clone();
+	let gridDrag = CalendarGridDrag::new();
 	let titleConfig = config.clone();
-	let monthClassConfig = config.clone();
-	let monthPressedConfig = config.clone();
-	let weekClassConfig = config.clone();
-	let weekPressedConfig = config.clone();
 	let labelRuntime = runtime.clone();
+	let labelConfig = config.clone();
 	let statusRuntime = runtime.clone();
 	let viewSwitchLabelId = format!("calendar-view-switch-{}",moduleId.id);
 	let viewSwitchLabelledBy = viewSwitchLabelId.clone();
@@ -432,39 +431,78 @@ fn CalendarContentDraw(
 						<span class="visually_hidden"><TranslateText key="MODULE_CALENDAR_NEXT"/></span>
 					</button>
 				</div>
-				<div class="module_calendar_period_label" aria-live="polite">{move || calendarPeriodLabel_view(&labelRuntime.get())}</div>
+				<div class="module_calendar_period_label" aria-live="polite">{move || calendarPeriodLabel_view(&labelRuntime.get(),labelConfig.get().viewMode)}</div>
 				<div class="module_calendar_view_switch" role="group" aria-labelledby={viewSwitchLabelledBy}>
 					<span id={viewSwitchLabelId} class="visually_hidden"><TranslateText key="MODULE_CALENDAR_VIEW"/></span>
-					<button
-						type="button"
-						class:active=move || monthClassConfig.get().viewMode == CalendarViewMode::Month
-						aria-pressed=move || monthPressedConfig.get().viewMode == CalendarViewMode::Month
-						on:click=month
-					><TranslateText key="MODULE_CALENDAR_MONTH"/></button>
-					<button
-						type="button"
-						class:active=move || weekClassConfig.get().viewMode == CalendarViewMode::Week
-						aria-pressed=move || weekPressedConfig.get().viewMode == CalendarViewMode::Week
-						on:click=week
-					><TranslateText key="MODULE_CALENDAR_WEEK"/></button>
+					{[
+						(CalendarViewMode::Month,"MODULE_CALENDAR_MONTH"),
+						(CalendarViewMode::Week,"MODULE_CALENDAR_WEEK"),
+						(CalendarViewMode::Day,"MODULE_CALENDAR_DAY"),
+						(CalendarViewMode::Agenda,"MODULE_CALENDAR_AGENDA"),
+					].into_iter().map(|(viewMode,key)| calendarViewSwitch_button(
+						viewMode,key,config.clone(),runtime.clone(),update.clone(),moduleActions.clone(),moduleId.clone(),
+					)).collect_view()}
 				</div>
 			</div>
 			{move || calendarStatus_view(&statusRuntime.get())}
 			<div class="module_calendar_grid_container">
 				{move || calendarGrid_view(
-					gridConfig.clone(),&gridRuntime.get(),gridActions.clone(),gridId.clone(),gridDialog.clone(),
+					gridConfig.clone(),&gridRuntime.get(),gridDrag,gridActions.clone(),gridId.clone(),gridDialog.clone(),
 				)}
 			</div>
 		</div>
 	}
 }
 
-fn calendarPeriodLabel_view(runtime: &CalendarRuntime) -> leptos::prelude::AnyView
+fn calendarViewSwitch_button(
+	viewMode: CalendarViewMode,
+	key: &'static str,
+	config: ArcRwSignal<CalendarConfig>,
+	runtime: ArcRwSignal<CalendarRuntime>,
+	update: ArcRwSignal<Cache>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+) -> impl IntoView
+{
+	let classConfig = config.clone();
+	let pressedConfig = config.clone();
+	view! {
+		<button
+			type="button"
+			class:active=move || classConfig.get().viewMode == viewMode
+			aria-pressed=move || pressedConfig.get().viewMode == viewMode
+			on:click=move |_| calendarViewMode_set(
+				viewMode,config.clone(),runtime.clone(),update.clone(),moduleActions.clone(),moduleId.clone(),
+			)
+		><TranslateText key={key}/></button>
+	}
+}
+
+fn calendarPeriodLabel_view(runtime: &CalendarRuntime,viewMode: CalendarViewMode) -> leptos::prelude::AnyView
 {
 	let anchor = runtime.anchor.unwrap_or_else(browser_today_get);
-	view! {
-		<span><TranslateText key={month_key(anchor.month())}/>{" "}{anchor.year()}</span>
-	}.into_any()
+	return match (viewMode,runtime.period)
+	{
+		(CalendarViewMode::Day,_) => view! {
+			<span>
+				<TranslateText key={weekday_key(anchor.weekday())}/>{format!(" {} ",anchor.day())}
+				<TranslateText key={month_key(anchor.month())}/>{format!(" {}",anchor.year())}
+			</span>
+		}.into_any(),
+		(CalendarViewMode::Agenda,Some(period)) =>
+		{
+			let last = period.endExclusive - Duration::DAY;
+			view! {
+				<span>
+					{format!("{} ",period.start.day())}<TranslateText key={month_key(period.start.month())}/>
+					{format!(" – {} ",last.day())}<TranslateText key={month_key(last.month())}/>{format!(" {}",last.year())}
+				</span>
+			}.into_any()
+		},
+		_ => view! {
+			<span><TranslateText key={month_key(anchor.month())}/>{" "}{anchor.year()}</span>
+		}.into_any(),
+	};
 }
 
 fn calendarStatus_view(runtime: &CalendarRuntime) -> leptos::prelude::AnyView
@@ -550,9 +588,83 @@ fn calendarStatus_view(runtime: &CalendarRuntime) -> leptos::prelude::AnyView
 	return view!{<div class="module_calendar_statuses">{notices}</div>}.into_any();
 }
 
+const CALENDAR_DAY_MINUTES: i64 = 24 * 60;
+const CALENDAR_DROP_STEP_MINUTES: i64 = 15;
+
+/// the event being dragged on the grid and the day it was taken from
+#[derive(Clone)]
+struct CalendarDrag
+{
+	event: CalendarEvent,
+	sourceDate: Date,
+	kind: CalendarDragKind,
+}
+
+#[derive(Clone,Copy)]
+enum CalendarDragKind
+{
+	/// grabMinutes is the distance between the event start and the pointer
+	Move {grabMinutes: i64},
+	Resize,
+}
+
+#[derive(Clone,Copy)]
+struct CalendarGridDrag
+{
+	current: RwSignal<Option<CalendarDrag>>,
+	hoveredDate: RwSignal<Option<Date>>,
+}
+
+impl CalendarGridDrag
+{
+	fn new() -> Self
+	{
+		return Self {
+			current: RwSignal::new(None),
+			hoveredDate: RwSignal::new(None),
+		};
+	}
+
+	fn start(&self,event: leptos::ev::DragEvent,drag: CalendarDrag)
+	{
+		if let Some(transfer) = event.data_transfer()
+		{
+			let _ = transfer.set_data("text/plain",&drag.event.title);
+			transfer.set_effect_allowed("move");
+		}
+		self.current.set(Some(drag));
+	}
+
+	fn hover(&self,event: leptos::ev::DragEvent,date: Date)
+	{
+		if (self.current.with_untracked(Option::is_none)) {return;}
+		event.prevent_default();
+		if (self.hoveredDate.get_untracked() != Some(date))
+		{
+			self.hoveredDate.set(Some(date));
+		}
+	}
+
+	fn take(&self,event: &leptos::ev::DragEvent) -> Option<CalendarDrag>
+	{
+		event.prevent_default();
+		self.hoveredDate.set(None);
+		let drag = self.current.get_untracked();
+		self.current.set(None);
+		return drag;
+	}
+
+	fn end(&self)
+	{
+		self.current.set(None);
+		self.hoveredDate.set(None);
+	}
+}
+
 fn calendarGrid_view(
 	configSignal: ArcRwSignal<CalendarConfig>,
 	runtime: &CalendarRuntime,
+	drag: CalendarGridDrag,
 	moduleActions: ModuleActionFn,
 	moduleId: ModuleID,
 	dialogManager: DialogManager,
@@ -560,16 +672,16 @@ fn calendarGrid_view(
 {
 	let config = configSignal.get_untracked();
 	let Some(period) = runtime.period else {return view!{}.into_any();};
+	if (config.viewMode == CalendarViewMode::Agenda)
+	{
+		return calendarAgenda_view(period,runtime,configSignal,moduleActions,moduleId,dialogManager);
+	}
 	let anchor = runtime.anchor.unwrap_or_else(browser_today_get);
-	let weekdays = [
-		"MODULE_CALENDAR_WEEKDAY_MON","MODULE_CALENDAR_WEEKDAY_TUE","MODULE_CALENDAR_WEEKDAY_WED",
-		"MODULE_CALENDAR_WEEKDAY_THU","MODULE_CALENDAR_WEEKDAY_FRI","MODULE_CALENDAR_WEEKDAY_SAT",
-		"MODULE_CALENDAR_WEEKDAY_SUN",
-	];
-	let viewClass = if (config.viewMode == CalendarViewMode::Week) {" week"} else {" month"};
+	// week and day views place timed events on hourly timelines under the all-day row
+	let isTimeline = matches!(config.viewMode,CalendarViewMode::Week | CalendarViewMode::Day);
 	let days = period.days().map(|date| {
 		let events = runtime.events.iter()
-			.filter(|event| event_overlapsLocalDate(event,date))
+			.filter(|event| event_overlapsLocalDate(event,date) && !(isTimeline && event_isTimed(event)))
 			.cloned()
 			.collect::<Vec<_>>();
 		let outside = config.viewMode == CalendarViewMode::Month && date.month() != anchor.month();
@@ -582,13 +694,35 @@ fn calendarGrid_view(
 		let id = moduleId.clone();
 		let dialog = dialogManager.clone();
 		let eventConfig = configSignal.clone();
+		let dropConfig = configSignal.clone();
+		let dropActions = moduleActions.clone();
+		let dropId = moduleId.clone();
+		let dropDialog = dialogManager.clone();
 		view! {
-			<div class="module_calendar_day" class:outside=outside class:today=today class:weekend=weekend class:holiday=holiday>
+			<div
+				class="module_calendar_day"
+				class:outside=outside
+				class:today=today
+				class:weekend=weekend
+				class:holiday=holiday
+				class:drop_target=move || drag.hoveredDate.get() == Some(date)
+				on:dragover=move |event| drag.hover(event,date)
+				on:drop=move |event| {
+					let Some(dragged) = drag.take(&event) else {return;};
+					if let CalendarDragKind::Move {..} = dragged.kind
+					{
+						calendarDrop_apply(
+							CalendarDrop::Days((date - dragged.sourceDate).whole_days()),dragged.event,
+							dropConfig.clone(),dropActions.clone(),dropId.clone(),dropDialog.clone(),
+						);
+					}
+				}
+			>
 				<div class="module_calendar_day_number">
 					{holiday.then(|| view! {
 						<span class="module_calendar_holiday_name" title={holidayTitle}>{holidayNames.join(", ")}</span>
 					})}
-					<span>{if (config.viewMode == CalendarViewMode::Week)
+					<span>{if (isTimeline)
 						{
 							format!("{:02}/{:02}",date.day(),u8::from(date.month()))
 						}
@@ -598,57 +732,352 @@ fn calendarGrid_view(
 						}}</span>
 				</div>
 				<div class="module_calendar_day_events">
-					{events.into_iter().map(|event| {
-						let eventView = event.clone();
-						let actions = actions.clone();
-						let id = id.clone();
-						let dialog = dialog.clone();
-						let config = eventConfig.clone();
-						let time = eventTime_get(&event,date);
-						let style = eventColor_style(&event);
-						let title = event.title.clone();
-						view! {
-							<button
-								type="button"
-								class="module_calendar_event"
-								style={style}
-								on:click=move |_| calendarEventDetails_open(eventView.clone(),config.clone(),actions.clone(),id.clone(),dialog.clone())
-							>
-								<span class="module_calendar_event_time">{
-									if let Some(time) = time
-									{
-										view!{<span>{time}</span>}.into_any()
-									}
-									else
-									{
-										view!{<TranslateText key="MODULE_CALENDAR_ALL_DAY_SHORT"/>}.into_any()
-									}
-								}</span>
-								<span class="module_calendar_event_title">{if (title.is_empty())
-								{
-									view!{<TranslateText key="FRONTUI_NOTITLE"/>}.into_any()
-								}
-								else
-								{
-									view!{<span>{title}</span>}.into_any()
-								}}</span>
-							</button>
-						}
-					}).collect_view()}
+					{events.into_iter().map(|event| calendarEvent_view(
+						event,date,None,drag,eventConfig.clone(),actions.clone(),id.clone(),dialog.clone(),
+					)).collect_view()}
 				</div>
 			</div>
 		}
 	}).collect_view();
+	let timelines = isTimeline.then(|| {
+		let timelines = period.days().map(|date| calendarTimeline_view(
+			date,runtime,drag,configSignal.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
+		)).collect_view();
+		view! {
+			<div class="module_calendar_hours" aria-hidden="true">
+				{(0..24).map(|hour| view! {<span>{format!("{hour:02}:00")}</span>}).collect_view()}
+			</div>
+			{timelines}
+		}
+	});
+	let viewClass = match config.viewMode
+	{
+		CalendarViewMode::Week => " week",
+		CalendarViewMode::Day => " day",
+		_ => " month",
+	};
 	return view! {
-		<div class={format!("module_calendar_grid{viewClass}")}>
-			{weekdays.into_iter().map(|key| view! {
-				<div class="module_calendar_weekday"><TranslateText key={key}/></div>
+		<div class={format!("module_calendar_grid{viewClass}")} on:dragend=move |_| drag.end()>
+			{isTimeline.then(|| view! {<div class="module_calendar_hours_corner"></div>})}
+			{period.days().take(7).map(|date| view! {
+				<div class="module_calendar_weekday"><TranslateText key={weekday_key(date.weekday())}/></div>
 			}).collect_view()}
+			{isTimeline.then(|| view! {<div class="module_calendar_hours_corner"></div>})}
 			{days}
+			{timelines}
 		</div>
 	}.into_any();
 }
 
+/// the events of the period grouped by date, days without event nor holiday are left out
+fn calendarAgenda_view(
+	period: CalendarPeriod,
+	runtime: &CalendarRuntime,
+	config: ArcRwSignal<CalendarConfig>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+) -> leptos::prelude::AnyView
+{
+	let days = period.days()
+		.filter_map(|date| {
+			let events = runtime.events.iter()
+				.filter(|event| event_overlapsLocalDate(event,date))
+				.cloned()
+				.collect::<Vec<_>>();
+			let holidayNames = runtime.holidays.get(&date).cloned().unwrap_or_default();
+			if (events.is_empty() && holidayNames.is_empty()) {return None;}
+			return Some((date,events,holidayNames));
+		})
+		.collect::<Vec<_>>();
+	if (days.is_empty())
+	{
+		return view! {
+			<p class="module_calendar_agenda_empty"><TranslateText key="MODULE_CALENDAR_AGENDA_EMPTY"/></p>
+		}.into_any();
+	}
+	let today = browser_today_get();
+	let days = days.into_iter().map(|(date,events,holidayNames)| {
+		let events = events.into_iter().map(|event| {
+			let time = eventTimeRange_get(&event,date);
+			let style = eventColor_style(&event);
+			let title = event.title.clone();
+			let location = event.location.clone();
+			let config = config.clone();
+			let actions = moduleActions.clone();
+			let id = moduleId.clone();
+			let dialog = dialogManager.clone();
+			view! {
+				<li>
+					<button
+						type="button"
+						class="module_calendar_agenda_event"
+						style={style}
+						on:click=move |_| calendarEventDetails_open(event.clone(),config.clone(),actions.clone(),id.clone(),dialog.clone())
+					>
+						<span class="module_calendar_event_time">{
+							if let Some(time) = time
+							{
+								view!{<span>{time}</span>}.into_any()
+							}
+							else
+							{
+								view!{<TranslateText key="MODULE_CALENDAR_ALL_DAY_SHORT"/>}.into_any()
+							}
+						}</span>
+						<span class="module_calendar_event_title">{if (title.is_empty())
+						{
+							view!{<TranslateText key="FRONTUI_NOTITLE"/>}.into_any()
+						}
+						else
+						{
+							view!{<span>{title}</span>}.into_any()
+						}}</span>
+						{(!location.is_empty()).then(|| view! {
+							<span class="module_calendar_agenda_location">{location}</span>
+						})}
+					</button>
+				</li>
+			}
+		}).collect_view();
+		view! {
+			<section class="module_calendar_agenda_day" class:today={date == today}>
+				<h3 class="module_calendar_agenda_date">
+					<TranslateText key={weekday_key(date.weekday())}/>{format!(" {} ",date.day())}
+					<TranslateText key={month_key(date.month())}/>
+					{(!holidayNames.is_empty()).then(|| view! {
+						<span class="module_calendar_holiday_name">{holidayNames.join(", ")}</span>
+					})}
+				</h3>
+				<ul class="module_calendar_agenda_events">{events}</ul>
+			</section>
+		}
+	}).collect_view();
+	return view! {<div class="module_calendar_agenda">{days}</div>}.into_any();
+}
+
+/// the hours of a day, timed events are placed at their time side by side when they overlap, and can be moved or resized
+fn calendarTimeline_view(
+	date: Date,
+	runtime: &CalendarRuntime,
+	drag: CalendarGridDrag,
+	config: ArcRwSignal<CalendarConfig>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+) -> leptos::prelude::AnyView
+{
+	let (events,spans): (Vec<_>,Vec<_>) = runtime.events.iter()
+		.filter(|event| event_isTimed(event) && event_overlapsLocalDate(event,date))
+		.filter_map(|event| Some((event.clone(),eventLocalMinutes_get(event,date)?)))
+		.unzip();
+	let slots = timelineSlots_get(&spans);
+	let dropConfig = config.clone();
+	let dropActions = moduleActions.clone();
+	let dropId = moduleId.clone();
+	let dropDialog = dialogManager.clone();
+	view! {
+		<div
+			class="module_calendar_timeline"
+			class:drop_target=move || drag.hoveredDate.get() == Some(date)
+			on:dragover=move |event| drag.hover(event,date)
+			on:drop=move |event| {
+				let Some(dragged) = drag.take(&event) else {return;};
+				let Some(minute) = timelineMinute_get(event.target(),event.client_y()) else {return;};
+				let midnight = PrimitiveDateTime::new(date,Time::MIDNIGHT);
+				let drop = match (dragged.kind,&dragged.event.start)
+				{
+					(CalendarDragKind::Move {..},CalendarMoment::AllDay(_)) => CalendarDrop::Days((date - dragged.sourceDate).whole_days()),
+					(CalendarDragKind::Move {grabMinutes},CalendarMoment::Timed(_)) => CalendarDrop::Start(midnight + Duration::minutes(minute - grabMinutes)),
+					(CalendarDragKind::Resize,_) => CalendarDrop::End(midnight + Duration::minutes(minute)),
+				};
+				calendarDrop_apply(drop,dragged.event,dropConfig.clone(),dropActions.clone(),dropId.clone(),dropDialog.clone());
+			}
+		>
+			{events.into_iter().zip(slots).map(|(event,slot)| calendarEvent_view(
+				event,date,Some(slot),drag,config.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
+			)).collect_view()}
+		</div>
+	}.into_any()
+}
+
+/// slot places a timed event on a timeline
+fn calendarEvent_view(
+	event: CalendarEvent,
+	date: Date,
+	slot: Option<CalendarTimelineSlot>,
+	drag: CalendarGridDrag,
+	config: ArcRwSignal<CalendarConfig>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+) -> leptos::prelude::AnyView
+{
+	let time = eventTime_get(&event,date);
+	let mut style = eventColor_style(&event);
+	if let Some(slot) = slot
+	{
+		style.push_str(&format!(
+			" top: {:.4}%; height: {:.4}%; left: calc({:.4}% + 1px); width: calc({:.4}% - 2px);",
+			slot.startMinute as f64 * 100.0 / CALENDAR_DAY_MINUTES as f64,
+			(slot.endMinute - slot.startMinute) as f64 * 100.0 / CALENDAR_DAY_MINUTES as f64,
+			slot.column as f64 * 100.0 / slot.columns as f64,
+			100.0 / slot.columns as f64,
+		));
+	}
+	// the end is resized from the last day of the event only
+	let isResizable = slot.is_some() && match event.end
+	{
+		CalendarMoment::Timed(end) => timestampLocalDate_get(end.saturating_sub(1)) == Some(date),
+		CalendarMoment::AllDay(_) => false,
+	};
+	let title = event.title.clone();
+	let eventView = event.clone();
+	let moveEvent = event.clone();
+	view! {
+		<button
+			type="button"
+			class="module_calendar_event"
+			class:timed=slot.is_some()
+			style={style}
+			draggable="true"
+			on:dragstart=move |dragEvent| {
+				let grabMinutes = match (slot,&moveEvent.start)
+				{
+					(Some(_),CalendarMoment::Timed(start)) => timelineMinute_get(dragEvent.target(),dragEvent.client_y())
+						.zip(timestampLocalDateTime_get(*start))
+						.map(|(minute,start)| (PrimitiveDateTime::new(date,Time::MIDNIGHT) - start).whole_minutes() + minute)
+						.unwrap_or(0),
+					_ => 0,
+				};
+				drag.start(dragEvent,CalendarDrag {event: moveEvent.clone(),sourceDate: date,kind: CalendarDragKind::Move {grabMinutes}});
+			}
+			on:click=move |_| calendarEventDetails_open(eventView.clone(),config.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone())
+		>
+			<span class="module_calendar_event_time">{
+				if let Some(time) = time
+				{
+					view!{<span>{time}</span>}.into_any()
+				}
+				else
+				{
+					view!{<TranslateText key="MODULE_CALENDAR_ALL_DAY_SHORT"/>}.into_any()
+				}
+			}</span>
+			<span class="module_calendar_event_title">{if (title.is_empty())
+			{
+				view!{<TranslateText key="FRONTUI_NOTITLE"/>}.into_any()
+			}
+			else
+			{
+				view!{<span>{title}</span>}.into_any()
+			}}</span>
+			{isResizable.then(|| view! {
+				<span
+					class="module_calendar_event_resize"
+					aria-hidden="true"
+					draggable="true"
+					on:dragstart=move |dragEvent| {
+						dragEvent.stop_propagation();
+						drag.start(dragEvent,CalendarDrag {event: event.clone(),sourceDate: date,kind: CalendarDragKind::Resize});
+					}
+				></span>
+			})}
+		</button>
+	}.into_any()
+}
+
+fn event_isTimed(event: &CalendarEvent) -> bool
+{
+	matches!(event.start,CalendarMoment::Timed(_))
+}
+
+/// the minutes of a day covered by a timed event, from 0 to a day long
+fn eventLocalMinutes_get(event: &CalendarEvent,date: Date) -> Option<(i64,i64)>
+{
+	let (CalendarMoment::Timed(start),CalendarMoment::Timed(end)) = (&event.start,&event.end) else {return None;};
+	let midnight = PrimitiveDateTime::new(date,Time::MIDNIGHT);
+	let start = (timestampLocalDateTime_get(*start)? - midnight).whole_minutes()
+		.clamp(0,CALENDAR_DAY_MINUTES - CALENDAR_DROP_STEP_MINUTES);
+	let end = (timestampLocalDateTime_get(*end)? - midnight).whole_minutes()
+		.clamp(start + CALENDAR_DROP_STEP_MINUTES,CALENDAR_DAY_MINUTES);
+	return Some((start,end));
+}
+
+/// a timed event on a timeline, overlapping events share the width in columns
+#[derive(Clone,Copy,Debug,PartialEq)]
+struct CalendarTimelineSlot
+{
+	startMinute: i64,
+	endMinute: i64,
+	column: usize,
+	columns: usize,
+}
+
+/// events overlapping each other, directly or through another one, get as many columns as they need at once
+fn timelineSlots_get(spans: &[(i64,i64)]) -> Vec<CalendarTimelineSlot>
+{
+	let mut slots = spans.iter()
+		.map(|(startMinute,endMinute)| CalendarTimelineSlot {startMinute: *startMinute,endMinute: *endMinute,column: 0,columns: 1})
+		.collect::<Vec<_>>();
+	let mut order = (0..spans.len()).collect::<Vec<_>>();
+	order.sort_by_key(|index| (spans[*index].0,std::cmp::Reverse(spans[*index].1)));
+	let mut group: Vec<usize> = Vec::new();
+	let mut columnEnds: Vec<i64> = Vec::new();
+	let mut groupEnd = i64::MIN;
+	for index in order
+	{
+		let (start,end) = spans[index];
+		if (start >= groupEnd)
+		{
+			for member in group.drain(..)
+			{
+				slots[member].columns = columnEnds.len();
+			}
+			columnEnds.clear();
+		}
+		let column = match columnEnds.iter().position(|columnEnd| *columnEnd <= start)
+		{
+			Some(column) =>
+			{
+				columnEnds[column] = end;
+				column
+			},
+			None =>
+			{
+				columnEnds.push(end);
+				columnEnds.len() - 1
+			},
+		};
+		slots[index].column = column;
+		group.push(index);
+		groupEnd = groupEnd.max(end);
+	}
+	for member in group
+	{
+		slots[member].columns = columnEnds.len();
+	}
+	return slots;
+}
+
+/// the minute of the day under the pointer on a timeline, rounded to the drop step
+#[cfg(feature = "hydrate")]
+fn timelineMinute_get(target: Option<web_sys::EventTarget>,clientY: i32) -> Option<i64>
+{
+	let element = target?.dyn_into::<web_sys::Element>().ok()?;
+	let timeline = element.closest(".module_calendar_timeline").ok().flatten()?;
+	let rect = timeline.get_bounding_client_rect();
+	if (rect.height() <= 0.0) {return None;}
+	let minute = ((clientY as f64 - rect.top()) / rect.height() * CALENDAR_DAY_MINUTES as f64).clamp(0.0,CALENDAR_DAY_MINUTES as f64);
+	return Some((minute / CALENDAR_DROP_STEP_MINUTES as f64).round() as i64 * CALENDAR_DROP_STEP_MINUTES);
+}
+
+#[cfg(not(feature = "hydrate"))]
+fn timelineMinute_get(_target: Option<web_sys::EventTarget>,_clientY: i32) -> Option<i64>
+{
+	None
+}
+
 fn calendarPeriod_shift(
 	direction: i8,
 	config: ArcRwSignal<CalendarConfig>,
@@ -657,12 +1086,18 @@ fn calendarPeriod_shift(
 	moduleId: ModuleID,
 )
 {
-	let viewMode = config.get_untracked().viewMode;
+	let (viewMode,agendaDays) = config.with_untracked(|config| (config.viewMode,config.agendaDays));
 	let anchor = runtime.get_untracked().anchor.unwrap_or_else(browser_today_get);
 	let anchor = match viewMode
 	{
+		CalendarViewMode::Day => anchor + Duration::days(direction as i64),
 		CalendarViewMode::Week => anchor + Duration::days(direction as i64 * 7),
 		CalendarViewMode::Month => month_shift(anchor,direction),
+		CalendarViewMode::Agenda =>
+		{
+			let period = CalendarPeriod::from_anchor(anchor,viewMode,agendaDays);
+			anchor + (period.endExclusive - period.start) * direction as i32
+		},
 	};
 	calendarPeriod_set(anchor,config,runtime,moduleActions,moduleId);
 }
@@ -675,8 +1110,8 @@ fn calendarPeriod_set(
 	moduleId: ModuleID,
 )
 {
-	let viewMode = config.get_untracked().viewMode;
-	runtime.update(|runtime| {runtime.period_set(anchor,viewMode);});
+	let (viewMode,agendaDays) = config.with_untracked(|config| (config.viewMode,config.agendaDays));
+	runtime.update(|runtime| {runtime.period_set(anchor,viewMode,agendaDays);});
 	(moduleActions.refreshFn)(moduleId);
 }
 
@@ -696,7 +1131,8 @@ fn calendarViewMode_set(
 	config.update(|config| config.viewMode = viewMode);
 	update.update(|cache| cache.update());
 	let anchor = runtime.get_untracked().anchor.unwrap_or_else(browser_today_get);
-	runtime.update(|runtime| {runtime.period_set(anchor,viewMode);});
+	let agendaDays = config.with_untracked(|config| config.agendaDays);
+	runtime.update(|runtime| {runtime.period_set(anchor,viewMode,agendaDays);});
 	(moduleActions.updateFn)(moduleId.clone());
 	(moduleActions.refreshFn)(moduleId);
 }
@@ -738,6 +1174,20 @@ fn month_key(month: Month) -> &'static str
 	};
 }
 
+fn weekday_key(weekday: Weekday) -> &'static str
+{
+	return match weekday
+	{
+		Weekday::Monday => "MODULE_CALENDAR_WEEKDAY_MON",
+		Weekday::Tuesday => "MODULE_CALENDAR_WEEKDAY_TUE",
+		Weekday::Wednesday => "MODULE_CALENDAR_WEEKDAY_WED",
+		Weekday::Thursday => "MODULE_CALENDAR_WEEKDAY_THU",
+		Weekday::Friday => "MODULE_CALENDAR_WEEKDAY_FRI",
+		Weekday::Saturday => "MODULE_CALENDAR_WEEKDAY_SAT",
+		Weekday::Sunday => "MODULE_CALENDAR_WEEKDAY_SUN",
+	};
+}
+
 fn collectionColor_style(collection: &CalendarCollection) -> String
 {
 	return collection.color_get()
@@ -748,11 +1198,11 @@ fn collectionColor_style(collection: &CalendarCollection) -> String
 fn eventColor_style(event: &CalendarEvent) -> String
 {
 	return CalendarCollection::color_normalize(event.collectionColor.clone())
-		.map(|color| format!("--calendar-event-color: {color}"))
+		.map(|color| format!("--calendar-event-color: {color};"))
 		.unwrap_or_default();
 }
 
-fn calDavError_key(error: CalDavError) -> &'static str
+pub(super) fn calDavError_key(error: CalDavError) -> &'static str
 {
 	return match error
 	{
@@ -771,6 +1221,8 @@ fn calDavError_key(error: CalDavError) -> &'static str
 		CalDavError::ResponseTooLarge | CalDavError::TooManyItems => "MODULE_CALENDAR_ERROR_LIMIT",
 		CalDavError::InvalidResponse | CalDavError::InvalidCalendar => "MODULE_CALENDAR_ERROR_RESPONSE",
 		CalDavError::MissingEtag => "MODULE_CALENDAR_ERROR_ETAG",
+		CalDavError::InvitationMismatch => "MODULE_CALENDAR_ERROR_INVITATION_MISMATCH",
+		CalDavError::SeriesMoveUnsupported => "MODULE_CALENDAR_ERROR_SERIES_MOVE",
 	};
 }
 
@@ -799,17 +1251,45 @@ fn calendarHolidayError_key(error: CalendarHolidayError) -> &'static str
 }
 
 #[cfg(feature = "hydrate")]
-fn timestampLocalDate_get(timestamp: i64) -> Option<Date>
+fn timestampLocalDateTime_get(timestamp: i64) -> Option<PrimitiveDateTime>
 {
 	let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp as f64 * 1_000.0));
 	let month = u8::try_from(date.get_month() + 1).ok()?.try_into().ok()?;
-	return Date::from_calendar_date(date.get_full_year() as i32,month,date.get_date() as u8).ok();
+	let day = Date::from_calendar_date(date.get_full_year() as i32,month,date.get_date() as u8).ok()?;
+	let time = Time::from_hms(date.get_hours() as u8,date.get_minutes() as u8,date.get_seconds() as u8).ok()?;
+	return Some(PrimitiveDateTime::new(day,time));
 }
 
 #[cfg(not(feature = "hydrate"))]
+fn timestampLocalDateTime_get(timestamp: i64) -> Option<PrimitiveDateTime>
+{
+	OffsetDateTime::from_unix_timestamp(timestamp).ok().map(|dateTime| PrimitiveDateTime::new(dateTime.date(),dateTime.time()))
+}
+
 fn timestampLocalDate_get(timestamp: i64) -> Option<Date>
 {
-	OffsetDateTime::from_unix_timestamp(timestamp).ok().map(|dateTime| dateTime.date())
+	timestampLocalDateTime_get(timestamp).map(|dateTime| dateTime.date())
+}
+
+/// value of a datetime-local input in the browser timezone
+fn timestampLocalInput_format(timestamp: i64) -> String
+{
+	return timestampLocalDateTime_get(timestamp)
+		.map(|dateTime| format!(
+			"{}T{:02}:{:02}",
+			dateInput_format(dateTime.date()),dateTime.hour(),dateTime.minute(),
+		))
+		.unwrap_or_default();
+}
+
+/// a displayed moment as the form and the drops write it, in the browser timezone
+fn eventMoment_local(moment: &CalendarMoment) -> Option<CalendarCreateMoment>
+{
+	return match moment
+	{
+		CalendarMoment::AllDay(date) => Some(CalendarCreateMoment::AllDay(*date)),
+		CalendarMoment::Timed(timestamp) => timestampLocalDateTime_get(*timestamp).map(CalendarCreateMoment::Local),
+	};
 }
 
 fn event_overlapsLocalDate(event: &CalendarEvent,date: Date) -> bool
@@ -828,25 +1308,31 @@ fn event_overlapsLocalDate(event: &CalendarEvent,date: Date) -> bool
 	};
 }
 
-#[cfg(feature = "hydrate")]
 fn eventTime_get(event: &CalendarEvent,date: Date) -> Option<String>
 {
 	let CalendarMoment::Timed(timestamp) = event.start else {return None;};
-	if (timestampLocalDate_get(timestamp) != Some(date))
-	{
-		return Some("↳".to_string());
-	}
-	let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp as f64 * 1_000.0));
-	return Some(format!("{:02}:{:02}",date.get_hours(),date.get_minutes()));
+	let dateTime = timestampLocalDateTime_get(timestamp)?;
+	if (dateTime.date() != date) {return Some("↳".to_string());}
+	return Some(format!("{:02}:{:02}",dateTime.hour(),dateTime.minute()));
 }
 
-#[cfg(not(feature = "hydrate"))]
-fn eventTime_get(event: &CalendarEvent,date: Date) -> Option<String>
+/// the hours of a timed event on a date, a side beyond the date is shown as an ellipsis
+fn eventTimeRange_get(event: &CalendarEvent,date: Date) -> Option<String>
 {
-	let CalendarMoment::Timed(timestamp) = event.start else {return None;};
-	let dateTime = OffsetDateTime::from_unix_timestamp(timestamp).ok()?;
-	if (dateTime.date() != date) {return Some("↳".to_string());}
-	return Some(format!("{:02}:{:02}",dateTime.hour(),dateTime.minute()));
+	let (CalendarMoment::Timed(start),CalendarMoment::Timed(end)) = (&event.start,&event.end) else {return None;};
+	let label = |timestamp: i64| timestampLocalDateTime_get(timestamp)
+		.filter(|dateTime| dateTime.date() == date)
+		.map(|dateTime| format!("{:02}:{:02}",dateTime.hour(),dateTime.minute()))
+		.unwrap_or_else(|| "…".to_string());
+	let endLabel = if (end > start && timestampLocalDate_get(end - 1) == Some(date) && timestampLocalDate_get(*end) != Some(date))
+	{
+		"24:00".to_string()
+	}
+	else
+	{
+		label(*end)
+	};
+	return Some(format!("{} – {}",label(*start),endLabel));
 }
 
 #[cfg(feature = "hydrate")]
@@ -945,22 +1431,8 @@ fn calendarCreate_open(
 		return;
 	}
 
-	let today = browser_today_get();
-	let title = RwSignal::new(String::new());
-	let description = RwSignal::new(String::new());
-	let location = RwSignal::new(String::new());
-	let allDay = RwSignal::new(false);
-	let allDayStart = RwSignal::new(dateInput_format(today));
-	let allDayEnd = RwSignal::new(dateInput_format(today));
-	let timedStart = RwSignal::new(format!("{}T09:00",dateInput_format(today)));
-	let timedEnd = RwSignal::new(format!("{}T10:00",dateInput_format(today)));
+	let form = CalendarEventForm::new(browser_today_get());
 	let collectionHref = RwSignal::new(configSnapshot.collections[0].href.clone());
-	let recurrenceEnabled = RwSignal::new(false);
-	let recurrenceFrequency = RwSignal::new("WEEKLY".to_string());
-	let recurrenceInterval = RwSignal::new("1".to_string());
-	let recurrenceEnd = RwSignal::new("NEVER".to_string());
-	let recurrenceUntil = RwSignal::new(dateInput_format(today));
-	let recurrenceCount = RwSignal::new("5".to_string());
 	let pending = RwSignal::new(false);
 	let collections = configSnapshot.collections.clone();
 
@@ -969,9 +1441,7 @@ fn calendarCreate_open(
 		.setTitle("MODULE_CALENDAR_CREATE")
 		.setIsLarger(true)
 		.setBody(move || calendarCreateForm_view(
-			title,description,location,allDay,allDayStart,allDayEnd,timedStart,timedEnd,
-			collectionHref,bodyCollections.clone(),recurrenceEnabled,recurrenceFrequency,
-			recurrenceInterval,recurrenceEnd,recurrenceUntil,recurrenceCount,pending,
+			form,collectionHref,bodyCollections.clone(),None,"MODULE_CALENDAR_SAVING",pending,
 		))
 		.setButtonValidateTitle(Some("MODULE_CALENDAR_CREATE_ACTION"))
 		.setOnValidate({
@@ -981,14 +1451,7 @@ fn calendarCreate_open(
 			let dialogManager = dialogManager.clone();
 			move |_| {
 				if (pending.get_untracked()) {return false;}
-				let Some(input) = calendarCreateInput_get(
-					title.get_untracked(),description.get_untracked(),location.get_untracked(),
-					allDay.get_untracked(),allDayStart.get_untracked(),allDayEnd.get_untracked(),
-					timedStart.get_untracked(),timedEnd.get_untracked(),
-					recurrenceEnabled.get_untracked(),recurrenceFrequency.get_untracked(),
-					recurrenceInterval.get_untracked(),recurrenceEnd.get_untracked(),
-					recurrenceUntil.get_untracked(),recurrenceCount.get_untracked(),
-				)
+				let Some(input) = form.input_get()
 				else
 				{
 					let toaster = expect_toaster();
@@ -1009,7 +1472,10 @@ fn calendarCreate_open(
 	dialogManager.open(dialog);
 }
 
-fn calendarCreateForm_view(
+/// fields of the create and edit dialogs, kept as the raw input values until validation
+#[derive(Clone,Copy)]
+struct CalendarEventForm
+{
 	title: RwSignal<String>,
 	description: RwSignal<String>,
 	location: RwSignal<String>,
@@ -1018,28 +1484,132 @@ fn calendarCreateForm_view(
 	allDayEnd: RwSignal<String>,
 	timedStart: RwSignal<String>,
 	timedEnd: RwSignal<String>,
-	collectionHref: RwSignal<String>,
-	collections: Vec<CalendarCollection>,
 	recurrenceEnabled: RwSignal<bool>,
 	recurrenceFrequency: RwSignal<String>,
 	recurrenceInterval: RwSignal<String>,
 	recurrenceEnd: RwSignal<String>,
 	recurrenceUntil: RwSignal<String>,
 	recurrenceCount: RwSignal<String>,
+}
+
+impl CalendarEventForm
+{
+	fn new(today: Date) -> Self
+	{
+		return Self {
+			title: RwSignal::new(String::new()),
+			description: RwSignal::new(String::new()),
+			location: RwSignal::new(String::new()),
+			allDay: RwSignal::new(false),
+			allDayStart: RwSignal::new(dateInput_format(today)),
+			allDayEnd: RwSignal::new(dateInput_format(today)),
+			timedStart: RwSignal::new(format!("{}T09:00",dateInput_format(today))),
+			timedEnd: RwSignal::new(format!("{}T10:00",dateInput_format(today))),
+			recurrenceEnabled: RwSignal::new(false),
+			recurrenceFrequency: RwSignal::new("WEEKLY".to_string()),
+			recurrenceInterval: RwSignal::new("1".to_string()),
+			recurrenceEnd: RwSignal::new("NEVER".to_string()),
+			recurrenceUntil: RwSignal::new(dateInput_format(today)),
+			recurrenceCount: RwSignal::new("5".to_string()),
+		};
+	}
+
+	fn editable_set(&self, editable: &CalendarEditableEvent)
+	{
+		self.title.set(editable.title.clone());
+		self.description.set(editable.description.clone());
+		self.location.set(editable.location.clone());
+		match (&editable.start,&editable.end)
+		{
+			(CalendarMoment::AllDay(start),CalendarMoment::AllDay(end)) =>
+			{
+				let inclusiveEnd = end.previous_day().filter(|inclusiveEnd| inclusiveEnd >= start).unwrap_or(*start);
+				self.allDay.set(true);
+				self.allDayStart.set(dateInput_format(*start));
+				self.allDayEnd.set(dateInput_format(inclusiveEnd));
+			},
+			(start,end) =>
+			{
+				self.allDay.set(false);
+				if let (CalendarMoment::Timed(start),CalendarMoment::Timed(end)) = (start,end)
+				{
+					self.timedStart.set(timestampLocalInput_format(*start));
+					self.timedEnd.set(timestampLocalInput_format(*end));
+				}
+			},
+		}
+		let Some(recurrence) = &editable.recurrence
+		else
+		{
+			self.recurrenceEnabled.set(false);
+			return;
+		};
+		self.recurrenceEnabled.set(true);
+		self.recurrenceFrequency.set(match recurrence.frequency
+		{
+			CalendarRecurrenceFrequency::Daily => "DAILY",
+			CalendarRecurrenceFrequency::Weekly => "WEEKLY",
+			CalendarRecurrenceFrequency::Monthly => "MONTHLY",
+			CalendarRecurrenceFrequency::Yearly => "YEARLY",
+		}.to_string());
+		self.recurrenceInterval.set(recurrence.interval.to_string());
+		match recurrence.end
+		{
+			CalendarRecurrenceEnd::Never => self.recurrenceEnd.set("NEVER".to_string()),
+			CalendarRecurrenceEnd::Until {date,..} =>
+			{
+				self.recurrenceEnd.set("UNTIL".to_string());
+				self.recurrenceUntil.set(dateInput_format(date));
+			},
+			CalendarRecurrenceEnd::Count(count) =>
+			{
+				self.recurrenceEnd.set("COUNT".to_string());
+				self.recurrenceCount.set(count.to_string());
+			},
+		}
+	}
+
+	fn input_get(&self) -> Option<CalendarCreateInput>
+	{
+		return calendarCreateInput_get(
+			self.title.get_untracked(),self.description.get_untracked(),self.location.get_untracked(),
+			self.allDay.get_untracked(),self.allDayStart.get_untracked(),self.allDayEnd.get_untracked(),
+			self.timedStart.get_untracked(),self.timedEnd.get_untracked(),
+			self.recurrenceEnabled.get_untracked(),self.recurrenceFrequency.get_untracked(),
+			self.recurrenceInterval.get_untracked(),self.recurrenceEnd.get_untracked(),
+			self.recurrenceUntil.get_untracked(),self.recurrenceCount.get_untracked(),
+		);
+	}
+}
+
+/// recurrenceNotice replaces the recurrence settings when they cannot be changed
+fn calendarCreateForm_view(
+	form: CalendarEventForm,
+	collectionHref: RwSignal<String>,
+	collections: Vec<CalendarCollection>,
+	recurrenceNotice: Option<&'static str>,
+	pendingKey: &'static str,
 	pending: RwSignal<bool>,
 ) -> leptos::prelude::AnyView
 {
+	let recurrenceView = match recurrenceNotice
+	{
+		Some(notice) => view! {
+			<p class="module_calendar_dialog_notice"><TranslateText key={notice}/></p>
+		}.into_any(),
+		None => calendarCreateRecurrence_view(
+			form.recurrenceEnabled,form.recurrenceFrequency,form.recurrenceInterval,form.recurrenceEnd,
+			form.recurrenceUntil,form.recurrenceCount,
+		),
+	};
 	view! {
 		<div class="module_calendar_create_form">
-			{calendarCreateIdentity_view(title,collectionHref,collections)}
-			{calendarCreatePeriod_view(allDay,allDayStart,allDayEnd,timedStart,timedEnd)}
-			{calendarCreateDetails_view(location,description)}
-			{calendarCreateRecurrence_view(
-				recurrenceEnabled,recurrenceFrequency,recurrenceInterval,recurrenceEnd,
-				recurrenceUntil,recurrenceCount,
-			)}
+			{calendarCreateIdentity_view(form.title,collectionHref,collections)}
+			{calendarCreatePeriod_view(form.allDay,form.allDayStart,form.allDayEnd,form.timedStart,form.timedEnd)}
+			{calendarCreateDetails_view(form.location,form.description)}
+			{recurrenceView}
 			{move || pending.get().then(|| view! {
-				<p class="module_calendar_dialog_notice" role="status"><TranslateText key="MODULE_CALENDAR_SAVING"/></p>
+				<p class="module_calendar_dialog_notice" role="status"><TranslateText key={pendingKey}/></p>
 			})}
 		</div>
 	}.into_any()
@@ -1332,40 +1902,158 @@ fn calendarEventActions_view(
 		CalendarEventRecurrenceState::Error(error) => view! {
 			<p class="module_calendar_dialog_notice module_calendar_status--error" role="alert"><TranslateText key={calDavError_key(error)}/></p>
 		}.into_any(),
-		CalendarEventRecurrenceState::Single => view! {
-			<button type="button" class="danger" on:click=move |_| calendarDeleteConfirmation_open(
-				CalendarDeleteScope::Event,event.clone(),config.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
-			)><TranslateText key="MODULE_CALENDAR_DELETE_EVENT"/></button>
-		}.into_any(),
+		CalendarEventRecurrenceState::Single =>
+		{
+			let editEvent = event.clone();
+			let editConfig = config.clone();
+			let editActions = moduleActions.clone();
+			let editId = moduleId.clone();
+			let editDialog = dialogManager.clone();
+			view! {
+				<button type="button" on:click=move |_| calendarEdit_open(
+					CalendarEventScope::Event,editEvent.clone(),editConfig.clone(),editActions.clone(),editId.clone(),editDialog.clone(),
+				)><TranslateText key="MODULE_CALENDAR_EDIT_EVENT"/></button>
+				<button type="button" class="danger" on:click=move |_| calendarDeleteConfirmation_open(
+					CalendarEventScope::Event,event.clone(),config.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
+				)><TranslateText key="MODULE_CALENDAR_DELETE_EVENT"/></button>
+			}.into_any()
+		},
 		CalendarEventRecurrenceState::Series =>
 		{
+			let editOccurrenceEvent = event.clone();
+			let editOccurrenceConfig = config.clone();
+			let editOccurrenceActions = moduleActions.clone();
+			let editOccurrenceId = moduleId.clone();
+			let editOccurrenceDialog = dialogManager.clone();
+			let editSeriesEvent = event.clone();
+			let editSeriesConfig = config.clone();
+			let editSeriesActions = moduleActions.clone();
+			let editSeriesId = moduleId.clone();
+			let editSeriesDialog = dialogManager.clone();
 			let occurrenceEvent = event.clone();
 			let occurrenceConfig = config.clone();
 			let occurrenceActions = moduleActions.clone();
 			let occurrenceId = moduleId.clone();
 			let occurrenceDialog = dialogManager.clone();
 			view! {
+				<button type="button" on:click=move |_| calendarEdit_open(
+					CalendarEventScope::Occurrence,editOccurrenceEvent.clone(),editOccurrenceConfig.clone(),editOccurrenceActions.clone(),editOccurrenceId.clone(),editOccurrenceDialog.clone(),
+				)><TranslateText key="MODULE_CALENDAR_EDIT_OCCURRENCE"/></button>
+				<button type="button" on:click=move |_| calendarEdit_open(
+					CalendarEventScope::Series,editSeriesEvent.clone(),editSeriesConfig.clone(),editSeriesActions.clone(),editSeriesId.clone(),editSeriesDialog.clone(),
+				)><TranslateText key="MODULE_CALENDAR_EDIT_SERIES"/></button>
 				<button type="button" class="danger" on:click=move |_| calendarDeleteConfirmation_open(
-					CalendarDeleteScope::Occurrence,occurrenceEvent.clone(),occurrenceConfig.clone(),occurrenceActions.clone(),occurrenceId.clone(),occurrenceDialog.clone(),
+					CalendarEventScope::Occurrence,occurrenceEvent.clone(),occurrenceConfig.clone(),occurrenceActions.clone(),occurrenceId.clone(),occurrenceDialog.clone(),
 				)><TranslateText key="MODULE_CALENDAR_DELETE_OCCURRENCE"/></button>
 				<button type="button" class="danger" on:click=move |_| calendarDeleteConfirmation_open(
-					CalendarDeleteScope::Series,event.clone(),config.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
+					CalendarEventScope::Series,event.clone(),config.clone(),moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
 				)><TranslateText key="MODULE_CALENDAR_DELETE_SERIES"/></button>
 			}.into_any()
 		},
 	};
 }
 
+/// what an edit or a deletion applies to: a single event, one occurrence of a series or the whole series
 #[derive(Clone,Copy)]
-enum CalendarDeleteScope
+enum CalendarEventScope
 {
 	Event,
 	Occurrence,
 	Series,
 }
 
+#[derive(Clone,Copy,PartialEq)]
+#[cfg_attr(not(feature = "hydrate"),allow(dead_code))]
+enum CalendarEditState
+{
+	Loading,
+	Ready,
+	Error(CalDavError),
+}
+
+/// the stored event is read first, its ETag then guards the update against a change made meanwhile
+fn calendarEdit_open(
+	scope: CalendarEventScope,
+	event: CalendarEvent,
+	config: ArcRwSignal<CalendarConfig>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+)
+{
+	let form = CalendarEventForm::new(browser_today_get());
+	let state = RwSignal::new(CalendarEditState::Loading);
+	let stored = RwSignal::new(None::<CalendarStoredEvent>);
+	let isRecurrenceCustom = RwSignal::new(false);
+	let pending = RwSignal::new(false);
+	let collectionHref = RwSignal::new(event.identity.collectionHref.clone());
+	let collections = vec![CalendarCollection {
+		href: event.identity.collectionHref.clone(),
+		name: event.collectionName.clone(),
+		color: event.collectionColor.clone(),
+	}];
+	let titleKey = match scope
+	{
+		CalendarEventScope::Event => "MODULE_CALENDAR_EDIT_EVENT",
+		CalendarEventScope::Occurrence => "MODULE_CALENDAR_EDIT_OCCURRENCE",
+		CalendarEventScope::Series => "MODULE_CALENDAR_EDIT_SERIES",
+	};
+	let validateEvent = event.clone();
+	let loadEvent = event.clone();
+	let loadConfig = config.clone();
+	let loadActions = moduleActions.clone();
+	let dialog = DialogData::new()
+		.setTitle(titleKey)
+		.setIsLarger(true)
+		.setBody(move || match state.get()
+		{
+			CalendarEditState::Loading => view! {
+				<p class="module_calendar_dialog_notice" role="status"><TranslateText key="MODULE_CALENDAR_EDIT_LOADING"/></p>
+			}.into_any(),
+			CalendarEditState::Error(error) => view! {
+				<p class="module_calendar_dialog_notice module_calendar_status--error" role="alert"><TranslateText key={calDavError_key(error)}/></p>
+			}.into_any(),
+			CalendarEditState::Ready =>
+			{
+				let recurrenceNotice = match scope
+				{
+					CalendarEventScope::Occurrence => Some("MODULE_CALENDAR_EDIT_OCCURRENCE_NOTICE"),
+					_ if isRecurrenceCustom.get_untracked() => Some("MODULE_CALENDAR_RECURRENCE_CUSTOM"),
+					_ => None,
+				};
+				calendarCreateForm_view(form,collectionHref,collections.clone(),recurrenceNotice,"MODULE_CALENDAR_UPDATING",pending)
+			},
+		})
+		.setButtonValidateTitle(Some("MODULE_CALENDAR_EDIT_ACTION"))
+		.setOnValidate({
+			let dialogManager = dialogManager.clone();
+			move |_| {
+				if (pending.get_untracked() || state.get_untracked() != CalendarEditState::Ready) {return false;}
+				let Some(resource) = stored.get_untracked() else {return false;};
+				let Some(input) = form.input_get()
+				else
+				{
+					let toaster = expect_toaster();
+					moduleActions.task_spawn(async move {
+						toastingErr(&toaster,"MODULE_CALENDAR_CREATE_INVALID").await;
+					});
+					return false;
+				};
+				pending.set(true);
+				calendarEvent_update(
+					scope,validateEvent.clone(),resource,input,isRecurrenceCustom.get_untracked(),config.clone(),pending,
+					moduleActions.clone(),moduleId.clone(),dialogManager.clone(),
+				);
+				return false;
+			}
+		})
+		.setCanClose(move || !pending.get());
+	dialogManager.open(dialog);
+	calendarEventEdit_load(scope,loadEvent,loadConfig,form,state,stored,isRecurrenceCustom,loadActions);
+}
+
 fn calendarDeleteConfirmation_open(
-	scope: CalendarDeleteScope,
+	scope: CalendarEventScope,
 	event: CalendarEvent,
 	config: ArcRwSignal<CalendarConfig>,
 	moduleActions: ModuleActionFn,
@@ -1376,9 +2064,9 @@ fn calendarDeleteConfirmation_open(
 	let pending = ArcRwSignal::new(false);
 	let messageKey = match scope
 	{
-		CalendarDeleteScope::Event => "MODULE_CALENDAR_DELETE_EVENT_CONFIRM",
-		CalendarDeleteScope::Occurrence => "MODULE_CALENDAR_DELETE_OCCURRENCE_CONFIRM",
-		CalendarDeleteScope::Series => "MODULE_CALENDAR_DELETE_SERIES_CONFIRM",
+		CalendarEventScope::Event => "MODULE_CALENDAR_DELETE_EVENT_CONFIRM",
+		CalendarEventScope::Occurrence => "MODULE_CALENDAR_DELETE_OCCURRENCE_CONFIRM",
+		CalendarEventScope::Series => "MODULE_CALENDAR_DELETE_SERIES_CONFIRM",
 	};
 	let validateDialog = dialogManager.clone();
 	let validateActions = moduleActions.clone();
@@ -1413,6 +2101,62 @@ fn calendarDeleteConfirmation_open(
 	dialogManager.open(dialog);
 }
 
+/// a drop changes a single event at once, a recurring one asks first whether the series follows
+fn calendarDrop_apply(
+	drop: CalendarDrop,
+	event: CalendarEvent,
+	config: ArcRwSignal<CalendarConfig>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+)
+{
+	let (Some(start),Some(end)) = (eventMoment_local(&event.start),eventMoment_local(&event.end)) else {return;};
+	let Some(target) = drop.period_apply(&start,&end) else {return;};
+	if (!event.recurrent)
+	{
+		calendarEvent_reschedule(
+			CalendarEventScope::Event,event,start,target,config,None,moduleActions,moduleId,dialogManager,
+		);
+		return;
+	}
+
+	let isSeries = RwSignal::new(false);
+	let pending = RwSignal::new(false);
+	let validateDialog = dialogManager.clone();
+	let dialog = DialogData::new()
+		.setTitle("MODULE_CALENDAR_RESCHEDULE_TITLE")
+		.setBody(move || view! {
+			<fieldset class="module_calendar_recurrence">
+				<legend><TranslateText key="MODULE_CALENDAR_RESCHEDULE_SCOPE"/></legend>
+				<label class="module_calendar_checkbox">
+					<input type="radio" name="calendar-reschedule-scope" prop:checked=move || !isSeries.get() on:change=move |_| isSeries.set(false)/>
+					<span><TranslateText key="MODULE_CALENDAR_RESCHEDULE_OCCURRENCE"/></span>
+				</label>
+				<label class="module_calendar_checkbox">
+					<input type="radio" name="calendar-reschedule-scope" prop:checked=move || isSeries.get() on:change=move |_| isSeries.set(true)/>
+					<span><TranslateText key="MODULE_CALENDAR_RESCHEDULE_SERIES"/></span>
+				</label>
+			</fieldset>
+			{move || pending.get().then(|| view! {
+				<p class="module_calendar_dialog_notice" role="status"><TranslateText key="MODULE_CALENDAR_UPDATING"/></p>
+			})}
+		}.into_any())
+		.setButtonValidateTitle(Some("MODULE_CALENDAR_RESCHEDULE_ACTION"))
+		.setOnValidate(move |_| {
+			if (pending.get_untracked()) {return false;}
+			pending.set(true);
+			let scope = if (isSeries.get_untracked()) {CalendarEventScope::Series} else {CalendarEventScope::Occurrence};
+			calendarEvent_reschedule(
+				scope,event.clone(),start.clone(),target.clone(),config.clone(),Some(pending),
+				moduleActions.clone(),moduleId.clone(),validateDialog.clone(),
+			);
+			return false;
+		})
+		.setCanClose(move || !pending.get());
+	dialogManager.open(dialog);
+}
+
 fn calendarCreateInput_get(
 	title: String,
 	description: String,
@@ -1636,7 +2380,7 @@ fn calendarEvent_create(
 
 #[cfg(feature = "hydrate")]
 fn calendarEvent_delete(
-	scope: CalendarDeleteScope,
+	scope: CalendarEventScope,
 	event: CalendarEvent,
 	config: ArcRwSignal<CalendarConfig>,
 	pending: ArcRwSignal<bool>,
@@ -1653,8 +2397,8 @@ fn calendarEvent_delete(
 		{
 			Ok(client) => match scope
 			{
-				CalendarDeleteScope::Occurrence => client.event_delete_occurrence(&event).await,
-				CalendarDeleteScope::Event | CalendarDeleteScope::Series => client.event_delete_series(&event).await,
+				CalendarEventScope::Occurrence => client.event_delete_occurrence(&event).await,
+				CalendarEventScope::Event | CalendarEventScope::Series => client.event_delete_series(&event).await,
 			},
 			Err(error) => Err(error),
 		};
@@ -1675,7 +2419,7 @@ fn calendarEvent_delete(
 
 #[cfg(not(feature = "hydrate"))]
 fn calendarEvent_delete(
-	_scope: CalendarDeleteScope,
+	_scope: CalendarEventScope,
 	_event: CalendarEvent,
 	_config: ArcRwSignal<CalendarConfig>,
 	pending: ArcRwSignal<bool>,
@@ -1687,8 +2431,239 @@ fn calendarEvent_delete(
 	pending.set(false);
 }
 
+/// occurrenceStart is the displayed start of the dragged event, target its period after the drop
+#[cfg(feature = "hydrate")]
+fn calendarEvent_reschedule(
+	scope: CalendarEventScope,
+	event: CalendarEvent,
+	occurrenceStart: CalendarCreateMoment,
+	target: (CalendarCreateMoment,CalendarCreateMoment),
+	config: ArcRwSignal<CalendarConfig>,
+	pending: Option<RwSignal<bool>>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+)
+{
+	let config = config.get_untracked();
+	let toaster = expect_toaster();
+	let taskActions = moduleActions.clone();
+	moduleActions.task_spawn(async move {
+		let result = match CalDavClient::new(&config)
+		{
+			Ok(client) => calendarEvent_rescheduleWrite(&client,scope,&event,&occurrenceStart,target).await,
+			Err(error) => Err(error),
+		};
+		if (!taskActions.lifecycle_isActive()) {return;}
+		if let Some(pending) = pending
+		{
+			pending.set(false);
+		}
+		match result
+		{
+			Ok(()) =>
+			{
+				if (pending.is_some())
+				{
+					dialogManager.clear();
+				}
+				(taskActions.refreshFn)(moduleId);
+				toastingSuccess(&toaster,"MODULE_CALENDAR_RESCHEDULE_SUCCESS").await;
+			},
+			Err(error) => toastingErr(&toaster,calDavError_key(error)).await,
+		}
+	});
+}
+
+#[cfg(feature = "hydrate")]
+async fn calendarEvent_rescheduleWrite(
+	client: &CalDavClient,
+	scope: CalendarEventScope,
+	event: &CalendarEvent,
+	occurrenceStart: &CalendarCreateMoment,
+	target: (CalendarCreateMoment,CalendarCreateMoment),
+) -> Result<(),CalDavError>
+{
+	let stored = client.event_load(event).await?;
+	// the event was dropped as the grid showed it, a newer version on the server is not overwritten
+	if (event.etag.as_ref().is_some_and(|etag| *etag != stored.etag))
+	{
+		return Err(CalDavError::Conflict);
+	}
+	let timezone = browserTimezone_get();
+	let (editable,(start,end)) = match scope
+	{
+		CalendarEventScope::Occurrence => (CalendarEditableEvent::from_occurrence(event),target),
+		CalendarEventScope::Event | CalendarEventScope::Series =>
+		{
+			let editable = editable_event_get(&stored.content,&event.identity.uid,&timezone)?;
+			let (Some(masterStart),Some(masterEnd)) = (eventMoment_local(&editable.start),eventMoment_local(&editable.end))
+			else
+			{
+				return Err(CalDavError::InvalidCalendar);
+			};
+			let period = master_period_reschedule((&masterStart,&masterEnd),occurrenceStart,(&target.0,&target.1))
+				.ok_or(CalDavError::InvalidCalendar)?;
+			(editable,period)
+		},
+	};
+	let input = CalendarCreateInput {
+		title: editable.title,
+		description: editable.description,
+		location: editable.location,
+		start,
+		end,
+		timezone: timezone.clone(),
+		recurrence: None,
+	};
+	return match scope
+	{
+		CalendarEventScope::Occurrence => client.event_update_occurrence(event,&stored,&input,&timezone).await,
+		CalendarEventScope::Event | CalendarEventScope::Series => client.event_update_series(event,&stored,&input,true).await,
+	};
+}
+
+#[cfg(not(feature = "hydrate"))]
+fn calendarEvent_reschedule(
+	_scope: CalendarEventScope,
+	_event: CalendarEvent,
+	_occurrenceStart: CalendarCreateMoment,
+	_target: (CalendarCreateMoment,CalendarCreateMoment),
+	_config: ArcRwSignal<CalendarConfig>,
+	pending: Option<RwSignal<bool>>,
+	_moduleActions: ModuleActionFn,
+	_moduleId: ModuleID,
+	_dialogManager: DialogManager,
+)
+{
+	if let Some(pending) = pending
+	{
+		pending.set(false);
+	}
+}
+
+#[cfg(feature = "hydrate")]
+fn calendarEventEdit_load(
+	scope: CalendarEventScope,
+	event: CalendarEvent,
+	config: ArcRwSignal<CalendarConfig>,
+	form: CalendarEventForm,
+	state: RwSignal<CalendarEditState>,
+	stored: RwSignal<Option<CalendarStoredEvent>>,
+	isRecurrenceCustom: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+)
+{
+	let config = config.get_untracked();
+	let taskActions = moduleActions.clone();
+	moduleActions.task_spawn(async move {
+		let result = match CalDavClient::new(&config)
+		{
+			Ok(client) => client.event_load(&event).await,
+			Err(error) => Err(error),
+		};
+		let editable = result.and_then(|resource| {
+			let editable = match scope
+			{
+				CalendarEventScope::Occurrence => CalendarEditableEvent::from_occurrence(&event),
+				CalendarEventScope::Event | CalendarEventScope::Series =>
+					editable_event_get(&resource.content,&event.identity.uid,&browserTimezone_get())?,
+			};
+			return Ok((resource,editable));
+		});
+		if (!taskActions.lifecycle_isActive()) {return;}
+		match editable
+		{
+			Ok((resource,editable)) =>
+			{
+				form.editable_set(&editable);
+				isRecurrenceCustom.set(editable.isRecurrenceCustom);
+				stored.set(Some(resource));
+				state.set(CalendarEditState::Ready);
+			},
+			Err(error) => state.set(CalendarEditState::Error(error)),
+		}
+	});
+}
+
+#[cfg(not(feature = "hydrate"))]
+fn calendarEventEdit_load(
+	_scope: CalendarEventScope,
+	event: CalendarEvent,
+	_config: ArcRwSignal<CalendarConfig>,
+	form: CalendarEventForm,
+	state: RwSignal<CalendarEditState>,
+	_stored: RwSignal<Option<CalendarStoredEvent>>,
+	_isRecurrenceCustom: RwSignal<bool>,
+	_moduleActions: ModuleActionFn,
+)
+{
+	form.editable_set(&CalendarEditableEvent::from_occurrence(&event));
+	state.set(CalendarEditState::Ready);
+}
+
+#[cfg(feature = "hydrate")]
+fn calendarEvent_update(
+	scope: CalendarEventScope,
+	event: CalendarEvent,
+	stored: CalendarStoredEvent,
+	input: CalendarCreateInput,
+	isRecurrenceKept: bool,
+	config: ArcRwSignal<CalendarConfig>,
+	pending: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+	dialogManager: DialogManager,
+)
+{
+	let config = config.get_untracked();
+	let toaster = expect_toaster();
+	let taskActions = moduleActions.clone();
+	moduleActions.task_spawn(async move {
+		let result = match CalDavClient::new(&config)
+		{
+			Ok(client) => match scope
+			{
+				CalendarEventScope::Occurrence => client.event_update_occurrence(&event,&stored,&input,&browserTimezone_get()).await,
+				CalendarEventScope::Event | CalendarEventScope::Series =>
+					client.event_update_series(&event,&stored,&input,isRecurrenceKept).await,
+			},
+			Err(error) => Err(error),
+		};
+		if (!taskActions.lifecycle_isActive()) {return;}
+		pending.set(false);
+		match result
+		{
+			Ok(()) =>
+			{
+				dialogManager.clear();
+				(taskActions.refreshFn)(moduleId);
+				toastingSuccess(&toaster,"MODULE_CALENDAR_EDIT_SUCCESS").await;
+			},
+			Err(error) => toastingErr(&toaster,calDavError_key(error)).await,
+		}
+	});
+}
+
+#[cfg(not(feature = "hydrate"))]
+fn calendarEvent_update(
+	_scope: CalendarEventScope,
+	_event: CalendarEvent,
+	_stored: CalendarStoredEvent,
+	_input: CalendarCreateInput,
+	_isRecurrenceKept: bool,
+	_config: ArcRwSignal<CalendarConfig>,
+	pending: RwSignal<bool>,
+	_moduleActions: ModuleActionFn,
+	_moduleId: ModuleID,
+	_dialogManager: DialogManager,
+)
+{
+	pending.set(false);
+}
+
 #[cfg(feature = "hydrate")]
-fn eventMoment_label(moment: &CalendarMoment) -> String
+pub(super) fn eventMoment_label(moment: &CalendarMoment) -> String
 {
 	return match moment
 	{
@@ -1704,7 +2679,7 @@ fn eventMoment_label(moment: &CalendarMoment) -> String
 #[cfg(test)]
 mod tests
 {
-	use super::event_overlapsLocalDate;
+	use super::{CalendarTimelineSlot,event_overlapsLocalDate,eventTimeRange_get,timelineSlots_get};
 	use crate::front::modules::calendar::domain::{
 		CalendarEvent,CalendarEventIdentity,CalendarMoment,
 	};
@@ -1736,6 +2711,20 @@ mod tests
 		};
 
 		assert!(event_overlapsLocalDate(&event,date));
+		assert_eq!(eventTimeRange_get(&event,date).as_deref(),Some("08:00 – 09:00"));
+		let late = CalendarEvent {start: CalendarMoment::Timed(timestamp(22)),end: CalendarMoment::Timed(timestamp(22) + 7_200),..event.clone()};
+		assert_eq!(eventTimeRange_get(&late,date).as_deref(),Some("22:00 – 24:00"));
+	}
+
+	#[test]
+	fn timelineSlots_shareColumnsOnlyBetweenOverlappingEvents()
+	{
+		let slot = |startMinute,endMinute,column,columns| CalendarTimelineSlot {startMinute,endMinute,column,columns};
+
+		let slots = timelineSlots_get(&[(540,600),(570,660),(600,630),(720,780)]);
+
+		assert_eq!(slots,vec![slot(540,600,0,2),slot(570,660,1,2),slot(600,630,0,2),slot(720,780,0,1)]);
+		assert!(timelineSlots_get(&[]).is_empty());
 	}
 }
 
@@ -1752,7 +2741,7 @@ fn eventEnd_label(event: &CalendarEvent) -> String
 }
 
 #[cfg(not(feature = "hydrate"))]
-fn eventMoment_label(moment: &CalendarMoment) -> String
+pub(super) fn eventMoment_label(moment: &CalendarMoment) -> String
 {
 	return match moment
 	{
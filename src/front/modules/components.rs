@@ -84,7 +84,15 @@ pub trait Cacheable
 }
 
 
-pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + 'static>>;
+/// result of a module refresh, a failure delays the next automatic refresh (see RefreshScheduler)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RefreshOutcome
+{
+	SUCCESS,
+	FAILED,
+}
+
+pub type RefreshFuture = Pin<Box<dyn Future<Output = RefreshOutcome> + 'static>>;
 
 pub trait ModuleName
 {
@@ -98,7 +106,7 @@ pub trait Backable
 	fn draw(&self, editMode: RwSignal<bool>,moduleActions: ModuleActionFn, moduleId: ModuleID) -> ViewFn;
 
 	fn refresh_time(&self) -> RefreshTime;
-	fn refresh(&self, moduleActions: ModuleActionFn, moduleId:ModuleID, toaster: ToasterContext) -> Option<BoxFuture>;
+	fn refresh(&self, moduleActions: ModuleActionFn, moduleId:ModuleID, toaster: ToasterContext) -> Option<RefreshFuture>;
 
 	fn export(&self) -> ModuleContent;
 	fn import(&mut self, import: ModuleContent);
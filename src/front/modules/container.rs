@@ -0,0 +1,590 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use leptoaster::ToasterContext;
+use leptos::prelude::{
+	AnyView, ArcRwSignal, AriaAttributes, ClassAttribute, CollectView, ElementChild, Get, GetUntracked, GlobalAttributes,
+	IntoAny, OnAttribute, OnTargetAttribute, PropAttribute, RwSignal, Set, StyleAttribute, Update, ViewFn, With,
+	WithUntracked,
+};
+use leptos::{component, view, IntoView};
+use serde::{Deserialize, Serialize};
+use crate::api::modules::components::{ModuleContent, ModuleID};
+use crate::front::modules::components::{
+	moduleContent, Backable, RefreshFuture, Cache, Cacheable, FieldHelper, ModuleName, ModuleSizeContrainte, RefreshTime,
+};
+use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::modules::module_holder::ModuleHolder;
+use crate::front::modules::module_positions::ModulePositions;
+use crate::front::modules::module_type::{ModuleType, ModuleTypeDiscriminants};
+use crate::front::utils::translate::{Translate, TranslateText};
+
+/// offset applied to a module moved out of a container, so it does not hide the container header
+const CONTAINER_DETACH_OFFSET: i32 = 24;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum ContainerLayout
+{
+	#[default]
+	TABS,
+	ACCORDION,
+}
+
+impl ContainerLayout
+{
+	fn translateKey_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::TABS => "MODULE_CONTAINER_LAYOUT_TABS",
+			Self::ACCORDION => "MODULE_CONTAINER_LAYOUT_ACCORDION",
+		};
+	}
+
+	fn fromValue(value: &str) -> Self
+	{
+		return match value
+		{
+			"ACCORDION" => Self::ACCORDION,
+			_ => Self::TABS,
+		};
+	}
+
+	fn value_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::TABS => "TABS",
+			Self::ACCORDION => "ACCORDION",
+		};
+	}
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct ContainerConfig
+{
+	#[serde(default)]
+	title: String,
+	#[serde(default)]
+	layout: ContainerLayout,
+	/// tab shown in the TABS layout, the first child is used when absent
+	#[serde(default)]
+	activeTab: Option<ModuleID>,
+	/// sections opened in the ACCORDION layout, every other child is collapsed
+	#[serde(default)]
+	expanded: BTreeSet<ModuleID>,
+}
+
+impl ContainerConfig
+{
+	fn activeTab_get(&self, children: &[ModuleID]) -> Option<ModuleID>
+	{
+		if let Some(activeTab) = &self.activeTab && children.contains(activeTab)
+		{
+			return Some(activeTab.clone());
+		}
+		return children.first().cloned();
+	}
+
+	/// return true if the active tab changed
+	fn activeTab_set(&mut self, child: ModuleID) -> bool
+	{
+		if (self.activeTab.as_ref() == Some(&child))
+		{
+			return false;
+		}
+		self.activeTab = Some(child);
+		return true;
+	}
+
+	fn expanded_toggle(&mut self, child: ModuleID)
+	{
+		if (!self.expanded.remove(&child))
+		{
+			self.expanded.insert(child);
+		}
+	}
+
+	/// a child is visible when it is the current tab, or when its accordion section is expanded
+	fn child_isVisible(&self, child: &ModuleID, children: &[ModuleID]) -> bool
+	{
+		return match self.layout
+		{
+			ContainerLayout::TABS => self.activeTab_get(children).as_ref() == Some(child),
+			ContainerLayout::ACCORDION => self.expanded.contains(child),
+		};
+	}
+}
+
+/// module that holds other modules as tabs or as an accordion.
+/// Children keep their own ModuleContent, only their `parent` points to this container.
+pub struct Container
+{
+	config: ArcRwSignal<ContainerConfig>,
+	_update: ArcRwSignal<Cache>,
+	_sended: ArcRwSignal<Cache>,
+}
+
+impl fmt::Debug for Container
+{
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		formatter.debug_struct("Container")
+			.field("config", &self.config.get_untracked())
+			.finish_non_exhaustive()
+	}
+}
+
+impl Default for Container
+{
+	fn default() -> Self
+	{
+		Self {
+			config: ArcRwSignal::new(ContainerConfig::default()),
+			_update: ArcRwSignal::new(Cache::default()),
+			_sended: ArcRwSignal::new(Cache::default()),
+		}
+	}
+}
+
+impl Container
+{
+	pub(super) fn child_isVisible(&self, child: &ModuleID, children: &[ModuleID]) -> bool
+	{
+		return self.config.with_untracked(|config| config.child_isVisible(child, children));
+	}
+
+	fn childLabel_get(module: &ArcRwSignal<ModulePositions<ModuleType>>, index: usize) -> AnyView
+	{
+		let key = module.with(|module| ModuleTypeDiscriminants::from(module.inner()).translateKey_get());
+		return view!{<TranslateText key={key}/>" "{index + 1}}.into_any();
+	}
+
+	fn child_draw(module: &ArcRwSignal<ModulePositions<ModuleType>>, editMode: RwSignal<bool>, moduleActions: ModuleActionFn, childId: ModuleID) -> AnyView
+	{
+		let module = module.clone();
+		return view!{
+			{move || module.with(|module| module.draw(editMode, moduleActions.clone(), childId.clone()))}
+		}.into_any();
+	}
+
+	/// persist a container state change made outside edit mode, and resume the children that became visible
+	fn state_commit(update: &ArcRwSignal<Cache>, editMode: RwSignal<bool>, moduleActions: &ModuleActionFn, moduleId: &ModuleID)
+	{
+		update.update(|cache| cache.update());
+		moduleActions.visibility_apply();
+		if (!editMode.get_untracked())
+		{
+			(moduleActions.saveFn)(moduleId.clone());
+		}
+	}
+}
+
+impl ModuleName for Container
+{
+	const MODULE_NAME: &'static str = "CONTAINER";
+}
+
+impl Backable for Container
+{
+	fn module_name(&self) -> String
+	{
+		Self::MODULE_NAME.to_string()
+	}
+
+	fn draw(&self, editMode: RwSignal<bool>, moduleActions: ModuleActionFn, moduleId: ModuleID) -> ViewFn
+	{
+		let config = self.config.clone();
+		let update = self._update.clone();
+		ViewFn::from(move || view! {
+			<ContainerDraw
+				config=config.clone()
+				update=update.clone()
+				editMode=editMode
+				moduleActions=moduleActions.clone()
+				moduleId=moduleId.clone()
+			/>
+		}.into_any())
+	}
+
+	fn refresh_time(&self) -> RefreshTime
+	{
+		RefreshTime::NONE
+	}
+
+	fn refresh(&self, _moduleActions: ModuleActionFn, _moduleId: ModuleID, _toaster: ToasterContext) -> Option<RefreshFuture>
+	{
+		None
+	}
+
+	fn export(&self) -> ModuleContent
+	{
+		ModuleContent {
+			id: ModuleID::new(),
+			typeModule: self.module_name(),
+			timestamp: self._update.get_untracked().get(),
+			content: serde_json::to_string(&self.config.get_untracked()).unwrap_or_default(),
+			..Default::default()
+		}
+	}
+
+	fn import(&mut self, import: ModuleContent)
+	{
+		let Ok(config): Result<ContainerConfig,_> = serde_json::from_str(&import.content)
+		else
+		{
+			return;
+		};
+		self.config.update(|current| *current = config);
+		self._update.update(|cache| cache.update_from(import.timestamp));
+		self._sended.update(|cache| cache.update_from(import.timestamp));
+	}
+
+	fn isOlderThan(&self, other: &ModuleContent) -> bool
+	{
+		other.timestamp > self._update.get_untracked().get()
+	}
+
+	fn newFromModuleContent(from: &ModuleContent) -> Option<Self>
+	{
+		let config = serde_json::from_str(&from.content).ok()?;
+		Some(Self {
+			config: ArcRwSignal::new(config),
+			_update: ArcRwSignal::new(Cache::newFrom(from.timestamp)),
+			_sended: ArcRwSignal::new(Cache::newFrom(from.timestamp)),
+		})
+	}
+
+	fn size(&self) -> ModuleSizeContrainte
+	{
+		ModuleSizeContrainte {
+			x_min: Some(300),
+			x_max: None,
+			y_min: Some(200),
+			y_max: None,
+		}
+	}
+}
+
+impl Cacheable for Container
+{
+	fn cache_time(&self) -> i64
+	{
+		self._update.get_untracked().get()
+	}
+
+	fn cache_mustUpdate(&self) -> bool
+	{
+		self._update.get_untracked().isNewer(&self._sended.get())
+	}
+
+	fn cache_getUpdate(&self) -> ArcRwSignal<Cache>
+	{
+		self._update.clone()
+	}
+
+	fn cache_getSended(&self) -> ArcRwSignal<Cache>
+	{
+		self._sended.clone()
+	}
+}
+
+impl moduleContent for Container {}
+
+#[component]
+fn ContainerDraw(
+	config: ArcRwSignal<ContainerConfig>,
+	update: ArcRwSignal<Cache>,
+	editMode: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+) -> impl IntoView
+{
+	let titleConfig = config.clone();
+	let bodyConfig = config.clone();
+	let bodyUpdate = update.clone();
+	let bodyActions = moduleActions.clone();
+	let bodyId = moduleId.clone();
+	let settingsConfig = config.clone();
+	let settingsUpdate = update.clone();
+	let settingsActions = moduleActions.clone();
+	let settingsId = moduleId.clone();
+
+	view! {
+		<div class="module_container">
+			{move || {
+				if (editMode.get())
+				{
+					return containerSettings_view(settingsConfig.clone(), settingsUpdate.clone(), editMode, settingsActions.clone(), settingsId.clone());
+				}
+				let title = titleConfig.get().title;
+				if (title.is_empty())
+				{
+					return view!{}.into_any();
+				}
+				view!{<h2 class="module_title">{title}</h2>}.into_any()
+			}}
+			{move || {
+				let children = ModuleHolder::getSingleton().with(|holder| holder.blocks_children(&bodyId));
+				if (children.is_empty())
+				{
+					return view!{<p class="module_container_empty"><Translate key="MODULE_CONTAINER_EMPTY"/></p>}.into_any();
+				}
+				return match bodyConfig.get().layout
+				{
+					ContainerLayout::TABS => containerTabs_view(children, bodyConfig.clone(), bodyUpdate.clone(), editMode, bodyActions.clone(), bodyId.clone()),
+					ContainerLayout::ACCORDION => containerAccordion_view(children, bodyConfig.clone(), bodyUpdate.clone(), editMode, bodyActions.clone(), bodyId.clone()),
+				};
+			}}
+		</div>
+	}
+}
+
+fn containerTabs_view(
+	children: Vec<(ModuleID, ArcRwSignal<ModulePositions<ModuleType>>)>,
+	config: ArcRwSignal<ContainerConfig>,
+	update: ArcRwSignal<Cache>,
+	editMode: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+) -> AnyView
+{
+	let childrenId = children.iter().map(|(childId,_)| childId.clone()).collect::<Vec<_>>();
+	let activeTab = config.with(|config| config.activeTab_get(&childrenId));
+	let tabs = children.iter().enumerate().map(|(index,(childId,module))| {
+		let isActive = activeTab.as_ref() == Some(childId);
+		let config = config.clone();
+		let update = update.clone();
+		let moduleActions = moduleActions.clone();
+		let moduleId = moduleId.clone();
+		let childId = childId.clone();
+		let select = move |_| {
+			let mut changed = false;
+			config.update(|config| changed = config.activeTab_set(childId.clone()));
+			if (changed)
+			{
+				Container::state_commit(&update, editMode, &moduleActions, &moduleId);
+			}
+		};
+		view!{
+			<button type="button" role="tab" class="module_container_tab" class:active=isActive aria-selected=isActive.to_string() on:click=select>
+				{Container::childLabel_get(module, index)}
+			</button>
+		}
+	}).collect_view();
+
+	let panel = children.iter()
+		.find(|(childId,_)| activeTab.as_ref() == Some(childId))
+		.map(|(childId,module)| Container::child_draw(module, editMode, moduleActions.clone(), childId.clone()))
+		.unwrap_or_else(|| view!{}.into_any());
+
+	return view!{
+		<div class="module_container_tabs" role="tablist">{tabs}</div>
+		<div class="module_container_panel" role="tabpanel">{panel}</div>
+	}.into_any();
+}
+
+fn containerAccordion_view(
+	children: Vec<(ModuleID, ArcRwSignal<ModulePositions<ModuleType>>)>,
+	config: ArcRwSignal<ContainerConfig>,
+	update: ArcRwSignal<Cache>,
+	editMode: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+) -> AnyView
+{
+	let sections = children.into_iter().enumerate().map(|(index,(childId,module))| {
+		let isExpanded = config.with(|config| config.expanded.contains(&childId));
+		let toggleConfig = config.clone();
+		let toggleUpdate = update.clone();
+		let toggleActions = moduleActions.clone();
+		let toggleModuleId = moduleId.clone();
+		let toggleChildId = childId.clone();
+		let toggle = move |_| {
+			toggleConfig.update(|config| config.expanded_toggle(toggleChildId.clone()));
+			Container::state_commit(&toggleUpdate, editMode, &toggleActions, &toggleModuleId);
+		};
+		let label = Container::childLabel_get(&module, index);
+		let panel = if (isExpanded)
+		{
+			let height = module.with(|module| module.size_get()[1] as i32 + module.pos_get()[1]);
+			let panel = Container::child_draw(&module, editMode, moduleActions.clone(), childId.clone());
+			view!{<div class="module_container_panel" style={format!("height: {}px", height.max(0))}>{panel}</div>}.into_any()
+		}
+		else
+		{
+			view!{}.into_any()
+		};
+		view!{
+			<section class="module_container_section">
+				<button type="button" class="module_container_section_header" aria-expanded=isExpanded.to_string() on:click=toggle>
+					<i class={if isExpanded {"iconoir-nav-arrow-down"} else {"iconoir-nav-arrow-right"}} aria-hidden="true"></i>
+					{label}
+				</button>
+				{panel}
+			</section>
+		}
+	}).collect_view();
+
+	return view!{<div class="module_container_accordion">{sections}</div>}.into_any();
+}
+
+fn containerSettings_view(
+	config: ArcRwSignal<ContainerConfig>,
+	update: ArcRwSignal<Cache>,
+	editMode: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+	moduleId: ModuleID,
+) -> AnyView
+{
+	let mut titleF = FieldHelper::new(&config, &update, "MODULE_TITLE_CONF",
+		|d| d.get().title,
+		|ev,inner| inner.title = ev.target().value());
+	titleF.setFullSize();
+
+	let layoutConfig = config.clone();
+	let layoutUpdate = update.clone();
+	let layoutActions = moduleActions.clone();
+	let layoutId = moduleId.clone();
+	let layoutValue = config.clone();
+
+	let selectedModule = RwSignal::new("".to_string());
+	let attachActions = moduleActions.clone();
+	let attachId = moduleId.clone();
+	let attach = move |_| {
+		let selected = ModuleID {id: selectedModule.get_untracked()};
+		let Some(child) = ModuleHolder::getSingleton().with_untracked(|holder| holder.blocks_get().get(&selected).cloned()) else {return};
+		ModuleHolder::history_record();
+		child.with_untracked(|child| {
+			child.pos_set([0, 0]);
+			child.parent_set(Some(attachId.clone()));
+		});
+		selectedModule.set("".to_string());
+		attachActions.visibility_apply();
+	};
+
+	let candidatesId = moduleId.clone();
+	let childrenId = moduleId.clone();
+	let detachActions = moduleActions.clone();
+
+	return view!{
+		<div class="module_config module_container_config">
+			{titleF.draw()}
+			<label class="module_config_field">
+				<Translate key="MODULE_CONTAINER_LAYOUT"/>
+				<select class="module_config_input"
+					prop:value=move || layoutValue.get().layout.value_get()
+					on:change:target=move |ev| {
+						let layout = ContainerLayout::fromValue(&ev.target().value());
+						layoutConfig.update(|config| config.layout = layout);
+						Container::state_commit(&layoutUpdate, editMode, &layoutActions, &layoutId);
+					}>
+					{[ContainerLayout::TABS, ContainerLayout::ACCORDION].into_iter().map(|layout| view!{
+						<option value={layout.value_get()}><TranslateText key={layout.translateKey_get()}/></option>
+					}).collect_view()}
+				</select>
+			</label>
+			<div class="module_config_field">
+				<span class="module_config_section_title"><Translate key="MODULE_CONTAINER_CHILDREN"/></span>
+				<ul class="module_container_children">
+					{move || {
+						let detachActions = detachActions.clone();
+						let containerId = childrenId.clone();
+						ModuleHolder::getSingleton().with(|holder| holder.blocks_children(&containerId))
+							.into_iter().enumerate().map(|(index,(_,module))| {
+								let detachActions = detachActions.clone();
+								let containerId = containerId.clone();
+								let detachModule = module.clone();
+								let detach = move |_| {
+									let containerPos = ModuleHolder::getSingleton().with_untracked(|holder| {
+										holder.blocks_get().get(&containerId).map(|container| container.with_untracked(|container| container.pos_get()))
+									}).unwrap_or([0, 0]);
+									ModuleHolder::history_record();
+									detachModule.with_untracked(|child| {
+										child.pos_set([containerPos[0] + CONTAINER_DETACH_OFFSET, containerPos[1] + CONTAINER_DETACH_OFFSET]);
+										child.parent_set(None);
+									});
+									detachActions.visibility_apply();
+								};
+								view!{
+									<li>
+										<span>{Container::childLabel_get(&module, index)}</span>
+										<button type="button" class="icon_button" on:click=detach>
+											<i class="iconoir-log-out" aria-hidden="true"></i>
+											<span class="visually_hidden"><TranslateText key="MODULE_CONTAINER_DETACH_ACTION"/></span>
+										</button>
+									</li>
+								}
+							}).collect_view()
+					}}
+				</ul>
+			</div>
+			<div class="module_config_field module_container_attach">
+				<label>
+					<Translate key="MODULE_CONTAINER_ATTACH"/>
+					<select class="module_config_input"
+						prop:value=move || selectedModule.get()
+						on:change:target=move |ev| selectedModule.set(ev.target().value())>
+						<option value=""></option>
+						{move || {
+							ModuleHolder::getSingleton().with(|holder| holder.blocks_attachable(&candidatesId))
+								.into_iter().enumerate().map(|(index,(candidateId,module))| view!{
+									<option value={candidateId.id.clone()}>{Container::childLabel_get(&module, index)}</option>
+								}).collect_view()
+						}}
+					</select>
+				</label>
+				<button type="button" disabled=move || selectedModule.get().is_empty() on:click=attach>
+					<TranslateText key="MODULE_CONTAINER_ATTACH_ACTION"/>
+				</button>
+			</div>
+		</div>
+	}.into_any();
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::modules::components::ModuleID;
+
+	use super::{ContainerConfig, ContainerLayout};
+
+	fn children_get() -> Vec<ModuleID>
+	{
+		return ["first","second","third"].into_iter().map(|id| ModuleID {id: id.to_string()}).collect();
+	}
+
+	#[test]
+	fn tabsLayout_showsOnlyActiveTabAndFallsBackToFirstChild()
+	{
+		let children = children_get();
+		let mut config = ContainerConfig::default();
+		assert!(config.child_isVisible(&children[0], &children));
+		assert!(!config.child_isVisible(&children[1], &children));
+
+		assert!(config.activeTab_set(children[2].clone()));
+		assert!(!config.activeTab_set(children[2].clone()));
+		assert!(config.child_isVisible(&children[2], &children));
+		assert!(!config.child_isVisible(&children[0], &children));
+
+		// the active tab left the container
+		assert_eq!(config.activeTab_get(&children[..2]), Some(children[0].clone()));
+	}
+
+	#[test]
+	fn accordionLayout_hidesCollapsedSections()
+	{
+		let children = children_get();
+		let mut config = ContainerConfig {
+			layout: ContainerLayout::ACCORDION,
+			..Default::default()
+		};
+		assert!(children.iter().all(|child| !config.child_isVisible(child, &children)));
+
+		config.expanded_toggle(children[1].clone());
+		assert!(config.child_isVisible(&children[1], &children));
+		assert!(!config.child_isVisible(&children[0], &children));
+
+		config.expanded_toggle(children[1].clone());
+		assert!(!config.child_isVisible(&children[1], &children));
+	}
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use leptos::prelude::ArcRwSignal;
+use crate::api::modules::components::ModuleID;
+use crate::front::modules::module_positions::{ModuleLayout, ModulePositions};
+use crate::front::modules::module_type::ModuleType;
+
+/// state of every module of the dashboard at one step of an edit session.
+/// The module signals are kept, so a removed module comes back exactly as it was.
+#[derive(Clone)]
+pub(crate) struct LayoutSnapshot
+{
+	modules: HashMap<ModuleID, (ArcRwSignal<ModulePositions<ModuleType>>, ModuleLayout)>,
+}
+
+impl LayoutSnapshot
+{
+	pub(crate) fn new(modules: HashMap<ModuleID, (ArcRwSignal<ModulePositions<ModuleType>>, ModuleLayout)>) -> Self
+	{
+		return Self {
+			modules,
+		};
+	}
+
+	pub(crate) fn modules_get(&self) -> &HashMap<ModuleID, (ArcRwSignal<ModulePositions<ModuleType>>, ModuleLayout)>
+	{
+		return &self.modules;
+	}
+
+	fn isSame(&self, other: &LayoutSnapshot) -> bool
+	{
+		return self.modules.len() == other.modules.len()
+			&& self.modules.iter().all(|(moduleId, (_, layout))| {
+				other.modules.get(moduleId).is_some_and(|(_, otherLayout)| otherLayout == layout)
+			});
+	}
+}
+
+/// undo/redo history of an edit session, nothing is recorded outside of edit mode
+#[derive(Default)]
+pub(crate) struct LayoutHistory
+{
+	// dashboard state when the edit session started
+	_baseline: Option<LayoutSnapshot>,
+	_undo: Vec<LayoutSnapshot>,
+	_redo: Vec<LayoutSnapshot>,
+	// state at the start of a drag, recorded only if the drag changed something
+	_gesture: Option<LayoutSnapshot>,
+}
+
+impl LayoutHistory
+{
+	const STEP_MAXIMUM: usize = 100;
+
+	pub(crate) fn begin(&mut self, baseline: LayoutSnapshot)
+	{
+		*self = Self::default();
+		self._baseline = Some(baseline);
+	}
+
+	/// stop recording, return the state from before the edit session
+	pub(crate) fn end(&mut self) -> Option<LayoutSnapshot>
+	{
+		return std::mem::take(self)._baseline;
+	}
+
+	pub(crate) fn isRecording(&self) -> bool
+	{
+		return self._baseline.is_some();
+	}
+
+	pub(crate) fn canUndo(&self) -> bool
+	{
+		return !self._undo.is_empty();
+	}
+
+	pub(crate) fn canRedo(&self) -> bool
+	{
+		return !self._redo.is_empty();
+	}
+
+	/// save the state from before a change, a new change forgets the undone steps
+	pub(crate) fn record(&mut self, before: LayoutSnapshot)
+	{
+		if (!self.isRecording())
+		{
+			return;
+		}
+		if (self._undo.last().is_some_and(|last| last.isSame(&before)))
+		{
+			return;
+		}
+		if (self._undo.len() >= Self::STEP_MAXIMUM)
+		{
+			self._undo.remove(0);
+		}
+		self._undo.push(before);
+		self._redo.clear();
+	}
+
+	pub(crate) fn gesture_begin(&mut self, before: LayoutSnapshot)
+	{
+		if (self.isRecording())
+		{
+			self._gesture = Some(before);
+		}
+	}
+
+	pub(crate) fn gesture_end(&mut self, current: &LayoutSnapshot)
+	{
+		let Some(before) = self._gesture.take() else {return};
+		if (!before.isSame(current))
+		{
+			self.record(before);
+		}
+	}
+
+	/// return the state to restore, `current` becomes the next redo step
+	pub(crate) fn undo(&mut self, current: LayoutSnapshot) -> Option<LayoutSnapshot>
+	{
+		let previous = Self::step_pop(&mut self._undo, &current)?;
+		self._redo.push(current);
+		return Some(previous);
+	}
+
+	/// return the state to restore, `current` becomes the next undo step
+	pub(crate) fn redo(&mut self, current: LayoutSnapshot) -> Option<LayoutSnapshot>
+	{
+		let next = Self::step_pop(&mut self._redo, &current)?;
+		self._undo.push(current);
+		return Some(next);
+	}
+
+	/// modules present when the edit session started and removed since, sorted by id
+	pub(crate) fn removed_get(&self, isPresent: impl Fn(&ModuleID) -> bool) -> Vec<ModuleID>
+	{
+		let Some(baseline) = &self._baseline else {return vec![]};
+		let mut removed = baseline.modules.keys()
+			.filter(|moduleId| !isPresent(moduleId))
+			.cloned()
+			.collect::<Vec<_>>();
+		removed.sort();
+		return removed;
+	}
+
+	// steps identical to the current state would make undo look like it does nothing
+	fn step_pop(steps: &mut Vec<LayoutSnapshot>, current: &LayoutSnapshot) -> Option<LayoutSnapshot>
+	{
+		while let Some(step) = steps.pop()
+		{
+			if (!step.isSame(current))
+			{
+				return Some(step);
+			}
+		}
+		return None;
+	}
+}
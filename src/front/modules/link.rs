@@ -1,6 +1,6 @@
 use crate::api::modules::components::{ModuleContent, ModuleID};
 use crate::front::modules::components::{
-	Backable, BoxFuture, Cache, Cacheable, ModuleName, ModuleSizeContrainte, RefreshTime,
+	Backable, RefreshFuture, Cache, Cacheable, ModuleName, ModuleSizeContrainte, RefreshTime,
 };
 use crate::front::modules::module_actions::ModuleActionFn;
 use crate::front::utils::all_front_enum::AllFrontUIEnum;
@@ -363,7 +363,7 @@ impl Backable for LinksHolder
 		_moduleActions: ModuleActionFn,
 		_moduleId: ModuleID,
 		_toaster: ToasterContext,
-	) -> Option<BoxFuture>
+	) -> Option<RefreshFuture>
 	{
 		return None;
 	}
@@ -1,12 +1,12 @@
 use std::ops::DerefMut;
-use std::collections::{HashMap,HashSet};
+use std::collections::{BTreeMap,HashMap,HashSet};
 use std::sync::Arc;
 use gloo_timers::callback::Timeout;
 use leptoaster::{expect_toaster, ToasterContext};
 use leptos::children::ViewFn;
-use leptos::prelude::{use_context, AriaAttributes, CollectView, GlobalAttributes, StyleAttribute, Write};
-use leptos::prelude::{ClassAttribute, ElementChild, GetUntracked, Update};
-use leptos::prelude::{AnyView, ArcRwSignal, Get, IntoAny, OnAttribute, RwSignal, Set};
+use leptos::prelude::{event_target_checked, event_target_value, use_context, AriaAttributes, CollectView, GlobalAttributes, PropAttribute, StyleAttribute, With, Write};
+use leptos::prelude::{ClassAttribute, ElementChild, GetUntracked, Update, WithUntracked};
+use leptos::prelude::{AnyView, ArcRwSignal, Effect, Get, IntoAny, OnAttribute, OnTargetAttribute, RwSignal, Set};
 use leptos::{component, view, IntoView};
 use leptos_use::{use_event_listener, use_window};
 use serde::{Deserialize, Serialize};
@@ -14,9 +14,10 @@ use time::UtcDateTime;
 #[cfg(feature="hydrate")]
 use wasm_bindgen::JsCast;
 use crate::api::modules::components::{ModuleContent, ModuleID};
-use crate::api::proxys::imap::{API_proxys_imap_getMailContent, API_proxys_imap_listbox, API_proxys_imap_setMailSee, API_proxys_imap_sync};
-use crate::api::proxys::imap_components::{imap_connector, Attachment, BoxName, ImapMailboxSync, ImapMailboxSyncState, ImapMail, ImapMailContentType, ImapMailKey, ImapSyncRequest};
-use crate::front::modules::components::{distant_time_simpler, Backable, BoxFuture, Cache, Cacheable, FieldHelper, FieldHelperType, ModuleName, ModuleSizeContrainte, RefreshTime};
+use crate::api::proxys::imap::{API_proxys_imap_getMailContent, API_proxys_imap_listbox, API_proxys_imap_mailsAction, API_proxys_imap_search, API_proxys_imap_setMailSee, API_proxys_imap_sync};
+use crate::api::proxys::imap_components::{imap_connector, Attachment, ImapConnectionSecurity, BoxName, ImapMailAction, ImapMailboxSync, ImapMailboxSyncState, ImapMail, ImapMailContentType, ImapMailKey, ImapSearchQuery, ImapSearchResult, ImapSyncRequest, MAIL_IMAGE_PROXY_PATH};
+use crate::api::proxys::smtp_components::smtp_connector;
+use crate::front::modules::components::{distant_time_simpler, Backable, RefreshFuture, RefreshOutcome, Cache, Cacheable, FieldHelper, FieldHelperType, ModuleName, ModuleSizeContrainte, RefreshTime};
 use crate::front::modules::module_actions::ModuleActionFn;
 use crate::front::utils::contentDownloader::download_attachment;
 use crate::front::utils::dialog::{DialogData, DialogManager};
@@ -24,6 +25,27 @@ use crate::front::utils::draw_title_if_present;
 use crate::front::utils::toaster_helpers::{toaster_api, toastingErr};
 use crate::front::utils::translate::{Translate, TranslateText};
 use crate::HWebTrace;
+use attachment_preview::{AttachmentPreviewKind, MailAttachmentPreview};
+use attachments::MailAttachments;
+use compose::{mailCompose_open, MailDraft};
+use idle::{mailIdle_listen, MailIdleListening};
+use invitation::mailInvitation_draw;
+use oauth::{mailOAuth_draw, MailOAuthRotations};
+use rules::{mailRules_apply, mailRules_configDraw, mailRules_hasTag, mailRules_serverActions, MailRule};
+use secure::{mailSecure_apply, mailSecure_configDraw, mailSecure_draw, MailSecureKeys};
+use source::MailSource;
+use thread::MailThread;
+
+mod attachment_preview;
+mod attachments;
+mod compose;
+mod idle;
+mod invitation;
+mod oauth;
+mod rules;
+mod secure;
+mod source;
+mod thread;
 
 #[derive(Serialize,Deserialize,Debug)]
 #[derive(Clone)]
@@ -35,7 +57,23 @@ struct MailConfig
 	mailAsTag: String,
 	#[serde(default)]
 	remoteImageSenderAllowList: Vec<String>,
+	/// authserv-id of the servers of the accounts (RFC 8601), only their Authentication-Results are trusted
+	#[serde(default)]
+	authenticationServers: String,
 	pub imap: imap_connector,
+	// other accounts listed with imap in a unified inbox
+	#[serde(default)]
+	imapAccounts: Vec<imap_connector>,
+	#[serde(default)]
+	pub smtp: smtp_connector,
+	// sending servers of imapAccounts, at the same index
+	#[serde(default)]
+	smtpAccounts: Vec<smtp_connector>,
+	#[serde(default)]
+	secureKeys: MailSecureKeys,
+	/// filtering rules of the list, applied in the order of the configuration
+	#[serde(default)]
+	rules: Vec<MailRule>,
 }
 impl Default for MailConfig
 {
@@ -45,7 +83,13 @@ impl Default for MailConfig
 			title: "".to_string(),
 			mailAsTag: "".to_string(),
 			remoteImageSenderAllowList: Vec::new(),
+			authenticationServers: "".to_string(),
 			imap: imap_connector::default(),
+			imapAccounts: Vec::new(),
+			smtp: smtp_connector::default(),
+			smtpAccounts: Vec::new(),
+			secureKeys: MailSecureKeys::default(),
+			rules: Vec::new(),
 		}
 	}
 }
@@ -174,6 +218,13 @@ impl MailTag
 		}
 	}
 
+	/// tag of the account a mail comes from, in a unified inbox
+	fn from_account(connector: &imap_connector) -> Self
+	{
+		let label = if (connector.username.is_empty()) {&connector.host} else {&connector.username};
+		return Self::new(label);
+	}
+
 	fn style(&self) -> String
 	{
 		return format!("--mail-tag-color:{}",self.color);
@@ -323,15 +374,29 @@ impl MailContentFrame
 		return self.content.is_html();
 	}
 
+	/// the sandboxed frame has an opaque origin, 'self' would not match the server
+	#[cfg(feature="hydrate")]
+	fn proxyOrigin_get() -> String
+	{
+		return web_sys::window().and_then(|window| window.location().origin().ok()).unwrap_or_default();
+	}
+
+	#[cfg(not(feature="hydrate"))]
+	fn proxyOrigin_get() -> String
+	{
+		return String::new();
+	}
+
 	fn srcdoc_get(&self, remoteImagesAllowed: bool) -> String
 	{
+		// remote images only come through the image proxy of the server, addresses rewritten by the sanitizer
 		let imageSources = if (remoteImagesAllowed)
 		{
-			"data: blob: http: https:"
+			format!("data: blob: {}{}",Self::proxyOrigin_get(),MAIL_IMAGE_PROXY_PATH)
 		}
 		else
 		{
-			"data: blob:"
+			"data: blob:".to_string()
 		};
 		let body = match self.content.as_ref()
 		{
@@ -385,6 +450,123 @@ impl MailConfig
 		return MailTag::from_address_header(&mail.to,&self.mailAsTag);
 	}
 
+	/// the configured authserv-ids, separated by spaces or commas, compared without case
+	fn authenticationServers_get(&self) -> Vec<String>
+	{
+		return self.authenticationServers.split(|character: char| character.is_whitespace() || character == ',')
+			.map(|server| server.trim_end_matches('.').to_ascii_lowercase())
+			.filter(|server| !server.is_empty())
+			.collect();
+	}
+
+	/// several accounts are merged in one list, each mail shows the tag of its account
+	fn isUnified(&self) -> bool
+	{
+		return !self.imapAccounts.is_empty();
+	}
+
+	fn accountTag_get(&self, account: usize) -> Option<MailTag>
+	{
+		if (!self.isUnified())
+		{
+			return None;
+		}
+		return self.imap_get(account).map(MailTag::from_account);
+	}
+
+	/// connector of an account, 0 is imap and the next ones are imapAccounts
+	fn imap_get(&self, account: usize) -> Option<&imap_connector>
+	{
+		return match account
+		{
+			0 => Some(&self.imap),
+			account => self.imapAccounts.get(account - 1),
+		};
+	}
+
+	fn imap_getMut(&mut self, account: usize) -> Option<&mut imap_connector>
+	{
+		return match account
+		{
+			0 => Some(&mut self.imap),
+			account => self.imapAccounts.get_mut(account - 1),
+		};
+	}
+
+	fn imaps_get(&self) -> Vec<imap_connector>
+	{
+		return std::iter::once(&self.imap).chain(self.imapAccounts.iter()).cloned().collect();
+	}
+
+	/// current index of the account synchronized with this identity, None once it is removed or edited
+	fn account_find(&self, syncIdentity: &MailSyncIdentity) -> Option<usize>
+	{
+		return std::iter::once(&self.imap).chain(self.imapAccounts.iter())
+			.position(|connector| MailSyncIdentity::new(connector) == *syncIdentity);
+	}
+
+	/// sending server of an account, 0 is smtp and the next ones are smtpAccounts
+	fn smtp_get(&self, account: usize) -> Option<&smtp_connector>
+	{
+		return match account
+		{
+			0 => Some(&self.smtp),
+			account => self.smtpAccounts.get(account - 1),
+		};
+	}
+
+	fn smtp_getMut(&mut self, account: usize) -> Option<&mut smtp_connector>
+	{
+		if (account > self.imapAccounts.len())
+		{
+			return None;
+		}
+		return match account
+		{
+			0 => Some(&mut self.smtp),
+			account => {
+				// the accounts added before their sending server have none saved yet
+				if (self.smtpAccounts.len() < account)
+				{
+					self.smtpAccounts.resize(account,smtp_connector::default());
+				}
+				self.smtpAccounts.get_mut(account - 1)
+			},
+		};
+	}
+
+	/// accounts able to send, with the address they send from
+	fn senders_get(&self) -> Vec<(usize,String)>
+	{
+		return (0..=self.imapAccounts.len())
+			.filter_map(|account| {
+				let (imap,smtp) = (self.imap_get(account)?,self.smtp_get(account)?);
+				return smtp.isConfigured().then(|| (account,smtp.sender_get(&imap.username).to_string()));
+			})
+			.collect();
+	}
+
+	fn account_add(&mut self) -> usize
+	{
+		self.smtpAccounts.resize(self.imapAccounts.len(),smtp_connector::default());
+		self.imapAccounts.push(imap_connector::default());
+		self.smtpAccounts.push(smtp_connector::default());
+		return self.imapAccounts.len();
+	}
+
+	fn account_remove(&mut self, account: usize)
+	{
+		if (account == 0 || account > self.imapAccounts.len())
+		{
+			return;
+		}
+		self.imapAccounts.remove(account - 1);
+		if (account <= self.smtpAccounts.len())
+		{
+			self.smtpAccounts.remove(account - 1);
+		}
+	}
+
 	fn remoteImageSender_isAllowed(&self, senderAddress: &MailSenderAddress) -> bool
 	{
 		return self.remoteImageSenderAllowList.iter()
@@ -411,6 +593,13 @@ impl MailConfig
 		return self.remoteImageSenderAllowList.len() != previousLength;
 	}
 
+	/// address of the owner of an account, left out of the answers to its mails
+	fn ownAddress_get(&self, account: usize) -> Option<MailSenderAddress>
+	{
+		let (imap,smtp) = (self.imap_get(account)?,self.smtp_get(account)?);
+		return MailSenderAddress::from_header(smtp.sender_get(&imap.username));
+	}
+
 	fn remoteImageSenderAllowList_get(&self) -> Vec<String>
 	{
 		let mut allowList = self.remoteImageSenderAllowList.clone();
@@ -420,6 +609,12 @@ impl MailConfig
 	}
 }
 
+fn mailDate_format(timestamp: i64) -> String
+{
+	let date = UtcDateTime::from_unix_timestamp(timestamp).unwrap_or(UtcDateTime::now());
+	return format!("{:0>2}/{:0>2}/{:0>4} {:0>2}:{:0>2}:{:0>2}",date.day(),date.month() as u8,date.year(),date.hour(),date.minute(),date.second());
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct MailSyncIdentity
 {
@@ -456,6 +651,28 @@ impl MailSyncIdentity
 	}
 }
 
+/// results of the active search, shown in place of the synchronized mails
+#[derive(Clone, Debug, Default)]
+struct MailSearchResults
+{
+	query: ImapSearchQuery,
+	mails: Vec<(ImapMailKey,ImapMail)>,
+	total: usize,
+	// start of the next page, none once every result is listed
+	next: Option<ImapMailKey>,
+	// moved mails, until the search is sent again
+	hidden: HashSet<ImapMailKey>,
+}
+
+/// mail of one account of the module, the account 0 is its imap connector
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct MailKey
+{
+	account: usize,
+	imap: ImapMailKey,
+}
+
+/// synchronized mails of one account
 #[derive(Clone, Debug, Default)]
 struct MailsContent
 {
@@ -465,11 +682,15 @@ struct MailsContent
 	confirmedSeen: HashSet<ImapMailKey>,
 	boxs: Vec<BoxName>,
 	syncIdentity: Option<MailSyncIdentity>,
+	search: Option<MailSearchResults>,
+	// groups of the server THREAD extension, by mailbox with their uid validity
+	serverThreads: HashMap<String,(u32,Vec<Vec<u32>>)>,
 }
 
 impl MailsContent
 {
-	fn syncRequest_get(&mut self, connector: &imap_connector) -> Option<ImapSyncRequest>
+	/// a scoped request only synchronizes onlyBox, when it is a known mailbox
+	fn syncRequest_get(&mut self, connector: &imap_connector, onlyBox: Option<&str>) -> Option<ImapSyncRequest>
 	{
 		let syncIdentity = MailSyncIdentity::new(connector);
 		if (self.syncIdentity.as_ref() != Some(&syncIdentity))
@@ -495,30 +716,46 @@ impl MailsContent
 		}
 
 		let mailboxes = self.mailboxes.as_ref().map(|mailboxes| {
-			let mut states = mailboxes.iter().map(|(boxName,uidValidity)| {
-				let mut knownUids = uidValidity.map(|uidValidity| {
-					return self.mailsData.keys().chain(self.pendingSeen.iter())
-						.filter(|key| key.boxName == *boxName && key.uidValidity == uidValidity)
-						.map(|key| key.uid)
-						.collect::<Vec<_>>();
-				}).unwrap_or_default();
-				knownUids.sort_unstable();
-				knownUids.dedup();
-				return ImapMailboxSyncState {
-					boxName: boxName.clone(),
-					uidValidity: *uidValidity,
-					knownUids,
-				};
-			}).collect::<Vec<_>>();
+			let mut states = mailboxes.iter()
+				.filter(|(boxName,_)| onlyBox.is_none_or(|onlyBox| onlyBox == boxName.as_str()))
+				.map(|(boxName,uidValidity)| {
+					let mut knownUids = uidValidity.map(|uidValidity| {
+						return self.mailsData.keys().chain(self.pendingSeen.iter())
+							.filter(|key| key.boxName == *boxName && key.uidValidity == uidValidity)
+							.map(|key| key.uid)
+							.collect::<Vec<_>>();
+					}).unwrap_or_default();
+					knownUids.sort_unstable();
+					knownUids.dedup();
+					return ImapMailboxSyncState {
+						boxName: boxName.clone(),
+						uidValidity: *uidValidity,
+						knownUids,
+					};
+				}).collect::<Vec<_>>();
 			states.sort_unstable_by(|left,right| left.boxName.cmp(&right.boxName));
 			return states;
 		});
+		if (onlyBox.is_some() && mailboxes.as_ref().is_none_or(Vec::is_empty))
+		{
+			return None;
+		}
 		return Some(ImapSyncRequest { mailboxes });
 	}
 
-	fn sync_apply(&mut self, mailboxes: Vec<ImapMailboxSync>)
+	/// a scoped result keeps the other mailboxes as they are
+	/// returns the keys of the mails the synchronization added to the cache
+	fn sync_apply(&mut self, mailboxes: Vec<ImapMailboxSync>, onlyBox: Option<&str>) -> Vec<ImapMailKey>
 	{
-		let mut synchronizedMailboxes = HashMap::new();
+		let mut addedKeys = Vec::new();
+		let mut synchronizedMailboxes = match onlyBox
+		{
+			Some(onlyBox) => self.mailboxes.iter().flatten()
+				.filter(|(boxName,_)| boxName.as_str() != onlyBox)
+				.map(|(boxName,uidValidity)| (boxName.clone(),*uidValidity))
+				.collect(),
+			None => HashMap::new(),
+		};
 		for mailbox in mailboxes
 		{
 			let previousValidity = self.mailboxes.as_ref()
@@ -549,9 +786,9 @@ impl MailsContent
 					uidValidity: mailbox.uidValidity,
 					uid: mail.uid,
 				};
-				if (!self.pendingSeen.contains(&key))
+				if (!self.pendingSeen.contains(&key) && self.mailsData.insert(key.clone(),mail).is_none())
 				{
-					self.mailsData.insert(key,mail);
+					addedKeys.push(key);
 				}
 			}
 			let reconciledSeen = self.confirmedSeen.iter()
@@ -563,6 +800,7 @@ impl MailsContent
 				self.confirmedSeen.remove(&key);
 				self.pendingSeen.remove(&key);
 			}
+			self.serverThreads.insert(mailbox.boxName.clone(),(mailbox.uidValidity,mailbox.threads));
 			synchronizedMailboxes.insert(mailbox.boxName,Some(mailbox.uidValidity));
 		}
 		self.mailsData.retain(|key,_| {
@@ -574,7 +812,25 @@ impl MailsContent
 		self.confirmedSeen.retain(|key| {
 			return synchronizedMailboxes.get(&key.boxName) == Some(&Some(key.uidValidity));
 		});
+		self.serverThreads.retain(|boxName,(uidValidity,_)| {
+			return synchronizedMailboxes.get(boxName) == Some(&Some(*uidValidity));
+		});
+		addedKeys.retain(|key| self.mailsData.contains_key(key));
 		self.mailboxes = Some(synchronizedMailboxes);
+		return addedKeys;
+	}
+
+	/// the mailbox watched by IMAP IDLE, INBOX when it is synchronized, else the first synchronized one
+	fn idleBox_get(&self) -> Option<String>
+	{
+		let mut synchronized = self.mailboxes.iter().flatten()
+			.filter(|(_,uidValidity)| uidValidity.is_some())
+			.map(|(boxName,_)| boxName)
+			.collect::<Vec<_>>();
+		synchronized.sort_unstable();
+		return synchronized.iter().find(|boxName| boxName.eq_ignore_ascii_case("INBOX"))
+			.or(synchronized.first())
+			.map(|boxName| boxName.to_string());
 	}
 
 	fn topology_set(&mut self, boxes: &[BoxName], connector: &imap_connector)
@@ -587,6 +843,7 @@ impl MailsContent
 			self.mailsData.clear();
 			self.pendingSeen.clear();
 			self.confirmedSeen.clear();
+			self.serverThreads.clear();
 			return;
 		}
 		let previousMailboxes = self.mailboxes.take().unwrap_or_default();
@@ -605,17 +862,109 @@ impl MailsContent
 		self.confirmedSeen.retain(|key| {
 			return selectedMailboxes.get(&key.boxName) == Some(&Some(key.uidValidity));
 		});
+		self.serverThreads.retain(|boxName,(uidValidity,_)| {
+			return selectedMailboxes.get(boxName) == Some(&Some(*uidValidity));
+		});
 		self.mailboxes = Some(selectedMailboxes);
 	}
 
-	fn confirmation_set(&mut self, key: &ImapMailKey, value: bool)
+	/// listed mails, in the server order for a search, newest first otherwise
+	fn mails_get(&self) -> Vec<(ImapMailKey,ImapMail)>
+	{
+		if let Some(search) = &self.search
+		{
+			return search.mails.iter()
+				.filter(|(key,_)| !search.hidden.contains(key))
+				.cloned()
+				.collect();
+		}
+		let mut mails = self.mailsData.iter()
+			.map(|(key,mail)| (key.clone(),mail.clone()))
+			.collect::<Vec<_>>();
+		mails.sort_by(|(_,left),(_,right)| left.date.cmp(&right.date).reverse());
+		return mails;
+	}
+
+	/// synchronized unread mails, a search does not change the count
+	fn unread_get(&self, isHidden: &impl Fn(&ImapMail) -> bool) -> usize
+	{
+		return self.mailsData.values().filter(|mail| !mail.seen && !isHidden(mail)).count();
+	}
+
+	/// groups of the server THREAD extension, as mail keys
+	fn serverThreads_get(&self) -> Vec<Vec<ImapMailKey>>
+	{
+		return self.serverThreads.iter()
+			.flat_map(|(boxName,(uidValidity,threads))| threads.iter().map(move |uids| {
+				return uids.iter().map(|uid| ImapMailKey {
+					boxName: boxName.clone(),
+					uidValidity: *uidValidity,
+					uid: *uid,
+				}).collect::<Vec<_>>();
+			}))
+			.collect();
+	}
+
+	fn mail_get(&self, key: &ImapMailKey) -> Option<&ImapMail>
+	{
+		if let Some(search) = &self.search
+		{
+			return search.mails.iter()
+				.find(|(searchKey,_)| searchKey == key && !search.hidden.contains(key))
+				.map(|(_,mail)| mail);
+		}
+		return self.mailsData.get(key);
+	}
+
+	/// applies change to the synchronized mail and to its search result
+	fn mail_update(&mut self, key: &ImapMailKey, change: impl Fn(&mut ImapMail))
 	{
 		if let Some(mail) = self.mailsData.get_mut(key)
 		{
-			mail.confirmVue = value;
+			change(mail);
+		}
+		let searchMails = self.search.iter_mut().flat_map(|search| search.mails.iter_mut());
+		for (_,mail) in searchMails.filter(|(searchKey,_)| searchKey == key)
+		{
+			change(mail);
+		}
+	}
+
+	fn search_set(&mut self, query: ImapSearchQuery, result: ImapSearchResult)
+	{
+		let search = self.search.get_or_insert_with(MailSearchResults::default);
+		if (query.after.is_none())
+		{
+			search.mails.clear();
+			search.hidden.clear();
+		}
+		search.mails.extend(result.mails);
+		search.total = result.total;
+		search.next = result.next;
+		search.query = query;
+	}
+
+	fn searchMails_hide(&mut self, keys: &[ImapMailKey], hidden: bool)
+	{
+		let Some(search) = &mut self.search else {return};
+		for key in keys
+		{
+			if (hidden)
+			{
+				search.hidden.insert(key.clone());
+			}
+			else
+			{
+				search.hidden.remove(key);
+			}
 		}
 	}
 
+	fn confirmation_set(&mut self, key: &ImapMailKey, value: bool)
+	{
+		self.mail_update(key,|mail| mail.confirmVue = value);
+	}
+
 	fn mailSeen_begin(&mut self, key: &ImapMailKey) -> Option<ImapMail>
 	{
 		self.pendingSeen.insert(key.clone());
@@ -640,6 +989,58 @@ impl MailsContent
 		}
 	}
 
+	/// mails marked unread on the server, the next synchronization lists them again
+	fn mailsUnseen_apply(&mut self, keys: &[ImapMailKey])
+	{
+		for key in keys
+		{
+			self.pendingSeen.remove(key);
+			self.confirmedSeen.remove(key);
+		}
+		if let Some(search) = &mut self.search
+		{
+			search.mails.iter_mut()
+				.filter(|(key,_)| keys.contains(key))
+				.for_each(|(_,mail)| mail.seen = false);
+		}
+	}
+
+	/// returns the previous state of the changed mails, for the rollback
+	fn mailsFlagged_set(&mut self, keys: &[ImapMailKey], flagged: bool) -> Vec<(ImapMailKey,bool)>
+	{
+		let mut previous = Vec::new();
+		for key in keys
+		{
+			if let Some(mail) = self.mail_get(key)
+			{
+				previous.push((key.clone(),mail.flagged));
+				self.mail_update(key,|mail| mail.flagged = flagged);
+			}
+		}
+		return previous;
+	}
+
+	fn mailsFlagged_restore(&mut self, previous: Vec<(ImapMailKey,bool)>)
+	{
+		for (key,flagged) in previous
+		{
+			self.mail_update(&key,|mail| mail.flagged = flagged);
+		}
+	}
+
+	/// destinations of a move: the listed mailboxes when known, otherwise the synchronized ones
+	fn moveTargets_get(&self) -> Vec<String>
+	{
+		let mut boxNames = self.boxs.iter()
+			.filter(|boxContent| !boxContent.attributes.is_no_select)
+			.map(|boxContent| boxContent.name.clone())
+			.chain(self.mailboxes.iter().flat_map(|mailboxes| mailboxes.keys().cloned()))
+			.collect::<Vec<_>>();
+		boxNames.sort_unstable();
+		boxNames.dedup();
+		return boxNames;
+	}
+
 	fn sync_reset(&mut self, syncIdentity: MailSyncIdentity)
 	{
 		self.mailboxes = None;
@@ -648,6 +1049,154 @@ impl MailsContent
 		self.confirmedSeen.clear();
 		self.boxs.clear();
 		self.syncIdentity = Some(syncIdentity);
+		self.search = None;
+		self.serverThreads.clear();
+	}
+}
+
+/// one cache by account, so each account keeps its own synchronization identity and uid validities
+#[derive(Clone, Debug, Default)]
+struct MailsInbox
+{
+	accounts: Vec<MailsContent>,
+}
+
+impl MailsInbox
+{
+	fn account_get(&self, account: usize) -> Option<&MailsContent>
+	{
+		return self.accounts.get(account);
+	}
+
+	/// the caches of the new accounts start empty, the ones of the removed last accounts are dropped
+	fn accounts_resize(&mut self, count: usize)
+	{
+		self.accounts.resize_with(count,MailsContent::default);
+	}
+
+	/// the next accounts keep their cache under their new position
+	fn account_remove(&mut self, account: usize)
+	{
+		if (account < self.accounts.len())
+		{
+			self.accounts.remove(account);
+		}
+	}
+
+	fn account_update(&mut self, account: usize, change: impl FnOnce(&mut MailsContent))
+	{
+		if let Some(content) = self.accounts.get_mut(account)
+		{
+			change(content);
+		}
+	}
+
+	/// current index of the account synchronized with this identity, wherever the removal of another account moved it
+	fn accountSynced_find(&self, syncIdentity: &MailSyncIdentity) -> Option<usize>
+	{
+		return self.accounts.iter().position(|content| content.syncIdentity.as_ref() == Some(syncIdentity));
+	}
+
+	fn accountSynced_get(&self, syncIdentity: &MailSyncIdentity) -> Option<&MailsContent>
+	{
+		return self.accounts.get(self.accountSynced_find(syncIdentity)?);
+	}
+
+	/// an edited or removed account drops the answer of its synchronization
+	fn accountSynced_update(&mut self, syncIdentity: &MailSyncIdentity, change: impl FnOnce(&mut MailsContent))
+	{
+		if let Some(account) = self.accountSynced_find(syncIdentity)
+		{
+			self.account_update(account,change);
+		}
+	}
+
+	fn search_isActive(&self) -> bool
+	{
+		return self.accounts.iter().any(|content| content.search.is_some());
+	}
+
+	fn search_clear(&mut self)
+	{
+		self.accounts.iter_mut().for_each(|content| content.search = None);
+	}
+
+	/// total of the active search, with the next page of each account where the server found more mails
+	fn searchSummary_get(&self) -> Option<(usize,Vec<(usize,ImapSearchQuery)>)>
+	{
+		if (!self.search_isActive())
+		{
+			return None;
+		}
+		let searches = self.accounts.iter().enumerate()
+			.filter_map(|(account,content)| content.search.as_ref().map(|search| (account,search)));
+		let mut total = 0;
+		let mut nextQueries = Vec::new();
+		for (account,search) in searches
+		{
+			total += search.total;
+			if let Some(next) = &search.next
+			{
+				nextQueries.push((account,search.query.nextPage_get(next.clone())));
+			}
+		}
+		return Some((total,nextQueries));
+	}
+
+	/// listed mails of every account, newest first. A single account keeps its own order,
+	/// the server one for a search
+	fn mails_get(&self) -> Vec<(MailKey,ImapMail)>
+	{
+		let isSearching = self.search_isActive();
+		let mut mails = self.accounts.iter().enumerate()
+			// an account without result for the search does not mix its synchronized mails in
+			.filter(|(_,content)| !isSearching || content.search.is_some())
+			.flat_map(|(account,content)| content.mails_get().into_iter().map(move |(key,mail)| {
+				return (MailKey {account, imap: key},mail);
+			}))
+			.collect::<Vec<_>>();
+		if (self.accounts.len() > 1)
+		{
+			mails.sort_by(|(_,left),(_,right)| left.date.cmp(&right.date).reverse());
+		}
+		return mails;
+	}
+
+	/// listed mails grouped in conversations, in the order of their newest mail, without the hidden ones.
+	/// A conversation received on two accounts is one thread
+	fn threads_get(&self, isHidden: impl Fn(&ImapMail) -> bool) -> Vec<MailThread>
+	{
+		let serverThreads = self.accounts.iter().enumerate()
+			.flat_map(|(account,content)| content.serverThreads_get().into_iter().map(move |keys| {
+				return keys.into_iter().map(|key| MailKey {account, imap: key}).collect::<Vec<_>>();
+			}))
+			.collect::<Vec<_>>();
+		let mails = self.mails_get().into_iter().filter(|(_,mail)| !isHidden(mail)).collect();
+		return MailThread::list_build(mails,&serverThreads);
+	}
+
+	/// unread mails of every account, reported to the dashboard title and favicon
+	fn unread_get(&self, isHidden: impl Fn(&ImapMail) -> bool) -> usize
+	{
+		return self.accounts.iter().map(|content| content.unread_get(&isHidden)).sum();
+	}
+
+	fn mail_get(&self, key: &MailKey) -> Option<&ImapMail>
+	{
+		return self.account_get(key.account)?.mail_get(&key.imap);
+	}
+
+	/// destinations of a move shared by every given account
+	fn moveTargets_get(&self, accounts: &[usize]) -> Vec<String>
+	{
+		let mut targets = accounts.iter()
+			.map(|account| self.account_get(*account).map(MailsContent::moveTargets_get).unwrap_or_default());
+		let Some(mut common) = targets.next() else {return Vec::new()};
+		for target in targets
+		{
+			common.retain(|boxName| target.contains(boxName));
+		}
+		return common;
 	}
 }
 
@@ -656,18 +1205,23 @@ pub struct Mail
 {
 	config: ArcRwSignal<MailConfig>,
 	#[serde(skip_serializing,skip_deserializing)]
-	mailsClientCache: ArcRwSignal<MailsContent>,
+	mailsClientCache: ArcRwSignal<MailsInbox>,
 	_update: ArcRwSignal<Cache>,
 	_sended: ArcRwSignal<Cache>,
+	#[serde(skip_serializing,skip_deserializing)]
+	_idleListening: MailIdleListening,
 }
 
 impl Mail
 {
-	fn draw_config(getBoxsMailConfig: ArcRwSignal<MailConfig>, getBoxsMailsCache: ArcRwSignal<MailsContent>, update: ArcRwSignal<Cache>, moduleActions: ModuleActionFn) -> AnyView
+	/// the connection fields and the mailboxes are the ones of editedAccount
+	fn draw_config(getBoxsMailConfig: ArcRwSignal<MailConfig>, getBoxsMailsCache: ArcRwSignal<MailsInbox>, update: ArcRwSignal<Cache>, editedAccount: RwSignal<usize>, moduleActions: ModuleActionFn) -> AnyView
 	{
+		let account = editedAccount.get().min(getBoxsMailConfig.get_untracked().imapAccounts.len());
 		let getBoxsMailConfigInner = getBoxsMailConfig.clone();
 		let getBoxsMailsCacheInner = getBoxsMailsCache.clone();
 		let getBoxsConfigCache = update.clone();
+		let oauthRotations = MailOAuthRotations::new(getBoxsMailConfig.clone(),update.clone());
 		let toaster = expect_toaster();
 		let moduleActionsGetBox = moduleActions.clone();
 		let getBoxsFn = move |_| {
@@ -675,9 +1229,11 @@ impl Mail
 			let getBoxsMailConfig = getBoxsMailConfigInner.clone();
 			let getBoxsMailContent = getBoxsMailsCacheInner.clone();
 			let getBoxsConfigCache = getBoxsConfigCache.clone();
+			let oauthRotations = oauthRotations.clone();
 			let moduleActionsTask = moduleActionsGetBox.clone();
 			moduleActionsGetBox.task_spawn(async move {
-				let apiResult = API_proxys_imap_listbox(getBoxsMailConfig.get_untracked().imap.clone()).await;
+				let Some(connector) = getBoxsMailConfig.get_untracked().imap_get(account).cloned() else {return};
+				let apiResult = oauthRotations.keep(&connector,API_proxys_imap_listbox(connector.clone()).await);
 				if (!moduleActionsTask.lifecycle_isActive())
 				{
 					return;
@@ -688,14 +1244,23 @@ impl Mail
 					{
 						return;
 					}
-					let mut connector = getBoxsMailConfig.get_untracked().imap.clone();
+					let config = getBoxsMailConfig.get_untracked();
+					let Some(mut connector) = config.imap_get(account).cloned() else {return};
 					if (connector.boxSelection_migrate(&result))
 					{
 						let updatedConnector = connector.clone();
-						getBoxsMailConfig.update(|config| config.imap = updatedConnector);
+						getBoxsMailConfig.update(|config| {
+							if let Some(imap) = config.imap_getMut(account)
+							{
+								*imap = updatedConnector;
+							}
+						});
 						getBoxsConfigCache.update(|cache| cache.update());
 					}
-					getBoxsMailContent.update(|mailContent| mailContent.topology_set(&result,&connector));
+					getBoxsMailContent.update(|mailContent| {
+						mailContent.accounts_resize(config.imapAccounts.len() + 1);
+						mailContent.account_update(account,|content| content.topology_set(&result,&connector));
+					});
 				}
 			});
 		};
@@ -709,33 +1274,103 @@ impl Mail
 		                                  |d| d.get().mailAsTag,
 		                                  |ev,inner| inner.mailAsTag = ev.target().value());
 		mailAsTagF.setFullSize();
+		let mut authenticationServersF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_AUTHENTICATION_SERVERS",
+		                                  |d| d.get().authenticationServers,
+		                                  |ev,inner| inner.authenticationServers = ev.target().value());
+		authenticationServersF.setFullSize();
+		let imapField = move |config: ArcRwSignal<MailConfig>, field: fn(&imap_connector) -> String| {
+			return config.get().imap_get(account).map(field).unwrap_or_default();
+		};
+		let imapFieldSet = move |config: &mut MailConfig, change: &dyn Fn(&mut imap_connector)| {
+			if let Some(imap) = config.imap_getMut(account)
+			{
+				change(imap);
+			}
+		};
 		let hostF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_HOST",
-		                                  |d| d.get().imap.host,
-		                                  |ev,inner| inner.imap.host = ev.target().value());
+		                                  move |d| imapField(d,|imap| imap.host.clone()),
+		                                  move |ev,inner| imapFieldSet(inner,&|imap| imap.host = ev.target().value()));
 		let mut portF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_PORT",
-		                              |d| d.get().imap.port.to_string(),
-		                              |ev,inner| inner.imap.port = ev.target().value().parse::<u16>().unwrap_or(993));
+		                              move |d| imapField(d,|imap| imap.port.to_string()),
+		                              move |ev,inner| imapFieldSet(inner,&|imap| imap.port = ev.target().value().parse::<u16>().unwrap_or(993)));
 		portF.setInputType(FieldHelperType::NUMBER(1,65535));
+		let securityValue = getBoxsMailConfig.clone();
+		let securityConfig = getBoxsMailConfig.clone();
+		let securityCache = update.clone();
 		let usernameF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_USERNAME",
-		                              |d| d.get().imap.username,
-		                              |ev,inner| inner.imap.username = ev.target().value());
+		                              move |d| imapField(d,|imap| imap.username.clone()),
+		                              move |ev,inner| imapFieldSet(inner,&|imap| imap.username = ev.target().value()));
 		let mut passwordF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_PASSWORD",
-		                              |d| d.get().imap.password,
-		                              |ev,inner| inner.imap.password = ev.target().value());
+		                              move |d| imapField(d,|imap| imap.password.clone()),
+		                              move |ev,inner| imapFieldSet(inner,&|imap| imap.password = ev.target().value()));
 		passwordF.setInputType(FieldHelperType::PASSWORD);
+		let smtpField = move |config: ArcRwSignal<MailConfig>, field: fn(&smtp_connector) -> String| {
+			return config.get().smtp_get(account).map(field).unwrap_or_default();
+		};
+		let smtpFieldSet = move |config: &mut MailConfig, change: &dyn Fn(&mut smtp_connector)| {
+			if let Some(smtp) = config.smtp_getMut(account)
+			{
+				change(smtp);
+			}
+		};
+		let smtpHostF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_SMTP_HOST",
+		                                  move |d| smtpField(d,|smtp| smtp.host.clone()),
+		                                  move |ev,inner| smtpFieldSet(inner,&|smtp| smtp.host = ev.target().value()));
+		let mut smtpPortF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_SMTP_PORT",
+		                              move |d| smtpField(d,|smtp| smtp.port.to_string()),
+		                              move |ev,inner| smtpFieldSet(inner,&|smtp| smtp.port = ev.target().value().parse::<u16>().unwrap_or(465)));
+		smtpPortF.setInputType(FieldHelperType::NUMBER(1,65535));
+		let mut smtpSenderF = FieldHelper::new(&getBoxsMailConfig,&update,"MODULE_MAIL_SMTP_SENDER",
+		                              move |d| smtpField(d,|smtp| smtp.sender.clone()),
+		                              move |ev,inner| smtpFieldSet(inner,&|smtp| smtp.sender = ev.target().value()));
+		smtpSenderF.setFullSize();
 		let remoteImageAllowListConfig = getBoxsMailConfig.clone();
 		let remoteImageAllowListCache = update.clone();
+		let oauthView = mailOAuth_draw(getBoxsMailConfig.clone(),update.clone(),account,moduleActions.clone());
+		let accountsView = Self::draw_configAccounts(getBoxsMailConfig.clone(),getBoxsMailsCache.clone(),update.clone(),account,editedAccount);
+		let secureView = mailSecure_configDraw(getBoxsMailConfig.clone(),update.clone());
+		let rulesView = mailRules_configDraw(getBoxsMailConfig.clone(),update.clone());
 
 		view!{
 			<div class="module_config module_mail_config">
 				{titleF.draw()}
 				{mailAsTagF.draw()}
+				{accountsView}
 				<div class="module_mail_connection_fields">
 					{hostF.draw()}
 					{portF.draw()}
 				</div>
+				<label class="module_config_field">
+					<Translate key="MODULE_MAIL_SECURITY"/>
+					<select class="module_config_input"
+						prop:value=move || securityValue.get().imap_get(account).map(|imap| imap.security.value_get()).unwrap_or_default()
+						on:change:target=move |ev| {
+							let security = ImapConnectionSecurity::fromValue(&ev.target().value());
+							securityConfig.update(|config| {
+								if let Some(imap) = config.imap_getMut(account)
+								{
+									imap.port = imap.security.port_switch(imap.port,security);
+									imap.security = security;
+								}
+							});
+							securityCache.update(|cache| cache.update());
+						}>
+						{[ImapConnectionSecurity::TLS, ImapConnectionSecurity::STARTTLS, ImapConnectionSecurity::NONE].into_iter().map(|security| view!{
+							<option value={security.value_get()}><TranslateText key={security.translateKey_get()}/></option>
+						}).collect_view()}
+					</select>
+				</label>
 				{usernameF.draw()}
 				{passwordF.draw()}
+				{oauthView}
+				<div class="module_mail_connection_fields">
+					{smtpHostF.draw()}
+					{smtpPortF.draw()}
+				</div>
+				{smtpSenderF.draw()}
+				{authenticationServersF.draw()}
+				{secureView}
+				{rulesView}
 				{
 					move || {
 						let allowedAddresses = remoteImageAllowListConfig.get().remoteImageSenderAllowList_get();
@@ -783,20 +1418,23 @@ impl Mail
 					let boxTopology = getBoxsMailsCache.clone();
 					let switchBoxFn = move |boxName:String,isSelected:bool| {
 						boxConfig.update(|mailContent|{
-							mailContent.imap.boxSelection_set(boxName.clone(),!isSelected);
+							if let Some(imap) = mailContent.imap_getMut(account)
+							{
+								imap.boxSelection_set(boxName.clone(),!isSelected);
+							}
 							boxConfigCache.update(|cache|{
 								cache.update();
 							});
 						});
-						let connector = boxConfig.get_untracked().imap.clone();
-						boxTopology.update(|mailContent| {
-							let boxes = mailContent.boxs.clone();
-							mailContent.topology_set(&boxes,&connector);
-						});
+						let Some(connector) = boxConfig.get_untracked().imap_get(account).cloned() else {return};
+						boxTopology.update(|mailContent| mailContent.account_update(account,|content| {
+							let boxes = content.boxs.clone();
+							content.topology_set(&boxes,&connector);
+						}));
 					};
 
-					let mailsCache = getBoxsMailsCache.clone().get();
-					let configBoxContent = getBoxsMailConfig.clone().get();
+					let mailsCache = getBoxsMailsCache.clone().get().account_get(account).cloned().unwrap_or_default();
+					let configBoxContent = getBoxsMailConfig.clone().get().imap_get(account).cloned().unwrap_or_default();
 					if(!mailsCache.boxs.is_empty())
 					{
 						view!{
@@ -810,7 +1448,7 @@ impl Mail
 											return view!{<span class="boxmail boxmail--unavailable" aria-disabled="true">{boxName}</span>}.into_any();
 										}
 										let switchBoxFn = switchBoxFn.clone();
-										let isSelected = configBoxContent.imap.isBoxSelected(&boxName);
+										let isSelected = configBoxContent.isBoxSelected(&boxName);
 										let boxNameContent = boxName.clone();
 										view!{
 											<button type="button"
@@ -831,24 +1469,72 @@ impl Mail
 			}.into_any()
 	}
 
-	fn mail_mark_see(imapConnector: imap_connector, toaster: ToasterContext, mailKey: ImapMailKey, mailsContent: ArcRwSignal<MailsContent>, moduleActions: ModuleActionFn)
+	/// chooses the account edited by the connection fields, a second account makes the module a unified inbox
+	fn draw_configAccounts(config: ArcRwSignal<MailConfig>, mailsCache: ArcRwSignal<MailsInbox>, update: ArcRwSignal<Cache>, account: usize, editedAccount: RwSignal<usize>) -> AnyView
 	{
-		// The request survives the dialog Owner, but remains owned by the active ModuleHolder lifecycle.
-		let moduleActionsTask = moduleActions.clone();
-		moduleActions.task_spawn(async move {
-			// we remove the old data sooner to improve reactivity and re-add them later if something gone wrong
-			let oldMail;
-			{
+		let accounts = config.get_untracked().imaps_get();
+		let configAdd = config.clone();
+		let updateAdd = update.clone();
+		let removeAction = (account > 0).then(|| view!{
+			<button type="button" on:click={move |_| {
+				config.update(|config| config.account_remove(account));
+				mailsCache.update(|mailsCache| mailsCache.account_remove(account));
+				update.update(|cache| cache.update());
+				editedAccount.set(account - 1);
+			}}><Translate key="MODULE_MAIL_ACCOUNT_REMOVE"/></button>
+		});
+		return view!{
+			<div class="module_mail_accounts">
+				<label class="module_config_field">
+					<Translate key="MODULE_MAIL_ACCOUNT"/>
+					<select class="module_config_input"
+						prop:value={account.to_string()}
+						on:change:target=move |ev| editedAccount.set(ev.target().value().parse().unwrap_or(0))>
+						{accounts.iter().enumerate().map(|(index,connector)| {
+							let label = MailTag::from_account(connector).label;
+							view!{
+								<option value={index.to_string()} selected={index == account}>{
+									if (label.is_empty())
+									{
+										view!{<TranslateText key="MODULE_MAIL_ACCOUNT_NEW"/>}.into_any()
+									}
+									else {label.into_any()}
+								}</option>
+							}
+						}).collect_view()}
+					</select>
+				</label>
+				<button type="button" on:click={move |_| {
+					let mut added = 0;
+					configAdd.update(|config| added = config.account_add());
+					updateAdd.update(|cache| cache.update());
+					editedAccount.set(added);
+				}}><Translate key="MODULE_MAIL_ACCOUNT_ADD"/></button>
+				{removeAction}
+			</div>
+		}.into_any();
+	}
+
+	fn mail_mark_see(imapConnector: imap_connector, oauthRotations: MailOAuthRotations, toaster: ToasterContext, mailKey: MailKey, mailsContent: ArcRwSignal<MailsInbox>, moduleActions: ModuleActionFn)
+	{
+		// The request survives the dialog Owner, but remains owned by the active ModuleHolder lifecycle.
+		let moduleActionsTask = moduleActions.clone();
+		moduleActions.task_spawn(async move {
+			// we remove the old data sooner to improve reactivity and re-add them later if something gone wrong
+			let oldMail;
+			{
 				let Some(mut binding) = mailsContent.try_write()
 				else
 				{
 					return;
 				};
-				let mailsDatas: &mut MailsContent = binding.deref_mut();
-				oldMail = mailsDatas.mailSeen_begin(&mailKey);
+				let Some(mailsDatas) = binding.deref_mut().accounts.get_mut(mailKey.account) else {return};
+				oldMail = mailsDatas.mailSeen_begin(&mailKey.imap);
+				// a search result stays listed as a read mail
+				mailsDatas.mail_update(&mailKey.imap,|mail| mail.seen = true);
 			}
 
-			let apiResult = API_proxys_imap_setMailSee(imapConnector,mailKey.clone()).await;
+			let apiResult = oauthRotations.keep(&imapConnector,API_proxys_imap_setMailSee(imapConnector.clone(),mailKey.imap.clone()).await);
 			if (!moduleActionsTask.lifecycle_isActive())
 			{
 				return;
@@ -860,21 +1546,84 @@ impl Mail
 			}
 			if (requestFailed)
 			{
-				mailsContent.update(|mailContent| mailContent.mailSeen_rollback(mailKey,oldMail));
+				mailsContent.update(|mailContent| mailContent.account_update(mailKey.account,|mailContent| {
+					mailContent.mail_update(&mailKey.imap,|mail| mail.seen = false);
+					mailContent.mailSeen_rollback(mailKey.imap,oldMail);
+				}));
 				return;
 			};
-			mailsContent.update(|mailContent| mailContent.mailSeen_commit(mailKey));
+			mailsContent.update(|mailContent| mailContent.account_update(mailKey.account,|mailContent| mailContent.mailSeen_commit(mailKey.imap)));
 
 		});
 	}
 
-	fn mail_view_content(mailConfig: ArcRwSignal<MailConfig>, configUpdate: ArcRwSignal<Cache>, toaster: ToasterContext, dialogManager: DialogManager, mailKey: ImapMailKey, mailIdContent: ImapMail, mailsCache: ArcRwSignal<MailsContent>, moduleActions: ModuleActionFn, moduleId: ModuleID)
+	/// mailKeys are mails of the account of imapConnector
+	fn mails_action(imapConnector: imap_connector, oauthRotations: MailOAuthRotations, toaster: ToasterContext, account: usize, mailKeys: Vec<ImapMailKey>, action: ImapMailAction, mailsContent: ArcRwSignal<MailsInbox>, moduleActions: ModuleActionFn)
+	{
+		if (mailKeys.is_empty())
+		{
+			return;
+		}
+		let moduleActionsTask = moduleActions.clone();
+		moduleActions.task_spawn(async move {
+			// moved mails are hidden like the seen ones, until the next synchronization confirms their removal
+			let (movedMails,previousFlags) = {
+				let Some(mut binding) = mailsContent.try_write()
+				else
+				{
+					return;
+				};
+				let Some(mailsDatas) = binding.deref_mut().accounts.get_mut(account) else {return};
+				match &action
+				{
+					action if action.isMoving() => {
+						mailsDatas.searchMails_hide(&mailKeys,true);
+						(mailKeys.iter().map(|key| (key.clone(),mailsDatas.mailSeen_begin(key))).collect::<Vec<_>>(),Vec::new())
+					},
+					ImapMailAction::FLAGGED(flagged) => (Vec::new(),mailsDatas.mailsFlagged_set(&mailKeys,*flagged)),
+					_ => (Vec::new(),Vec::new()),
+				}
+			};
+
+			let isUnseen = action == ImapMailAction::UNSEEN;
+			let successMessage = isUnseen.then_some("MODULE_MAIL_ACTION_UNSEEN_DONE");
+			let apiResult = oauthRotations.keep(&imapConnector,API_proxys_imap_mailsAction(imapConnector.clone(),mailKeys.clone(),action).await);
+			if (!moduleActionsTask.lifecycle_isActive())
+			{
+				return;
+			}
+			let requestFailed = toaster_api(&toaster, apiResult, successMessage).await.is_none();
+			if (!moduleActionsTask.lifecycle_isActive())
+			{
+				return;
+			}
+			mailsContent.update(|mailContent| mailContent.account_update(account,|mailContent| {
+				if (requestFailed)
+				{
+					mailContent.searchMails_hide(&movedMails.iter().map(|(key,_)| key.clone()).collect::<Vec<_>>(),false);
+					movedMails.into_iter().for_each(|(key,mail)| mailContent.mailSeen_rollback(key,mail));
+					mailContent.mailsFlagged_restore(previousFlags);
+				}
+				else
+				{
+					movedMails.into_iter().for_each(|(key,_)| mailContent.mailSeen_commit(key));
+					if (isUnseen)
+					{
+						mailContent.mailsUnseen_apply(&mailKeys);
+					}
+				}
+			}));
+		});
+	}
+
+	fn mail_view_content(mailConfig: ArcRwSignal<MailConfig>, configUpdate: ArcRwSignal<Cache>, toaster: ToasterContext, dialogManager: DialogManager, mailKey: MailKey, mailIdContent: ImapMail, mailsCache: ArcRwSignal<MailsInbox>, moduleActions: ModuleActionFn, moduleId: ModuleID)
 	{
 		// The resulting dialog can outlive the mail row Owner, but not the holder lifecycle.
-		let imapConnector = mailConfig.get_untracked().imap.clone();
+		let Some(imapConnector) = mailConfig.get_untracked().imap_get(mailKey.account).cloned() else {return};
+		let oauthRotations = MailOAuthRotations::new(mailConfig.clone(),configUpdate.clone());
 		let moduleActionsTask = moduleActions.clone();
 		moduleActions.task_spawn(async move {
-			let apiResult = API_proxys_imap_getMailContent(imapConnector.clone(),mailKey.clone()).await;
+			let apiResult = oauthRotations.keep(&imapConnector,API_proxys_imap_getMailContent(imapConnector.clone(),mailKey.imap.clone()).await);
 			if (!moduleActionsTask.lifecycle_isActive())
 			{
 				return;
@@ -884,8 +1633,13 @@ impl Mail
 			{
 				return;
 			}
+			// signatures and decryption are done here, with keys the server never sees
+			let sender = MailSenderAddress::from_header(&mailIdContent.from);
+			let (mailContent,secureState) = mailSecure_apply(mailContent,&mailConfig.get_untracked().secureKeys,sender.as_ref());
 
 			let toasterBody = toaster.clone();
+			let attachmentsBody = MailAttachments::new(imapConnector.clone(),oauthRotations.clone(),mailKey.imap.clone(),mailContent.attachement.clone());
+			let sourceBody = MailSource::new(imapConnector.clone(),oauthRotations.clone(),mailKey.imap.clone(),mailIdContent.subject.clone(),mailConfig.get_untracked().authenticationServers_get(),moduleActionsTask.clone());
 			let mailIdContentBody = mailIdContent.clone();
 			let contentFrameBody = MailContentFrame::new(mailContent.content.clone());
 			let mailContentBody = Arc::new(mailContent);
@@ -897,8 +1651,12 @@ impl Mail
 			let configUpdateBody = configUpdate.clone();
 			let moduleActionsBody = moduleActionsTask.clone();
 			let moduleIdBody = moduleId.clone();
+			let oauthRotationsBody = oauthRotations.clone();
 			let moduleActionsValidate = moduleActionsTask.clone();
 			let mailKeyValidate = mailKey.clone();
+			let dialogManagerBody = dialogManager.clone();
+			let mailKeyManage = mailKey.clone();
+			let mailsCacheManage = mailsCache.clone();
 
 			let dialogContent = DialogData::new()
 				.setTitle(mailIdContent.subject.clone().map(|subject|format!("€{}", subject)).unwrap_or("MODULE_MAIL_NO_SUBJECT".to_string()))
@@ -911,50 +1669,81 @@ impl Mail
 					let configUpdate = configUpdateBody.clone();
 					let moduleId = moduleIdBody.clone();
 					let remoteImagesAllowed = ArcRwSignal::new(remoteImagesInitiallyAllowedBody);
+					let attachments = attachmentsBody.clone();
+					let oauthRotations = oauthRotationsBody.clone();
+					let answerActions = Self::mail_answerActions(mailConfig.clone(),oauthRotations.clone(),mailKeyManage.account,mailContent.clone(),attachments.clone(),dialogManagerBody.clone(),moduleActionsBody.clone());
+					let manageActions = Self::mail_manageActions(mailConfig.clone(),oauthRotations.clone(),mailKeyManage.clone(),mailsCacheManage.clone(),dialogManagerBody.clone(),moduleActionsBody.clone());
+					let source = sourceBody.clone();
 
 					let moduleActionsDownload = moduleActionsBody.clone();
+					let moduleActionsPreview = moduleActionsBody.clone();
 					let moduleActionsRemoteImages = moduleActionsBody.clone();
-					let downloadAttachement = move |attachement: Attachment, toaster: ToasterContext| {
-						Self::attachment_download(attachement,toaster,moduleActionsDownload.clone());
+					let attachmentsDownload = attachments.clone();
+					let downloadAttachement = move |attachmentIndex: usize, toaster: ToasterContext| {
+						let attachments = attachmentsDownload.clone();
+						let moduleActions = moduleActionsDownload.clone();
+						moduleActionsDownload.task_spawn(async move {
+							let Some(attachment) = attachments.load(attachmentIndex,&toaster).await else {return};
+							Self::attachment_download(attachment,toaster,moduleActions);
+						});
 					};
 
 					let toasterInner = toasterBody.clone();
+					// attachment shown inline under the metadata, one at a time
+					let attachmentPreviewed = ArcRwSignal::new(None::<usize>);
 					view!{
 						<div class="module_mail_content_parent">
 							<div class="module_mail_metadata">
 								<span><b><Translate key="MODULE_MAIL_FROM"/></b>{" "}{mailId.from}</span>
 								<span><b><Translate key="MODULE_MAIL_TO"/></b>{" "}{mailId.to}</span>
-								<span><b><Translate key="MODULE_MAIL_DATE"/></b>{" "}{
-									let date = UtcDateTime::from_unix_timestamp(mailId.date).unwrap_or(UtcDateTime::now());
-									format!("{:0>2}/{:0>2}/{:0>4} {:0>2}:{:0>2}:{:0>2}",date.day(),date.month() as u8,date.year(),date.hour(),date.minute(),date.second())
-								}</span>
+								<span><b><Translate key="MODULE_MAIL_DATE"/></b>{" "}{mailDate_format(mailId.date)}</span>
 								{
 							let views = mailContent.attachement.iter().enumerate().map(|(attachmentIndex,att)| {
-								let attachmentMailContent = mailContent.clone();
 								let downloadAttachement = downloadAttachement.clone();
-									return match &att.filename {
-										None => {view!{{" "}<button type="button" class="attachement" on:click={
-												let toasterInner = toasterInner.clone();
-												move |_| {
-													if let Some(attachment) = attachmentMailContent.attachement.get(attachmentIndex).cloned()
-													{
-														downloadAttachement(attachment,toasterInner.clone());
-													}
+								let attachmentsBusy = attachments.clone();
+								let label = match &att.filename {
+									None => view!{<TranslateText key="MODULE_MAIL_NO_SUBJECT"/>}.into_any(),
+									Some(filename) => view!{{filename.clone()}}.into_any(),
+								};
+								let previewAction = AttachmentPreviewKind::fromAttachment(att).map(|_| {
+									let previewedPressed = attachmentPreviewed.clone();
+									let previewedToggle = attachmentPreviewed.clone();
+									let attachmentsPreview = attachments.clone();
+									let moduleActionsPreview = moduleActionsPreview.clone();
+									let toasterPreview = toasterInner.clone();
+									view!{<button type="button" class="attachement module_mail_attachment_preview_action"
+										aria-pressed=move || (previewedPressed.get() == Some(attachmentIndex)).to_string()
+										on:click=move |_| {
+											if (previewedToggle.get_untracked() == Some(attachmentIndex))
+											{
+												previewedToggle.set(None);
+												return;
+											}
+											let attachments = attachmentsPreview.clone();
+											let previewed = previewedToggle.clone();
+											let toaster = toasterPreview.clone();
+											let moduleActions = moduleActionsPreview.clone();
+											moduleActionsPreview.task_spawn(async move {
+												let Some(attachment) = attachments.load(attachmentIndex,&toaster).await else {return};
+												if (!moduleActions.lifecycle_isActive())
+												{
+													return;
 												}
-											}><i class="iconoir-doc-magnifying-glass" aria-hidden="true"/>{" "}<TranslateText key="MODULE_MAIL_NO_SUBJECT"/><span class="visually_hidden">{" - "}<TranslateText key="MODULE_MAIL_DOWNLOAD_ATTACHMENT_ACTION"/></span></button>}}.into_any(),
-										Some(filename) => {
-											let attachmentMailContent = attachmentMailContent.clone();
-											view!{{" "}<button type="button" class="attachement" on:click={
-												let toasterInner = toasterInner.clone();
-												move |_| {
-													if let Some(attachment) = attachmentMailContent.attachement.get(attachmentIndex).cloned()
-													{
-														downloadAttachement(attachment,toasterInner.clone());
-													}
+												// the fetched data can tell a binary file behind a text type
+												if (AttachmentPreviewKind::fromAttachment(&attachment).is_none())
+												{
+													toastingErr(&toaster, "MODULE_MAIL_ATTACHMENT_PREVIEW_FAILED").await;
+													return;
 												}
-											}><i class="iconoir-doc-magnifying-glass" aria-hidden="true"/>{" "}{filename.clone()}<span class="visually_hidden">{" - "}<TranslateText key="MODULE_MAIL_DOWNLOAD_ATTACHMENT_ACTION"/></span></button>}.into_any()
+												previewed.set(Some(attachmentIndex));
+											});
 										}
-									};
+									><i class="iconoir-eye" aria-hidden="true"/><span class="visually_hidden"><TranslateText key="MODULE_MAIL_ATTACHMENT_PREVIEW_ACTION"/></span></button>}
+								});
+								view!{{" "}<button type="button" class="attachement" aria-busy=move || attachmentsBusy.isLoading(attachmentIndex).to_string() on:click={
+										let toasterInner = toasterInner.clone();
+										move |_| downloadAttachement(attachmentIndex,toasterInner.clone())
+									}><i class="iconoir-doc-magnifying-glass" aria-hidden="true"/>{" "}{label}<span class="visually_hidden">{" - "}<TranslateText key="MODULE_MAIL_DOWNLOAD_ATTACHMENT_ACTION"/></span></button>{previewAction}}.into_any()
 								});
 
 								if(views.len() > 0)
@@ -964,6 +1753,24 @@ impl Mail
 								else {view!{}.into_any()}
 								}
 							</div>
+							{
+								let attachments = attachments.clone();
+								let attachmentPreviewed = attachmentPreviewed.clone();
+								move || {
+									let index = attachmentPreviewed.get()?;
+									let attachment = attachments.get_untracked(index).filter(Attachment::isLoaded)?;
+									let kind = AttachmentPreviewKind::fromAttachment(&attachment)?;
+									return Some(view!{<MailAttachmentPreview attachment kind previewed=attachmentPreviewed.clone()/>});
+								}
+							}
+							{source.inspector_draw()}
+							{secureState.clone().map(mailSecure_draw)}
+							{mailContent.invitation.clone().map(|invitation| mailInvitation_draw(mailConfig.clone(),oauthRotations.clone(),mailKeyManage.account,invitation,moduleActionsBody.clone()))}
+							<div class="module_mail_answer_actions">
+								{manageActions}
+								{answerActions}
+								{source.action_draw()}
+							</div>
 							{
 								let contentFrame = contentFrame.clone();
 								let remoteImagesAllowed = remoteImagesAllowed.clone();
@@ -1040,7 +1847,7 @@ impl Mail
 				})
 				.setButtonValidateTitle(Some("MODULE_MAIL_MAILCONTENTSEEN"))
 				.setOnValidate(move |_| {
-					Self::mail_mark_see(imapConnector.clone(), toaster.clone(), mailKeyValidate.clone(), mailsCache.clone(), moduleActionsValidate.clone());
+					Self::mail_mark_see(imapConnector.clone(), oauthRotations.clone(), toaster.clone(), mailKeyValidate.clone(), mailsCache.clone(), moduleActionsValidate.clone());
 					return true;
 				})
 				.setIsLarger(true);
@@ -1049,6 +1856,358 @@ impl Mail
 		});
 	}
 
+	/// the answers are sent by the account of the mail
+	fn mail_answerActions(mailConfig: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, account: usize, mailContent: Arc<ImapMail>, attachments: MailAttachments, dialogManager: DialogManager, moduleActions: ModuleActionFn) -> AnyView
+	{
+		if (!mailConfig.get_untracked().smtp_get(account).is_some_and(smtp_connector::isConfigured))
+		{
+			return view!{}.into_any();
+		}
+		let answerButton = move |translateKey: &'static str, withAttachments: bool, draftFn: fn(&ImapMail, Option<&MailSenderAddress>) -> MailDraft| {
+			let mailConfig = mailConfig.clone();
+			let oauthRotations = oauthRotations.clone();
+			let mailContent = mailContent.clone();
+			let attachments = attachments.clone();
+			let dialogManager = dialogManager.clone();
+			let moduleActions = moduleActions.clone();
+			return view!{
+				<button type="button" on:click={move |_| {
+					if (!moduleActions.lifecycle_isActive())
+					{
+						return;
+					}
+					if (!withAttachments)
+					{
+						let draft = draftFn(&mailContent,mailConfig.get_untracked().ownAddress_get(account).as_ref());
+						mailCompose_open(mailConfig.clone(),oauthRotations.clone(),account,draft,dialogManager.clone(),moduleActions.clone());
+						return;
+					}
+					let toaster = expect_toaster();
+					let mailConfig = mailConfig.clone();
+					let oauthRotations = oauthRotations.clone();
+					let mailContent = mailContent.clone();
+					let attachments = attachments.clone();
+					let dialogManager = dialogManager.clone();
+					let moduleActionsTask = moduleActions.clone();
+					moduleActions.task_spawn(async move {
+						let Some(loaded) = attachments.all_load(&toaster).await else {return};
+						if (!moduleActionsTask.lifecycle_isActive())
+						{
+							return;
+						}
+						let mail = ImapMail {attachement: loaded, ..(*mailContent).clone()};
+						let draft = draftFn(&mail,mailConfig.get_untracked().ownAddress_get(account).as_ref());
+						mailCompose_open(mailConfig,oauthRotations,account,draft,dialogManager,moduleActionsTask);
+					});
+				}}><Translate key={translateKey}/></button>
+			};
+		};
+		return view!{
+			<>
+				{answerButton("MODULE_MAIL_REPLY",false,|mail,ownAddress| MailDraft::reply_get(mail,ownAddress,false))}
+				{answerButton("MODULE_MAIL_REPLY_ALL",false,|mail,ownAddress| MailDraft::reply_get(mail,ownAddress,true))}
+				// the attachments left on the server are fetched to be sent again
+				{answerButton("MODULE_MAIL_FORWARD",true,|mail,_| MailDraft::forward_get(mail))}
+			</>
+		}.into_any();
+	}
+
+	fn mail_manageActions(mailConfig: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, mailKey: MailKey, mailsCache: ArcRwSignal<MailsInbox>, dialogManager: DialogManager, moduleActions: ModuleActionFn) -> AnyView
+	{
+		let isFlagged = mailsCache.get_untracked().mail_get(&mailKey).is_some_and(|mail| mail.flagged);
+		let actionButton = move |translateKey: &'static str, action: ImapMailAction| {
+			let mailConfig = mailConfig.clone();
+			let oauthRotations = oauthRotations.clone();
+			let mailKey = mailKey.clone();
+			let mailsCache = mailsCache.clone();
+			let dialogManager = dialogManager.clone();
+			let moduleActions = moduleActions.clone();
+			return view!{
+				<button type="button" on:click={move |_| {
+					if (!moduleActions.lifecycle_isActive())
+					{
+						return;
+					}
+					let Some(imapConnector) = mailConfig.get_untracked().imap_get(mailKey.account).cloned() else {return};
+					// a mail marked unread goes back to the list, it is closed like a moved one
+					if (action.isMoving() || action == ImapMailAction::UNSEEN)
+					{
+						dialogManager.clear();
+					}
+					Self::mails_action(imapConnector,oauthRotations.clone(),expect_toaster(),mailKey.account,vec![mailKey.imap.clone()],action.clone(),mailsCache.clone(),moduleActions.clone());
+				}}><Translate key={translateKey}/></button>
+			};
+		};
+		let flagAction = if (isFlagged)
+		{
+			actionButton("MODULE_MAIL_UNFLAG",ImapMailAction::FLAGGED(false))
+		}
+		else
+		{
+			actionButton("MODULE_MAIL_FLAG",ImapMailAction::FLAGGED(true))
+		};
+		return view!{
+			<>
+				{actionButton("MODULE_MAIL_ARCHIVE",ImapMailAction::ARCHIVE)}
+				{actionButton("MODULE_MAIL_TRASH",ImapMailAction::TRASH)}
+				{flagAction}
+				{actionButton("MODULE_MAIL_MARK_UNREAD",ImapMailAction::UNSEEN)}
+			</>
+		}.into_any();
+	}
+
+	fn mails_bulkActions(config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, mailsCache: ArcRwSignal<MailsInbox>, selection: RwSignal<HashSet<MailKey>>, selectedCount: usize, moveTarget: RwSignal<String>, moduleActions: ModuleActionFn) -> AnyView
+	{
+		// the selected mails of each account, which receives its own action
+		let selectedKeys = {
+			let mailsCache = mailsCache.clone();
+			move || {
+				let mailsCache = mailsCache.get_untracked();
+				let mut keys = BTreeMap::<usize,Vec<ImapMailKey>>::new();
+				for key in selection.get_untracked().into_iter().filter(|key| mailsCache.mail_get(key).is_some())
+				{
+					keys.entry(key.account).or_default().push(key.imap);
+				}
+				keys.values_mut().for_each(|keys| keys.sort_unstable_by(|left,right| (&left.boxName,left.uid).cmp(&(&right.boxName,right.uid))));
+				return keys;
+			}
+		};
+		// a move needs a mailbox of every selected account
+		let selectedAccounts = selectedKeys().into_keys().collect::<Vec<_>>();
+		let moveTargets = mailsCache.get_untracked().moveTargets_get(&selectedAccounts);
+		// the synchronized list only holds unread mails, only a search can select read ones
+		let hasSeenMail = {
+			let mailsCache = mailsCache.get_untracked();
+			selection.get_untracked().iter().any(|key| mailsCache.mail_get(key).is_some_and(|mail| mail.seen))
+		};
+		let actionButton = {
+			let mailsCache = mailsCache.clone();
+			move |translateKey: &'static str, action: fn(String) -> Option<ImapMailAction>| {
+				let config = config.clone();
+				let oauthRotations = oauthRotations.clone();
+				let mailsCache = mailsCache.clone();
+				let moduleActions = moduleActions.clone();
+				let selectedKeys = selectedKeys.clone();
+				return view!{
+					<button type="button" on:click={move |_| {
+						if (!moduleActions.lifecycle_isActive())
+						{
+							return;
+						}
+						let Some(action) = action(moveTarget.get_untracked()) else {return};
+						selection.set(HashSet::new());
+						let config = config.get_untracked();
+						for (account,mailKeys) in selectedKeys()
+						{
+							let Some(imapConnector) = config.imap_get(account).cloned() else {continue};
+							Self::mails_action(imapConnector,oauthRotations.clone(),expect_toaster(),account,mailKeys,action.clone(),mailsCache.clone(),moduleActions.clone());
+						}
+					}}><Translate key={translateKey}/></button>
+				};
+			}
+		};
+		return view!{
+			<div class="module_mail_bulk_actions" role="group">
+				<span class="module_mail_bulk_count">
+					<Translate key="MODULE_MAIL_SELECTED_COUNT" params={HashMap::from([("count".to_string(),selectedCount.to_string())])}/>
+				</span>
+				{actionButton("MODULE_MAIL_ARCHIVE",|_| Some(ImapMailAction::ARCHIVE))}
+				{actionButton("MODULE_MAIL_TRASH",|_| Some(ImapMailAction::TRASH))}
+				{actionButton("MODULE_MAIL_FLAG",|_| Some(ImapMailAction::FLAGGED(true)))}
+				{actionButton("MODULE_MAIL_UNFLAG",|_| Some(ImapMailAction::FLAGGED(false)))}
+				{hasSeenMail.then(|| actionButton("MODULE_MAIL_MARK_UNREAD",|_| Some(ImapMailAction::UNSEEN)))}
+				<label class="module_mail_move">
+					<span class="visually_hidden"><TranslateText key="MODULE_MAIL_MOVE_TARGET"/></span>
+					<select prop:value={move || moveTarget.get()} on:change={move |event| moveTarget.set(leptos::prelude::event_target_value(&event))}>
+						<option value=""><TranslateText key="MODULE_MAIL_MOVE_TARGET"/></option>
+						{moveTargets.into_iter().map(|boxName| view!{<option value={boxName.clone()}>{boxName.clone()}</option>}).collect_view()}
+					</select>
+				</label>
+				{actionButton("MODULE_MAIL_MOVE",|target| (!target.is_empty()).then_some(ImapMailAction::MOVE(target)))}
+			</div>
+		}.into_any();
+	}
+
+	/// sends each query to its account, a query without criterion leaves the search
+	fn mails_search(config: MailConfig, oauthRotations: MailOAuthRotations, toaster: ToasterContext, queries: Vec<(usize,ImapSearchQuery)>, mailsContent: ArcRwSignal<MailsInbox>, moduleActions: ModuleActionFn)
+	{
+		if (queries.iter().any(|(_,query)| !query.hasCriterion()))
+		{
+			mailsContent.update(MailsInbox::search_clear);
+			return;
+		}
+		for (account,query) in queries
+		{
+			let Some(imapConnector) = config.imap_get(account).cloned() else {continue};
+			let oauthRotations = oauthRotations.clone();
+			let toaster = toaster.clone();
+			let mailsContent = mailsContent.clone();
+			let moduleActionsTask = moduleActions.clone();
+			moduleActions.task_spawn(async move {
+				let syncIdentity = MailSyncIdentity::new(&imapConnector);
+				let apiResult = oauthRotations.keep(&imapConnector,API_proxys_imap_search(imapConnector.clone(),query.clone()).await);
+				if (!moduleActionsTask.lifecycle_isActive())
+				{
+					return;
+				}
+				let Some(result) = toaster_api(&toaster, apiResult, None).await else {return};
+				if (!moduleActionsTask.lifecycle_isActive())
+				{
+					return;
+				}
+				mailsContent.update(|mailContent| mailContent.account_update(account,|mailContent| {
+					// the results of a previous account are dropped
+					if (mailContent.syncIdentity.as_ref() == Some(&syncIdentity))
+					{
+						mailContent.search_set(query,result);
+					}
+				}));
+			});
+		}
+	}
+
+	fn mails_searchForm(config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, mailsCache: ArcRwSignal<MailsInbox>, draft: RwSignal<ImapSearchQuery>, advanced: RwSignal<bool>, moduleActions: ModuleActionFn) -> AnyView
+	{
+		let textField = move |translateKey: &'static str, field: fn(&mut ImapSearchQuery) -> &mut String| {
+			return view!{
+				<label>
+					<span><TranslateText key={translateKey}/></span>
+					<input type="text"
+						prop:value={move || draft.with(|query| field(&mut query.clone()).clone())}
+						on:input={move |event| draft.update(|query| *field(query) = event_target_value(&event))}/>
+				</label>
+			};
+		};
+		let dateField = move |translateKey: &'static str, field: fn(&mut ImapSearchQuery) -> &mut Option<String>| {
+			return view!{
+				<label>
+					<span><TranslateText key={translateKey}/></span>
+					<input type="date"
+						prop:value={move || draft.with(|query| field(&mut query.clone()).clone().unwrap_or_default())}
+						on:input={move |event| {
+							let value = event_target_value(&event);
+							draft.update(|query| *field(query) = (!value.is_empty()).then_some(value));
+						}}/>
+				</label>
+			};
+		};
+		let checkField = move |translateKey: &'static str, field: fn(&mut ImapSearchQuery) -> &mut bool| {
+			return view!{
+				<label class="module_mail_search_check">
+					<input type="checkbox"
+						prop:checked={move || draft.with(|query| *field(&mut query.clone()))}
+						on:change={move |event| draft.update(|query| *field(query) = event_target_checked(&event))}/>
+					<span><TranslateText key={translateKey}/></span>
+				</label>
+			};
+		};
+		let mailsCacheClear = mailsCache.clone();
+		let moduleActionsClear = moduleActions.clone();
+		let isSearching = {
+			let mailsCache = mailsCache.clone();
+			move || mailsCache.with(MailsInbox::search_isActive)
+		};
+		return view!{
+			<form class="module_mail_search" role="search" on:submit={move |event| {
+				event.prevent_default();
+				if (moduleActions.lifecycle_isActive())
+				{
+					let config = config.get_untracked();
+					let query = draft.get_untracked();
+					let queries = (0..=config.imapAccounts.len()).map(|account| (account,query.clone())).collect();
+					Self::mails_search(config,oauthRotations.clone(),expect_toaster(),queries,mailsCache.clone(),moduleActions.clone());
+				}
+			}}>
+				<div class="module_mail_search_bar">
+					<label class="module_mail_search_text">
+						<span class="visually_hidden"><TranslateText key="MODULE_MAIL_SEARCH"/></span>
+						<input type="search"
+							prop:value={move || draft.with(|query| query.text.clone())}
+							on:input={move |event| draft.update(|query| query.text = event_target_value(&event))}/>
+					</label>
+					<button type="submit"><Translate key="MODULE_MAIL_SEARCH_ACTION"/></button>
+					<button type="button"
+						aria-expanded={move || advanced.get().to_string()}
+						on:click={move |_| advanced.update(|advanced| *advanced = !*advanced)}
+					><Translate key="MODULE_MAIL_SEARCH_ADVANCED"/></button>
+					{move || isSearching().then(|| {
+						let mailsCache = mailsCacheClear.clone();
+						let moduleActions = moduleActionsClear.clone();
+						view!{
+							<button type="button" on:click={move |_| {
+								if (moduleActions.lifecycle_isActive())
+								{
+									draft.set(ImapSearchQuery::default());
+									mailsCache.update(MailsInbox::search_clear);
+								}
+							}}>
+								<i class="iconoir-xmark" aria-hidden="true"/>
+								<span class="visually_hidden"><TranslateText key="MODULE_MAIL_SEARCH_CLEAR"/></span>
+							</button>
+						}
+					})}
+				</div>
+				{move || advanced.get().then(|| view!{
+					<div class="module_mail_search_advanced">
+						{textField("MODULE_MAIL_FROM",|query| &mut query.from)}
+						{textField("MODULE_MAIL_TO",|query| &mut query.to)}
+						{textField("MODULE_MAIL_SUBJECT",|query| &mut query.subject)}
+						{textField("MODULE_MAIL_SEARCH_BODY",|query| &mut query.body)}
+						{dateField("MODULE_MAIL_SEARCH_SINCE",|query| &mut query.since)}
+						{dateField("MODULE_MAIL_SEARCH_UNTIL",|query| &mut query.until)}
+						{checkField("MODULE_MAIL_SEARCH_UNSEEN",|query| &mut query.unseen)}
+						{checkField("MODULE_MAIL_SEARCH_ATTACHMENT",|query| &mut query.hasAttachment)}
+					</div>
+				})}
+			</form>
+		}.into_any();
+	}
+
+	/// count of the active search, with the next page of the accounts where the server found more mails
+	fn mails_searchSummary(config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, mailsCache: ArcRwSignal<MailsInbox>, total: usize, nextQueries: Vec<(usize,ImapSearchQuery)>, moduleActions: ModuleActionFn) -> AnyView
+	{
+		return view!{
+			<div class="module_mail_search_summary" role="status">
+				<span><Translate key="MODULE_MAIL_SEARCH_RESULTS" params={HashMap::from([("count".to_string(),total.to_string())])}/></span>
+				{(!nextQueries.is_empty()).then(|| view!{
+					<button type="button" on:click={move |_| {
+						if (moduleActions.lifecycle_isActive())
+						{
+							Self::mails_search(config.get_untracked(),oauthRotations.clone(),expect_toaster(),nextQueries.clone(),mailsCache.clone(),moduleActions.clone());
+						}
+					}}><Translate key="MODULE_MAIL_SEARCH_MORE"/></button>
+				})}
+			</div>
+		}.into_any();
+	}
+
+	/// expands the older mails of a thread, its newest mail stays listed
+	fn mails_threadToggle(threadId: MailKey, count: usize, unread: usize, threadsExpanded: RwSignal<HashSet<MailKey>>) -> AnyView
+	{
+		let threadIdExpanded = threadId.clone();
+		let isExpanded = move || threadsExpanded.with(|expanded| expanded.contains(&threadIdExpanded));
+		let isExpandedIcon = isExpanded.clone();
+		let summaryParams = HashMap::from([
+			("count".to_string(),count.to_string()),
+			("unread".to_string(),unread.to_string()),
+		]);
+		return view!{
+			<button type="button" class="module_mail_thread_toggle"
+				aria-expanded={move || isExpanded().to_string()}
+				on:click={move |_| threadsExpanded.update(|expanded| {
+					if (!expanded.remove(&threadId))
+					{
+						expanded.insert(threadId.clone());
+					}
+				})}>
+				<i class={move || if (isExpandedIcon()) {"iconoir-nav-arrow-down"} else {"iconoir-nav-arrow-right"}} aria-hidden="true"/>
+				<span aria-hidden="true">{count}</span>
+				{(unread > 0).then(|| view!{<span class="module_mail_thread_unread" aria-hidden="true">{unread}</span>})}
+				<span class="visually_hidden"><TranslateText key="MODULE_MAIL_THREAD_SUMMARY" params={summaryParams}/></span>
+			</button>
+		}.into_any();
+	}
+
 	fn attachment_download(attachement: Attachment, toaster: ToasterContext, moduleActions: ModuleActionFn)
 	{
 		if (!moduleActions.lifecycle_isActive())
@@ -1063,30 +2222,82 @@ impl Mail
 		}
 	}
 
-	async fn sync(toaster: ToasterContext, mailContentRaw: ArcRwSignal<MailsContent>, config: ArcRwSignal<MailConfig>, moduleActions: ModuleActionFn)
+	/// synchronizes every selected mailbox of each account, or only the mailbox of onlyBox for a change pushed by IMAP IDLE
+	async fn sync(toaster: ToasterContext, mailContentRaw: ArcRwSignal<MailsInbox>, config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, moduleActions: ModuleActionFn, onlyBox: Option<(MailSyncIdentity,String)>) -> RefreshOutcome
 	{
-		if (!moduleActions.lifecycle_isActive())
+		let syncIdentities = config.get_untracked().imaps_get().iter().map(MailSyncIdentity::new).collect::<Vec<_>>();
+		let mut outcome = RefreshOutcome::SUCCESS;
+		let mut addedMails = Vec::new();
+		for syncIdentity in syncIdentities
+		{
+			let accountBox = match &onlyBox
+			{
+				Some((boxIdentity,_)) if *boxIdentity != syncIdentity => continue,
+				Some((_,boxName)) => Some(boxName.as_str()),
+				None => None,
+			};
+			// one failed account does not keep the others from their synchronization
+			if (Self::sync_account(toaster.clone(),mailContentRaw.clone(),config.clone(),oauthRotations.clone(),syncIdentity,moduleActions.clone(),accountBox,&mut addedMails).await == RefreshOutcome::FAILED)
+			{
+				outcome = RefreshOutcome::FAILED;
+			}
+		}
+		if (moduleActions.lifecycle_isActive())
+		{
+			Self::rules_serverApply(toaster,mailContentRaw,config,oauthRotations,&addedMails,moduleActions);
+		}
+		return outcome;
+	}
+
+	/// moves and flags asked by the rules, on the mails the synchronization brought
+	fn rules_serverApply(toaster: ToasterContext, mailsContent: ArcRwSignal<MailsInbox>, config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, addedMails: &[(MailSyncIdentity,Vec<ImapMailKey>)], moduleActions: ModuleActionFn)
+	{
+		if (addedMails.is_empty())
 		{
 			return;
 		}
 		let config = config.get_untracked();
+		let actions = mailsContent.with_untracked(|mailsContent| mailRules_serverActions(&config.rules,mailsContent,addedMails));
+		for (account,action,mailKeys) in actions
+		{
+			let Some(connector) = config.imap_get(account).cloned() else {continue};
+			Self::mails_action(connector,oauthRotations.clone(),toaster.clone(),account,mailKeys,action,mailsContent.clone(),moduleActions.clone());
+		}
+	}
+
+	/// the account is found by its identity before and after the request, the previous ones may have been removed meanwhile
+	/// the keys of the mails it added are kept in addedMails with the identity of the account
+	async fn sync_account(toaster: ToasterContext, mailContentRaw: ArcRwSignal<MailsInbox>, config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, syncIdentity: MailSyncIdentity, moduleActions: ModuleActionFn, onlyBox: Option<&str>, addedMails: &mut Vec<(MailSyncIdentity,Vec<ImapMailKey>)>) -> RefreshOutcome
+	{
+		if (!moduleActions.lifecycle_isActive())
+		{
+			return RefreshOutcome::SUCCESS;
+		}
+		let (accountsCount,account,connector) = {
+			let config = config.get_untracked();
+			let Some(account) = config.account_find(&syncIdentity) else {return RefreshOutcome::SUCCESS};
+			let Some(connector) = config.imap_get(account).cloned() else {return RefreshOutcome::SUCCESS};
+			(config.imapAccounts.len() + 1,account,connector)
+		};
 		let request = {
 			let Some(mut mailContent) = mailContentRaw.try_write()
 			else
 			{
-				return;
+				return RefreshOutcome::SUCCESS;
 			};
-			mailContent.syncRequest_get(&config.imap)
+			mailContent.accounts_resize(accountsCount);
+			let Some(mailContent) = mailContent.accounts.get_mut(account) else {return RefreshOutcome::SUCCESS};
+			mailContent.syncRequest_get(&connector,onlyBox)
 		};
 		let Some(request) = request
 		else
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		};
-		let apiResult = API_proxys_imap_sync(config.imap.clone(),request).await;
+		let apiResult = oauthRotations.keep(&connector,API_proxys_imap_sync(connector.clone(),request).await);
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
 		let Some(mailboxes) = toaster_api(&toaster,apiResult,None).await
 		else
@@ -1095,13 +2306,19 @@ impl Mail
 			{
 				toastingErr(&toaster,"MODULE_MAIL_SYNCERROR".to_string()).await;
 			}
-			return;
+			return RefreshOutcome::FAILED;
 		};
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
-		mailContentRaw.update(move |mailContent| mailContent.sync_apply(mailboxes));
+		let mut addedKeys = Vec::new();
+		mailContentRaw.update(|mailContent| mailContent.accountSynced_update(&syncIdentity,|mailContent| addedKeys = mailContent.sync_apply(mailboxes,onlyBox)));
+		if (!addedKeys.is_empty())
+		{
+			addedMails.push((syncIdentity,addedKeys));
+		}
+		return RefreshOutcome::SUCCESS;
 	}
 
 	fn utils_mailOverlay(state: &MailOverlayState) -> AnyView
@@ -1113,10 +2330,7 @@ impl Mail
 			<div class="alttext module_mail_overlay" role="tooltip" style={style}>
 				<span><Translate key="MODULE_MAIL_FROM"/>{" "}{mail.from.clone()}</span><br/>
 				<span><Translate key="MODULE_MAIL_TO"/>{" "}{mail.to.clone()}</span><br/>
-				<span><Translate key="MODULE_MAIL_DATE"/>{" "}{
-					let date = UtcDateTime::from_unix_timestamp(mail.date).unwrap_or(UtcDateTime::now());
-					format!("{:0>2}/{:0>2}/{:0>4} {:0>2}:{:0>2}:{:0>2}",date.day(),date.month() as u8,date.year(),date.hour(),date.minute(),date.second())
-				}</span>
+				<span><Translate key="MODULE_MAIL_DATE"/>{" "}{mailDate_format(mail.date)}</span>
 			</div>
 		}.into_any();
 	}
@@ -1149,12 +2363,20 @@ impl Backable for Mail
 		RefreshTime::MINUTES(30)
 	}
 
-	fn refresh(&self, moduleActions: ModuleActionFn, _moduleId: ModuleID, toaster: ToasterContext) -> Option<BoxFuture> {
+	fn refresh(&self, moduleActions: ModuleActionFn, _moduleId: ModuleID, toaster: ToasterContext) -> Option<RefreshFuture> {
 		let config = self.config.clone();
 		let mailsCache = self.mailsClientCache.clone();
-		let tmp = Self::sync(toaster, mailsCache, config, moduleActions);
+		let idleListening = self._idleListening.clone();
+		let oauthRotations = MailOAuthRotations::new(config.clone(), self._update.clone());
+		let tmp = Self::sync(toaster.clone(), mailsCache.clone(), config.clone(), oauthRotations.clone(), moduleActions.clone(), None);
 		return Some(Box::pin(async move {
-			tmp.await;
+			let outcome = tmp.await;
+			// the listener starts after the first complete synchronization, and again after it gave up
+			if (outcome == RefreshOutcome::SUCCESS)
+			{
+				mailIdle_listen(&idleListening, toaster, mailsCache, config, oauthRotations, moduleActions);
+			}
+			return outcome;
 		}));
 	}
 
@@ -1196,6 +2418,7 @@ impl Backable for Mail
 			mailsClientCache: Default::default(),
 			_update: ArcRwSignal::new(Cache::newFrom(from.timestamp)),
 			_sended: ArcRwSignal::new(Cache::newFrom(from.timestamp)),
+			_idleListening: Default::default(),
 		})
 	}
 
@@ -1231,7 +2454,7 @@ impl Cacheable for Mail
 
 #[component]
 fn MailDraw(config: ArcRwSignal<MailConfig>,
-	            mailsClientCache: ArcRwSignal<MailsContent>,
+	            mailsClientCache: ArcRwSignal<MailsInbox>,
 	            update: ArcRwSignal<Cache>,
 	            editMode: RwSignal<bool>,
 	            moduleActions: ModuleActionFn,
@@ -1242,6 +2465,13 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 		return view!{}.into_any();
 	};
 	let toaster = expect_toaster();
+	let mailSelection = RwSignal::new(HashSet::<MailKey>::new());
+	let mailThreadsExpanded = RwSignal::new(HashSet::<MailKey>::new());
+	let configEditedAccount = RwSignal::new(0_usize);
+	let mailMoveTarget = RwSignal::new(String::new());
+	let mailSearchDraft = RwSignal::new(ImapSearchQuery::default());
+	let mailSearchAdvanced = RwSignal::new(false);
+	let mailRulesHiddenShown = RwSignal::new(false);
 	let mailOverlayHovered = RwSignal::new(None::<MailOverlayState>);
 	let mailOverlayFocused = RwSignal::new(None::<MailOverlayState>);
 	let overlayHoveredResize = mailOverlayHovered;
@@ -1251,29 +2481,43 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 		overlayFocusedResize.set(None);
 	});
 
+	let oauthRotations = MailOAuthRotations::new(config.clone(),update.clone());
 	let mailConfigView = config.clone();
 	let configUpdateView = update.clone();
 	let toasterInner = toaster.clone();
 	let mailsCache = mailsClientCache.clone();
 	let moduleActionsView = moduleActions.clone();
 	let moduleIdView = moduleId.clone();
-	let viewContentFn = move |mailKey:ImapMailKey,mailData:ImapMail| {
+	let dialogManagerCompose = dialogManager.clone();
+	let viewContentFn = move |mailKey:MailKey,mailData:ImapMail| {
 		Mail::mail_view_content(mailConfigView.clone(), configUpdateView.clone(), toasterInner.clone(), dialogManager.clone(), mailKey, mailData, mailsCache.clone(), moduleActionsView.clone(), moduleIdView.clone());
 	};
 
 	let imapConnector = config.clone();
 	let mailsCache = mailsClientCache.clone();
+	let oauthRotationsMark = oauthRotations.clone();
 	let moduleActionsMark = moduleActions.clone();
-	let markViewFn = move |mailKey:ImapMailKey| {
-		Mail::mail_mark_see(imapConnector.get_untracked().imap.clone(), toaster.clone(), mailKey, mailsCache.clone(), moduleActionsMark.clone());
+	let markViewFn = move |mailKey:MailKey| {
+		let Some(connector) = imapConnector.get_untracked().imap_get(mailKey.account).cloned() else {return};
+		Mail::mail_mark_see(connector, oauthRotationsMark.clone(), toaster.clone(), mailKey, mailsCache.clone(), moduleActionsMark.clone());
 	};
 
+	// the mails hidden by a rule are not counted as unread on the dashboard
+	let unreadConfig = config.clone();
+	let unreadCache = mailsClientCache.clone();
+	let unreadActions = moduleActions.clone();
+	let unreadModuleId = moduleId.clone();
+	Effect::new(move || {
+		let unread = unreadConfig.with(|config| unreadCache.with(|inbox| inbox.unread_get(|mail| mailRules_apply(&config.rules,mail).hidden)));
+		unreadActions.unread_set(unreadModuleId.clone(), unread);
+	});
+
 
 	view!{{move || {
 			let editMode = editMode.get();
 			if(editMode)
 			{
-				Mail::draw_config(config.clone(), mailsClientCache.clone(), update.clone(), moduleActions.clone())
+				Mail::draw_config(config.clone(), mailsClientCache.clone(), update.clone(), configEditedAccount, moduleActions.clone())
 			}
 			else
 			{
@@ -1281,24 +2525,87 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 				let mailsCache = mailsClientCache.clone();
 				let mailConfig = config.get();
 				let mailTagIsActive = mailConfig.mail_tag_is_active();
+				let mailTagColumn = mailTagIsActive || mailConfig.isUnified() || mailRules_hasTag(&mailConfig.rules);
+				// a new mail is sent by the first account able to, the dialog offers the other ones
+				let composeAction = mailConfig.senders_get().first().map(|(account,_)| {
+					let account = *account;
+					let composeConfig = config.clone();
+					let oauthRotations = oauthRotations.clone();
+					let dialogManager = dialogManagerCompose.clone();
+					let moduleActions = moduleActions.clone();
+					view!{
+						<button type="button" on:click={move |_| {
+							if (moduleActions.lifecycle_isActive())
+							{
+								mailCompose_open(composeConfig.clone(),oauthRotations.clone(),account,MailDraft::default(),dialogManager.clone(),moduleActions.clone());
+							}
+						}}><i class="iconoir-edit-pencil" aria-hidden="true"/>{" "}<Translate key="MODULE_MAIL_COMPOSE_NEW"/></button>
+					}
+				});
+				let bulkActions = {
+					let config = config.clone();
+					let oauthRotations = oauthRotations.clone();
+					let mailsCache = mailsCache.clone();
+					let moduleActions = moduleActions.clone();
+					move || {
+						let selectedCount = mailSelection.with(|selection| {
+							let mailsCache = mailsCache.get();
+							return selection.iter().filter(|key| mailsCache.mail_get(key).is_some()).count();
+						});
+						if (selectedCount == 0)
+						{
+							return view!{}.into_any();
+						}
+						return Mail::mails_bulkActions(config.clone(),oauthRotations.clone(),mailsCache.clone(),mailSelection,selectedCount,mailMoveTarget,moduleActions.clone());
+					}
+				};
+				let searchForm = Mail::mails_searchForm(config.clone(),oauthRotations.clone(),mailsCache.clone(),mailSearchDraft,mailSearchAdvanced,moduleActions.clone());
+				let searchSummary = {
+					let config = config.clone();
+					let oauthRotations = oauthRotations.clone();
+					let mailsCache = mailsCache.clone();
+					let moduleActions = moduleActions.clone();
+					move || mailsCache.get().searchSummary_get().map(|(total,nextQueries)| Mail::mails_searchSummary(config.clone(),oauthRotations.clone(),mailsCache.clone(),total,nextQueries,moduleActions.clone()))
+				};
 				view!{
 					{draw_title_if_present(mailConfig.title.clone())}
+					<div class="module_mail_toolbar">
+						{bulkActions}
+						{composeAction}
+					</div>
+					{searchForm}
+					{searchSummary}
 					<div class="module_rss_upper" on:scroll={move |_| {
 						mailOverlayHovered.set(None);
 						mailOverlayFocused.set(None);
 					}}>{
 							let markVueCacheInner = mailsCache.clone();
-							let mails = mailsCache.get().mailsData.clone();
-							let mut mailsContent = mails.into_iter().collect::<Vec<_>>();
-							mailsContent.sort_by(|(_,left),(_,right)| left.date.cmp(&right.date).reverse());
-							if (mailsContent.is_empty())
+							let mailsCacheContent = mailsCache.get();
+							// an explicit search shows every result, the rules only hide mails from the synchronized list
+							let rulesHide = !mailsCacheContent.search_isActive() && !mailRulesHiddenShown.get();
+							let isRuleHidden = |mail: &ImapMail| mailRules_apply(&mailConfig.rules,mail).hidden;
+							let hiddenCount = if (mailsCacheContent.search_isActive()) {0} else {mailsCacheContent.mails_get().iter().filter(|(_,mail)| isRuleHidden(mail)).count()};
+							let mailThreads = mailsCacheContent.threads_get(|mail| rulesHide && isRuleHidden(mail));
+							let rulesNotice = (hiddenCount > 0).then(|| {
+								let toggleKey = if (rulesHide) {"MODULE_MAIL_RULES_HIDDEN_SHOW"} else {"MODULE_MAIL_RULES_HIDDEN_HIDE"};
+								view!{
+									<div class="module_mail_rules_hidden" role="status">
+										<span><Translate key="MODULE_MAIL_RULES_HIDDEN" params={HashMap::from([("count".to_string(),hiddenCount.to_string())])}/></span>
+										<button type="button" on:click={move |_| mailRulesHiddenShown.update(|shown| *shown = !*shown)}>
+											<Translate key={toggleKey}/>
+										</button>
+									</div>
+								}
+							});
+							let mailList = if (mailThreads.is_empty())
 							{
-								view!{<div class="module_empty_state"><Translate key="MODULE_MAIL_NO_MAIL"/></div>}.into_any()
+								let emptyKey = if (mailsCacheContent.search_isActive()) {"MODULE_MAIL_SEARCH_NO_RESULT"} else {"MODULE_MAIL_NO_MAIL"};
+								view!{<div class="module_empty_state"><Translate key={emptyKey}/></div>}.into_any()
 							}
 							else
 							{
-							view!{<table class="module_rss_table module_mail_table"><tbody>{mailsContent.into_iter()
-								.map(|(mailKey,mail)|{
+							// replyOf is the thread of an older mail, hidden until the thread is expanded
+							let mailRow = |mailKey: MailKey, mail: ImapMail, threadToggle: Option<AnyView>, replyOf: Option<MailKey>| {
 									let mailKeyView = mailKey.clone();
 									let mailKeyMark = mailKey.clone();
 									let mailKeyConfirm = mailKey.clone();
@@ -1307,32 +2614,93 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 									let markViewFn = markViewFn.clone();
 									let markVueCacheInner = markVueCacheInner.clone();
 									let mailTag = if(mailTagIsActive) {mailConfig.mail_tag(&mail)} else {None};
+									let ruleOutcome = mailRules_apply(&mailConfig.rules,&mail);
+									let ruleStyle = ruleOutcome.style_get();
+									let accountTag = mailConfig.accountTag_get(mailKey.account);
 									let mailSubject = mail.subject.clone().filter(|subject| !subject.is_empty());
 									let mailOverlayMouse = mail.clone();
 									let mailOverlayFocus = mail.clone();
+									let mailKeySelected = mailKey.clone();
+									let mailKeySelect = mailKey.clone();
+									let mut rowClass = String::from("module_mail_row");
+									if (mail.flagged)
+									{
+										rowClass.push_str(" module_mail_row--flagged");
+									}
+									if (replyOf.is_some())
+									{
+										rowClass.push_str(" module_mail_row--thread_reply");
+									}
+									if (ruleOutcome.highlighted)
+									{
+										rowClass.push_str(" module_mail_row--highlighted");
+									}
+									if (ruleOutcome.color.is_some())
+									{
+										rowClass.push_str(" module_mail_row--colored");
+									}
+									// a mail hidden by a rule and shown again keeps a mark
+									if (ruleOutcome.hidden)
+									{
+										rowClass.push_str(" module_mail_row--rule_hidden");
+									}
+									let isHidden = move || replyOf.as_ref().is_some_and(|threadId| !mailThreadsExpanded.with(|expanded| expanded.contains(threadId)));
+									let flagMarker = mail.flagged.then(|| view!{
+										<span class="module_mail_flagged" aria-hidden="true">{"★ "}</span>
+										<span class="visually_hidden"><TranslateText key="MODULE_MAIL_FLAGGED"/>{" - "}</span>
+									});
 									view!{
 										<tr
-											class="module_mail_row"
+											class={rowClass}
+											style={ruleStyle}
+											hidden={isHidden}
 											on:mouseenter={move |event| mailOverlayHovered.set(MailOverlayState::from_event_target(mailOverlayMouse.clone(),event.current_target()))}
 											on:mouseleave={move |_| mailOverlayHovered.set(None)}
 										>
+											<td class="module_mail_select">
+												<label>
+													<input type="checkbox"
+														prop:checked={move || mailSelection.with(|selection| selection.contains(&mailKeySelected))}
+														on:change={move |event| {
+															let isChecked = event_target_checked(&event);
+															mailSelection.update(|selection| {
+																if (isChecked)
+																{
+																	selection.insert(mailKeySelect.clone());
+																}
+																else
+																{
+																	selection.remove(&mailKeySelect);
+																}
+															});
+														}}/>
+													<span class="visually_hidden"><TranslateText key="MODULE_MAIL_SELECT"/></span>
+												</label>
+											</td>
 											<td class="module_mail_date">{distant_time_simpler(mail.date)}</td>
 											{
-												if(mailTagIsActive)
+												if(mailTagColumn)
 												{
 													view!{
-														<td class="module_mail_tag_cell">{
-															if let Some(mailTag) = mailTag
-															{
+														<td class="module_mail_tag_cell">
+															{accountTag.map(|accountTag| {
+																let style = accountTag.style();
+																view!{
+																	<span class="module_mail_tag module_mail_tag--account" style={style}>
+																		<span class="visually_hidden"><TranslateText key="MODULE_MAIL_ACCOUNT"/>{" "}</span>
+																		<span class="module_mail_tag_label">{accountTag.label}</span>
+																	</span>
+																}
+															})}
+															{mailTag.into_iter().chain(ruleOutcome.tags).map(|mailTag| {
 																let style = mailTag.style();
 																view!{
 																	<span class="module_mail_tag" style={style}>
 																		<span class="module_mail_tag_label">{mailTag.label}</span>
 																	</span>
-																}.into_any()
-															}
-															else {view!{}.into_any()}
-														}</td>
+																}
+															}).collect_view()}
+														</td>
 													}.into_any()
 												}
 												else {view!{}.into_any()}
@@ -1340,6 +2708,7 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 							<td
 								class="module_mail_subject"
 							>
+								{threadToggle}
 								<button
 									type="button"
 									class="module_mail_subject_button"
@@ -1350,18 +2719,22 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 									{
 										if let Some(subject) = mailSubject
 										{
-											view!{<span>{subject}</span>}.into_any()
+											view!{<span>{flagMarker}{subject}</span>}.into_any()
 										}
 										else
 										{
-											view!{<span><TranslateText key="MODULE_MAIL_NO_SUBJECT"/></span>}.into_any()
+											view!{<span>{flagMarker}<TranslateText key="MODULE_MAIL_NO_SUBJECT"/></span>}.into_any()
 										}
 									}
 									<span class="visually_hidden">{" - "}<TranslateText key="MODULE_MAIL_OPEN_ACTION"/></span>
 								</button>
 							</td>
 							<td class="module_mail_status">{
-								if(mail.confirmVue)
+								if(mail.seen)
+								{
+									view!{}.into_any()
+								}
+								else if(mail.confirmVue)
 								{
 									view!{
 										<button type="button" class="module_mail_status_button module_mail_status_button--confirm" on:click={move |_| markViewFn.clone()(mailKeyMark.clone())}>
@@ -1376,10 +2749,10 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 										let markVueCacheInnerInner = markVueCacheInner.clone();
 										let mailKeyInner = mailKeyConfirm.clone();
 										markVueCacheInner.update(|mailCache|{
-											mailCache.confirmation_set(&mailKeyInner,true);
+											mailCache.account_update(mailKeyInner.account,|mailCache| mailCache.confirmation_set(&mailKeyInner.imap,true));
 										});
 										Timeout::new(5000, move || {
-											markVueCacheInnerInner.update(|mailCache| mailCache.confirmation_set(&mailKeyInner,false));
+											markVueCacheInnerInner.update(|mailCache| mailCache.account_update(mailKeyInner.account,|mailCache| mailCache.confirmation_set(&mailKeyInner.imap,false)));
 										}).forget();
 									}}>
 										<i class="iconoir-mail-open" aria-hidden="true"/>
@@ -1389,8 +2762,22 @@ fn MailDraw(config: ArcRwSignal<MailConfig>,
 							}</td>
 										</tr>
 									}
+								};
+							view!{<table class="module_rss_table module_mail_table"><tbody>{mailThreads.into_iter()
+								.map(|thread| {
+									let count = thread.mails.len();
+									let unread = thread.unread_get();
+									let threadId = thread.id_get().cloned().filter(|_| count > 1);
+									let mut mails = thread.mails.into_iter();
+									let Some((newestKey,newestMail)) = mails.next() else {return view!{}.into_any()};
+									let Some(threadId) = threadId else {return mailRow(newestKey,newestMail,None,None).into_any()};
+									let threadToggle = Mail::mails_threadToggle(threadId.clone(),count,unread,mailThreadsExpanded);
+									let newestRow = mailRow(newestKey,newestMail,Some(threadToggle),None);
+									let olderRows = mails.map(|(mailKey,mail)| mailRow(mailKey,mail,None,Some(threadId.clone()))).collect_view();
+									view!{{newestRow}{olderRows}}.into_any()
 								}).collect_view()}</tbody></table>}.into_any()
-							}
+							};
+							view!{{rulesNotice}{mailList}}.into_any()
 						}
 				</div>
 				{move || mailOverlayFocused.get()
@@ -1406,8 +2793,12 @@ mod tests
 {
 	use std::collections::HashMap;
 
-	use super::{MailConfig, MailContentFrame, MailOverlayPlacement, MailOverlaySide, MailSenderAddress, MailSyncIdentity, MailTag, MailsContent};
-	use crate::api::proxys::imap_components::{Attributs, BoxName, imap_connector, imap_connector_extra, ImapMailboxSync, ImapMail, ImapMailContentType, ImapMailKey};
+	use leptos::prelude::{ArcRwSignal, GetUntracked};
+
+	use super::{MailConfig, MailContentFrame, MailKey, MailOAuthRotations, MailOverlayPlacement, MailOverlaySide, MailSenderAddress, MailSyncIdentity, MailTag, MailsContent, MailsInbox};
+	use crate::api::proxys::imap_components::{Attributs, BoxName, imap_connector, imap_connector_extra, imap_connector_oauth, ImapMailboxSync, ImapMail, ImapMailContentType, ImapMailKey, ImapProxyResponse, ImapSearchQuery, ImapSearchResult};
+	use crate::api::proxys::imap_error::ImapError;
+	use crate::front::modules::components::Cache;
 
 	fn config_with_suffix(suffix: &str) -> MailConfig
 	{
@@ -1471,6 +2862,7 @@ mod tests
 			uidValidity,
 			removedUids,
 			mails: mailUids.into_iter().map(|uid| ImapMail {uid,..Default::default()}).collect(),
+			threads: Vec::new(),
 		};
 	}
 
@@ -1515,6 +2907,17 @@ mod tests
 		assert!(restoredConfig.remoteImageSender_isAllowed(&senderAddress));
 	}
 
+	#[test]
+	fn mailConfig_readsTheAuthenticationServersWithoutCase()
+	{
+		let mut config = MailConfig::default();
+		assert!(config.authenticationServers_get().is_empty());
+
+		config.authenticationServers = " MX.Example.org., inbox.example.org  ".to_string();
+
+		assert_eq!(config.authenticationServers_get(),vec!["mx.example.org","inbox.example.org"]);
+	}
+
 	#[test]
 	fn mailSenderAddress_normalizesExactAddressWithoutDisplayName()
 	{
@@ -1604,6 +3007,35 @@ mod tests
 		assert!(lowerTag.style().starts_with("--mail-tag-color:hsl("));
 	}
 
+	#[test]
+	fn mail_config_without_accounts_keeps_a_single_inbox()
+	{
+		let mut serializedConfig = serde_json::to_value(MailConfig::default()).unwrap();
+		serializedConfig.as_object_mut().unwrap().remove("imapAccounts");
+
+		let config: MailConfig = serde_json::from_value(serializedConfig).unwrap();
+
+		assert!(!config.isUnified());
+		assert_eq!(config.imaps_get().len(),1);
+		assert!(config.imap_get(1).is_none());
+		assert_eq!(config.accountTag_get(0),None);
+	}
+
+	#[test]
+	fn mail_config_unified_inbox_tags_each_account()
+	{
+		let mut config = config_with_suffix("");
+		config.imap.username = "me@work.example".to_string();
+		config.imapAccounts.push(imap_connector {host: "imap.home.example".to_string(),..Default::default()});
+
+		assert!(config.isUnified());
+		assert_eq!(config.accountTag_get(0),Some(MailTag::new("me@work.example")));
+		assert_eq!(config.accountTag_get(1),Some(MailTag::new("imap.home.example")));
+		assert_eq!(config.accountTag_get(2),None);
+		config.imap_getMut(1).unwrap().username = "me@home.example".to_string();
+		assert_eq!(config.accountTag_get(1).unwrap().label,"me@home.example");
+	}
+
 	#[test]
 	fn mailContentFrameEscapesPlainTextBeforeSrcdocRendering()
 	{
@@ -1631,8 +3063,9 @@ mod tests
 		assert!(blocked.contains("default-src 'none'"));
 		assert!(blocked.contains("form-action 'none'"));
 		assert!(blocked.contains("img-src data: blob:;"));
-		assert!(!blocked.contains("img-src data: blob: http: https:;"));
-		assert!(allowed.contains("img-src data: blob: http: https:;"));
+		assert!(!blocked.contains("/api/mail_image"));
+		assert!(allowed.contains("img-src data: blob: /api/mail_image;"));
+		assert!(!allowed.contains("https:;"));
 	}
 
 	#[test]
@@ -1642,7 +3075,7 @@ mod tests
 		cache.sync_apply(vec![
 			mailboxSync_get("Alerts",42,Vec::new(),vec![7]),
 			mailboxSync_get("News",99,Vec::new(),vec![7]),
-		]);
+		],None);
 
 		assert_eq!(cache.mailsData.len(),2);
 		assert!(cache.mailsData.contains_key(&mailKey_get("Alerts",42,7)));
@@ -1656,11 +3089,11 @@ mod tests
 		cache.sync_apply(vec![
 			mailboxSync_get("Alerts",42,Vec::new(),vec![7]),
 			mailboxSync_get("News",99,Vec::new(),vec![7]),
-		]);
+		],None);
 		cache.sync_apply(vec![
 			mailboxSync_get("Alerts",42,vec![7],Vec::new()),
 			mailboxSync_get("News",99,Vec::new(),Vec::new()),
-		]);
+		],None);
 
 		assert!(!cache.mailsData.contains_key(&mailKey_get("Alerts",42,7)));
 		assert!(cache.mailsData.contains_key(&mailKey_get("News",99,7)));
@@ -1673,26 +3106,56 @@ mod tests
 		cache.sync_apply(vec![
 			mailboxSync_get("Alerts",42,Vec::new(),vec![7]),
 			mailboxSync_get("News",99,Vec::new(),vec![7]),
-		]);
+		],None);
 		cache.sync_apply(vec![
 			mailboxSync_get("Alerts",43,Vec::new(),vec![1]),
 			mailboxSync_get("News",99,Vec::new(),Vec::new()),
-		]);
+		],None);
 
 		assert!(!cache.mailsData.contains_key(&mailKey_get("Alerts",42,7)));
 		assert!(cache.mailsData.contains_key(&mailKey_get("Alerts",43,1)));
 		assert!(cache.mailsData.contains_key(&mailKey_get("News",99,7)));
 	}
 
+	#[test]
+	fn mailCache_flagChangeRollsBackOnlyTheChangedMails()
+	{
+		let mut cache = MailsContent::default();
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7,8])],None);
+		let keys = vec![mailKey_get("Alerts",42,7),mailKey_get("Alerts",42,9)];
+
+		let previous = cache.mailsFlagged_set(&keys,true);
+		assert_eq!(previous,vec![(mailKey_get("Alerts",42,7),false)]);
+		assert!(cache.mailsData[&mailKey_get("Alerts",42,7)].flagged);
+		assert!(!cache.mailsData[&mailKey_get("Alerts",42,8)].flagged);
+
+		cache.mailsFlagged_restore(previous);
+		assert!(!cache.mailsData[&mailKey_get("Alerts",42,7)].flagged);
+	}
+
+	#[test]
+	fn mailCache_moveTargetsMergeListedAndSynchronizedMailboxes()
+	{
+		let mut cache = MailsContent::default();
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),Vec::new())],None);
+		cache.boxs = vec![
+			BoxName {name: "Archive".to_string(),attributes: Attributs::default()},
+			BoxName {name: "[Gmail]".to_string(),attributes: Attributs {is_no_select: true,..Default::default()}},
+			BoxName {name: "Alerts".to_string(),attributes: Attributs::default()},
+		];
+
+		assert_eq!(cache.moveTargets_get(),vec!["Alerts","Archive"]);
+	}
+
 	#[test]
 	fn mailCache_acceptsMailThatBecomesUnreadAgain()
 	{
 		let mut cache = MailsContent::default();
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])]);
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,vec![7],Vec::new())]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])],None);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,vec![7],Vec::new())],None);
 		assert!(cache.mailsData.is_empty());
 
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])],None);
 		assert!(cache.mailsData.contains_key(&mailKey_get("Alerts",42,7)));
 	}
 
@@ -1701,11 +3164,11 @@ mod tests
 	{
 		let mut cache = MailsContent::default();
 		let key = mailKey_get("Alerts",42,7);
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])],None);
 		let removedMail = cache.mailSeen_begin(&key);
 		assert!(!cache.mailsData.contains_key(&key));
 
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])],None);
 		assert!(!cache.mailsData.contains_key(&key));
 		assert!(cache.pendingSeen.contains(&key));
 
@@ -1719,16 +3182,16 @@ mod tests
 	{
 		let mut cache = MailsContent::default();
 		let key = mailKey_get("Alerts",42,7);
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])],None);
 		cache.mailSeen_begin(&key);
 		cache.mailSeen_commit(key.clone());
 
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),Vec::new())]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),Vec::new())],None);
 		assert!(!cache.pendingSeen.contains(&key));
 		assert!(!cache.confirmedSeen.contains(&key));
 		assert!(!cache.mailsData.contains_key(&key));
 
-		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])]);
+		cache.sync_apply(vec![mailboxSync_get("Alerts",42,Vec::new(),vec![7])],None);
 		assert!(cache.mailsData.contains_key(&key));
 	}
 
@@ -1746,7 +3209,7 @@ mod tests
 		cache.mailsData.insert(mailKey_get("Alerts",42,7),ImapMail {uid: 7,..Default::default()});
 		cache.mailsData.insert(mailKey_get("News",99,7),ImapMail {uid: 7,..Default::default()});
 
-		let request = cache.syncRequest_get(&connector).unwrap();
+		let request = cache.syncRequest_get(&connector,None).unwrap();
 		let mailboxes = request.mailboxes.unwrap();
 		assert_eq!(mailboxes.len(),2);
 		assert_eq!(mailboxes[0].boxName,"Alerts");
@@ -1755,13 +3218,93 @@ mod tests
 		assert_eq!(mailboxes[1].knownUids,vec![7]);
 	}
 
+	#[test]
+	fn mailCache_idleChangeSyncsOnlyItsMailboxAndKeepsTheOthers()
+	{
+		let mut connector = imap_connector::default();
+		connector.extra = Some(imap_connector_extra::default());
+		let mut cache = MailsContent::default();
+		cache.syncIdentity = Some(MailSyncIdentity::new(&connector));
+		cache.sync_apply(vec![
+			mailboxSync_get("Alerts",42,Vec::new(),vec![7]),
+			mailboxSync_get("INBOX",5,Vec::new(),vec![3]),
+		],None);
+		assert_eq!(cache.idleBox_get(),Some("INBOX".to_string()));
+
+		let request = cache.syncRequest_get(&connector,Some("INBOX")).unwrap();
+		let mailboxes = request.mailboxes.unwrap();
+		assert_eq!(mailboxes.len(),1);
+		assert_eq!(mailboxes[0].boxName,"INBOX");
+		assert_eq!(mailboxes[0].knownUids,vec![3]);
+		assert!(cache.syncRequest_get(&connector,Some("Unknown")).is_none());
+
+		cache.sync_apply(vec![mailboxSync_get("INBOX",5,vec![3],vec![4])],Some("INBOX"));
+		assert!(cache.mailsData.contains_key(&mailKey_get("Alerts",42,7)));
+		assert!(cache.mailsData.contains_key(&mailKey_get("INBOX",5,4)));
+		assert!(!cache.mailsData.contains_key(&mailKey_get("INBOX",5,3)));
+	}
+
+	#[test]
+	fn mailCache_searchReplacesTheListAndSharesTheMailChanges()
+	{
+		let mut cache = MailsContent::default();
+		cache.sync_apply(vec![mailboxSync_get("INBOX",5,Vec::new(),vec![3])],None);
+		let searchResult = |uids: Vec<u32>, next: Option<ImapMailKey>| ImapSearchResult {
+			mails: uids.into_iter().map(|uid| (mailKey_get("INBOX",5,uid),ImapMail {uid,..Default::default()})).collect(),
+			total: 3,
+			next,
+		};
+		let query = ImapSearchQuery {subject: "report".to_string(), ..Default::default()};
+
+		cache.search_set(query.clone(),searchResult(vec![3,1],Some(mailKey_get("INBOX",5,1))));
+		cache.search_set(query.nextPage_get(mailKey_get("INBOX",5,1)),searchResult(vec![2],None));
+		assert_eq!(cache.mails_get().iter().map(|(key,_)| key.uid).collect::<Vec<_>>(),vec![3,1,2]);
+		assert_eq!(cache.search.as_ref().unwrap().query.after,Some(mailKey_get("INBOX",5,1)));
+		assert_eq!(cache.search.as_ref().unwrap().next,None);
+
+		cache.mailsFlagged_set(&[mailKey_get("INBOX",5,3)],true);
+		assert!(cache.mailsData[&mailKey_get("INBOX",5,3)].flagged);
+		assert!(cache.mail_get(&mailKey_get("INBOX",5,3)).unwrap().flagged);
+
+		cache.searchMails_hide(&[mailKey_get("INBOX",5,1)],true);
+		assert!(cache.mail_get(&mailKey_get("INBOX",5,1)).is_none());
+		assert_eq!(cache.mails_get().len(),2);
+
+		cache.search_set(query,searchResult(vec![1],None));
+		assert_eq!(cache.mails_get().iter().map(|(key,_)| key.uid).collect::<Vec<_>>(),vec![1]);
+
+		cache.sync_reset(MailSyncIdentity::new(&imap_connector::default()));
+		assert!(cache.search.is_none());
+	}
+
+	#[test]
+	fn mailCache_markedUnreadMailIsListedAgainByTheNextSync()
+	{
+		let mut connector = imap_connector::default();
+		connector.extra = Some(imap_connector_extra::default());
+		let mut cache = MailsContent::default();
+		cache.syncIdentity = Some(MailSyncIdentity::new(&connector));
+		cache.mailboxes = Some([("INBOX".to_string(),Some(5))].into_iter().collect());
+		cache.sync_apply(vec![mailboxSync_get("INBOX",5,Vec::new(),vec![3])],None);
+		let key = mailKey_get("INBOX",5,3);
+
+		cache.mailSeen_begin(&key);
+		cache.mailSeen_commit(key.clone());
+		assert_eq!(cache.syncRequest_get(&connector,None).unwrap().mailboxes.unwrap()[0].knownUids,vec![3]);
+
+		cache.mailsUnseen_apply(&[key.clone()]);
+		assert!(cache.syncRequest_get(&connector,None).unwrap().mailboxes.unwrap()[0].knownUids.is_empty());
+		cache.sync_apply(vec![mailboxSync_get("INBOX",5,Vec::new(),vec![3])],None);
+		assert!(cache.mailsData.contains_key(&key));
+	}
+
 	#[test]
 	fn mailCache_doesNotSyncBeforeExplicitSelection()
 	{
 		let connector = imap_connector::default();
 		let mut cache = MailsContent::default();
 
-		assert!(cache.syncRequest_get(&connector).is_none());
+		assert!(cache.syncRequest_get(&connector,None).is_none());
 		assert!(cache.mailboxes.is_none());
 	}
 
@@ -1775,7 +3318,7 @@ mod tests
 		});
 		let mut cache = MailsContent::default();
 
-		assert!(cache.syncRequest_get(&connector).is_none());
+		assert!(cache.syncRequest_get(&connector,None).is_none());
 		assert_eq!(cache.mailboxes,Some(HashMap::new()));
 	}
 
@@ -1789,7 +3332,7 @@ mod tests
 		});
 		let mut cache = MailsContent::default();
 
-		let request = cache.syncRequest_get(&connector).unwrap();
+		let request = cache.syncRequest_get(&connector,None).unwrap();
 		let mailboxes = request.mailboxes.unwrap();
 		assert_eq!(mailboxes.len(),2);
 		assert_eq!(mailboxes[0].boxName,"Alerts");
@@ -1817,4 +3360,191 @@ mod tests
 		assert_eq!(cache.boxs.len(),3);
 		assert_eq!(cache.mailboxes,Some([("Alerts".to_string(),None)].into_iter().collect()));
 	}
+
+	fn connector_withInbox(host: &str) -> imap_connector
+	{
+		let mut connector = imap_connector {host: host.to_string(),..Default::default()};
+		connector.boxSelection_set("INBOX".to_string(),true);
+		return connector;
+	}
+
+	fn inboxOrder_get(inbox: &MailsInbox) -> Vec<(usize,i64)>
+	{
+		return inbox.mails_get().into_iter().map(|(key,mail)| (key.account,mail.date)).collect();
+	}
+
+	#[test]
+	fn mailInbox_keepsTheSynchronizationStateOfEachAccount()
+	{
+		let work = connector_withInbox("imap.work.example");
+		let home = connector_withInbox("imap.home.example");
+		let mut inbox = MailsInbox::default();
+		inbox.accounts_resize(2);
+		for (account,connector) in [&work,&home].into_iter().enumerate()
+		{
+			inbox.account_update(account,|content| {
+				assert!(content.syncRequest_get(connector,None).is_some());
+				assert_eq!(content.sync_apply(vec![mailboxSync_get("INBOX",42,Vec::new(),vec![7])],None),vec![mailKey_get("INBOX",42,7)]);
+				// a mail already cached is not added again
+				assert!(content.sync_apply(vec![mailboxSync_get("INBOX",42,Vec::new(),vec![7])],None).is_empty());
+			});
+		}
+		assert_eq!(inbox.mails_get().len(),2);
+
+		// a new uid validity of one server keeps the same uid of the other account
+		inbox.account_update(1,|content| {content.sync_apply(vec![mailboxSync_get("INBOX",43,Vec::new(),Vec::new())],None);});
+		assert!(inbox.mail_get(&MailKey {account: 0, imap: mailKey_get("INBOX",42,7)}).is_some());
+		assert!(inbox.mail_get(&MailKey {account: 1, imap: mailKey_get("INBOX",42,7)}).is_none());
+
+		// a changed account only resets its own cache
+		inbox.account_update(1,|content| {content.sync_apply(vec![mailboxSync_get("INBOX",43,Vec::new(),vec![8])],None);});
+		let mut moved = home.clone();
+		moved.username = "other".to_string();
+		inbox.account_update(1,|content| {
+			content.syncRequest_get(&moved,None);
+		});
+		assert_eq!(inbox.account_get(0).unwrap().syncIdentity,Some(MailSyncIdentity::new(&work)));
+		assert_eq!(inbox.account_get(1).unwrap().syncIdentity,Some(MailSyncIdentity::new(&moved)));
+		assert_eq!(inbox.mails_get().into_iter().map(|(key,_)| key).collect::<Vec<_>>(),vec![MailKey {account: 0, imap: mailKey_get("INBOX",42,7)}]);
+	}
+
+	#[test]
+	fn mailInbox_appliesASynchronizationToTheMovedAccount()
+	{
+		let work = connector_withInbox("imap.work.example");
+		let home = connector_withInbox("imap.home.example");
+		let mut config = MailConfig {imap: work.clone(),..Default::default()};
+		config.account_add();
+		config.account_add();
+		*config.imap_getMut(2).unwrap() = home.clone();
+		let mut inbox = MailsInbox::default();
+		inbox.accounts_resize(3);
+		inbox.account_update(2,|content| assert!(content.syncRequest_get(&home,None).is_some()));
+
+		// the second account is removed while the third one waits for its answer
+		config.account_remove(1);
+		inbox.account_remove(1);
+		let homeIdentity = MailSyncIdentity::new(&home);
+		assert_eq!(config.account_find(&homeIdentity),Some(1));
+		inbox.accountSynced_update(&homeIdentity,|content| {content.sync_apply(vec![mailboxSync_get("INBOX",42,Vec::new(),vec![7])],None);});
+		assert_eq!(inbox.mails_get().into_iter().map(|(key,_)| key).collect::<Vec<_>>(),vec![MailKey {account: 1, imap: mailKey_get("INBOX",42,7)}]);
+
+		// an edited account drops the answer asked with its previous identity
+		config.imap_getMut(1).unwrap().username = "other".to_string();
+		inbox.account_update(1,|content| {
+			content.syncRequest_get(config.imap_get(1).unwrap(),None);
+		});
+		assert_eq!(config.account_find(&homeIdentity),None);
+		inbox.accountSynced_update(&homeIdentity,|content| {content.sync_apply(vec![mailboxSync_get("INBOX",42,Vec::new(),vec![8])],None);});
+		assert!(inbox.mails_get().is_empty());
+	}
+
+	#[test]
+	fn mailConfig_sendsFromTheAccountOfTheMail()
+	{
+		let mut config = MailConfig {imap: imap_connector {username: "me@work.example".to_string(),..Default::default()},..Default::default()};
+		config.smtp.host = "smtp.work.example".to_string();
+		assert_eq!(config.account_add(),1);
+		config.imap_getMut(1).unwrap().username = "me@home.example".to_string();
+		assert_eq!(config.ownAddress_get(1),MailSenderAddress::from_header("me@home.example"));
+		assert_eq!(config.senders_get(),vec![(0,"me@work.example".to_string())]);
+
+		config.smtp_getMut(1).unwrap().host = "smtp.home.example".to_string();
+		config.smtp_getMut(1).unwrap().sender = "Me <me@home.example>".to_string();
+		assert_eq!(config.senders_get().iter().map(|(account,_)| *account).collect::<Vec<_>>(),vec![0,1]);
+		assert_eq!(config.smtp_get(1).map(|smtp| smtp.host.as_str()),Some("smtp.home.example"));
+
+		// a configuration saved before the sending servers of the accounts keeps them aligned
+		config.smtpAccounts.clear();
+		assert_eq!(config.account_add(),2);
+		config.smtp_getMut(2).unwrap().host = "smtp.other.example".to_string();
+		config.account_remove(1);
+		assert_eq!(config.smtp_get(1).map(|smtp| smtp.host.as_str()),Some("smtp.other.example"));
+		assert!(config.smtp_getMut(2).is_none());
+	}
+
+	#[test]
+	fn mailInbox_mergesTheAccountsNewestFirst()
+	{
+		let mail = |uid: u32, date: i64| (mailKey_get("INBOX",1,uid),ImapMail {uid,date,..Default::default()});
+		let mut inbox = MailsInbox::default();
+		inbox.accounts_resize(2);
+		inbox.account_update(0,|content| content.mailsData.extend([mail(1,100),mail(2,300)]));
+		inbox.account_update(1,|content| content.mailsData.extend([mail(1,200)]));
+
+		assert_eq!(inboxOrder_get(&inbox),vec![(0,300),(1,200),(0,100)]);
+		assert_eq!(inbox.threads_get(|_| false).len(),3);
+		assert_eq!(inbox.unread_get(|_| false),3);
+		assert_eq!(inbox.unread_get(|mail| mail.date == 200),2);
+		inbox.account_update(0,|content| content.mail_update(&mailKey_get("INBOX",1,1),|mail| mail.seen = true));
+		assert_eq!(inbox.unread_get(|_| false),2);
+
+		// the next account keeps its cache under its new position
+		inbox.account_remove(0);
+		assert_eq!(inboxOrder_get(&inbox),vec![(0,200)]);
+		inbox.accounts_resize(0);
+		assert!(inbox.mails_get().is_empty());
+	}
+
+	#[test]
+	fn mailInbox_searchListsOnlyTheResultsOfEachAccount()
+	{
+		let query = ImapSearchQuery {subject: "report".to_string(), ..Default::default()};
+		let searchResult = |uids: Vec<u32>, total: usize| ImapSearchResult {
+			next: (uids.len() < total).then(|| mailKey_get("INBOX",5,*uids.last().unwrap())),
+			mails: uids.into_iter().map(|uid| (mailKey_get("INBOX",5,uid),ImapMail {uid,date: uid as i64,..Default::default()})).collect(),
+			total,
+		};
+		let mut inbox = MailsInbox::default();
+		inbox.accounts_resize(3);
+		inbox.account_update(2,|content| {content.sync_apply(vec![mailboxSync_get("INBOX",5,Vec::new(),vec![9])],None);});
+		assert_eq!(inbox.searchSummary_get().map(|(total,_)| total),None);
+
+		inbox.account_update(0,|content| content.search_set(query.clone(),searchResult(vec![1],3)));
+		inbox.account_update(1,|content| content.search_set(query.clone(),searchResult(vec![2],1)));
+		assert!(inbox.search_isActive());
+		assert_eq!(inboxOrder_get(&inbox),vec![(1,2),(0,1)]);
+		let (total,nextQueries) = inbox.searchSummary_get().unwrap();
+		assert_eq!(total,4);
+		assert_eq!(nextQueries,vec![(0,query.nextPage_get(mailKey_get("INBOX",5,1)))]);
+
+		inbox.search_clear();
+		assert!(!inbox.search_isActive());
+		assert_eq!(inboxOrder_get(&inbox),vec![(2,0)]);
+	}
+
+	#[test]
+	fn mailInbox_moveTargetsAreSharedByTheSelectedAccounts()
+	{
+		let mut inbox = MailsInbox::default();
+		inbox.accounts_resize(2);
+		inbox.account_update(0,|content| content.boxs = vec![boxName_get("Archive",Attributs::default()),boxName_get("Work",Attributs::default())]);
+		inbox.account_update(1,|content| content.boxs = vec![boxName_get("Archive",Attributs::default()),boxName_get("Home",Attributs::default())]);
+
+		assert_eq!(inbox.moveTargets_get(&[0]),vec!["Archive","Work"]);
+		assert_eq!(inbox.moveTargets_get(&[0,1]),vec!["Archive"]);
+		assert!(inbox.moveTargets_get(&[]).is_empty());
+	}
+
+	#[test]
+	fn mailOAuthRotations_replacesTheTokenOfEveryAccountSignedInWithIt()
+	{
+		let oauth = |refreshToken: &str| imap_connector_oauth {provider: "provider".to_string(),refreshToken: refreshToken.to_string()};
+		let signedIn = imap_connector {oauth: Some(oauth("previous")),..connector_withInbox("imap.work.example")};
+		let config = ArcRwSignal::new(MailConfig {
+			imap: signedIn.clone(),
+			imapAccounts: vec![
+				imap_connector {oauth: Some(oauth("other")),..connector_withInbox("imap.home.example")},
+				imap_connector {oauth: Some(oauth("previous")),..connector_withInbox("imap.shared.example")},
+			],
+			..Default::default()
+		});
+		let rotations = MailOAuthRotations::new(config.clone(),ArcRwSignal::new(Cache::default()));
+
+		let answer = rotations.keep(&signedIn,Ok(ImapProxyResponse {result: Err::<(),_>(ImapError::SERVER_ERROR),oauthRotated: Some(oauth("rotated"))}));
+
+		assert_eq!(answer,Err(ImapError::SERVER_ERROR));
+		let refreshTokens = config.get_untracked().imaps_get().into_iter().map(|imap| imap.oauth.unwrap().refreshToken).collect::<Vec<_>>();
+		assert_eq!(refreshTokens,vec!["rotated","other","rotated"]);
+	}
 }
@@ -0,0 +1,450 @@
+use leptos::prelude::{on_cleanup, ArcRwSignal, AriaAttributes, ClassAttribute, CollectView, ElementChild, GlobalAttributes, IntoAny, OnAttribute, Set};
+use leptos::{component, view, IntoView};
+use crate::api::proxys::imap_components::Attachment;
+use crate::front::utils::contentDownloader::{blobUrl_create, blobUrl_revoke};
+use crate::front::utils::translate::TranslateText;
+
+struct AttachmentPreviewLimits;
+
+impl AttachmentPreviewLimits
+{
+	// longer text is cut, the whole file stays available through the download
+	const TEXT_MAXIMUM_BYTES: usize = 256 * 1024;
+	// a NUL byte in the start of the data marks a binary file
+	const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+	const IMAGE_MIMES: [&'static str; 6] = ["image/png", "image/jpeg", "image/gif", "image/webp", "image/bmp", "image/avif"];
+	const TEXT_MIMES: [&'static str; 6] = ["application/json", "application/xml", "application/javascript", "application/x-sh", "application/toml", "application/sql"];
+}
+
+/// how an attachment is shown in the mail overlay, the other attachments can only be downloaded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum AttachmentPreviewKind
+{
+	IMAGE,
+	PDF,
+	TEXT(Option<SyntaxLanguage>),
+}
+
+impl AttachmentPreviewKind
+{
+	pub(super) fn fromAttachment(attachment: &Attachment) -> Option<Self>
+	{
+		let mime = attachment.content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+		let extension = attachment.filename.as_deref()
+			.and_then(|filename| filename.rsplit_once('.'))
+			.map(|(_,extension)| extension.to_ascii_lowercase());
+		// mail clients often send any file as application/octet-stream, the extension tells the type then
+		let isUntyped = mime.is_empty() || mime == "application/octet-stream";
+
+		if (AttachmentPreviewLimits::IMAGE_MIMES.contains(&mime.as_str()))
+		{
+			return Some(Self::IMAGE);
+		}
+		if (mime == "application/pdf" || (isUntyped && extension.as_deref() == Some("pdf")))
+		{
+			return Some(Self::PDF);
+		}
+		let language = extension.as_deref().and_then(SyntaxLanguage::fromExtension)
+			.or_else(|| SyntaxLanguage::fromMime(&mime));
+		let isText = (mime.starts_with("text/") && mime != "text/calendar")
+			|| AttachmentPreviewLimits::TEXT_MIMES.contains(&mime.as_str())
+			|| (isUntyped && language.is_some());
+		if (!isText || Self::data_isBinary(&attachment.data))
+		{
+			return None;
+		}
+		return Some(Self::TEXT(language));
+	}
+
+	fn data_isBinary(data: &[u8]) -> bool
+	{
+		return data.iter().take(AttachmentPreviewLimits::BINARY_SNIFF_BYTES).any(|byte| *byte == 0);
+	}
+}
+
+/// preview of an attachment inside the mail overlay.
+/// Images and PDFs are loaded from blob urls allowed by the page CSP. A PDF opens in the viewer of the browser,
+/// in a new tab: Chromium refuses to show it in a sandboxed frame and an unsandboxed one would share the page origin
+#[component]
+pub(super) fn MailAttachmentPreview(attachment: Attachment, kind: AttachmentPreviewKind, previewed: ArcRwSignal<Option<usize>>) -> impl IntoView
+{
+	let filename = attachment.filename.clone().unwrap_or_default();
+	let content = match kind
+	{
+		AttachmentPreviewKind::IMAGE | AttachmentPreviewKind::PDF => {
+			let contentType = if (kind == AttachmentPreviewKind::PDF) {"application/pdf"} else {attachment.content_type.as_str()};
+			match blobUrl_create(&attachment.data, contentType)
+			{
+				None => view!{<p class="module_mail_attachment_preview_notice" role="alert"><TranslateText key="MODULE_MAIL_ATTACHMENT_PREVIEW_FAILED"/></p>}.into_any(),
+				Some(url) => {
+					let revokedUrl = url.clone();
+					on_cleanup(move || blobUrl_revoke(&revokedUrl));
+					if (kind == AttachmentPreviewKind::IMAGE)
+					{
+						view!{<img class="module_mail_attachment_image" src=url alt=filename.clone()/>}.into_any()
+					}
+					else
+					{
+						view!{
+							<a class="module_mail_attachment_pdf" href=url target="_blank" rel="noopener noreferrer">
+								<i class="iconoir-open-new-window" aria-hidden="true"></i>{" "}<TranslateText key="MODULE_MAIL_ATTACHMENT_PREVIEW_PDF_OPEN"/>
+							</a>
+							<p class="module_mail_attachment_preview_notice"><TranslateText key="MODULE_MAIL_ATTACHMENT_PREVIEW_PDF_HELP"/></p>
+						}.into_any()
+					}
+				}
+			}
+		},
+		AttachmentPreviewKind::TEXT(language) => {
+			let (text,isTruncated) = attachmentText_get(&attachment.data);
+			let tokens = match language
+			{
+				Some(language) => syntax_tokenize(&text, language),
+				None => vec![(SyntaxToken::PLAIN, text.as_str())],
+			};
+			let code = tokens.into_iter()
+				.map(|(token,part)| match token.class_get()
+				{
+					Some(class) => view!{<span class=class>{part.to_string()}</span>}.into_any(),
+					None => view!{{part.to_string()}}.into_any(),
+				})
+				.collect_view();
+			view!{
+				<pre class="module_mail_attachment_text"><code>{code}</code></pre>
+				{isTruncated.then(|| view!{<p class="module_mail_attachment_preview_notice"><TranslateText key="MODULE_MAIL_ATTACHMENT_PREVIEW_TRUNCATED"/></p>})}
+			}.into_any()
+		},
+	};
+
+	return view!{
+		<section class="module_mail_attachment_preview" aria-label=filename.clone()>
+			<div class="module_mail_attachment_preview_header">
+				<span class="module_mail_attachment_preview_name">{filename.clone()}</span>
+				<button type="button" class="module_mail_attachment_preview_close" on:click=move |_| previewed.set(None)>
+					<i class="iconoir-xmark" aria-hidden="true"></i>
+					<span class="visually_hidden"><TranslateText key="MODULE_MAIL_ATTACHMENT_PREVIEW_CLOSE"/></span>
+				</button>
+			</div>
+			{content}
+		</section>
+	};
+}
+
+/// text of the attachment up to the preview limit, and whether it was cut
+pub(super) fn attachmentText_get(data: &[u8]) -> (String, bool)
+{
+	let isTruncated = data.len() > AttachmentPreviewLimits::TEXT_MAXIMUM_BYTES;
+	let mut end = data.len().min(AttachmentPreviewLimits::TEXT_MAXIMUM_BYTES);
+	// do not cut a character in two, at most 3 bytes are dropped
+	let endMinimum = end.saturating_sub(3);
+	while (end > endMinimum && end < data.len() && (data[end] & 0b1100_0000) == 0b1000_0000)
+	{
+		end -= 1;
+	}
+	return (String::from_utf8_lossy(&data[..end]).into_owned(), isTruncated);
+}
+
+/// languages coloured by the text preview, with a deliberately small tokenizer
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum SyntaxLanguage
+{
+	RUST,
+	CLIKE,
+	PYTHON,
+	SHELL,
+	JSON,
+	MARKUP,
+	CSS,
+	SQL,
+	CONFIG,
+}
+
+struct SyntaxRules
+{
+	lineComments: &'static [&'static str],
+	blockComment: Option<(&'static str, &'static str)>,
+	quotes: &'static [char],
+	keywords: &'static [&'static str],
+}
+
+impl SyntaxLanguage
+{
+	fn fromExtension(extension: &str) -> Option<Self>
+	{
+		return match extension
+		{
+			"rs" => Some(Self::RUST),
+			"c" | "h" | "cpp" | "hpp" | "cc" | "cs" | "java" | "kt" | "go" | "swift" | "js" | "mjs" | "ts" | "tsx" | "jsx" | "php" => Some(Self::CLIKE),
+			"py" => Some(Self::PYTHON),
+			"sh" | "bash" | "zsh" => Some(Self::SHELL),
+			"json" => Some(Self::JSON),
+			"html" | "htm" | "xml" | "svg" => Some(Self::MARKUP),
+			"css" | "scss" => Some(Self::CSS),
+			"sql" => Some(Self::SQL),
+			"toml" | "yaml" | "yml" | "ini" | "conf" | "cfg" | "properties" => Some(Self::CONFIG),
+			_ => None,
+		};
+	}
+
+	fn fromMime(mime: &str) -> Option<Self>
+	{
+		return match mime
+		{
+			"application/json" => Some(Self::JSON),
+			"application/xml" | "text/xml" | "text/html" => Some(Self::MARKUP),
+			"application/javascript" | "text/javascript" => Some(Self::CLIKE),
+			"application/x-sh" => Some(Self::SHELL),
+			"text/css" => Some(Self::CSS),
+			"application/sql" => Some(Self::SQL),
+			"application/toml" => Some(Self::CONFIG),
+			_ => None,
+		};
+	}
+
+	fn rules_get(&self) -> SyntaxRules
+	{
+		return match self
+		{
+			Self::RUST => SyntaxRules {
+				lineComments: &["//"],
+				blockComment: Some(("/*", "*/")),
+				quotes: &['"'],
+				keywords: &["as", "async", "await", "break", "const", "continue", "crate", "else", "enum", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while"],
+			},
+			Self::CLIKE => SyntaxRules {
+				lineComments: &["//"],
+				blockComment: Some(("/*", "*/")),
+				quotes: &['"', '\'', '`'],
+				keywords: &["break", "case", "class", "const", "continue", "default", "do", "else", "enum", "export", "extends", "false", "final", "for", "func", "function", "if", "import", "interface", "let", "new", "null", "package", "private", "protected", "public", "return", "static", "struct", "switch", "this", "throw", "true", "try", "catch", "typedef", "var", "void", "while"],
+			},
+			Self::PYTHON => SyntaxRules {
+				lineComments: &["#"],
+				blockComment: None,
+				quotes: &['"', '\''],
+				keywords: &["and", "as", "async", "await", "break", "class", "continue", "def", "elif", "else", "except", "False", "finally", "for", "from", "if", "import", "in", "is", "lambda", "None", "not", "or", "pass", "raise", "return", "True", "try", "while", "with", "yield"],
+			},
+			Self::SHELL => SyntaxRules {
+				lineComments: &["#"],
+				blockComment: None,
+				quotes: &['"', '\''],
+				keywords: &["case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local", "return", "then", "while"],
+			},
+			Self::JSON => SyntaxRules {
+				lineComments: &[],
+				blockComment: None,
+				quotes: &['"'],
+				keywords: &["false", "null", "true"],
+			},
+			Self::MARKUP => SyntaxRules {
+				lineComments: &[],
+				blockComment: Some(("<!--", "-->")),
+				quotes: &['"', '\''],
+				keywords: &[],
+			},
+			Self::CSS => SyntaxRules {
+				lineComments: &[],
+				blockComment: Some(("/*", "*/")),
+				quotes: &['"', '\''],
+				keywords: &["important", "inherit", "initial", "none"],
+			},
+			Self::SQL => SyntaxRules {
+				lineComments: &["--"],
+				blockComment: Some(("/*", "*/")),
+				quotes: &['\''],
+				keywords: &["AND", "AS", "BY", "CREATE", "DELETE", "FROM", "GROUP", "INSERT", "INTO", "JOIN", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "SELECT", "SET", "TABLE", "UPDATE", "VALUES", "WHERE"],
+			},
+			Self::CONFIG => SyntaxRules {
+				lineComments: &["#", ";"],
+				blockComment: None,
+				quotes: &['"', '\''],
+				keywords: &["false", "no", "true", "yes"],
+			},
+		};
+	}
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum SyntaxToken
+{
+	PLAIN,
+	COMMENT,
+	STRING,
+	NUMBER,
+	KEYWORD,
+}
+
+impl SyntaxToken
+{
+	fn class_get(&self) -> Option<&'static str>
+	{
+		return match self
+		{
+			Self::PLAIN => None,
+			Self::COMMENT => Some("module_mail_syntax_comment"),
+			Self::STRING => Some("module_mail_syntax_string"),
+			Self::NUMBER => Some("module_mail_syntax_number"),
+			Self::KEYWORD => Some("module_mail_syntax_keyword"),
+		};
+	}
+}
+
+/// split the text in coloured parts, the plain text between them is kept as one part
+fn syntax_tokenize<'a>(text: &'a str, language: SyntaxLanguage) -> Vec<(SyntaxToken, &'a str)>
+{
+	let rules = language.rules_get();
+	let mut tokens = Vec::new();
+	let mut plainStart = 0;
+	let mut position = 0;
+	let push = |tokens: &mut Vec<(SyntaxToken, &'a str)>, plainStart: &mut usize, start: usize, end: usize, token: SyntaxToken| {
+		if (*plainStart < start)
+		{
+			tokens.push((SyntaxToken::PLAIN, &text[*plainStart..start]));
+		}
+		tokens.push((token, &text[start..end]));
+		*plainStart = end;
+	};
+
+	while let Some(character) = text[position..].chars().next()
+	{
+		let rest = &text[position..];
+		let previousIsWord = text[..position].chars().next_back().is_some_and(|previous| previous.is_alphanumeric() || previous == '_');
+		let end = if (rules.lineComments.iter().any(|prefix| rest.starts_with(prefix)))
+		{
+			let end = rest.find('\n').map(|end| position + end).unwrap_or(text.len());
+			push(&mut tokens, &mut plainStart, position, end, SyntaxToken::COMMENT);
+			end
+		}
+		else if let Some((open,close)) = rules.blockComment && rest.starts_with(open)
+		{
+			let end = rest[open.len()..].find(close).map(|end| position + open.len() + end + close.len()).unwrap_or(text.len());
+			push(&mut tokens, &mut plainStart, position, end, SyntaxToken::COMMENT);
+			end
+		}
+		else if (rules.quotes.contains(&character))
+		{
+			let end = syntax_stringEnd(rest, character).map(|end| position + end).unwrap_or(text.len());
+			push(&mut tokens, &mut plainStart, position, end, SyntaxToken::STRING);
+			end
+		}
+		else if (character.is_ascii_digit() && !previousIsWord)
+		{
+			let end = rest.find(|next: char| !(next.is_ascii_alphanumeric() || next == '.' || next == '_')).map(|end| position + end).unwrap_or(text.len());
+			push(&mut tokens, &mut plainStart, position, end, SyntaxToken::NUMBER);
+			end
+		}
+		else if ((character.is_alphabetic() || character == '_') && !previousIsWord)
+		{
+			let end = rest.find(|next: char| !(next.is_alphanumeric() || next == '_')).map(|end| position + end).unwrap_or(text.len());
+			let word = &text[position..end];
+			let isKeyword = if (language == SyntaxLanguage::SQL)
+			{
+				rules.keywords.iter().any(|keyword| keyword.eq_ignore_ascii_case(word))
+			}
+			else
+			{
+				rules.keywords.contains(&word)
+			};
+			if (isKeyword)
+			{
+				push(&mut tokens, &mut plainStart, position, end, SyntaxToken::KEYWORD);
+			}
+			end
+		}
+		else
+		{
+			position + character.len_utf8()
+		};
+		position = end;
+	}
+	if (plainStart < text.len())
+	{
+		tokens.push((SyntaxToken::PLAIN, &text[plainStart..]));
+	}
+	return tokens;
+}
+
+/// end of the string starting the text (after its closing quote), a string stops at the end of its line
+fn syntax_stringEnd(text: &str, quote: char) -> Option<usize>
+{
+	let mut isEscaped = false;
+	for (index,character) in text.char_indices().skip(1)
+	{
+		if (character == '\n')
+		{
+			return Some(index);
+		}
+		if (isEscaped)
+		{
+			isEscaped = false;
+		}
+		else if (character == '\\')
+		{
+			isEscaped = true;
+		}
+		else if (character == quote)
+		{
+			return Some(index + character.len_utf8());
+		}
+	}
+	return None;
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::proxys::imap_components::Attachment;
+	use super::{attachmentText_get, syntax_tokenize, AttachmentPreviewKind, AttachmentPreviewLimits, SyntaxLanguage, SyntaxToken};
+
+	fn attachment_get(filename: &str, contentType: &str, data: &[u8]) -> Attachment
+	{
+		return Attachment {
+			filename: Some(filename.to_string()),
+			content_type: contentType.to_string(),
+			content_id: None,
+			data: data.to_vec(),
+			section: None,
+		};
+	}
+
+	#[test]
+	fn previewKind_followsTheMimeThenTheExtensionOfUntypedFiles()
+	{
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("photo", "image/PNG", b"\x89PNG")), Some(AttachmentPreviewKind::IMAGE));
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("report.pdf", "application/octet-stream", b"%PDF")), Some(AttachmentPreviewKind::PDF));
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("main.rs", "application/octet-stream", b"fn main() {}")), Some(AttachmentPreviewKind::TEXT(Some(SyntaxLanguage::RUST))));
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("notes", "text/plain; charset=utf-8", b"hello")), Some(AttachmentPreviewKind::TEXT(None)));
+		// an svg can carry scripts, it is never shown as an image
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("logo.svg", "image/svg+xml", b"<svg/>")), None);
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("data.txt", "text/plain", b"a\0b")), None);
+		assert_eq!(AttachmentPreviewKind::fromAttachment(&attachment_get("archive.zip", "application/zip", b"PK")), None);
+	}
+
+	#[test]
+	fn attachmentText_isCutOnACharacterBoundary()
+	{
+		let mut data = vec![b'a'; AttachmentPreviewLimits::TEXT_MAXIMUM_BYTES - 1];
+		data.extend("é and more".as_bytes());
+		let (text,isTruncated) = attachmentText_get(&data);
+
+		assert!(isTruncated);
+		assert!(!text.contains('\u{FFFD}'));
+		assert_eq!(text.len(), AttachmentPreviewLimits::TEXT_MAXIMUM_BYTES - 1);
+		assert_eq!(attachmentText_get(b"short"), ("short".to_string(), false));
+	}
+
+	#[test]
+	fn syntaxTokenize_coloursCommentsStringsNumbersAndKeywords()
+	{
+		let tokens = syntax_tokenize("let x = \"a\\\"b\"; // 42\nreturn x1 + 7;", SyntaxLanguage::RUST);
+		let coloured = tokens.iter().filter(|(token,_)| *token != SyntaxToken::PLAIN).cloned().collect::<Vec<_>>();
+
+		assert_eq!(coloured, vec![
+			(SyntaxToken::KEYWORD, "let"),
+			(SyntaxToken::STRING, "\"a\\\"b\""),
+			(SyntaxToken::COMMENT, "// 42"),
+			(SyntaxToken::KEYWORD, "return"),
+			(SyntaxToken::NUMBER, "7"),
+		]);
+		assert_eq!(tokens.iter().map(|(_,part)| *part).collect::<String>(), "let x = \"a\\\"b\"; // 42\nreturn x1 + 7;");
+	}
+}
@@ -0,0 +1,110 @@
+use futures::StreamExt;
+use leptoaster::ToasterContext;
+use leptos::prelude::{ArcRwSignal, Get, Set, UpdateUntracked, WithUntracked};
+use crate::api::proxys::imap::API_proxys_imap_getAttachment;
+use crate::api::proxys::imap_components::{imap_connector, Attachment, ImapMailKey, ImapProxyResponse, ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES};
+use crate::api::proxys::imap_error::ImapError;
+use crate::front::utils::toaster_helpers::toaster_api;
+use super::oauth::MailOAuthRotations;
+
+/// attachments of an opened mail, the server only lists them until one is downloaded, previewed or forwarded
+#[derive(Clone)]
+pub(super) struct MailAttachments
+{
+	connector: imap_connector,
+	oauthRotations: MailOAuthRotations,
+	mail: ImapMailKey,
+	attachments: ArcRwSignal<Vec<Attachment>>,
+	loading: ArcRwSignal<Option<usize>>,
+}
+
+impl MailAttachments
+{
+	pub(super) fn new(connector: imap_connector, oauthRotations: MailOAuthRotations, mail: ImapMailKey, attachments: Vec<Attachment>) -> Self
+	{
+		return Self {
+			connector,
+			oauthRotations,
+			mail,
+			attachments: ArcRwSignal::new(attachments),
+			loading: ArcRwSignal::new(None),
+		};
+	}
+
+	pub(super) fn get_untracked(&self, index: usize) -> Option<Attachment>
+	{
+		return self.attachments.with_untracked(|attachments| attachments.get(index).cloned());
+	}
+
+	pub(super) fn isLoading(&self, index: usize) -> bool
+	{
+		return self.loading.get() == Some(index);
+	}
+
+	/// the attachment with its data, fetched once then kept while the mail stays open
+	pub(super) async fn load(&self, index: usize, toaster: &ToasterContext) -> Option<Attachment>
+	{
+		let attachment = self.get_untracked(index)?;
+		let Some(section) = attachment.section.clone()
+		else
+		{
+			return Some(attachment);
+		};
+		self.loading.set(Some(index));
+		let result = attachmentData_fetch(&self.connector, &self.oauthRotations, self.mail.clone(), section).await;
+		self.loading.set(None);
+		let data = toaster_api(toaster, result, None).await?;
+		let loaded = Attachment {data, section: None, ..attachment};
+		self.attachments.update_untracked(|attachments| {
+			if let Some(entry) = attachments.get_mut(index)
+			{
+				*entry = loaded.clone();
+			}
+		});
+		return Some(loaded);
+	}
+
+	/// every attachment with its data, a forward sends them again
+	pub(super) async fn all_load(&self, toaster: &ToasterContext) -> Option<Vec<Attachment>>
+	{
+		let count = self.attachments.with_untracked(Vec::len);
+		let mut loaded = Vec::with_capacity(count);
+		for index in 0..count
+		{
+			loaded.push(self.load(index, toaster).await?);
+		}
+		return Some(loaded);
+	}
+}
+
+/// the head line of the stream gives the rotated refresh token and the failure before the first range, the data follows it
+async fn attachmentData_fetch(connector: &imap_connector, oauthRotations: &MailOAuthRotations, mail: ImapMailKey, section: String) -> Result<Vec<u8>, ImapError>
+{
+	let stream = API_proxys_imap_getAttachment(connector.clone(), mail, section).await?.into_inner();
+	futures::pin_mut!(stream);
+	let mut buffer = Vec::new();
+	let mut isHeadRead = false;
+	while let Some(chunk) = stream.next().await
+	{
+		buffer.extend_from_slice(&chunk?);
+		if (!isHeadRead)
+		{
+			let Some(head) = ImapProxyResponse::attachmentHead_take(&mut buffer)
+			else
+			{
+				continue;
+			};
+			oauthRotations.keep(connector, head)?;
+			isHeadRead = true;
+		}
+		if (buffer.len() > ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES)
+		{
+			return Err(ImapError::RESOURCE_LIMIT);
+		}
+	}
+	if (!isHeadRead)
+	{
+		return Err(ImapError::SERVER_ERROR);
+	}
+	return Ok(buffer);
+}
@@ -0,0 +1,537 @@
+use leptoaster::expect_toaster;
+use leptos::prelude::{AriaAttributes, ArcRwSignal, ClassAttribute, CollectView, ElementChild, Get, GetUntracked, GlobalAttributes, IntoAny, OnAttribute, PropAttribute, Set, Update};
+use leptos::prelude::AnyView;
+use leptos::view;
+use crate::api::proxys::imap_components::{Attachment, ImapMail, ImapMailContentType};
+use crate::api::proxys::smtp::API_proxys_smtp_send;
+use crate::api::proxys::smtp_components::SmtpMessage;
+use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::utils::dialog::{DialogData, DialogManager};
+use crate::front::utils::toaster_helpers::{toaster_api, toastingErr, toastingSuccess, toastingWarn};
+use crate::front::utils::translate::TranslateText;
+use super::{mailDate_format, MailConfig, MailSenderAddress};
+use super::oauth::MailOAuthRotations;
+
+/// content of the compose dialog, prefilled by a reply or a forward
+#[derive(Clone, Debug, Default)]
+pub(super) struct MailDraft
+{
+	to: String,
+	cc: String,
+	bcc: String,
+	subject: String,
+	body: String,
+	inReplyTo: Option<String>,
+	references: Vec<String>,
+	attachments: Vec<Attachment>,
+}
+
+impl MailDraft
+{
+	// answers keep the last references only, the server refuses longer chains
+	const REFERENCE_MAXIMUM: usize = 64;
+
+	/// answers the sender, or every other recipient too, in the thread of the mail
+	pub(super) fn reply_get(mail: &ImapMail, ownAddress: Option<&MailSenderAddress>, isReplyAll: bool) -> Self
+	{
+		let mut recipients = Vec::<MailSenderAddress>::new();
+		let mut to = Self::addresses_get(&mail.from, ownAddress, &mut recipients);
+		let mut cc = Vec::new();
+		if (isReplyAll)
+		{
+			to.extend(Self::addresses_get(&mail.to, ownAddress, &mut recipients));
+			cc = Self::addresses_get(&mail.cc, ownAddress, &mut recipients);
+		}
+		let mut references = mail.references.as_deref()
+			.map(|references| references.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+			.unwrap_or_default();
+		if let Some(messageId) = &mail.messageId
+		{
+			references.push(messageId.clone());
+		}
+		let referencesSkipped = references.len().saturating_sub(Self::REFERENCE_MAXIMUM);
+		references.drain(..referencesSkipped);
+
+		let quoted = Self::text_get(&mail.content).lines()
+			.map(|line| if (line.is_empty()) {">".to_string()} else {format!("> {}",line)})
+			.collect::<Vec<_>>()
+			.join("\n");
+		return Self {
+			to: to.join(", "),
+			cc: cc.join(", "),
+			subject: Self::subject_prefix(mail.subject.as_deref(), "Re:"),
+			body: format!("\n\n{} - {}\n{}\n", mailDate_format(mail.date), mail.from, quoted),
+			inReplyTo: mail.messageId.clone(),
+			references,
+			..Default::default()
+		};
+	}
+
+	/// copies the content and the attachments, without recipient
+	pub(super) fn forward_get(mail: &ImapMail) -> Self
+	{
+		let mut header = format!("From: {}\nDate: {}\n", mail.from, mailDate_format(mail.date));
+		if let Some(subject) = &mail.subject
+		{
+			header.push_str(&format!("Subject: {}\n", subject));
+		}
+		header.push_str(&format!("To: {}\n", mail.to));
+		if (!mail.cc.is_empty())
+		{
+			header.push_str(&format!("Cc: {}\n", mail.cc));
+		}
+		return Self {
+			subject: Self::subject_prefix(mail.subject.as_deref(), "Fwd:"),
+			body: format!("\n\n----------\n{}\n{}", header, Self::text_get(&mail.content)),
+			attachments: mail.attachement.clone(),
+			..Default::default()
+		};
+	}
+
+	pub(super) fn message_get(&self) -> Option<SmtpMessage>
+	{
+		let message = SmtpMessage {
+			to: Self::recipients_split(&self.to),
+			cc: Self::recipients_split(&self.cc),
+			bcc: Self::recipients_split(&self.bcc),
+			subject: self.subject.trim().to_string(),
+			body: self.body.clone(),
+			inReplyTo: self.inReplyTo.clone(),
+			references: self.references.clone(),
+			attachments: self.attachments.clone(),
+			calendar: None,
+		};
+		if (message.to.is_empty() && message.cc.is_empty() && message.bcc.is_empty())
+		{
+			return None;
+		}
+		return Some(message);
+	}
+
+	fn addresses_get(header: &str, ownAddress: Option<&MailSenderAddress>, known: &mut Vec<MailSenderAddress>) -> Vec<String>
+	{
+		let mut addresses = Vec::new();
+		for address in header.split([',',';']).filter_map(MailSenderAddress::from_candidate)
+		{
+			if (ownAddress == Some(&address) || known.contains(&address))
+			{
+				continue;
+			}
+			addresses.push(address.as_str().to_string());
+			known.push(address);
+		}
+		return addresses;
+	}
+
+	fn subject_prefix(subject: Option<&str>, prefix: &str) -> String
+	{
+		let subject = subject.unwrap_or_default().trim();
+		let isPrefixed = subject.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix));
+		if (isPrefixed)
+		{
+			return subject.to_string();
+		}
+		return format!("{} {}", prefix, subject).trim_end().to_string();
+	}
+
+	fn recipients_split(value: &str) -> Vec<String>
+	{
+		return value.split([',',';','\n'])
+			.map(str::trim)
+			.filter(|recipient| !recipient.is_empty())
+			.map(str::to_string)
+			.collect();
+	}
+
+	/// plain text of the sanitized content, for the quote of an answer
+	fn text_get(content: &ImapMailContentType) -> String
+	{
+		return match content
+		{
+			ImapMailContentType::Text(text) => text.trim_end().to_string(),
+			ImapMailContentType::Html(html) => Self::text_fromHtml(html),
+			ImapMailContentType::None => String::new(),
+		};
+	}
+
+	pub(super) fn text_fromHtml(html: &str) -> String
+	{
+		let mut text = String::with_capacity(html.len());
+		let mut rest = html;
+		while let Some(tagStart) = rest.find('<')
+		{
+			text.push_str(&rest[..tagStart]);
+			let Some(tagLength) = rest[tagStart..].find('>')
+			else
+			{
+				rest = "";
+				break;
+			};
+			let tag = rest[tagStart + 1..tagStart + tagLength].trim_start_matches('/').to_ascii_lowercase();
+			let tagName = tag.split(|character: char| character.is_whitespace() || character == '/').next().unwrap_or_default();
+			if (matches!(tagName, "br" | "p" | "div" | "tr" | "li" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote"))
+			{
+				text.push('\n');
+			}
+			rest = &rest[tagStart + tagLength + 1..];
+		}
+		text.push_str(rest);
+		let text = text.replace("&nbsp;", " ")
+			.replace("&lt;", "<")
+			.replace("&gt;", ">")
+			.replace("&quot;", "\"")
+			.replace("&#39;", "'")
+			.replace("&amp;", "&");
+		let mut lines = text.lines().map(str::trim_end).collect::<Vec<_>>();
+		lines.dedup_by(|line,previous| line.is_empty() && previous.is_empty());
+		return lines.join("\n").trim().to_string();
+	}
+}
+
+/// the mail is sent by the server of account, the answers keep the account of their mail
+pub(super) fn mailCompose_open(config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, account: usize, draft: MailDraft, dialogManager: DialogManager, moduleActions: ModuleActionFn)
+{
+	// The dialog replaces the mail dialog and must not depend on its Owner.
+	let sender = ArcRwSignal::new(account);
+	let to = ArcRwSignal::new(draft.to.clone());
+	let cc = ArcRwSignal::new(draft.cc.clone());
+	let bcc = ArcRwSignal::new(draft.bcc.clone());
+	let subject = ArcRwSignal::new(draft.subject.clone());
+	let body = ArcRwSignal::new(draft.body.clone());
+	let attachments = ArcRwSignal::new(draft.attachments.clone());
+	let pending = ArcRwSignal::new(false);
+
+	let bodyView = MailComposeFields {
+		sender: sender.clone(),
+		senders: config.get_untracked().senders_get(),
+		to: to.clone(),
+		cc: cc.clone(),
+		bcc: bcc.clone(),
+		subject: subject.clone(),
+		body: body.clone(),
+		attachments: attachments.clone(),
+		pending: pending.clone(),
+	};
+	let moduleActionsBody = moduleActions.clone();
+	let pendingClose = pending.clone();
+	let dialog = DialogData::new()
+		.setTitle("MODULE_MAIL_COMPOSE")
+		.setIsLarger(true)
+		.setBody(move || bodyView.view_get(moduleActionsBody.clone()))
+		.setButtonValidateTitle(Some("MODULE_MAIL_COMPOSE_SEND"))
+		.setOnValidate({
+			let dialogManager = dialogManager.clone();
+			move |_| {
+				if (pending.get_untracked())
+				{
+					return false;
+				}
+				let currentDraft = MailDraft {
+					to: to.get_untracked(),
+					cc: cc.get_untracked(),
+					bcc: bcc.get_untracked(),
+					subject: subject.get_untracked(),
+					body: body.get_untracked(),
+					attachments: attachments.get_untracked(),
+					..draft.clone()
+				};
+				let toaster = expect_toaster();
+				let Some(message) = currentDraft.message_get()
+				else
+				{
+					moduleActions.task_spawn(async move {
+						toastingErr(&toaster,"MODULE_MAIL_COMPOSE_NO_RECIPIENT").await;
+					});
+					return false;
+				};
+				let config = config.get_untracked();
+				let account = sender.get_untracked();
+				let Some((imap,smtp)) = config.imap_get(account).cloned().zip(config.smtp_get(account).filter(|smtp| smtp.isConfigured()).cloned())
+				else
+				{
+					moduleActions.task_spawn(async move {
+						toastingErr(&toaster,"MODULE_MAIL_COMPOSE_NO_SENDER").await;
+					});
+					return false;
+				};
+				pending.set(true);
+				let pending = pending.clone();
+				let dialogManager = dialogManager.clone();
+				let oauthRotations = oauthRotations.clone();
+				let taskActions = moduleActions.clone();
+				moduleActions.task_spawn(async move {
+					let apiResult = oauthRotations.keep(&imap,API_proxys_smtp_send(imap.clone(),smtp,message).await);
+					if (!taskActions.lifecycle_isActive())
+					{
+						return;
+					}
+					pending.set(false);
+					let Some(sent) = toaster_api(&toaster,apiResult,None).await else {return};
+					if (!taskActions.lifecycle_isActive())
+					{
+						return;
+					}
+					dialogManager.clear();
+					if (sent.sentCopySaved)
+					{
+						toastingSuccess(&toaster,"MODULE_MAIL_COMPOSE_SENT").await;
+					}
+					else
+					{
+						toastingWarn(&toaster,"MODULE_MAIL_COMPOSE_SENT_WITHOUT_COPY").await;
+					}
+				});
+				return false;
+			}
+		})
+		.setCanClose(move || !pendingClose.get());
+	dialogManager.open(dialog);
+}
+
+struct MailComposeFields
+{
+	sender: ArcRwSignal<usize>,
+	senders: Vec<(usize,String)>,
+	to: ArcRwSignal<String>,
+	cc: ArcRwSignal<String>,
+	bcc: ArcRwSignal<String>,
+	subject: ArcRwSignal<String>,
+	body: ArcRwSignal<String>,
+	attachments: ArcRwSignal<Vec<Attachment>>,
+	pending: ArcRwSignal<bool>,
+}
+
+impl MailComposeFields
+{
+	fn view_get(&self, moduleActions: ModuleActionFn) -> AnyView
+	{
+		let pending = self.pending.clone();
+		let attachmentsList = self.attachments.clone();
+		let attachmentsPicked = self.attachments.clone();
+		return view!{
+			<div class="module_mail_compose">
+				{self.sender_view()}
+				{Self::field_view("mail-compose-to","MODULE_MAIL_TO","email",self.to.clone())}
+				{Self::field_view("mail-compose-cc","MODULE_MAIL_CC","email",self.cc.clone())}
+				{Self::field_view("mail-compose-bcc","MODULE_MAIL_BCC","email",self.bcc.clone())}
+				{Self::field_view("mail-compose-subject","MODULE_MAIL_SUBJECT","text",self.subject.clone())}
+				<label for="mail-compose-body">
+					<span><TranslateText key="MODULE_MAIL_COMPOSE_BODY"/></span>
+					<textarea id="mail-compose-body" class="module_mail_compose_body" rows="12"
+						prop:value={
+							let body = self.body.clone();
+							move || body.get()
+						}
+						on:input={
+							let body = self.body.clone();
+							move |event| body.set(leptos::prelude::event_target_value(&event))
+						}></textarea>
+				</label>
+				<div class="module_mail_compose_attachments">
+					<label class="module_mail_compose_attach" for="mail-compose-attachments">
+						<i class="iconoir-attachment" aria-hidden="true"/>{" "}<TranslateText key="MODULE_MAIL_COMPOSE_ATTACH"/>
+						<input id="mail-compose-attachments" class="visually_hidden" type="file" multiple
+							on:change={move |event| attachments_pick(event, attachmentsPicked.clone(), moduleActions.clone())}/>
+					</label>
+					{move || attachmentsList.get().into_iter().enumerate().map(|(attachmentIndex,attachment)| {
+						let attachmentsRemove = attachmentsList.clone();
+						let filename = attachment.filename.clone().unwrap_or_else(|| "attachment".to_string());
+						view!{
+							<button type="button" class="module_mail_compose_attachment" on:click={move |_| {
+								attachmentsRemove.update(|attachments| {
+									if (attachmentIndex < attachments.len())
+									{
+										attachments.remove(attachmentIndex);
+									}
+								});
+							}}>
+								<span class="visually_hidden"><TranslateText key="FRONTUI_REMOVED"/>{" "}</span>
+								<span>{filename}</span><span class="module_mail_compose_attachment_remove" aria-hidden="true">{"×"}</span>
+							</button>
+						}
+					}).collect_view()}
+				</div>
+				{move || pending.get().then(|| view! {
+					<p class="module_mail_compose_notice" role="status"><TranslateText key="MODULE_MAIL_COMPOSE_SENDING"/></p>
+				})}
+			</div>
+		}.into_any();
+	}
+
+	/// the sending account is chosen only when several accounts can send
+	fn sender_view(&self) -> Option<AnyView>
+	{
+		if (self.senders.len() < 2)
+		{
+			return None;
+		}
+		let senderValue = self.sender.clone();
+		let senderChange = self.sender.clone();
+		return Some(view!{
+			<label for="mail-compose-from">
+				<span><TranslateText key="MODULE_MAIL_FROM"/></span>
+				<select id="mail-compose-from"
+					prop:value={move || senderValue.get().to_string()}
+					on:change={move |event| {
+						if let Ok(account) = leptos::prelude::event_target_value(&event).parse()
+						{
+							senderChange.set(account);
+						}
+					}}>
+					{self.senders.iter().map(|(account,address)| view!{
+						<option value={account.to_string()}>{address.clone()}</option>
+					}).collect_view()}
+				</select>
+			</label>
+		}.into_any());
+	}
+
+	fn field_view(id: &'static str, translateKey: &'static str, inputType: &'static str, value: ArcRwSignal<String>) -> AnyView
+	{
+		let valueInput = value.clone();
+		// several addresses are separated by commas, the browser validation of "email" would refuse names
+		let inputType = if (inputType == "email") {"text"} else {inputType};
+		return view!{
+			<label for={id}>
+				<span><TranslateText key={translateKey}/></span>
+				<input id={id} type={inputType}
+					prop:value={move || value.get()}
+					on:input={move |event| valueInput.set(leptos::prelude::event_target_value(&event))}/>
+			</label>
+		}.into_any();
+	}
+}
+
+#[cfg(feature="hydrate")]
+fn attachments_pick(event: leptos::ev::Event, attachments: ArcRwSignal<Vec<Attachment>>, moduleActions: ModuleActionFn)
+{
+	use wasm_bindgen::JsCast;
+	use wasm_bindgen_futures::JsFuture;
+	use crate::api::proxys::smtp_components::SMTP_ATTACHMENT_TOTAL_MAXIMUM_BYTES;
+
+	let Some(input) = event.target().and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok()) else {return};
+	let Some(fileList) = input.files() else {return};
+	let files = (0..fileList.length()).filter_map(|index| fileList.get(index)).collect::<Vec<_>>();
+	// the same file can be picked again after its removal
+	input.set_value("");
+	let toaster = expect_toaster();
+	let taskActions = moduleActions.clone();
+	moduleActions.task_spawn(async move {
+		let mut totalBytes = attachments.get_untracked().iter().map(|attachment| attachment.data.len()).sum::<usize>();
+		let mut picked = Vec::with_capacity(files.len());
+		for file in files
+		{
+			totalBytes = totalBytes.saturating_add(file.size() as usize);
+			if (totalBytes > SMTP_ATTACHMENT_TOTAL_MAXIMUM_BYTES)
+			{
+				toastingErr(&toaster,"MODULE_MAIL_COMPOSE_ATTACHMENTS_TOO_LARGE").await;
+				break;
+			}
+			let Ok(buffer) = JsFuture::from(file.array_buffer()).await
+			else
+			{
+				toastingErr(&toaster,"MODULE_MAIL_COMPOSE_ATTACHMENT_READ_ERROR").await;
+				continue;
+			};
+			let contentType = file.type_();
+			picked.push(Attachment {
+				filename: Some(file.name()),
+				content_type: if (contentType.is_empty()) {"application/octet-stream".to_string()} else {contentType},
+				content_id: None,
+				data: js_sys::Uint8Array::new(&buffer).to_vec(),
+				section: None,
+			});
+		}
+		if (!taskActions.lifecycle_isActive())
+		{
+			return;
+		}
+		attachments.update(|attachments| attachments.extend(picked));
+	});
+}
+
+#[cfg(not(feature="hydrate"))]
+fn attachments_pick(_event: leptos::ev::Event, _attachments: ArcRwSignal<Vec<Attachment>>, _moduleActions: ModuleActionFn)
+{
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::proxys::imap_components::{Attachment, ImapMail, ImapMailContentType};
+	use super::super::MailSenderAddress;
+	use super::MailDraft;
+
+	fn mail_get() -> ImapMail
+	{
+		return ImapMail {
+			from: "Alice <alice@example.org>".to_string(),
+			to: "me@example.com, Bob <bob@example.org>".to_string(),
+			cc: "carol@example.org; ME@example.com".to_string(),
+			subject: Some("Lunch".to_string()),
+			content: ImapMailContentType::Text("See you\n\nat noon".to_string()),
+			messageId: Some("<second@example.org>".to_string()),
+			references: Some("<first@example.org>".to_string()),
+			attachement: vec![Attachment {filename: Some("menu.pdf".to_string()),..Default::default()}],
+			..Default::default()
+		};
+	}
+
+	#[test]
+	fn mailDraft_replyAnswersTheSenderInTheSameThread()
+	{
+		let ownAddress = MailSenderAddress::from_header("me@example.com");
+		let draft = MailDraft::reply_get(&mail_get(),ownAddress.as_ref(),false);
+
+		assert_eq!(draft.to,"alice@example.org");
+		assert_eq!(draft.cc,"");
+		assert_eq!(draft.subject,"Re: Lunch");
+		assert_eq!(draft.inReplyTo.as_deref(),Some("<second@example.org>"));
+		assert_eq!(draft.references,vec!["<first@example.org>","<second@example.org>"]);
+		assert!(draft.body.ends_with("> See you\n>\n> at noon\n"),"{}",draft.body);
+		assert!(draft.attachments.is_empty());
+	}
+
+	#[test]
+	fn mailDraft_replyAllSkipsOwnAddressAndDuplicates()
+	{
+		let ownAddress = MailSenderAddress::from_header("me@example.com");
+		let mut mail = mail_get();
+		mail.subject = Some("RE: Lunch".to_string());
+		mail.cc.push_str(", alice@example.org");
+		let draft = MailDraft::reply_get(&mail,ownAddress.as_ref(),true);
+
+		assert_eq!(draft.to,"alice@example.org, bob@example.org");
+		assert_eq!(draft.cc,"carol@example.org");
+		assert_eq!(draft.subject,"RE: Lunch");
+	}
+
+	#[test]
+	fn mailDraft_forwardKeepsAttachmentsWithoutRecipient()
+	{
+		let draft = MailDraft::forward_get(&mail_get());
+
+		assert_eq!(draft.to,"");
+		assert_eq!(draft.subject,"Fwd: Lunch");
+		assert_eq!(draft.inReplyTo,None);
+		assert_eq!(draft.attachments.len(),1);
+		assert!(draft.body.contains("From: Alice <alice@example.org>\n"),"{}",draft.body);
+		assert!(draft.body.ends_with("See you\n\nat noon"),"{}",draft.body);
+		assert!(draft.message_get().is_none());
+	}
+
+	#[test]
+	fn mailDraft_messageSplitsRecipientsAndQuotesHtml()
+	{
+		let mut mail = mail_get();
+		mail.content = ImapMailContentType::Html("<p>Hello&nbsp;<b>you</b></p><p>a &lt; b</p>".to_string());
+		let mut draft = MailDraft::reply_get(&mail,None,false);
+		draft.bcc = " hidden@example.org ;\nother@example.org, ".to_string();
+
+		let message = draft.message_get().unwrap();
+
+		assert_eq!(message.to,vec!["alice@example.org"]);
+		assert_eq!(message.bcc,vec!["hidden@example.org","other@example.org"]);
+		assert!(message.body.ends_with("> Hello you\n>\n> a < b\n"),"{}",message.body);
+	}
+}
@@ -0,0 +1,162 @@
+use std::sync::{Arc, Mutex, Weak};
+use futures::StreamExt;
+use gloo_timers::future::TimeoutFuture;
+use leptoaster::ToasterContext;
+use leptos::prelude::{ArcRwSignal, GetUntracked, WithUntracked};
+use crate::api::proxys::imap::API_proxys_imap_idle;
+use crate::api::proxys::imap_components::{imap_connector, ImapIdleEvent};
+use crate::front::modules::module_actions::ModuleActionFn;
+use super::{Mail, MailConfig, MailSyncIdentity, MailsInbox};
+use super::oauth::MailOAuthRotations;
+
+struct MailIdleLimits;
+
+impl MailIdleLimits
+{
+	// failed connections in a row before the listener leaves the module to its periodic refresh
+	const RETRY_MAXIMUM: u32 = 5;
+	const RETRY_DELAY_MS: u32 = 5_000;
+	const RETRY_DELAY_MAXIMUM_MS: u32 = 5 * 60_000;
+}
+
+/// accounts of a module with a running listener, by their synchronization identity
+pub(super) type MailIdleListening = Arc<Mutex<Vec<MailSyncIdentity>>>;
+
+/// releases the account from the listening ones of the module, also when the task is cancelled with the holder lifecycle
+struct MailIdleGuard(Weak<Mutex<Vec<MailSyncIdentity>>>, MailSyncIdentity);
+
+impl MailIdleGuard
+{
+	fn isModuleAlive(&self) -> bool
+	{
+		return self.0.strong_count() > 0;
+	}
+}
+
+impl Drop for MailIdleGuard
+{
+	fn drop(&mut self)
+	{
+		if let Some(listening) = self.0.upgrade() && let Ok(mut listening) = listening.lock()
+		{
+			listening.retain(|syncIdentity| *syncIdentity != self.1);
+		}
+	}
+}
+
+/// forwards the IMAP IDLE changes of each account of one mail module to a synchronization of the changed mailbox only.
+/// Without IDLE on the server, or after too many failures, the account keeps the periodic refresh alone.
+/// The listener of an edited or removed account ends, the next refresh starts the ones of the new accounts
+pub(super) fn mailIdle_listen(listening: &MailIdleListening, toaster: ToasterContext, mailsCache: ArcRwSignal<MailsInbox>, config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, moduleActions: ModuleActionFn)
+{
+	let syncIdentities = config.get_untracked().imaps_get().iter().map(MailSyncIdentity::new).collect::<Vec<_>>();
+	for syncIdentity in syncIdentities
+	{
+		{
+			let Ok(mut listening) = listening.lock() else {return};
+			if (listening.contains(&syncIdentity))
+			{
+				continue;
+			}
+			listening.push(syncIdentity.clone());
+		}
+		let guard = MailIdleGuard(Arc::downgrade(listening), syncIdentity);
+		let (toaster,mailsCache,config,oauthRotations) = (toaster.clone(),mailsCache.clone(),config.clone(),oauthRotations.clone());
+		let moduleActionsTask = moduleActions.clone();
+		moduleActions.task_spawn(async move {
+			mailIdle_run(&guard, toaster, mailsCache, config, oauthRotations, moduleActionsTask).await;
+		});
+	}
+}
+
+async fn mailIdle_run(guard: &MailIdleGuard, toaster: ToasterContext, mailsCache: ArcRwSignal<MailsInbox>, config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, moduleActions: ModuleActionFn)
+{
+	let syncIdentity = &guard.1;
+	let mut failures = 0;
+	let mut wasWatching = false;
+	loop
+	{
+		if (!moduleActions.lifecycle_isActive() || !guard.isModuleAlive())
+		{
+			return;
+		}
+		let Some(connector) = mailIdle_connector(&config,syncIdentity) else {return};
+		let Some(boxName) = mailsCache.with_untracked(|mailsCache| mailIdle_box(mailsCache,syncIdentity)) else {return};
+
+		let mut isWatching = false;
+		let mut isEndedByServer = false;
+		if let Ok(stream) = API_proxys_imap_idle(connector.clone(), boxName.clone()).await
+		{
+			let mut chunks = stream.into_inner();
+			let mut buffer = String::new();
+			loop
+			{
+				let chunk = match chunks.next().await
+				{
+					Some(Ok(chunk)) => chunk,
+					Some(Err(_)) => break,
+					None => {
+						isEndedByServer = true;
+						break;
+					},
+				};
+				buffer.push_str(&chunk);
+				for event in ImapIdleEvent::lines_take(&mut buffer)
+				{
+					let changedBox = match event
+					{
+						ImapIdleEvent::UNSUPPORTED => return,
+						ImapIdleEvent::HEARTBEAT => None,
+						ImapIdleEvent::OAUTH_ROTATED(rotated) => {
+							oauthRotations.rotated_set(&connector,rotated);
+							None
+						},
+						ImapIdleEvent::CHANGED(changedBox) => Some(changedBox),
+						// the changes made while reconnecting were not pushed
+						ImapIdleEvent::WATCHING(watchedBox) => {
+							isWatching = true;
+							wasWatching.then_some(watchedBox)
+						},
+					};
+					if let Some(changedBox) = changedBox
+					{
+						Mail::sync(toaster.clone(), mailsCache.clone(), config.clone(), oauthRotations.clone(), moduleActions.clone(), Some((syncIdentity.clone(),changedBox))).await;
+					}
+				}
+				// a new configuration or mailbox selection needs a new IDLE connection
+				if (!moduleActions.lifecycle_isActive()
+					|| !guard.isModuleAlive()
+					|| mailIdle_connector(&config,syncIdentity).is_none()
+					|| mailsCache.with_untracked(|mailsCache| mailIdle_box(mailsCache,syncIdentity)).as_ref() != Some(&boxName))
+				{
+					break;
+				}
+			}
+		}
+
+		wasWatching |= isWatching;
+		failures = if (isWatching) {0} else {failures + 1};
+		if (failures > MailIdleLimits::RETRY_MAXIMUM)
+		{
+			return;
+		}
+		// the server ends each IDLE stream after a few minutes, the next one is opened right away
+		if (isWatching && isEndedByServer)
+		{
+			continue;
+		}
+		let delay = MailIdleLimits::RETRY_DELAY_MS.saturating_mul(1 << failures).min(MailIdleLimits::RETRY_DELAY_MAXIMUM_MS);
+		TimeoutFuture::new(delay).await;
+	}
+}
+
+/// current connector of the watched account, wherever the removal of another account moved it
+fn mailIdle_connector(config: &ArcRwSignal<MailConfig>, syncIdentity: &MailSyncIdentity) -> Option<imap_connector>
+{
+	return config.with_untracked(|config| config.imap_get(config.account_find(syncIdentity)?).cloned());
+}
+
+fn mailIdle_box(mailsCache: &MailsInbox, syncIdentity: &MailSyncIdentity) -> Option<String>
+{
+	return mailsCache.accountSynced_get(syncIdentity)?.idleBox_get();
+}
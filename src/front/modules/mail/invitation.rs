@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use leptoaster::{expect_toaster, ToasterContext};
+use leptos::prelude::{ArcRwSignal, ClassAttribute, CollectView, ElementChild, Get, GetUntracked, IntoAny, OnAttribute, PropAttribute, Set, event_target_value};
+use leptos::prelude::AnyView;
+use leptos::view;
+use crate::api::proxys::imap_components::{ImapInvitation, ImapInvitationMethod};
+use crate::api::proxys::smtp::API_proxys_smtp_send;
+use crate::api::proxys::smtp_components::{smtp_connector, SmtpMessage};
+use crate::front::modules::calendar::invitation::{invitation_reply_get, CalendarInvitation, CalendarInvitationAnswer, CalendarInvitationTarget};
+use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::utils::fluent::FluentManager::FluentManager;
+use crate::front::utils::toaster_helpers::{toaster_api, toastingErr, toastingSuccess};
+use crate::front::utils::translate::{Translate, TranslateText};
+use crate::front::utils::users_data::ClientState;
+use super::MailConfig;
+use super::oauth::MailOAuthRotations;
+
+/// card of a meeting invitation received in the mail.
+/// An answer keeps the event in a calendar module of the dashboard, then tells the organizer when SMTP is configured for the account of the mail
+pub(super) fn mailInvitation_draw(config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, account: usize, invitation: ImapInvitation, moduleActions: ModuleActionFn) -> AnyView
+{
+	// an unreadable calendar stays available as a plain attachment for the user
+	let Some(parsed) = CalendarInvitation::parse(&invitation.calendar) else {return view!{}.into_any()};
+	let isCancelled = parsed.isCancelled || invitation.method == ImapInvitationMethod::CANCEL;
+	let heading = if (isCancelled) {"MODULE_MAIL_INVITATION_CANCELLED"} else {"MODULE_MAIL_INVITATION"};
+	let title = if (parsed.title.trim().is_empty()) {None} else {Some(parsed.title.clone())};
+	let period = parsed.period_label();
+	let location = if (parsed.location.trim().is_empty()) {None} else {Some(parsed.location.clone())};
+	let organizer = parsed.organizer.clone();
+
+	let answers = if (isCancelled)
+	{
+		mailInvitation_cancelDraw(invitation.calendar,moduleActions)
+	}
+	else
+	{
+		mailInvitation_answersDraw(config,oauthRotations,account,invitation.calendar,parsed,moduleActions)
+	};
+
+	return view!{
+		<div class="module_mail_invitation">
+			<b><Translate key={heading}/></b>
+			{title.map(|title| view!{<span class="module_mail_invitation_title">{title}</span>})}
+			{period.map(|period| view!{<span>{period}</span>})}
+			{location.map(|location| view!{<span><b><Translate key="MODULE_MAIL_INVITATION_LOCATION"/></b>{" "}{location}</span>})}
+			{organizer.map(|organizer| view!{<span><b><Translate key="MODULE_MAIL_INVITATION_ORGANIZER"/></b>{" "}{organizer}</span>})}
+			{answers}
+		</div>
+	}.into_any();
+}
+
+fn mailInvitation_answersDraw(config: ArcRwSignal<MailConfig>, oauthRotations: MailOAuthRotations, account: usize, calendar: String, invitation: CalendarInvitation, moduleActions: ModuleActionFn) -> AnyView
+{
+	let targets = CalendarInvitationTarget::list_get();
+	let selectedTarget = ArcRwSignal::new(0usize);
+	let pending = ArcRwSignal::new(false);
+	let answered = ArcRwSignal::new(None::<CalendarInvitationAnswer>);
+
+	let targetView = mailInvitation_targetDraw(&targets,selectedTarget.clone());
+	let targets = Arc::new(targets);
+	let buttons = CalendarInvitationAnswer::ALL.into_iter().map(|answer| {
+		let config = config.clone();
+		let oauthRotations = oauthRotations.clone();
+		let calendar = calendar.clone();
+		let invitation = invitation.clone();
+		let targets = targets.clone();
+		let selectedTarget = selectedTarget.clone();
+		let pendingClick = pending.clone();
+		let pendingView = pending.clone();
+		let answeredClick = answered.clone();
+		let answeredView = answered.clone();
+		let moduleActions = moduleActions.clone();
+		// keeping the event needs a calendar, a decline removes it from the calendar or only replies to the organizer
+		let isAvailable = !targets.is_empty() || (!answer.isStored() && invitation.organizer.is_some() && config.get_untracked().smtp_get(account).is_some_and(smtp_connector::isConfigured));
+		view!{
+			<button type="button"
+				class:module_mail_invitation_answered={move || answeredView.get() == Some(answer)}
+				prop:disabled={move || !isAvailable || pendingView.get()}
+				on:click={move |_| {
+					if (!moduleActions.lifecycle_isActive() || pendingClick.get_untracked())
+					{
+						return;
+					}
+					let target = targets.get(selectedTarget.get_untracked()).cloned();
+					if (answer.isStored() && target.is_none())
+					{
+						return;
+					}
+					pendingClick.set(true);
+					mailInvitation_answer(
+						config.clone(),oauthRotations.clone(),account,calendar.clone(),invitation.clone(),answer,target,
+						expect_toaster(),pendingClick.clone(),answeredClick.clone(),moduleActions.clone(),
+					);
+				}}><TranslateText key={answer.translateKey_get()}/></button>
+		}
+	}).collect_view();
+
+	return view!{
+		{targetView}
+		<div class="module_mail_invitation_actions">{buttons}</div>
+	}.into_any();
+}
+
+/// a cancelled meeting is removed from the calendar chosen by the user, only when its organizer stored it there
+fn mailInvitation_cancelDraw(calendar: String, moduleActions: ModuleActionFn) -> AnyView
+{
+	let targets = CalendarInvitationTarget::list_get();
+	if (targets.is_empty())
+	{
+		return view!{}.into_any();
+	}
+	let selectedTarget = ArcRwSignal::new(0usize);
+	let pending = ArcRwSignal::new(false);
+	let isRemoved = ArcRwSignal::new(false);
+	let targetView = mailInvitation_targetDraw(&targets,selectedTarget.clone());
+	let pendingView = pending.clone();
+	let isRemovedView = isRemoved.clone();
+
+	return view!{
+		{targetView}
+		<div class="module_mail_invitation_actions">
+			<button type="button"
+				class:module_mail_invitation_answered={move || isRemovedView.get()}
+				prop:disabled={move || pendingView.get()}
+				on:click={move |_| {
+					if (!moduleActions.lifecycle_isActive() || pending.get_untracked())
+					{
+						return;
+					}
+					let Some(target) = targets.get(selectedTarget.get_untracked()).cloned() else {return};
+					pending.set(true);
+					let calendar = calendar.clone();
+					let pending = pending.clone();
+					let isRemoved = isRemoved.clone();
+					let toaster = expect_toaster();
+					let taskActions = moduleActions.clone();
+					moduleActions.task_spawn(async move {
+						let removed = target.invitation_remove(&calendar).await;
+						if (!taskActions.lifecycle_isActive())
+						{
+							return;
+						}
+						pending.set(false);
+						match removed
+						{
+							Ok(()) =>
+							{
+								isRemoved.set(true);
+								toastingSuccess(&toaster,"MODULE_MAIL_INVITATION_REMOVED").await;
+							},
+							Err(key) => toastingErr(&toaster,key).await,
+						}
+					});
+				}}><TranslateText key="MODULE_MAIL_INVITATION_REMOVE"/></button>
+		</div>
+	}.into_any();
+}
+
+fn mailInvitation_targetDraw(targets: &[CalendarInvitationTarget], selectedTarget: ArcRwSignal<usize>) -> AnyView
+{
+	if (targets.is_empty())
+	{
+		return view!{<span class="module_mail_invitation_note"><Translate key="MODULE_MAIL_INVITATION_NO_CALENDAR"/></span>}.into_any();
+	}
+	return view!{
+		<label>
+			<span><TranslateText key="MODULE_MAIL_INVITATION_CALENDAR"/></span>
+			<select on:change={move |event| selectedTarget.set(event_target_value(&event).parse().unwrap_or(0))}>
+				{targets.iter().enumerate().map(|(index,target)| view!{<option value={index.to_string()}>{target.label.clone()}</option>}).collect_view()}
+			</select>
+		</label>
+	}.into_any();
+}
+
+/// keeps the event in the calendar first: the organizer is only told about an answer that is really recorded
+#[allow(clippy::too_many_arguments)]
+fn mailInvitation_answer(
+	config: ArcRwSignal<MailConfig>,
+	oauthRotations: MailOAuthRotations,
+	account: usize,
+	calendar: String,
+	invitation: CalendarInvitation,
+	answer: CalendarInvitationAnswer,
+	target: Option<CalendarInvitationTarget>,
+	toaster: ToasterContext,
+	pending: ArcRwSignal<bool>,
+	answered: ArcRwSignal<Option<CalendarInvitationAnswer>>,
+	moduleActions: ModuleActionFn,
+)
+{
+	let config = config.get_untracked();
+	let imap = config.imap_get(account).cloned().unwrap_or_default();
+	let smtp = config.smtp_get(account).filter(|smtp| smtp.isConfigured()).cloned();
+	let attendee = config.ownAddress_get(account).map(|address| address.normalized).unwrap_or_else(|| imap.username.clone());
+	let lang = ClientState::expect().lang_get_untracked();
+	let taskActions = moduleActions.clone();
+	moduleActions.task_spawn(async move {
+		if let Some(target) = target
+		{
+			let stored = target.invitation_store(&calendar,&attendee,answer).await;
+			if (!taskActions.lifecycle_isActive())
+			{
+				return;
+			}
+			if let Err(key) = stored
+			{
+				pending.set(false);
+				toastingErr(&toaster,key).await;
+				return;
+			}
+		}
+
+		let reply = invitation_reply_get(&calendar,&attendee,answer);
+		let (Some(organizer),Some(reply),Some(smtp)) = (invitation.organizer.clone(),reply,smtp) else {
+			pending.set(false);
+			answered.set(Some(answer));
+			toastingSuccess(&toaster,if (answer.isStored()) {"MODULE_MAIL_INVITATION_STORED"} else {"MODULE_MAIL_INVITATION_REMOVED"}).await;
+			return;
+		};
+		let params = Arc::new(HashMap::from([("title".to_string(),invitation.title)]));
+		let subject = FluentManager::singleton().translate(lang,mailInvitation_replyKey(answer),params).await;
+		let message = SmtpMessage {
+			to: vec![organizer],
+			cc: Vec::new(),
+			bcc: Vec::new(),
+			body: subject.clone(),
+			subject,
+			inReplyTo: None,
+			references: Vec::new(),
+			attachments: Vec::new(),
+			calendar: Some(reply),
+		};
+		let apiResult = oauthRotations.keep(&imap,API_proxys_smtp_send(imap.clone(),smtp,message).await);
+		if (!taskActions.lifecycle_isActive())
+		{
+			return;
+		}
+		pending.set(false);
+		// the calendar already holds the answer, a failed reply can be sent again
+		let Some(_) = toaster_api(&toaster,apiResult,None).await else {return};
+		answered.set(Some(answer));
+		toastingSuccess(&toaster,"MODULE_MAIL_INVITATION_REPLIED").await;
+	});
+}
+
+fn mailInvitation_replyKey(answer: CalendarInvitationAnswer) -> &'static str
+{
+	return match answer
+	{
+		CalendarInvitationAnswer::Accepted => "MODULE_MAIL_INVITATION_REPLY_ACCEPTED",
+		CalendarInvitationAnswer::Tentative => "MODULE_MAIL_INVITATION_REPLY_TENTATIVE",
+		CalendarInvitationAnswer::Declined => "MODULE_MAIL_INVITATION_REPLY_DECLINED",
+	};
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use gloo_timers::future::TimeoutFuture;
+use leptoaster::{expect_toaster, ToasterContext};
+use leptos::prelude::{ArcRwSignal, ClassAttribute, CollectView, ElementChild, Get, GetUntracked, IntoAny, OnAttribute, PropAttribute, Set, Update};
+use leptos::prelude::AnyView;
+use leptos::view;
+use crate::api::proxys::imap::{API_proxys_imap_oauthBegin, API_proxys_imap_oauthFinish, API_proxys_imap_oauthProviders};
+use crate::api::proxys::imap_components::{imap_connector, imap_connector_oauth, ImapProxyResponse};
+use crate::api::proxys::imap_error::ImapError;
+use crate::front::modules::components::Cache;
+use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::utils::toaster_helpers::{toaster_api, toastingErr};
+use crate::front::utils::translate::{Translate, TranslateText};
+use super::{MailConfig, MailSyncIdentity};
+
+struct MailOAuthLimits;
+
+impl MailOAuthLimits
+{
+	const POLL_DELAY_MS: u32 = 2_000;
+	// the server forgets a pending authorization after ten minutes
+	const POLL_MAXIMUM: u32 = 300;
+}
+
+/// sign-in of an account with an OAuth provider of the server, in place of the password.
+/// The section stays hidden when the server offers no provider
+pub(super) fn mailOAuth_draw(config: ArcRwSignal<MailConfig>, update: ArcRwSignal<Cache>, account: usize, moduleActions: ModuleActionFn) -> AnyView
+{
+	let providers = ArcRwSignal::new(Vec::<String>::new());
+	let selectedProvider = ArcRwSignal::new(String::new());
+	let pendingUrl = ArcRwSignal::new(None::<String>);
+	{
+		let providers = providers.clone();
+		let moduleActionsTask = moduleActions.clone();
+		moduleActions.task_spawn(async move {
+			// without provider the password login is the only choice, nothing to report
+			if let Ok(names) = API_proxys_imap_oauthProviders().await && moduleActionsTask.lifecycle_isActive()
+			{
+				providers.set(names);
+			}
+		});
+	}
+
+	return view!{{move || {
+		if let Some(oauth) = config.get().imap_get(account).and_then(|imap| imap.oauth.clone())
+		{
+			let config = config.clone();
+			let update = update.clone();
+			return view!{
+				<div class="module_mail_oauth">
+					<span><Translate key="MODULE_MAIL_OAUTH_CONNECTED" params={HashMap::from([("provider".to_string(),oauth.provider)])}/></span>
+					<button type="button" on:click={move |_| {
+						config.update(|config| {
+							if let Some(imap) = config.imap_getMut(account)
+							{
+								imap.oauth = None;
+							}
+						});
+						update.update(|cache| cache.update());
+					}}><Translate key="MODULE_MAIL_OAUTH_DISCONNECT"/></button>
+				</div>
+			}.into_any();
+		}
+		let names = providers.get();
+		if (names.is_empty())
+		{
+			return view!{}.into_any();
+		}
+		let selectedProviderView = selectedProvider.clone();
+		let selectedProviderChange = selectedProvider.clone();
+		let selectedProviderBegin = selectedProvider.clone();
+		let defaultProvider = names[0].clone();
+		let config = config.clone();
+		let update = update.clone();
+		let pendingUrlBegin = pendingUrl.clone();
+		let moduleActions = moduleActions.clone();
+		return view!{
+			<div class="module_mail_oauth">
+				<label>
+					<span><TranslateText key="MODULE_MAIL_OAUTH_PROVIDER"/></span>
+					<select
+						prop:value={move || {
+							let selected = selectedProviderView.get();
+							return if (selected.is_empty()) {defaultProvider.clone()} else {selected};
+						}}
+						on:change={move |event| selectedProviderChange.set(leptos::prelude::event_target_value(&event))}>
+						{names.iter().map(|name| view!{<option value={name.clone()}>{name.clone()}</option>}).collect_view()}
+					</select>
+				</label>
+				<button type="button" on:click={
+					let firstProvider = names[0].clone();
+					move |_| {
+						if (!moduleActions.lifecycle_isActive() || pendingUrlBegin.get_untracked().is_some())
+						{
+							return;
+						}
+						let selected = selectedProviderBegin.get_untracked();
+						let provider = if (selected.is_empty()) {firstProvider.clone()} else {selected};
+						let Some(connector) = config.get_untracked().imap_get(account).cloned() else {return};
+						mailOAuth_begin(provider,expect_toaster(),config.clone(),update.clone(),MailSyncIdentity::new(&connector),pendingUrlBegin.clone(),moduleActions.clone());
+					}
+				}><Translate key="MODULE_MAIL_OAUTH_SIGN_IN"/></button>
+				{pendingUrl.get().map(|url| view!{
+					<a href={url} target="_blank" rel="noopener noreferrer"><Translate key="MODULE_MAIL_OAUTH_OPEN"/></a>
+				})}
+			</div>
+		}.into_any();
+	}}}.into_any();
+}
+
+/// opens the provider page, then waits for its answer to keep the authorization in the account it was asked for
+fn mailOAuth_begin(provider: String, toaster: ToasterContext, config: ArcRwSignal<MailConfig>, update: ArcRwSignal<Cache>, syncIdentity: MailSyncIdentity, pendingUrl: ArcRwSignal<Option<String>>, moduleActions: ModuleActionFn)
+{
+	let moduleActionsTask = moduleActions.clone();
+	moduleActions.task_spawn(async move {
+		let apiResult = API_proxys_imap_oauthBegin(provider).await;
+		if (!moduleActionsTask.lifecycle_isActive())
+		{
+			return;
+		}
+		let Some(authorization) = toaster_api(&toaster,apiResult,None).await else {return};
+		if (!moduleActionsTask.lifecycle_isActive())
+		{
+			return;
+		}
+		// the link stays shown for a blocked popup
+		mailOAuthPage_open(&authorization.url);
+		pendingUrl.set(Some(authorization.url));
+
+		for _ in 0..MailOAuthLimits::POLL_MAXIMUM
+		{
+			TimeoutFuture::new(MailOAuthLimits::POLL_DELAY_MS).await;
+			if (!moduleActionsTask.lifecycle_isActive())
+			{
+				return;
+			}
+			let apiResult = API_proxys_imap_oauthFinish(authorization.state.clone()).await;
+			if (!moduleActionsTask.lifecycle_isActive())
+			{
+				return;
+			}
+			if (apiResult == Err(ImapError::OAUTH_PENDING))
+			{
+				continue;
+			}
+			pendingUrl.set(None);
+			if let Some(oauth) = toaster_api(&toaster,apiResult,Some("MODULE_MAIL_OAUTH_CONNECTED_DONE")).await
+				&& moduleActionsTask.lifecycle_isActive()
+			{
+				// the account may have moved while the user signed in, an edited or removed one keeps its login
+				let mut isChanged = false;
+				config.update(|config| {
+					if let Some(account) = config.account_find(&syncIdentity) && let Some(imap) = config.imap_getMut(account)
+					{
+						imap.oauth = Some(oauth);
+						isChanged = true;
+					}
+				});
+				if (isChanged)
+				{
+					update.update(|cache| cache.update());
+				}
+			}
+			return;
+		}
+		pendingUrl.set(None);
+		toastingErr(&toaster,ImapError::OAUTH_REJECTED).await;
+	});
+}
+
+/// saves in the accounts the refresh token their provider rotated during a proxy request.
+/// The server does not keep it, a rotation lost here makes the account sign in again
+#[derive(Clone)]
+pub(super) struct MailOAuthRotations
+{
+	config: ArcRwSignal<MailConfig>,
+	update: ArcRwSignal<Cache>,
+}
+
+impl MailOAuthRotations
+{
+	pub(super) fn new(config: ArcRwSignal<MailConfig>, update: ArcRwSignal<Cache>) -> Self
+	{
+		return Self { config, update };
+	}
+
+	/// the answer of a request made with connector, once the rotation it carries is saved
+	pub(super) fn keep<T>(&self, connector: &imap_connector, apiResult: Result<ImapProxyResponse<T>, ImapError>) -> Result<T, ImapError>
+	{
+		let response = apiResult?;
+		if let Some(rotated) = response.oauthRotated
+		{
+			self.rotated_set(connector, rotated);
+		}
+		return response.result;
+	}
+
+	/// every account signed in with the token of connector takes the rotated one, an account signed in again meanwhile keeps its own
+	pub(super) fn rotated_set(&self, connector: &imap_connector, rotated: imap_connector_oauth)
+	{
+		let Some(previous) = connector.oauth.as_ref() else {return};
+		let mut isChanged = false;
+		self.config.update(|config| {
+			for account in 0..=config.imapAccounts.len()
+			{
+				if let Some(imap) = config.imap_getMut(account) && imap.oauth.as_ref() == Some(previous)
+				{
+					imap.oauth = Some(rotated.clone());
+					isChanged = true;
+				}
+			}
+		});
+		if (isChanged)
+		{
+			self.update.update(|cache| cache.update());
+		}
+	}
+}
+
+#[cfg(feature="hydrate")]
+fn mailOAuthPage_open(url: &str)
+{
+	let Some(window) = web_sys::window() else {return};
+	let _ = window.open_with_url_and_target_and_features(url,"_blank","noopener,noreferrer");
+}
+
+#[cfg(not(feature="hydrate"))]
+fn mailOAuthPage_open(_: &str)
+{
+}
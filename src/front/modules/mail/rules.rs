@@ -0,0 +1,746 @@
+use leptos::prelude::{event_target_checked, event_target_value, AnyView, ArcRwSignal, AriaAttributes, ClassAttribute, CollectView, ElementChild, Get, IntoAny, OnAttribute, PropAttribute, Update};
+use leptos::view;
+use serde::{Deserialize, Serialize};
+use crate::api::proxys::imap_components::{ImapMail, ImapMailAction, ImapMailKey};
+use crate::front::modules::components::Cache;
+use crate::front::utils::translate::{Translate, TranslateText};
+use super::{MailConfig, MailSyncIdentity, MailTag, MailsInbox};
+
+struct MailRuleLimits;
+
+impl MailRuleLimits
+{
+	const RULE_MAXIMUM: usize = 64;
+	const CONDITION_MAXIMUM: usize = 16;
+	const SIZE_UNIT_BYTES: u64 = 1024;
+}
+
+/// part of the synchronized headers a condition looks at
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub(super) enum MailRuleField
+{
+	#[default]
+	FROM,
+	/// To and Cc
+	TO,
+	SUBJECT,
+	/// one of ImapMail::FILTER_HEADERS
+	HEADER,
+	/// size in KiB
+	SIZE_ABOVE,
+	SIZE_BELOW,
+	ATTACHMENT,
+}
+
+impl MailRuleField
+{
+	const ALL: [Self; 7] = [Self::FROM, Self::TO, Self::SUBJECT, Self::HEADER, Self::SIZE_ABOVE, Self::SIZE_BELOW, Self::ATTACHMENT];
+
+	fn value_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::FROM => "FROM",
+			Self::TO => "TO",
+			Self::SUBJECT => "SUBJECT",
+			Self::HEADER => "HEADER",
+			Self::SIZE_ABOVE => "SIZE_ABOVE",
+			Self::SIZE_BELOW => "SIZE_BELOW",
+			Self::ATTACHMENT => "ATTACHMENT",
+		};
+	}
+
+	fn fromValue(value: &str) -> Self
+	{
+		return Self::ALL.into_iter().find(|field| field.value_get() == value).unwrap_or_default();
+	}
+
+	fn translateKey_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::FROM => "MODULE_MAIL_RULE_FIELD_FROM",
+			Self::TO => "MODULE_MAIL_RULE_FIELD_TO",
+			Self::SUBJECT => "MODULE_MAIL_RULE_FIELD_SUBJECT",
+			Self::HEADER => "MODULE_MAIL_RULE_FIELD_HEADER",
+			Self::SIZE_ABOVE => "MODULE_MAIL_RULE_FIELD_SIZE_ABOVE",
+			Self::SIZE_BELOW => "MODULE_MAIL_RULE_FIELD_SIZE_BELOW",
+			Self::ATTACHMENT => "MODULE_MAIL_RULE_FIELD_ATTACHMENT",
+		};
+	}
+
+	fn hasValue(&self) -> bool
+	{
+		return *self != Self::ATTACHMENT;
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub(super) struct MailRuleCondition
+{
+	#[serde(default)]
+	field: MailRuleField,
+	#[serde(default)]
+	header: String,
+	/// text searched without case, or a size in KiB
+	#[serde(default)]
+	value: String,
+	#[serde(default)]
+	negated: bool,
+}
+
+impl MailRuleCondition
+{
+	/// an unfinished condition (empty text, invalid size) never matches, even negated
+	fn matches(&self, mail: &ImapMail) -> bool
+	{
+		let text = self.value.trim().to_lowercase();
+		let contains = |value: &str| value.to_lowercase().contains(&text);
+		let isMatching = match self.field
+		{
+			MailRuleField::FROM | MailRuleField::TO | MailRuleField::SUBJECT | MailRuleField::HEADER if text.is_empty() => return false,
+			MailRuleField::FROM => contains(&mail.from),
+			MailRuleField::TO => contains(&mail.to) || contains(&mail.cc),
+			MailRuleField::SUBJECT => mail.subject.as_deref().is_some_and(contains),
+			MailRuleField::HEADER => mail.header_get(&self.header).is_some_and(contains),
+			MailRuleField::SIZE_ABOVE | MailRuleField::SIZE_BELOW =>
+			{
+				let Some(limit) = self.sizeBytes_get() else {return false};
+				if (self.field == MailRuleField::SIZE_ABOVE) {u64::from(mail.size) > limit} else {u64::from(mail.size) < limit}
+			},
+			MailRuleField::ATTACHMENT => mail.hasAttachment(),
+		};
+		return isMatching != self.negated;
+	}
+
+	fn sizeBytes_get(&self) -> Option<u64>
+	{
+		return self.value.trim().parse::<u64>().ok().map(|size| size.saturating_mul(MailRuleLimits::SIZE_UNIT_BYTES));
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub(super) enum MailRuleCombination
+{
+	#[default]
+	ALL,
+	ANY,
+}
+
+/// change of the mail on the IMAP server, done by the browser after the synchronization
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub(super) enum MailRuleServerAction
+{
+	#[default]
+	NONE,
+	FLAG,
+	/// mailbox of the account receiving the mail, the accounts without it are skipped
+	MOVE(String),
+}
+
+impl MailRuleServerAction
+{
+	fn value_get(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::NONE => "NONE",
+			Self::FLAG => "FLAG",
+			Self::MOVE(_) => "MOVE",
+		};
+	}
+}
+
+/// user rule, kept in the encrypted module content with the rest of the MailConfig
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub(super) struct MailRule
+{
+	#[serde(default)]
+	name: String,
+	#[serde(default = "MailRule::enabled_default")]
+	enabled: bool,
+	#[serde(default)]
+	combination: MailRuleCombination,
+	#[serde(default)]
+	conditions: Vec<MailRuleCondition>,
+	#[serde(default)]
+	hide: bool,
+	#[serde(default)]
+	highlight: bool,
+	/// label of a tag shown on the matching mails
+	#[serde(default)]
+	tag: String,
+	/// #rrggbb accent of the matching rows and of the tag
+	#[serde(default)]
+	color: Option<String>,
+	#[serde(default)]
+	serverAction: MailRuleServerAction,
+}
+
+impl Default for MailRule
+{
+	fn default() -> Self
+	{
+		return Self {
+			name: String::new(),
+			enabled: true,
+			combination: MailRuleCombination::ALL,
+			conditions: vec![MailRuleCondition::default()],
+			hide: false,
+			highlight: false,
+			tag: String::new(),
+			color: None,
+			serverAction: MailRuleServerAction::NONE,
+		};
+	}
+}
+
+impl MailRule
+{
+	fn enabled_default() -> bool
+	{
+		return true;
+	}
+
+	fn matches(&self, mail: &ImapMail) -> bool
+	{
+		if (!self.enabled || self.conditions.is_empty())
+		{
+			return false;
+		}
+		return match self.combination
+		{
+			MailRuleCombination::ALL => self.conditions.iter().all(|condition| condition.matches(mail)),
+			MailRuleCombination::ANY => self.conditions.iter().any(|condition| condition.matches(mail)),
+		};
+	}
+
+	/// a color is written in a style attribute, only the value of a color input is kept
+	fn color_get(&self) -> Option<String>
+	{
+		let color = self.color.as_deref()?.trim();
+		let digits = color.strip_prefix('#')?;
+		if (digits.len() != 6 || !digits.chars().all(|character| character.is_ascii_hexdigit()))
+		{
+			return None;
+		}
+		return Some(color.to_ascii_lowercase());
+	}
+}
+
+/// display of a mail after the rules, the first matching rule with a color gives it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct MailRuleOutcome
+{
+	pub(super) hidden: bool,
+	pub(super) highlighted: bool,
+	pub(super) tags: Vec<MailTag>,
+	pub(super) color: Option<String>,
+}
+
+impl MailRuleOutcome
+{
+	pub(super) fn style_get(&self) -> String
+	{
+		return self.color.as_ref().map(|color| format!("--mail-rule-color:{}",color)).unwrap_or_default();
+	}
+}
+
+pub(super) fn mailRules_apply(rules: &[MailRule], mail: &ImapMail) -> MailRuleOutcome
+{
+	let mut outcome = MailRuleOutcome::default();
+	for rule in rules.iter().filter(|rule| rule.matches(mail))
+	{
+		outcome.hidden |= rule.hide;
+		outcome.highlighted |= rule.highlight;
+		let color = rule.color_get();
+		let label = rule.tag.trim();
+		if (!label.is_empty() && !outcome.tags.iter().any(|tag| tag.label.eq_ignore_ascii_case(label)))
+		{
+			let mut tag = MailTag::new(label);
+			if let Some(color) = &color
+			{
+				tag.color = color.clone();
+			}
+			outcome.tags.push(tag);
+		}
+		if (outcome.color.is_none())
+		{
+			outcome.color = color;
+		}
+	}
+	return outcome;
+}
+
+pub(super) fn mailRules_hasTag(rules: &[MailRule]) -> bool
+{
+	return rules.iter().any(|rule| rule.enabled && !rule.tag.trim().is_empty());
+}
+
+/// IMAP changes asked by the rules for the mails a synchronization added, grouped by account and action.
+/// The first matching rule with a server action wins; the mails already cached were handled by an earlier
+/// synchronization and are left to the user
+pub(super) fn mailRules_serverActions(rules: &[MailRule], inbox: &MailsInbox, addedMails: &[(MailSyncIdentity,Vec<ImapMailKey>)]) -> Vec<(usize,ImapMailAction,Vec<ImapMailKey>)>
+{
+	let mut actions = Vec::<(usize,ImapMailAction,Vec<ImapMailKey>)>::new();
+	if (rules.iter().all(|rule| rule.serverAction == MailRuleServerAction::NONE))
+	{
+		return actions;
+	}
+	for (syncIdentity,addedKeys) in addedMails
+	{
+		let Some(account) = inbox.accountSynced_find(syncIdentity) else {continue};
+		let Some(content) = inbox.account_get(account) else {continue};
+		let moveTargets = content.moveTargets_get();
+		for (key,mail) in addedKeys.iter().filter_map(|key| Some((key,content.mailsData.get(key)?)))
+		{
+			let action = rules.iter().filter(|rule| rule.serverAction != MailRuleServerAction::NONE && rule.matches(mail)).find_map(|rule| {
+				return match &rule.serverAction
+				{
+					MailRuleServerAction::NONE => None,
+					MailRuleServerAction::FLAG => (!mail.flagged).then_some(ImapMailAction::FLAGGED(true)),
+					MailRuleServerAction::MOVE(target) => (key.boxName != *target && moveTargets.contains(target)).then(|| ImapMailAction::MOVE(target.clone())),
+				};
+			});
+			let Some(action) = action else {continue};
+			match actions.iter_mut().find(|(actionAccount,existing,_)| *actionAccount == account && *existing == action)
+			{
+				Some((_,_,keys)) => keys.push(key.clone()),
+				None => actions.push((account,action,vec![key.clone()])),
+			}
+		}
+	}
+	for (_,_,keys) in &mut actions
+	{
+		keys.sort_by(|left,right| (&left.boxName,left.uid).cmp(&(&right.boxName,right.uid)));
+	}
+	return actions;
+}
+
+#[derive(Clone)]
+struct MailRuleEditor
+{
+	config: ArcRwSignal<MailConfig>,
+	update: ArcRwSignal<Cache>,
+	index: usize,
+}
+
+impl MailRuleEditor
+{
+	fn rules_edit(config: &ArcRwSignal<MailConfig>, update: &ArcRwSignal<Cache>, change: impl FnOnce(&mut Vec<MailRule>))
+	{
+		config.update(|config| change(&mut config.rules));
+		update.update(|cache| cache.update());
+	}
+
+	fn edit(&self, change: impl FnOnce(&mut MailRule))
+	{
+		let index = self.index;
+		Self::rules_edit(&self.config,&self.update,|rules| {
+			if let Some(rule) = rules.get_mut(index)
+			{
+				change(rule);
+			}
+		});
+	}
+
+	fn conditionEdit(&self, condition: usize, change: impl FnOnce(&mut MailRuleCondition))
+	{
+		self.edit(|rule| {
+			if let Some(condition) = rule.conditions.get_mut(condition)
+			{
+				change(condition);
+			}
+		});
+	}
+}
+
+pub(super) fn mailRules_configDraw(config: ArcRwSignal<MailConfig>, update: ArcRwSignal<Cache>) -> AnyView
+{
+	let configAdd = config.clone();
+	let updateAdd = update.clone();
+	let configList = config.clone();
+	let isFull = move || config.get().rules.len() >= MailRuleLimits::RULE_MAXIMUM;
+	return view!{
+		<section class="module_mail_rules">
+			<span class="module_config_section_title"><Translate key="MODULE_MAIL_RULES"/></span>
+			<p class="module_mail_rules_note"><Translate key="MODULE_MAIL_RULES_NOTE"/></p>
+			{move || configList.get().rules.into_iter().enumerate().map(|(index,rule)| {
+				let editor = MailRuleEditor {config: configList.clone(), update: update.clone(), index};
+				return mailRules_ruleDraw(editor,rule);
+			}).collect_view()}
+			<div class="module_config_actions">
+				<button type="button" disabled={isFull} on:click={move |_| {
+					MailRuleEditor::rules_edit(&configAdd,&updateAdd,|rules| {
+						if (rules.len() < MailRuleLimits::RULE_MAXIMUM)
+						{
+							rules.push(MailRule::default());
+						}
+					});
+				}}><Translate key="MODULE_MAIL_RULE_ADD"/></button>
+			</div>
+		</section>
+	}.into_any();
+}
+
+fn mailRules_ruleDraw(editor: MailRuleEditor, rule: MailRule) -> AnyView
+{
+	let conditionsFull = rule.conditions.len() >= MailRuleLimits::CONDITION_MAXIMUM;
+	let conditions = rule.conditions.iter().cloned().enumerate().map(|(conditionIndex,condition)| {
+		return mailRules_conditionDraw(editor.clone(),conditionIndex,condition);
+	}).collect_view();
+	let moveTarget = match &rule.serverAction
+	{
+		MailRuleServerAction::MOVE(target) => Some(target.clone()),
+		_ => None,
+	};
+	let (nameEditor,enabledEditor,combinationEditor,conditionAddEditor) = (editor.clone(),editor.clone(),editor.clone(),editor.clone());
+	let (hideEditor,highlightEditor,tagEditor,colorSwitchEditor,colorEditor) = (editor.clone(),editor.clone(),editor.clone(),editor.clone(),editor.clone());
+	let (serverActionEditor,moveTargetEditor,removeEditor) = (editor.clone(),editor.clone(),editor);
+	let color = rule.color_get();
+	return view!{
+		<fieldset class="module_mail_rule">
+			<div class="module_mail_rule_header">
+				<label class="module_config_field">
+					<TranslateText key="MODULE_MAIL_RULE_NAME"/>
+					<input type="text" class="module_config_input" prop:value={rule.name.clone()}
+						on:change={move |event| {
+							let value = event_target_value(&event);
+							nameEditor.edit(|rule| rule.name = value);
+						}}/>
+				</label>
+				<label class="module_mail_rule_check">
+					<input type="checkbox" prop:checked={rule.enabled}
+						on:change={move |event| {
+							let enabled = event_target_checked(&event);
+							enabledEditor.edit(|rule| rule.enabled = enabled);
+						}}/>
+					<span><TranslateText key="MODULE_MAIL_RULE_ENABLED"/></span>
+				</label>
+				<label class="module_config_field">
+					<TranslateText key="MODULE_MAIL_RULE_COMBINATION"/>
+					<select class="module_config_input" prop:value={if rule.combination == MailRuleCombination::ANY {"ANY"} else {"ALL"}}
+						on:change={move |event| {
+							let combination = if (event_target_value(&event) == "ANY") {MailRuleCombination::ANY} else {MailRuleCombination::ALL};
+							combinationEditor.edit(|rule| rule.combination = combination);
+						}}>
+						<option value="ALL"><TranslateText key="MODULE_MAIL_RULE_MATCH_ALL"/></option>
+						<option value="ANY"><TranslateText key="MODULE_MAIL_RULE_MATCH_ANY"/></option>
+					</select>
+				</label>
+			</div>
+			<div class="module_mail_rule_conditions">{conditions}</div>
+			<div class="module_config_actions">
+				<button type="button" disabled={conditionsFull} on:click={move |_| {
+					conditionAddEditor.edit(|rule| {
+						if (rule.conditions.len() < MailRuleLimits::CONDITION_MAXIMUM)
+						{
+							rule.conditions.push(MailRuleCondition::default());
+						}
+					});
+				}}><TranslateText key="MODULE_MAIL_RULE_CONDITION_ADD"/></button>
+			</div>
+			<div class="module_mail_rule_actions">
+				<label class="module_mail_rule_check">
+					<input type="checkbox" prop:checked={rule.hide}
+						on:change={move |event| {
+							let hide = event_target_checked(&event);
+							hideEditor.edit(|rule| rule.hide = hide);
+						}}/>
+					<span><TranslateText key="MODULE_MAIL_RULE_HIDE"/></span>
+				</label>
+				<label class="module_mail_rule_check">
+					<input type="checkbox" prop:checked={rule.highlight}
+						on:change={move |event| {
+							let highlight = event_target_checked(&event);
+							highlightEditor.edit(|rule| rule.highlight = highlight);
+						}}/>
+					<span><TranslateText key="MODULE_MAIL_RULE_HIGHLIGHT"/></span>
+				</label>
+				<label class="module_config_field">
+					<TranslateText key="MODULE_MAIL_RULE_TAG"/>
+					<input type="text" class="module_config_input" prop:value={rule.tag.clone()}
+						on:change={move |event| {
+							let value = event_target_value(&event);
+							tagEditor.edit(|rule| rule.tag = value);
+						}}/>
+				</label>
+				<label class="module_mail_rule_check">
+					<input type="checkbox" prop:checked={color.is_some()}
+						on:change={move |event| {
+							let isColored = event_target_checked(&event);
+							colorSwitchEditor.edit(|rule| rule.color = isColored.then(|| "#f5a623".to_string()));
+						}}/>
+					<span><TranslateText key="MODULE_MAIL_RULE_COLOR"/></span>
+				</label>
+				<input type="color" class="module_mail_rule_color" disabled={color.is_none()} prop:value={color.clone().unwrap_or_else(|| "#f5a623".to_string())}
+					on:change={move |event| {
+						let value = event_target_value(&event);
+						colorEditor.edit(|rule| rule.color = Some(value));
+					}}/>
+			</div>
+			<div class="module_mail_rule_actions">
+				<label class="module_config_field">
+					<TranslateText key="MODULE_MAIL_RULE_SERVER_ACTION"/>
+					<select class="module_config_input" prop:value={rule.serverAction.value_get()}
+						on:change={move |event| {
+							let value = event_target_value(&event);
+							serverActionEditor.edit(|rule| rule.serverAction = match value.as_str()
+							{
+								"FLAG" => MailRuleServerAction::FLAG,
+								"MOVE" => MailRuleServerAction::MOVE(String::new()),
+								_ => MailRuleServerAction::NONE,
+							});
+						}}>
+						<option value="NONE"><TranslateText key="MODULE_MAIL_RULE_SERVER_NONE"/></option>
+						<option value="FLAG"><TranslateText key="MODULE_MAIL_RULE_SERVER_FLAG"/></option>
+						<option value="MOVE"><TranslateText key="MODULE_MAIL_RULE_SERVER_MOVE"/></option>
+					</select>
+				</label>
+				{moveTarget.map(|target| view!{
+					<label class="module_config_field">
+						<TranslateText key="MODULE_MAIL_RULE_MOVE_TARGET"/>
+						<input type="text" class="module_config_input" prop:value={target}
+							on:change={move |event| {
+								let value = event_target_value(&event).trim().to_string();
+								moveTargetEditor.edit(|rule| rule.serverAction = MailRuleServerAction::MOVE(value));
+							}}/>
+					</label>
+				})}
+				<button type="button" class="module_mail_rule_remove" on:click={move |_| {
+					let index = removeEditor.index;
+					MailRuleEditor::rules_edit(&removeEditor.config,&removeEditor.update,|rules| {
+						if (index < rules.len())
+						{
+							rules.remove(index);
+						}
+					});
+				}}><TranslateText key="MODULE_MAIL_RULE_REMOVE"/></button>
+			</div>
+		</fieldset>
+	}.into_any();
+}
+
+fn mailRules_conditionDraw(editor: MailRuleEditor, index: usize, condition: MailRuleCondition) -> AnyView
+{
+	let (fieldEditor,headerEditor,valueEditor,negatedEditor,removeEditor) = (editor.clone(),editor.clone(),editor.clone(),editor.clone(),editor);
+	let headerSelect = (condition.field == MailRuleField::HEADER).then(|| view!{
+		<select class="module_config_input" prop:value={condition.header.clone()}
+			on:change={move |event| {
+				let header = event_target_value(&event);
+				headerEditor.conditionEdit(index,|condition| condition.header = header);
+			}}>
+			{ImapMail::FILTER_HEADERS.into_iter().map(|header| view!{<option value={header}>{header}</option>}).collect_view()}
+		</select>
+	});
+	let valueInput = condition.field.hasValue().then(|| {
+		let inputType = if (matches!(condition.field,MailRuleField::SIZE_ABOVE | MailRuleField::SIZE_BELOW)) {"number"} else {"text"};
+		view!{
+			<input type={inputType} min="0" class="module_config_input" prop:value={condition.value.clone()}
+				on:change={move |event| {
+					let value = event_target_value(&event);
+					valueEditor.conditionEdit(index,|condition| condition.value = value);
+				}}/>
+		}
+	});
+	return view!{
+		<div class="module_mail_rule_condition">
+			<label class="module_mail_rule_check">
+				<input type="checkbox" prop:checked={condition.negated}
+					on:change={move |event| {
+						let negated = event_target_checked(&event);
+						negatedEditor.conditionEdit(index,|condition| condition.negated = negated);
+					}}/>
+				<span><TranslateText key="MODULE_MAIL_RULE_NEGATED"/></span>
+			</label>
+			<select class="module_config_input" prop:value={condition.field.value_get()}
+				on:change={move |event| {
+					let field = MailRuleField::fromValue(&event_target_value(&event));
+					fieldEditor.conditionEdit(index,|condition| {
+						condition.field = field;
+						if (field == MailRuleField::HEADER && condition.header.is_empty())
+						{
+							condition.header = ImapMail::FILTER_HEADERS[0].to_string();
+						}
+					});
+				}}>
+				{MailRuleField::ALL.into_iter().map(|field| view!{
+					<option value={field.value_get()}><TranslateText key={field.translateKey_get()}/></option>
+				}).collect_view()}
+			</select>
+			{headerSelect}
+			{valueInput}
+			<button type="button" class="module_mail_rule_condition_remove" on:click={move |_| {
+				removeEditor.edit(|rule| {
+					if (index < rule.conditions.len())
+					{
+						rule.conditions.remove(index);
+					}
+				});
+			}}>
+				<span aria-hidden="true">{"×"}</span>
+				<span class="visually_hidden"><TranslateText key="MODULE_MAIL_RULE_CONDITION_REMOVE"/></span>
+			</button>
+		</div>
+	}.into_any();
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::api::proxys::imap_components::imap_connector;
+	use crate::front::modules::mail::MailsContent;
+
+	fn mail_get(from: &str, subject: &str, size: u32) -> ImapMail
+	{
+		return ImapMail {
+			from: from.to_string(),
+			to: "me@example.com".to_string(),
+			subject: Some(subject.to_string()),
+			size,
+			..Default::default()
+		};
+	}
+
+	fn condition_get(field: MailRuleField, value: &str) -> MailRuleCondition
+	{
+		return MailRuleCondition {field, value: value.to_string(), ..Default::default()};
+	}
+
+	fn rule_get(combination: MailRuleCombination, conditions: Vec<MailRuleCondition>) -> MailRule
+	{
+		return MailRule {combination, conditions, ..Default::default()};
+	}
+
+	#[test]
+	fn mailRule_combinesConditionsWithAllOrAny()
+	{
+		let newsletter = mail_get("News <news@shop.example>","Weekly deals",300 * 1024);
+		let conditions = vec![
+			condition_get(MailRuleField::FROM,"SHOP.example"),
+			condition_get(MailRuleField::SIZE_ABOVE,"500"),
+		];
+
+		assert!(!rule_get(MailRuleCombination::ALL,conditions.clone()).matches(&newsletter));
+		assert!(rule_get(MailRuleCombination::ANY,conditions.clone()).matches(&newsletter));
+
+		let mut negated = conditions;
+		negated[1].negated = true;
+		assert!(rule_get(MailRuleCombination::ALL,negated).matches(&newsletter));
+	}
+
+	#[test]
+	fn mailRule_unfinishedConditionsNeverMatch()
+	{
+		let mail = mail_get("someone@example.com","Hello",10);
+		let mut empty = condition_get(MailRuleField::SUBJECT,"  ");
+		assert!(!empty.matches(&mail));
+		empty.negated = true;
+		assert!(!empty.matches(&mail));
+		assert!(!condition_get(MailRuleField::SIZE_BELOW,"small").matches(&mail));
+		assert!(!rule_get(MailRuleCombination::ALL,Vec::new()).matches(&mail));
+
+		let mut disabled = rule_get(MailRuleCombination::ALL,vec![condition_get(MailRuleField::SUBJECT,"hello")]);
+		assert!(disabled.matches(&mail));
+		disabled.enabled = false;
+		assert!(!disabled.matches(&mail));
+	}
+
+	#[test]
+	fn mailRule_readsSynchronizedHeadersAndAttachments()
+	{
+		let mut mail = mail_get("list@example.com","Digest",10);
+		mail.cc = "team@example.org".to_string();
+		mail.headers = vec![
+			("List-Id".to_string(),"<dev.lists.example.com>".to_string()),
+			("Content-Type".to_string(),"multipart/mixed; boundary=x".to_string()),
+		];
+		let header = MailRuleCondition {field: MailRuleField::HEADER, header: "list-id".to_string(), value: "dev.lists".to_string(), negated: false};
+
+		assert!(header.matches(&mail));
+		assert!(condition_get(MailRuleField::TO,"team@").matches(&mail));
+		assert!(condition_get(MailRuleField::ATTACHMENT,"").matches(&mail));
+		mail.headers.pop();
+		assert!(!condition_get(MailRuleField::ATTACHMENT,"").matches(&mail));
+	}
+
+	#[test]
+	fn mailRules_mergeDisplayOutcomes()
+	{
+		let mail = mail_get("alerts@monitoring.example","Disk full",10);
+		let rules = vec![
+			MailRule {
+				hide: true,
+				tag: "Ops".to_string(),
+				color: Some("#FF0000\" onclick=\"x".to_string()),
+				..rule_get(MailRuleCombination::ALL,vec![condition_get(MailRuleField::FROM,"monitoring")])
+			},
+			MailRule {
+				highlight: true,
+				tag: "ops".to_string(),
+				color: Some("#00AA00".to_string()),
+				..rule_get(MailRuleCombination::ALL,vec![condition_get(MailRuleField::SUBJECT,"disk")])
+			},
+		];
+
+		let outcome = mailRules_apply(&rules,&mail);
+
+		assert!(outcome.hidden);
+		assert!(outcome.highlighted);
+		assert_eq!(outcome.tags.iter().map(|tag| tag.label.as_str()).collect::<Vec<_>>(),vec!["Ops"]);
+		assert_eq!(outcome.color.as_deref(),Some("#00aa00"));
+		assert_eq!(outcome.style_get(),"--mail-rule-color:#00aa00");
+		assert!(mailRules_hasTag(&rules));
+		assert_eq!(mailRules_apply(&rules,&mail_get("friend@example.com","Lunch",10)),MailRuleOutcome::default());
+	}
+
+	#[test]
+	fn mailRules_serverActionsSkipDoneMailsAndUnknownMailboxes()
+	{
+		let key = |boxName: &str, uid: u32| ImapMailKey {boxName: boxName.to_string(), uidValidity: 1, uid};
+		let identity = |host: &str| MailSyncIdentity::new(&imap_connector {host: host.to_string(), ..Default::default()});
+		let mut content = MailsContent::default();
+		content.mailsData.insert(key("INBOX",2),mail_get("news@shop.example","Deals",10));
+		content.mailsData.insert(key("INBOX",1),mail_get("news@shop.example","More deals",10));
+		content.mailsData.insert(key("Newsletters",3),mail_get("news@shop.example","Old deals",10));
+		content.mailsData.insert(key("INBOX",4),ImapMail {flagged: true, ..mail_get("boss@work.example","Urgent",10)});
+		content.mailsData.insert(key("INBOX",5),mail_get("boss@work.example","Today",10));
+		content.mailsData.insert(key("INBOX",6),mail_get("news@shop.example","Kept by the user",10));
+		content.mailboxes = Some([("INBOX".to_string(),None),("Newsletters".to_string(),None)].into_iter().collect());
+		content.syncIdentity = Some(identity("imap.work.example"));
+		let inbox = MailsInbox {accounts: vec![content.clone(),MailsContent {
+			mailboxes: Some([("INBOX".to_string(),None)].into_iter().collect()),
+			syncIdentity: Some(identity("imap.home.example")),
+			..content
+		}]};
+		let rules = vec![
+			MailRule {
+				serverAction: MailRuleServerAction::MOVE("Newsletters".to_string()),
+				..rule_get(MailRuleCombination::ALL,vec![condition_get(MailRuleField::FROM,"shop.example")])
+			},
+			MailRule {
+				serverAction: MailRuleServerAction::FLAG,
+				..rule_get(MailRuleCombination::ALL,vec![condition_get(MailRuleField::FROM,"boss@")])
+			},
+		];
+		// the mail 6 was cached by an earlier synchronization, the removed account is skipped
+		let added = [1,2,3,4,5].map(|uid| key(if (uid == 3) {"Newsletters"} else {"INBOX"},uid)).to_vec();
+		let addedMails = vec![
+			(identity("imap.home.example"),added.clone()),
+			(identity("imap.work.example"),added.clone()),
+			(identity("imap.removed.example"),added),
+		];
+
+		let mut actions = mailRules_serverActions(&rules,&inbox,&addedMails);
+		actions.sort_by_key(|(account,action,_)| (*account,action == &ImapMailAction::FLAGGED(true)));
+
+		assert_eq!(actions,vec![
+			(0,ImapMailAction::MOVE("Newsletters".to_string()),vec![key("INBOX",1),key("INBOX",2)]),
+			(0,ImapMailAction::FLAGGED(true),vec![key("INBOX",5)]),
+			(1,ImapMailAction::FLAGGED(true),vec![key("INBOX",5)]),
+		]);
+		assert!(mailRules_serverActions(&[],&inbox,&addedMails).is_empty());
+		assert!(mailRules_serverActions(&rules,&inbox,&[]).is_empty());
+	}
+}
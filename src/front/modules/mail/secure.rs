@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+use std::fmt;
+use leptos::prelude::{ArcRwSignal, AriaAttributes, ClassAttribute, ElementChild, Get, GlobalAttributes, IntoAny, OnAttribute, PropAttribute, Update, event_target_value};
+use leptos::prelude::AnyView;
+use leptos::view;
+use serde::{Deserialize, Serialize};
+use crate::api::proxys::imap_components::ImapMail;
+use crate::front::modules::components::{Cache, FieldHelper, FieldHelperType};
+use crate::front::utils::translate::Translate;
+use super::{MailConfig, MailSenderAddress};
+#[cfg(feature="mail_secure")]
+use {
+	mailparse::ParsedMail,
+	crate::api::proxys::imap_components::{Attachment, ImapMailContentType, ImapSecuredKind, ImapSecuredPart},
+	mime::{attachment_get, crlf_canonicalize, html_toText},
+	openpgp::OpenPgpKey,
+	rsa::RsaPrivateKey,
+	smime::CmsContent,
+};
+
+#[cfg(feature="mail_secure")]
+mod hash;
+#[cfg(feature="mail_secure")]
+mod mime;
+#[cfg(feature="mail_secure")]
+mod openpgp;
+#[cfg(feature="mail_secure")]
+mod smime;
+#[cfg(feature="mail_secure")]
+mod symmetric;
+
+#[cfg(feature="mail_secure")]
+struct MailSecureLimits;
+
+#[cfg(feature="mail_secure")]
+
+impl MailSecureLimits
+{
+	// a signed then encrypted message nests two entities, a few more are tolerated
+	const NESTING_MAXIMUM: usize = 6;
+}
+
+/// keys of the user, kept in the module content only: the server never receives them
+#[derive(Serialize,Deserialize,Clone,Default,PartialEq)]
+pub(super) struct MailSecureKeys
+{
+	#[serde(default)]
+	openpgpPublicKeys: String,
+	#[serde(default)]
+	openpgpSecretKey: String,
+	#[serde(default)]
+	openpgpPassphrase: String,
+	#[serde(default)]
+	smimePrivateKey: String,
+	/// certificates of the contacts whose S/MIME signatures are trusted, PEM
+	#[serde(default)]
+	smimeCertificates: String,
+}
+
+impl fmt::Debug for MailSecureKeys
+{
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		return formatter.debug_struct("MailSecureKeys")
+			.field("openpgpPublicKeys",&!self.openpgpPublicKeys.is_empty())
+			.field("openpgpSecretKey",&!self.openpgpSecretKey.is_empty())
+			.field("smimePrivateKey",&!self.smimePrivateKey.is_empty())
+			.field("smimeCertificates",&!self.smimeCertificates.is_empty())
+			.finish_non_exhaustive();
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(not(feature="mail_secure"), allow(dead_code))]
+pub(super) enum MailSignatureStatus
+{
+	/// name or address of the signer, a trusted one matching the sender
+	Valid(String),
+	/// name or address of the signer, unknown to the user or not matching the sender
+	Untrusted(String),
+	Invalid,
+	/// key ID or certificate serial of the signer
+	UnknownKey(String),
+	Unsupported,
+}
+
+/// signer of a signature whose value is right, trusted when its key or certificate is configured by the user
+#[cfg(feature="mail_secure")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct MailSigner
+{
+	pub(super) label: String,
+	/// user IDs or certificate addresses, each one may carry a display name
+	pub(super) addresses: Vec<String>,
+	pub(super) isTrusted: bool,
+}
+
+#[cfg(feature="mail_secure")]
+impl MailSigner
+{
+	/// a signature only vouches for the sender when the signer is trusted and one of its addresses is the From one
+	fn status_get(self, from: Option<&MailSenderAddress>) -> MailSignatureStatus
+	{
+		let isSender = from.is_some_and(|from| {
+			return self.addresses.iter().any(|address| MailSenderAddress::from_candidate(address).as_ref() == Some(from));
+		});
+		if (self.isTrusted && isSender)
+		{
+			return MailSignatureStatus::Valid(self.label);
+		}
+		return MailSignatureStatus::Untrusted(self.label);
+	}
+}
+
+/// signer of a right signature, or the status of the others
+#[cfg(feature="mail_secure")]
+pub(super) type MailSignatureCheck = Result<MailSigner,MailSignatureStatus>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(not(feature="mail_secure"), allow(dead_code))]
+pub(super) enum MailSecureError
+{
+	NoKey,
+	KeyMismatch,
+	KeyInvalid,
+	Unsupported,
+	Invalid,
+	/// this installation was built without the mail_secure feature
+	#[cfg_attr(feature="mail_secure", allow(dead_code))]
+	Disabled,
+}
+
+impl MailSecureError
+{
+	fn translateKey_get(self) -> &'static str
+	{
+		return match self
+		{
+			Self::NoKey => "MODULE_MAIL_SECURE_ERROR_NO_KEY",
+			Self::KeyMismatch => "MODULE_MAIL_SECURE_ERROR_KEY_MISMATCH",
+			Self::KeyInvalid => "MODULE_MAIL_SECURE_ERROR_KEY_INVALID",
+			Self::Unsupported => "MODULE_MAIL_SECURE_ERROR_UNSUPPORTED",
+			Self::Invalid => "MODULE_MAIL_SECURE_ERROR_INVALID",
+			Self::Disabled => "MODULE_MAIL_SECURE_ERROR_DISABLED",
+		};
+	}
+}
+
+/// what the browser could check of a signed or encrypted mail
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct MailSecureState
+{
+	signature: Option<MailSignatureStatus>,
+	isEncrypted: bool,
+	error: Option<MailSecureError>,
+}
+
+/// verifies the signature or decrypts the secured part of a mail content, from is the sender the signature must match.
+/// A decrypted content replaces the one of the server, which could only see the encrypted data
+#[cfg(feature="mail_secure")]
+pub(super) fn mailSecure_apply(mut mail: ImapMail, keys: &MailSecureKeys, from: Option<&MailSenderAddress>) -> (ImapMail,Option<MailSecureState>)
+{
+	let Some(secured) = mail.secured.take() else {return (mail,None)};
+	let mut opener = MailSecureOpener::new(keys,from);
+	opener.secured_open(&secured);
+	if let Some(content) = opener.content_get()
+	{
+		mail.content = content;
+	}
+	mail.attachement.append(&mut opener.attachments);
+	return (mail,Some(opener.state));
+}
+
+/// the secured part stays closed, the mail shows why
+#[cfg(not(feature="mail_secure"))]
+pub(super) fn mailSecure_apply(mail: ImapMail, _keys: &MailSecureKeys, _from: Option<&MailSenderAddress>) -> (ImapMail,Option<MailSecureState>)
+{
+	let state = mail.secured.is_some().then_some(MailSecureState {signature: None,isEncrypted: false,error: Some(MailSecureError::Disabled)});
+	return (mail,state);
+}
+
+pub(super) fn mailSecure_draw(state: MailSecureState) -> AnyView
+{
+	let encrypted = state.isEncrypted.then(|| view!{
+		<span class="module_mail_secure_badge module_mail_secure_badge--valid">
+			<i class="iconoir-lock" aria-hidden="true"/>{" "}<Translate key="MODULE_MAIL_SECURE_ENCRYPTED"/>
+		</span>
+	});
+	let signature = state.signature.map(|signature| {
+		let (modifier,icon) = match &signature
+		{
+			MailSignatureStatus::Valid(_) => ("module_mail_secure_badge--valid","iconoir-check-circle"),
+			MailSignatureStatus::Invalid => ("module_mail_secure_badge--invalid","iconoir-xmark-circle"),
+			MailSignatureStatus::Untrusted(_) | MailSignatureStatus::UnknownKey(_) | MailSignatureStatus::Unsupported => ("module_mail_secure_badge--unknown","iconoir-help-circle"),
+		};
+		let (key,params) = match signature
+		{
+			MailSignatureStatus::Valid(signer) => ("MODULE_MAIL_SECURE_SIGNATURE_VALID",HashMap::from([("signer".to_string(),signer)])),
+			MailSignatureStatus::Untrusted(signer) => ("MODULE_MAIL_SECURE_SIGNATURE_UNTRUSTED",HashMap::from([("signer".to_string(),signer)])),
+			MailSignatureStatus::Invalid => ("MODULE_MAIL_SECURE_SIGNATURE_INVALID",HashMap::new()),
+			MailSignatureStatus::UnknownKey(keyId) => ("MODULE_MAIL_SECURE_SIGNATURE_UNKNOWN_KEY",HashMap::from([("key".to_string(),keyId)])),
+			MailSignatureStatus::Unsupported => ("MODULE_MAIL_SECURE_SIGNATURE_UNSUPPORTED",HashMap::new()),
+		};
+		view!{
+			<span class={format!("module_mail_secure_badge {}",modifier)}>
+				<i class={icon} aria-hidden="true"/>{" "}<Translate key={key} params={params}/>
+			</span>
+		}
+	});
+	let error = state.error.map(|error| view!{
+		<span class="module_mail_secure_badge module_mail_secure_badge--invalid">
+			<i class="iconoir-warning-triangle" aria-hidden="true"/>{" "}<Translate key={error.translateKey_get()}/>
+		</span>
+	});
+	return view!{
+		<div class="module_mail_secure" role="status">
+			{encrypted}
+			{signature}
+			{error}
+		</div>
+	}.into_any();
+}
+
+/// keys section of the configuration
+pub(super) fn mailSecure_configDraw(config: ArcRwSignal<MailConfig>, update: ArcRwSignal<Cache>) -> AnyView
+{
+	let mut passphraseF = FieldHelper::new(&config,&update,"MODULE_MAIL_SECURE_OPENPGP_PASSPHRASE",
+	                                       |d| d.get().secureKeys.openpgpPassphrase,
+	                                       |ev,inner| inner.secureKeys.openpgpPassphrase = ev.target().value());
+	passphraseF.setInputType(FieldHelperType::PASSWORD);
+	return view!{
+		<section class="module_mail_secure_config">
+			<span class="module_config_section_title"><Translate key="MODULE_MAIL_SECURE_KEYS"/></span>
+			<p class="module_mail_secure_note"><Translate key="MODULE_MAIL_SECURE_KEYS_NOTE"/></p>
+			{cfg!(not(feature="mail_secure")).then(|| view!{<p class="module_mail_secure_note"><Translate key="MODULE_MAIL_SECURE_ERROR_DISABLED"/></p>})}
+			{mailSecure_keyField(config.clone(),update.clone(),"mail-secure-openpgp-public","MODULE_MAIL_SECURE_OPENPGP_PUBLIC",
+				|keys| keys.openpgpPublicKeys.clone(),|keys,value| keys.openpgpPublicKeys = value)}
+			{mailSecure_keyField(config.clone(),update.clone(),"mail-secure-openpgp-secret","MODULE_MAIL_SECURE_OPENPGP_SECRET",
+				|keys| keys.openpgpSecretKey.clone(),|keys,value| keys.openpgpSecretKey = value)}
+			{passphraseF.draw()}
+			{mailSecure_keyField(config.clone(),update.clone(),"mail-secure-smime-key","MODULE_MAIL_SECURE_SMIME_KEY",
+				|keys| keys.smimePrivateKey.clone(),|keys,value| keys.smimePrivateKey = value)}
+			{mailSecure_keyField(config,update,"mail-secure-smime-certificates","MODULE_MAIL_SECURE_SMIME_CERTIFICATES",
+				|keys| keys.smimeCertificates.clone(),|keys,value| keys.smimeCertificates = value)}
+		</section>
+	}.into_any();
+}
+
+fn mailSecure_keyField(
+	config: ArcRwSignal<MailConfig>,
+	update: ArcRwSignal<Cache>,
+	id: &'static str,
+	translateKey: &'static str,
+	getKey: fn(&MailSecureKeys) -> String,
+	setKey: fn(&mut MailSecureKeys,String),
+) -> AnyView
+{
+	let configValue = config.clone();
+	return view!{
+		<label class="module_config_field" for={id}>
+			<Translate key={translateKey}/>
+			<textarea id={id} class="module_config_input module_mail_secure_key" rows="4" spellcheck="false" autocomplete="off"
+				prop:value={move || getKey(&configValue.get().secureKeys)}
+				on:change={move |event| {
+					let value = event_target_value(&event);
+					config.update(|config| setKey(&mut config.secureKeys,value));
+					update.update(|cache| cache.update());
+				}}></textarea>
+		</label>
+	}.into_any();
+}
+
+/// walks the entities of a secured mail, the keys being parsed once when a part needs them
+#[cfg(feature="mail_secure")]
+struct MailSecureOpener<'a>
+{
+	keys: &'a MailSecureKeys,
+	from: Option<&'a MailSenderAddress>,
+	openpgpKeys: Option<Result<Vec<OpenPgpKey>,MailSecureError>>,
+	smimeKey: Option<Result<RsaPrivateKey,MailSecureError>>,
+	smimeCertificates: Option<Vec<Vec<u8>>>,
+	state: MailSecureState,
+	text: Option<String>,
+	html: Option<String>,
+	attachments: Vec<Attachment>,
+}
+
+#[cfg(feature="mail_secure")]
+impl<'a> MailSecureOpener<'a>
+{
+	fn new(keys: &'a MailSecureKeys, from: Option<&'a MailSenderAddress>) -> Self
+	{
+		return Self {
+			keys,
+			from,
+			openpgpKeys: None,
+			smimeKey: None,
+			smimeCertificates: None,
+			state: MailSecureState {signature: None,isEncrypted: false,error: None},
+			text: None,
+			html: None,
+			attachments: Vec::new(),
+		};
+	}
+
+	fn secured_open(&mut self, secured: &ImapSecuredPart)
+	{
+		match secured.kind
+		{
+			ImapSecuredKind::OPENPGP_SIGNED => self.openpgpSigned_open(&secured.signed,&secured.payload),
+			ImapSecuredKind::SMIME_SIGNED => self.smimeSigned_open(&secured.signed,&secured.payload),
+			ImapSecuredKind::OPENPGP_ENCRYPTED => self.openpgpEncrypted_open(&secured.payload,0),
+			ImapSecuredKind::SMIME_MIME => self.smime_open(&secured.payload,0),
+		}
+	}
+
+	/// text of the decrypted entities, their HTML is reduced to text
+	fn content_get(&self) -> Option<ImapMailContentType>
+	{
+		if let Some(text) = &self.text
+		{
+			return Some(ImapMailContentType::Text(text.clone()));
+		}
+		return self.html.as_ref().map(|html| ImapMailContentType::Text(html_toText(html)));
+	}
+
+	fn openpgpKeys_get(&mut self) -> Result<&[OpenPgpKey],MailSecureError>
+	{
+		let keys = self.keys;
+		let parsed = self.openpgpKeys.get_or_insert_with(|| {
+			let mut parsed = openpgp::keys_parse(&keys.openpgpPublicKeys,"")?;
+			if (!keys.openpgpSecretKey.trim().is_empty())
+			{
+				parsed.extend(openpgp::keys_parse(&keys.openpgpSecretKey,&keys.openpgpPassphrase)?);
+			}
+			return Ok(parsed);
+		});
+		return parsed.as_deref().map_err(|error| *error);
+	}
+
+	fn smimeKey_get(&mut self) -> Result<&RsaPrivateKey,MailSecureError>
+	{
+		let keys = self.keys;
+		let parsed = self.smimeKey.get_or_insert_with(|| {
+			if (keys.smimePrivateKey.trim().is_empty())
+			{
+				return Err(MailSecureError::NoKey);
+			}
+			return smime::privateKey_parse(&keys.smimePrivateKey);
+		});
+		return parsed.as_ref().map_err(|error| *error);
+	}
+
+	/// the certificates trusted by the user, a block which cannot be read is never trusted
+	fn smimeCertificates_get(&mut self) -> &[Vec<u8>]
+	{
+		let keys = self.keys;
+		return self.smimeCertificates.get_or_insert_with(|| smime::certificates_parse(&keys.smimeCertificates));
+	}
+
+	/// the outermost signature is the one shown
+	fn signature_set(&mut self, signature: MailSignatureCheck)
+	{
+		if (self.state.signature.is_none())
+		{
+			self.state.signature = Some(signature.map_or_else(|status| status,|signer| signer.status_get(self.from)));
+		}
+	}
+
+	fn error_set(&mut self, error: MailSecureError)
+	{
+		if (self.state.error.is_none())
+		{
+			self.state.error = Some(error);
+		}
+	}
+
+	fn openpgpSigned_open(&mut self, signed: &[u8], signature: &[u8])
+	{
+		let canonical = crlf_canonicalize(signed);
+		match self.openpgpKeys_get()
+		{
+			Ok(keys) =>
+			{
+				let status = openpgp::signature_verify(signature,&canonical,keys);
+				self.signature_set(status);
+			},
+			Err(error) => self.error_set(error),
+		}
+	}
+
+	fn smimeSigned_open(&mut self, signed: &[u8], signature: &[u8])
+	{
+		let canonical = crlf_canonicalize(signed);
+		let status = smime::signature_verify(signature,&canonical,self.smimeCertificates_get());
+		self.signature_set(status);
+	}
+
+	fn openpgpEncrypted_open(&mut self, message: &[u8], depth: usize)
+	{
+		let decrypted = match self.openpgpKeys_get()
+		{
+			Ok(keys) => openpgp::message_decrypt(message,keys),
+			Err(error) => Err(error),
+		};
+		match decrypted
+		{
+			Ok(decrypted) =>
+			{
+				self.state.isEncrypted = true;
+				if let Some(signature) = decrypted.signature
+				{
+					self.signature_set(signature);
+				}
+				self.entity_read(&decrypted.content,depth + 1);
+			},
+			Err(error) => self.error_set(error),
+		}
+	}
+
+	fn smime_open(&mut self, message: &[u8], depth: usize)
+	{
+		// a signed message needs no key, the enveloped one reports the missing key
+		let key = self.smimeKey_get().ok().cloned();
+		let opened = smime::message_open(message,key.as_ref(),self.smimeCertificates_get());
+		match opened
+		{
+			Ok(CmsContent::Signed {content,signature}) =>
+			{
+				self.signature_set(signature);
+				self.entity_read(&content,depth + 1);
+			},
+			Ok(CmsContent::Enveloped(content)) =>
+			{
+				self.state.isEncrypted = true;
+				self.entity_read(&content,depth + 1);
+			},
+			Err(MailSecureError::NoKey) =>
+			{
+				let error = self.smimeKey_get().err().unwrap_or(MailSecureError::NoKey);
+				self.error_set(error);
+			},
+			Err(error) => self.error_set(error),
+		}
+	}
+
+	fn entity_read(&mut self, data: &[u8], depth: usize)
+	{
+		if (depth > MailSecureLimits::NESTING_MAXIMUM)
+		{
+			self.error_set(MailSecureError::Invalid);
+			return;
+		}
+		match mailparse::parse_mail(data)
+		{
+			Ok(entity) => self.part_read(&entity,depth),
+			Err(_) => self.error_set(MailSecureError::Invalid),
+		}
+	}
+
+	/// same walk as the server, the secured entities being opened on the way
+	fn part_read(&mut self, part: &ParsedMail<'_>, depth: usize)
+	{
+		match part.ctype.mimetype.as_str()
+		{
+			"text/plain" if self.text.is_none() => self.text = part.get_body().ok(),
+			"text/html" if self.html.is_none() => self.html = part.get_body().ok(),
+			"multipart/signed" =>
+			{
+				let [signedPart,signaturePart] = part.subparts.as_slice() else {return};
+				let Ok(signature) = signaturePart.get_body_raw() else {return};
+				let protocol = part.ctype.params.get("protocol").map(|protocol| protocol.to_ascii_lowercase()).unwrap_or_default();
+				match protocol.as_str()
+				{
+					"application/pgp-signature" => self.openpgpSigned_open(signedPart.raw_bytes,&signature),
+					"application/pkcs7-signature" | "application/x-pkcs7-signature" => self.smimeSigned_open(signedPart.raw_bytes,&signature),
+					_ => {},
+				}
+				self.part_read(signedPart,depth);
+			},
+			"multipart/encrypted" =>
+			{
+				if let [_,encryptedPart] = part.subparts.as_slice() && let Ok(message) = encryptedPart.get_body_raw()
+				{
+					self.openpgpEncrypted_open(&message,depth);
+				}
+			},
+			"application/pkcs7-mime" | "application/x-pkcs7-mime" =>
+			{
+				if let Ok(message) = part.get_body_raw()
+				{
+					self.smime_open(&message,depth);
+				}
+			},
+			mimetype if mimetype.starts_with("multipart/") =>
+			{
+				for subpart in &part.subparts
+				{
+					self.part_read(subpart,depth);
+				}
+			},
+			_ => self.attachments.extend(attachment_get(part)),
+		}
+	}
+}
+
+#[cfg(all(test, feature="mail_secure"))]
+mod tests
+{
+	use crate::api::proxys::imap_components::{ImapMail, ImapMailContentType, ImapSecuredKind, ImapSecuredPart};
+	use super::{mailSecure_apply, MailSecureError, MailSecureKeys, MailSenderAddress, MailSignatureStatus};
+
+	// RSA 1024 keys of "Alice <alice@example.com>" made with GnuPG and OpenSSL, the secret one protected by "secret words"
+	const OPENPGP_PUBLIC: &str = include_str!("secure/testdata/public.asc");
+	const OPENPGP_SECRET: &str = include_str!("secure/testdata/secret.asc");
+	const OPENPGP_SIGNED: &[u8] = include_bytes!("secure/testdata/signed.txt");
+	const OPENPGP_SIGNATURE: &[u8] = include_bytes!("secure/testdata/signed.asc");
+	const OPENPGP_MESSAGE: &[u8] = include_bytes!("secure/testdata/message.asc");
+	const SMIME_KEY: &str = include_str!("secure/testdata/smime.key");
+	const SMIME_CERTIFICATE: &str = include_str!("secure/testdata/smime.crt");
+	const SMIME_SIGNATURE: &[u8] = include_bytes!("secure/testdata/detached.p7s");
+	const SMIME_ENVELOPED: &[u8] = include_bytes!("secure/testdata/enveloped.p7m");
+
+	fn mail_secured(kind: ImapSecuredKind, signed: &[u8], payload: &[u8]) -> ImapMail
+	{
+		let mut mail = ImapMail::default();
+		mail.secured = Some(ImapSecuredPart {kind,signed: signed.to_vec(),payload: payload.to_vec()});
+		return mail;
+	}
+
+	fn keys_get(passphrase: &str) -> MailSecureKeys
+	{
+		return MailSecureKeys {
+			openpgpPublicKeys: OPENPGP_PUBLIC.to_string(),
+			openpgpSecretKey: OPENPGP_SECRET.to_string(),
+			openpgpPassphrase: passphrase.to_string(),
+			smimePrivateKey: SMIME_KEY.to_string(),
+			smimeCertificates: SMIME_CERTIFICATE.to_string(),
+		};
+	}
+
+	fn alice() -> Option<MailSenderAddress>
+	{
+		return MailSenderAddress::from_header("Alice <ALICE@example.com>");
+	}
+
+	#[test]
+	fn openpgpSignature_isCheckedAgainstTheSignedEntity()
+	{
+		let keys = MailSecureKeys {openpgpPublicKeys: OPENPGP_PUBLIC.to_string(),..MailSecureKeys::default()};
+		let signed = || mail_secured(ImapSecuredKind::OPENPGP_SIGNED,OPENPGP_SIGNED,OPENPGP_SIGNATURE);
+
+		let (_,valid) = mailSecure_apply(signed(),&keys,alice().as_ref());
+		let (_,otherSender) = mailSecure_apply(signed(),&keys,MailSenderAddress::from_header("mallory@example.com").as_ref());
+		let mut tampered = OPENPGP_SIGNED.to_vec();
+		tampered.extend_from_slice(b"!");
+		let (_,invalid) = mailSecure_apply(mail_secured(ImapSecuredKind::OPENPGP_SIGNED,&tampered,OPENPGP_SIGNATURE),&keys,alice().as_ref());
+		let (_,unknown) = mailSecure_apply(signed(),&MailSecureKeys::default(),alice().as_ref());
+
+		assert_eq!(valid.unwrap().signature,Some(MailSignatureStatus::Valid("Alice <alice@example.com>".to_string())));
+		assert_eq!(otherSender.unwrap().signature,Some(MailSignatureStatus::Untrusted("Alice <alice@example.com>".to_string())));
+		assert_eq!(invalid.unwrap().signature,Some(MailSignatureStatus::Invalid));
+		assert_eq!(unknown.unwrap().signature,Some(MailSignatureStatus::UnknownKey("ACBDAE27F649E885F621ACBCA789FBDC72A0C935".to_string())));
+	}
+
+	#[test]
+	fn openpgpMessage_isDecryptedOnlyWithTheRightPassphrase()
+	{
+		let (mail,state) = mailSecure_apply(mail_secured(ImapSecuredKind::OPENPGP_ENCRYPTED,b"",OPENPGP_MESSAGE),&keys_get("secret words"),alice().as_ref());
+		let (wrongMail,wrongState) = mailSecure_apply(mail_secured(ImapSecuredKind::OPENPGP_ENCRYPTED,b"",OPENPGP_MESSAGE),&keys_get("guess"),alice().as_ref());
+
+		let state = state.unwrap();
+		assert!(state.isEncrypted);
+		assert_eq!(state.error,None);
+		assert_eq!(state.signature,Some(MailSignatureStatus::Valid("Alice <alice@example.com>".to_string())));
+		assert!(matches!(mail.content,ImapMailContentType::Text(ref text) if text.trim() == "The secret plan"));
+		assert_eq!(wrongState.unwrap().error,Some(MailSecureError::KeyInvalid));
+		assert!(wrongMail.content.is_none());
+	}
+
+	#[test]
+	fn smimeMessages_areVerifiedAndDecrypted()
+	{
+		let trusted = MailSecureKeys {smimeCertificates: SMIME_CERTIFICATE.to_string(),..MailSecureKeys::default()};
+		let detached = || mail_secured(ImapSecuredKind::SMIME_SIGNED,OPENPGP_SIGNED,SMIME_SIGNATURE);
+		let (_,trustedSignature) = mailSecure_apply(detached(),&trusted,alice().as_ref());
+		let (_,embeddedOnly) = mailSecure_apply(detached(),&MailSecureKeys::default(),alice().as_ref());
+		let (_,otherSender) = mailSecure_apply(detached(),&trusted,MailSenderAddress::from_header("bob@example.com").as_ref());
+		let (mail,enveloped) = mailSecure_apply(mail_secured(ImapSecuredKind::SMIME_MIME,b"",SMIME_ENVELOPED),&keys_get(""),alice().as_ref());
+		let (_,withoutKey) = mailSecure_apply(mail_secured(ImapSecuredKind::SMIME_MIME,b"",SMIME_ENVELOPED),&MailSecureKeys::default(),alice().as_ref());
+
+		assert_eq!(trustedSignature.unwrap().signature,Some(MailSignatureStatus::Valid("alice@example.com".to_string())));
+		// the certificate carried by the signature proves nothing by itself
+		assert_eq!(embeddedOnly.unwrap().signature,Some(MailSignatureStatus::Untrusted("alice@example.com".to_string())));
+		assert_eq!(otherSender.unwrap().signature,Some(MailSignatureStatus::Untrusted("alice@example.com".to_string())));
+		let enveloped = enveloped.unwrap();
+		assert!(enveloped.isEncrypted);
+		assert_eq!(enveloped.signature,Some(MailSignatureStatus::Valid("alice@example.com".to_string())));
+		assert!(matches!(mail.content,ImapMailContentType::Text(ref text) if text.trim() == "The secret plan"));
+		assert_eq!(withoutKey.unwrap().error,Some(MailSecureError::NoKey));
+	}
+}
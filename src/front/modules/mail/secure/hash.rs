@@ -0,0 +1,94 @@
+use rsa::traits::PublicKeyParts;
+use rsa::{Pkcs1v15Sign, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+/// hash of a signature, an OpenPGP fingerprint or a key derivation
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum SecureHash
+{
+	Sha1,
+	Sha224,
+	Sha256,
+	Sha384,
+	Sha512,
+}
+
+impl SecureHash
+{
+	pub(super) fn hasher_get(self) -> SecureHasher
+	{
+		return match self
+		{
+			Self::Sha1 => SecureHasher::Sha1(Sha1::new()),
+			Self::Sha224 => SecureHasher::Sha224(Sha224::new()),
+			Self::Sha256 => SecureHasher::Sha256(Sha256::new()),
+			Self::Sha384 => SecureHasher::Sha384(Sha384::new()),
+			Self::Sha512 => SecureHasher::Sha512(Sha512::new()),
+		};
+	}
+
+	pub(super) fn digest_get(self, parts: &[&[u8]]) -> Vec<u8>
+	{
+		let mut hasher = self.hasher_get();
+		for part in parts
+		{
+			hasher.update(part);
+		}
+		return hasher.finalize();
+	}
+
+	/// RSASSA-PKCS1-v1_5 verification of a digest of this hash
+	pub(super) fn rsa_verify(self, public: &RsaPublicKey, digest: &[u8], signature: &[u8]) -> bool
+	{
+		let scheme = match self
+		{
+			Self::Sha1 => Pkcs1v15Sign::new::<Sha1>(),
+			Self::Sha224 => Pkcs1v15Sign::new::<Sha224>(),
+			Self::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+			Self::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+			Self::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+		};
+		// an OpenPGP integer drops the leading zeros the scheme expects
+		let mut padded = vec![0; public.size().saturating_sub(signature.len())];
+		padded.extend_from_slice(signature);
+		return public.verify(scheme,digest,&padded).is_ok();
+	}
+}
+
+/// running digest, for the inputs too large to be gathered first
+pub(super) enum SecureHasher
+{
+	Sha1(Sha1),
+	Sha224(Sha224),
+	Sha256(Sha256),
+	Sha384(Sha384),
+	Sha512(Sha512),
+}
+
+impl SecureHasher
+{
+	pub(super) fn update(&mut self, data: &[u8])
+	{
+		match self
+		{
+			Self::Sha1(hasher) => hasher.update(data),
+			Self::Sha224(hasher) => hasher.update(data),
+			Self::Sha256(hasher) => hasher.update(data),
+			Self::Sha384(hasher) => hasher.update(data),
+			Self::Sha512(hasher) => hasher.update(data),
+		}
+	}
+
+	pub(super) fn finalize(self) -> Vec<u8>
+	{
+		return match self
+		{
+			Self::Sha1(hasher) => hasher.finalize().to_vec(),
+			Self::Sha224(hasher) => hasher.finalize().to_vec(),
+			Self::Sha256(hasher) => hasher.finalize().to_vec(),
+			Self::Sha384(hasher) => hasher.finalize().to_vec(),
+			Self::Sha512(hasher) => hasher.finalize().to_vec(),
+		};
+	}
+}
@@ -0,0 +1,68 @@
+use mailparse::{DispositionType, ParsedMail};
+use crate::api::proxys::imap_components::Attachment;
+use super::super::compose::MailDraft;
+
+/// CRLF line endings of the signed text: a signature is computed over the canonical form (RFC 3156, RFC 8551)
+pub(super) fn crlf_canonicalize(data: &[u8]) -> Vec<u8>
+{
+	let mut canonical = Vec::with_capacity(data.len() + data.len() / 32);
+	let mut previous = 0u8;
+	for byte in data
+	{
+		if (*byte == b'\n' && previous != b'\r')
+		{
+			canonical.push(b'\r');
+		}
+		canonical.push(*byte);
+		previous = *byte;
+	}
+	return canonical;
+}
+
+/// plain text of an HTML part. Decrypted HTML did not go through the sanitizer of the server, it is never rendered
+pub(super) fn html_toText(html: &str) -> String
+{
+	// ASCII lowercase keeps the byte positions of the original
+	let lower = html.to_ascii_lowercase();
+	let mut visible = String::with_capacity(html.len());
+	let mut position = 0;
+	while let Some(offset) = lower[position..].find('<')
+	{
+		let start = position + offset;
+		let hidden = ["script","style","head"].into_iter().find(|name| {
+			return lower[start + 1..].strip_prefix(name)
+				.is_some_and(|rest| rest.starts_with(|character: char| character == '>' || character == '/' || character.is_ascii_whitespace()));
+		});
+		let Some(name) = hidden else {
+			visible.push_str(&html[position..=start]);
+			position = start + 1;
+			continue;
+		};
+		// the whole element goes away with its content
+		visible.push_str(&html[position..start]);
+		let closing = lower[start..].find(&format!("</{}",name)).map(|closing| start + closing);
+		position = closing.and_then(|closing| lower[closing..].find('>').map(|end| closing + end + 1)).unwrap_or(html.len());
+	}
+	visible.push_str(&html[position..]);
+	return MailDraft::text_fromHtml(&visible);
+}
+
+/// leaf part shown as an attachment, like the ones listed by the server
+pub(super) fn attachment_get(part: &ParsedMail<'_>) -> Option<Attachment>
+{
+	let disposition = part.get_content_disposition();
+	let filename = disposition.params.get("filename").or_else(|| part.ctype.params.get("name")).cloned();
+	if (disposition.disposition != DispositionType::Attachment && filename.is_none())
+	{
+		return None;
+	}
+	let contentId = part.headers.iter().find(|header| header.get_key_ref().eq_ignore_ascii_case("Content-ID"))
+		.map(|header| header.get_value().trim().trim_start_matches('<').trim_end_matches('>').to_string());
+	return Some(Attachment {
+		filename,
+		content_type: part.ctype.mimetype.clone(),
+		content_id: contentId,
+		data: part.get_body_raw().ok()?,
+		section: None,
+	});
+}
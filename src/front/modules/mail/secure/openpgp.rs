@@ -0,0 +1,622 @@
+use base64ct::{Base64, Encoding};
+use super::mime::crlf_canonicalize;
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+use super::hash::SecureHash;
+use super::symmetric::AesKey;
+use super::{MailSecureError, MailSignatureCheck, MailSignatureStatus, MailSigner};
+
+struct OpenPgpLimits;
+
+impl OpenPgpLimits
+{
+	const PACKETS_MAXIMUM: usize = 4_096;
+	const DECOMPRESSED_MAXIMUM_BYTES: usize = 32 * 1024 * 1024;
+	const NESTING_MAXIMUM: usize = 8;
+}
+
+const TAG_PKESK: u8 = 1;
+const TAG_SIGNATURE: u8 = 2;
+const TAG_SECRET_KEY: u8 = 5;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_SECRET_SUBKEY: u8 = 7;
+const TAG_COMPRESSED: u8 = 8;
+const TAG_SED: u8 = 9;
+const TAG_LITERAL: u8 = 11;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_SEIPD: u8 = 18;
+const TAG_AEAD: u8 = 20;
+
+// RSA, RSA encrypt-only and RSA sign-only
+const ALGORITHMS_RSA: [u8; 3] = [1,2,3];
+
+/// key of a keyring, public or secret, RSA being the only usable algorithm
+pub(super) struct OpenPgpKey
+{
+	keyId: [u8; 8],
+	fingerprint: [u8; 20],
+	userIds: Vec<String>,
+	public: Option<RsaPublicKey>,
+	secret: Option<RsaPrivateKey>,
+}
+
+impl OpenPgpKey
+{
+	fn label_get(&self) -> String
+	{
+		return self.userIds.first().cloned().unwrap_or_else(|| hex_get(&self.fingerprint));
+	}
+}
+
+/// content of a decrypted message, with the check of the signature it held
+pub(super) struct OpenPgpMessage
+{
+	pub(super) content: Vec<u8>,
+	pub(super) signature: Option<MailSignatureCheck>,
+}
+
+/// keys of one or several armored blocks, the secret ones unlocked with the passphrase
+pub(super) fn keys_parse(text: &str, passphrase: &str) -> Result<Vec<OpenPgpKey>,MailSecureError>
+{
+	let mut keys = Vec::new();
+	if (text.trim().is_empty())
+	{
+		return Ok(keys);
+	}
+	for block in armor_decode(text.trim().as_bytes()).ok_or(MailSecureError::KeyInvalid)?
+	{
+		let packets = packets_read(&block).ok_or(MailSecureError::KeyInvalid)?;
+		let mut primaryIndex = None;
+		for packet in packets
+		{
+			match packet.tag
+			{
+				TAG_PUBLIC_KEY | TAG_SECRET_KEY | TAG_PUBLIC_SUBKEY | TAG_SECRET_SUBKEY =>
+				{
+					let isSecret = packet.tag == TAG_SECRET_KEY || packet.tag == TAG_SECRET_SUBKEY;
+					// keys of an older version than 4 are skipped, they have no usable fingerprint
+					let Some(key) = key_read(&packet.body,isSecret.then_some(passphrase)).ok_or(MailSecureError::KeyInvalid)? else {continue};
+					let isPrimary = packet.tag == TAG_PUBLIC_KEY || packet.tag == TAG_SECRET_KEY;
+					let mut key = key;
+					if (isPrimary)
+					{
+						primaryIndex = Some(keys.len());
+					}
+					else
+					{
+						key.userIds = primaryIndex.and_then(|index| keys.get(index)).map(|primary| primary.userIds.clone()).unwrap_or_default();
+					}
+					keys.push(key);
+				},
+				TAG_USER_ID =>
+				{
+					let Some(primary) = primaryIndex.and_then(|index| keys.get_mut(index)) else {continue};
+					primary.userIds.push(String::from_utf8_lossy(&packet.body).into_owned());
+				},
+				_ => {},
+			}
+		}
+	}
+	return Ok(keys);
+}
+
+/// detached signature of a multipart/signed mail, over its canonical first part
+pub(super) fn signature_verify(signature: &[u8], content: &[u8], keys: &[OpenPgpKey]) -> MailSignatureCheck
+{
+	let Some(packets) = armor_decode(signature).and_then(|blocks| blocks.into_iter().next()).and_then(|block| packets_read(&block)) else {
+		return Err(MailSignatureStatus::Invalid);
+	};
+	let Some(packet) = packets.iter().find(|packet| packet.tag == TAG_SIGNATURE) else {return Err(MailSignatureStatus::Invalid)};
+	return signature_check(&packet.body,content,keys);
+}
+
+/// content of an encrypted message, its session key wrapped for one of the secret keys
+pub(super) fn message_decrypt(message: &[u8], keys: &[OpenPgpKey]) -> Result<OpenPgpMessage,MailSecureError>
+{
+	let block = armor_decode(message).and_then(|blocks| blocks.into_iter().next()).ok_or(MailSecureError::Invalid)?;
+	let packets = packets_read(&block).ok_or(MailSecureError::Invalid)?;
+	if (!keys.iter().any(|key| key.secret.is_some()))
+	{
+		return Err(MailSecureError::NoKey);
+	}
+
+	let mut sessionKey = None;
+	let mut error = MailSecureError::KeyMismatch;
+	for packet in packets.iter().filter(|packet| packet.tag == TAG_PKESK)
+	{
+		let mut reader = OpenPgpReader {data: &packet.body};
+		let (Some(3),Some(keyId),Some(algorithm)) = (reader.byte(),reader.bytes(8),reader.byte()) else {continue};
+		// a zero key ID hides the recipient, every secret key is tried
+		let candidates = keys.iter().filter(|key| key.secret.is_some() && (keyId == key.keyId || keyId == [0; 8]));
+		for key in candidates
+		{
+			if (!ALGORITHMS_RSA.contains(&algorithm))
+			{
+				error = MailSecureError::Unsupported;
+				continue;
+			}
+			let Some(encrypted) = (OpenPgpReader {data: reader.data}).mpi() else {continue};
+			let Some(decrypted) = key.secret.as_ref().and_then(|secret| secret.decrypt(Pkcs1v15Encrypt,encrypted).ok()) else {continue};
+			match sessionKey_read(&decrypted)
+			{
+				Ok(key) =>
+				{
+					sessionKey = Some(key);
+					break;
+				},
+				Err(keyError) => error = keyError,
+			}
+		}
+		if (sessionKey.is_some())
+		{
+			break;
+		}
+	}
+	let (_,sessionKey) = sessionKey.ok_or(error)?;
+
+	let encrypted = packets.iter().find(|packet| matches!(packet.tag,TAG_SEIPD | TAG_SED | TAG_AEAD)).ok_or(MailSecureError::Invalid)?;
+	// the old packet without integrity protection can be tampered with, AEAD packets are not supported yet
+	if (encrypted.tag != TAG_SEIPD || encrypted.body.first() != Some(&1))
+	{
+		return Err(MailSecureError::Unsupported);
+	}
+	let cipher = AesKey::new(&sessionKey).ok_or(MailSecureError::Unsupported)?;
+	let plaintext = cipher.cfb_decrypt(&[0; 16],&encrypted.body[1..]).ok_or(MailSecureError::Invalid)?;
+	// random prefix of a block with its two last bytes repeated, then the data and the modification detection code
+	if (plaintext.len() < 18 + 22 || plaintext[14..16] != plaintext[16..18])
+	{
+		return Err(MailSecureError::Invalid);
+	}
+	let (protected,mdc) = plaintext.split_at(plaintext.len() - 20);
+	if (protected[protected.len() - 2..] != [0xd3,0x14] || SecureHash::Sha1.digest_get(&[protected]) != mdc)
+	{
+		return Err(MailSecureError::Invalid);
+	}
+	return message_read(&protected[18..protected.len() - 2],keys,0);
+}
+
+struct OpenPgpPacket
+{
+	tag: u8,
+	body: Vec<u8>,
+}
+
+struct OpenPgpReader<'a>
+{
+	data: &'a [u8],
+}
+
+impl<'a> OpenPgpReader<'a>
+{
+	fn byte(&mut self) -> Option<u8>
+	{
+		let (first,rest) = self.data.split_first()?;
+		self.data = rest;
+		return Some(*first);
+	}
+
+	fn bytes(&mut self, size: usize) -> Option<&'a [u8]>
+	{
+		if (self.data.len() < size)
+		{
+			return None;
+		}
+		let (bytes,rest) = self.data.split_at(size);
+		self.data = rest;
+		return Some(bytes);
+	}
+
+	fn u16(&mut self) -> Option<usize>
+	{
+		let bytes = self.bytes(2)?;
+		return Some(((bytes[0] as usize) << 8) | bytes[1] as usize);
+	}
+
+	/// multiprecision integer: its bit count then the big-endian bytes
+	fn mpi(&mut self) -> Option<&'a [u8]>
+	{
+		let bits = self.u16()?;
+		return self.bytes(bits.div_ceil(8));
+	}
+}
+
+/// binary data, or the base64 of every armored block of the text
+fn armor_decode(data: &[u8]) -> Option<Vec<Vec<u8>>>
+{
+	if (data.first().is_some_and(|first| first & 0x80 != 0))
+	{
+		return Some(vec![data.to_vec()]);
+	}
+	let text = std::str::from_utf8(data).ok()?;
+	let mut blocks = Vec::new();
+	let mut lines = text.lines().map(str::trim);
+	while let Some(line) = lines.next()
+	{
+		if (!line.starts_with("-----BEGIN PGP "))
+		{
+			continue;
+		}
+		// armor headers end with the first empty line
+		for header in lines.by_ref()
+		{
+			if (header.is_empty())
+			{
+				break;
+			}
+		}
+		let mut encoded = String::new();
+		for line in lines.by_ref()
+		{
+			// the checksum line is optional and redundant with the packets integrity
+			if (line.starts_with("-----END PGP ") || line.starts_with('='))
+			{
+				break;
+			}
+			encoded.push_str(line);
+		}
+		blocks.push(Base64::decode_vec(&encoded).ok()?);
+	}
+	return (!blocks.is_empty()).then_some(blocks);
+}
+
+fn packets_read(data: &[u8]) -> Option<Vec<OpenPgpPacket>>
+{
+	let mut packets = Vec::new();
+	let mut position = 0;
+	while position < data.len()
+	{
+		if (packets.len() >= OpenPgpLimits::PACKETS_MAXIMUM)
+		{
+			return None;
+		}
+		let header = data[position];
+		position += 1;
+		if (header & 0x80 == 0)
+		{
+			return None;
+		}
+		let mut body = Vec::new();
+		let tag = if (header & 0x40 != 0)
+		{
+			loop
+			{
+				let (length,isPartial) = newLength_read(data,&mut position)?;
+				body.extend_from_slice(data.get(position..position.checked_add(length)?)?);
+				position += length;
+				if (!isPartial)
+				{
+					break;
+				}
+			}
+			header & 0x3f
+		}
+		else
+		{
+			let length = match header & 0x03
+			{
+				0 => *data.get(position)? as usize,
+				1 => ((*data.get(position)? as usize) << 8) | *data.get(position + 1)? as usize,
+				2 => data.get(position..position + 4)?.iter().fold(0usize,|length,byte| (length << 8) | *byte as usize),
+				// indeterminate length, up to the end of the data
+				_ => data.len() - position,
+			};
+			position += match header & 0x03 {0 => 1,1 => 2,2 => 4,_ => 0};
+			body.extend_from_slice(data.get(position..position.checked_add(length)?)?);
+			position += length;
+			(header >> 2) & 0x0f
+		};
+		packets.push(OpenPgpPacket {tag,body});
+	}
+	return Some(packets);
+}
+
+/// length of a new format packet, and whether another chunk follows it
+fn newLength_read(data: &[u8], position: &mut usize) -> Option<(usize,bool)>
+{
+	let first = *data.get(*position)? as usize;
+	*position += 1;
+	return match first
+	{
+		0..=191 => Some((first,false)),
+		192..=223 =>
+		{
+			let second = *data.get(*position)? as usize;
+			*position += 1;
+			Some((((first - 192) << 8) + second + 192,false))
+		},
+		255 =>
+		{
+			let bytes = data.get(*position..*position + 4)?;
+			*position += 4;
+			Some((bytes.iter().fold(0usize,|length,byte| (length << 8) | *byte as usize),false))
+		},
+		_ => Some((1 << (first & 0x1f),true)),
+	};
+}
+
+/// version 4 key, None for the older versions
+fn key_read(body: &[u8], passphrase: Option<&str>) -> Option<Option<OpenPgpKey>>
+{
+	let mut reader = OpenPgpReader {data: body};
+	if (reader.byte()? != 4)
+	{
+		return Some(None);
+	}
+	reader.bytes(4)?;
+	let algorithm = reader.byte()?;
+	let mut public = None;
+	match algorithm
+	{
+		1..=3 =>
+		{
+			let (modulus,exponent) = (reader.mpi()?,reader.mpi()?);
+			public = RsaPublicKey::new(BigUint::from_bytes_be(modulus),BigUint::from_bytes_be(exponent)).ok();
+		},
+		// ElGamal and DSA
+		16 => {reader.mpi()?;reader.mpi()?;reader.mpi()?;},
+		17 => {reader.mpi()?;reader.mpi()?;reader.mpi()?;reader.mpi()?;},
+		// ECDH, ECDSA and EdDSA: a curve identifier then a point, ECDH adds its KDF parameters
+		18 | 19 | 22 =>
+		{
+			let oidLength = reader.byte()? as usize;
+			reader.bytes(oidLength)?;
+			reader.mpi()?;
+			if (algorithm == 18)
+			{
+				let kdfLength = reader.byte()? as usize;
+				reader.bytes(kdfLength)?;
+			}
+		},
+		_ => return Some(None),
+	}
+	let publicPart = &body[..body.len() - reader.data.len()];
+	let fingerprint: [u8; 20] = SecureHash::Sha1.digest_get(&[&[0x99],&(publicPart.len() as u16).to_be_bytes(),publicPart]).try_into().ok()?;
+	let mut keyId = [0; 8];
+	keyId.copy_from_slice(&fingerprint[12..]);
+
+	let secret = match (passphrase,public.as_ref())
+	{
+		(Some(passphrase),Some(public)) => secret_read(reader.data,passphrase,public)?,
+		_ => None,
+	};
+	return Some(Some(OpenPgpKey {keyId,fingerprint,userIds: Vec::new(),public,secret}));
+}
+
+/// secret part of a RSA key. An outer None is a broken key, an inner one a key without its secret (GNU stub)
+fn secret_read(data: &[u8], passphrase: &str, public: &RsaPublicKey) -> Option<Option<RsaPrivateKey>>
+{
+	let mut reader = OpenPgpReader {data};
+	let usage = reader.byte()?;
+	let plaintext = match usage
+	{
+		0 =>
+		{
+			let (material,checksum) = reader.data.split_at(reader.data.len().checked_sub(2)?);
+			if (checksum16_get(material) != checksum)
+			{
+				return None;
+			}
+			material.to_vec()
+		},
+		254 | 255 =>
+		{
+			let algorithm = reader.byte()?;
+			let s2kType = reader.byte()?;
+			let hash = hash_get(reader.byte()?)?;
+			let (salt,count) = match s2kType
+			{
+				0 => (&[][..],0),
+				1 => (reader.bytes(8)?,0),
+				3 =>
+				{
+					let salt = reader.bytes(8)?;
+					let coded = reader.byte()? as usize;
+					(salt,(16 + (coded & 15)) << ((coded >> 4) + 6))
+				},
+				// GNU extension, the secret stays on a smartcard or was removed
+				101 => return Some(None),
+				_ => return None,
+			};
+			let keyBytes = algorithm_keyBytes(algorithm)?;
+			let iv = reader.bytes(16)?;
+			let key = s2k_derive(hash,salt,count,passphrase.as_bytes(),keyBytes);
+			let plaintext = AesKey::new(&key)?.cfb_decrypt(iv,reader.data)?;
+			let checkBytes = if (usage == 254) {20} else {2};
+			let (material,check) = plaintext.split_at(plaintext.len().checked_sub(checkBytes)?);
+			let isValid = if (usage == 254) {SecureHash::Sha1.digest_get(&[material]) == check} else {checksum16_get(material) == check};
+			// a wrong passphrase fails the check
+			if (!isValid)
+			{
+				return None;
+			}
+			material.to_vec()
+		},
+		_ => return None,
+	};
+	// private exponent then the primes, the last coefficient is computed again
+	let mut material = OpenPgpReader {data: &plaintext};
+	let (exponent,first,second) = (material.mpi()?,material.mpi()?,material.mpi()?);
+	let primes = vec![BigUint::from_bytes_be(first),BigUint::from_bytes_be(second)];
+	return Some(Some(RsaPrivateKey::from_components(public.n().clone(),public.e().clone(),BigUint::from_bytes_be(exponent),primes).ok()?));
+}
+
+/// salted and iterated string-to-key, one hash context per block of the key
+fn s2k_derive(hash: SecureHash, salt: &[u8], count: usize, passphrase: &[u8], keyBytes: usize) -> Vec<u8>
+{
+	let chunk = [salt,passphrase].concat();
+	let total = count.max(chunk.len());
+	// the repeated chunk is hashed by large blocks
+	let repeated = chunk.repeat((4_096 / chunk.len().max(1)).max(1));
+	let mut key = Vec::new();
+	let mut round = 0;
+	while key.len() < keyBytes
+	{
+		let mut hasher = hash.hasher_get();
+		hasher.update(&vec![0; round]);
+		let mut remaining = total;
+		while !chunk.is_empty() && remaining > 0
+		{
+			let size = remaining.min(repeated.len());
+			hasher.update(&repeated[..size]);
+			remaining -= size;
+		}
+		key.extend(hasher.finalize());
+		round += 1;
+	}
+	key.truncate(keyBytes);
+	return key;
+}
+
+fn checksum16_get(data: &[u8]) -> [u8; 2]
+{
+	return (data.iter().fold(0u16,|sum,byte| sum.wrapping_add(*byte as u16))).to_be_bytes();
+}
+
+fn hash_get(algorithm: u8) -> Option<SecureHash>
+{
+	return match algorithm
+	{
+		2 => Some(SecureHash::Sha1),
+		8 => Some(SecureHash::Sha256),
+		9 => Some(SecureHash::Sha384),
+		10 => Some(SecureHash::Sha512),
+		11 => Some(SecureHash::Sha224),
+		_ => None,
+	};
+}
+
+/// AES-128, AES-192 and AES-256
+fn algorithm_keyBytes(algorithm: u8) -> Option<usize>
+{
+	return match algorithm
+	{
+		7 => Some(16),
+		8 => Some(24),
+		9 => Some(32),
+		_ => None,
+	};
+}
+
+/// decrypted session key: its algorithm, the key and a checksum
+fn sessionKey_read(decrypted: &[u8]) -> Result<(u8,Vec<u8>),MailSecureError>
+{
+	let (&algorithm,rest) = decrypted.split_first().ok_or(MailSecureError::Invalid)?;
+	let keyBytes = algorithm_keyBytes(algorithm).ok_or(MailSecureError::Unsupported)?;
+	if (rest.len() != keyBytes + 2 || checksum16_get(&rest[..keyBytes]) != rest[keyBytes..])
+	{
+		return Err(MailSecureError::Invalid);
+	}
+	return Ok((algorithm,rest[..keyBytes].to_vec()));
+}
+
+/// packets of a decrypted message: compressed data, a literal and maybe its signature
+fn message_read(data: &[u8], keys: &[OpenPgpKey], depth: usize) -> Result<OpenPgpMessage,MailSecureError>
+{
+	if (depth > OpenPgpLimits::NESTING_MAXIMUM)
+	{
+		return Err(MailSecureError::Invalid);
+	}
+	let packets = packets_read(data).ok_or(MailSecureError::Invalid)?;
+	let mut content = None;
+	let mut signature = None;
+	for packet in packets
+	{
+		match packet.tag
+		{
+			TAG_COMPRESSED =>
+			{
+				let (&algorithm,compressed) = packet.body.split_first().ok_or(MailSecureError::Invalid)?;
+				let limit = OpenPgpLimits::DECOMPRESSED_MAXIMUM_BYTES;
+				let decompressed = match algorithm
+				{
+					0 => Ok(compressed.to_vec()),
+					1 => miniz_oxide::inflate::decompress_to_vec_with_limit(compressed,limit),
+					2 => miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(compressed,limit),
+					_ => return Err(MailSecureError::Unsupported),
+				};
+				return message_read(&decompressed.map_err(|_| MailSecureError::Invalid)?,keys,depth + 1);
+			},
+			TAG_LITERAL =>
+			{
+				// format, file name and date before the data
+				let mut reader = OpenPgpReader {data: &packet.body};
+				reader.byte().ok_or(MailSecureError::Invalid)?;
+				let nameLength = reader.byte().ok_or(MailSecureError::Invalid)? as usize;
+				reader.bytes(nameLength + 4).ok_or(MailSecureError::Invalid)?;
+				content = Some(reader.data.to_vec());
+			},
+			TAG_SIGNATURE => signature = Some(packet.body),
+			_ => {},
+		}
+	}
+	let content = content.ok_or(MailSecureError::Invalid)?;
+	let signature = signature.map(|signature| signature_check(&signature,&content,keys));
+	return Ok(OpenPgpMessage {content,signature});
+}
+
+/// version 4 signature of a binary or text document, by one of the configured keys
+fn signature_check(body: &[u8], content: &[u8], keys: &[OpenPgpKey]) -> MailSignatureCheck
+{
+	let mut reader = OpenPgpReader {data: body};
+	let (Some(4),Some(signatureType),Some(algorithm),Some(hashAlgorithm),Some(hashedLength)) = (reader.byte(),reader.byte(),reader.byte(),reader.byte(),reader.u16()) else {
+		return Err(MailSignatureStatus::Unsupported);
+	};
+	let Some(hashed) = reader.bytes(hashedLength) else {return Err(MailSignatureStatus::Invalid)};
+	let Some(unhashed) = reader.u16().and_then(|length| reader.bytes(length)) else {return Err(MailSignatureStatus::Invalid)};
+	let (Some(left16),Some(value)) = (reader.bytes(2),reader.mpi()) else {return Err(MailSignatureStatus::Invalid)};
+
+	let mut issuer = None;
+	let mut issuerFingerprint = None;
+	for (kind,data) in subpackets_read(hashed).into_iter().chain(subpackets_read(unhashed))
+	{
+		match kind
+		{
+			16 if data.len() == 8 => issuer = Some(data),
+			// version then fingerprint
+			33 if data.len() == 21 => issuerFingerprint = Some(&data[1..]),
+			_ => {},
+		}
+	}
+	let key = keys.iter().find(|key| issuerFingerprint.is_some_and(|fingerprint| fingerprint == key.fingerprint) || issuer.is_some_and(|issuer| issuer == key.keyId));
+	let Some(key) = key else {
+		return Err(MailSignatureStatus::UnknownKey(hex_get(issuerFingerprint.or(issuer).unwrap_or_default())));
+	};
+	let (Some(hash),Some(public),true) = (hash_get(hashAlgorithm),key.public.as_ref(),ALGORITHMS_RSA.contains(&algorithm)) else {
+		return Err(MailSignatureStatus::Unsupported);
+	};
+	let content = match signatureType
+	{
+		0x00 => content.to_vec(),
+		0x01 => crlf_canonicalize(content),
+		_ => return Err(MailSignatureStatus::Invalid),
+	};
+	let signedPart = &body[..6 + hashedLength];
+	let digest = hash.digest_get(&[&content,signedPart,&[0x04,0xff],&(signedPart.len() as u32).to_be_bytes()]);
+	if (digest[..2] != *left16 || !hash.rsa_verify(public,&digest,value))
+	{
+		return Err(MailSignatureStatus::Invalid);
+	}
+	// the configured public keys are the trusted ones, any of their user IDs may be the sender
+	return Ok(MailSigner {label: key.label_get(),addresses: key.userIds.clone(),isTrusted: true});
+}
+
+fn subpackets_read(data: &[u8]) -> Vec<(u8,&[u8])>
+{
+	let mut subpackets = Vec::new();
+	let mut position = 0;
+	while position < data.len()
+	{
+		let Some((length,false)) = newLength_read(data,&mut position) else {break};
+		let Some(subpacket) = data.get(position..position + length).filter(|subpacket| !subpacket.is_empty()) else {break};
+		position += length;
+		// the critical bit is not needed to find the issuer
+		subpackets.push((subpacket[0] & 0x7f,&subpacket[1..]));
+	}
+	return subpackets;
+}
+
+fn hex_get(bytes: &[u8]) -> String
+{
+	return bytes.iter().map(|byte| format!("{:02X}",byte)).collect();
+}
@@ -0,0 +1,334 @@
+use base64ct::{Base64, Encoding};
+use cms::cert::x509::der::asn1::{Ia5StringRef, OctetString, OctetStringRef};
+use cms::cert::x509::der::{Decode, DecodeOwned, Encode};
+use cms::cert::x509::ext::pkix::name::GeneralName;
+use cms::cert::x509::ext::pkix::{SubjectAltName, SubjectKeyIdentifier};
+use cms::cert::x509::spki::ObjectIdentifier;
+use cms::cert::x509::Certificate;
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::enveloped_data::{EnvelopedData, RecipientInfo};
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+use super::hash::SecureHash;
+use super::symmetric::AesKey;
+use super::{MailSecureError, MailSignatureCheck, MailSignatureStatus, MailSigner};
+
+const OID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const OID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const OID_ENVELOPED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.3");
+const OID_EMAIL_ADDRESS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.1");
+const OID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const OID_RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const OID_COMMON_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.3");
+const OID_SUBJECT_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.14");
+const OID_SUBJECT_ALT_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.17");
+// sha1, sha256, sha384, sha512 and sha224 WithRSAEncryption
+const OID_RSA_SIGNATURES: [ObjectIdentifier; 5] = [
+	ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.5"),
+	ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11"),
+	ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12"),
+	ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13"),
+	ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.14"),
+];
+const OID_HASHES: [(ObjectIdentifier,SecureHash); 5] = [
+	(ObjectIdentifier::new_unwrap("1.3.14.3.2.26"),SecureHash::Sha1),
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1"),SecureHash::Sha256),
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2"),SecureHash::Sha384),
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3"),SecureHash::Sha512),
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.4"),SecureHash::Sha224),
+];
+// aes-CBC 128, 192 and 256 with the length of their key
+const OID_AES_CBC: [(ObjectIdentifier,usize); 3] = [
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.1.2"),16),
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.1.22"),24),
+	(ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.1.42"),32),
+];
+
+/// content of an application/pkcs7-mime part
+pub(super) enum CmsContent
+{
+	Signed
+	{
+		content: Vec<u8>,
+		signature: MailSignatureCheck,
+	},
+	Enveloped(Vec<u8>),
+}
+
+/// PKCS #8 or PKCS #1 RSA key of a PEM text, other blocks (the certificate of the key) are ignored
+pub(super) fn privateKey_parse(text: &str) -> Result<RsaPrivateKey,MailSecureError>
+{
+	for (label,data) in pem_decode(text)
+	{
+		match label.as_str()
+		{
+			"PRIVATE KEY" => return RsaPrivateKey::from_pkcs8_der(&data).map_err(|_| MailSecureError::KeyInvalid),
+			"RSA PRIVATE KEY" => return RsaPrivateKey::from_pkcs1_der(&data).map_err(|_| MailSecureError::KeyInvalid),
+			// a passphrase-protected key would need PBES2, the module content is already encrypted
+			"ENCRYPTED PRIVATE KEY" => return Err(MailSecureError::Unsupported),
+			_ => {},
+		}
+	}
+	return Err(MailSecureError::KeyInvalid);
+}
+
+/// DER of the CERTIFICATE blocks of a PEM text
+pub(super) fn certificates_parse(text: &str) -> Vec<Vec<u8>>
+{
+	return pem_decode(text).into_iter()
+		.filter(|(label,_)| label == "CERTIFICATE")
+		.map(|(_,data)| data)
+		.collect();
+}
+
+/// detached signature of a multipart/signed mail, over its canonical first part.
+/// The signer is only trusted when its certificate is one of the trusted ones
+pub(super) fn signature_verify(signature: &[u8], content: &[u8], trusted: &[Vec<u8>]) -> MailSignatureCheck
+{
+	let Some(signedData) = contentInfo_read::<SignedData>(signature,OID_SIGNED_DATA) else {return Err(MailSignatureStatus::Invalid)};
+	return signedData_verify(&signedData,Some(content),trusted).map_or(Err(MailSignatureStatus::Invalid),|(_,signature)| signature);
+}
+
+/// opaque signed or enveloped message, the enveloped one needs the private key of the recipient
+pub(super) fn message_open(message: &[u8], key: Option<&RsaPrivateKey>, trusted: &[Vec<u8>]) -> Result<CmsContent,MailSecureError>
+{
+	if let Some(signedData) = contentInfo_read::<SignedData>(message,OID_SIGNED_DATA)
+	{
+		let (content,signature) = signedData_verify(&signedData,None,trusted).ok_or(MailSecureError::Invalid)?;
+		return Ok(CmsContent::Signed {content: content.ok_or(MailSecureError::Invalid)?,signature});
+	}
+	let envelopedData = contentInfo_read::<EnvelopedData>(message,OID_ENVELOPED_DATA).ok_or(MailSecureError::Invalid)?;
+	let key = key.ok_or(MailSecureError::NoKey)?;
+	return envelopedData_open(&envelopedData,key).map(CmsContent::Enveloped);
+}
+
+fn pem_decode(text: &str) -> Vec<(String,Vec<u8>)>
+{
+	let mut blocks = Vec::new();
+	let mut current: Option<(String,String)> = None;
+	for line in text.lines().map(str::trim)
+	{
+		if let Some(label) = line.strip_prefix("-----BEGIN ").and_then(|line| line.strip_suffix("-----"))
+		{
+			current = Some((label.to_string(),String::new()));
+		}
+		else if (line.starts_with("-----END "))
+		{
+			if let Some((label,encoded)) = current.take() && let Ok(data) = Base64::decode_vec(&encoded)
+			{
+				blocks.push((label,data));
+			}
+		}
+		else if let Some((_,encoded)) = current.as_mut()
+		{
+			encoded.push_str(line);
+		}
+	}
+	return blocks;
+}
+
+/// inner structure of a ContentInfo of this type
+fn contentInfo_read<T: DecodeOwned>(data: &[u8], contentType: ObjectIdentifier) -> Option<T>
+{
+	let info = ContentInfo::from_der(data).ok().filter(|info| info.content_type == contentType)?;
+	return T::from_der(&info.content.to_der().ok()?).ok();
+}
+
+/// encapsulated content when it is not detached, with the check of the first signer
+fn signedData_verify(signedData: &SignedData, detached: Option<&[u8]>, trusted: &[Vec<u8>]) -> Option<(Option<Vec<u8>>,MailSignatureCheck)>
+{
+	let encapsulated = &signedData.encap_content_info;
+	if (encapsulated.econtent_type != OID_DATA)
+	{
+		return None;
+	}
+	let encapsulatedContent = match &encapsulated.econtent
+	{
+		Some(content) => Some(content.decode_as::<OctetString>().ok()?.into_bytes()),
+		None => None,
+	};
+	let certificates = signedData.certificates.iter()
+		.flat_map(|certificates| certificates.0.iter())
+		.filter_map(|certificate| match certificate
+		{
+			CertificateChoices::Certificate(certificate) => Some(certificate),
+			CertificateChoices::Other(_) => None,
+		})
+		.collect::<Vec<_>>();
+	let signerInfo = signedData.signer_infos.0.iter().next()?;
+
+	let content = detached.map(<[u8]>::to_vec).or_else(|| encapsulatedContent.clone())?;
+	let signature = signerInfo_verify(signerInfo,&content,&certificates,trusted);
+	return Some((encapsulatedContent,signature));
+}
+
+/// the certificates of the message only give the key of the signer, the user decides which ones are trusted
+fn signerInfo_verify(signerInfo: &SignerInfo, content: &[u8], certificates: &[&Certificate], trusted: &[Vec<u8>]) -> MailSignatureCheck
+{
+	let certificate = certificates.iter().find(|certificate| certificate_isSigner(certificate,&signerInfo.sid));
+	let Some(certificate) = certificate else {
+		return Err(MailSignatureStatus::UnknownKey(signerId_label(&signerInfo.sid)));
+	};
+	let hash = OID_HASHES.iter().find(|(oid,_)| *oid == signerInfo.digest_alg.oid).map(|(_,hash)| *hash);
+	let signatureAlgorithm = signerInfo.signature_algorithm.oid;
+	let isRsa = signatureAlgorithm == OID_RSA_ENCRYPTION || OID_RSA_SIGNATURES.contains(&signatureAlgorithm);
+	let (Some(hash),Some(public),true) = (hash,certificate_publicKey(certificate),isRsa) else {
+		return Err(MailSignatureStatus::Unsupported);
+	};
+
+	let contentDigest = hash.digest_get(&[content]);
+	let digest = match &signerInfo.signed_attrs
+	{
+		Some(attributes) =>
+		{
+			let messageDigest = attributes.iter()
+				.find(|attribute| attribute.oid == OID_MESSAGE_DIGEST)
+				.and_then(|attribute| attribute.values.iter().next())
+				.and_then(|digest| digest.decode_as::<OctetStringRef<'_>>().ok());
+			if (messageDigest.map(|digest| digest.as_bytes()) != Some(contentDigest.as_slice()))
+			{
+				return Err(MailSignatureStatus::Invalid);
+			}
+			// the attributes are signed with their SET tag instead of the IMPLICIT one
+			let Ok(encoded) = attributes.to_der() else {return Err(MailSignatureStatus::Invalid)};
+			hash.digest_get(&[&encoded])
+		},
+		None => contentDigest,
+	};
+	if (!hash.rsa_verify(&public,&digest,signerInfo.signature.as_bytes()))
+	{
+		return Err(MailSignatureStatus::Invalid);
+	}
+	let addresses = certificate_addresses(certificate);
+	return Ok(MailSigner {
+		label: certificate_label(certificate,&addresses),
+		addresses,
+		isTrusted: certificate.to_der().is_ok_and(|raw| trusted.contains(&raw)),
+	});
+}
+
+fn signerId_label(signerId: &SignerIdentifier) -> String
+{
+	let bytes = match signerId
+	{
+		SignerIdentifier::IssuerAndSerialNumber(issuer) => issuer.serial_number.as_bytes(),
+		SignerIdentifier::SubjectKeyIdentifier(identifier) => identifier.0.as_bytes(),
+	};
+	return bytes.iter().map(|byte| format!("{:02X}",byte)).collect();
+}
+
+/// RSA key transport to one of the recipients, then the AES-CBC content
+fn envelopedData_open(envelopedData: &EnvelopedData, key: &RsaPrivateKey) -> Result<Vec<u8>,MailSecureError>
+{
+	let mut error = MailSecureError::KeyMismatch;
+	let mut sessionKey = None;
+	for recipient in envelopedData.recip_infos.0.iter()
+	{
+		// key agreement, KEK and password recipients
+		let RecipientInfo::Ktri(recipient) = recipient else {continue};
+		// RSAES-OAEP
+		if (recipient.key_enc_alg.oid != OID_RSA_ENCRYPTION)
+		{
+			error = MailSecureError::Unsupported;
+			continue;
+		}
+		if let Ok(decrypted) = key.decrypt(Pkcs1v15Encrypt,recipient.enc_key.as_bytes())
+		{
+			sessionKey = Some(decrypted);
+			break;
+		}
+	}
+	let sessionKey = sessionKey.ok_or(error)?;
+
+	let encryptedInfo = &envelopedData.encrypted_content;
+	let algorithm = &encryptedInfo.content_enc_alg;
+	// 3DES, RC2 and the AES modes with authentication
+	let (_,keyBytes) = OID_AES_CBC.iter().find(|(oid,_)| *oid == algorithm.oid).ok_or(MailSecureError::Unsupported)?;
+	let iv = algorithm.parameters.as_ref()
+		.and_then(|parameters| parameters.decode_as::<OctetStringRef<'_>>().ok())
+		.ok_or(MailSecureError::Invalid)?;
+	let encrypted = encryptedInfo.encrypted_content.as_ref().ok_or(MailSecureError::Invalid)?;
+	if (sessionKey.len() != *keyBytes)
+	{
+		return Err(MailSecureError::Invalid);
+	}
+	let cipher = AesKey::new(&sessionKey).ok_or(MailSecureError::Invalid)?;
+	return cipher.cbc_decrypt(iv.as_bytes(),encrypted.as_bytes()).ok_or(MailSecureError::Invalid);
+}
+
+// The chain of authorities of a certificate is not verified, the trusted ones are listed by the user
+
+fn certificate_publicKey(certificate: &Certificate) -> Option<RsaPublicKey>
+{
+	let publicKeyInfo = &certificate.tbs_certificate.subject_public_key_info;
+	if (publicKeyInfo.algorithm.oid != OID_RSA_ENCRYPTION)
+	{
+		return None;
+	}
+	return RsaPublicKey::from_pkcs1_der(publicKeyInfo.subject_public_key.as_bytes()?).ok();
+}
+
+/// IssuerAndSerialNumber or subject key identifier of a signer
+fn certificate_isSigner(certificate: &Certificate, signerId: &SignerIdentifier) -> bool
+{
+	let tbs = &certificate.tbs_certificate;
+	return match signerId
+	{
+		SignerIdentifier::IssuerAndSerialNumber(signer) => signer.issuer == tbs.issuer && signer.serial_number == tbs.serial_number,
+		SignerIdentifier::SubjectKeyIdentifier(signer) =>
+		{
+			certificate_extension(certificate,OID_SUBJECT_KEY_IDENTIFIER)
+				.and_then(|value| SubjectKeyIdentifier::from_der(value).ok())
+				.is_some_and(|identifier| identifier == *signer)
+		},
+	};
+}
+
+fn certificate_extension(certificate: &Certificate, oid: ObjectIdentifier) -> Option<&[u8]>
+{
+	return certificate.tbs_certificate.extensions.iter()
+		.flatten()
+		.find(|extension| extension.extn_id == oid)
+		.map(|extension| extension.extn_value.as_bytes());
+}
+
+/// mail addresses of the alternative names, then the one of the subject
+fn certificate_addresses(certificate: &Certificate) -> Vec<String>
+{
+	let mut addresses = certificate_extension(certificate,OID_SUBJECT_ALT_NAME)
+		.and_then(|value| SubjectAltName::from_der(value).ok())
+		.map(|names| names.0.into_iter().filter_map(|name| match name
+		{
+			GeneralName::Rfc822Name(address) => Some(address.to_string()),
+			_ => None,
+		}).collect::<Vec<_>>())
+		.unwrap_or_default();
+	addresses.extend(certificate_subjectAttribute(certificate,OID_EMAIL_ADDRESS));
+	return addresses;
+}
+
+/// mail address of the alternative names or of the subject, else its common name
+fn certificate_label(certificate: &Certificate, addresses: &[String]) -> String
+{
+	return addresses.first().cloned()
+		.or_else(|| certificate_subjectAttribute(certificate,OID_COMMON_NAME))
+		.unwrap_or_else(|| certificate.tbs_certificate.serial_number.as_bytes().iter().map(|byte| format!("{:02X}",byte)).collect());
+}
+
+fn certificate_subjectAttribute(certificate: &Certificate, oid: ObjectIdentifier) -> Option<String>
+{
+	return certificate.tbs_certificate.subject.0.iter()
+		.flat_map(|names| names.0.iter())
+		.find(|attribute| attribute.oid == oid)
+		.map(|attribute| {
+			// IA5String for the address, UTF8String or PrintableString for the name
+			return match attribute.value.decode_as::<Ia5StringRef<'_>>()
+			{
+				Ok(value) => value.to_string(),
+				Err(_) => String::from_utf8_lossy(attribute.value.value()).into_owned(),
+			};
+		});
+}
@@ -0,0 +1,91 @@
+use aes::{Aes128, Aes192, Aes256};
+use aes::cipher::{Array, BlockCipherDecrypt, BlockCipherEncrypt, KeyInit};
+
+const AES_BLOCK_BYTES: usize = 16;
+
+/// AES key of a mail session, the only cipher of current OpenPGP and S/MIME clients
+pub(super) enum AesKey
+{
+	Aes128(Aes128),
+	Aes192(Aes192),
+	Aes256(Aes256),
+}
+
+impl AesKey
+{
+	pub(super) fn new(key: &[u8]) -> Option<Self>
+	{
+		return match key.len()
+		{
+			16 => Aes128::new_from_slice(key).ok().map(Self::Aes128),
+			24 => Aes192::new_from_slice(key).ok().map(Self::Aes192),
+			32 => Aes256::new_from_slice(key).ok().map(Self::Aes256),
+			_ => None,
+		};
+	}
+
+	fn encrypt(&self, block: [u8; AES_BLOCK_BYTES]) -> [u8; AES_BLOCK_BYTES]
+	{
+		let mut block = Array::from(block);
+		match self
+		{
+			Self::Aes128(cipher) => cipher.encrypt_block(&mut block),
+			Self::Aes192(cipher) => cipher.encrypt_block(&mut block),
+			Self::Aes256(cipher) => cipher.encrypt_block(&mut block),
+		}
+		return block.into();
+	}
+
+	fn decrypt(&self, block: [u8; AES_BLOCK_BYTES]) -> [u8; AES_BLOCK_BYTES]
+	{
+		let mut block = Array::from(block);
+		match self
+		{
+			Self::Aes128(cipher) => cipher.decrypt_block(&mut block),
+			Self::Aes192(cipher) => cipher.decrypt_block(&mut block),
+			Self::Aes256(cipher) => cipher.decrypt_block(&mut block),
+		}
+		return block.into();
+	}
+
+	/// full-block CFB decryption, without the OpenPGP resynchronization
+	pub(super) fn cfb_decrypt(&self, iv: &[u8], data: &[u8]) -> Option<Vec<u8>>
+	{
+		let mut feedback: [u8; AES_BLOCK_BYTES] = iv.try_into().ok()?;
+		let mut plaintext = Vec::with_capacity(data.len());
+		for chunk in data.chunks(AES_BLOCK_BYTES)
+		{
+			let stream = self.encrypt(feedback);
+			plaintext.extend(chunk.iter().zip(stream.iter()).map(|(byte,key)| byte ^ key));
+			if let Ok(chunk) = chunk.try_into()
+			{
+				feedback = chunk;
+			}
+		}
+		return Some(plaintext);
+	}
+
+	/// CBC decryption without its PKCS #7 padding
+	pub(super) fn cbc_decrypt(&self, iv: &[u8], data: &[u8]) -> Option<Vec<u8>>
+	{
+		let mut previous: [u8; AES_BLOCK_BYTES] = iv.try_into().ok()?;
+		if (data.is_empty() || data.len() % AES_BLOCK_BYTES != 0)
+		{
+			return None;
+		}
+		let mut plaintext = Vec::with_capacity(data.len());
+		for chunk in data.chunks_exact(AES_BLOCK_BYTES)
+		{
+			let block: [u8; AES_BLOCK_BYTES] = chunk.try_into().ok()?;
+			plaintext.extend(self.decrypt(block).iter().zip(previous.iter()).map(|(byte,previous)| byte ^ previous));
+			previous = block;
+		}
+		let padding = *plaintext.last()? as usize;
+		if (padding == 0 || padding > AES_BLOCK_BYTES || !plaintext[plaintext.len() - padding..].iter().all(|byte| *byte as usize == padding))
+		{
+			return None;
+		}
+		plaintext.truncate(plaintext.len() - padding);
+		return Some(plaintext);
+	}
+}
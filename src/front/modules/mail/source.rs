@@ -0,0 +1,517 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use leptoaster::expect_toaster;
+use leptos::prelude::{AnyView, ArcRwSignal, AriaAttributes, ClassAttribute, CollectView, ElementChild, Get, GetUntracked, GlobalAttributes, IntoAny, OnAttribute, Set};
+use leptos::{component, view, IntoView};
+use crate::api::proxys::imap::API_proxys_imap_getMailSource;
+use crate::api::proxys::imap_components::{imap_connector, Attachment, ImapMailKey, ImapMailSource, ImapMailSourceHeader};
+use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::utils::contentDownloader::download_attachment;
+use crate::front::utils::toaster_helpers::{toaster_api, toastingErr};
+use crate::front::utils::translate::{Translate, TranslateText};
+use super::attachment_preview::attachmentText_get;
+use super::mailDate_format;
+use super::oauth::MailOAuthRotations;
+
+struct MailSourceLimits;
+
+impl MailSourceLimits
+{
+	// rows of each table, the rest stays readable in the raw source
+	const ROW_MAXIMUM: usize = 200;
+	const FILENAME_SUBJECT_MAXIMUM_CHARACTERS: usize = 80;
+}
+
+/// one Received header, the hops are read from the oldest one
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct MailReceivedHop
+{
+	from: String,
+	by: String,
+	with: String,
+	date: Option<i64>,
+}
+
+/// one check of an Authentication-Results header (RFC 8601)
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct MailAuthenticationResult
+{
+	server: String,
+	method: String,
+	result: String,
+	details: String,
+	/// added by a configured server, any sender can write the other ones
+	isTrusted: bool,
+}
+
+impl MailAuthenticationResult
+{
+	fn class_get(&self) -> &'static str
+	{
+		if (!self.isTrusted)
+		{
+			return "module_mail_source_result module_mail_source_result--unverified";
+		}
+		return match self.result.to_ascii_lowercase().as_str()
+		{
+			"pass" => "module_mail_source_result module_mail_source_result--pass",
+			"fail" | "softfail" | "permerror" => "module_mail_source_result module_mail_source_result--fail",
+			_ => "module_mail_source_result",
+		};
+	}
+}
+
+/// the headers a deliverability check looks at, among the ones the server read from the source
+#[derive(Clone, Debug, Default)]
+struct MailSourceHeaders
+{
+	headers: Vec<(String, String)>,
+	returnPath: Option<String>,
+	received: Vec<MailReceivedHop>,
+	authentication: Vec<MailAuthenticationResult>,
+}
+
+impl MailSourceHeaders
+{
+	/// only the topmost Authentication-Results with a trusted authserv-id are trusted: the receiving server adds its own
+	/// on top and removes the forged ones with its id (RFC 8601 §5), the ones below come from the sender side
+	fn fromHeaders(headers: &[ImapMailSourceHeader], trustedServers: &[String]) -> Self
+	{
+		let mut result = Self::default();
+		let mut isTrustedRun = true;
+		for header in headers.iter().take(MailSourceLimits::ROW_MAXIMUM)
+		{
+			let name = header.name.clone();
+			let value = header.value.clone();
+			match name.to_ascii_lowercase().as_str()
+			{
+				"return-path" => result.returnPath = result.returnPath.take().or_else(|| Some(value.clone())),
+				"received" => result.received.push(Self::receivedHop_parse(&value, header.date)),
+				"authentication-results" =>
+				{
+					let server = Self::authenticationServer_parse(&value).trim_end_matches('.').to_ascii_lowercase();
+					isTrustedRun &= trustedServers.contains(&server);
+					result.authentication.extend(Self::authentication_parse(&value, isTrustedRun));
+				},
+				_ => {},
+			}
+			result.headers.push((name, value));
+		}
+		// each server adds its Received header on top of the previous ones
+		result.received.reverse();
+		result.authentication.truncate(MailSourceLimits::ROW_MAXIMUM);
+		return result;
+	}
+
+	/// "from A (comment) by B with C id D; date", the comments stay with their clause. The date is read by the server
+	fn receivedHop_parse(value: &str, date: Option<i64>) -> MailReceivedHop
+	{
+		let clauses = value.rsplit_once(';').map_or(value, |(clauses, _)| clauses);
+		let mut hop = MailReceivedHop {date, ..Default::default()};
+		let mut clause = "";
+		for word in topLevel_split(clauses, char::is_whitespace)
+		{
+			let keyword = word.to_ascii_lowercase();
+			if (matches!(keyword.as_str(), "from" | "by" | "with" | "id" | "for" | "via"))
+			{
+				clause = match keyword.as_str()
+				{
+					"from" => "from",
+					"by" => "by",
+					"with" => "with",
+					_ => "",
+				};
+				continue;
+			}
+			let target = match clause
+			{
+				"from" => &mut hop.from,
+				"by" => &mut hop.by,
+				"with" => &mut hop.with,
+				_ => continue,
+			};
+			if (!target.is_empty())
+			{
+				target.push(' ');
+			}
+			target.push_str(word);
+		}
+		return hop;
+	}
+
+	/// authserv-id of "server [version]; ...", without its version
+	fn authenticationServer_parse(value: &str) -> String
+	{
+		return topLevel_split(value, |character| character == ';').first()
+			.and_then(|server| server.split_whitespace().next())
+			.unwrap_or_default()
+			.to_string();
+	}
+
+	/// "server; method=result properties; ...", the comments in parentheses stay in the details
+	fn authentication_parse(value: &str, isTrusted: bool) -> Vec<MailAuthenticationResult>
+	{
+		let server = Self::authenticationServer_parse(value);
+		return topLevel_split(value, |character| character == ';').into_iter().skip(1).filter_map(|statement| {
+			let statement = statement.trim();
+			let (methodResult, details) = statement.split_once(char::is_whitespace).unwrap_or((statement, ""));
+			let (method, result) = methodResult.split_once('=')?;
+			return Some(MailAuthenticationResult {
+				server: server.clone(),
+				method: method.trim().to_ascii_lowercase(),
+				result: result.trim().to_ascii_lowercase(),
+				details: details.split_whitespace().collect::<Vec<_>>().join(" "),
+				isTrusted,
+			});
+		}).collect();
+	}
+}
+
+/// parts of the value split on the separator outside of comments and quoted strings, without the empty ones
+fn topLevel_split(value: &str, isSeparator: impl Fn(char) -> bool) -> Vec<&str>
+{
+	let mut parts = Vec::new();
+	let mut depth = 0usize;
+	let mut isQuoted = false;
+	let mut start = 0;
+	for (index, character) in value.char_indices()
+	{
+		match character
+		{
+			'"' if depth == 0 => isQuoted = !isQuoted,
+			'(' if !isQuoted => depth += 1,
+			')' if !isQuoted => depth = depth.saturating_sub(1),
+			_ if depth == 0 && !isQuoted && isSeparator(character) =>
+			{
+				parts.push(&value[start..index]);
+				start = index + character.len_utf8();
+			},
+			_ => {},
+		}
+	}
+	parts.push(&value[start..]);
+	return parts.into_iter().map(str::trim).filter(|part| !part.is_empty()).collect();
+}
+
+/// time spent since the previous hop, servers with a wrong clock give negative delays
+fn hopDelay_format(seconds: i64) -> String
+{
+	let sign = if (seconds < 0) {"-"} else {"+"};
+	let seconds = seconds.unsigned_abs();
+	return match seconds
+	{
+		0..60 => format!("{}{} s", sign, seconds),
+		60..3_600 => format!("{}{} min", sign, seconds / 60),
+		_ => format!("{}{} h {} min", sign, seconds / 3_600, seconds % 3_600 / 60),
+	};
+}
+
+/// source of the opened mail, fetched the first time its inspector is shown
+#[derive(Clone)]
+pub(super) struct MailSource
+{
+	connector: imap_connector,
+	oauthRotations: MailOAuthRotations,
+	mail: ImapMailKey,
+	subject: Option<String>,
+	trustedServers: Arc<[String]>,
+	source: ArcRwSignal<Option<Arc<ImapMailSource>>>,
+	shown: ArcRwSignal<bool>,
+	loading: ArcRwSignal<bool>,
+	moduleActions: ModuleActionFn,
+}
+
+impl MailSource
+{
+	pub(super) fn new(connector: imap_connector, oauthRotations: MailOAuthRotations, mail: ImapMailKey, subject: Option<String>, trustedServers: Vec<String>, moduleActions: ModuleActionFn) -> Self
+	{
+		return Self {
+			connector,
+			oauthRotations,
+			mail,
+			subject,
+			trustedServers: trustedServers.into(),
+			source: ArcRwSignal::new(None),
+			shown: ArcRwSignal::new(false),
+			loading: ArcRwSignal::new(false),
+			moduleActions,
+		};
+	}
+
+	pub(super) fn action_draw(&self) -> AnyView
+	{
+		let shownPressed = self.shown.clone();
+		let loadingBusy = self.loading.clone();
+		let mailSource = self.clone();
+		return view!{
+			<button type="button"
+				aria-pressed=move || shownPressed.get().to_string()
+				aria-busy=move || loadingBusy.get().to_string()
+				on:click=move |_| mailSource.toggle()
+			><Translate key="MODULE_MAIL_SOURCE_ACTION"/></button>
+		}.into_any();
+	}
+
+	pub(super) fn inspector_draw(&self) -> impl Fn() -> Option<AnyView> + Send + Sync + 'static
+	{
+		let mailSource = self.clone();
+		return move || {
+			if (!mailSource.shown.get())
+			{
+				return None;
+			}
+			let source = mailSource.source.get()?;
+			return Some(view!{<MailSourceInspector source subject=mailSource.subject.clone() trustedServers=mailSource.trustedServers.clone() shown=mailSource.shown.clone() moduleActions=mailSource.moduleActions.clone()/>}.into_any());
+		};
+	}
+
+	fn toggle(&self)
+	{
+		if (!self.moduleActions.lifecycle_isActive() || self.loading.get_untracked())
+		{
+			return;
+		}
+		if (self.shown.get_untracked() || self.source.get_untracked().is_some())
+		{
+			self.shown.set(!self.shown.get_untracked());
+			return;
+		}
+		let toaster = expect_toaster();
+		let mailSource = self.clone();
+		let moduleActionsTask = self.moduleActions.clone();
+		self.loading.set(true);
+		self.moduleActions.task_spawn(async move {
+			let apiResult = mailSource.oauthRotations.keep(&mailSource.connector, API_proxys_imap_getMailSource(mailSource.connector.clone(), mailSource.mail.clone()).await);
+			mailSource.loading.set(false);
+			if (!moduleActionsTask.lifecycle_isActive())
+			{
+				return;
+			}
+			let Some(source) = toaster_api(&toaster, apiResult, None).await else {return};
+			mailSource.source.set(Some(Arc::new(source)));
+			mailSource.shown.set(true);
+		});
+	}
+}
+
+/// the parsed headers of a deliverability check over the raw source, which can be saved as an .eml file
+#[component]
+fn MailSourceInspector(source: Arc<ImapMailSource>, subject: Option<String>, trustedServers: Arc<[String]>, shown: ArcRwSignal<bool>, moduleActions: ModuleActionFn) -> impl IntoView
+{
+	let headers = MailSourceHeaders::fromHeaders(&source.headers, &trustedServers);
+	let (rawText, isRawTruncated) = attachmentText_get(&source.data);
+	let isTruncated = source.isTruncated();
+
+	let downloadAction = (!isTruncated).then(|| {
+		let source = source.clone();
+		view!{
+			<button type="button" class="module_mail_source_download" on:click=move |_| {
+				let filename = format!("{}.eml", subject.as_deref()
+					.map(|subject| subject.chars().take(MailSourceLimits::FILENAME_SUBJECT_MAXIMUM_CHARACTERS).collect::<String>())
+					.filter(|subject| !subject.trim().is_empty())
+					.unwrap_or_else(|| "message".to_string()));
+				let attachment = Attachment {
+					filename: Some(filename),
+					content_type: "message/rfc822".to_string(),
+					data: source.data.clone(),
+					..Default::default()
+				};
+				if (moduleActions.lifecycle_isActive() && !download_attachment(attachment))
+				{
+					let toaster = expect_toaster();
+					moduleActions.task_spawn(async move {
+						toastingErr(&toaster, "MODULE_MAIL_BLOBCREATORERROR").await;
+					});
+				}
+			}><i class="iconoir-download" aria-hidden="true"/>{" "}<TranslateText key="MODULE_MAIL_SOURCE_DOWNLOAD"/></button>
+		}
+	});
+
+	let authentication = if (headers.authentication.is_empty())
+	{
+		view!{<p class="module_mail_source_notice"><TranslateText key="MODULE_MAIL_SOURCE_AUTHENTICATION_NONE"/></p>}.into_any()
+	}
+	else
+	{
+		let untrustedNotice = headers.authentication.iter().any(|result| !result.isTrusted).then(|| {
+			let key = if (trustedServers.is_empty()) {"MODULE_MAIL_SOURCE_AUTHENTICATION_NO_SERVER"} else {"MODULE_MAIL_SOURCE_AUTHENTICATION_UNTRUSTED"};
+			view!{<p class="module_mail_source_notice"><TranslateText key=key/></p>}
+		});
+		let rows = headers.authentication.iter().map(|result| view!{
+			<tr>
+				<td>{result.method.clone()}</td>
+				<td>
+					<span class=result.class_get()>{result.result.clone()}</span>
+					{(!result.isTrusted).then(|| view!{" "<span class="module_mail_source_unverified"><TranslateText key="MODULE_MAIL_SOURCE_UNVERIFIED"/></span>})}
+				</td>
+				<td>{result.details.clone()}</td>
+				<td>{result.server.clone()}</td>
+			</tr>
+		}).collect_view();
+		view!{
+			{untrustedNotice}
+			<table class="module_mail_source_table">
+				<thead><tr>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_METHOD"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_RESULT"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_DETAILS"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_SERVER"/></th>
+				</tr></thead>
+				<tbody>{rows}</tbody>
+			</table>
+		}.into_any()
+	};
+
+	let received = if (headers.received.is_empty())
+	{
+		view!{<p class="module_mail_source_notice"><TranslateText key="MODULE_MAIL_SOURCE_RECEIVED_NONE"/></p>}.into_any()
+	}
+	else
+	{
+		let mut previousDate = None;
+		let rows = headers.received.iter().enumerate().map(|(index, hop)| {
+			let delay = hop.date.zip(previousDate).map(|(date, previous): (i64, i64)| hopDelay_format(date - previous));
+			previousDate = hop.date.or(previousDate);
+			view!{
+				<tr>
+					<td>{index + 1}</td>
+					<td>{hop.from.clone()}</td>
+					<td>{hop.by.clone()}</td>
+					<td>{hop.with.clone()}</td>
+					<td>{hop.date.map(mailDate_format).unwrap_or_default()}</td>
+					<td>{delay.unwrap_or_default()}</td>
+				</tr>
+			}
+		}).collect_view();
+		view!{
+			<table class="module_mail_source_table">
+				<thead><tr>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_HOP"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_FROM"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_BY"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_WITH"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_DATE"/></th>
+					<th scope="col"><TranslateText key="MODULE_MAIL_SOURCE_DELAY"/></th>
+				</tr></thead>
+				<tbody>{rows}</tbody>
+			</table>
+		}.into_any()
+	};
+
+	let headerCount = HashMap::from([("count".to_string(), headers.headers.len().to_string())]);
+	let headerRows = headers.headers.into_iter().map(|(name, value)| view!{
+		<tr><th scope="row">{name}</th><td>{value}</td></tr>
+	}).collect_view();
+
+	return view!{
+		<section class="module_mail_source">
+			<div class="module_mail_source_header">
+				<h4><TranslateText key="MODULE_MAIL_SOURCE_TITLE"/></h4>
+				<div class="module_mail_source_actions">
+					{downloadAction}
+					<button type="button" class="module_mail_attachment_preview_close" on:click=move |_| shown.set(false)>
+						<i class="iconoir-xmark" aria-hidden="true"></i>
+						<span class="visually_hidden"><TranslateText key="MODULE_MAIL_SOURCE_CLOSE"/></span>
+					</button>
+				</div>
+			</div>
+			{isTruncated.then(|| view!{<p class="module_mail_source_notice" role="status"><TranslateText key="MODULE_MAIL_SOURCE_TRUNCATED"/></p>})}
+			<dl class="module_mail_source_summary">
+				<dt><TranslateText key="MODULE_MAIL_SOURCE_RETURN_PATH"/></dt>
+				<dd>{headers.returnPath.unwrap_or_else(|| "—".to_string())}</dd>
+			</dl>
+			<h5><TranslateText key="MODULE_MAIL_SOURCE_AUTHENTICATION"/></h5>
+			{authentication}
+			<h5><TranslateText key="MODULE_MAIL_SOURCE_RECEIVED"/></h5>
+			{received}
+			<details>
+				<summary><TranslateText key="MODULE_MAIL_SOURCE_HEADERS" params=headerCount/></summary>
+				<table class="module_mail_source_table module_mail_source_headers"><tbody>{headerRows}</tbody></table>
+			</details>
+			<details>
+				<summary><TranslateText key="MODULE_MAIL_SOURCE_RAW"/></summary>
+				<pre class="module_mail_attachment_text">{rawText}</pre>
+				{isRawTruncated.then(|| view!{<p class="module_mail_source_notice"><TranslateText key="MODULE_MAIL_SOURCE_RAW_TRUNCATED"/></p>})}
+			</details>
+		</section>
+	};
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::proxys::imap_components::ImapMailSource;
+	use super::{hopDelay_format, MailSourceHeaders};
+
+	#[test]
+	fn sourceHeaders_readTheReceivedChainFromTheOldestHop()
+	{
+		let source = concat!(
+			"Return-Path: <bounce@news.example.com>\r\n",
+			"Received: from mx.example.org (mx.example.org [192.0.2.7]) by inbox.example.org with LMTP id abc; Mon, 5 Oct 2026 10:00:42 +0000\r\n",
+			"Received: from mail.example.com (mail.example.com [198.51.100.2])\r\n\tby mx.example.org (Postfix) with ESMTPS id 42\r\n\tfor <me@example.org>; Mon, 5 Oct 2026 10:00:30 +0000\r\n",
+			"Subject: News\r\n\r\nbody\r\n",
+		);
+
+		let source = ImapMailSource::new(source.as_bytes().to_vec(), source.len() as u32);
+		let headers = MailSourceHeaders::fromHeaders(&source.headers, &[]);
+
+		assert_eq!(headers.returnPath.as_deref(), Some("<bounce@news.example.com>"));
+		assert_eq!(headers.headers.len(), 4);
+		assert_eq!(headers.received.len(), 2);
+		assert_eq!(headers.received[0].from, "mail.example.com (mail.example.com [198.51.100.2])");
+		assert_eq!(headers.received[0].by, "mx.example.org (Postfix)");
+		assert_eq!(headers.received[0].with, "ESMTPS");
+		assert_eq!(headers.received[1].with, "LMTP");
+		assert_eq!(headers.received[1].date.zip(headers.received[0].date).map(|(last, first)| last - first), Some(12));
+		assert_eq!(hopDelay_format(12), "+12 s");
+		assert_eq!(hopDelay_format(-3_900), "-1 h 5 min");
+	}
+
+	#[test]
+	fn sourceHeaders_splitAuthenticationResultsOutsideComments()
+	{
+		let source = concat!(
+			"Authentication-Results: mx.example.org;\r\n",
+			"\tdkim=pass header.i=@example.com header.s=s1;\r\n",
+			"\tspf=softfail (mx.example.org: domain of a; b does not designate 192.0.2.1) smtp.mailfrom=a@example.com;\r\n",
+			"\tdmarc=FAIL (p=REJECT) header.from=example.com\r\n",
+			"\r\n",
+		);
+
+		let source = ImapMailSource::new(source.as_bytes().to_vec(), source.len() as u32);
+		let headers = MailSourceHeaders::fromHeaders(&source.headers, &["mx.example.org".to_string()]);
+
+		let checks = headers.authentication.iter()
+			.map(|result| (result.method.as_str(), result.result.as_str()))
+			.collect::<Vec<_>>();
+		assert_eq!(checks, vec![("dkim", "pass"), ("spf", "softfail"), ("dmarc", "fail")]);
+		assert_eq!(headers.authentication[1].details, "(mx.example.org: domain of a; b does not designate 192.0.2.1) smtp.mailfrom=a@example.com");
+		assert!(headers.authentication.iter().all(|result| result.server == "mx.example.org"));
+		assert!(headers.authentication[2].class_get().ends_with("--fail"));
+	}
+
+	#[test]
+	fn sourceHeaders_trustOnlyTheTopmostResultsOfAConfiguredServer()
+	{
+		let source = concat!(
+			"Authentication-Results: MX.Example.org.; dmarc=fail header.from=bank.example\r\n",
+			"Authentication-Results: mx.example.org 1; none\r\n",
+			"Authentication-Results: relay.example.net; dkim=pass header.d=bank.example\r\n",
+			"Authentication-Results: mx.example.org; spf=pass smtp.mailfrom=bank.example\r\n",
+			"\r\n",
+		);
+
+		let source = ImapMailSource::new(source.as_bytes().to_vec(), source.len() as u32);
+		let trusted = MailSourceHeaders::fromHeaders(&source.headers, &["mx.example.org".to_string()]);
+		let unconfigured = MailSourceHeaders::fromHeaders(&source.headers, &[]);
+
+		let checks = trusted.authentication.iter()
+			.map(|result| (result.method.as_str(), result.isTrusted))
+			.collect::<Vec<_>>();
+		assert_eq!(checks, vec![("dmarc", true), ("dkim", false), ("spf", false)]);
+		assert!(trusted.authentication[0].class_get().ends_with("--fail"));
+		assert!(trusted.authentication[1].class_get().ends_with("--unverified"));
+		assert!(unconfigured.authentication.iter().all(|result| !result.isTrusted));
+	}
+}
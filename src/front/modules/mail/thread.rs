@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use crate::api::proxys::imap_components::ImapMail;
+use super::MailKey;
+
+struct MailThreadLimits;
+
+impl MailThreadLimits
+{
+	// message ids kept from one References header, the oldest are dropped first
+	const REFERENCE_MAXIMUM: usize = 64;
+}
+
+/// mails of one conversation, newest first
+#[derive(Clone, Debug)]
+pub(super) struct MailThread
+{
+	pub(super) mails: Vec<(MailKey,ImapMail)>,
+}
+
+impl MailThread
+{
+	/// the oldest mail, it stays the same while answers arrive
+	pub(super) fn id_get(&self) -> Option<&MailKey>
+	{
+		return self.mails.last().map(|(key,_)| key);
+	}
+
+	pub(super) fn unread_get(&self) -> usize
+	{
+		return self.mails.iter().filter(|(_,mail)| !mail.seen).count();
+	}
+
+	/// groups the mails with the JWZ algorithm (https://www.jwz.org/doc/threading.html) on their Message-ID,
+	/// In-Reply-To and References headers, then by subject for the replies and missing mails. serverThreads are the groups of the IMAP THREAD
+	/// extension, they join threads the headers alone cannot link, such as answers to a mail which is not listed.
+	/// The threads keep the order of their first mail in mails
+	pub(super) fn list_build(mails: Vec<(MailKey,ImapMail)>, serverThreads: &[Vec<MailKey>]) -> Vec<Self>
+	{
+		let mut threader = MailThreader::default();
+		let mut groups = MailGroups::new(mails.len());
+		let mut messageIndexes = HashMap::<String,usize>::new();
+		let mut messageContainers = Vec::with_capacity(mails.len());
+		for (index,(_,mail)) in mails.iter().enumerate()
+		{
+			let messageId = mail.messageId.as_deref().and_then(|header| MailThreader::ids_parse(header).pop());
+			let container = match &messageId
+			{
+				Some(messageId) if !messageIndexes.contains_key(messageId) =>
+				{
+					messageIndexes.insert(messageId.clone(),index);
+					threader.container_get(messageId)
+				},
+				// the same mail in two mailboxes, or without Message-ID: it cannot be referenced
+				Some(messageId) =>
+				{
+					groups.union(index,messageIndexes[messageId]);
+					threader.container_new()
+				},
+				None => threader.container_new(),
+			};
+			threader.message_link(container,&MailThreader::references_get(mail));
+			messageContainers.push(container);
+		}
+
+		let mut roots = HashMap::<usize,usize>::new();
+		for (index,container) in messageContainers.iter().enumerate()
+		{
+			let root = threader.root_get(*container);
+			match roots.get(&root)
+			{
+				Some(first) => groups.union(index,*first),
+				None => {roots.insert(root,index);},
+			}
+		}
+		// a root with a reply subject, or an empty one standing for a missing mail, joins the first original of its subject,
+		// two originals with the same subject stay apart
+		let containerIndexes = messageContainers.iter().enumerate()
+			.map(|(index,container)| (*container,index))
+			.collect::<HashMap<_,_>>();
+		let mut subjects = HashMap::<String,MailSubjectRoots>::new();
+		let mut rootMessages = roots.into_iter().collect::<Vec<_>>();
+		rootMessages.sort_unstable_by_key(|(_,index)| *index);
+		for (root,firstIndex) in rootMessages
+		{
+			// an empty root stands for a missing mail, the subject of its first listed answer replaces it
+			let (index,isDummy) = match containerIndexes.get(&root)
+			{
+				Some(index) => (*index,false),
+				None => (firstIndex,true),
+			};
+			let (subject,isReply) = MailThreader::subject_normalize(mails[index].1.subject.as_deref().unwrap_or_default());
+			if (subject.is_empty())
+			{
+				continue;
+			}
+			let subjectRoots = subjects.entry(subject).or_default();
+			match (isReply || isDummy, subjectRoots.original)
+			{
+				(true,_) => subjectRoots.joined.push(index),
+				(false,None) => subjectRoots.original = Some(index),
+				(false,Some(_)) => {},
+			}
+		}
+		for subjectRoots in subjects.into_values()
+		{
+			let mut indexes = subjectRoots.original.into_iter().chain(subjectRoots.joined);
+			if let Some(first) = indexes.next()
+			{
+				for index in indexes
+				{
+					groups.union(index,first);
+				}
+			}
+		}
+		let keyIndexes = mails.iter().enumerate()
+			.map(|(index,(key,_))| (key,index))
+			.collect::<HashMap<_,_>>();
+		for serverThread in serverThreads
+		{
+			let mut indexes = serverThread.iter().filter_map(|key| keyIndexes.get(key).copied());
+			if let Some(first) = indexes.next()
+			{
+				for index in indexes
+				{
+					groups.union(index,first);
+				}
+			}
+		}
+
+		let mut threadIndexes = HashMap::<usize,usize>::new();
+		let mut threads = Vec::<Self>::new();
+		let groupRoots = (0..mails.len()).map(|index| groups.root_get(index)).collect::<Vec<_>>();
+		for (mail,groupRoot) in mails.into_iter().zip(groupRoots)
+		{
+			match threadIndexes.get(&groupRoot)
+			{
+				Some(threadIndex) => threads[*threadIndex].mails.push(mail),
+				None =>
+				{
+					threadIndexes.insert(groupRoot,threads.len());
+					threads.push(Self {mails: vec![mail]});
+				},
+			}
+		}
+		for thread in &mut threads
+		{
+			thread.mails.sort_by(|(_,left),(_,right)| left.date.cmp(&right.date).reverse());
+		}
+		return threads;
+	}
+}
+
+/// thread roots sharing a normalized subject
+#[derive(Default)]
+struct MailSubjectRoots
+{
+	original: Option<usize>,
+	joined: Vec<usize>,
+}
+
+/// containers of the JWZ algorithm, one per message id, listed or only referenced
+#[derive(Default)]
+struct MailThreader
+{
+	containers: HashMap<String,usize>,
+	parents: Vec<Option<usize>>,
+}
+
+impl MailThreader
+{
+	fn container_get(&mut self, messageId: &str) -> usize
+	{
+		if let Some(container) = self.containers.get(messageId)
+		{
+			return *container;
+		}
+		let container = self.container_new();
+		self.containers.insert(messageId.to_string(),container);
+		return container;
+	}
+
+	fn container_new(&mut self) -> usize
+	{
+		self.parents.push(None);
+		return self.parents.len() - 1;
+	}
+
+	/// a broken chain of parents counts as a loop, linking it again would make it worse
+	fn isAncestor(&self, ancestor: usize, container: usize) -> bool
+	{
+		let mut current = Some(container);
+		for _ in 0..=self.parents.len()
+		{
+			match current
+			{
+				Some(container) if container == ancestor => return true,
+				Some(container) => current = self.parents[container],
+				None => return false,
+			}
+		}
+		return true;
+	}
+
+	fn root_get(&self, container: usize) -> usize
+	{
+		let mut root = container;
+		for _ in 0..self.parents.len()
+		{
+			match self.parents[root]
+			{
+				Some(parent) => root = parent,
+				None => break,
+			}
+		}
+		return root;
+	}
+
+	/// links the references to each other when they have no parent yet, then the message to the last one
+	fn message_link(&mut self, message: usize, references: &[String])
+	{
+		let mut previous = None;
+		for reference in references
+		{
+			let container = self.container_get(reference);
+			if let Some(parent) = previous
+				&& parent != container
+				&& self.parents[container].is_none()
+				&& !self.isAncestor(container,parent)
+			{
+				self.parents[container] = Some(parent);
+			}
+			previous = Some(container);
+		}
+		// the message knows its parent better than the guesses made from the other references
+		self.parents[message] = None;
+		if let Some(parent) = previous && !self.isAncestor(message,parent)
+		{
+			self.parents[message] = Some(parent);
+		}
+	}
+
+	/// References, completed by In-Reply-To when it is not already the last one
+	fn references_get(mail: &ImapMail) -> Vec<String>
+	{
+		let mut references = mail.references.as_deref().map(Self::ids_parse).unwrap_or_default();
+		if let Some(inReplyTo) = mail.inReplyTo.as_deref().and_then(|header| Self::ids_parse(header).into_iter().next())
+			&& references.last() != Some(&inReplyTo)
+		{
+			references.push(inReplyTo);
+		}
+		let skipped = references.len().saturating_sub(MailThreadLimits::REFERENCE_MAXIMUM);
+		references.drain(..skipped);
+		return references;
+	}
+
+	/// the <id> of a header, a bare value when the brackets are missing
+	fn ids_parse(header: &str) -> Vec<String>
+	{
+		let mut ids = Vec::new();
+		let mut remaining = header;
+		while let Some(start) = remaining.find('<')
+		{
+			let Some(length) = remaining[start + 1..].find('>') else {break};
+			let id = remaining[start + 1..start + 1 + length].trim();
+			if (!id.is_empty())
+			{
+				ids.push(id.to_string());
+			}
+			remaining = &remaining[start + 1 + length + 1..];
+		}
+		let bare = header.trim();
+		if (ids.is_empty() && !bare.is_empty() && !bare.contains(char::is_whitespace))
+		{
+			ids.push(bare.to_string());
+		}
+		return ids;
+	}
+
+	/// the subject without its answer and forward prefixes, "Re: Fwd: [2] Hello" and "RE[2]: hello" give "hello",
+	/// and whether a prefix was removed
+	fn subject_normalize(subject: &str) -> (String, bool)
+	{
+		let mut subject = subject.trim().to_lowercase();
+		let mut isReply = false;
+		loop
+		{
+			let prefixLength = ["re","fwd","fw","tr","aw","sv"].iter().find_map(|prefix| {
+				let rest = subject.strip_prefix(prefix)?;
+				let afterCounter = match rest.strip_prefix('[')
+				{
+					Some(counter) => counter.trim_start_matches(|character: char| character.is_ascii_digit()).strip_prefix(']')?,
+					None => rest,
+				};
+				let afterColon = afterCounter.trim_start().strip_prefix(':')?;
+				return Some(subject.len() - afterColon.len());
+			});
+			match prefixLength
+			{
+				Some(length) => subject = subject[length..].trim_start().to_string(),
+				None => break,
+			}
+			isReply = true;
+		}
+		return (subject,isReply);
+	}
+}
+
+/// union-find over the mail indexes
+struct MailGroups(Vec<usize>);
+
+impl MailGroups
+{
+	fn new(length: usize) -> Self
+	{
+		return Self((0..length).collect());
+	}
+
+	fn root_get(&mut self, index: usize) -> usize
+	{
+		let mut root = index;
+		while (self.0[root] != root)
+		{
+			root = self.0[root];
+		}
+		let mut current = index;
+		while (self.0[current] != root)
+		{
+			let next = self.0[current];
+			self.0[current] = root;
+			current = next;
+		}
+		return root;
+	}
+
+	fn union(&mut self, left: usize, right: usize)
+	{
+		let leftRoot = self.root_get(left);
+		let rightRoot = self.root_get(right);
+		// the smaller index stays the root, the first listed mail keeps the thread position
+		if (leftRoot < rightRoot)
+		{
+			self.0[rightRoot] = leftRoot;
+		}
+		else
+		{
+			self.0[leftRoot] = rightRoot;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+	use crate::api::proxys::imap_components::ImapMailKey;
+
+	fn mail_get(uid: u32, date: i64, subject: &str, messageId: Option<&str>, references: Option<&str>, inReplyTo: Option<&str>) -> (MailKey,ImapMail)
+	{
+		let key = MailKey {account: 0, imap: ImapMailKey {boxName: "INBOX".to_string(), uidValidity: 1, uid}};
+		let mail = ImapMail {
+			uid,
+			date,
+			subject: Some(subject.to_string()),
+			messageId: messageId.map(str::to_string),
+			references: references.map(str::to_string),
+			inReplyTo: inReplyTo.map(str::to_string),
+			..Default::default()
+		};
+		return (key,mail);
+	}
+
+	fn uids_get(threads: &[MailThread]) -> Vec<Vec<u32>>
+	{
+		return threads.iter().map(|thread| thread.mails.iter().map(|(key,_)| key.imap.uid).collect()).collect();
+	}
+
+	#[test]
+	fn threads_groupByReferencesAndKeepTheListOrder()
+	{
+		let threads = MailThread::list_build(vec![
+			mail_get(4,40,"Re: Lunch",Some("<d@x>"),Some("<a@x> <b@x>"),Some("<b@x>")),
+			mail_get(3,30,"Report",Some("<c@x>"),None,None),
+			mail_get(2,20,"Re: Lunch",Some("<b@x>"),None,Some("<a@x>")),
+			mail_get(1,10,"Lunch",Some("<a@x>"),None,None),
+		],&[]);
+
+		assert_eq!(uids_get(&threads),vec![vec![4,2,1],vec![3]]);
+		assert_eq!(threads[0].id_get().map(|key| key.imap.uid),Some(1));
+	}
+
+	#[test]
+	fn threads_joinAnswersOfAMissingMailAndSameSubjects()
+	{
+		let threads = MailThread::list_build(vec![
+			mail_get(3,30,"Re: Plans",Some("<c@x>"),Some("<missing@x>"),None),
+			mail_get(2,20,"Re: Plans",Some("<b@x>"),Some("<missing@x>"),None),
+			mail_get(5,15,"RE[2]: tr: plans",Some("<e@x>"),None,None),
+			mail_get(1,10,"Other",Some("<a@x>"),None,None),
+			mail_get(6,5,"",None,None,None),
+			mail_get(7,4,"",None,None,None),
+		],&[]);
+
+		assert_eq!(uids_get(&threads),vec![vec![3,2,5],vec![1],vec![6],vec![7]]);
+	}
+
+	#[test]
+	fn threads_mergeSameSubjectsOnlyForRepliesAndMissingMails()
+	{
+		let threads = MailThread::list_build(vec![
+			mail_get(4,40,"Re: Meeting",Some("<d@x>"),None,None),
+			mail_get(3,30,"Meeting",Some("<c@x>"),None,None),
+			mail_get(2,20,"Re: Meeting",Some("<b@x>"),Some("<missing@x>"),None),
+			mail_get(1,10,"Meeting",Some("<a@x>"),None,None),
+		],&[]);
+
+		assert_eq!(uids_get(&threads),vec![vec![4,3,2],vec![1]]);
+	}
+
+	#[test]
+	fn threads_survivesReferenceLoopsAndDuplicatedIds()
+	{
+		let threads = MailThread::list_build(vec![
+			mail_get(2,20,"First",Some("<a@x>"),Some("<b@x>"),None),
+			mail_get(1,10,"Second",Some("<b@x>"),Some("<a@x>"),None),
+			mail_get(3,5,"Copy",Some("<a@x>"),None,None),
+		],&[]);
+
+		assert_eq!(uids_get(&threads),vec![vec![2,1,3]]);
+	}
+
+	#[test]
+	fn threads_serverGroupsJoinMailsWithoutHeaders()
+	{
+		let (firstKey,first) = mail_get(2,20,"Hello",None,None,None);
+		let (secondKey,second) = mail_get(1,10,"Different",None,None,None);
+		let threads = MailThread::list_build(vec![(firstKey.clone(),first),(secondKey.clone(),second)],&[vec![secondKey,firstKey]]);
+
+		assert_eq!(uids_get(&threads),vec![vec![2,1]]);
+		assert_eq!(threads[0].unread_get(),2);
+	}
+}
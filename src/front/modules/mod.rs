@@ -1,11 +1,16 @@
 pub mod components;
 pub mod calendar;
+pub mod container;
+pub(crate) mod layout_history;
 pub mod link;
 mod mail;
 pub mod module_actions;
+pub(crate) mod module_events;
 pub mod module_positions;
 pub(crate) mod module_type;
 pub mod rss;
 pub mod todo;
 pub mod weather;
 pub mod module_holder;
+pub(crate) mod refresh_scheduler;
+pub(crate) mod unread_counters;
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 use leptoaster::ToasterContext;
+use leptos::prelude::Update;
 use crate::front::modules::module_holder::{ModuleHolder, ModuleHolderEpoch};
 use crate::api::modules::components::ModuleID;
 use crate::front::utils::all_front_enum::AllFrontUIEnum;
@@ -10,9 +11,14 @@ pub struct ModuleActionFn
 	_epoch: ModuleHolderEpoch,
 	/// (moduleName/key, login)
 	pub updateFn: Arc<dyn Fn(ModuleID) + Send + Sync>,
+	/// same as updateFn, without the success toast (for state changes that are not an explicit save)
+	pub saveFn: Arc<dyn Fn(ModuleID) + Send + Sync>,
 	pub getFn: Arc<dyn Fn(ModuleID) + Send + Sync>,
+	/// edit mode only, the removal is sent with the other changes when the edit session is saved
 	pub removeFn: Arc<dyn Fn(ModuleID) + Send + Sync>,
-	pub refreshFn: Arc<dyn Fn(ModuleID) + Send + Sync>
+	pub refreshFn: Arc<dyn Fn(ModuleID) + Send + Sync>,
+	/// retrieve every module newer on the server, used when pushed live updates were missed
+	pub retrieveAllFn: Arc<dyn Fn() + Send + Sync>,
 }
 
 impl ModuleActionFn
@@ -23,10 +29,12 @@ impl ModuleActionFn
 	{
 		Self {
 			_epoch: epoch,
-			updateFn: Arc::new(Self::module_update(toasterInnerValidate.clone(), epoch)),
+			updateFn: Arc::new(Self::module_update(toasterInnerValidate.clone(), epoch, Some(AllFrontUIEnum::UPDATE))),
+			saveFn: Arc::new(Self::module_update(toasterInnerValidate.clone(), epoch, None)),
 			getFn: Arc::new(Self::module_get( toasterInnerValidate.clone(), true, epoch)),
-			removeFn: Arc::new(Self::module_remove(toasterInnerValidate.clone(), epoch)),
+			removeFn: Arc::new(Self::module_remove(epoch)),
 			refreshFn: Arc::new(Self::module_refresh(toasterInnerValidate.clone(), epoch)),
+			retrieveAllFn: Arc::new(Self::modules_retrieve(toasterInnerValidate.clone(), epoch)),
 		}
 	}
 
@@ -40,21 +48,50 @@ impl ModuleActionFn
 		return ModuleHolder::network_isActive(self._epoch);
 	}
 
+	/// undo the last layout change of the edit session
+	pub(crate) fn layout_undo(&self) -> bool
+	{
+		return ModuleHolder::history_undo(self._epoch);
+	}
+
+	pub(crate) fn layout_redo(&self) -> bool
+	{
+		return ModuleHolder::history_redo(self._epoch);
+	}
+
+	/// pause or resume module refresh following the containers state, then refresh the modules that became visible
+	pub(super) fn visibility_apply(&self)
+	{
+		for moduleId in ModuleHolder::modules_visibility_apply(self._epoch)
+		{
+			(self.refreshFn)(moduleId);
+		}
+	}
+
+	/// report how many items of the module are unread, aggregated in the document title and the favicon
+	pub(super) fn unread_set(&self, moduleId: ModuleID, count: usize)
+	{
+		ModuleHolder::unread_set(self._epoch, moduleId, count);
+	}
+
 	#[cfg(test)]
 	pub(super) fn test_get(epoch: ModuleHolderEpoch) -> Self
 	{
 		return Self {
 			_epoch: epoch,
 			updateFn: Arc::new(|_| {}),
+			saveFn: Arc::new(|_| {}),
 			getFn: Arc::new(|_| {}),
 			removeFn: Arc::new(|_| {}),
 			refreshFn: Arc::new(|_| {}),
+			retrieveAllFn: Arc::new(|| {}),
 		};
 	}
 
 	fn module_update(
 		toasterInnerValidate: ToasterContext,
 		epoch: ModuleHolderEpoch,
+		toastingSuccess: Option<AllFrontUIEnum>,
 		//dialog: DialogManager
 	) -> impl Fn((ModuleID)) -> ()
 	{
@@ -63,7 +100,7 @@ impl ModuleActionFn
 
 			ModuleHolder::task_spawn(
 				epoch,
-				ModuleHolder::network_deferredCall(ModuleHolder::getSingleton().clone(), epoch, toasterInnerValidate.clone(), |holder|ModuleHolder::network_module_update_caller(holder,moduleId), Some(AllFrontUIEnum::UPDATE))
+				ModuleHolder::network_deferredCall(ModuleHolder::getSingleton().clone(), epoch, toasterInnerValidate.clone(), |holder|ModuleHolder::network_module_update_caller(holder,moduleId), toastingSuccess)
 			);
 		};
 	}
@@ -85,22 +122,28 @@ impl ModuleActionFn
 		};
 	}
 
-	fn module_remove(
-		toasterInnerValidate: ToasterContext,
+	fn modules_retrieve(
+		toaster: ToasterContext,
 		epoch: ModuleHolderEpoch,
-		//dialog: DialogManager
-	) -> impl Fn((ModuleID)) -> ()
+	) -> impl Fn() -> ()
 	{
-		return move |(moduleId)| {
-			let toasterInnerValidate = toasterInnerValidate.clone();
-
+		return move || {
 			ModuleHolder::task_spawn(
 				epoch,
-				ModuleHolder::network_deferredCall(ModuleHolder::getSingleton(), epoch, toasterInnerValidate.clone(), |holder|ModuleHolder::network_module_remove_caller(holder,moduleId), Some(AllFrontUIEnum::REMOVED))
+				ModuleHolder::network_deferredCall(ModuleHolder::getSingleton().clone(), epoch, toaster.clone(), |holder|ModuleHolder::network_modules_retrieve_caller(holder,true), None)
 			);
 		};
 	}
 
+	fn module_remove(
+		epoch: ModuleHolderEpoch,
+	) -> impl Fn((ModuleID)) -> ()
+	{
+		return move |(moduleId)| {
+			let _ = ModuleHolder::getSingleton().try_update(|holder| holder.blocks_remove(epoch, &moduleId));
+		};
+	}
+
 	fn module_refresh(
 		toaster: ToasterContext,
 		epoch: ModuleHolderEpoch,
@@ -0,0 +1,82 @@
+use crate::front::modules::module_holder::ModuleHolderEpoch;
+#[cfg(feature="hydrate")]
+use crate::api::modules::events::{ModuleEventRemoved, ModuleEventUpdated, MODULE_EVENTS_PATH, MODULE_EVENT_LAGGED, MODULE_EVENT_REMOVED, MODULE_EVENT_UPDATED};
+#[cfg(feature="hydrate")]
+use crate::front::modules::module_holder::ModuleHolder;
+#[cfg(feature="hydrate")]
+use wasm_bindgen::closure::Closure;
+#[cfg(feature="hydrate")]
+use wasm_bindgen::JsCast;
+#[cfg(feature="hydrate")]
+use web_sys::{Event, EventSource, MessageEvent};
+
+/// live module updates pushed by the server, dropping the listener closes the connection.
+/// The browser reconnects by itself, a refused reconnection (rotated credential, logout) stops it.
+pub(crate) struct ModuleEventsListener
+{
+	#[cfg(feature="hydrate")]
+	source: EventSource,
+	#[cfg(feature="hydrate")]
+	_onUpdated: Closure<dyn FnMut(MessageEvent)>,
+	#[cfg(feature="hydrate")]
+	_onRemoved: Closure<dyn FnMut(MessageEvent)>,
+	#[cfg(feature="hydrate")]
+	_onResync: Closure<dyn FnMut(Event)>,
+}
+
+impl ModuleEventsListener
+{
+	#[cfg(feature="hydrate")]
+	pub(crate) fn new(epoch: ModuleHolderEpoch) -> Option<Self>
+	{
+		let source = EventSource::new(MODULE_EVENTS_PATH).ok()?;
+
+		let onUpdated = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+			let Some(data) = event.data().as_string() else {return};
+			let Ok(updated) = serde_json::from_str::<ModuleEventUpdated>(&data) else {return};
+			ModuleHolder::live_updated_apply(epoch, updated);
+		});
+		let onRemoved = Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+			let Some(data) = event.data().as_string() else {return};
+			let Ok(removed) = serde_json::from_str::<ModuleEventRemoved>(&data) else {return};
+			ModuleHolder::live_removed_apply(epoch, removed);
+		});
+		// the events sent while disconnected are lost, the first connection follows the initial load
+		let mut wasOpened = false;
+		let onResync = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+			if (event.type_() == "open" && !wasOpened)
+			{
+				wasOpened = true;
+				return;
+			}
+			ModuleHolder::live_resync(epoch);
+		});
+
+		let _ = source.add_event_listener_with_callback(MODULE_EVENT_UPDATED, onUpdated.as_ref().unchecked_ref());
+		let _ = source.add_event_listener_with_callback(MODULE_EVENT_REMOVED, onRemoved.as_ref().unchecked_ref());
+		let _ = source.add_event_listener_with_callback(MODULE_EVENT_LAGGED, onResync.as_ref().unchecked_ref());
+		let _ = source.add_event_listener_with_callback("open", onResync.as_ref().unchecked_ref());
+
+		return Some(Self {
+			source,
+			_onUpdated: onUpdated,
+			_onRemoved: onRemoved,
+			_onResync: onResync,
+		});
+	}
+
+	#[cfg(not(feature="hydrate"))]
+	pub(crate) fn new(_epoch: ModuleHolderEpoch) -> Option<Self>
+	{
+		return None;
+	}
+}
+
+impl Drop for ModuleEventsListener
+{
+	fn drop(&mut self)
+	{
+		#[cfg(feature="hydrate")]
+		self.source.close();
+	}
+}
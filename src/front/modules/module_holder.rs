@@ -1,16 +1,24 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature="hydrate")]
 use std::sync::Arc;
 use leptoaster::ToasterContext;
-use leptos::prelude::{ArcRwSignal, Owner, Set, Update, With, WithUntracked};
+use leptos::prelude::{ArcRwSignal, Owner, Notify, Set, Update, UpdateUntracked, With, WithUntracked};
 use leptos::reactive::spawn_local_scoped_with_cancellation;
-use crate::api::modules::{API_module_remove, API_module_retrieve, API_modules_retrieve, API_modules_update, ModuleApiError, ModuleReturnRetrieve};
+use crate::api::modules::{API_module_retrieve, API_modules_retrieve, API_modules_update, ModuleApiError, ModuleReturnRetrieve, ModuleReturnUpdate};
 use crate::api::modules::components::{ApiModulesID, ModuleContent, ModuleID};
-use crate::front::modules::components::{API_return_apply, ApiCall, Backable, BoxFuture, Cacheable, ModuleName, PausableStocker, RefreshTime};
+use crate::api::modules::events::{ModuleEventRemoved, ModuleEventUpdated};
+use crate::front::modules::components::{API_return_apply, ApiCall, Backable, Cacheable, ModuleName, PausableStocker, RefreshFuture, RefreshOutcome};
+use crate::front::modules::layout_history::{LayoutHistory, LayoutSnapshot};
 use crate::front::modules::link::LinksHolder;
 use crate::front::modules::module_actions;
+use crate::front::modules::module_events::ModuleEventsListener;
 use crate::front::modules::module_positions::ModulePositions;
 use crate::front::modules::module_type::ModuleType;
+use crate::front::modules::refresh_scheduler::{page_isHidden, refresh_jitter, refresh_now, RefreshScheduler, REFRESH_HIDDEN_DELAY_MS};
+use crate::front::modules::unread_counters::UnreadCounters;
+#[cfg(feature="hydrate")]
+use crate::front::modules::refresh_scheduler::REFRESH_TICK_MS;
 use crate::front::utils::all_front_enum::{AllFrontErrorEnum, AllFrontUIEnum};
 use crate::front::utils::toaster_helpers;
 use crate::front::utils::toaster_helpers::toastingErr;
@@ -27,18 +35,21 @@ pub(crate) struct ModuleHolderEpoch(u64);
 struct ModuleRefreshTask
 {
 	owner: Owner,
-	futures: Vec<BoxFuture>,
+	epoch: ModuleHolderEpoch,
+	futures: Vec<(ModuleID, RefreshFuture)>,
 }
 
 impl ModuleRefreshTask
 {
 	fn spawn(self)
 	{
+		let epoch = self.epoch;
 		self.owner.with(|| {
 			spawn_local_scoped_with_cancellation(async move {
-				for oneFuture in self.futures
+				for (moduleId, oneFuture) in self.futures
 				{
-					oneFuture.await;
+					let outcome = oneFuture.await;
+					ModuleHolder::refresh_report(epoch, &moduleId, outcome);
 				}
 			});
 		});
@@ -53,17 +64,20 @@ mod tests
 
 	use crate::api::modules::ModuleApiError;
 	use crate::api::modules::components::{ModuleContent, ModuleID};
-	use crate::front::modules::components::{API_return_apply, PausableStocker};
+	use crate::api::modules::events::ModuleEventUpdated;
+	use crate::front::modules::components::{API_return_apply, Backable, ModuleName, PausableStocker, RefreshTime};
+	use crate::front::modules::container::Container;
 	use crate::front::modules::module_actions::ModuleActionFn;
 	use crate::front::modules::module_positions::ModulePositions;
 	use crate::front::modules::module_type::ModuleType;
 	use crate::front::modules::todo::Todo;
+	use crate::front::modules::refresh_scheduler::REFRESH_HIDDEN_DELAY_MS;
 	use crate::front::utils::all_front_enum::AllFrontErrorEnum;
 	use crate::front::utils::users_data::ClientCryptoContext;
 	use leptoaster::ToasterContext;
-	use leptos::prelude::{ArcRwSignal, Owner};
+	use leptos::prelude::{ArcRwSignal, Owner, Update, With, WithUntracked};
 
-	use super::ModuleHolder;
+	use super::{ModuleHolder, ModuleHolderEpoch};
 
 	#[test]
 	fn networkError_authRequiredMarksLocalSessionInvalid()
@@ -113,9 +127,13 @@ mod tests
 				moduleId.clone(),
 				ArcRwSignal::new(ModulePositions::new(ModuleType::TODO(Todo::new()))),
 			);
-			holder._crons.insert(moduleId, PausableStocker::test_paused());
+			holder._refresh.update(|scheduler| scheduler.module_set(moduleId, &RefreshTime::MINUTES(5), 0, 0.0));
+			holder._refreshTicker = Some(PausableStocker::test_paused());
 			holder._moduleActions = Some(ModuleActionFn::test_get(epoch));
 			holder._blockNb = 7;
+			holder.unread_set_inner(epoch, ModuleID {id: "account-a-module".to_string()}, 4);
+			holder.unread_set_inner(epoch, ModuleID {id: "unknown-module".to_string()}, 2);
+			assert_eq!(holder._unread.with(|unread| unread.total_get()), 4);
 
 			let ownerWasCleaned = Arc::new(AtomicBool::new(false));
 			let ownerWasCleanedInner = ownerWasCleaned.clone();
@@ -126,8 +144,10 @@ mod tests
 			let taskOwner = holder.lifecycle_close_inner().unwrap();
 			assert!(!holder.lifecycle_epoch_isActive(epoch));
 			assert!(holder._blocks.is_empty());
-			assert!(holder._crons.is_empty());
+			assert!(holder._refreshTicker.is_none());
+			assert!(holder._refresh.with(|scheduler| scheduler.status_get(&ModuleID {id: "account-a-module".to_string()}).is_none()));
 			assert!(holder._moduleActions.is_none());
+			assert_eq!(holder._unread.with(|unread| unread.total_get()), 0);
 			assert_eq!(holder._blockNb, 0);
 			assert_ne!(holder._links.id_get().id, "account-a-links");
 
@@ -193,6 +213,21 @@ mod tests
 		parentOwner.cleanup();
 	}
 
+	#[test]
+	fn hiddenPage_refreshesLaterOnlyForTheNotifications()
+	{
+		let mut holder = ModuleHolder::new();
+		assert_eq!(holder.refresh_dueTime_get(REFRESH_HIDDEN_DELAY_MS * 2), Some(REFRESH_HIDDEN_DELAY_MS * 2));
+
+		holder._pageHidden = true;
+		assert_eq!(holder.refresh_dueTime_get(REFRESH_HIDDEN_DELAY_MS * 2), None);
+		holder._backgroundRefresh = true;
+		assert_eq!(holder.refresh_dueTime_get(REFRESH_HIDDEN_DELAY_MS * 2), Some(REFRESH_HIDDEN_DELAY_MS));
+
+		holder._networkSuspended = true;
+		assert_eq!(holder.refresh_dueTime_get(REFRESH_HIDDEN_DELAY_MS * 2), None);
+	}
+
 	#[test]
 	fn blocksView_ordersModulesFromTopToBottomThenLeftToRight()
 	{
@@ -227,13 +262,269 @@ mod tests
 		});
 		parentOwner.cleanup();
 	}
+
+	fn containerHolder_get(layout: &str) -> ModuleHolder
+	{
+		let mut holder = ModuleHolder::new();
+		let containerId = ModuleID {id: "container".to_string()};
+		let containerContent = ModuleContent {
+			id: containerId.clone(),
+			typeModule: Container::MODULE_NAME.to_string(),
+			content: format!(r#"{{"layout":"{}"}}"#, layout),
+			..Default::default()
+		};
+		let container = Container::newFromModuleContent(&containerContent).unwrap();
+		holder._blocks.insert(
+			containerId.clone(),
+			ArcRwSignal::new(ModulePositions::newFromModuleContent(containerContent, ModuleType::CONTAINER(container))),
+		);
+		for (id,parent) in [
+			("child-a",Some(containerId.clone())),
+			("child-b",Some(containerId.clone())),
+			("orphan",Some(ModuleID {id: "missing".to_string()})),
+			("top",None),
+		]
+		{
+			let moduleId = ModuleID {id: id.to_string()};
+			let moduleContent = ModuleContent {
+				id: moduleId.clone(),
+				parent,
+				..Default::default()
+			};
+			holder._blocks.insert(
+				moduleId,
+				ArcRwSignal::new(ModulePositions::newFromModuleContent(moduleContent,ModuleType::TODO(Todo::new()))),
+			);
+		}
+		return holder;
+	}
+
+	#[test]
+	fn blocksView_drawsContainerChildrenInsideTheirContainer()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let holder = containerHolder_get("TABS");
+			let containerId = ModuleID {id: "container".to_string()};
+
+			let topIds = holder.blocks_view().into_iter().map(|(id,_)| id.id).collect::<Vec<_>>();
+			assert_eq!(topIds,["container","orphan","top"]);
+
+			let childrenIds = holder.blocks_children(&containerId).into_iter().map(|(id,_)| id.id).collect::<Vec<_>>();
+			assert_eq!(childrenIds,["child-a","child-b"]);
+
+			let attachableIds = holder.blocks_attachable(&containerId).into_iter().map(|(id,_)| id.id).collect::<Vec<_>>();
+			assert_eq!(attachableIds,["orphan","top"]);
+		});
+		parentOwner.cleanup();
+	}
+
+	#[test]
+	fn containerVisibility_skipsHiddenChildrenAndReportsRevealedOnes()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let mut holder = containerHolder_get("TABS");
+			let childA = ModuleID {id: "child-a".to_string()};
+			let childB = ModuleID {id: "child-b".to_string()};
+			holder._refresh.update(|scheduler| {
+				scheduler.module_set(childA.clone(), &RefreshTime::MINUTES(1), 0, 0.0);
+				scheduler.module_set(childB.clone(), &RefreshTime::MINUTES(1), 0, 0.0);
+			});
+
+			assert!(holder.modules_hidden_apply().is_empty());
+			let dueModule = holder._refresh.with(|scheduler| scheduler.due_get(60_000, |moduleId| holder._hiddenModules.contains(moduleId)));
+			assert_eq!(dueModule, Some(childA.clone()));
+			assert!(!holder._hiddenModules.contains(&childA));
+			assert!(holder._hiddenModules.contains(&childB));
+
+			let container = holder._blocks.get(&ModuleID {id: "container".to_string()}).unwrap().clone();
+			container.update(|container| container.import(ModuleContent {
+				content: r#"{"activeTab":"child-b"}"#.to_string(),
+				timestamp: 1,
+				..Default::default()
+			}));
+
+			assert_eq!(holder.modules_hidden_apply(), [childB.clone()]);
+			assert!(holder._hiddenModules.contains(&childA));
+			assert!(!holder._hiddenModules.contains(&childB));
+		});
+		parentOwner.cleanup();
+	}
+
+	fn historyHolder_get(ids: &[&str]) -> (ModuleHolder, ModuleHolderEpoch)
+	{
+		let mut holder = ModuleHolder::new();
+		let (epoch, _) = holder.lifecycle_open_inner();
+		for id in ids
+		{
+			holder._blocks.insert(
+				ModuleID {id: id.to_string()},
+				ArcRwSignal::new(ModulePositions::new(ModuleType::TODO(Todo::new()))),
+			);
+		}
+		let baseline = holder.layout_snapshot();
+		holder._history.update(|history| history.begin(baseline));
+		return (holder, epoch);
+	}
+
+	#[test]
+	fn layoutHistory_undoRedoRestoresMovesAndRemovals()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let (mut holder, epoch) = historyHolder_get(&["moved", "removed"]);
+			let moved = ModuleID {id: "moved".to_string()};
+			let removed = ModuleID {id: "removed".to_string()};
+			let movedPosition = |holder: &ModuleHolder| holder._blocks[&moved].with_untracked(|module| module.layout_get().pos);
+
+			holder.history_record_inner();
+			holder._blocks[&moved].with_untracked(|module| module.pos_set([40, 20]));
+			holder.blocks_remove(epoch, &removed);
+			assert_eq!(holder.network_modules_removed_get(), [removed.clone()]);
+
+			assert!(holder.history_undo_inner(epoch));
+			assert!(holder._blocks.contains_key(&removed));
+			assert!(holder.network_modules_removed_get().is_empty());
+			assert_eq!(movedPosition(&holder), [40, 20]);
+			assert!(holder.history_undo_inner(epoch));
+			assert_eq!(movedPosition(&holder), [0, 0]);
+			assert!(!holder.history_undo_inner(epoch));
+
+			assert!(holder.history_redo_inner(epoch));
+			assert_eq!(movedPosition(&holder), [40, 20]);
+			assert!(holder.history_redo_inner(epoch));
+			assert!(!holder._blocks.contains_key(&removed));
+			assert!(!holder._history.with(|history| history.canRedo()));
+
+			holder.lifecycle_close_inner().unwrap().cleanup();
+		});
+		parentOwner.cleanup();
+	}
+
+	#[test]
+	fn containerRemoval_releasesChildrenAtTheirAbsolutePosition()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let mut holder = ModuleHolder::new();
+			let (epoch, _) = holder.lifecycle_open_inner();
+			holder._blocks = containerHolder_get("TABS")._blocks;
+			let containerId = ModuleID {id: "container".to_string()};
+			let childA = ModuleID {id: "child-a".to_string()};
+			holder._blocks[&containerId].with_untracked(|container| container.pos_set([100, 50]));
+			holder._blocks[&childA].with_untracked(|child| child.pos_set([10, 20]));
+			let baseline = holder.layout_snapshot();
+			holder._history.update(|history| history.begin(baseline));
+
+			holder.blocks_remove(epoch, &containerId);
+			let childLayout = holder._blocks[&childA].with_untracked(|child| child.layout_get());
+			assert_eq!(childLayout.pos, [110, 70]);
+			assert_eq!(childLayout.parent, None);
+			let topIds = holder.blocks_view().into_iter().map(|(id,_)| id.id).collect::<Vec<_>>();
+			assert_eq!(topIds,["orphan","top","child-b","child-a"]);
+
+			assert!(holder.history_undo_inner(epoch));
+			let childLayout = holder._blocks[&childA].with_untracked(|child| child.layout_get());
+			assert_eq!(childLayout.pos, [10, 20]);
+			assert_eq!(childLayout.parent, Some(containerId.clone()));
+
+			holder.lifecycle_close_inner().unwrap().cleanup();
+		});
+		parentOwner.cleanup();
+	}
+
+	#[test]
+	fn layoutHistory_rollbackDropsAddedModulesAndIgnoresEmptyDrags()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let (mut holder, epoch) = historyHolder_get(&["kept"]);
+			let kept = ModuleID {id: "kept".to_string()};
+
+			let beforeDrag = holder.layout_snapshot();
+			holder._history.update(|history| history.gesture_begin(beforeDrag.clone()));
+			holder._history.update(|history| history.gesture_end(&beforeDrag));
+			assert!(!holder._history.with(|history| history.canUndo()));
+
+			holder.blocks_insert(epoch, ModulePositions::new(ModuleType::TODO(Todo::new())));
+			holder._blocks[&kept].with_untracked(|module| module.pos_set([10, 10]));
+			assert_eq!(holder._blocks.len(), 2);
+			assert!(holder._history.with(|history| history.canUndo()));
+
+			holder.history_rollback_inner(epoch);
+			assert_eq!(holder._blocks.keys().collect::<Vec<_>>(), [&kept]);
+			assert_eq!(holder._blocks[&kept].with_untracked(|module| module.layout_get().pos), [0, 0]);
+			assert!(!holder._history.with(|history| history.isRecording()));
+
+			holder.lifecycle_close_inner().unwrap().cleanup();
+		});
+		parentOwner.cleanup();
+	}
+
+	#[test]
+	fn liveUpdate_retrievesOnlyNewerModulesOutsideOfEditMode()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let (mut holder, epoch) = historyHolder_get(&["todo"]);
+			holder._history.update(|history| {history.end();});
+			holder._moduleActions = Some(ModuleActionFn::test_get(epoch));
+			holder._links.id_set(ModuleID {id: "links".to_string()});
+			let updated = |id: &str, timestamp: i64| ModuleEventUpdated {id: ModuleID {id: id.to_string()}, timestamp};
+
+			assert!(holder.live_updated_get(epoch, &updated("todo", 0)).is_none());
+			assert!(holder.live_updated_get(epoch, &updated("unknown", i64::MAX)).is_none());
+			assert_eq!(holder.live_updated_get(epoch, &updated("todo", i64::MAX)).map(|(_, isLinks)| isLinks), Some(false));
+			assert_eq!(holder.live_updated_get(epoch, &updated("links", i64::MAX)).map(|(_, isLinks)| isLinks), Some(true));
+
+			let baseline = holder.layout_snapshot();
+			holder._history.update(|history| history.begin(baseline));
+			assert!(holder.live_updated_get(epoch, &updated("todo", i64::MAX)).is_none());
+
+			holder.lifecycle_close_inner().unwrap().cleanup();
+			assert!(holder.live_updated_get(epoch, &updated("todo", i64::MAX)).is_none());
+		});
+		parentOwner.cleanup();
+	}
+
+	#[test]
+	fn liveRemoval_dropsTheModuleOutsideOfEditMode()
+	{
+		let parentOwner = Owner::new();
+		parentOwner.with(|| {
+			let (mut holder, epoch) = historyHolder_get(&["kept", "removed"]);
+			let removed = ModuleID {id: "removed".to_string()};
+
+			assert!(!holder.live_removed_apply_inner(epoch, &removed));
+			assert!(holder._blocks.contains_key(&removed));
+
+			holder._history.update(|history| {history.end();});
+			assert!(holder.live_removed_apply_inner(epoch, &removed));
+			assert!(!holder._blocks.contains_key(&removed));
+			assert!(!holder.live_removed_apply_inner(epoch, &removed));
+
+			holder.lifecycle_close_inner().unwrap().cleanup();
+		});
+		parentOwner.cleanup();
+	}
 }
 
 pub struct ModuleHolder
 {
 	_links: LinksHolder,
 	_blocks: HashMap<ModuleID, ArcRwSignal<ModulePositions<ModuleType>>>,
-	_crons: HashMap<ModuleID, PausableStocker>,
+	// when each module refreshes next, driven by a single ticker
+	_refresh: ArcRwSignal<RefreshScheduler>,
+	_refreshTicker: Option<PausableStocker>,
+	// changes made by the other sessions of the account, pushed by the server
+	_events: Option<ModuleEventsListener>,
+	// modules inside a collapsed container (or a hidden tab), the scheduler skips them
+	_hiddenModules: HashSet<ModuleID>,
+	// undo/redo of the layout changes made in edit mode
+	_history: ArcRwSignal<LayoutHistory>,
+	// unread items reported by the modules, drawn in the document title and the favicon
+	_unread: ArcRwSignal<UnreadCounters>,
 	_moduleActions: Option<module_actions::ModuleActionFn>,
 	_blockNb: usize,
 	_epochCounter: u64,
@@ -241,6 +532,10 @@ pub struct ModuleHolder
 	_taskOwner: Option<Owner>,
 	_networkGeneration: u64,
 	_networkSuspended: bool,
+	// Page Visibility API, nothing refreshes while the page is in the background
+	_pageHidden: bool,
+	// unless the unread notifications are enabled, they need a slower refresh to notice the new items
+	_backgroundRefresh: bool,
 }
 
 impl ModuleHolder
@@ -257,7 +552,12 @@ impl ModuleHolder
 		Self {
 			_links: LinksHolder::new(),
 			_blocks: HashMap::new(),
-			_crons: Default::default(),
+			_refresh: ArcRwSignal::new(RefreshScheduler::default()),
+			_refreshTicker: None,
+			_events: None,
+			_hiddenModules: Default::default(),
+			_history: ArcRwSignal::new(LayoutHistory::default()),
+			_unread: ArcRwSignal::new(UnreadCounters::default()),
 			_moduleActions: None,
 			_blockNb: 0,
 			_epochCounter: 0,
@@ -265,6 +565,8 @@ impl ModuleHolder
 			_taskOwner: None,
 			_networkGeneration: 0,
 			_networkSuspended: false,
+			_pageHidden: false,
+			_backgroundRefresh: false,
 		}
 	}
 
@@ -349,10 +651,7 @@ impl ModuleHolder
 		self._networkGeneration = self._networkGeneration.checked_add(1)
 			.expect("ModuleHolder network generation exhausted");
 		self._networkSuspended = true;
-		for cron in self._crons.values_mut()
-		{
-			cron.pause();
-		}
+		self.refresh_ticker_apply();
 	}
 
 	fn network_resume_inner(&mut self)
@@ -362,10 +661,7 @@ impl ModuleHolder
 			return;
 		}
 		self._networkSuspended = false;
-		for cron in self._crons.values_mut()
-		{
-			cron.resume();
-		}
+		self.refresh_ticker_apply();
 	}
 
 	fn lifecycle_open_inner(&mut self) -> (ModuleHolderEpoch, Option<Owner>)
@@ -386,7 +682,13 @@ impl ModuleHolder
 			.expect("ModuleHolder network generation exhausted");
 		self._networkSuspended = false;
 		let owner = self._taskOwner.take();
-		self._crons.clear();
+		self._refreshTicker = None;
+		self._backgroundRefresh = false;
+		self._events = None;
+		self._refresh.update(|scheduler| scheduler.clear());
+		self._hiddenModules.clear();
+		self._history.set(LayoutHistory::default());
+		self._unread.update(|unread| unread.clear());
 		self._moduleActions = None;
 		self._blocks.clear();
 		self._links = LinksHolder::new();
@@ -438,6 +740,14 @@ impl ModuleHolder
 		if (self.lifecycle_epoch_isActive(epoch))
 		{
 			self._moduleActions = Some(ma);
+			// timers only exist in the browser
+			#[cfg(feature="hydrate")]
+			{
+				self._refreshTicker = Some(PausableStocker::new(REFRESH_TICK_MS, Arc::new(move || Self::refresh_tick(epoch))));
+			}
+			self._pageHidden = page_isHidden();
+			self.refresh_ticker_apply();
+			self._events = ModuleEventsListener::new(epoch);
 		}
 	}
 
@@ -449,6 +759,13 @@ impl ModuleHolder
 		}
 		toApply.retrieve.into_iter().for_each(|f| f(self));
 		toApply.update.into_iter().for_each(|f| f(self));
+		for moduleId in self.modules_hidden_apply()
+		{
+			if (!toApply.moduleIdToRefresh.contains(&moduleId))
+			{
+				toApply.moduleIdToRefresh.push(moduleId);
+			}
+		}
 
 		return self.module_refresh_prepare(epoch, toApply.moduleIdToRefresh.drain(..).collect(), toaster);
 	}
@@ -580,7 +897,7 @@ impl ModuleHolder
 	{
 		return Self::network_deferredCall_inner(
 			moduleHolder,
-			|holder, crypto| holder.network_modules_update_prepare(crypto).map(|modules| (modules, true)),
+			|holder, crypto| holder.network_modules_update_prepare(crypto).map(|modules| (modules, holder.network_modules_removed_get(), true)),
 			Self::network_modules_update_async,
 		);
 	}
@@ -608,15 +925,26 @@ impl ModuleHolder
 		return Ok(moduleToUpdateData);
 	}
 
-	async fn network_modules_update_async((moduleToUpdate, overwrite): (Vec<ModuleContent>, bool)) -> API_return_apply
+	/// modules removed during the edit session, they are deleted by the same call that saves the others
+	fn network_modules_removed_get(&self) -> Vec<ModuleID>
+	{
+		return self._history.with_untracked(|history| history.removed_get(|moduleId| self._blocks.contains_key(moduleId)));
+	}
+
+	async fn network_modules_update_async((moduleToUpdate, moduleToRemove, overwrite): (Vec<ModuleContent>, Vec<ModuleID>, bool)) -> API_return_apply
 	{
-		if(moduleToUpdate.len()==0) {return API_return_apply::default();}
+		if(moduleToUpdate.len()==0 && moduleToRemove.len()==0) {return API_return_apply::default();}
 
 		let mut apiReturn = API_return_apply::default();
 
-		match API_modules_update(moduleToUpdate, overwrite).await
+		match API_modules_update(moduleToUpdate, moduleToRemove, overwrite).await
 		{
-			Ok(_) => {},
+			Ok(returned) => {
+				if (returned.values().any(|returned| matches!(returned, ModuleReturnUpdate::REMOVE_FAILED)))
+				{
+					apiReturn.error.push(AllFrontErrorEnum::SERVER_ERROR);
+				}
+			},
 			Err(err) => {
 				Self::network_error_apply(&mut apiReturn, err);
 				return apiReturn;
@@ -638,7 +966,7 @@ impl ModuleHolder
 	{
 		return Self::network_deferredCall_inner(
 			moduleHolder,
-			move |holder, crypto| holder.network_module_update_prepare(module.clone(), crypto).map(|modules| (modules, false)),
+			move |holder, crypto| holder.network_module_update_prepare(module.clone(), crypto).map(|modules| (modules, vec![], false)),
 			Self::network_modules_update_async,
 		);
 	}
@@ -810,16 +1138,7 @@ impl ModuleHolder
 				{moduleHolder._blocks.insert(moduleId.clone(), ArcRwSignal::new(thisModule));}
 			}
 
-			let refreshTime = moduleHolder._blocks.get_mut(&moduleId).unwrap().with_untracked(|module| module.inner().refresh_time());
-			if let Some(actions) = &moduleHolder._moduleActions
-			{
-				Self::add_cron(
-					refreshTime,
-					moduleId.clone(),
-					&mut moduleHolder._crons,
-					actions.clone(),
-				);
-			}
+			moduleHolder.refresh_register(&moduleId);
 		};
 		apiReturn.retrieve.push(Box::new(addReturnWork));
 		return true;
@@ -829,43 +1148,6 @@ impl ModuleHolder
 	// END MONO MODULE RETRIEVE ZONE ---
 	////////////////////////////////////////
 
-	////////////////////////////////////////
-	// START MODULE REMOVE ZONE ---
-	////////////////////////////////////////
-
-
-	pub fn network_module_remove_caller(moduleHolder: ArcRwSignal<ModuleHolder>, moduleToRemove: ModuleID) -> Option<ApiCall>
-	{
-		return Self::network_deferredCall_inner(moduleHolder, move |_, _| Ok(moduleToRemove.clone()), Self::network_module_remove_async);
-	}
-
-	async fn network_module_remove_async(moduleToRetrieve: ModuleID) -> API_return_apply
-	{
-		let mut apiReturn = API_return_apply::default();
-
-		match API_module_remove(moduleToRetrieve.clone()).await
-		{
-			Ok(_) => {},
-			Err(err) => {
-				Self::network_error_apply(&mut apiReturn, err);
-				return apiReturn;
-			}
-		};
-
-		let addReturnWork = move |moduleHolder: &mut ModuleHolder| {
-			 moduleHolder._crons.remove(&moduleToRetrieve);
-			moduleHolder._blocks.remove(&moduleToRetrieve);
-		};
-		apiReturn.retrieve.push(Box::new(addReturnWork));
-
-		return apiReturn;
-	}
-
-
-	////////////////////////////////////////
-	// START MODULE REMOVE ZONE ---
-	////////////////////////////////////////
-
 	pub(super) fn module_refresh(epoch: ModuleHolderEpoch, modulesId: Vec<ModuleID>, toaster: ToasterContext)
 	{
 		let refreshTask = Self::getSingleton().with_untracked(|holder| {
@@ -892,6 +1174,10 @@ impl ModuleHolder
 			{
 				continue;
 			};
+			if (self._hiddenModules.contains(&moduleId))
+			{
+				continue;
+			}
 
 			if let Some(actions) = &self._moduleActions
 			{
@@ -899,7 +1185,12 @@ impl ModuleHolder
 					.refresh(actions.clone(), moduleId.clone(), toaster.clone()));
 				if let Some(refreshFutur) = tmp
 				{
-					allBoxedFuture.push(refreshFutur);
+					self._refresh.update(|scheduler| scheduler.run_start(&moduleId));
+					allBoxedFuture.push((moduleId, refreshFutur));
+				}
+				else
+				{
+					self._refresh.update(|scheduler| scheduler.run_skip(&moduleId, refresh_now()));
 				}
 			}
 		}
@@ -909,46 +1200,192 @@ impl ModuleHolder
 		}
 		return Some(ModuleRefreshTask {
 			owner,
+			epoch,
 			futures: allBoxedFuture,
 		});
 	}
 
-	fn add_cron(
-		refreshTimeRaw: RefreshTime,
-		moduleId: ModuleID,
-		crons: &mut HashMap<ModuleID, PausableStocker>,
-		moduleActions: module_actions::ModuleActionFn,
-	)
+	////////////////////////////////////////
+	// START REFRESH SCHEDULER ZONE ---
+	////////////////////////////////////////
+
+	pub(crate) fn refreshScheduler_get() -> ArcRwSignal<RefreshScheduler>
 	{
-		let timeMinute = match refreshTimeRaw
+		return Self::getSingleton().with_untracked(|holder| holder._refresh.clone());
+	}
+
+	/// follow the Page Visibility API, a page back in the foreground catches up one module per tick
+	pub(crate) fn page_visibility_apply(epoch: ModuleHolderEpoch)
+	{
+		let _ = Self::getSingleton().try_update(|holder| {
+			if (holder.lifecycle_epoch_isActive(epoch))
+			{
+				holder._pageHidden = page_isHidden();
+				holder.refresh_ticker_apply();
+			}
+		});
+	}
+
+	/// the unread notifications keep a slower refresh running while the page is in the background
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn backgroundRefresh_set(epoch: ModuleHolderEpoch, isEnabled: bool)
+	{
+		let _ = Self::getSingleton().try_update(|holder| {
+			if (holder.lifecycle_epoch_isActive(epoch) && holder._backgroundRefresh != isEnabled)
+			{
+				holder._backgroundRefresh = isEnabled;
+				holder.refresh_ticker_apply();
+			}
+		});
+	}
+
+	/// time the refreshes are due before, none when nothing refreshes
+	fn refresh_dueTime_get(&self, now: i64) -> Option<i64>
+	{
+		if (self._networkSuspended || (self._pageHidden && !self._backgroundRefresh))
 		{
-			RefreshTime::NONE => {
-				crons.remove(&moduleId);
-				return;
-			},
-			RefreshTime::MINUTES(i) => i as u32,
-			RefreshTime::HOURS(h) => h as u32 * 60,
-		};
+			return None;
+		}
+		return Some(if (self._pageHidden) {now - REFRESH_HIDDEN_DELAY_MS} else {now});
+	}
+
+	/// start the most overdue refresh, the others wait for the next ticks so they do not run all at once
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	fn refresh_tick(epoch: ModuleHolderEpoch)
+	{
+		let dueModule = Self::getSingleton().with_untracked(|holder| {
+			if (!holder.lifecycle_epoch_isActive(epoch))
+			{
+				return None;
+			}
+			let dueTime = holder.refresh_dueTime_get(refresh_now())?;
+			let dueModule = holder._refresh.with_untracked(|scheduler| scheduler.due_get(dueTime, |moduleId| holder._hiddenModules.contains(moduleId)))?;
+			return holder._moduleActions.clone().map(|actions| (dueModule, actions));
+		});
+		if let Some((moduleId, actions)) = dueModule
+		{
+			(actions.refreshFn)(moduleId);
+		}
+	}
+
+	fn refresh_report(epoch: ModuleHolderEpoch, moduleId: &ModuleID, outcome: RefreshOutcome)
+	{
+		Self::getSingleton().with_untracked(|holder| {
+			if (holder.lifecycle_epoch_isActive(epoch))
+			{
+				holder._refresh.update(|scheduler| scheduler.run_finish(moduleId, outcome, refresh_now(), refresh_jitter()));
+			}
+		});
+	}
 
-		let timeMillisecond = timeMinute * 60 * 1000;
+	fn refresh_register(&self, moduleId: &ModuleID)
+	{
+		let Some(refreshTime) = self._blocks.get(moduleId).map(|module| module.with_untracked(|module| module.inner().refresh_time())) else {return};
+		self._refresh.update(|scheduler| scheduler.module_set(moduleId.clone(), &refreshTime, refresh_now(), refresh_jitter()));
+	}
 
+	fn refresh_ticker_apply(&mut self)
+	{
+		let mustRun = self.refresh_dueTime_get(0).is_some();
+		if let Some(ticker) = &mut self._refreshTicker
+		{
+			if (mustRun)
+			{
+				ticker.resume();
+			}
+			else
+			{
+				ticker.pause();
+			}
+		}
+	}
 
+	////////////////////////////////////////
+	// END REFRESH SCHEDULER ZONE ---
+	////////////////////////////////////////
 
-		if let Some(cron) = crons.get_mut(&moduleId) {
-			cron.set_interval(timeMillisecond);
-			return;
+	////////////////////////////////////////
+	// START LIVE UPDATE ZONE ---
+	////////////////////////////////////////
+
+	/// retrieve a module saved by another session, unless the local one is already as recent.
+	/// Edit mode is left alone, the edit session is reconciled with the server when it ends.
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn live_updated_apply(epoch: ModuleHolderEpoch, updated: ModuleEventUpdated)
+	{
+		let Some((actions, isLinks)) = Self::getSingleton().with_untracked(|holder| holder.live_updated_get(epoch, &updated)) else {return};
+		if (isLinks)
+		{
+			(actions.retrieveAllFn)();
 		}
+		else
+		{
+			(actions.getFn)(updated.id);
+		}
+	}
 
-		let refresh_fn = moduleActions.refreshFn.clone();
-		let tick_module_id = moduleId.clone();
-		let tick = Arc::new(move || {
-			(refresh_fn)(tick_module_id.clone());
+	/// some pushed updates were missed (reconnection or slow client), check every module
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn live_resync(epoch: ModuleHolderEpoch)
+	{
+		let actions = Self::getSingleton().with_untracked(|holder| {
+			if (holder.network_generation_get(epoch).is_none() || holder._history.with_untracked(|history| history.isRecording()))
+			{
+				return None;
+			}
+			return holder._moduleActions.clone();
 		});
+		if let Some(actions) = actions
+		{
+			(actions.retrieveAllFn)();
+		}
+	}
 
-		crons.insert(moduleId, PausableStocker::new(timeMillisecond, tick));
+	/// a module removed by another session, kept while this one is editing the layout (its save decides)
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn live_removed_apply(epoch: ModuleHolderEpoch, removed: ModuleEventRemoved)
+	{
+		let _ = Self::getSingleton().try_update(|holder| holder.live_removed_apply_inner(epoch, &removed.id));
+	}
 
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	fn live_removed_apply_inner(&mut self, epoch: ModuleHolderEpoch, moduleId: &ModuleID) -> bool
+	{
+		if (self.network_generation_get(epoch).is_none() || self._history.with_untracked(|history| history.isRecording()) || !self._blocks.contains_key(moduleId))
+		{
+			return false;
+		}
+		self.block_children_release(moduleId);
+		self.block_forget(moduleId);
+		self.modules_hidden_apply();
+		return true;
 	}
 
+	/// the actions and whether the update is for the links, only if the pushed version is newer
+	fn live_updated_get(&self, epoch: ModuleHolderEpoch, updated: &ModuleEventUpdated) -> Option<(module_actions::ModuleActionFn, bool)>
+	{
+		if (self.network_generation_get(epoch).is_none() || self._history.with_untracked(|history| history.isRecording()))
+		{
+			return None;
+		}
+		let isLinks = self._links.id_get() == updated.id;
+		let localTime = match self._blocks.get(&updated.id)
+		{
+			Some(module) => module.with_untracked(|module| module.inner().cache_time()),
+			None if isLinks => self._links.cache_time(),
+			None => return None,
+		};
+		if (updated.timestamp <= localTime)
+		{
+			return None;
+		}
+		return self._moduleActions.clone().map(|actions| (actions, isLinks));
+	}
+
+	////////////////////////////////////////
+	// END LIVE UPDATE ZONE ---
+	////////////////////////////////////////
+
 	pub fn links_get(&self) -> &LinksHolder
 	{
 		return &self._links;
@@ -964,17 +1401,96 @@ impl ModuleHolder
 		return &self._blocks;
 	}
 
+	/// top level modules, modules inside a container are drawn by this container
 	pub fn blocks_view(&self) -> Vec<(ModuleID, ArcRwSignal<ModulePositions<ModuleType>>)> {
 		let mut blocks = self._blocks
 			.iter()
+			.filter(|(_, module)| self.block_container_get(module).is_none())
 			.map(|(id, module)| (id.clone(), module.clone()))
 			.collect::<Vec<_>>();
+		Self::blocks_sort(&mut blocks);
+		return blocks;
+	}
+
+	/// modules held by a container, in the same visual order as blocks_view
+	pub fn blocks_children(&self, containerId: &ModuleID) -> Vec<(ModuleID, ArcRwSignal<ModulePositions<ModuleType>>)>
+	{
+		let mut blocks = self._blocks
+			.iter()
+			.filter(|(_, module)| self.block_container_get(module).as_ref() == Some(containerId))
+			.map(|(id, module)| (id.clone(), module.clone()))
+			.collect::<Vec<_>>();
+		Self::blocks_sort(&mut blocks);
+		return blocks;
+	}
+
+	/// top level modules that can be moved into a container (containers cannot be nested)
+	pub fn blocks_attachable(&self, containerId: &ModuleID) -> Vec<(ModuleID, ArcRwSignal<ModulePositions<ModuleType>>)>
+	{
+		return self.blocks_view()
+			.into_iter()
+			.filter(|(id, module)| id != containerId && !module.with(|module| matches!(module.inner(), ModuleType::CONTAINER(_))))
+			.collect();
+	}
+
+	fn blocks_sort(blocks: &mut Vec<(ModuleID, ArcRwSignal<ModulePositions<ModuleType>>)>)
+	{
 		blocks.sort_by(|(leftId,leftModule),(rightId,rightModule)| {
 			let leftOrder = leftModule.with(|module| module.visual_order_get());
 			let rightOrder = rightModule.with(|module| module.visual_order_get());
 			return leftOrder.cmp(&rightOrder).then_with(|| leftId.cmp(rightId));
 		});
-		return blocks;
+	}
+
+	/// return the container holding this module, a parent that is missing or is not a container is ignored
+	fn block_container_get(&self, module: &ArcRwSignal<ModulePositions<ModuleType>>) -> Option<ModuleID>
+	{
+		let parentId = module.with(|module| module.parent_get())?;
+		let parent = self._blocks.get(&parentId)?;
+		return parent.with(|parent| matches!(parent.inner(), ModuleType::CONTAINER(_))).then_some(parentId);
+	}
+
+	fn module_isHidden(&self, moduleId: &ModuleID) -> bool
+	{
+		let Some(module) = self._blocks.get(moduleId) else {return false};
+		let Some(containerId) = self.block_container_get(module) else {return false};
+		let children = self.blocks_children(&containerId).into_iter()
+			.map(|(id, _)| id)
+			.collect::<Vec<_>>();
+		return self._blocks.get(&containerId).is_some_and(|container| container.with_untracked(|container| match container.inner()
+		{
+			ModuleType::CONTAINER(container) => !container.child_isVisible(moduleId, &children),
+			_ => false,
+		}));
+	}
+
+	pub(crate) fn modules_visibility_apply(epoch: ModuleHolderEpoch) -> Vec<ModuleID>
+	{
+		return Self::getSingleton()
+			.try_update(|holder| {
+				if (!holder.lifecycle_epoch_isActive(epoch))
+				{
+					return vec![];
+				}
+				return holder.modules_hidden_apply();
+			})
+			.unwrap_or_default();
+	}
+
+	/// update the hidden modules skipped by the refresh scheduler, return the modules that became visible
+	fn modules_hidden_apply(&mut self) -> Vec<ModuleID>
+	{
+		let hidden = self._blocks.keys()
+			.filter(|moduleId| self.module_isHidden(moduleId))
+			.cloned()
+			.collect::<HashSet<_>>();
+		let revealed = self._hiddenModules.iter()
+			.filter(|moduleId| !hidden.contains(*moduleId) && self._blocks.contains_key(*moduleId))
+			.cloned()
+			.collect::<Vec<_>>();
+
+		self._hiddenModules = hidden;
+		return revealed;
 	}
 
 	pub(crate) fn blocks_insert(&mut self, epoch: ModuleHolderEpoch, newmodule: ModulePositions<ModuleType>)
@@ -983,11 +1499,224 @@ impl ModuleHolder
 		{
 			return;
 		}
+		self.history_record_inner();
 		newmodule.depth_set(self._blockNb as u32);
 		self._blocks.insert(ModuleID::new(), ArcRwSignal::new(newmodule));
 		self._blockNb += 1;
 	}
 
+	/// remove a module from the dashboard, the server forgets it when the edit session is saved
+	pub(crate) fn blocks_remove(&mut self, epoch: ModuleHolderEpoch, moduleId: &ModuleID)
+	{
+		if (!self.lifecycle_epoch_isActive(epoch) || !self._blocks.contains_key(moduleId))
+		{
+			return;
+		}
+		self.history_record_inner();
+		self.block_children_release(moduleId);
+		self.block_forget(moduleId);
+		self.modules_hidden_apply();
+	}
+
+	/// move the children of a removed container back to the dashboard, their position was relative to the container
+	fn block_children_release(&self, containerId: &ModuleID)
+	{
+		let Some(containerPos) = self._blocks.get(containerId).map(|container| container.with_untracked(|container| container.pos_get())) else {return};
+		for (_, child) in self.blocks_children(containerId)
+		{
+			child.with_untracked(|child| {
+				let childPos = child.pos_get();
+				child.pos_set([(containerPos[0] + childPos[0]).max(0), (containerPos[1] + childPos[1]).max(0)]);
+				child.parent_set(None);
+			});
+		}
+	}
+
+	fn block_forget(&mut self, moduleId: &ModuleID)
+	{
+		self._refresh.update(|scheduler| scheduler.module_remove(moduleId));
+		self._hiddenModules.remove(moduleId);
+		self._unread.update(|unread| unread.module_remove(moduleId));
+		self._blocks.remove(moduleId);
+	}
+
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn unread_get() -> ArcRwSignal<UnreadCounters>
+	{
+		return Self::getSingleton().with_untracked(|holder| holder._unread.clone());
+	}
+
+	/// unread count reported by a module, ignored once the dashboard is closed
+	pub(crate) fn unread_set(epoch: ModuleHolderEpoch, moduleId: ModuleID, count: usize)
+	{
+		Self::getSingleton().with_untracked(|holder| holder.unread_set_inner(epoch, moduleId, count));
+	}
+
+	fn unread_set_inner(&self, epoch: ModuleHolderEpoch, moduleId: ModuleID, count: usize)
+	{
+		if (!self.lifecycle_epoch_isActive(epoch) || !self._blocks.contains_key(&moduleId))
+		{
+			return;
+		}
+		// subscribers (title, favicon, notification) only wake up on an actual change
+		if (self._unread.try_update_untracked(|unread| unread.count_set(moduleId, count)).unwrap_or(false))
+		{
+			self._unread.notify();
+		}
+	}
+
+	////////////////////////////////////////
+	// START LAYOUT HISTORY ZONE ---
+	////////////////////////////////////////
+
+	pub(crate) fn history_get() -> ArcRwSignal<LayoutHistory>
+	{
+		return Self::getSingleton().with_untracked(|holder| holder._history.clone());
+	}
+
+	/// start recording the layout changes of an edit session
+	pub(crate) fn history_begin(epoch: ModuleHolderEpoch)
+	{
+		Self::getSingleton().with_untracked(|holder| {
+			if (holder.lifecycle_epoch_isActive(epoch))
+			{
+				let baseline = holder.layout_snapshot();
+				holder._history.update(|history| history.begin(baseline));
+			}
+		});
+	}
+
+	pub(crate) fn history_end()
+	{
+		Self::getSingleton().with_untracked(|holder| {
+			if (holder._history.with_untracked(|history| history.isRecording()))
+			{
+				holder._history.update(|history| {history.end();});
+			}
+		});
+	}
+
+	/// save the current layout as an undo step, call it before changing the layout
+	pub(crate) fn history_record()
+	{
+		Self::getSingleton().with_untracked(|holder| holder.history_record_inner());
+	}
+
+	pub(crate) fn history_gestureBegin()
+	{
+		Self::getSingleton().with_untracked(|holder| {
+			let before = holder.layout_snapshot();
+			holder._history.update_untracked(|history| history.gesture_begin(before));
+		});
+	}
+
+	pub(crate) fn history_gestureEnd()
+	{
+		Self::getSingleton().with_untracked(|holder| {
+			let current = holder.layout_snapshot();
+			holder._history.update(|history| history.gesture_end(&current));
+		});
+	}
+
+	pub(crate) fn history_undo(epoch: ModuleHolderEpoch) -> bool
+	{
+		return Self::getSingleton()
+			.try_update(|holder| holder.history_undo_inner(epoch))
+			.unwrap_or(false);
+	}
+
+	pub(crate) fn history_redo(epoch: ModuleHolderEpoch) -> bool
+	{
+		return Self::getSingleton()
+			.try_update(|holder| holder.history_redo_inner(epoch))
+			.unwrap_or(false);
+	}
+
+	/// put the dashboard back as it was when the edit session started
+	pub(crate) fn history_rollback(epoch: ModuleHolderEpoch)
+	{
+		let _ = Self::getSingleton().try_update(|holder| holder.history_rollback_inner(epoch));
+	}
+
+	fn history_record_inner(&self)
+	{
+		if (!self._history.with_untracked(|history| history.isRecording()))
+		{
+			return;
+		}
+		let before = self.layout_snapshot();
+		self._history.update(|history| history.record(before));
+	}
+
+	fn history_undo_inner(&mut self, epoch: ModuleHolderEpoch) -> bool
+	{
+		if (!self.lifecycle_epoch_isActive(epoch))
+		{
+			return false;
+		}
+		let current = self.layout_snapshot();
+		let Some(previous) = self._history.try_update(|history| history.undo(current)).flatten() else {return false};
+		self.layout_apply(&previous);
+		return true;
+	}
+
+	fn history_redo_inner(&mut self, epoch: ModuleHolderEpoch) -> bool
+	{
+		if (!self.lifecycle_epoch_isActive(epoch))
+		{
+			return false;
+		}
+		let current = self.layout_snapshot();
+		let Some(next) = self._history.try_update(|history| history.redo(current)).flatten() else {return false};
+		self.layout_apply(&next);
+		return true;
+	}
+
+	fn history_rollback_inner(&mut self, epoch: ModuleHolderEpoch)
+	{
+		if (!self.lifecycle_epoch_isActive(epoch))
+		{
+			return;
+		}
+		let Some(baseline) = self._history.try_update(|history| history.end()).flatten() else {return};
+		self.layout_apply(&baseline);
+	}
+
+	fn layout_snapshot(&self) -> LayoutSnapshot
+	{
+		return LayoutSnapshot::new(self._blocks.iter()
+			.map(|(moduleId, module)| (moduleId.clone(), (module.clone(), module.with_untracked(|module| module.layout_get()))))
+			.collect());
+	}
+
+	/// restore a snapshot through the modules signals, modules added since are dropped and removed ones come back
+	fn layout_apply(&mut self, snapshot: &LayoutSnapshot)
+	{
+		let added = self._blocks.keys()
+			.filter(|moduleId| !snapshot.modules_get().contains_key(*moduleId))
+			.cloned()
+			.collect::<Vec<_>>();
+		for moduleId in added
+		{
+			self.block_forget(&moduleId);
+		}
+
+		for (moduleId, (module, layout)) in snapshot.modules_get()
+		{
+			if (!self._blocks.contains_key(moduleId))
+			{
+				self._blocks.insert(moduleId.clone(), module.clone());
+				self.refresh_register(moduleId);
+			}
+			module.with_untracked(|module| module.layout_set(layout));
+		}
+		self.modules_hidden_apply();
+	}
+
+	////////////////////////////////////////
+	// END LAYOUT HISTORY ZONE ---
+	////////////////////////////////////////
+
 	/// This function is used to decrypt the content of a moduleContent before generating the module
 	/// return if the content have been correctly decrypted
 	fn import_decrypt_content(moduleContent: &mut ModuleContent, crypto: &ClientCryptoContext) -> Result<(), AllFrontErrorEnum>
@@ -1,9 +1,11 @@
 use crate::api::modules::components::{ModuleContent, ModuleID};
 use crate::front::modules::components::{moduleContent, Cache, ModuleSizeContrainte};
 use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::modules::module_holder::ModuleHolder;
+use crate::front::modules::refresh_scheduler::RefreshStatusDraw;
 use crate::front::utils::translate::TranslateText;
 use leptos::html::Button;
-use leptos::prelude::{AnyView, ClassAttribute, IntoAny, RwSignal};
+use leptos::prelude::{AnyView, ClassAttribute, CustomAttribute, GlobalAttributes, IntoAny, RwSignal};
 use leptos::prelude::{
 	ArcRwSignal, ElementChild, Get, GetUntracked, NodeRef, NodeRefAttribute, OnAttribute,
 	Set, StyleAttribute, Update, ViewFn,
@@ -11,11 +13,22 @@ use leptos::prelude::{
 use leptos::{component, view, IntoView};
 use leptos_use::{use_draggable_with_options, UseDraggableOptions};
 
+/// position, size, depth and parent of a module, the part of a module restored by the edit history
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ModuleLayout
+{
+	pub pos: [i32; 2],
+	pub size: [u32; 2],
+	pub depth: u32,
+	pub parent: Option<ModuleID>,
+}
+
 pub struct ModulePositions<module: moduleContent>
 {
 	_pos: ArcRwSignal<[i32; 2]>,
 	_size: ArcRwSignal<[u32; 2]>,
 	_depth: ArcRwSignal<u32>,
+	_parent: ArcRwSignal<Option<ModuleID>>,
 	_module: module,
 }
 
@@ -27,6 +40,7 @@ impl<module: moduleContent> ModulePositions<module>
 			_pos: ArcRwSignal::new([0, 0]),
 			_size: ArcRwSignal::new([100, 100]),
 			_depth: Default::default(),
+			_parent: Default::default(),
 			_module: module,
 		}
 	}
@@ -37,6 +51,7 @@ impl<module: moduleContent> ModulePositions<module>
 			_pos: ArcRwSignal::new(from.pos.clone()),
 			_size: ArcRwSignal::new(from.size.clone()),
 			_depth: ArcRwSignal::new(from.depth.clone()),
+			_parent: ArcRwSignal::new(from.parent.clone()),
 			_module: module,
 		}
 	}
@@ -46,6 +61,77 @@ impl<module: moduleContent> ModulePositions<module>
 		self._depth.set(depth);
 	}
 
+	pub fn pos_get(&self) -> [i32; 2]
+	{
+		return self._pos.get();
+	}
+
+	pub fn pos_set(&self, pos: [i32; 2])
+	{
+		self._pos.set(pos);
+	}
+
+	pub fn size_get(&self) -> [u32; 2]
+	{
+		return self._size.get();
+	}
+
+	pub fn parent_get(&self) -> Option<ModuleID>
+	{
+		return self._parent.get();
+	}
+
+	/// attach this module to a container (or detach it with `None`), the position is then relative to the new parent
+	pub fn parent_set(&self, parent: Option<ModuleID>)
+	{
+		self._parent.set(parent);
+		self._module.cache_getUpdate().update(|cache| cache.update());
+	}
+
+	/// move the module with the keyboard, same bounds as a drag
+	pub fn keyboard_move(&self, delta: [i32; 2])
+	{
+		self._pos.update(|pos| {
+			pos[0] = (pos[0] + delta[0]).max(0);
+			pos[1] = (pos[1] + delta[1]).max(0);
+		});
+		self._module.cache_getUpdate().update(|cache| cache.update());
+	}
+
+	/// resize the module with the keyboard, same constraints as a drag
+	pub fn keyboard_resize(&self, delta: [i32; 2])
+	{
+		let constraints = self._module.size();
+		self._size.update(|size| {
+			*size = size_constrain(&constraints, size[0] as i32 + delta[0], size[1] as i32 + delta[1]);
+		});
+		self._module.cache_getUpdate().update(|cache| cache.update());
+	}
+
+	pub(crate) fn layout_get(&self) -> ModuleLayout
+	{
+		return ModuleLayout {
+			pos: self._pos.get_untracked(),
+			size: self._size.get_untracked(),
+			depth: self._depth.get_untracked(),
+			parent: self._parent.get_untracked(),
+		};
+	}
+
+	/// restore a layout from the edit history, the module is marked as changed only if something moved
+	pub(crate) fn layout_set(&self, layout: &ModuleLayout)
+	{
+		if (self.layout_get() == *layout)
+		{
+			return;
+		}
+		self._pos.set(layout.pos);
+		self._size.set(layout.size);
+		self._depth.set(layout.depth);
+		self._parent.set(layout.parent.clone());
+		self._module.cache_getUpdate().update(|cache| cache.update());
+	}
+
 	pub(super) fn visual_order_get(&self) -> (i32,i32,u32)
 	{
 		let position = self._pos.get();
@@ -63,6 +149,7 @@ impl<module: moduleContent> ModulePositions<module>
 		export.pos = self._pos.get();
 		export.size = self._size.get();
 		export.depth = self._depth.get();
+		export.parent = self._parent.get();
 		return export;
 	}
 
@@ -82,6 +169,7 @@ impl<module: moduleContent> ModulePositions<module>
 			size[1] = import.size[1];
 		});
 		self._depth.set(import.depth);
+		self._parent.set(import.parent.clone());
 		self._module.import(import);
 	}
 
@@ -142,6 +230,11 @@ fn ModulePositionDraw(
 			let currentPosition = moveOffset.get_untracked();
 			(currentPosition[0] as f64,currentPosition[1] as f64)
 		})
+		.on_start(|_| {
+			ModuleHolder::history_gestureBegin();
+			true
+		})
+		.on_end(|_| ModuleHolder::history_gestureEnd())
 		.on_move(move |drag| {
 			moveCache.update(|cache| cache.update());
 			movePosition.update(|position| {
@@ -154,10 +247,6 @@ fn ModulePositionDraw(
 	let resizeOffset = size.clone();
 	let resizeSize = size.clone();
 	let resizeCache = cache.clone();
-	let xMin = constraints.x_min;
-	let xMax = constraints.x_max;
-	let yMin = constraints.y_min;
-	let yMax = constraints.y_max;
 	let config_resize = UseDraggableOptions::default()
 		.exact(true)
 		.prevent_default(true)
@@ -165,32 +254,15 @@ fn ModulePositionDraw(
 			let currentSize = resizeOffset.get_untracked();
 			(currentSize[0] as f64,currentSize[1] as f64)
 		})
+		.on_start(|_| {
+			ModuleHolder::history_gestureBegin();
+			true
+		})
+		.on_end(|_| ModuleHolder::history_gestureEnd())
 		.on_move(move |drag| {
-			let mut newWidth = (drag.position.x.round() as i32).max(150);
-			let mut newHeight = (drag.position.y.round() as i32).max(150);
-
-			if let Some(max) = xMax
-			{
-				newWidth = newWidth.min(max as i32);
-			}
-			if let Some(min) = xMin
-			{
-				newWidth = newWidth.max(min as i32);
-			}
-			if let Some(max) = yMax
-			{
-				newHeight = newHeight.min(max as i32);
-			}
-			if let Some(min) = yMin
-			{
-				newHeight = newHeight.max(min as i32);
-			}
-
+			let newSize = size_constrain(&constraints, drag.position.x.round() as i32, drag.position.y.round() as i32);
 			resizeCache.update(|cache| cache.update());
-			resizeSize.update(|size| {
-				size[0] = newWidth as u32;
-				size[1] = newHeight as u32;
-			});
+			resizeSize.set(newSize);
 		});
 	let _resizeDraggable = use_draggable_with_options(el_resize, config_resize);
 
@@ -202,17 +274,21 @@ fn ModulePositionDraw(
 		}
 	};
 
+	let elementId = moduleElement_id(&moduleId);
+	let dataModuleId = moduleId.id.clone();
+	// the style is reactive on its own, so moving a module keeps its element (and the keyboard focus)
+	let style = move || intoStyle(
+		pos.get(),
+		size.get(),
+		depth.get(),
+	);
+
 	view! {
 		{move || {
-			let style = intoStyle(
-				pos.get(),
-				size.get(),
-				depth.get(),
-			);
-
+			let style = style.clone();
 			if editMode.get() {
 				view! {
-					<div class="module_position" style=style>
+					<div class="module_position" id=elementId.clone() data-module-id=dataModuleId.clone() tabindex="0" style=style>
 						<div class="module module--editing">
 							<div class="module_header">
 								<button type="button" class="module_handle module_move_handle" node_ref=el_move>
@@ -226,6 +302,7 @@ fn ModulePositionDraw(
 							</div>
 							<div class="module_content">{innerView.run()}</div>
 						</div>
+						<RefreshStatusDraw moduleId=moduleId.clone()/>
 						<button type="button" class="module_handle module_resize_handle" node_ref=el_resize>
 							<i class="iconoir-arrow-down-right-square" aria-hidden="true"></i>
 							<span class="visually_hidden"><TranslateText key="FRONTUI_MODULE_RESIZE_ACTION"/></span>
@@ -234,8 +311,9 @@ fn ModulePositionDraw(
 				}.into_any()
 			} else {
 				view! {
-					<div class="module module_position" style=style>
+					<div class="module module_position" id=elementId.clone() data-module-id=dataModuleId.clone() tabindex="0" style=style>
 						<div class="module_content">{innerView.run()}</div>
+						<RefreshStatusDraw moduleId=moduleId.clone()/>
 					</div>
 				}.into_any()
 			}
@@ -243,6 +321,37 @@ fn ModulePositionDraw(
 	}
 }
 
+/// DOM id of a drawn module, used to move the keyboard focus between modules
+pub(crate) fn moduleElement_id(moduleId: &ModuleID) -> String
+{
+	return format!("module-{}", moduleId.id);
+}
+
+/// apply the module size constraints, a module is never smaller than 150px
+fn size_constrain(constraints: &ModuleSizeContrainte, width: i32, height: i32) -> [u32; 2]
+{
+	let mut newWidth = width.max(150);
+	let mut newHeight = height.max(150);
+
+	if let Some(max) = constraints.x_max
+	{
+		newWidth = newWidth.min(max as i32);
+	}
+	if let Some(min) = constraints.x_min
+	{
+		newWidth = newWidth.max(min as i32);
+	}
+	if let Some(max) = constraints.y_max
+	{
+		newHeight = newHeight.min(max as i32);
+	}
+	if let Some(min) = constraints.y_min
+	{
+		newHeight = newHeight.max(min as i32);
+	}
+	return [newWidth as u32, newHeight as u32];
+}
+
 fn intoStyle(pos: [i32; 2], size: [u32; 2], depth: u32) -> String
 {
 	return format!(
@@ -250,3 +359,42 @@ fn intoStyle(pos: [i32; 2], size: [u32; 2], depth: u32) -> String
 		pos[0], pos[1], size[0], size[1], depth
 	);
 }
+
+#[cfg(test)]
+mod tests
+{
+	use leptos::prelude::Owner;
+	use crate::front::modules::components::ModuleSizeContrainte;
+	use crate::front::modules::module_type::ModuleType;
+	use crate::front::modules::todo::Todo;
+	use super::{size_constrain, ModulePositions};
+
+	#[test]
+	fn sizeConstrain_appliesMinimumThenModuleBounds()
+	{
+		let constraints = ModuleSizeContrainte {
+			x_min: Some(200),
+			x_max: Some(400),
+			y_min: None,
+			y_max: Some(300),
+		};
+
+		assert_eq!(size_constrain(&constraints, 10, 10), [200, 150]);
+		assert_eq!(size_constrain(&constraints, 900, 900), [400, 300]);
+		assert_eq!(size_constrain(&ModuleSizeContrainte::default(), -20, 250), [150, 250]);
+	}
+
+	#[test]
+	fn keyboardMove_staysInsideTheDashboard()
+	{
+		let owner = Owner::new();
+		owner.with(|| {
+			let module = ModulePositions::new(ModuleType::TODO(Todo::new()));
+			module.pos_set([5, 40]);
+			module.keyboard_move([-10, -10]);
+
+			assert_eq!(module.pos_get(), [0, 30]);
+		});
+		owner.cleanup();
+	}
+}
@@ -2,8 +2,9 @@ use leptoaster::ToasterContext;
 use leptos::prelude::{ArcRwSignal, RwSignal, ViewFn};
 use strum_macros::EnumDiscriminants;
 use crate::api::modules::components::{ModuleContent, ModuleID};
-use crate::front::modules::components::{moduleContent, Backable, BoxFuture, Cache, Cacheable, ModuleName, ModuleSizeContrainte, RefreshTime};
+use crate::front::modules::components::{moduleContent, Backable, RefreshFuture, Cache, Cacheable, ModuleName, ModuleSizeContrainte, RefreshTime};
 use crate::front::modules::calendar::Calendar;
+use crate::front::modules::container::Container;
 use crate::front::modules::todo::Todo;
 use strum_macros::EnumIter;
 use crate::front::modules::mail::Mail;
@@ -25,6 +26,8 @@ pub enum ModuleType
 	WEATHER(Weather),
 	#[strum(to_string = "CALENDAR")]
 	CALENDAR(Calendar),
+	#[strum(to_string = "CONTAINER")]
+	CONTAINER(Container),
 }
 
 impl ModuleTypeDiscriminants
@@ -38,6 +41,7 @@ impl ModuleTypeDiscriminants
 			Self::MAIL => "MODULE_TYPE_MAIL",
 			Self::WEATHER => "MODULE_TYPE_WEATHER",
 			Self::CALENDAR => "MODULE_TYPE_CALENDAR",
+			Self::CONTAINER => "MODULE_TYPE_CONTAINER",
 		};
 	}
 }
@@ -50,6 +54,7 @@ impl ModuleType {
 			ModuleType::MAIL(x) => Box::new(x),
 			ModuleType::WEATHER(x) => Box::new(x),
 			ModuleType::CALENDAR(x) => Box::new(x),
+			ModuleType::CONTAINER(x) => Box::new(x),
 		}
 	}
 
@@ -60,6 +65,7 @@ impl ModuleType {
 			ModuleType::MAIL(x) => Box::new(x),
 			ModuleType::WEATHER(x) => Box::new(x),
 			ModuleType::CALENDAR(x) => Box::new(x),
+			ModuleType::CONTAINER(x) => Box::new(x),
 		}
 	}
 
@@ -70,6 +76,7 @@ impl ModuleType {
 			ModuleType::MAIL(x) => Box::new(x),
 			ModuleType::WEATHER(x) => Box::new(x),
 			ModuleType::CALENDAR(x) => Box::new(x),
+			ModuleType::CONTAINER(x) => Box::new(x),
 		}
 	}
 }
@@ -87,7 +94,7 @@ impl Backable for ModuleType {
 		return self.intoBackable().refresh_time();
 	}
 
-	fn refresh(&self,moduleActions: ModuleActionFn, moduleId: ModuleID, toaster: ToasterContext) -> Option<BoxFuture> {
+	fn refresh(&self,moduleActions: ModuleActionFn, moduleId: ModuleID, toaster: ToasterContext) -> Option<RefreshFuture> {
 		return self.intoBackable().refresh(moduleActions,moduleId,toaster);
 	}
 
@@ -107,6 +114,7 @@ impl Backable for ModuleType {
 			ModuleType::MAIL(x) => x.isOlderThan(other),
 			ModuleType::WEATHER(x) => x.isOlderThan(other),
 			ModuleType::CALENDAR(x) => x.isOlderThan(other),
+			ModuleType::CONTAINER(x) => x.isOlderThan(other),
 		}
 	}
 
@@ -127,6 +135,9 @@ impl Backable for ModuleType {
 			"CALENDAR" => {
 				Calendar::newFromModuleContent(from).map(|content| Self::CALENDAR(content))
 			},
+			"CONTAINER" => {
+				Container::newFromModuleContent(from).map(|content| Self::CONTAINER(content))
+			},
 			&_ => panic!("ModuleType::newFromModuleContent : unknown module type {}", from.typeModule)
 		}
 	}
@@ -169,6 +180,7 @@ pub fn StringToModuleType(from: impl AsRef<str>) -> Option<ModuleType>
 		"WEATHER" => Some(ModuleType::WEATHER(Default::default())),
 		"MAIL" => Some(ModuleType::MAIL(Default::default())),
 		"CALENDAR" => Some(ModuleType::CALENDAR(Calendar::new())),
+		"CONTAINER" => Some(ModuleType::CONTAINER(Default::default())),
 		&_ => None
 	}
 }
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use leptos::prelude::{AriaAttributes, ClassAttribute, ElementChild, Get, GlobalAttributes, IntoAny, Memo, OnAttribute, RwSignal, Update, With};
+use leptos::{component, view, IntoView};
+use time::UtcDateTime;
+use crate::api::modules::components::ModuleID;
+use crate::front::modules::components::{distant_time_simpler, RefreshOutcome, RefreshTime};
+use crate::front::modules::module_holder::ModuleHolder;
+use crate::front::utils::translate::{Translate, TranslateText};
+
+/// delay between two checks of the scheduler, at most one automatic refresh starts per check
+#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+pub(crate) const REFRESH_TICK_MS: u32 = 1_000;
+
+/// a page in the background keeps refreshing only for the unread notifications, every module this late after its normal time
+pub(crate) const REFRESH_HIDDEN_DELAY_MS: i64 = 4 * 60 * 1000;
+
+/// refresh state of one module, shown by the status indicator of its frame (unix times in milliseconds)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct RefreshStatus
+{
+	/// next automatic refresh, none while a refresh is running
+	pub nextRun: Option<i64>,
+	pub lastSuccess: Option<i64>,
+	pub lastError: Option<i64>,
+	/// consecutive failures, drives the backoff
+	pub failures: u32,
+	pub running: bool,
+}
+
+struct RefreshEntry
+{
+	interval: i64,
+	status: RefreshStatus,
+}
+
+/// single refresh timeline of every module, replacing one interval per module
+#[derive(Default)]
+pub(crate) struct RefreshScheduler
+{
+	_entries: HashMap<ModuleID, RefreshEntry>,
+}
+
+impl RefreshScheduler
+{
+	const BACKOFF_MAXIMUM_MS: i64 = 6 * 3600 * 1000;
+	/// the first automatic refresh of a module is shifted by up to this part of its interval
+	const STAGGER_RATIO: f64 = 0.1;
+
+	pub(crate) fn interval_get(refreshTime: &RefreshTime) -> Option<i64>
+	{
+		let minutes = match refreshTime
+		{
+			RefreshTime::NONE => return None,
+			RefreshTime::MINUTES(minutes) => *minutes as i64,
+			RefreshTime::HOURS(hours) => *hours as i64 * 60,
+		};
+		return Some(minutes.max(1) * 60 * 1000);
+	}
+
+	/// register a module (or update its interval), a module without refresh time is forgotten.
+	/// `jitter` is a random value in [0,1) spreading the first run of modules loaded together.
+	pub(crate) fn module_set(&mut self, moduleId: ModuleID, refreshTime: &RefreshTime, now: i64, jitter: f64)
+	{
+		let Some(interval) = Self::interval_get(refreshTime)
+		else
+		{
+			self._entries.remove(&moduleId);
+			return;
+		};
+
+		if let Some(entry) = self._entries.get_mut(&moduleId)
+		{
+			if (entry.interval != interval)
+			{
+				entry.interval = interval;
+				if (!entry.status.running && entry.status.failures == 0)
+				{
+					entry.status.nextRun = Some(now + interval);
+				}
+			}
+			return;
+		}
+
+		let stagger = (interval as f64 * Self::STAGGER_RATIO * jitter) as i64;
+		self._entries.insert(moduleId, RefreshEntry {
+			interval,
+			status: RefreshStatus {
+				nextRun: Some(now + interval + stagger),
+				..Default::default()
+			},
+		});
+	}
+
+	pub(crate) fn module_remove(&mut self, moduleId: &ModuleID)
+	{
+		self._entries.remove(moduleId);
+	}
+
+	pub(crate) fn clear(&mut self)
+	{
+		self._entries.clear();
+	}
+
+	pub(crate) fn status_get(&self, moduleId: &ModuleID) -> Option<RefreshStatus>
+	{
+		return self._entries.get(moduleId).map(|entry| entry.status.clone());
+	}
+
+	/// the most overdue module, modules that are running or hidden are skipped
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn due_get(&self, now: i64, isHidden: impl Fn(&ModuleID) -> bool) -> Option<ModuleID>
+	{
+		return self._entries.iter()
+			.filter(|(moduleId, entry)| !entry.status.running && !isHidden(moduleId))
+			.filter_map(|(moduleId, entry)| entry.status.nextRun.filter(|nextRun| *nextRun <= now).map(|nextRun| (nextRun, moduleId)))
+			.min()
+			.map(|(_, moduleId)| moduleId.clone());
+	}
+
+	pub(crate) fn run_start(&mut self, moduleId: &ModuleID)
+	{
+		if let Some(entry) = self._entries.get_mut(moduleId)
+		{
+			entry.status.running = true;
+			entry.status.nextRun = None;
+		}
+	}
+
+	/// a module that had nothing to refresh keeps its normal cadence
+	pub(crate) fn run_skip(&mut self, moduleId: &ModuleID, now: i64)
+	{
+		if let Some(entry) = self._entries.get_mut(moduleId)
+		{
+			entry.status.running = false;
+			entry.status.nextRun = Some(now + entry.interval);
+		}
+	}
+
+	pub(crate) fn run_finish(&mut self, moduleId: &ModuleID, outcome: RefreshOutcome, now: i64, jitter: f64)
+	{
+		let Some(entry) = self._entries.get_mut(moduleId) else {return};
+		entry.status.running = false;
+		match outcome
+		{
+			RefreshOutcome::SUCCESS => {
+				entry.status.failures = 0;
+				entry.status.lastSuccess = Some(now);
+				entry.status.nextRun = Some(now + entry.interval);
+			},
+			RefreshOutcome::FAILED => {
+				entry.status.failures = entry.status.failures.saturating_add(1);
+				entry.status.lastError = Some(now);
+				entry.status.nextRun = Some(now + Self::backoff_get(entry.interval, entry.status.failures, jitter));
+			},
+		}
+	}
+
+	/// exponential backoff with "equal jitter": between half and the whole doubled delay,
+	/// never sooner than the normal interval and never later than the maximum (or the interval if it is longer)
+	fn backoff_get(interval: i64, failures: u32, jitter: f64) -> i64
+	{
+		let maximum = Self::BACKOFF_MAXIMUM_MS.max(interval);
+		let delay = interval.saturating_mul(1_i64 << failures.min(16)).min(maximum);
+		let jittered = delay / 2 + ((delay / 2) as f64 * jitter.clamp(0.0, 1.0)) as i64;
+		return jittered.clamp(interval, maximum);
+	}
+}
+
+/// unix time in milliseconds
+pub(crate) fn refresh_now() -> i64
+{
+	return (UtcDateTime::now().unix_timestamp_nanos() / 1_000_000) as i64;
+}
+
+#[cfg(feature="hydrate")]
+pub(crate) fn refresh_jitter() -> f64
+{
+	return js_sys::Math::random();
+}
+
+#[cfg(not(feature="hydrate"))]
+pub(crate) fn refresh_jitter() -> f64
+{
+	return 0.5;
+}
+
+/// Page Visibility API, a page in a background tab slows down or stops the refresh of its modules
+#[cfg(feature="hydrate")]
+pub(crate) fn page_isHidden() -> bool
+{
+	return web_sys::window()
+		.and_then(|window| window.document())
+		.is_some_and(|document| document.hidden());
+}
+
+#[cfg(not(feature="hydrate"))]
+pub(crate) fn page_isHidden() -> bool
+{
+	return false;
+}
+
+/// indicator on a module frame: running refresh, consecutive failures and next automatic refresh
+#[component]
+pub(crate) fn RefreshStatusDraw(moduleId: ModuleID) -> impl IntoView
+{
+	let scheduler = ModuleHolder::refreshScheduler_get();
+	let status = Memo::new(move |_| scheduler.with(|scheduler| scheduler.status_get(&moduleId)));
+	let isScheduled = Memo::new(move |_| status.with(|status| status.is_some()));
+	// the delays are relative to now, they are computed again when the tooltip opens
+	let opened = RwSignal::new(0_u32);
+
+	view! {
+		{move || isScheduled.get().then(|| view!{
+			<span class=move || format!("module_refresh_status module_refresh_status--{} alttext_upper", refreshStatus_look(status.get().as_ref()).0) tabindex="0"
+				on:mouseenter=move |_| opened.update(|opened| *opened += 1)
+				on:focus=move |_| opened.update(|opened| *opened += 1)>
+				<i class=move || refreshStatus_look(status.get().as_ref()).1 aria-hidden="true"></i>
+				<span class="visually_hidden"><TranslateText key="MODULE_REFRESH_STATUS"/></span>
+				<span class="alttext" role="tooltip">{move || {
+					opened.get();
+					let Some(status) = status.get() else {return view!{}.into_any()};
+					let running = status.running.then(|| view!{<span><Translate key="MODULE_REFRESH_STATUS_RUNNING"/></span><br/>});
+					let failures = (status.failures > 0).then(|| view!{
+						<span><Translate key="MODULE_REFRESH_STATUS_FAILURES" params=HashMap::from([("count".to_string(), status.failures.to_string())])/></span><br/>
+					});
+					let nextRun = status.nextRun.map(|nextRun| view!{
+						<span><Translate key="MODULE_REFRESH_STATUS_NEXT"/>{" "}{distant_time_simpler(nextRun / 1000)}</span>
+					});
+					view!{{running}{failures}{nextRun}}.into_any()
+				}}</span>
+			</span>
+		})}
+	}
+}
+
+/// (css modifier, icon) of the status indicator
+fn refreshStatus_look(status: Option<&RefreshStatus>) -> (&'static str, &'static str)
+{
+	return match status
+	{
+		Some(status) if status.running => ("running", "iconoir-refresh-double"),
+		Some(status) if status.failures > 0 => ("error", "iconoir-warning-triangle"),
+		_ => ("idle", "iconoir-clock"),
+	};
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::modules::components::ModuleID;
+	use crate::front::modules::components::{RefreshOutcome, RefreshTime};
+	use super::RefreshScheduler;
+
+	const MINUTE: i64 = 60 * 1000;
+
+	fn moduleId_get(id: &str) -> ModuleID
+	{
+		return ModuleID {id: id.to_string()};
+	}
+
+	#[test]
+	fn moduleSet_staggersTheFirstRunInsideTheInterval()
+	{
+		let mut scheduler = RefreshScheduler::default();
+		scheduler.module_set(moduleId_get("early"), &RefreshTime::MINUTES(10), 0, 0.0);
+		scheduler.module_set(moduleId_get("late"), &RefreshTime::MINUTES(10), 0, 0.99);
+		scheduler.module_set(moduleId_get("never"), &RefreshTime::NONE, 0, 0.5);
+
+		assert_eq!(scheduler.status_get(&moduleId_get("early")).unwrap().nextRun, Some(10 * MINUTE));
+		let late = scheduler.status_get(&moduleId_get("late")).unwrap().nextRun.unwrap();
+		assert!(late > 10 * MINUTE && late < 11 * MINUTE);
+		assert!(scheduler.status_get(&moduleId_get("never")).is_none());
+	}
+
+	#[test]
+	fn dueGet_returnsTheMostOverdueVisibleModule()
+	{
+		let mut scheduler = RefreshScheduler::default();
+		for (id, refreshTime) in [("hidden", RefreshTime::MINUTES(1)), ("slow", RefreshTime::MINUTES(5)), ("fast", RefreshTime::MINUTES(2))]
+		{
+			scheduler.module_set(moduleId_get(id), &refreshTime, 0, 0.0);
+		}
+		let isHidden = |moduleId: &ModuleID| moduleId.id == "hidden";
+
+		assert_eq!(scheduler.due_get(MINUTE, isHidden), None);
+		assert_eq!(scheduler.due_get(10 * MINUTE, isHidden), Some(moduleId_get("fast")));
+
+		scheduler.run_start(&moduleId_get("fast"));
+		assert_eq!(scheduler.due_get(10 * MINUTE, isHidden), Some(moduleId_get("slow")));
+	}
+
+	#[test]
+	fn runFinish_backsOffAfterFailuresAndResetsOnSuccess()
+	{
+		let mut scheduler = RefreshScheduler::default();
+		let moduleId = moduleId_get("mail");
+		scheduler.module_set(moduleId.clone(), &RefreshTime::MINUTES(10), 0, 0.0);
+
+		let mut previousDelay = 0;
+		for _ in 0..3
+		{
+			scheduler.run_start(&moduleId);
+			scheduler.run_finish(&moduleId, RefreshOutcome::FAILED, 0, 1.0);
+			let delay = scheduler.status_get(&moduleId).unwrap().nextRun.unwrap();
+			assert!(delay > previousDelay);
+			previousDelay = delay;
+		}
+		let status = scheduler.status_get(&moduleId).unwrap();
+		assert_eq!(status.failures, 3);
+		assert_eq!(status.lastError, Some(0));
+		assert_eq!(previousDelay, 80 * MINUTE);
+
+		scheduler.run_finish(&moduleId, RefreshOutcome::SUCCESS, MINUTE, 0.0);
+		let status = scheduler.status_get(&moduleId).unwrap();
+		assert_eq!(status.failures, 0);
+		assert_eq!(status.nextRun, Some(11 * MINUTE));
+	}
+
+	#[test]
+	fn backoff_staysBetweenTheIntervalAndTheMaximum()
+	{
+		assert_eq!(RefreshScheduler::backoff_get(10 * MINUTE, 1, 0.0), 10 * MINUTE);
+		assert_eq!(RefreshScheduler::backoff_get(10 * MINUTE, 1, 1.0), 20 * MINUTE);
+		assert_eq!(RefreshScheduler::backoff_get(10 * MINUTE, 40, 1.0), RefreshScheduler::BACKOFF_MAXIMUM_MS);
+		assert_eq!(RefreshScheduler::backoff_get(24 * 60 * MINUTE, 3, 0.0), 24 * 60 * MINUTE);
+	}
+}
@@ -1,14 +1,15 @@
-use leptos::prelude::{AriaAttributes, ClassAttribute, CollectView, ElementChild, GlobalAttributes};
-use feed_rs::model::{Feed, Link, Text};
+use std::collections::HashMap;
+use leptos::prelude::{AriaAttributes, ClassAttribute, CollectView, ElementChild, GlobalAttributes, OnAttribute};
+use feed_rs::model::{Entry, Feed, Link, Text};
 use feed_rs::parser;
 use leptoaster::{ToasterContext};
 use leptos::children::ViewFn;
-use leptos::prelude::{AnyView, ArcRwSignal, Get, GetUntracked, IntoAny, RwSignal, Update};
+use leptos::prelude::{AnyView, ArcRwSignal, Effect, Get, GetUntracked, IntoAny, RwSignal, Update, With, WithUntracked};
 use leptos::{component, view, IntoView};
 use serde::{Deserialize, Serialize};
 use crate::api::modules::components::{ModuleContent, ModuleID};
 use crate::api::proxys::wget::{API_proxys_wget};
-use crate::front::modules::components::{distant_time_simpler, Backable, BoxFuture, Cache, Cacheable, FieldHelper, ModuleName, ModuleSizeContrainte, RefreshTime};
+use crate::front::modules::components::{distant_time_simpler, Backable, RefreshFuture, RefreshOutcome, Cache, Cacheable, FieldHelper, ModuleName, ModuleSizeContrainte, RefreshTime};
 use crate::front::modules::module_actions::ModuleActionFn;
 use crate::front::utils::toaster_helpers::toaster_api;
 use crate::front::utils::translate::{Translate, TranslateText};
@@ -22,6 +23,9 @@ struct RssConfig
 	pub link: String,
 	#[serde(default = "maxline_default")]
 	pub maxline: u8,
+	/// publication time of the newest entry the user has seen, none until the feed is loaded once
+	#[serde(default)]
+	pub lastSeen: Option<i64>,
 }
 
 fn maxline_default() -> u8{
@@ -36,6 +40,7 @@ impl Default for RssConfig
 			title: "".to_string(),
 			link: "".to_string(),
 			maxline: maxline_default(),
+			lastSeen: None,
 		}
 	}
 }
@@ -63,29 +68,65 @@ impl Rss
 		}
 	}
 
-	async fn sync(toaster: ToasterContext, rssContent: ArcRwSignal<Option<(u64,Feed)>>, config: ArcRwSignal<RssConfig>, moduleActions: ModuleActionFn)
+	async fn sync(toaster: ToasterContext, rssContent: ArcRwSignal<Option<(u64,Feed)>>, config: ArcRwSignal<RssConfig>, moduleActions: ModuleActionFn) -> RefreshOutcome
 	{
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
 		let url = config.get_untracked().link.clone();
 		let oldTime = rssContent.get_untracked().map(|content| content.0);
 		let apiResult = API_proxys_wget(url.to_string(),oldTime).await;
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
-		let Some((time,text)) = toaster_api(&toaster,apiResult, None).await else {return}; // TODO: return must throw error toaster
+		let Some((time,text)) = toaster_api(&toaster,apiResult, None).await else {return RefreshOutcome::FAILED}; // TODO: return must throw error toaster
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
-		let Ok(feed) = parser::parse(text.as_bytes()) else {return};
+		let Ok(feed) = parser::parse(text.as_bytes()) else {return RefreshOutcome::FAILED};
 
 		rssContent.update(|rssContent| {
 			*rssContent = Some((time,feed));
-		})
+		});
+		return RefreshOutcome::SUCCESS;
+	}
+
+	fn entry_time(entry: &Entry) -> Option<i64>
+	{
+		return entry.published.or(entry.updated).map(|date| date.timestamp());
+	}
+
+	fn entries_newest(feed: &Feed) -> Option<i64>
+	{
+		return feed.entries.iter().filter_map(Self::entry_time).max();
+	}
+
+	/// entries published since the last view, the first load of a feed has nothing new
+	fn entries_newCount(feed: &Feed, lastSeen: Option<i64>) -> usize
+	{
+		let Some(lastSeen) = lastSeen else {return 0};
+		return feed.entries.iter()
+			.filter(|entry| Self::entry_time(entry).is_some_and(|time| time > lastSeen))
+			.count();
+	}
+
+	/// remember the newest entry as seen, saved with the module so every session shares it
+	fn entries_markSeen(config: &ArcRwSignal<RssConfig>, update: &ArcRwSignal<Cache>, feed: &Feed, editMode: RwSignal<bool>, moduleActions: &ModuleActionFn, moduleId: &ModuleID)
+	{
+		let Some(newest) = Self::entries_newest(feed) else {return};
+		if (config.with_untracked(|config| config.lastSeen.is_some_and(|lastSeen| lastSeen >= newest)))
+		{
+			return;
+		}
+		config.update(|config| config.lastSeen = Some(newest));
+		update.update(|cache| cache.update());
+		if (!editMode.get_untracked())
+		{
+			(moduleActions.saveFn)(moduleId.clone());
+		}
 	}
 
 	fn utils_title(title: String, entryTitle: Option<Text>) -> AnyView
@@ -167,14 +208,14 @@ impl Backable for Rss
 		Rss::MODULE_NAME.to_string()
 	}
 
-	fn draw(&self, editMode: RwSignal<bool>, _moduleActions: ModuleActionFn, _: ModuleID) -> ViewFn
+	fn draw(&self, editMode: RwSignal<bool>, moduleActions: ModuleActionFn, moduleId: ModuleID) -> ViewFn
 	{
 		let configInner = self.config.clone();
 		let contentInner = self.rssContent.clone();
 		let updateInner = self._update.clone();
 		ViewFn::from(move || {
 			view! {
-				<RssDraw config=configInner.clone() content=contentInner.clone() update=updateInner.clone() editMode=editMode/>
+				<RssDraw config=configInner.clone() content=contentInner.clone() update=updateInner.clone() editMode=editMode moduleActions=moduleActions.clone() moduleId=moduleId.clone()/>
 			}.into_any()
 		})
 	}
@@ -183,12 +224,12 @@ impl Backable for Rss
 		return RefreshTime::MINUTES(10);
 	}
 
-	fn refresh(&self,moduleActions: ModuleActionFn, _moduleId: ModuleID, toaster: ToasterContext) -> Option<BoxFuture> {
+	fn refresh(&self,moduleActions: ModuleActionFn, _moduleId: ModuleID, toaster: ToasterContext) -> Option<RefreshFuture> {
 		let config = self.config.clone();
 		let rssContent = self.rssContent.clone();
 		let tmp = Self::sync(toaster,rssContent,config,moduleActions);
 		return Some(Box::pin(async move {
-			tmp.await;
+			return tmp.await;
 		}));
 	}
 
@@ -242,8 +283,34 @@ impl Backable for Rss
 fn RssDraw(config: ArcRwSignal<RssConfig>,
            content: ArcRwSignal<Option<(u64,Feed)>>,
            update: ArcRwSignal<Cache>,
-           editMode: RwSignal<bool>) -> impl IntoView
+           editMode: RwSignal<bool>,
+           moduleActions: ModuleActionFn,
+           moduleId: ModuleID) -> impl IntoView
 {
+	let unreadConfig = config.clone();
+	let unreadContent = content.clone();
+	let unreadActions = moduleActions.clone();
+	let unreadModuleId = moduleId.clone();
+	Effect::new(move || {
+		let lastSeen = unreadConfig.with(|config| config.lastSeen);
+		let newCount = unreadContent.with(|content| {
+			let (_,feed) = content.as_ref()?;
+			if (lastSeen.is_none())
+			{
+				// first load of the feed, everything already published counts as seen. The baseline stays in
+				// memory, opening the page must not write the module; the next "mark seen" or edit saves it
+				if let Some(newest) = Rss::entries_newest(feed)
+				{
+					unreadConfig.update(|config| config.lastSeen = Some(newest));
+				}
+				return Some(0);
+			}
+			return Some(Rss::entries_newCount(feed, lastSeen));
+		});
+		unreadActions.unread_set(unreadModuleId.clone(), newCount.unwrap_or(0));
+	});
+	let markEditMode = editMode;
+
 	view! {{move || {
 		let editMode = editMode.get();
 			if editMode
@@ -254,7 +321,11 @@ fn RssDraw(config: ArcRwSignal<RssConfig>,
 				titleF.setFullSize();
 				let mut linkF = FieldHelper::new(&config,&update,"MODULE_RSS_LINK",
 					|d| d.get().link,
-					|ev,inner| inner.link = ev.target().value());
+					|ev,inner| {
+						inner.link = ev.target().value();
+						// another feed, its entries are not compared with the previous one
+						inner.lastSeen = None;
+					});
 				linkF.setFullSize();
 				let maxLineF = FieldHelper::new(&config,&update,"MODULE_RSS_MAXLINE",
 					|d| d.get().maxline.to_string(),
@@ -278,13 +349,31 @@ fn RssDraw(config: ArcRwSignal<RssConfig>,
 			{
 				view!{{
 					let config = config.clone();
+					let update = update.clone();
+					let moduleActions = moduleActions.clone();
+					let moduleId = moduleId.clone();
 					content.get().map(|(_,mut rssContent)|{
+					let lastSeen = config.get().lastSeen;
+					let newCount = Rss::entries_newCount(&rssContent, lastSeen);
+					let newAction = (newCount > 0).then(|| {
+						let feed = rssContent.clone();
+						let markConfig = config.clone();
+						let markUpdate = update.clone();
+						let markActions = moduleActions.clone();
+						let markModuleId = moduleId.clone();
+						view!{
+							<button type="button" class="module_title_action module_rss_new" on:click=move |_| Rss::entries_markSeen(&markConfig, &markUpdate, &feed, markEditMode, &markActions, &markModuleId)>
+								<Translate key="MODULE_RSS_NEW" params={HashMap::from([("count".to_string(),newCount.to_string())])}/>
+								<span class="visually_hidden"><TranslateText key="MODULE_RSS_MARK_SEEN"/></span>
+							</button>
+						}
+					});
 
 					view!{
 						<>
 						<div class="module_titlebar">
 							<h2 class="module_title">{Rss::utils_title(config.get().title,rssContent.title)}</h2>
-							<div class="module_title_actions">{Rss::utils_desc(&rssContent.description)}{Rss::utils_link(rssContent.links)}</div>
+							<div class="module_title_actions">{newAction}{Rss::utils_desc(&rssContent.description)}{Rss::utils_link(rssContent.links)}</div>
 						</div>
 						<div class="module_rss_upper">
 						<table class="module_rss_table"><tbody>
@@ -303,8 +392,10 @@ fn RssDraw(config: ArcRwSignal<RssConfig>,
 									{
 										view!{<span>{title}</span>}.into_any()
 									};
+									let isNew = lastSeen.is_some_and(|lastSeen| Rss::entry_time(entry).is_some_and(|time| time > lastSeen));
+									let rowClass = if (isNew) {"module_rss_row module_rss_row--new"} else {"module_rss_row"};
 									view!{
-										<tr class="module_rss_row">
+										<tr class=rowClass>
 											<td class="module_rss_age">{distant_time_simpler(entry.published.clone().unwrap_or_default().timestamp())}</td>
 											<td>{titleView}</td>
 										</tr>
@@ -323,3 +414,29 @@ fn RssDraw(config: ArcRwSignal<RssConfig>,
 			}
 		}}}.into_any()
 }
+
+#[cfg(test)]
+mod tests
+{
+	use feed_rs::parser;
+	use super::Rss;
+
+	#[test]
+	fn entriesNewCount_countsOnlyEntriesPublishedSinceTheLastView()
+	{
+		let feed = parser::parse(r#"<?xml version="1.0" encoding="utf-8"?>
+			<feed xmlns="http://www.w3.org/2005/Atom">
+				<title>Example</title>
+				<id>urn:example</id>
+				<updated>2026-01-03T00:00:00Z</updated>
+				<entry><title>Old</title><id>urn:old</id><updated>2026-01-01T00:00:00Z</updated></entry>
+				<entry><title>New</title><id>urn:new</id><published>2026-01-03T00:00:00Z</published><updated>2026-01-03T00:00:00Z</updated></entry>
+			</feed>"#.as_bytes()).unwrap();
+		let newest = Rss::entries_newest(&feed).unwrap();
+
+		assert_eq!(Rss::entries_newCount(&feed, None), 0);
+		assert_eq!(Rss::entries_newCount(&feed, Some(newest - 1)), 1);
+		assert_eq!(Rss::entries_newCount(&feed, Some(newest)), 0);
+		assert_eq!(Rss::entries_newCount(&feed, Some(0)), 2);
+	}
+}
@@ -8,7 +8,7 @@ use leptos::prelude::{ArcRwSignal, RwSignal};
 use leptos::children::ViewFn;
 use serde::{Deserialize, Serialize};
 use crate::api::modules::components::{ModuleContent, ModuleID};
-use crate::front::modules::components::{Backable, BoxFuture, Cache, Cacheable, ModuleName, ModuleSizeContrainte, RefreshTime};
+use crate::front::modules::components::{Backable, RefreshFuture, Cache, Cacheable, ModuleName, ModuleSizeContrainte, RefreshTime};
 use crate::front::modules::module_actions::ModuleActionFn;
 
 static MAX_LENGTH: usize = 100000;
@@ -84,14 +84,13 @@ impl Backable for Todo
 		})
 	}
 
+	/// the list is only changed by the dashboard, the other sessions push their changes as live updates
 	fn refresh_time(&self) -> RefreshTime {
-		RefreshTime::MINUTES(1)
+		RefreshTime::NONE
 	}
 
-	fn refresh(&self,moduleActions: ModuleActionFn, moduleId: ModuleID, _toaster: ToasterContext) -> Option<BoxFuture> {
-		return Some(Box::pin(async move {
-			(moduleActions.clone().getFn)((moduleId.clone()));
-		}));
+	fn refresh(&self,_moduleActions: ModuleActionFn, _moduleId: ModuleID, _toaster: ToasterContext) -> Option<RefreshFuture> {
+		return None;
 	}
 
 	fn export(&self) -> ModuleContent
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use crate::api::modules::components::ModuleID;
+
+/// unread items reported by each module (unread mails, new feed entries...), shown in the title and the favicon
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct UnreadCounters
+{
+	_counts: HashMap<ModuleID, usize>,
+}
+
+impl UnreadCounters
+{
+	/// a module without anything unread is forgotten, return true when the count changed
+	pub(crate) fn count_set(&mut self, moduleId: ModuleID, count: usize) -> bool
+	{
+		if (count == 0)
+		{
+			return self._counts.remove(&moduleId).is_some();
+		}
+		return self._counts.insert(moduleId, count) != Some(count);
+	}
+
+	pub(crate) fn module_remove(&mut self, moduleId: &ModuleID)
+	{
+		self._counts.remove(moduleId);
+	}
+
+	pub(crate) fn clear(&mut self)
+	{
+		self._counts.clear();
+	}
+
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) fn total_get(&self) -> usize
+	{
+		return self._counts.values().fold(0usize, |total, count| total.saturating_add(*count));
+	}
+}
+
+#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+pub(crate) struct UnreadLimits;
+
+impl UnreadLimits
+{
+	/// above this, the badge only shows "99+"
+	#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+	pub(crate) const BADGE_MAXIMUM: usize = 99;
+}
+
+/// document title with the unread total in front, the title is left untouched when nothing is unread
+#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+pub(crate) fn unreadTitle_get(title: &str, total: usize) -> String
+{
+	if (total == 0)
+	{
+		return title.to_string();
+	}
+	return format!("({}) {}", unreadBadge_label(total), title);
+}
+
+#[cfg_attr(not(feature="hydrate"), allow(dead_code))]
+pub(crate) fn unreadBadge_label(total: usize) -> String
+{
+	if (total > UnreadLimits::BADGE_MAXIMUM)
+	{
+		return format!("{}+", UnreadLimits::BADGE_MAXIMUM);
+	}
+	return total.to_string();
+}
+
+#[cfg(test)]
+mod tests
+{
+	use crate::api::modules::components::ModuleID;
+	use super::{unreadBadge_label, unreadTitle_get, UnreadCounters};
+
+	fn moduleId_get(id: &str) -> ModuleID
+	{
+		return ModuleID {id: id.to_string()};
+	}
+
+	#[test]
+	fn countSet_sumsModulesAndForgetsEmptyOnes()
+	{
+		let mut counters = UnreadCounters::default();
+		assert!(counters.count_set(moduleId_get("mail"), 3));
+		assert!(counters.count_set(moduleId_get("rss"), 4));
+		assert!(!counters.count_set(moduleId_get("mail"), 3));
+		assert_eq!(counters.total_get(), 7);
+
+		assert!(counters.count_set(moduleId_get("mail"), 0));
+		assert!(!counters.count_set(moduleId_get("mail"), 0));
+		assert_eq!(counters.total_get(), 4);
+
+		counters.module_remove(&moduleId_get("rss"));
+		assert_eq!(counters, UnreadCounters::default());
+	}
+
+	#[test]
+	fn unreadTitle_prefixesTheCappedTotal()
+	{
+		assert_eq!(unreadTitle_get("Web Home", 0), "Web Home");
+		assert_eq!(unreadTitle_get("Web Home", 5), "(5) Web Home");
+		assert_eq!(unreadTitle_get("Web Home", 250), "(99+) Web Home");
+		assert_eq!(unreadBadge_label(99), "99");
+	}
+}
@@ -1,7 +1,7 @@
 use crate::api::modules::components::{ModuleContent, ModuleID};
 use crate::front::modules::components::{
-	Backable, BoxFuture, Cache, Cacheable, FieldHelper, ModuleName, ModuleSizeContrainte,
-	RefreshTime,
+	Backable, RefreshFuture, Cache, Cacheable, FieldHelper, ModuleName, ModuleSizeContrainte,
+	RefreshOutcome, RefreshTime,
 };
 use crate::front::modules::module_actions::ModuleActionFn;
 use crate::front::utils::draw_title_if_present;
@@ -143,13 +143,13 @@ impl Backable for Weather
 		moduleActions: ModuleActionFn,
 		_moduleId: ModuleID,
 		_toaster: ToasterContext,
-	) -> Option<BoxFuture>
+	) -> Option<RefreshFuture>
 	{
 		let config = self.config.clone();
 		let refreshContent = self.weatherContent.clone();
 
 		return Some(Box::pin(async move {
-			sync_weather_api(config, refreshContent, moduleActions).await;
+			return sync_weather_api(config, refreshContent, moduleActions).await;
 		}));
 	}
 
@@ -346,17 +346,17 @@ async fn sync_weather_api(
 	config: ArcRwSignal<WeatherConfig>,
 	weatherContent: ArcRwSignal<Option<WeatherApiResult>>,
 	moduleActions: ModuleActionFn,
-)
+) -> RefreshOutcome
 {
 	if (!moduleActions.lifecycle_isActive())
 	{
-		return;
+		return RefreshOutcome::SUCCESS;
 	}
 	if let Some(lastContent) = (weatherContent.clone().get_untracked())
 	{
 		if (Cache::now() - lastContent.lastUpdate.get() < 30 * 1_000_000_000)
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
 	}
 
@@ -396,42 +396,42 @@ async fn sync_weather_api(
 		let Ok(window) = web_sys::window().ok_or("no window")
 		else
 		{
-			return;
+			return RefreshOutcome::FAILED;
 		};
 		let Ok(resp_value) = JsFuture::from(window.fetch_with_str(&url)).await
 		else
 		{
-			return;
+			return RefreshOutcome::FAILED;
 		};
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
 		let Ok(resp): Result<Response, _> = resp_value.dyn_into()
 		else
 		{
-			return;
+			return RefreshOutcome::FAILED;
 		};
 
 		// On récupère le body en texte (JSON)
 		let Ok(text_promise) = resp.text().map_err(|_| "text() failed")
 		else
 		{
-			return;
+			return RefreshOutcome::FAILED;
 		};
 		let Ok(text_js) = JsFuture::from(text_promise).await
 		else
 		{
-			return;
+			return RefreshOutcome::FAILED;
 		};
 		if (!moduleActions.lifecycle_isActive())
 		{
-			return;
+			return RefreshOutcome::SUCCESS;
 		}
 		let Some(text) = text_js.as_string()
 		else
 		{
-			return;
+			return RefreshOutcome::FAILED;
 		};
 		text
 	};
@@ -471,7 +471,7 @@ async fn sync_weather_api(
 		Err(e) =>
 		{
 			HWebTrace!("error : {:?}", e);
-			// TODO
+			return RefreshOutcome::FAILED;
 		}
 		_ =>
 		{}
@@ -479,11 +479,12 @@ async fn sync_weather_api(
 
 	if (!moduleActions.lifecycle_isActive())
 	{
-		return;
+		return RefreshOutcome::SUCCESS;
 	}
 	weatherContent.update(|content| {
 		*content = Some(weatherResult);
 	});
+	return RefreshOutcome::SUCCESS;
 }
 
 fn json_read_hourly(result: &mut WeatherApiResult, json: &Value)
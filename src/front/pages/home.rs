@@ -5,8 +5,10 @@ use crate::front::components::options_menu::OptionsMenu;
 use crate::front::modules::module_holder::{ModuleHolder, ModuleHolderEpoch};
 use crate::front::utils::all_front_enum::{AllFrontErrorEnum, AllFrontLoginEnum, AllFrontUIEnum};
 use crate::front::utils::dialog::{DialogActionStyle, DialogData, DialogManager};
+use crate::front::utils::shortcuts::{shortcutsHelp_open, shortcuts_listen};
 use crate::front::utils::toaster_helpers::{toastingErr, toastingSuccess};
 use crate::front::utils::translate::{Translate, TranslateText};
+use crate::front::utils::unread_badge::unreadBadge_listen;
 use crate::front::utils::users_data::{ClientCryptoContext, ClientState};
 use crate::{HWebTrace};
 use leptoaster::{expect_toaster, ToasterContext};
@@ -20,7 +22,7 @@ use leptos::{component, island, view, IntoView};
 use leptos_router::{hooks, NavigateOptions};
 use leptos::logging::log;
 use leptos::task::spawn_local;
-use leptos_use::use_interval_fn;
+use leptos_use::{use_document, use_event_listener, use_interval_fn};
 use strum::IntoEnumIterator;
 use crate::api::modules::components::ModuleID;
 use crate::front::modules::module_actions::ModuleActionFn;
@@ -120,6 +122,23 @@ pub fn Home() -> impl IntoView
 		lifecycleEpoch,
 	);
 
+	shortcuts_listen(
+		clientState.clone(),
+		dialogManager.clone(),
+		editMode,
+		moduleActions.clone(),
+		editModeValidateFn.clone(),
+		editModeCancelFn.clone(),
+	);
+	unreadBadge_listen(clientState.clone(), lifecycleEpoch);
+	// background tabs do not refresh modules, the due ones run when the page is visible again
+	let _ = use_event_listener(use_document(), leptos::ev::visibilitychange, move |_| {
+		ModuleHolder::page_visibility_apply(lifecycleEpoch);
+	});
+	let dialogManagerShortcuts = dialogManager.clone();
+	let clientStateShortcuts = clientState.clone();
+	let shortcutsHelpFn = move |_| shortcutsHelp_open(&dialogManagerShortcuts, clientStateShortcuts.shortcuts_get_untracked());
+
 	let editModeActivateFn = move |_| {
 
 		HWebTrace!("editModeActivateFn");
@@ -130,6 +149,19 @@ pub fn Home() -> impl IntoView
 
 	let editModeAddModuleFn = editMode_AddBlock(dialogManager.clone(), lifecycleEpoch);
 
+	// every edit session has its own undo/redo history
+	Effect::new(move || {
+		if (editMode.get())
+		{
+			ModuleHolder::history_begin(lifecycleEpoch);
+		}
+		else
+		{
+			ModuleHolder::history_end();
+		}
+	});
+	let layoutHistory = ModuleHolder::history_get();
+
 	// disconnect func
 	let toasterInner = toaster.clone();
 	let clientStateDisconnect = clientState.clone();
@@ -162,6 +194,8 @@ pub fn Home() -> impl IntoView
 						let editModeCancelFn = editModeCancelFn.clone();
 						let editModeActivateFn = editModeActivateFn.clone();
 						let editModeAddModuleFn = editModeAddModuleFn.clone();
+						let undoHistory = layoutHistory.clone();
+						let redoHistory = layoutHistory.clone();
 						if editMode.get()
 						{
 							view!{
@@ -170,11 +204,19 @@ pub fn Home() -> impl IntoView
 										<i class="iconoir-plus-circle" aria-hidden="true"></i>
 										<span class="visually_hidden"><TranslateText key="FRONTUI_HOME_ADD_ACTION"/></span>
 									</button>
-									<button type="button" class="icon_button icon_button--success" on:click=editModeValidateFn>
+									<button type="button" class="icon_button" disabled=move || !undoHistory.with(|history| history.canUndo()) on:click=move |_| {ModuleHolder::history_undo(lifecycleEpoch);}>
+										<i class="iconoir-undo" aria-hidden="true"></i>
+										<span class="visually_hidden"><TranslateText key="FRONTUI_HOME_UNDO_ACTION"/></span>
+									</button>
+									<button type="button" class="icon_button" disabled=move || !redoHistory.with(|history| history.canRedo()) on:click=move |_| {ModuleHolder::history_redo(lifecycleEpoch);}>
+										<i class="iconoir-redo" aria-hidden="true"></i>
+										<span class="visually_hidden"><TranslateText key="FRONTUI_HOME_REDO_ACTION"/></span>
+									</button>
+									<button type="button" class="icon_button icon_button--success" on:click=move |_| editModeValidateFn(())>
 										<i class="iconoir-check" aria-hidden="true"></i>
 										<span class="visually_hidden"><TranslateText key="FRONTUI_HOME_SAVE_ACTION"/></span>
 									</button>
-									<button type="button" class="icon_button icon_button--danger" on:click=editModeCancelFn>
+									<button type="button" class="icon_button icon_button--danger" on:click=move |_| editModeCancelFn(())>
 										<i class="iconoir-xmark" aria-hidden="true"></i>
 										<span class="visually_hidden"><TranslateText key="FRONTUI_HOME_CANCEL_ACTION"/></span>
 									</button>
@@ -191,6 +233,10 @@ pub fn Home() -> impl IntoView
 							}.into_any()
 						}
 					}}
+					<button type="button" class="icon_button" on:click=shortcutsHelpFn>
+						<i class="iconoir-keyboard" aria-hidden="true"></i>
+						<span class="visually_hidden"><TranslateText key="FRONTUI_SHORTCUTS_ACTION"/></span>
+					</button>
 					<OptionsMenu/>
 					<button type="button" class="icon_button icon_button--warning" on:click=disconnectFn>
 						<i class="iconoir-key" aria-hidden="true"></i>
@@ -235,7 +281,7 @@ fn editMode_cancel(
 	toasterInnerValidate: ToasterContext,
 	dialogManager: DialogManager,
 	lifecycleEpoch: ModuleHolderEpoch,
-) -> impl Fn(MouseEvent) + Clone
+) -> impl Fn(()) + Clone
 {
 	return move |_| {
 		let editModeInnerValidate = editModeInnerValidate.clone();
@@ -248,6 +294,7 @@ fn editMode_cancel(
 			.setOnValidate(move |_| {
 				let editModeInnerValidate = editModeInnerValidate.clone();
 				let toasterInnerValidate = toasterInnerValidate.clone();
+				ModuleHolder::history_rollback(lifecycleEpoch);
 				ModuleHolder::task_spawn(lifecycleEpoch, async move {
 					ModuleHolder::network_deferredCall(ModuleHolder::getSingleton(), lifecycleEpoch, toasterInnerValidate.clone(), |holder|ModuleHolder::network_modules_retrieve_caller(holder,false), Some(AllFrontUIEnum::HOME_CHANGE_CANCEL)).await;
 					if (ModuleHolder::lifecycle_isActive(lifecycleEpoch))
@@ -269,7 +316,7 @@ fn editMode_validate(
 	toasterInnerValidate: ToasterContext,
 	dialogManager: DialogManager,
 	lifecycleEpoch: ModuleHolderEpoch,
-) -> impl Fn(MouseEvent) + Clone
+) -> impl Fn(()) + Clone
 {
 	return move |_| {
 		let editModeInnerValidate = editModeInnerValidate.clone();
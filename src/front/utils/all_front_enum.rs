@@ -29,7 +29,7 @@ impl From<ModuleApiError> for AllFrontErrorEnum
 	}
 }
 
-#[derive(strum_macros::Display)]
+#[derive(Clone, Copy, strum_macros::Display)]
 #[strum(prefix = "FRONTUI_")]
 pub enum AllFrontUIEnum
 {
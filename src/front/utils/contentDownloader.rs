@@ -3,7 +3,7 @@ use js_sys::Uint8Array;
 use wasm_bindgen::JsCast;
 use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
 
-use crate::api::proxys::imap_components::Attachment;
+use crate::api::proxys::imap_components::{Attachment, ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES};
 
 struct AttachmentDownload
 {
@@ -14,7 +14,7 @@ struct AttachmentDownload
 
 impl AttachmentDownload
 {
-	const DATA_MAXIMUM_BYTES: usize = 16 * 1024 * 1024;
+	const DATA_MAXIMUM_BYTES: usize = ATTACHMENT_INDIVIDUAL_MAXIMUM_BYTES;
 	const FILENAME_MAXIMUM_CHARACTERS: usize = 255;
 	const MIME_MAXIMUM_BYTES: usize = 255;
 
@@ -35,18 +35,7 @@ impl AttachmentDownload
 
 	fn download(self) -> bool
 	{
-		let uint8Array = Uint8Array::from(self.data.as_slice());
-		let blobOptions = BlobPropertyBag::new();
-		blobOptions.set_type(&self.contentType);
-		let Ok(blob) = Blob::new_with_u8_array_sequence_and_options(
-			&js_sys::Array::of1(&uint8Array),
-			&blobOptions,
-		)
-		else
-		{
-			return false;
-		};
-		let Ok(url) = Url::create_object_url_with_blob(&blob)
+		let Some(url) = blobUrl_create(&self.data,&self.contentType)
 		else
 		{
 			return false;
@@ -123,6 +112,28 @@ impl AttachmentDownload
 	}
 }
 
+/// object url of the data, the caller revokes it once the url is not used anymore
+pub(crate) fn blobUrl_create(data: &[u8], contentType: &str) -> Option<String>
+{
+	if (data.len() > AttachmentDownload::DATA_MAXIMUM_BYTES)
+	{
+		return None;
+	}
+	let uint8Array = Uint8Array::from(data);
+	let blobOptions = BlobPropertyBag::new();
+	blobOptions.set_type(&AttachmentDownload::contentType_sanitize(contentType));
+	let blob = Blob::new_with_u8_array_sequence_and_options(
+		&js_sys::Array::of1(&uint8Array),
+		&blobOptions,
+	).ok()?;
+	return Url::create_object_url_with_blob(&blob).ok();
+}
+
+pub(crate) fn blobUrl_revoke(url: &str)
+{
+	let _ = Url::revoke_object_url(url);
+}
+
 pub fn download_attachment(attachment: Attachment) -> bool
 {
 	return AttachmentDownload::new(attachment)
@@ -3,7 +3,7 @@ use leptos::html::Div;
 use leptos::prelude::{AriaAttributes, ElementChild, GlobalAttributes, IntoAny, NodeRef, NodeRefAttribute};
 use leptos::prelude::OnAttribute;
 use leptos::prelude::{AnyView, ClassAttribute, Effect, Signal, Update};
-use leptos::prelude::{Get, GetUntracked, RwSignal, Set};
+use leptos::prelude::{Get, GetUntracked, RwSignal, Set, WithUntracked};
 use leptos::{component, view, IntoView};
 use leptos_use::{use_css_var, use_timeout_fn, UseTimeoutFnReturn};
 use std::sync::Arc;
@@ -346,6 +346,11 @@ impl DialogManager
 		self.dialog.set(Some(dialog));
 	}
 
+	pub(crate) fn isOpen_untracked(&self) -> bool
+	{
+		return self.dialog.with_untracked(|dialog| dialog.is_some());
+	}
+
 	pub(crate) fn clear(&self)
 	{
 		if let Some(dialog) = self.dialog.get_untracked()
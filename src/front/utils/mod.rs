@@ -10,6 +10,8 @@ pub mod dialog;
 pub mod toaster_helpers;
 pub mod all_front_enum;
 pub mod contentDownloader;
+pub mod shortcuts;
+pub(crate) mod unread_badge;
 mod external_url;
 
 pub(super) use external_url::SafeExternalUrl;
@@ -0,0 +1,602 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+use leptos::ev::KeyboardEvent;
+use leptos::prelude::{ClassAttribute, CollectView, ElementChild, GetUntracked, IntoAny, RwSignal, Set, WithUntracked};
+use leptos::view;
+use leptos_use::{use_event_listener, use_window};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+#[cfg(feature="hydrate")]
+use wasm_bindgen::JsCast;
+use crate::api::modules::components::ModuleID;
+use crate::front::modules::module_actions::ModuleActionFn;
+use crate::front::modules::module_holder::ModuleHolder;
+#[cfg(feature="hydrate")]
+use crate::front::modules::module_positions::moduleElement_id;
+use crate::front::utils::dialog::{DialogData, DialogManager};
+use crate::front::utils::translate::TranslateText;
+use crate::front::utils::users_data::ClientState;
+
+/// distance in pixels of one keyboard move or resize step
+const MODULE_KEYBOARD_STEP: i32 = 10;
+
+/// every action that can be bound to a keyboard shortcut
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, strum_macros::Display, strum_macros::EnumIter, strum_macros::EnumString)]
+pub(crate) enum ShortcutAction
+{
+	HELP,
+	FOCUS_NEXT,
+	FOCUS_PREVIOUS,
+	EDIT_MODE_ENTER,
+	EDIT_MODE_SAVE,
+	EDIT_MODE_CANCEL,
+	UNDO,
+	REDO,
+	MODULE_MOVE_LEFT,
+	MODULE_MOVE_RIGHT,
+	MODULE_MOVE_UP,
+	MODULE_MOVE_DOWN,
+	MODULE_SHRINK_WIDTH,
+	MODULE_GROW_WIDTH,
+	MODULE_SHRINK_HEIGHT,
+	MODULE_GROW_HEIGHT,
+	MODULE_REFRESH,
+	MODULE_SETTINGS,
+}
+
+impl ShortcutAction
+{
+	pub(crate) fn translateKey_get(&self) -> String
+	{
+		return format!("FRONTUI_SHORTCUT_{}", self);
+	}
+
+	fn binding_default(&self) -> KeyCombo
+	{
+		let value = match self
+		{
+			Self::HELP => "?",
+			Self::FOCUS_NEXT => "J",
+			Self::FOCUS_PREVIOUS => "K",
+			Self::EDIT_MODE_ENTER => "E",
+			Self::EDIT_MODE_SAVE => "Ctrl+Enter",
+			Self::EDIT_MODE_CANCEL => "Escape",
+			Self::UNDO => "Ctrl+Z",
+			Self::REDO => "Ctrl+Shift+Z",
+			Self::MODULE_MOVE_LEFT => "ArrowLeft",
+			Self::MODULE_MOVE_RIGHT => "ArrowRight",
+			Self::MODULE_MOVE_UP => "ArrowUp",
+			Self::MODULE_MOVE_DOWN => "ArrowDown",
+			Self::MODULE_SHRINK_WIDTH => "Shift+ArrowLeft",
+			Self::MODULE_GROW_WIDTH => "Shift+ArrowRight",
+			Self::MODULE_SHRINK_HEIGHT => "Shift+ArrowUp",
+			Self::MODULE_GROW_HEIGHT => "Shift+ArrowDown",
+			Self::MODULE_REFRESH => "R",
+			Self::MODULE_SETTINGS => "S",
+		};
+		return KeyCombo::parse(value).unwrap_or_default();
+	}
+
+	/// move or resize delta applied to the focused module
+	fn delta_get(&self) -> Option<([i32; 2], bool)>
+	{
+		return match self
+		{
+			Self::MODULE_MOVE_LEFT => Some(([-MODULE_KEYBOARD_STEP, 0], false)),
+			Self::MODULE_MOVE_RIGHT => Some(([MODULE_KEYBOARD_STEP, 0], false)),
+			Self::MODULE_MOVE_UP => Some(([0, -MODULE_KEYBOARD_STEP], false)),
+			Self::MODULE_MOVE_DOWN => Some(([0, MODULE_KEYBOARD_STEP], false)),
+			Self::MODULE_SHRINK_WIDTH => Some(([-MODULE_KEYBOARD_STEP, 0], true)),
+			Self::MODULE_GROW_WIDTH => Some(([MODULE_KEYBOARD_STEP, 0], true)),
+			Self::MODULE_SHRINK_HEIGHT => Some(([0, -MODULE_KEYBOARD_STEP], true)),
+			Self::MODULE_GROW_HEIGHT => Some(([0, MODULE_KEYBOARD_STEP], true)),
+			_ => None,
+		};
+	}
+}
+
+/// a key with its modifiers, written like "Ctrl+Shift+Z".
+/// Meta (command on macOS) is read as Ctrl.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub(crate) struct KeyCombo
+{
+	ctrl: bool,
+	alt: bool,
+	shift: bool,
+	key: String,
+}
+
+impl KeyCombo
+{
+	const KEY_MAXIMUM_LENGTH: usize = 32;
+
+	fn new(ctrl: bool, alt: bool, shift: bool, key: &str) -> Option<Self>
+	{
+		let key = match key
+		{
+			" " => "Space".to_string(),
+			key if key.chars().count() == 1 => key.to_uppercase(),
+			key => key.to_string(),
+		};
+		if (key.is_empty()
+			|| key.len() > Self::KEY_MAXIMUM_LENGTH
+			|| key.chars().any(|c| c.is_whitespace() || c.is_control())
+			|| Self::key_isModifier(&key))
+		{
+			return None;
+		}
+		// a symbol already depends on shift, "?" is Shift+, on some layouts and Shift+/ on others
+		let isSymbol = key.chars().count() == 1 && !key.chars().any(|c| c.is_alphanumeric());
+		return Some(Self {
+			ctrl,
+			alt,
+			shift: shift && !isSymbol,
+			key,
+		});
+	}
+
+	fn key_isModifier(key: &str) -> bool
+	{
+		return ["Control", "Shift", "Alt", "AltGraph", "Meta", "OS", "CapsLock", "Dead", "Unidentified", "Process"].contains(&key);
+	}
+
+	pub(crate) fn parse(value: &str) -> Option<Self>
+	{
+		let (modifiers, key) = if (value == "+")
+		{
+			("", "+")
+		}
+		else if let Some(modifiers) = value.strip_suffix("++")
+		{
+			(modifiers, "+")
+		}
+		else
+		{
+			value.rsplit_once('+').unwrap_or(("", value))
+		};
+
+		let (mut ctrl, mut alt, mut shift) = (false, false, false);
+		for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty())
+		{
+			match modifier
+			{
+				"Ctrl" => ctrl = true,
+				"Alt" => alt = true,
+				"Shift" => shift = true,
+				_ => return None,
+			}
+		}
+		return Self::new(ctrl, alt, shift, key);
+	}
+
+	/// return None while only a modifier key is pressed
+	pub(crate) fn fromEvent(event: &KeyboardEvent) -> Option<Self>
+	{
+		return Self::new(event.ctrl_key() || event.meta_key(), event.alt_key(), event.shift_key(), &event.key());
+	}
+}
+
+impl fmt::Display for KeyCombo
+{
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		if (self.ctrl)
+		{
+			formatter.write_str("Ctrl+")?;
+		}
+		if (self.alt)
+		{
+			formatter.write_str("Alt+")?;
+		}
+		if (self.shift)
+		{
+			formatter.write_str("Shift+")?;
+		}
+		return formatter.write_str(&self.key);
+	}
+}
+
+/// shortcuts of an account, only the bindings changed by the user are stored
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, String>", into = "BTreeMap<String, String>")]
+pub(crate) struct KeyboardShortcuts
+{
+	overrides: BTreeMap<ShortcutAction, KeyCombo>,
+}
+
+impl KeyboardShortcuts
+{
+	pub(crate) fn binding_get(&self, action: ShortcutAction) -> KeyCombo
+	{
+		return self.overrides.get(&action).cloned().unwrap_or_else(|| action.binding_default());
+	}
+
+	pub(crate) fn action_find(&self, combo: &KeyCombo) -> Option<ShortcutAction>
+	{
+		return ShortcutAction::iter().find(|action| &self.binding_get(*action) == combo);
+	}
+
+	/// bind a combination to an action, return the action already using it
+	pub(crate) fn binding_set(&mut self, action: ShortcutAction, combo: KeyCombo) -> Result<(), ShortcutAction>
+	{
+		if let Some(conflict) = self.action_find(&combo) && conflict != action
+		{
+			return Err(conflict);
+		}
+		if (combo == action.binding_default())
+		{
+			self.overrides.remove(&action);
+		}
+		else
+		{
+			self.overrides.insert(action, combo);
+		}
+		return Ok(());
+	}
+
+	pub(crate) fn binding_reset(&mut self, action: ShortcutAction) -> Result<(), ShortcutAction>
+	{
+		return self.binding_set(action, action.binding_default());
+	}
+
+	pub(crate) fn isDefault(&self) -> bool
+	{
+		return self.overrides.is_empty();
+	}
+
+	/// overridden actions sharing a combination with another action
+	fn conflicts_get(&self) -> Option<Vec<ShortcutAction>>
+	{
+		let mut used: HashMap<KeyCombo, Vec<ShortcutAction>> = HashMap::new();
+		for action in ShortcutAction::iter()
+		{
+			used.entry(self.binding_get(action)).or_default().push(action);
+		}
+		let conflicts = used.into_values()
+			.filter(|actions| actions.len() > 1)
+			.flatten()
+			.filter(|action| self.overrides.contains_key(action))
+			.collect::<Vec<_>>();
+		return (!conflicts.is_empty()).then_some(conflicts);
+	}
+}
+
+impl From<BTreeMap<String, String>> for KeyboardShortcuts
+{
+	/// unknown actions and invalid combinations are dropped, the overrides are checked together so swapped bindings survive,
+	/// the actions still sharing a combination go back to their default
+	fn from(raw: BTreeMap<String, String>) -> Self
+	{
+		let mut shortcuts = Self::default();
+		for (action, combo) in raw
+		{
+			let (Ok(action), Some(combo)) = (ShortcutAction::from_str(&action), KeyCombo::parse(&combo)) else {continue};
+			if (combo != action.binding_default())
+			{
+				shortcuts.overrides.insert(action, combo);
+			}
+		}
+		while let Some(conflicts) = shortcuts.conflicts_get()
+		{
+			shortcuts.overrides.retain(|action, _| !conflicts.contains(action));
+		}
+		return shortcuts;
+	}
+}
+
+impl From<KeyboardShortcuts> for BTreeMap<String, String>
+{
+	fn from(shortcuts: KeyboardShortcuts) -> Self
+	{
+		return shortcuts.overrides.into_iter()
+			.map(|(action, combo)| (action.to_string(), combo.to_string()))
+			.collect();
+	}
+}
+
+/// next module to focus, in blocks_view order, wrapping around
+fn focus_cycle(order: &[ModuleID], current: Option<&ModuleID>, forward: bool) -> Option<ModuleID>
+{
+	if (order.is_empty())
+	{
+		return None;
+	}
+	let Some(index) = current.and_then(|current| order.iter().position(|moduleId| moduleId == current))
+	else
+	{
+		return if (forward) {order.first().cloned()} else {order.last().cloned()};
+	};
+	let next = if (forward) {(index + 1) % order.len()} else {(index + order.len() - 1) % order.len()};
+	return order.get(next).cloned();
+}
+
+/// listen to the dashboard shortcuts, ignored while a dialog is open or while typing
+pub(crate) fn shortcuts_listen(
+	clientState: ClientState,
+	dialogManager: DialogManager,
+	editMode: RwSignal<bool>,
+	moduleActions: ModuleActionFn,
+	editModeValidate: impl Fn(()) + 'static,
+	editModeCancel: impl Fn(()) + 'static,
+)
+{
+	let _ = use_event_listener(use_window(), leptos::ev::keydown, move |event: KeyboardEvent| {
+		if (event.default_prevented() || (event.repeat() && !event.key().starts_with("Arrow")) || dialogManager.isOpen_untracked() || event_isTyping(&event))
+		{
+			return;
+		}
+		let Some(combo) = KeyCombo::fromEvent(&event) else {return};
+		let shortcuts = clientState.shortcuts_get_untracked();
+		let Some(action) = shortcuts.action_find(&combo) else {return};
+
+		let handled = match action
+		{
+			ShortcutAction::HELP => {
+				shortcutsHelp_open(&dialogManager, shortcuts);
+				true
+			},
+			ShortcutAction::FOCUS_NEXT | ShortcutAction::FOCUS_PREVIOUS => {
+				let order = ModuleHolder::getSingleton().with_untracked(|holder| holder.blocks_view())
+					.into_iter()
+					.map(|(moduleId, _)| moduleId)
+					.collect::<Vec<_>>();
+				let next = focus_cycle(&order, moduleFocused_get().as_ref(), action == ShortcutAction::FOCUS_NEXT);
+				next.inspect(|moduleId| moduleFocus_set(moduleId)).is_some()
+			},
+			ShortcutAction::EDIT_MODE_ENTER => {
+				let mustEnter = !editMode.get_untracked();
+				if (mustEnter)
+				{
+					editMode.set(true);
+				}
+				mustEnter
+			},
+			ShortcutAction::EDIT_MODE_SAVE | ShortcutAction::EDIT_MODE_CANCEL => {
+				let isEditing = editMode.get_untracked();
+				if (isEditing && action == ShortcutAction::EDIT_MODE_SAVE)
+				{
+					editModeValidate(());
+				}
+				else if (isEditing)
+				{
+					editModeCancel(());
+				}
+				isEditing
+			},
+			ShortcutAction::UNDO | ShortcutAction::REDO => {
+				let isEditing = editMode.get_untracked();
+				if (isEditing && action == ShortcutAction::UNDO)
+				{
+					moduleActions.layout_undo();
+				}
+				else if (isEditing)
+				{
+					moduleActions.layout_redo();
+				}
+				isEditing
+			},
+			ShortcutAction::MODULE_REFRESH => {
+				let focused = moduleFocused_get();
+				focused.clone().map(|moduleId| (moduleActions.refreshFn)(moduleId)).is_some()
+			},
+			ShortcutAction::MODULE_SETTINGS => {
+				let focused = moduleFocused_get();
+				if let Some(moduleId) = &focused
+				{
+					editMode.set(true);
+					moduleSettings_focus(moduleId.clone());
+				}
+				focused.is_some()
+			},
+			_ => {
+				let (Some((delta, isResize)), Some(moduleId)) = (action.delta_get(), moduleFocused_get()) else {return};
+				if (!editMode.get_untracked())
+				{
+					return;
+				}
+				let module = ModuleHolder::getSingleton().with_untracked(|holder| holder.blocks_get().get(&moduleId).cloned());
+				// holding an arrow key is a single undo step
+				if (module.is_some() && !event.repeat())
+				{
+					ModuleHolder::history_record();
+				}
+				module.map(|module| module.with_untracked(|module| {
+					if (isResize) {module.keyboard_resize(delta)} else {module.keyboard_move(delta)}
+				})).is_some()
+			},
+		};
+		if (handled)
+		{
+			event.prevent_default();
+		}
+	});
+}
+
+pub(crate) fn shortcutsHelp_open(dialogManager: &DialogManager, shortcuts: KeyboardShortcuts)
+{
+	let dialogContent = DialogData::new()
+		.setTitle("FRONTUI_SHORTCUTS_TITLE")
+		.setBody(move || {
+			let rows = ShortcutAction::iter().map(|action| view!{
+				<div>
+					<dt><TranslateText key={action.translateKey_get()}/></dt>
+					<dd><kbd>{shortcuts.binding_get(action).to_string()}</kbd></dd>
+				</div>
+			}).collect_view();
+			view!{
+				<p class="shortcuts_help_notice"><TranslateText key="FRONTUI_SHORTCUTS_HELP"/></p>
+				<dl class="shortcuts_help">{rows}</dl>
+			}.into_any()
+		})
+		.setIsLarger(true)
+		.setButtonValidateTitle(None::<String>);
+	dialogManager.open(dialogContent);
+}
+
+#[cfg(feature="hydrate")]
+fn event_isTyping(event: &KeyboardEvent) -> bool
+{
+	let Some(element) = event.target().and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok()) else {return false};
+	return element.is_content_editable() || ["INPUT", "TEXTAREA", "SELECT"].contains(&element.tag_name().as_str());
+}
+
+#[cfg(not(feature="hydrate"))]
+fn event_isTyping(_: &KeyboardEvent) -> bool
+{
+	return false;
+}
+
+/// module holding the keyboard focus (the module itself or one of its controls)
+#[cfg(feature="hydrate")]
+fn moduleFocused_get() -> Option<ModuleID>
+{
+	let activeElement = web_sys::window()?.document()?.active_element()?;
+	let moduleElement = activeElement.closest("[data-module-id]").ok()??;
+	return moduleElement.get_attribute("data-module-id").map(|id| ModuleID {id});
+}
+
+#[cfg(not(feature="hydrate"))]
+fn moduleFocused_get() -> Option<ModuleID>
+{
+	return None;
+}
+
+#[cfg(feature="hydrate")]
+fn moduleFocus_set(moduleId: &ModuleID)
+{
+	let Some(document) = web_sys::window().and_then(|window| window.document()) else {return};
+	let Some(element) = document.get_element_by_id(&moduleElement_id(moduleId)) else {return};
+	if let Ok(element) = element.dyn_into::<web_sys::HtmlElement>()
+	{
+		let _ = element.focus();
+	}
+}
+
+#[cfg(not(feature="hydrate"))]
+fn moduleFocus_set(_: &ModuleID)
+{
+}
+
+/// focus the first settings field of a module, once the edit mode view is drawn
+#[cfg(feature="hydrate")]
+fn moduleSettings_focus(moduleId: ModuleID)
+{
+	leptos::leptos_dom::helpers::request_animation_frame(move || {
+		let Some(document) = web_sys::window().and_then(|window| window.document()) else {return};
+		let Some(moduleElement) = document.get_element_by_id(&moduleElement_id(&moduleId)) else {return};
+		let target = moduleElement.query_selector(".module_content input, .module_content select, .module_content textarea").ok().flatten()
+			.unwrap_or(moduleElement);
+		if let Ok(target) = target.dyn_into::<web_sys::HtmlElement>()
+		{
+			let _ = target.focus();
+		}
+	});
+}
+
+#[cfg(not(feature="hydrate"))]
+fn moduleSettings_focus(_: ModuleID)
+{
+}
+
+#[cfg(test)]
+mod tests
+{
+	use std::collections::BTreeMap;
+	use crate::api::modules::components::ModuleID;
+	use super::{focus_cycle, KeyCombo, KeyboardShortcuts, ShortcutAction};
+
+	#[test]
+	fn keyCombo_parsesAndFormatsModifiersInFixedOrder()
+	{
+		let combo = KeyCombo::parse("Shift+Ctrl+z").unwrap();
+		assert_eq!(combo.to_string(), "Ctrl+Shift+Z");
+		assert_eq!(KeyCombo::parse("Ctrl++").unwrap().to_string(), "Ctrl++");
+		assert_eq!(KeyCombo::parse("ArrowLeft").unwrap().to_string(), "ArrowLeft");
+		assert_eq!(KeyCombo::new(false, false, false, " ").unwrap().to_string(), "Space");
+
+		assert!(KeyCombo::parse("").is_none());
+		assert!(KeyCombo::parse("Ctrl+").is_none());
+		assert!(KeyCombo::parse("Hyper+A").is_none());
+		assert!(KeyCombo::parse("Shift").is_none());
+		assert!(KeyCombo::parse(&"A".repeat(64)).is_none());
+	}
+
+	#[test]
+	fn keyCombo_ignoresShiftOnSymbols()
+	{
+		assert_eq!(KeyCombo::new(false, false, true, "?"), KeyCombo::parse("?"));
+		assert_ne!(KeyCombo::new(false, false, true, "j"), KeyCombo::parse("J"));
+	}
+
+	#[test]
+	fn keyboardShortcuts_rejectConflictsAndStoreOnlyChanges()
+	{
+		let mut shortcuts = KeyboardShortcuts::default();
+		assert_eq!(shortcuts.action_find(&KeyCombo::parse("J").unwrap()), Some(ShortcutAction::FOCUS_NEXT));
+
+		assert_eq!(
+			shortcuts.binding_set(ShortcutAction::MODULE_REFRESH, KeyCombo::parse("J").unwrap()),
+			Err(ShortcutAction::FOCUS_NEXT),
+		);
+		assert!(shortcuts.binding_set(ShortcutAction::MODULE_REFRESH, KeyCombo::parse("Alt+R").unwrap()).is_ok());
+		assert_eq!(shortcuts.action_find(&KeyCombo::parse("Alt+R").unwrap()), Some(ShortcutAction::MODULE_REFRESH));
+		assert_eq!(shortcuts.action_find(&KeyCombo::parse("R").unwrap()), None);
+		assert!(!shortcuts.isDefault());
+
+		assert!(shortcuts.binding_reset(ShortcutAction::MODULE_REFRESH).is_ok());
+		assert!(shortcuts.isDefault());
+	}
+
+	#[test]
+	fn keyboardShortcuts_serializationDropsInvalidEntries()
+	{
+		let mut shortcuts = KeyboardShortcuts::default();
+		shortcuts.binding_set(ShortcutAction::HELP, KeyCombo::parse("F1").unwrap()).unwrap();
+		let serialized = serde_json::to_string(&shortcuts).unwrap();
+		assert_eq!(serialized, r#"{"HELP":"F1"}"#);
+		assert_eq!(serde_json::from_str::<KeyboardShortcuts>(&serialized).unwrap(), shortcuts);
+
+		let raw = BTreeMap::from([
+			("UNKNOWN".to_string(), "F2".to_string()),
+			("MODULE_REFRESH".to_string(), "Hyper+R".to_string()),
+			("MODULE_SETTINGS".to_string(), "J".to_string()),
+		]);
+		assert!(KeyboardShortcuts::from(raw).isDefault());
+	}
+
+	#[test]
+	fn keyboardShortcuts_swappedBindingsSurviveARoundTrip()
+	{
+		let mut shortcuts = KeyboardShortcuts::default();
+		shortcuts.binding_set(ShortcutAction::FOCUS_NEXT, KeyCombo::parse("X").unwrap()).unwrap();
+		shortcuts.binding_set(ShortcutAction::FOCUS_PREVIOUS, KeyCombo::parse("J").unwrap()).unwrap();
+		shortcuts.binding_set(ShortcutAction::FOCUS_NEXT, KeyCombo::parse("K").unwrap()).unwrap();
+
+		let serialized = serde_json::to_string(&shortcuts).unwrap();
+		assert_eq!(serialized, r#"{"FOCUS_NEXT":"K","FOCUS_PREVIOUS":"J"}"#);
+		assert_eq!(serde_json::from_str::<KeyboardShortcuts>(&serialized).unwrap(), shortcuts);
+
+		let raw = BTreeMap::from([
+			("FOCUS_NEXT".to_string(), "K".to_string()),
+			("MODULE_REFRESH".to_string(), "K".to_string()),
+		]);
+		let shortcuts = KeyboardShortcuts::from(raw);
+		assert_eq!(shortcuts.binding_get(ShortcutAction::FOCUS_NEXT), KeyCombo::parse("J").unwrap());
+		assert_eq!(shortcuts.binding_get(ShortcutAction::MODULE_REFRESH), KeyCombo::parse("R").unwrap());
+		assert!(shortcuts.isDefault());
+	}
+
+	#[test]
+	fn focusCycle_followsOrderAndWraps()
+	{
+		let order = ["a","b","c"].into_iter().map(|id| ModuleID {id: id.to_string()}).collect::<Vec<_>>();
+
+		assert_eq!(focus_cycle(&order, None, true), Some(order[0].clone()));
+		assert_eq!(focus_cycle(&order, None, false), Some(order[2].clone()));
+		assert_eq!(focus_cycle(&order, Some(&order[2]), true), Some(order[0].clone()));
+		assert_eq!(focus_cycle(&order, Some(&order[0]), false), Some(order[2].clone()));
+		assert_eq!(focus_cycle(&order, Some(&ModuleID {id: "gone".to_string()}), true), Some(order[0].clone()));
+		assert_eq!(focus_cycle(&[], None, true), None);
+	}
+}
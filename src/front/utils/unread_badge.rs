@@ -0,0 +1,177 @@
+#[cfg(feature="hydrate")]
+use std::collections::HashMap;
+#[cfg(feature="hydrate")]
+use std::sync::Arc;
+#[cfg(feature="hydrate")]
+use leptos::prelude::{on_cleanup, Effect, With};
+#[cfg(feature="hydrate")]
+use wasm_bindgen::closure::Closure;
+#[cfg(feature="hydrate")]
+use wasm_bindgen::JsCast;
+#[cfg(feature="hydrate")]
+use crate::front::modules::module_holder::ModuleHolder;
+use crate::front::modules::module_holder::ModuleHolderEpoch;
+#[cfg(feature="hydrate")]
+use crate::front::modules::refresh_scheduler::page_isHidden;
+#[cfg(feature="hydrate")]
+use crate::front::modules::unread_counters::{unreadBadge_label, unreadTitle_get};
+#[cfg(feature="hydrate")]
+use crate::front::utils::fluent::FluentManager::FluentManager;
+use crate::front::utils::users_data::ClientState;
+
+pub(crate) const DOCUMENT_TITLE: &str = "Web Home";
+pub(crate) const FAVICON_PATH: &str = "/favicon.png";
+
+#[cfg(feature="hydrate")]
+const FAVICON_SIZE: u32 = 64;
+#[cfg(feature="hydrate")]
+const BADGE_COLOR: &str = "#d32f2f";
+// only one notification stays on screen, a new one replaces it
+#[cfg(feature="hydrate")]
+const NOTIFICATION_TAG: &str = "web-home-unread";
+
+/// draw the unread total of the modules in the document title and on the favicon,
+/// and raise a browser notification when it grows while the page is in the background.
+/// The notifications keep a slower refresh of the modules running in the background
+#[cfg(feature="hydrate")]
+pub(crate) fn unreadBadge_listen(clientState: ClientState, epoch: ModuleHolderEpoch)
+{
+	let unread = ModuleHolder::unread_get();
+	let clientStateRefresh = clientState.clone();
+	Effect::new(move || ModuleHolder::backgroundRefresh_set(epoch, clientStateRefresh.notifications_get()));
+	Effect::new(move |previous: Option<usize>| {
+		let total = unread.with(|unread| unread.total_get());
+		unreadTitle_apply(total);
+		unreadFavicon_apply(total);
+		if let Some(previous) = previous
+			&& total > previous
+			&& page_isHidden()
+			&& clientState.notifications_get()
+		{
+			unreadNotification_show(epoch, clientState.lang_get_untracked(), total);
+		}
+		return total;
+	});
+	on_cleanup(|| {
+		unreadTitle_apply(0);
+		unreadFavicon_apply(0);
+	});
+}
+
+#[cfg(not(feature="hydrate"))]
+pub(crate) fn unreadBadge_listen(_: ClientState, _: ModuleHolderEpoch)
+{
+}
+
+/// ask the browser once, a refused permission leaves the preference without effect
+#[cfg(feature="hydrate")]
+pub(crate) fn unreadNotifications_permissionRequest()
+{
+	if (notifications_isSupported() && web_sys::Notification::permission() == web_sys::NotificationPermission::Default)
+	{
+		let _ = web_sys::Notification::request_permission();
+	}
+}
+
+#[cfg(not(feature="hydrate"))]
+pub(crate) fn unreadNotifications_permissionRequest()
+{
+}
+
+#[cfg(feature="hydrate")]
+fn notifications_isSupported() -> bool
+{
+	// missing outside of secure contexts and on some mobile browsers
+	return web_sys::window()
+		.is_some_and(|window| js_sys::Reflect::has(&window, &"Notification".into()).unwrap_or(false));
+}
+
+#[cfg(feature="hydrate")]
+fn unreadNotification_show(epoch: ModuleHolderEpoch, lang: String, total: usize)
+{
+	if (!notifications_isSupported() || web_sys::Notification::permission() != web_sys::NotificationPermission::Granted)
+	{
+		return;
+	}
+	ModuleHolder::task_spawn(epoch, async move {
+		let params = Arc::new(HashMap::from([("count".to_string(), total.to_string())]));
+		let body = FluentManager::singleton().translate(lang, "FRONTUI_UNREAD_NOTIFICATION", params).await;
+		let options = web_sys::NotificationOptions::new();
+		options.set_body(&body);
+		options.set_icon(FAVICON_PATH);
+		options.set_tag(NOTIFICATION_TAG);
+		let _ = web_sys::Notification::new_with_options(DOCUMENT_TITLE, &options);
+	});
+}
+
+#[cfg(feature="hydrate")]
+fn unreadTitle_apply(total: usize)
+{
+	if let Some(document) = web_sys::window().and_then(|window| window.document())
+	{
+		document.set_title(&unreadTitle_get(DOCUMENT_TITLE, total));
+	}
+}
+
+#[cfg(feature="hydrate")]
+fn unreadFavicon_apply(total: usize)
+{
+	let Some(link) = web_sys::window()
+		.and_then(|window| window.document())
+		.and_then(|document| document.query_selector("link[rel~='icon']").ok().flatten())
+		.and_then(|element| element.dyn_into::<web_sys::HtmlLinkElement>().ok())
+		else {return};
+	if (total == 0)
+	{
+		let _ = link.remove_attribute("data-unread");
+		link.set_href(FAVICON_PATH);
+		return;
+	}
+	let label = unreadBadge_label(total);
+	// the icon loads asynchronously, a newer total drawn in the meantime wins
+	let _ = link.set_attribute("data-unread", &label);
+	let Ok(image) = web_sys::HtmlImageElement::new() else {return};
+	let loadedImage = image.clone();
+	let onload = Closure::once_into_js(move || {
+		if (link.get_attribute("data-unread").as_deref() != Some(label.as_str()))
+		{
+			return;
+		}
+		if let Some(href) = unreadFavicon_draw(&loadedImage, &label)
+		{
+			link.set_href(&href);
+		}
+	});
+	image.set_onload(Some(onload.unchecked_ref()));
+	image.set_src(FAVICON_PATH);
+}
+
+/// the favicon with the unread count in a pill on its top right corner, as a data url
+#[cfg(feature="hydrate")]
+fn unreadFavicon_draw(image: &web_sys::HtmlImageElement, label: &str) -> Option<String>
+{
+	let document = web_sys::window()?.document()?;
+	let canvas = document.create_element("canvas").ok()?.dyn_into::<web_sys::HtmlCanvasElement>().ok()?;
+	canvas.set_width(FAVICON_SIZE);
+	canvas.set_height(FAVICON_SIZE);
+	let context = canvas.get_context("2d").ok()??.dyn_into::<web_sys::CanvasRenderingContext2d>().ok()?;
+	let size = f64::from(FAVICON_SIZE);
+	context.draw_image_with_html_image_element_and_dw_and_dh(image, 0.0, 0.0, size, size).ok()?;
+
+	let radius = size * 0.3;
+	let width = (radius * 2.0).max(radius * 0.8 * label.len() as f64 + radius).min(size);
+	let left = size - width;
+	context.begin_path();
+	context.arc(left + radius, radius, radius, std::f64::consts::FRAC_PI_2, 3.0 * std::f64::consts::FRAC_PI_2).ok()?;
+	context.arc(size - radius, radius, radius, -std::f64::consts::FRAC_PI_2, std::f64::consts::FRAC_PI_2).ok()?;
+	context.close_path();
+	context.set_fill_style_str(BADGE_COLOR);
+	context.fill();
+
+	context.set_fill_style_str("#ffffff");
+	context.set_font(&format!("bold {}px sans-serif", (radius * 1.4).round()));
+	context.set_text_align("center");
+	context.set_text_baseline("middle");
+	context.fill_text_with_max_width(label, left + width / 2.0, radius * 1.05, width - radius * 0.4).ok()?;
+	return canvas.to_data_url().ok();
+}
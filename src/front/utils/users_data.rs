@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::api::login::{API_user_login, API_user_logout, API_user_passwordRotation_finalize, API_user_passwordRotation_prepare, API_user_preferences_get, API_user_preferences_set, API_user_salt, API_user_sign};
 use crate::api::login::components::{AccountPreferencesError, PasswordRotationContent, PasswordRotationError, PasswordRotationFinalize};
 use crate::front::utils::all_front_enum::AllFrontLoginEnum;
+use crate::front::utils::shortcuts::{KeyCombo, KeyboardShortcuts, ShortcutAction};
 use crate::global_security::{generate_salt_raw, hash};
 
 const COOKIE_MAX_AGE: i64 = 24 * 3600 * 1000;
@@ -451,6 +452,11 @@ struct UserPreferences
 	lang: String,
 	#[serde(default)]
 	primaryHue: PrimaryHue,
+	#[serde(default, skip_serializing_if = "KeyboardShortcuts::isDefault")]
+	shortcuts: KeyboardShortcuts,
+	// browser notifications when unread items arrive while the page is in the background
+	#[serde(default, skip_serializing_if = "UserPreferences::notifications_isDefault")]
+	notifications: bool,
 	connected: bool,
 	updateVal: u128,
 }
@@ -567,6 +573,8 @@ struct PreferencesPreview
 {
 	lang: String,
 	primaryHue: PrimaryHue,
+	shortcuts: KeyboardShortcuts,
+	notifications: bool,
 }
 
 impl PreferencesPreview
@@ -576,6 +584,8 @@ impl PreferencesPreview
 		return Self {
 			lang: UserPreferences::lang_normalized(&preferences.lang),
 			primaryHue: preferences.primaryHue,
+			shortcuts: preferences.shortcuts.clone(),
+			notifications: preferences.notifications,
 		};
 	}
 
@@ -600,6 +610,11 @@ struct AccountPreferences
 	version: u8,
 	lang: String,
 	primaryHue: PrimaryHue,
+	// added without a version change, older content has no shortcuts
+	#[serde(default)]
+	shortcuts: KeyboardShortcuts,
+	#[serde(default)]
+	notifications: bool,
 }
 
 impl AccountPreferences
@@ -612,6 +627,8 @@ impl AccountPreferences
 			version: Self::VERSION,
 			lang: UserPreferences::lang_normalized(&preferences.lang),
 			primaryHue: preferences.primaryHue,
+			shortcuts: preferences.shortcuts.clone(),
+			notifications: preferences.notifications,
 		};
 	}
 
@@ -621,6 +638,8 @@ impl AccountPreferences
 			version: Self::VERSION,
 			lang: UserPreferences::lang_normalized(&preview.lang),
 			primaryHue: preview.primaryHue,
+			shortcuts: preview.shortcuts.clone(),
+			notifications: preview.notifications,
 		};
 	}
 
@@ -657,6 +676,11 @@ impl UserPreferences
 		self.lang = Self::lang_normalized(&lang.into());
 	}
 
+	fn notifications_isDefault(notifications: &bool) -> bool
+	{
+		return !notifications;
+	}
+
 	fn lang_supported(lang: &str) -> Option<String>
 	{
 		let lang = lang.split('-').next().unwrap_or("EN").to_uppercase();
@@ -701,6 +725,8 @@ impl Default for UserPreferences
 		return Self {
 			lang: "EN".to_string(),
 			primaryHue: PrimaryHue::default(),
+			shortcuts: KeyboardShortcuts::default(),
+			notifications: false,
 			connected: false,
 			updateVal: 0,
 		};
@@ -744,6 +770,8 @@ impl LegacyUserData
 		let preferences = UserPreferences {
 			lang: self.lang,
 			primaryHue: PrimaryHue::default(),
+			shortcuts: KeyboardShortcuts::default(),
+			notifications: false,
 			connected: crypto.is_some(),
 			updateVal: self.updateVal,
 		};
@@ -846,9 +874,11 @@ impl ClientState
 		if (!self.login_isConnected_untracked())
 		{
 			let mut preferences = self.preferences.get_untracked().unwrap_or_default();
-			if (preferences.primaryHue != PrimaryHue::default())
+			if (preferences.primaryHue != PrimaryHue::default() || !preferences.shortcuts.isDefault() || preferences.notifications)
 			{
 				preferences.primaryHue = PrimaryHue::default();
+				preferences.shortcuts = KeyboardShortcuts::default();
+				preferences.notifications = false;
 				preferences.valUpdate();
 				self.setPreferences.set(Some(preferences));
 			}
@@ -926,6 +956,8 @@ impl ClientState
 		let mut preferences = self.preferences.get_untracked().unwrap_or_default();
 		preferences.lang = accountPreferences.lang;
 		preferences.primaryHue = accountPreferences.primaryHue;
+		preferences.shortcuts = accountPreferences.shortcuts;
+		preferences.notifications = accountPreferences.notifications;
 		preferences.connected = true;
 		preferences.valUpdate();
 		self.setPreferences.set(Some(preferences));
@@ -938,6 +970,8 @@ impl ClientState
 		let legacyClearResult = self.legacyCookies_clear();
 		let mut preferences = self.preferences.get_untracked().unwrap_or_default();
 		preferences.primaryHue = PrimaryHue::default();
+		preferences.shortcuts = KeyboardShortcuts::default();
+		preferences.notifications = false;
 		preferences.connected = false;
 		preferences.valUpdate();
 		self.preferencesPreview.set(None);
@@ -1138,6 +1172,35 @@ impl ClientState
 		return self.preferences.get().map(|preferences| preferences.primaryHue.get()).unwrap_or(PrimaryHue::DEFAULT);
 	}
 
+	/// shortcuts in use on the dashboard, the preview is only shown in the settings
+	pub(crate) fn shortcuts_get_untracked(&self) -> KeyboardShortcuts
+	{
+		return self.preferences.get_untracked().map(|preferences| preferences.shortcuts).unwrap_or_default();
+	}
+
+	pub(crate) fn shortcutsPreview_get(&self) -> KeyboardShortcuts
+	{
+		if let Some(preview) = self.preferencesPreview.get()
+		{
+			return preview.shortcuts;
+		}
+		return self.preferences.get().map(|preferences| preferences.shortcuts).unwrap_or_default();
+	}
+
+	pub(crate) fn notifications_get(&self) -> bool
+	{
+		return self.preferences.get().is_some_and(|preferences| preferences.notifications);
+	}
+
+	pub(crate) fn notificationsPreview_get(&self) -> bool
+	{
+		if let Some(preview) = self.preferencesPreview.get()
+		{
+			return preview.notifications;
+		}
+		return self.notifications_get();
+	}
+
 	pub(crate) fn preferencesPreview_begin(&self)
 	{
 		let preferences = self.preferences.get_untracked().unwrap_or_default();
@@ -1166,6 +1229,26 @@ impl ClientState
 		return true;
 	}
 
+	pub(crate) fn preferencesPreview_notificationsSet(&self, notifications: bool)
+	{
+		let Some(mut preview) = self.preferencesPreview.get_untracked() else {return};
+		preview.notifications = notifications;
+		self.preferencesPreview.set(Some(preview));
+	}
+
+	/// return the action already using this combination
+	pub(crate) fn preferencesPreview_shortcutSet(&self, action: ShortcutAction, combo: Option<KeyCombo>) -> Result<(), ShortcutAction>
+	{
+		let Some(mut preview) = self.preferencesPreview.get_untracked() else {return Ok(())};
+		match combo
+		{
+			Some(combo) => preview.shortcuts.binding_set(action, combo)?,
+			None => preview.shortcuts.binding_reset(action)?,
+		}
+		self.preferencesPreview.set(Some(preview));
+		return Ok(());
+	}
+
 	pub(crate) async fn preferencesPreview_commit(&self) -> Result<(), AccountPreferencesError>
 	{
 		let preview = self.preferencesPreview.get_untracked().ok_or(AccountPreferencesError::CONTENT_INVALID)?;
@@ -1178,6 +1261,8 @@ impl ClientState
 		let mut preferences = self.preferences.get_untracked().unwrap_or_default();
 		preferences.lang = accountPreferences.lang;
 		preferences.primaryHue = accountPreferences.primaryHue;
+		preferences.shortcuts = accountPreferences.shortcuts;
+		preferences.notifications = accountPreferences.notifications;
 		preferences.valUpdate();
 		self.setPreferences.set(Some(preferences));
 		self.preferencesPreview.set(None);
@@ -1231,6 +1316,7 @@ mod tests
 	use leptos::prelude::{GetUntracked, Owner, Set};
 	use crate::api::login::components::{AccountPreferencesError, PasswordRotationContent, PasswordRotationFinalize};
 	use crate::api::modules::components::ModuleID;
+	use crate::front::utils::shortcuts::{KeyCombo, KeyboardShortcuts, ShortcutAction};
 	use super::{AccountPreferences, ClientCryptoContext, ClientCryptoPending, ClientCryptoStorage, ClientCryptoStorageCompatibility, ClientState, LegacyUserData, PreferencesPreview, PrimaryHue, UserPreferences, CLIENT_CRYPTO_STORAGE_KEY, LEGACY_COOKIE_NAME, LEGACY_CRYPTO_COOKIE_NAME, LEGACY_CRYPTO_COOKIE_PATH, ROOT_COOKIE_PATH};
 
 	fn cookiePath_matches(cookiePath: &str, requestPath: &str) -> bool
@@ -1398,9 +1484,13 @@ mod tests
 	fn accountPreferences_areVersionedAndEncryptedWithoutConnectionState()
 	{
 		let crypto = ClientCryptoContext::test_get();
+		let mut shortcuts = KeyboardShortcuts::default();
+		shortcuts.binding_set(ShortcutAction::HELP, KeyCombo::parse("F1").unwrap()).unwrap();
 		let preferences = UserPreferences {
 			lang: "FR".to_string(),
 			primaryHue: PrimaryHue::new(42).unwrap(),
+			shortcuts,
+			notifications: true,
 			connected: true,
 			updateVal: 99,
 		};
@@ -1416,6 +1506,19 @@ mod tests
 		assert_eq!(AccountPreferences::deserialize(&decrypted).unwrap(),accountPreferences);
 	}
 
+	#[test]
+	fn accountPreferences_withoutShortcutsUseDefaults()
+	{
+		let preferences = AccountPreferences::deserialize(r#"{"version":1,"lang":"FR","primaryHue":42}"#).unwrap();
+
+		assert!(preferences.shortcuts.isDefault());
+		assert!(!preferences.notifications);
+		// default shortcuts and disabled notifications are not written into the preferences cookie
+		let serialized = serde_json::to_string(&UserPreferences::default()).unwrap();
+		assert!(!serialized.contains("shortcuts"));
+		assert!(!serialized.contains("notifications"));
+	}
+
 	#[test]
 	fn accountPreferences_rejectUnknownVersionAndLanguage()
 	{
@@ -1435,9 +1538,13 @@ mod tests
 		let owner = Owner::new();
 		owner.with(|| {
 			let clientState = ClientState::new();
+			let mut shortcuts = KeyboardShortcuts::default();
+			shortcuts.binding_set(ShortcutAction::MODULE_REFRESH, KeyCombo::parse("Alt+R").unwrap()).unwrap();
 			clientState.setPreferences.set(Some(UserPreferences {
 				lang: "FR".to_string(),
 				primaryHue: PrimaryHue::new(42).unwrap(),
+				shortcuts,
+				notifications: true,
 				connected: true,
 				updateVal: 3,
 			}));
@@ -1449,6 +1556,8 @@ mod tests
 			let preferences = clientState.preferences.get_untracked().unwrap();
 			assert_eq!(preferences.lang,"FR");
 			assert_eq!(preferences.primaryHue,PrimaryHue::default());
+			assert!(preferences.shortcuts.isDefault());
+			assert!(!preferences.notifications);
 			assert!(!preferences.connected);
 			assert_eq!(preferences.updateVal,4);
 			assert!(clientState.preferencesPreview.get_untracked().is_none());
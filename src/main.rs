@@ -10,11 +10,20 @@ use web_home::entry::AppProps;
 use web_home::global_security::generate_salt;
 use web_home::server::{
 	runtimeConfig_set,
+	imapOAuth_callback,
+	mailImage_proxy,
+	moduleEvents_stream,
 	passwordRotationBodyLimit_apply,
 	sessionErrorActivity_renew,
+	smtpSendBodyLimit_apply,
 	sessionLayer_get,
 	traceFrontLog_enabled,
 	PROXY_CACHE_DIR,
+	MODULE_EVENTS_PATH,
+	IMAP_OAUTH_CALLBACK_PATH,
+	MAIL_IMAGE_PROXY_PATH,
+	IMAP_OAUTH_PROVIDERS_CONFIG_KEY,
+	IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY,
 };
 #[cfg(feature = "ssr")]
 use crate::browser_asset_delivery::BrowserAssetDelivery;
@@ -98,7 +107,10 @@ async fn main() {
 		helper::preFillConfig(config,"allow_registration",true);
 		helper::preFillConfig(config,"trace_front_log",!production);
 		helper::preFillConfig(config,"imap_allowed_ports",vec![JsonValue::Number(993.0)]);
+		helper::preFillConfig(config,"smtp_allowed_ports",vec![JsonValue::Number(465.0),JsonValue::Number(587.0)]);
 		helper::preFillConfig(config,"caldav_allowed_origins",Vec::<JsonValue>::new());
+		helper::preFillConfig(config,IMAP_OAUTH_PROVIDERS_CONFIG_KEY,Vec::<JsonValue>::new());
+		helper::preFillConfig(config,IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY,false);
 		if let Some(JsonValue::Boolean(raw)) = config.value_get("trace_front_log")
 		{
 			trace_front_log = traceFrontLog_enabled(raw,production);
@@ -140,11 +152,17 @@ async fn main() {
             let leptos_options = leptos_options.clone();
             move || shell((leptos_options.clone(),trace_front_log,allow_registration))
         })
+	    .route(MODULE_EVENTS_PATH, get(moduleEvents_stream))
 	    .fallback(leptos_axum::file_and_error_handler(move |lo|shell((lo,trace_front_log,allow_registration))))
 	    .layer(middleware::from_fn(sessionErrorActivity_renew))
 	    .layer(middleware::from_fn(passwordRotationBodyLimit_apply))
+	    .layer(middleware::from_fn(smtpSendBodyLimit_apply))
 	    .layer(session_layer)
 	    .route(DeploymentHealth::PATH, get(DeploymentHealth::response_get))
+	    // the provider redirection carries no session cookie, the pending state authenticates it
+	    .route(IMAP_OAUTH_CALLBACK_PATH, get(imapOAuth_callback))
+	    // the sandboxed mail frame sends no session cookie, the signature of the address authenticates it
+	    .route(MAIL_IMAGE_PROXY_PATH, get(mailImage_proxy))
 	    .route(
 		    BrowserContentSecurity::REPORT_PATH,
 		    post(BrowserContentSecurity::report_receive)
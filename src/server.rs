@@ -4,6 +4,11 @@ use axum::response::Response;
 use tower_sessions::{MemoryStore, Session, SessionManagerLayer};
 
 pub const PROXY_CACHE_DIR: &str = crate::api::proxys::proxy_cache::CACHE_DIR;
+pub const MODULE_EVENTS_PATH: &str = crate::api::modules::events::MODULE_EVENTS_PATH;
+pub const IMAP_OAUTH_CALLBACK_PATH: &str = crate::api::proxys::imap_oauth::IMAP_OAUTH_CALLBACK_PATH;
+pub const MAIL_IMAGE_PROXY_PATH: &str = crate::api::proxys::imap_components::MAIL_IMAGE_PROXY_PATH;
+pub const IMAP_OAUTH_PROVIDERS_CONFIG_KEY: &str = crate::api::proxys::imap_oauth::OAUTH_PROVIDERS_CONFIG_KEY;
+pub const IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY: &str = crate::api::proxys::outbound_policy::IMAP_PLAINTEXT_LOOPBACK_CONFIG_KEY;
 
 pub fn runtimeConfig_set(traceFrontLog: bool, allowRegistration: bool)
 {
@@ -20,6 +25,23 @@ pub fn sessionLayer_get() -> SessionManagerLayer<MemoryStore>
 	return crate::api::login::session::SessionCookie::layer_get();
 }
 
+/// live module updates of the authenticated session, as server-sent events
+pub async fn moduleEvents_stream(session: Session) -> Response
+{
+	return crate::api::modules::events::ModuleEvents::stream_get(session).await;
+}
+
+/// return of the OAuth providers, it only carries the authorization code of a pending mail authorization
+pub async fn imapOAuth_callback(query: axum::extract::Query<std::collections::HashMap<String, String>>) -> Response
+{
+	return crate::api::proxys::imap_oauth::ImapOAuthAuthorizations::callback_receive(query).await;
+}
+
+pub async fn mailImage_proxy(query: axum::extract::Query<std::collections::HashMap<String, String>>) -> Response
+{
+	return crate::api::proxys::mail_image::MailImageProxy::response_get(query).await;
+}
+
 pub async fn sessionErrorActivity_renew(
 	session: Session,
 	request: Request,
@@ -48,3 +70,24 @@ pub async fn passwordRotationBodyLimit_apply(
 	}
 	return next.run(request).await;
 }
+
+/// mails sent with attachments exceed the default request body limit, only for an authenticated session
+pub async fn smtpSendBodyLimit_apply(
+	session: Session,
+	mut request: Request,
+	next: Next,
+) -> Response
+{
+	use axum::extract::DefaultBodyLimit;
+	use leptos::server_fn::ServerFn;
+
+	let isSmtpSend = request.uri().path()
+		== <crate::api::proxys::smtp::ApiProxysSmtpSend as ServerFn>::PATH;
+	if (isSmtpSend
+		&& crate::api::login::user_back::AuthenticatedUser::fromSessionWithConfig(&session).await.is_ok())
+	{
+		DefaultBodyLimit::max(crate::api::proxys::smtp::SMTP_SEND_REQUEST_MAXIMUM_BYTES)
+			.apply(&mut request);
+	}
+	return next.run(request).await;
+}